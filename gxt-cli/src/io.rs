@@ -0,0 +1,103 @@
+use std::fs;
+use std::path::Path;
+
+use clap::ValueEnum;
+use gxt_core::{GxtEntry, LegacyFormat, TextFormat};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// 二进制 GXT
+    Gxt,
+    /// 每行 `KEY=value`
+    Txt,
+    /// `[{"key": ..., "value": ...}, ...]`
+    Json,
+    /// 社区补丁的 american.gxt.txt 样式：`[TABLE]` 表头、`KEY<Tab>文字`、`;` 注释
+    Community,
+}
+
+impl Format {
+    /// 批量转换时输出文件的扩展名（社区 dump 习惯叫 american.gxt.txt）
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Gxt => "gxt",
+            Format::Txt => "txt",
+            Format::Json => "json",
+            Format::Community => "gxt.txt",
+        }
+    }
+
+    /// 未显式指定时按扩展名推断
+    pub fn resolve(explicit: Option<Format>, path: &Path) -> Result<Format, String> {
+        if let Some(f) = explicit {
+            return Ok(f);
+        }
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("gxt") => Ok(Format::Gxt),
+            Some("txt") => Ok(Format::Txt),
+            Some("json") => Ok(Format::Json),
+            _ => Err(format!(
+                "Cannot infer format from {}; pass --format / --to explicitly",
+                path.display()
+            )),
+        }
+    }
+}
+
+pub fn read_entries(path: &Path, format: Format) -> Result<Vec<GxtEntry>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Read {} failed: {e}", path.display()))?;
+    let ctx = |e: String| format!("{}: {e}", path.display());
+    match format {
+        Format::Gxt => gxt_core::parse_gxt_bytes(&bytes).map_err(ctx),
+        // 社区 dump 也常叫 .txt：认出来时按它读，补丁文件不用另加 --format
+        Format::Txt if is_community_dump(&bytes) => parse_community(&bytes).map_err(ctx),
+        Format::Txt => {
+            let text = String::from_utf8(bytes).map_err(|e| ctx(format!("not valid UTF-8: {e}")))?;
+            gxt_core::entries_from_text(&text).map_err(ctx)
+        }
+        Format::Json => serde_json::from_slice(&bytes).map_err(|e| ctx(format!("JSON decode failed: {e}"))),
+        Format::Community => parse_community(&bytes).map_err(ctx),
+    }
+}
+
+fn is_community_dump(bytes: &[u8]) -> bool {
+    gxt_core::sniff_text_format(bytes) == Some(TextFormat::Legacy(LegacyFormat::Community))
+}
+
+/// 老工具的文件多半不是 UTF-8，按 legacy 模块的规则解码
+fn parse_community(bytes: &[u8]) -> Result<Vec<GxtEntry>, String> {
+    gxt_core::parse_legacy(
+        &gxt_core::decode_legacy_bytes(bytes),
+        LegacyFormat::Community,
+    )
+}
+
+pub fn encode_entries(entries: &[GxtEntry], format: Format) -> Result<Vec<u8>, String> {
+    match format {
+        Format::Gxt => gxt_core::build_gxt_bytes(entries),
+        Format::Txt => gxt_core::entries_to_text(entries).map(String::into_bytes),
+        Format::Json => {
+            serde_json::to_vec_pretty(entries).map_err(|e| format!("JSON encode failed: {e}"))
+        }
+        Format::Community => {
+            gxt_core::write_legacy(entries, LegacyFormat::Community).map(String::into_bytes)
+        }
+    }
+}
+
+/// path 为 None 时写到 stdout
+pub fn write_output(path: Option<&Path>, bytes: &[u8]) -> Result<(), String> {
+    match path {
+        Some(p) => fs::write(p, bytes).map_err(|e| format!("Write {} failed: {e}", p.display())),
+        None => {
+            use std::io::Write;
+            std::io::stdout()
+                .write_all(bytes)
+                .map_err(|e| format!("Write stdout failed: {e}"))
+        }
+    }
+}
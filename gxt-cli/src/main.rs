@@ -0,0 +1,735 @@
+mod io;
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use gxt_core::{CharsetFormat, GroupLevel, GxtEntry, KeyListFormat};
+
+use crate::io::{encode_entries, read_entries, write_output, Format};
+
+/// 退出码：0 成功；1 校验不通过 / diff 有差异；2 出错（读写失败、格式错误、参数错误）
+const EXIT_DIFFERENT: u8 = 1;
+const EXIT_ERROR: u8 = 2;
+
+#[derive(Debug, Parser)]
+#[command(name = "gxt", version, about = "Dump, build and compare Rockstar GXT files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// GXT -> 文本/JSON
+    Dump {
+        input: PathBuf,
+        /// 输出文件；省略则写到 stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[arg(long, value_enum, default_value = "txt")]
+        format: Format,
+    },
+    /// 文本/JSON -> GXT
+    Build {
+        input: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// 输入格式；省略则按扩展名推断
+        #[arg(long, value_enum)]
+        format: Option<Format>,
+        /// 按 key 的字节序排序：同样的内容不论输入顺序都生成同样的字节（适合提交进 git）
+        #[arg(long)]
+        sort: bool,
+    },
+    /// 任意两种格式互转（按扩展名推断，或用 --from / --to 指定）
+    Convert {
+        input: PathBuf,
+        output: PathBuf,
+        #[arg(long, value_enum)]
+        from: Option<Format>,
+        #[arg(long, value_enum)]
+        to: Option<Format>,
+        /// 按 key 的字节序排序，同 build --sort
+        #[arg(long)]
+        sort: bool,
+    },
+    /// 合并多个文件：后面的文件覆盖前面同 key 的值，新 key 追加到末尾
+    Merge {
+        #[arg(required = true, num_args = 2..)]
+        inputs: Vec<PathBuf>,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// 按 key 的字节序排序，同 build --sort
+        #[arg(long)]
+        sort: bool,
+    },
+    /// 原文更新后合并译文（类似 msgmerge）：新 key 以原文加入，删掉的 key 移入 --archive，
+    /// 原文有变化的已翻译条目列为 fuzzy
+    Update {
+        translation: PathBuf,
+        #[arg(long)]
+        old_source: PathBuf,
+        #[arg(long)]
+        new_source: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// 被删掉的条目写到这里（格式按扩展名推断）
+        #[arg(long)]
+        archive: Option<PathBuf>,
+    },
+    /// 比较两个文件；有差异时退出码为 1
+    Diff { a: PathBuf, b: PathBuf },
+    /// 计算 key 的哈希：SA 的 CRC32 与 IV 的 Jenkins（每行 `KEY crc32 jenkins`）
+    Hash {
+        #[arg(required = true)]
+        keys: Vec<String>,
+    },
+    /// 检查文件能否解析、key 是否合法且不重复；不通过时退出码为 1
+    Validate {
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+    },
+    /// GXT -> 目录：按 key 前缀每张表一个文件（取不出前缀的放进 _misc）；
+    /// --format gxt 时每张表一个单表 GXT，给只认单表文件的工具用
+    Decompile {
+        input: PathBuf,
+        dir: PathBuf,
+        #[arg(long, value_enum, default_value = "txt")]
+        format: Format,
+        /// 前缀到这个字符为止（含）
+        #[arg(long, default_value_t = '_', conflicts_with = "prefix_len")]
+        split_at: char,
+        /// 改用固定长度的前缀
+        #[arg(long)]
+        prefix_len: Option<usize>,
+        /// 目录里已有表文件时先删掉（否则报错，以免旧表在编译时混进来）
+        #[arg(long)]
+        force: bool,
+    },
+    /// 目录 -> GXT：按文件名顺序合并目录下的 .txt / .json / .gxt；同一个 key 出现在两张表里时报错
+    Compile {
+        dir: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// 按 key 的字节序排序（III / VC 的引擎要求）
+        #[arg(long)]
+        sort: bool,
+    },
+    /// 分析 TDAT 的空间浪费（无主字节、重复的 value）；给了 --compact 时写出压缩后的文件
+    Space {
+        input: PathBuf,
+        /// 丢掉无主字节、相同 value 只存一份后写到这里（可以与 input 相同）
+        #[arg(long)]
+        compact: Option<PathBuf>,
+        /// 压缩时保留指向别的 value 中间的 key（部分共用的文字）；缺省拆成各自独立的 value
+        #[arg(long, requires = "compact")]
+        keep_overlaps: bool,
+    },
+    /// GXT 的结构图：每个字段的 offset、长度与所属 key（JSON，或 --template 时为 010 Editor 模板）
+    Layout {
+        input: PathBuf,
+        /// 输出文件；省略则写到 stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[arg(long)]
+        template: bool,
+    },
+    /// 只导出 key 名（给写任务脚本的人）：每行一个，或 Sanny Builder 的常量文件
+    Keys {
+        input: PathBuf,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// 输出 `const ... end` 常量块
+        #[arg(long)]
+        constants: bool,
+        /// 常量名前缀，如 TXT_
+        #[arg(long, default_value = "", requires = "constants")]
+        prefix: String,
+        /// 按前缀分组，前缀到这个字符为止（含）
+        #[arg(long, conflicts_with = "prefix_len")]
+        split_at: Option<char>,
+        /// 按固定长度的前缀分组
+        #[arg(long)]
+        prefix_len: Option<usize>,
+    },
+    /// 导出几个文件合起来用到的字符（给做字库的人）：每行一段 `U+4E00-4E01`，
+    /// 或 FontForge 的选择脚本
+    Charset {
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// 输出 FontForge 脚本（`SelectNone()` + `SelectMore(...)`）
+        #[arg(long)]
+        fontforge: bool,
+        /// 直接列出字符
+        #[arg(long, conflicts_with = "fontforge")]
+        chars: bool,
+    },
+    /// 自检：内置样例走一遍 解析 -> 生成 -> 解析，有不一致时退出码为 1
+    Selftest,
+    /// 统计与校验（给 mod 的构建脚本）：条数、空值、文字量、TDAT 大小与错误；
+    /// 有文件读不了或校验不通过时退出码为 1
+    Stats {
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        /// 输出 JSON（`{"ok": …, "files": [...]}`）而不是文本
+        #[arg(long)]
+        json: bool,
+    },
+    /// 生成差分包：发布译文更新时只分发它，用户用 patch 打到自己的旧文件上
+    Delta {
+        old: PathBuf,
+        new: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+    /// 把 delta 生成的差分包打到旧文件上；旧文件与生成时的不一致时报错，不写出文件
+    Patch {
+        input: PathBuf,
+        delta: PathBuf,
+        /// 缺省覆盖 input
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 批量处理目录下的所有 .gxt：逐个校验，通过的按 --to 转换（或 --repack 重新生成），
+    /// 每个文件一行结果；有文件读不了或校验不通过时退出码为 1
+    Batch {
+        dir: PathBuf,
+        /// 转换成这个格式
+        #[arg(long)]
+        to: Option<Format>,
+        /// 重新生成 .gxt（整理 TKEY/TDAT）；没给 --out-dir 时覆盖原文件
+        #[arg(long)]
+        repack: bool,
+        /// 重新生成时按 key 的字节序排序
+        #[arg(long)]
+        sort: bool,
+        /// 输出目录，保持相对目录结构；省略则写在原文件旁边
+        #[arg(short, long)]
+        out_dir: Option<PathBuf>,
+        /// 连子目录一起处理
+        #[arg(short, long)]
+        recursive: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    match run(cli.command) {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(EXIT_ERROR)
+        }
+    }
+}
+
+fn load(path: &Path, format: Option<Format>) -> Result<Vec<GxtEntry>, String> {
+    read_entries(path, Format::resolve(format, path)?)
+}
+
+fn run(command: Command) -> Result<u8, String> {
+    match command {
+        Command::Dump {
+            input,
+            output,
+            format,
+        } => {
+            let entries = load(&input, Some(Format::Gxt))?;
+            write_output(output.as_deref(), &encode_entries(&entries, format)?)?;
+            Ok(0)
+        }
+        Command::Build {
+            input,
+            output,
+            format,
+            sort,
+        } => {
+            let mut entries = load(&input, format)?;
+            if sort {
+                gxt_core::sort_entries(&mut entries);
+            }
+            write_output(Some(&output), &encode_entries(&entries, Format::Gxt)?)?;
+            Ok(0)
+        }
+        Command::Convert {
+            input,
+            output,
+            from,
+            to,
+            sort,
+        } => {
+            let mut entries = load(&input, from)?;
+            if sort {
+                gxt_core::sort_entries(&mut entries);
+            }
+            let to = Format::resolve(to, &output)?;
+            write_output(Some(&output), &encode_entries(&entries, to)?)?;
+            Ok(0)
+        }
+        Command::Merge {
+            inputs,
+            output,
+            sort,
+        } => {
+            let mut merged: Vec<GxtEntry> = Vec::new();
+            let mut pos: HashMap<String, usize> = HashMap::new();
+            for input in &inputs {
+                for e in load(input, None)? {
+                    match pos.get(&e.key) {
+                        Some(&i) => merged[i] = e,
+                        None => {
+                            pos.insert(e.key.clone(), merged.len());
+                            merged.push(e);
+                        }
+                    }
+                }
+            }
+            if sort {
+                gxt_core::sort_entries(&mut merged);
+            }
+            let to = Format::resolve(None, &output)?;
+            write_output(Some(&output), &encode_entries(&merged, to)?)?;
+            Ok(0)
+        }
+        Command::Update {
+            translation,
+            old_source,
+            new_source,
+            output,
+            archive,
+        } => {
+            let update = gxt_core::update_translation(
+                &load(&old_source, None)?,
+                &load(&new_source, None)?,
+                &load(&translation, None)?,
+            );
+            let to = Format::resolve(None, &output)?;
+            write_output(Some(&output), &encode_entries(&update.entries, to)?)?;
+            if let Some(archive) = archive {
+                let to = Format::resolve(None, &archive)?;
+                write_output(Some(&archive), &encode_entries(&update.archived, to)?)?;
+            }
+            for key in &update.fuzzy {
+                println!("fuzzy {key}");
+            }
+            eprintln!(
+                "{} added, {} archived, {} fuzzy",
+                update.added.len(),
+                update.archived.len(),
+                update.fuzzy.len()
+            );
+            Ok(0)
+        }
+        Command::Diff { a, b } => {
+            let a = load(&a, None)?;
+            let b = load(&b, None)?;
+            Ok(if print_diff(&a, &b) { EXIT_DIFFERENT } else { 0 })
+        }
+        Command::Hash { keys } => {
+            for key in &keys {
+                println!(
+                    "{key} {} {}",
+                    gxt_core::format_hash(gxt_core::crc32_key(key)),
+                    gxt_core::format_hash(gxt_core::jenkins_key(key))
+                );
+            }
+            Ok(0)
+        }
+        Command::Validate { inputs } => {
+            let mut ok = true;
+            for input in &inputs {
+                let res = load(input, None).and_then(|entries| {
+                    gxt_core::validate_entries(&entries)?;
+                    Ok(entries.len())
+                });
+                match res {
+                    Ok(n) => println!("{}: OK ({n} entries)", input.display()),
+                    Err(e) => {
+                        ok = false;
+                        println!("{}: INVALID: {e}", input.display());
+                    }
+                }
+            }
+            Ok(if ok { 0 } else { EXIT_DIFFERENT })
+        }
+        Command::Decompile {
+            input,
+            dir,
+            format,
+            split_at,
+            prefix_len,
+            force,
+        } => {
+            let entries = load(&input, Some(Format::Gxt))?;
+            let level = match prefix_len {
+                Some(n) => GroupLevel::Prefix(n),
+                None => GroupLevel::Until(split_at),
+            };
+            // 输入本身就在目录里时不算旧表
+            let existing: Vec<PathBuf> = table_files(&dir)?
+                .into_iter()
+                .filter(|p| *p != input)
+                .collect();
+            if !existing.is_empty() {
+                if !force {
+                    return Err(format!(
+                        "{} already has {} table files; pass --force to replace them",
+                        dir.display(),
+                        existing.len()
+                    ));
+                }
+                for p in &existing {
+                    fs::remove_file(p).map_err(|e| format!("Remove {} failed: {e}", p.display()))?;
+                }
+            }
+            fs::create_dir_all(&dir).map_err(|e| format!("Create {} failed: {e}", dir.display()))?;
+            let ext = match format {
+                Format::Json => "json",
+                Format::Gxt => "gxt",
+                _ => "txt",
+            };
+            let tables = gxt_core::split_tables(&entries, &level);
+            for (name, entries) in &tables {
+                let path = dir.join(format!("{name}.{ext}"));
+                write_output(Some(&path), &encode_entries(entries, format)?)?;
+            }
+            eprintln!("{} tables, {} entries", tables.len(), entries.len());
+            Ok(0)
+        }
+        Command::Compile { dir, output, sort } => {
+            let mut tables = Vec::new();
+            // 输出也写在目录里时，不把上次编译的结果当成一张表
+            for path in table_files(&dir)?.into_iter().filter(|p| *p != output) {
+                let name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                tables.push((name, load(&path, None)?));
+            }
+            let mut entries = gxt_core::join_tables(tables)?;
+            if sort {
+                gxt_core::sort_entries(&mut entries);
+            }
+            write_output(Some(&output), &encode_entries(&entries, Format::Gxt)?)?;
+            Ok(0)
+        }
+        Command::Space {
+            input,
+            compact,
+            keep_overlaps,
+        } => {
+            let bytes =
+                fs::read(&input).map_err(|e| format!("Read {} failed: {e}", input.display()))?;
+            let r = gxt_core::analyze_space(&bytes)?;
+            println!("file:       {} bytes, {} keys", r.file_size, r.key_count);
+            println!("values:     {} bytes", r.value_bytes);
+            println!(
+                "orphan:     {} bytes in {} ranges",
+                r.orphan_bytes,
+                r.orphan_ranges.len()
+            );
+            println!(
+                "duplicates: {} values, {} bytes",
+                r.duplicate_values, r.duplicate_bytes
+            );
+            println!(
+                "compacted:  {} bytes ({} saved)",
+                r.compacted_size,
+                r.saved_bytes()
+            );
+            let overlaps = gxt_core::find_overlaps(&bytes)?;
+            println!("overlaps:   {} keys", overlaps.len());
+            for o in &overlaps {
+                println!(
+                    "  {} @{} is inside {} @{}",
+                    o.key, o.offset, o.host_key, o.host_offset
+                );
+            }
+            if let Some(output) = compact {
+                let repair = if keep_overlaps {
+                    gxt_core::OverlapRepair::Keep
+                } else {
+                    gxt_core::OverlapRepair::Split
+                };
+                write_output(Some(&output), &gxt_core::repair_overlaps(&bytes, repair)?)?;
+            }
+            Ok(0)
+        }
+        Command::Layout {
+            input,
+            output,
+            template,
+        } => {
+            let bytes =
+                fs::read(&input).map_err(|e| format!("Read {} failed: {e}", input.display()))?;
+            let items = gxt_core::gxt_layout(&bytes)?;
+            let out = if template {
+                let name = input.file_name().unwrap_or_default().to_string_lossy();
+                gxt_core::layout_template(&items, &name).into_bytes()
+            } else {
+                serde_json::to_vec_pretty(&items).map_err(|e| format!("Encode JSON failed: {e}"))?
+            };
+            write_output(output.as_deref(), &out)?;
+            Ok(0)
+        }
+        Command::Keys {
+            input,
+            output,
+            constants,
+            prefix,
+            split_at,
+            prefix_len,
+        } => {
+            let entries = load(&input, None)?;
+            let level = match (prefix_len, split_at) {
+                (Some(n), _) => Some(GroupLevel::Prefix(n)),
+                (None, Some(c)) => Some(GroupLevel::Until(c)),
+                (None, None) => None,
+            };
+            let format = if constants {
+                KeyListFormat::Constants
+            } else {
+                KeyListFormat::Plain
+            };
+            let text = gxt_core::key_list(&entries, format, level.as_ref(), &prefix);
+            write_output(output.as_deref(), text.as_bytes())?;
+            Ok(0)
+        }
+        Command::Charset {
+            inputs,
+            output,
+            fontforge,
+            chars,
+        } => {
+            let mut set = BTreeSet::new();
+            for input in &inputs {
+                set.extend(gxt_core::used_chars(&load(input, None)?));
+            }
+            let format = if fontforge {
+                CharsetFormat::FontForge
+            } else if chars {
+                CharsetFormat::Text
+            } else {
+                CharsetFormat::Ranges
+            };
+            let text = gxt_core::render_charset(&set, format);
+            write_output(output.as_deref(), text.as_bytes())?;
+            Ok(0)
+        }
+        Command::Selftest => {
+            let report = gxt_core::run_selftest();
+            for c in report.failures() {
+                println!("FAIL {}: {}", c.name, c.detail.as_deref().unwrap_or(""));
+            }
+            println!(
+                "{} checks, {} failed",
+                report.cases.len(),
+                report.failures().count()
+            );
+            Ok(if report.passed() { 0 } else { EXIT_DIFFERENT })
+        }
+        Command::Stats { inputs, json } => {
+            let files: Vec<serde_json::Value> = inputs.iter().map(|p| file_stats(p)).collect();
+            let ok = files
+                .iter()
+                .all(|f| f["errors"].as_array().is_some_and(Vec::is_empty));
+            if json {
+                let report = serde_json::json!({ "ok": ok, "files": files });
+                let text = serde_json::to_string_pretty(&report)
+                    .map_err(|e| format!("Encode JSON failed: {e}"))?;
+                println!("{text}");
+            } else {
+                for f in &files {
+                    println!(
+                        "{}: {} entries, {} empty, {} chars, {} TDAT bytes, {} unsorted",
+                        f["path"].as_str().unwrap_or_default(),
+                        f["entries"],
+                        f["empty_values"],
+                        f["chars"],
+                        f["tdat_bytes"],
+                        f["unsorted_keys"]
+                    );
+                    for e in f["errors"].as_array().into_iter().flatten() {
+                        println!("  error: {}", e.as_str().unwrap_or_default());
+                    }
+                }
+            }
+            Ok(if ok { 0 } else { EXIT_DIFFERENT })
+        }
+        Command::Delta { old, new, output } => {
+            let delta = gxt_core::make_delta(&read_bytes(&old)?, &read_bytes(&new)?)?;
+            write_output(Some(&output), &delta)?;
+            println!("{}: {} bytes", output.display(), delta.len());
+            Ok(0)
+        }
+        Command::Patch {
+            input,
+            delta,
+            output,
+        } => {
+            let patched = gxt_core::apply_delta(&read_bytes(&input)?, &read_bytes(&delta)?)?;
+            write_output(Some(output.as_ref().unwrap_or(&input)), &patched)?;
+            Ok(0)
+        }
+        Command::Batch {
+            dir,
+            to,
+            repack,
+            sort,
+            out_dir,
+            recursive,
+        } => {
+            let mut formats: Vec<Format> = to.into_iter().collect();
+            if repack && to != Some(Format::Gxt) {
+                formats.push(Format::Gxt);
+            }
+            let mut ok = true;
+            let mut count = 0;
+            for input in gxt_files(&dir, recursive)? {
+                count += 1;
+                let res = load(&input, Some(Format::Gxt)).and_then(|mut entries| {
+                    gxt_core::validate_entries(&entries)?;
+                    gxt_core::check_tdat_size(&entries)?;
+                    if sort {
+                        gxt_core::sort_entries(&mut entries);
+                    }
+                    let mut outputs = Vec::new();
+                    for &format in &formats {
+                        let rel = input.strip_prefix(&dir).unwrap_or(&input);
+                        let path = out_dir
+                            .as_deref()
+                            .map_or_else(|| input.clone(), |d| d.join(rel))
+                            .with_extension(format.extension());
+                        if let Some(parent) = path.parent() {
+                            fs::create_dir_all(parent)
+                                .map_err(|e| format!("Create {} failed: {e}", parent.display()))?;
+                        }
+                        write_output(Some(&path), &encode_entries(&entries, format)?)?;
+                        outputs.push(path.display().to_string());
+                    }
+                    Ok((entries.len(), outputs))
+                });
+                match res {
+                    Ok((n, outputs)) if outputs.is_empty() => {
+                        println!("{}: OK ({n} entries)", input.display())
+                    }
+                    Ok((n, outputs)) => println!(
+                        "{}: OK ({n} entries) -> {}",
+                        input.display(),
+                        outputs.join(", ")
+                    ),
+                    Err(e) => {
+                        ok = false;
+                        println!("{}: FAILED: {e}", input.display());
+                    }
+                }
+            }
+            eprintln!("{count} files");
+            Ok(if ok { 0 } else { EXIT_DIFFERENT })
+        }
+    }
+}
+
+fn read_bytes(path: &Path) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| format!("Read {} failed: {e}", path.display()))
+}
+
+/// 一个文件的统计与校验结果；读不了的文件也给出一项（errors 里说明原因），
+/// 让构建脚本总能拿到完整的报告
+fn file_stats(path: &Path) -> serde_json::Value {
+    let mut errors = Vec::new();
+    let entries = match load(path, None) {
+        Ok(entries) => entries,
+        Err(e) => {
+            errors.push(e);
+            Vec::new()
+        }
+    };
+    if let Err(e) = gxt_core::validate_entries(&entries) {
+        errors.push(e);
+    }
+    if let Err(e) = gxt_core::check_tdat_size(&entries) {
+        errors.push(e);
+    }
+    let mut tdat_bytes = 0usize;
+    for e in &entries {
+        match gxt_core::encoded_utf16z_len(&e.value) {
+            Ok(n) => tdat_bytes += n,
+            Err(err) => errors.push(format!("{}: {err}", e.key)),
+        }
+    }
+    serde_json::json!({
+        "path": path.display().to_string(),
+        "entries": entries.len(),
+        "empty_values": entries.iter().filter(|e| e.value.is_empty()).count(),
+        "chars": entries.iter().map(|e| e.value.chars().count()).sum::<usize>(),
+        "tdat_bytes": tdat_bytes,
+        "unsorted_keys": gxt_core::unsorted_keys(&entries).len(),
+        "errors": errors,
+    })
+}
+
+/// 目录下的表文件（.txt / .json），按文件名排序；目录不存在时为空
+fn table_files(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let Ok(read) = fs::read_dir(dir) else {
+        return Ok(Vec::new());
+    };
+    let mut out = Vec::new();
+    for item in read {
+        let path = item
+            .map_err(|e| format!("Read {} failed: {e}", dir.display()))?
+            .path();
+        let is_table = matches!(
+            Format::resolve(None, &path),
+            Ok(Format::Txt) | Ok(Format::Json) | Ok(Format::Gxt)
+        );
+        if is_table && path.is_file() {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// 目录下扩展名为 .gxt（不分大小写）的文件，按路径排序
+fn gxt_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>, String> {
+    let read = fs::read_dir(dir).map_err(|e| format!("Read {} failed: {e}", dir.display()))?;
+    let mut out = Vec::new();
+    for item in read {
+        let path = item
+            .map_err(|e| format!("Read {} failed: {e}", dir.display()))?
+            .path();
+        if path.is_dir() {
+            if recursive {
+                out.extend(gxt_files(&path, true)?);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("gxt"))
+        {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// 按 key 比较，输出 `- KEY` / `+ KEY=value` / `~ KEY: old -> new`；返回是否有差异
+fn print_diff(a: &[GxtEntry], b: &[GxtEntry]) -> bool {
+    let diff = gxt_core::diff_entries(a, b);
+    for e in &diff.removed {
+        println!("- {}", e.key);
+    }
+    for c in &diff.changed {
+        println!("~ {}: {:?} -> {:?}", c.key, c.old, c.new);
+    }
+    for e in &diff.added {
+        println!("+ {}={:?}", e.key, &*e.value);
+    }
+    !diff.is_empty()
+}
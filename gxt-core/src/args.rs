@@ -0,0 +1,135 @@
+//! 带编号的占位 token（`~1~` 之类）的参数类型：引擎按出现的先后把脚本传来的参数依次填进去，
+//! 译文里调换了数字与文字参数的先后、或者漏掉一个，游戏里就显示错乱甚至崩溃。
+//! 项目在规则文件里按 key 给出每个参数的类型，检查译文里的占位 token 是否仍一一对得上。
+
+use crate::limits::matches_pattern;
+use crate::tokens::tokens;
+
+/// 一个参数的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum ArgType {
+    Number,
+    /// 插入另一条文字（SA 的 `~a~`）
+    String,
+    /// 时间（脚本传来的是数字）
+    Time,
+}
+
+/// 各类型的参数在文字里写成哪些 token（不区分大小写）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ArgTokens {
+    pub number: Vec<String>,
+    pub string: Vec<String>,
+    pub time: Vec<String>,
+}
+
+impl Default for ArgTokens {
+    fn default() -> Self {
+        ArgTokens {
+            number: vec!["~1~".into()],
+            string: vec!["~a~".into()],
+            time: vec!["~1~".into()],
+        }
+    }
+}
+
+impl ArgTokens {
+    fn of(&self, ty: ArgType) -> &[String] {
+        match ty {
+            ArgType::Number => &self.number,
+            ArgType::String => &self.string,
+            ArgType::Time => &self.time,
+        }
+    }
+
+    fn accepts(&self, ty: ArgType, token: &str) -> bool {
+        self.of(ty).iter().any(|t| t.eq_ignore_ascii_case(token))
+    }
+
+    /// 是某种参数的 token
+    fn is_arg(&self, token: &str) -> bool {
+        [ArgType::Number, ArgType::String, ArgType::Time]
+            .into_iter()
+            .any(|ty| self.accepts(ty, token))
+    }
+}
+
+/// 一条规则
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArgRule {
+    /// key 模式：`*` 匹配任意多个字符，`?` 匹配一个；不区分大小写
+    pub pattern: String,
+    /// 给人看的名字，如“任务奖金”
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: String,
+    /// 脚本依次传来的参数
+    pub args: Vec<ArgType>,
+}
+
+/// 规则文件：`[tokens]` 改各类型的写法，若干 `[[rules]]` 按顺序取第一条匹配的
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ArgRules {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tokens: ArgTokens,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: Vec<ArgRule>,
+}
+
+/// index 从 1 开始
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "lowercase"))]
+pub enum ArgViolation {
+    /// 第 index 个参数在译文里没有了
+    Missing { index: usize, expected: ArgType },
+    /// 多出来的参数 token（引擎没有东西可填）
+    Extra { index: usize, token: String },
+    /// 第 index 个位置上的 token 不是这个类型的（多半是调换了先后）
+    Mismatch {
+        index: usize,
+        expected: ArgType,
+        token: String,
+    },
+}
+
+impl ArgRules {
+    pub fn rule_for(&self, key: &str) -> Option<&ArgRule> {
+        self.rules.iter().find(|r| matches_pattern(&r.pattern, key))
+    }
+
+    /// 按 rule 检查 value 里参数 token 的个数与先后；别的 token 不看
+    pub fn check(&self, rule: &ArgRule, value: &str) -> Vec<ArgViolation> {
+        let found: Vec<&str> = tokens(value)
+            .into_iter()
+            .filter(|t| self.tokens.is_arg(t))
+            .collect();
+        let mut out = Vec::new();
+        for (i, &expected) in rule.args.iter().enumerate() {
+            match found.get(i) {
+                None => out.push(ArgViolation::Missing {
+                    index: i + 1,
+                    expected,
+                }),
+                Some(t) if !self.tokens.accepts(expected, t) => out.push(ArgViolation::Mismatch {
+                    index: i + 1,
+                    expected,
+                    token: t.to_string(),
+                }),
+                Some(_) => {}
+            }
+        }
+        for (i, t) in found.iter().enumerate().skip(rule.args.len()) {
+            out.push(ArgViolation::Extra {
+                index: i + 1,
+                token: t.to_string(),
+            });
+        }
+        out
+    }
+}
@@ -0,0 +1,104 @@
+//! U+FFFF 以上的字符（emoji、生僻字扩展区等）。编码器照样写成代理对，
+//! 但 III / VC 的位图字体只按单个 UTF-16 单元查字形，游戏里显示成两个乱码甚至空白。
+
+use crate::escape::encode_units;
+use crate::tokens::{segments, Segment};
+
+/// 预设里对这类字符的处理
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum AstralPolicy {
+    /// 游戏能显示，不管
+    #[default]
+    Allow,
+    /// 检查时报告
+    Warn,
+    /// 检查时报告，保存时换成 astral_fallback 的文字（编辑器里的文本不变）
+    Substitute,
+}
+
+/// 保存时代替 emoji 的文字；表里没有的换成 `?`
+pub fn astral_fallback(c: char) -> &'static str {
+    match c {
+        '😀' | '😃' | '😄' | '😁' => ":D",
+        '😆' | '😂' | '🤣' => "XD",
+        '🙂' | '😊' => ":)",
+        '😉' => ";)",
+        '😛' | '😜' => ":P",
+        '😢' | '😞' | '🙁' => ":(",
+        '😭' => ":'(",
+        '😠' | '😡' => ">:(",
+        '😮' | '😲' => ":O",
+        '😎' => "B)",
+        '👍' => "(y)",
+        '👎' => "(n)",
+        '💀' => "x_x",
+        _ => "?",
+    }
+}
+
+/// 一段转义对应的字符；不是单个字符（或写错了）时为 None
+fn escaped_char(esc: &str) -> Option<char> {
+    let mut units = Vec::new();
+    encode_units(esc, &mut |u| units.push(u)).ok()?;
+    let mut chars = char::decode_utf16(units);
+    match (chars.next(), chars.next()) {
+        (Some(Ok(c)), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn is_astral(c: char) -> bool {
+    c as u32 > 0xFFFF
+}
+
+/// value 里 U+FFFF 以上的字符（直接写的与转义写的都算），按出现顺序去重；token 不看
+pub fn astral_chars(value: &str) -> Vec<char> {
+    let mut out = Vec::new();
+    for seg in segments(value) {
+        let found: Vec<char> = match seg {
+            Segment::Text(t) => t.chars().filter(|&c| is_astral(c)).collect(),
+            Segment::Escape(esc) => escaped_char(esc)
+                .filter(|&c| is_astral(c))
+                .into_iter()
+                .collect(),
+            Segment::Token(_) => Vec::new(),
+        };
+        for c in found {
+            if !out.contains(&c) {
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+/// 把 U+FFFF 以上的字符换成 astral_fallback 的文字；没有这类字符时为 None
+pub fn substitute_astral(value: &str) -> Option<String> {
+    let mut changed = false;
+    let mut out = String::with_capacity(value.len());
+    for seg in segments(value) {
+        match seg {
+            Segment::Text(t) => {
+                for c in t.chars() {
+                    if is_astral(c) {
+                        out.push_str(astral_fallback(c));
+                        changed = true;
+                    } else {
+                        out.push(c);
+                    }
+                }
+            }
+            Segment::Escape(esc) => match escaped_char(esc).filter(|&c| is_astral(c)) {
+                Some(c) => {
+                    out.push_str(astral_fallback(c));
+                    changed = true;
+                }
+                None => out.push_str(esc),
+            },
+            Segment::Token(t) => out.push_str(t),
+        }
+    }
+    changed.then_some(out)
+}
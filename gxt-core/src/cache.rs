@@ -0,0 +1,26 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// 单个文件的编码缓存：value 原文 -> 已编码的 UTF-16Z 字节（含 0 结尾）
+///
+/// 用 value 文本本身做键：没改过的条目命中缓存直接复用字节，
+/// 改过的条目自然查不到，重新走一遍转义编码器。
+#[derive(Debug, Default)]
+pub struct ValueCache {
+    encoded: HashMap<Arc<str>, Vec<u8>>,
+}
+
+impl ValueCache {
+    pub fn get(&self, value: &str) -> Option<&[u8]> {
+        self.encoded.get(value).map(|v| v.as_slice())
+    }
+
+    pub fn insert(&mut self, value: Arc<str>, bytes: Vec<u8>) {
+        self.encoded.insert(value, bytes);
+    }
+
+    /// 只保留本次保存实际用到的 value，避免删改多次后缓存无限增长
+    pub fn retain_used(&mut self, used: &HashSet<&str>) {
+        self.encoded.retain(|k, _| used.contains(&**k));
+    }
+}
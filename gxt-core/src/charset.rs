@@ -0,0 +1,90 @@
+//! 译文用到的字符集，给做自定义字库的人：只生成真正用得到的字形。
+//! 可导出成 Unicode 区段清单，或 FontForge 的选择脚本（`fontforge -script` 里跑完后选中的就是要做的字）。
+
+use std::collections::BTreeSet;
+
+use crate::entry::GxtEntry;
+use crate::escape::encode_units;
+use crate::tokens::{segments, Segment};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum CharsetFormat {
+    /// 每行一段 `U+4E00-4E01`（只有一个码点时 `U+0041`）
+    #[default]
+    Ranges,
+    /// FontForge 脚本：`SelectNone()` 后逐段 `SelectMore(0u4E00, 0u4E01)`
+    FontForge,
+    /// 直接列出字符，每行 64 个
+    Text,
+}
+
+/// 所有 value 里实际显示的字符：跳过 `~token~`，转义按码点算，控制字符不算（空格算）
+pub fn used_chars(entries: &[GxtEntry]) -> BTreeSet<char> {
+    let mut out = BTreeSet::new();
+    for e in entries {
+        for seg in segments(&e.value) {
+            match seg {
+                Segment::Text(t) => out.extend(t.chars()),
+                Segment::Escape(esc) => {
+                    let mut units = Vec::new();
+                    if encode_units(esc, &mut |u| units.push(u)).is_ok() {
+                        out.extend(char::decode_utf16(units).filter_map(Result::ok));
+                    }
+                }
+                Segment::Token(_) => {}
+            }
+        }
+    }
+    out.retain(|c| !c.is_control());
+    out
+}
+
+/// 连续的码点合并成闭区间，按码点排序
+pub fn char_ranges(chars: &BTreeSet<char>) -> Vec<(u32, u32)> {
+    let mut out: Vec<(u32, u32)> = Vec::new();
+    for &c in chars {
+        let cp = c as u32;
+        match out.last_mut() {
+            Some((_, end)) if *end + 1 == cp => *end = cp,
+            _ => out.push((cp, cp)),
+        }
+    }
+    out
+}
+
+/// 按格式生成导出文件的内容
+pub fn render_charset(chars: &BTreeSet<char>, format: CharsetFormat) -> String {
+    let ranges = char_ranges(chars);
+    let mut out = String::new();
+    match format {
+        CharsetFormat::Ranges => {
+            for (start, end) in ranges {
+                if start == end {
+                    out.push_str(&format!("U+{start:04X}\n"));
+                } else {
+                    out.push_str(&format!("U+{start:04X}-{end:04X}\n"));
+                }
+            }
+        }
+        CharsetFormat::FontForge => {
+            out.push_str(&format!(
+                "# {} glyphs in {} ranges\nSelectNone()\n",
+                chars.len(),
+                ranges.len()
+            ));
+            for (start, end) in ranges {
+                out.push_str(&format!("SelectMore(0u{start:04X}, 0u{end:04X})\n"));
+            }
+        }
+        CharsetFormat::Text => {
+            let chars: Vec<char> = chars.iter().copied().collect();
+            for line in chars.chunks(64) {
+                out.extend(line);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
@@ -0,0 +1,67 @@
+//! 字库替换式的本地化：文件里 0xA0..=0xFF 的 UTF-16 单元其实是某个 8 位代码页的字节
+//! （如俄化版 VC 把 CP1251 的西里尔字母放在 Latin-1 的位置）。读入时换成真正的字符，写回时换回去。
+//! 只动文字部分；0x80..=0x9F 本来就以转义出现，不在此列。
+
+use crate::tokens::{segments, Segment};
+
+/// 0xA0..=0xFF 之外的单元不变
+const HIGH_START: u32 = 0xA0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum Codepage {
+    /// Windows-1251（西里尔字母）
+    Cp1251,
+}
+
+/// CP1251 的 0xA0..=0xFF
+const CP1251_HIGH: [char; 96] = [
+    '\u{00A0}', 'Ў', 'ў', 'Ј', '¤', 'Ґ', '¦', '§', 'Ё', '©', 'Є', '«', '¬', '\u{00AD}', '®', 'Ї',
+    '°', '±', 'І', 'і', 'ґ', 'µ', '¶', '·', 'ё', '№', 'є', '»', 'ј', 'Ѕ', 'ѕ', 'ї', 'А', 'Б', 'В',
+    'Г', 'Д', 'Е', 'Ж', 'З', 'И', 'Й', 'К', 'Л', 'М', 'Н', 'О', 'П', 'Р', 'С', 'Т', 'У', 'Ф', 'Х',
+    'Ц', 'Ч', 'Ш', 'Щ', 'Ъ', 'Ы', 'Ь', 'Э', 'Ю', 'Я', 'а', 'б', 'в', 'г', 'д', 'е', 'ж', 'з', 'и',
+    'й', 'к', 'л', 'м', 'н', 'о', 'п', 'р', 'с', 'т', 'у', 'ф', 'х', 'ц', 'ч', 'ш', 'щ', 'ъ', 'ы',
+    'ь', 'э', 'ю', 'я',
+];
+
+impl Codepage {
+    fn high(self) -> &'static [char; 96] {
+        match self {
+            Codepage::Cp1251 => &CP1251_HIGH,
+        }
+    }
+
+    /// 文件里的值 -> 真正的文字
+    pub fn decode(self, value: &str) -> String {
+        let high = self.high();
+        map_text(value, |c| {
+            let code = c as u32;
+            if (HIGH_START..=0xFF).contains(&code) {
+                high[(code - HIGH_START) as usize]
+            } else {
+                c
+            }
+        })
+    }
+
+    /// 真正的文字 -> 写进文件的值；代码页里没有的字符原样保留
+    pub fn encode(self, value: &str) -> String {
+        let high = self.high();
+        map_text(value, |c| match high.iter().position(|&h| h == c) {
+            Some(i) => char::from_u32(HIGH_START + i as u32).unwrap_or(c),
+            None => c,
+        })
+    }
+}
+
+fn map_text(value: &str, f: impl Fn(char) -> char) -> String {
+    let mut out = String::with_capacity(value.len());
+    for seg in segments(value) {
+        match seg {
+            Segment::Text(t) => out.extend(t.chars().map(&f)),
+            other => out.push_str(other.as_str()),
+        }
+    }
+    out
+}
@@ -0,0 +1,377 @@
+//! 两个 GXT 文件之间的二进制差分：发布译文更新时只分发差分包，用户在自己的旧文件上打补丁。
+//!
+//! 格式：`GXTD` + 版本（1 字节）+ 旧文件与新文件各自的长度和 CRC32（各 4 字节），
+//! 之后是一串操作：`1` + offset + len 从旧文件复制，`2` + len + 字节 直接写入，
+//! `3` + offset + len + shift 复制 TKEY 记录并给每条的 value offset 加上 shift（整数都是小端 u32）。
+//! 大部分 value 没变时几乎全是复制，差分包只有改动的文字那么大。
+
+use std::collections::HashMap;
+
+use crate::format::{KEY_RECORD_BYTES, MAGIC_TDAT, MAGIC_TKEY};
+use crate::hash::crc32_bytes;
+
+pub const MAGIC_DELTA: &[u8; 4] = b"GXTD";
+const VERSION: u8 = 1;
+const OP_COPY: u8 = 1;
+const OP_ADD: u8 = 2;
+const OP_COPY_SHIFT: u8 = 3;
+/// 按这么长的块在旧文件里找相同的内容；太短了复制操作本身比内容还大
+const BLOCK: usize = 16;
+
+/// 差分包的头部信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeltaInfo {
+    pub old_size: usize,
+    pub old_crc32: u32,
+    pub new_size: usize,
+    pub new_crc32: u32,
+}
+
+fn push_u32(out: &mut Vec<u8>, n: usize) -> Result<(), String> {
+    let n = u32::try_from(n).map_err(|_| "File too large for a delta".to_string())?;
+    out.extend_from_slice(&n.to_le_bytes());
+    Ok(())
+}
+
+fn push_add(out: &mut Vec<u8>, bytes: &[u8]) -> Result<(), String> {
+    if !bytes.is_empty() {
+        out.push(OP_ADD);
+        push_u32(out, bytes.len())?;
+        out.extend_from_slice(bytes);
+    }
+    Ok(())
+}
+
+fn push_copy(out: &mut Vec<u8>, from: usize, len: usize) -> Result<(), String> {
+    out.push(OP_COPY);
+    push_u32(out, from)?;
+    push_u32(out, len)
+}
+
+/// 生成把 old 变成 new 的差分包
+pub fn make_delta(old: &[u8], new: &[u8]) -> Result<Vec<u8>, String> {
+    let mut out = MAGIC_DELTA.to_vec();
+    out.push(VERSION);
+    push_u32(&mut out, old.len())?;
+    out.extend_from_slice(&crc32_bytes(old).to_le_bytes());
+    push_u32(&mut out, new.len())?;
+    out.extend_from_slice(&crc32_bytes(new).to_le_bytes());
+    let mut done = 0;
+    if let (Some(o), Some(n)) = (key_table(old), key_table(new)) {
+        done = diff_key_table(&mut out, old, new, o, n)?;
+        if let Some(end) = diff_values(&mut out, old, new, o, n)? {
+            done = end;
+        }
+    }
+    diff_bytes(&mut out, old, new, done)?;
+    Ok(out)
+}
+
+/// GXT 的 TKEY 记录区 `(起点, 条数)`；不是 GXT 时为 None
+fn key_table(bytes: &[u8]) -> Option<(usize, usize)> {
+    if bytes.get(..4)? != MAGIC_TKEY {
+        return None;
+    }
+    let size = u32::from_le_bytes(bytes.get(4..8)?.try_into().ok()?) as usize;
+    (size.is_multiple_of(KEY_RECORD_BYTES) && bytes.len() >= 8 + size)
+        .then_some((8, size / KEY_RECORD_BYTES))
+}
+
+/// TKEY 单独处理：某条 value 长度一变，后面所有记录的 offset 都差同一个数，
+/// 按字节比几乎处处不同；改成“复制这些记录，offset 都加上 shift”。返回处理到 new 的哪里
+fn diff_key_table(
+    out: &mut Vec<u8>,
+    old: &[u8],
+    new: &[u8],
+    (old_start, old_count): (usize, usize),
+    (new_start, new_count): (usize, usize),
+) -> Result<usize, String> {
+    let old_index: HashMap<&[u8], usize> = (0..old_count)
+        .map(|i| (record(old, old_start, i).1, i))
+        .collect();
+    push_add(out, &new[..new_start])?;
+    // 当前这一段：(旧记录起点, 新记录起点, 条数, shift)
+    let mut run: Option<(usize, usize, usize, u32)> = None;
+    let mut added_from = None;
+    for j in 0..new_count {
+        let (offset, key) = record(new, new_start, j);
+        let matched = old_index.get(key).map(|&i| {
+            let shift = offset.wrapping_sub(record(old, old_start, i).0);
+            (i, shift)
+        });
+        match (matched, &mut run) {
+            (Some((i, shift)), Some((oi, _, n, s))) if *oi + *n == i && *s == shift => *n += 1,
+            (Some((i, shift)), _) => {
+                flush_run(out, run.take(), old_start)?;
+                flush_added(out, new, new_start, added_from.take(), j)?;
+                run = Some((i, j, 1, shift));
+            }
+            (None, _) => {
+                flush_run(out, run.take(), old_start)?;
+                added_from.get_or_insert(j);
+            }
+        }
+    }
+    flush_run(out, run, old_start)?;
+    flush_added(out, new, new_start, added_from, new_count)?;
+    Ok(new_start + new_count * KEY_RECORD_BYTES)
+}
+
+/// TDAT 也按 key 对齐：同一个 key 的 value 没变就从旧文件复制那段文字，
+/// 连续的几条合成一次复制；改过的和新增的 value 原样写入。TDAT 读不了时返回 None，交给 diff_bytes
+fn diff_values(
+    out: &mut Vec<u8>,
+    old: &[u8],
+    new: &[u8],
+    (old_start, old_count): (usize, usize),
+    (new_start, new_count): (usize, usize),
+) -> Result<Option<usize>, String> {
+    let (Some((old_data, old_end)), Some((new_data, new_end))) = (
+        value_table(old, old_start + old_count * KEY_RECORD_BYTES),
+        value_table(new, new_start + new_count * KEY_RECORD_BYTES),
+    ) else {
+        return Ok(None);
+    };
+    let old_values: HashMap<&[u8], (usize, usize)> = (0..old_count)
+        .filter_map(|i| {
+            let (offset, key) = record(old, old_start, i);
+            Some((key, value_range(old, old_data, old_end, offset)?))
+        })
+        .collect();
+    // 没变的 value：(新文件里的起点, 终点, 旧文件里的起点)，按新文件位置排序
+    let mut same: Vec<(usize, usize, usize)> = (0..new_count)
+        .filter_map(|j| {
+            let (offset, key) = record(new, new_start, j);
+            let (s, e) = value_range(new, new_data, new_end, offset)?;
+            let &(os, oe) = old_values.get(key)?;
+            (old[os..oe] == new[s..e]).then_some((s, e, os))
+        })
+        .collect();
+    same.sort_unstable();
+
+    push_add(out, &new[new_data - 8..new_data])?;
+    let mut pos = new_data;
+    // 还没写出的复制：(旧文件起点, 长度)
+    let mut copy: Option<(usize, usize)> = None;
+    for (s, e, os) in same {
+        if s < pos {
+            // 几个 key 共用同一段文字
+            continue;
+        }
+        if s > pos {
+            if let Some((from, len)) = copy.take() {
+                push_copy(out, from, len)?;
+            }
+            push_add(out, &new[pos..s])?;
+        }
+        copy = match copy {
+            Some((from, len)) if from + len == os => Some((from, len + e - s)),
+            Some((from, len)) => {
+                push_copy(out, from, len)?;
+                Some((os, e - s))
+            }
+            None => Some((os, e - s)),
+        };
+        pos = e;
+    }
+    if let Some((from, len)) = copy {
+        push_copy(out, from, len)?;
+    }
+    push_add(out, &new[pos..new_end])?;
+    Ok(Some(new_end))
+}
+
+/// TKEY 之后 TDAT 数据区的 `(起点, 终点)`
+fn value_table(bytes: &[u8], at: usize) -> Option<(usize, usize)> {
+    if bytes.get(at..at + 4)? != MAGIC_TDAT {
+        return None;
+    }
+    let size = u32::from_le_bytes(bytes.get(at + 4..at + 8)?.try_into().ok()?) as usize;
+    let start = at + 8;
+    (bytes.len() >= start + size).then_some((start, start + size))
+}
+
+/// offset 处 value 的 `(起点, 终点)`（含结尾的 0）
+fn value_range(bytes: &[u8], data: usize, end: usize, offset: u32) -> Option<(usize, usize)> {
+    let start = data + offset as usize;
+    if start >= end || !offset.is_multiple_of(2) {
+        return None;
+    }
+    let len = bytes[start..end]
+        .chunks_exact(2)
+        .position(|u| u == [0, 0])?;
+    Some((start, start + len * 2 + 2))
+}
+
+/// 第 i 条 TKEY 记录的 `(offset, key)`
+fn record(bytes: &[u8], start: usize, i: usize) -> (u32, &[u8]) {
+    let at = start + i * KEY_RECORD_BYTES;
+    let offset = u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+    (offset, &bytes[at + 4..at + KEY_RECORD_BYTES])
+}
+
+fn flush_run(
+    out: &mut Vec<u8>,
+    run: Option<(usize, usize, usize, u32)>,
+    old_start: usize,
+) -> Result<(), String> {
+    let Some((i, _, n, shift)) = run else {
+        return Ok(());
+    };
+    if shift == 0 {
+        return push_copy(out, old_start + i * KEY_RECORD_BYTES, n * KEY_RECORD_BYTES);
+    }
+    out.push(OP_COPY_SHIFT);
+    push_u32(out, old_start + i * KEY_RECORD_BYTES)?;
+    push_u32(out, n * KEY_RECORD_BYTES)?;
+    out.extend_from_slice(&shift.to_le_bytes());
+    Ok(())
+}
+
+fn flush_added(
+    out: &mut Vec<u8>,
+    new: &[u8],
+    new_start: usize,
+    from: Option<usize>,
+    to: usize,
+) -> Result<(), String> {
+    match from {
+        Some(j) => push_add(
+            out,
+            &new[new_start + j * KEY_RECORD_BYTES..new_start + to * KEY_RECORD_BYTES],
+        ),
+        None => Ok(()),
+    }
+}
+
+/// 从 new 的 pos 处起按字节找与 old 相同的段
+fn diff_bytes(out: &mut Vec<u8>, old: &[u8], new: &[u8], pos: usize) -> Result<(), String> {
+    // 旧文件按块建索引；同样内容的块（重复的文字）记下所有位置，位置按顺序排列
+    let mut blocks: HashMap<&[u8], Vec<usize>> = HashMap::new();
+    for (i, block) in old.chunks_exact(BLOCK).enumerate() {
+        blocks.entry(block).or_default().push(i * BLOCK);
+    }
+
+    let mut pending = pos;
+    let mut i = pos;
+    // 上一段复制在旧文件里的结尾：文字改动前后的内容在两边的顺序相同，离它最近的候选最可能对
+    let mut last_end = 0;
+    while i + BLOCK <= new.len() {
+        let Some(at) = blocks
+            .get(&new[i..i + BLOCK])
+            .map(|c| nearest(c, last_end + (i - pending)))
+        else {
+            i += 1;
+            continue;
+        };
+        // 向前、向后尽量延长相同的部分
+        let mut start = i;
+        let mut from = at;
+        while start > pending && from > 0 && new[start - 1] == old[from - 1] {
+            start -= 1;
+            from -= 1;
+        }
+        let mut end = i + BLOCK;
+        while end < new.len()
+            && from + (end - start) < old.len()
+            && new[end] == old[from + (end - start)]
+        {
+            end += 1;
+        }
+        push_add(out, &new[pending..start])?;
+        push_copy(out, from, end - start)?;
+        pending = end;
+        i = end;
+        last_end = from + (end - start);
+    }
+    push_add(out, &new[pending..])
+}
+
+/// 有序的 candidates 里离 target 最近的
+fn nearest(candidates: &[usize], target: usize) -> usize {
+    let i = candidates.partition_point(|&c| c < target);
+    match (i.checked_sub(1).map(|j| candidates[j]), candidates.get(i)) {
+        (Some(a), Some(&b)) if target - a <= b - target => a,
+        (_, Some(&b)) => b,
+        (Some(a), None) => a,
+        (None, None) => unreachable!("block index never stores an empty list"),
+    }
+}
+
+fn read_u32(bytes: &[u8], cur: &mut usize) -> Result<usize, String> {
+    let b = bytes.get(*cur..*cur + 4).ok_or("Delta is truncated")?;
+    *cur += 4;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]) as usize)
+}
+
+/// 读差分包的头部
+pub fn delta_info(delta: &[u8]) -> Result<DeltaInfo, String> {
+    if delta.get(..4) != Some(MAGIC_DELTA.as_slice()) {
+        return Err("Not a GXT delta (missing GXTD header)".to_string());
+    }
+    if delta.get(4) != Some(&VERSION) {
+        return Err(format!("Unsupported delta version: {:?}", delta.get(4)));
+    }
+    let mut cur = 5;
+    Ok(DeltaInfo {
+        old_size: read_u32(delta, &mut cur)?,
+        old_crc32: read_u32(delta, &mut cur)? as u32,
+        new_size: read_u32(delta, &mut cur)?,
+        new_crc32: read_u32(delta, &mut cur)? as u32,
+    })
+}
+
+/// 在旧文件上打补丁；旧文件与生成差分时的不一致（用户改过或版本不对）时报错，不会写出坏文件
+pub fn apply_delta(old: &[u8], delta: &[u8]) -> Result<Vec<u8>, String> {
+    let info = delta_info(delta)?;
+    if old.len() != info.old_size || crc32_bytes(old) != info.old_crc32 {
+        return Err(
+            "The file does not match the one this delta was made from (modified or a different version)"
+                .to_string(),
+        );
+    }
+    let mut out = Vec::with_capacity(info.new_size);
+    let mut cur = 21;
+    while cur < delta.len() {
+        let op = delta[cur];
+        cur += 1;
+        match op {
+            OP_COPY => {
+                let from = read_u32(delta, &mut cur)?;
+                let len = read_u32(delta, &mut cur)?;
+                let src = old
+                    .get(from..from + len)
+                    .ok_or("Delta copies past the end of the old file")?;
+                out.extend_from_slice(src);
+            }
+            OP_COPY_SHIFT => {
+                let from = read_u32(delta, &mut cur)?;
+                let len = read_u32(delta, &mut cur)?;
+                let shift = read_u32(delta, &mut cur)? as u32;
+                let src = old
+                    .get(from..from + len)
+                    .ok_or("Delta copies past the end of the old file")?;
+                for rec in src.chunks(KEY_RECORD_BYTES) {
+                    let start = out.len();
+                    out.extend_from_slice(rec);
+                    if let Some(b) = out.get_mut(start..start + 4) {
+                        let offset = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+                        b.copy_from_slice(&offset.wrapping_add(shift).to_le_bytes());
+                    }
+                }
+            }
+            OP_ADD => {
+                let len = read_u32(delta, &mut cur)?;
+                let src = delta.get(cur..cur + len).ok_or("Delta is truncated")?;
+                out.extend_from_slice(src);
+                cur += len;
+            }
+            _ => return Err(format!("Invalid delta operation {op} at {}", cur - 1)),
+        }
+    }
+    if out.len() != info.new_size || crc32_bytes(&out) != info.new_crc32 {
+        return Err("Patched file does not match the expected result".to_string());
+    }
+    Ok(out)
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+use crate::entry::GxtEntry;
+
+/// 按 key 比较两份条目列表的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EntryDiff {
+    /// 只在 new 里有（按 new 中顺序）
+    pub added: Vec<GxtEntry>,
+    /// 只在 old 里有（按 old 中顺序）
+    pub removed: Vec<GxtEntry>,
+    /// 两边都有但 value 不同（按 old 中顺序）
+    pub changed: Vec<ChangedEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ChangedEntry {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl EntryDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+pub fn diff_entries(old: &[GxtEntry], new: &[GxtEntry]) -> EntryDiff {
+    let old_map: HashMap<&str, &str> = old.iter().map(|e| (e.key.as_str(), &*e.value)).collect();
+    let new_map: HashMap<&str, &str> = new.iter().map(|e| (e.key.as_str(), &*e.value)).collect();
+
+    let mut diff = EntryDiff::default();
+    for e in old {
+        match new_map.get(e.key.as_str()) {
+            None => diff.removed.push(e.clone()),
+            Some(&v) if v != &*e.value => diff.changed.push(ChangedEntry {
+                key: e.key.clone(),
+                old: e.value.to_string(),
+                new: v.to_string(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.added = new
+        .iter()
+        .filter(|e| !old_map.contains_key(e.key.as_str()))
+        .cloned()
+        .collect();
+    diff
+}
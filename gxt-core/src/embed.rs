@@ -0,0 +1,77 @@
+//! 嵌在更大容器文件里的 GXT 段（有些全面转换 mod 把文本表打包进自己的数据文件）。
+//!
+//! 段从 `TKEY` 开始，到 TDAT 数据区结束；写回时只替换这一段，前后的字节原样保留。
+
+use crate::format::{parse_key_table, KEY_RECORD_BYTES, MAGIC_TKEY};
+
+/// 容器里 GXT 段的位置（字节）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EmbeddedSection {
+    pub offset: usize,
+    pub len: usize,
+}
+
+/// bytes 开头那个 GXT 段的长度（TKEY 头 + key 表 + TDAT 头 + 数据区）
+pub fn section_len(bytes: &[u8]) -> Result<usize, String> {
+    let (keys, val_field) = parse_key_table(bytes)?;
+    Ok(8 + keys.len() * KEY_RECORD_BYTES + 8 + val_field.len())
+}
+
+/// offset 处的 GXT 段
+pub fn section_at(bytes: &[u8], offset: usize) -> Result<EmbeddedSection, String> {
+    let tail = bytes
+        .get(offset..)
+        .ok_or_else(|| format!("Offset {offset} is past the end of the file"))?;
+    Ok(EmbeddedSection {
+        offset,
+        len: section_len(tail)?,
+    })
+}
+
+/// 找第一个能完整解析的 GXT 段（只出现 `TKEY` 四个字节、后面不是合法表的位置跳过）
+pub fn find_section(bytes: &[u8]) -> Option<EmbeddedSection> {
+    bytes
+        .windows(MAGIC_TKEY.len())
+        .enumerate()
+        .filter(|(_, w)| *w == MAGIC_TKEY)
+        .find_map(|(offset, _)| section_at(bytes, offset).ok())
+}
+
+/// 把容器里的段换成 rebuilt，返回新的容器与新段的位置。
+///
+/// rebuilt 比原来短时用 0 补齐，容器里其它数据的偏移不变；比原来长时只有 allow_grow
+/// 为 true 才写（后面的字节整体后移，容器里记着绝对偏移的格式会因此损坏）
+pub fn splice_section(
+    container: &[u8],
+    section: EmbeddedSection,
+    rebuilt: &[u8],
+    allow_grow: bool,
+) -> Result<(Vec<u8>, EmbeddedSection), String> {
+    let end = section
+        .offset
+        .checked_add(section.len)
+        .filter(|end| *end <= container.len())
+        .ok_or_else(|| "Embedded section is out of range of the container".to_string())?;
+    if rebuilt.len() > section.len && !allow_grow {
+        return Err(format!(
+            "Rebuilt GXT is {} bytes but the embedded section only has {}",
+            rebuilt.len(),
+            section.len
+        ));
+    }
+
+    let len = rebuilt.len().max(section.len);
+    let mut out = Vec::with_capacity(container.len() - section.len + len);
+    out.extend_from_slice(&container[..section.offset]);
+    out.extend_from_slice(rebuilt);
+    out.resize(section.offset + len, 0);
+    out.extend_from_slice(&container[end..]);
+    Ok((
+        out,
+        EmbeddedSection {
+            offset: section.offset,
+            len,
+        },
+    ))
+}
@@ -0,0 +1,10 @@
+use std::sync::Arc;
+
+/// 一条文本：KEY（1..=8 字节可见 ASCII）+ value（允许转义，见 escape 模块）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GxtEntry {
+    pub key: String,
+    /// 相同文本的 value 共享同一份内存（见 Interner）
+    pub value: Arc<str>,
+}
@@ -0,0 +1,317 @@
+use std::fmt::Write as _;
+
+// 你的“特殊区间”
+const SPECIAL_MIN: u16 = 0x0080;
+const SPECIAL_MAX: u16 = 0x009F;
+
+/// 把 start 处的 UTF-16Z 解码进 out（先清空）。
+/// 直接在字节切片上按 u16 迭代，不再先收集成 Vec<u16>；out 由调用方复用。
+pub fn decode_utf16z_with_escapes(val_field: &[u8], start: usize, out: &mut String) -> Result<(), String> {
+    out.clear();
+    let tail = val_field
+        .get(start..)
+        .ok_or("Unexpected EOF while reading UTF-16Z")?;
+    let units = tail
+        .chunks_exact(2)
+        .map(|b| u16::from_le_bytes([b[0], b[1]]))
+        .take_while(|&u| u != 0);
+    push_units_with_escapes(units, out);
+    Ok(())
+}
+
+fn push_units_with_escapes(units: impl Iterator<Item = u16>, out: &mut String) {
+    let mut units = units.peekable();
+
+    while let Some(u) = units.next() {
+        // surrogate pair -> Unicode
+        if (0xD800..=0xDBFF).contains(&u) {
+            if let Some(&lo) = units.peek() {
+                if (0xDC00..=0xDFFF).contains(&lo) {
+                    let hi = (u as u32) - 0xD800;
+                    let lo = (lo as u32) - 0xDC00;
+                    let cp = 0x10000 + ((hi << 10) | lo);
+                    if let Some(ch) = char::from_u32(cp) {
+                        out.push(ch);
+                        units.next();
+                        continue;
+                    }
+                }
+            }
+        }
+
+        // 特殊区间 + 不成对 surrogate：输出可逆转义
+        if (SPECIAL_MIN..=SPECIAL_MAX).contains(&u) || (0xD800..=0xDFFF).contains(&u) {
+            let _ = write!(out, "\\u{{{:04X}}}", u);
+            continue;
+        }
+
+        // 普通 BMP
+        match char::from_u32(u as u32) {
+            Some(ch) => out.push(ch),
+            None => {
+                let _ = write!(out, "\\u{{{:04X}}}", u);
+            }
+        }
+    }
+}
+
+/// 把 value 文本（含 \xNNNN / \uNNNN / \u{...} 转义）编码为 UTF-16LE 并追加 0 结尾，返回写入的字节数
+pub fn encode_utf16z_with_escapes(s: &str, out: &mut Vec<u8>) -> Result<u32, String> {
+    let start_len = out.len();
+    encode_units(s, &mut |u| push_u16_le(out, u))?;
+
+    // 0 terminator
+    push_u16_le(out, 0);
+
+    let written = out.len() - start_len;
+    u32::try_from(written).map_err(|_| "TDAT chunk too large".to_string())
+}
+
+/// encode_utf16z_with_escapes 会写出的字节数（含 0 结尾），只数不写
+pub fn encoded_utf16z_len(s: &str) -> Result<usize, String> {
+    let mut units = 1usize;
+    encode_units(s, &mut |_| units += 1)?;
+    Ok(units * 2)
+}
+
+/// 逐个产出 value 对应的 UTF-16 单元（不含结尾的 0）
+pub(crate) fn encode_units(s: &str, push: &mut impl FnMut(u16)) -> Result<(), String> {
+    let bytes = s.as_bytes();
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        if bytes[i] == b'\\' {
+            // \\ => literal '\'
+            if i + 1 < bytes.len() && bytes[i + 1] == b'\\' {
+                push(b'\\' as u16);
+                i += 2;
+                continue;
+            }
+
+            // \xNNNN
+            if i + 5 < bytes.len() && bytes[i + 1] == b'x' {
+                if let Some(u) = parse_fixed_4hex(&bytes[(i + 2)..(i + 6)]) {
+                    push(u);
+                    i += 6;
+                    continue;
+                }
+            }
+
+            // \uNNNN
+            if i + 5 < bytes.len() && bytes[i + 1] == b'u' && bytes[i + 2] != b'{' {
+                if let Some(u) = parse_fixed_4hex(&bytes[(i + 2)..(i + 6)]) {
+                    push(u);
+                    i += 6;
+                    continue;
+                }
+            }
+
+            // \u{...}
+            if i + 3 < bytes.len() && bytes[i + 1] == b'u' && bytes[i + 2] == b'{' {
+                if let Some((cp, consumed)) = parse_braced_hex(&bytes[(i + 3)..]) {
+                    if cp <= 0x10FFFF {
+                        if cp <= 0xFFFF {
+                            push(cp as u16);
+                        } else {
+                            let cp2 = cp - 0x10000;
+                            let hi = 0xD800 | ((cp2 >> 10) as u16);
+                            let lo = 0xDC00 | ((cp2 & 0x3FF) as u16);
+                            push(hi);
+                            push(lo);
+                        }
+                        i += 3 + consumed; // "\" "u" "{" + ... "}"
+                        continue;
+                    } else {
+                        return Err(format!("Invalid codepoint in \\u{{...}}: {cp:X}"));
+                    }
+                }
+            }
+
+            // fallback: treat '\' as normal char
+            push(b'\\' as u16);
+            i += 1;
+            continue;
+        }
+
+        let ch = match s[i..].chars().next() {
+            Some(c) => c,
+            None => break,
+        };
+        let mut buf = [0u16; 2];
+        let encoded = ch.encode_utf16(&mut buf);
+        for &u in encoded.iter() {
+            push(u);
+        }
+        i += ch.len_utf8();
+    }
+    Ok(())
+}
+
+/// 写错的转义：编码时会被当成普通的 `\` 加文字原样写进去
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EscapeIssue {
+    /// 在 value 里的位置（按字符数，不是字节）
+    pub at: usize,
+    /// 有问题的那段原文，如 `\x41`
+    pub text: String,
+    pub message: String,
+    /// 可能想写的转义；猜不出时为 None
+    pub suggestion: Option<String>,
+}
+
+/// 检查 value 里用户输入的转义是否完整（`\xNNNN` / `\uNNNN` / `\u{...}`）。
+/// 只管看起来像转义的：`\x`、`\u` 后面至少跟着一位十六进制数字或 `{`；
+/// 其它反斜杠（如路径里的 `C:\users`）照旧当普通字符，不报
+pub fn lint_escapes(s: &str) -> Vec<EscapeIssue> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::new();
+    let mut i = 0usize;
+    let issue =
+        |start: usize, end: usize, message: String, suggestion: Option<String>| EscapeIssue {
+            at: s[..start].chars().count(),
+            text: s[start..end].to_string(),
+            message,
+            suggestion,
+        };
+
+    while i < bytes.len() {
+        if bytes[i] != b'\\' {
+            i += 1;
+            continue;
+        }
+        let kind = match bytes.get(i + 1) {
+            Some(b'\\') => {
+                i += 2;
+                continue;
+            }
+            Some(&b @ (b'x' | b'u')) => b as char,
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+
+        if kind == 'u' && bytes.get(i + 2) == Some(&b'{') {
+            let body = &bytes[i + 3..];
+            let hex_len = body.iter().take_while(|b| is_hex(**b)).count();
+            let hex = &s[i + 3..i + 3 + hex_len];
+            match body.iter().position(|&b| b == b'}') {
+                None => {
+                    let end = i + 3 + hex_len;
+                    let suggestion = (hex_len > 0).then(|| format!("\\u{{{hex}}}"));
+                    out.push(issue(
+                        i,
+                        end,
+                        "Unclosed \\u{...} escape".to_string(),
+                        suggestion,
+                    ));
+                    i = end;
+                }
+                Some(0) => {
+                    out.push(issue(i, i + 4, "Empty \\u{} escape".to_string(), None));
+                    i += 4;
+                }
+                Some(close) if close != hex_len => {
+                    let end = i + 3 + close + 1;
+                    out.push(issue(
+                        i,
+                        end,
+                        "Non-hex digit in \\u{...} escape".to_string(),
+                        None,
+                    ));
+                    i = end;
+                }
+                Some(close) => {
+                    let end = i + 3 + close + 1;
+                    let in_range = u32::from_str_radix(hex, 16).is_ok_and(|cp| cp <= 0x10FFFF);
+                    if !in_range {
+                        out.push(issue(
+                            i,
+                            end,
+                            "Code point out of range in \\u{...} escape (max 10FFFF)".to_string(),
+                            None,
+                        ));
+                    }
+                    i = end;
+                }
+            }
+            continue;
+        }
+
+        let hex_len = bytes[i + 2..]
+            .iter()
+            .take(4)
+            .take_while(|b| is_hex(**b))
+            .count();
+        if hex_len == 0 || hex_len == 4 {
+            // 不像转义，或者完整
+            i += 2 + hex_len;
+            continue;
+        }
+        let end = i + 2 + hex_len;
+        let hex = &s[i + 2..end];
+        out.push(issue(
+            i,
+            end,
+            format!("\\{kind} escape needs exactly 4 hex digits"),
+            Some(format!("\\{kind}{hex:0>4}")),
+        ));
+        i = end;
+    }
+    out
+}
+
+fn push_u16_le(out: &mut Vec<u8>, u: u16) {
+    out.extend_from_slice(&u.to_le_bytes());
+}
+
+fn parse_fixed_4hex(hex4: &[u8]) -> Option<u16> {
+    if hex4.len() != 4 || !hex4.iter().all(|b| is_hex(*b)) {
+        return None;
+    }
+    let s = std::str::from_utf8(hex4).ok()?;
+    u16::from_str_radix(s, 16).ok()
+}
+
+fn parse_braced_hex(input: &[u8]) -> Option<(u32, usize)> {
+    let mut j = 0usize;
+    while j < input.len() && input[j] != b'}' {
+        j += 1;
+    }
+    if j == 0 || j >= input.len() {
+        return None;
+    }
+    let hex = &input[..j];
+    if !hex.iter().all(|b| is_hex(*b)) {
+        return None;
+    }
+    let s = std::str::from_utf8(hex).ok()?;
+    let cp = u32::from_str_radix(s, 16).ok()?;
+    Some((cp, j + 1))
+}
+
+fn is_hex(b: u8) -> bool {
+    b.is_ascii_hexdigit()
+}
+
+/// URL 里的 %XX 解码（HTTP API 路径里的 key、gxt:// 链接的参数）。
+/// 不完整或不是十六进制的 % 原样保留；解出的字节不是 UTF-8 时按替换字符处理
+pub fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 3 <= bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(b) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
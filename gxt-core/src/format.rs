@@ -0,0 +1,466 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+use crate::cache::ValueCache;
+use crate::entry::GxtEntry;
+use crate::escape::{decode_utf16z_with_escapes, encode_utf16z_with_escapes, encoded_utf16z_len};
+use crate::intern::Interner;
+use crate::validate::{validate_entries, validate_key};
+
+pub const MAGIC_TKEY: &[u8; 4] = b"TKEY";
+pub const MAGIC_TDAT: &[u8; 4] = b"TDAT";
+/// TKEY 里每条记录：4 字节 offset + 8 字节 key
+pub const KEY_RECORD_BYTES: usize = 12;
+
+/// TDAT 超限时报告里列出最大的几条
+const LARGEST_SHOWN: usize = 5;
+
+// -------------------- Core: parse/build --------------------
+
+/// 解析整个 GXT 文件（TKEY + TDAT）
+pub fn parse_gxt_bytes(bytes: &[u8]) -> Result<Vec<GxtEntry>, String> {
+    let (keys, val_field) = parse_key_table(bytes)?;
+    decode_values(keys, val_field)
+}
+
+/// parse_key_table 的结果：TKEY 表里的 (key, value offset)，以及 TDAT 数据区
+pub type KeyTable<'a> = (Vec<(&'a str, u32)>, &'a [u8]);
+
+/// 第一阶段：读 TKEY 表，并定位 TDAT 数据区（不解码任何 value）
+/// key 借用自 bytes，直到 decode_values 才转成 owned
+pub fn parse_key_table(bytes: &[u8]) -> Result<KeyTable<'_>, String> {
+    let mut cur = 0usize;
+
+    // TKEY
+    require_magic(bytes, &mut cur, MAGIC_TKEY)?;
+
+    // key_field_size
+    let key_field_size = read_u32_le(bytes, &mut cur)? as usize;
+    if !key_field_size.is_multiple_of(KEY_RECORD_BYTES) {
+        return Err(format!("Invalid key_field_size: {key_field_size} (not divisible by {KEY_RECORD_BYTES})"));
+    }
+
+    let entry_count = key_field_size / KEY_RECORD_BYTES;
+    let mut keys: Vec<(&str, u32)> = Vec::with_capacity(entry_count);
+    let mut seen = HashSet::with_capacity(entry_count);
+
+    for _ in 0..entry_count {
+        let idx = read_u32_le(bytes, &mut cur)?;
+        let key_raw = read_bytes(bytes, &mut cur, 8)?;
+        let key = decode_key_8bytes(key_raw)?;
+
+        if !seen.insert(key) {
+            return Err(format!("Duplicate key in file: {key}"));
+        }
+        keys.push((key, idx));
+    }
+
+    // TDAT
+    require_magic(bytes, &mut cur, MAGIC_TDAT)?;
+
+    let val_field_size = read_u32_le(bytes, &mut cur)? as usize;
+    let val_field = read_bytes(bytes, &mut cur, val_field_size)?;
+
+    Ok((keys, val_field))
+}
+
+/// 第二阶段：按 TKEY 里的 offset 从 TDAT 解码每条 value
+pub fn decode_values(keys: Vec<(&str, u32)>, val_field: &[u8]) -> Result<Vec<GxtEntry>, String> {
+    let mut interner = Interner::default();
+    let mut scratch = String::new();
+    let mut entries = Vec::with_capacity(keys.len());
+    for (key, idx) in keys {
+        let idx_usize = idx as usize;
+        if idx_usize >= val_field.len() {
+            return Err(format!("Value offset out of range for key {key}: idx={idx}"));
+        }
+        if !idx_usize.is_multiple_of(2) {
+            return Err(format!(
+                "Value offset is not aligned (must be even) for key {key}: idx={idx}"
+            ));
+        }
+
+        // 只有没见过的 value 才会真正分配（见 Interner）
+        decode_utf16z_with_escapes(val_field, idx_usize, &mut scratch)?;
+        let value = interner.intern(&scratch);
+        entries.push(GxtEntry {
+            key: key.to_string(),
+            value,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// 读文件时发现、但不妨碍打开的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "kebab-case"))]
+pub enum LoadWarning {
+    /// TDAT 之后还有多余的字节（保存时不会写回）
+    TrailingData { len: usize },
+    /// value 到 TDAT 末尾都没有 0 结尾，按读到的部分恢复
+    Unterminated { key: String },
+    /// 宽松模式：TDAT 比声明的短，只读到 actual 字节；offset 落在外面的条目丢掉
+    TruncatedTdat { declared: usize, actual: usize },
+    /// 宽松模式：重复的 key，只留第一个
+    DuplicateKey { key: String },
+    /// 宽松模式：offset 越界或不是偶数，丢掉这条
+    BadOffset { key: String, offset: u32 },
+    /// 宽松模式：第 index 条（从 0 开始）的 key 不是合法 UTF-8，丢掉这条
+    BadKey { index: usize },
+}
+
+impl LoadWarning {
+    /// 针对某一条的警告返回它的 key；针对整个文件的为 None
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            LoadWarning::Unterminated { key }
+            | LoadWarning::DuplicateKey { key }
+            | LoadWarning::BadOffset { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+}
+
+/// 与 parse_gxt_bytes 相同，另外报告不影响打开的问题（多余的尾部数据、没有 0 结尾的 value）。
+/// lenient 时重复的 key、坏的 offset、坏的 key、截短的 TDAT 也只作为警告，
+/// 丢掉读不出的条目后照常打开，用来抢救损坏的文件
+pub fn parse_gxt_bytes_checked(
+    bytes: &[u8],
+    lenient: bool,
+) -> Result<(Vec<GxtEntry>, Vec<LoadWarning>), String> {
+    let mut warnings = Vec::new();
+    let mut cur = 0usize;
+    require_magic(bytes, &mut cur, MAGIC_TKEY)?;
+    let key_field_size = read_u32_le(bytes, &mut cur)? as usize;
+    if !key_field_size.is_multiple_of(KEY_RECORD_BYTES) {
+        return Err(format!(
+            "Invalid key_field_size: {key_field_size} (not divisible by {KEY_RECORD_BYTES})"
+        ));
+    }
+
+    let mut keys: Vec<(&str, u32)> = Vec::with_capacity(key_field_size / KEY_RECORD_BYTES);
+    let mut seen = HashSet::new();
+    for index in 0..key_field_size / KEY_RECORD_BYTES {
+        let idx = read_u32_le(bytes, &mut cur)?;
+        let key = match decode_key_8bytes(read_bytes(bytes, &mut cur, 8)?) {
+            Ok(key) => key,
+            Err(_) if lenient => {
+                warnings.push(LoadWarning::BadKey { index });
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        if !seen.insert(key) {
+            if !lenient {
+                return Err(format!("Duplicate key in file: {key}"));
+            }
+            warnings.push(LoadWarning::DuplicateKey {
+                key: key.to_string(),
+            });
+            continue;
+        }
+        keys.push((key, idx));
+    }
+
+    require_magic(bytes, &mut cur, MAGIC_TDAT)?;
+    let declared = read_u32_le(bytes, &mut cur)? as usize;
+    let val_field = match read_bytes(bytes, &mut cur, declared) {
+        Ok(field) => field,
+        Err(_) if lenient => {
+            let field = &bytes[cur..];
+            cur = bytes.len();
+            warnings.push(LoadWarning::TruncatedTdat {
+                declared,
+                actual: field.len(),
+            });
+            field
+        }
+        Err(e) => return Err(e),
+    };
+    if cur < bytes.len() {
+        warnings.push(LoadWarning::TrailingData {
+            len: bytes.len() - cur,
+        });
+    }
+
+    let mut interner = Interner::default();
+    let mut scratch = String::new();
+    let mut entries = Vec::with_capacity(keys.len());
+    for (key, idx) in keys {
+        let start = idx as usize;
+        if start >= val_field.len() || !start.is_multiple_of(2) {
+            if !lenient {
+                return Err(format!("Bad value offset for key {key}: idx={idx}"));
+            }
+            warnings.push(LoadWarning::BadOffset {
+                key: key.to_string(),
+                offset: idx,
+            });
+            continue;
+        }
+        decode_utf16z_with_escapes(val_field, start, &mut scratch)?;
+        let terminated = val_field[start..].chunks_exact(2).any(|u| u == [0, 0]);
+        if !terminated {
+            warnings.push(LoadWarning::Unterminated {
+                key: key.to_string(),
+            });
+        }
+        entries.push(GxtEntry {
+            key: key.to_string(),
+            value: interner.intern(&scratch),
+        });
+    }
+    Ok((entries, warnings))
+}
+
+/// 输出只取决于条目（及其顺序）：offset 按顺序紧密排列，没有对齐填充，相同 value 也各写一份。
+/// 先用 sort_entries 排序即得到与条目原顺序无关的确定性输出
+pub fn build_gxt_bytes(entries: &[GxtEntry]) -> Result<Vec<u8>, String> {
+    build_gxt_bytes_cached(entries, &mut ValueCache::default())
+}
+
+/// value 的 UTF-16Z 字节优先从缓存取，只有缓存里没有的才重新编码；
+/// offset 仍按顺序重新累加，所以只要条目顺序/增删变化也能正确重建
+pub fn build_gxt_bytes_cached(
+    entries: &[GxtEntry],
+    cache: &mut ValueCache,
+) -> Result<Vec<u8>, String> {
+    build_gxt_bytes_with(entries, cache, &mut |_| Ok(()))
+}
+
+/// 每编码完一条调用一次 on_entry(已完成条数)；它返回 Err 即中止（用于取消）
+pub fn build_gxt_bytes_with(
+    entries: &[GxtEntry],
+    cache: &mut ValueCache,
+    on_entry: &mut dyn FnMut(usize) -> Result<(), String>,
+) -> Result<Vec<u8>, String> {
+    validate_entries(entries)?;
+    check_tdat_size(entries)?;
+
+    let mut out: Vec<u8> = Vec::new();
+    out.extend_from_slice(MAGIC_TKEY);
+
+    let key_field_size = (entries.len() * KEY_RECORD_BYTES) as u32;
+    out.extend_from_slice(&key_field_size.to_le_bytes());
+
+    let mut val_field: Vec<u8> = Vec::new();
+    let mut offset: u32 = 0;
+
+    for (n, e) in entries.iter().enumerate() {
+        out.extend_from_slice(&offset.to_le_bytes());
+
+        let key8 = encode_key_8bytes(&e.key)?;
+        out.extend_from_slice(&key8);
+
+        let written = match cache.get(&e.value) {
+            Some(cached) => {
+                val_field.extend_from_slice(cached);
+                u32::try_from(cached.len()).map_err(|_| "TDAT chunk too large".to_string())?
+            }
+            None => {
+                let start = val_field.len();
+                let written = encode_utf16z_with_escapes(&e.value, &mut val_field)?;
+                cache.insert(e.value.clone(), val_field[start..].to_vec());
+                written
+            }
+        };
+        offset = offset
+            .checked_add(written)
+            .ok_or("TDAT size overflow (too large)")?;
+        on_entry(n + 1)?;
+    }
+
+    let used: HashSet<&str> = entries.iter().map(|e| &*e.value).collect();
+    cache.retain_used(&used);
+
+    out.extend_from_slice(MAGIC_TDAT);
+    out.extend_from_slice(&(val_field.len() as u32).to_le_bytes());
+    out.extend_from_slice(&val_field);
+
+    Ok(out)
+}
+
+/// 与 build_gxt_bytes 相同，但相同的 value 只写一份，多个 key 指向同一个 offset（文件更小）
+pub fn build_gxt_bytes_shared(entries: &[GxtEntry]) -> Result<Vec<u8>, String> {
+    validate_entries(entries)?;
+    let mut seen = HashSet::new();
+    let unique: Vec<&GxtEntry> = entries.iter().filter(|e| seen.insert(&*e.value)).collect();
+    check_values_size(unique.iter().copied())?;
+
+    let mut out: Vec<u8> = Vec::new();
+    out.extend_from_slice(MAGIC_TKEY);
+    out.extend_from_slice(&((entries.len() * KEY_RECORD_BYTES) as u32).to_le_bytes());
+
+    let mut val_field: Vec<u8> = Vec::new();
+    let mut offsets: HashMap<&str, u32> = HashMap::new();
+    for e in entries {
+        let offset = match offsets.get(&*e.value) {
+            Some(&o) => o,
+            None => {
+                let o = u32::try_from(val_field.len()).map_err(|_| "TDAT size overflow")?;
+                encode_utf16z_with_escapes(&e.value, &mut val_field)?;
+                offsets.insert(&e.value, o);
+                o
+            }
+        };
+        out.extend_from_slice(&offset.to_le_bytes());
+        out.extend_from_slice(&encode_key_8bytes(&e.key)?);
+    }
+
+    out.extend_from_slice(MAGIC_TDAT);
+    out.extend_from_slice(&(val_field.len() as u32).to_le_bytes());
+    out.extend_from_slice(&val_field);
+
+    Ok(out)
+}
+
+/// 与 build_gxt_bytes_shared 相同，另外 aliases（key -> 宿主 key）里的 key 在自己的 value
+/// 正好是宿主 value 的结尾时指向宿主 value 的中间，不单独存放；不再是结尾的照常单独写一份
+pub fn build_gxt_bytes_aliased(
+    entries: &[GxtEntry],
+    aliases: &HashMap<String, String>,
+) -> Result<Vec<u8>, String> {
+    validate_entries(entries)?;
+    let mut seen = HashSet::new();
+    let unique: Vec<&GxtEntry> = entries.iter().filter(|e| seen.insert(&*e.value)).collect();
+    check_values_size(unique.iter().copied())?;
+    let values: HashMap<&str, &str> = entries
+        .iter()
+        .map(|e| (e.key.as_str(), &*e.value))
+        .collect();
+
+    // 先写宿主与普通的 value：value -> (offset, 字节数)
+    let mut val_field: Vec<u8> = Vec::new();
+    let mut written: HashMap<&str, (usize, usize)> = HashMap::new();
+    for e in entries.iter().filter(|e| !aliases.contains_key(&e.key)) {
+        if !written.contains_key(&*e.value) {
+            let o = val_field.len();
+            let n = encode_utf16z_with_escapes(&e.value, &mut val_field)? as usize;
+            written.insert(&e.value, (o, n));
+        }
+    }
+
+    let mut out: Vec<u8> = Vec::new();
+    out.extend_from_slice(MAGIC_TKEY);
+    out.extend_from_slice(&((entries.len() * KEY_RECORD_BYTES) as u32).to_le_bytes());
+    let mut own = Vec::new();
+    for e in entries {
+        let offset = match written.get(&*e.value) {
+            Some(&(o, _)) => o,
+            None => {
+                own.clear();
+                encode_utf16z_with_escapes(&e.value, &mut own)?;
+                let host = aliases
+                    .get(&e.key)
+                    .and_then(|h| values.get(h.as_str()))
+                    .and_then(|v| written.get(v));
+                match host {
+                    Some(&(o, n)) if n > own.len() && val_field[o..o + n].ends_with(&own) => {
+                        o + n - own.len()
+                    }
+                    _ => {
+                        let o = val_field.len();
+                        val_field.extend_from_slice(&own);
+                        written.insert(&e.value, (o, own.len()));
+                        o
+                    }
+                }
+            }
+        };
+        let offset = u32::try_from(offset).map_err(|_| "TDAT size overflow")?;
+        out.extend_from_slice(&offset.to_le_bytes());
+        out.extend_from_slice(&encode_key_8bytes(&e.key)?);
+    }
+
+    out.extend_from_slice(MAGIC_TDAT);
+    out.extend_from_slice(&(val_field.len() as u32).to_le_bytes());
+    out.extend_from_slice(&val_field);
+
+    Ok(out)
+}
+
+/// 编码前预估 TDAT 大小：超出 u32 offset 能表示的范围时直接报错，列出最大的几条与超出多少，
+/// 而不是编到一半才失败
+pub fn check_tdat_size(entries: &[GxtEntry]) -> Result<(), String> {
+    check_values_size(entries.iter())
+}
+
+fn check_values_size<'a>(
+    entries: impl Iterator<Item = &'a GxtEntry> + Clone,
+) -> Result<(), String> {
+    let limit = u64::from(u32::MAX);
+    // UTF-8 字节数的两倍不小于 UTF-16 字节数：绝大多数文件到这里就放行了，不用逐条数
+    let bound: u64 = entries.clone().map(|e| e.value.len() as u64 * 2 + 2).sum();
+    if bound <= limit {
+        return Ok(());
+    }
+    let mut sizes = entries
+        .map(|e| Ok((encoded_utf16z_len(&e.value)? as u64, e.key.as_str())))
+        .collect::<Result<Vec<_>, String>>()?;
+    let total: u64 = sizes.iter().map(|(n, _)| n).sum();
+    if total <= limit {
+        return Ok(());
+    }
+    sizes.sort_by_key(|s| Reverse(s.0));
+    let largest: Vec<String> = sizes
+        .iter()
+        .take(LARGEST_SHOWN)
+        .map(|(n, key)| format!("{key} ({n} bytes)"))
+        .collect();
+    Err(format!(
+        "Text data would take {total} bytes, {} bytes over the 4 GiB limit of GXT offsets. \
+         Largest entries: {}. Shorten them or split the text into several files",
+        total - limit,
+        largest.join(", ")
+    ))
+}
+
+// -------------------- Key encoding/decoding --------------------
+
+/// 直接借用原始 8 字节（截到第一个 0），不分配
+fn decode_key_8bytes(raw: &[u8]) -> Result<&str, String> {
+    if raw.len() != 8 {
+        return Err("Key raw size must be 8".into());
+    }
+    let len = raw.iter().position(|&b| b == 0).unwrap_or(raw.len());
+    std::str::from_utf8(&raw[..len]).map_err(|e| format!("Key is not valid UTF-8: {e}"))
+}
+
+fn encode_key_8bytes(key: &str) -> Result<[u8; 8], String> {
+    validate_key(key)?;
+    let bytes = key.as_bytes();
+    let mut out = [0u8; 8];
+    out[..bytes.len()].copy_from_slice(bytes);
+    Ok(out)
+}
+
+// -------------------- Low-level readers --------------------
+
+fn require_magic(bytes: &[u8], cur: &mut usize, magic: &[u8; 4]) -> Result<(), String> {
+    let got = read_bytes(bytes, cur, 4)?;
+    if got != magic {
+        return Err(format!(
+            "Magic mismatch at {:#X}: expected {:?}, got {:?}",
+            *cur - 4,
+            magic,
+            got
+        ));
+    }
+    Ok(())
+}
+
+fn read_u32_le(bytes: &[u8], cur: &mut usize) -> Result<u32, String> {
+    let b = read_bytes(bytes, cur, 4)?;
+    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn read_bytes<'a>(bytes: &'a [u8], cur: &mut usize, n: usize) -> Result<&'a [u8], String> {
+    if *cur + n > bytes.len() {
+        return Err("Unexpected EOF".into());
+    }
+    let s = &bytes[*cur..*cur + n];
+    *cur += n;
+    Ok(s)
+}
@@ -0,0 +1,87 @@
+//! 按 key 前缀把条目分组成树（导航侧栏用：单表的大文件里上万个 key 按任务/前缀折叠）。
+
+/// 树的一层怎么从 key 里取分组名
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum GroupLevel {
+    /// 前 n 个字符（在上一层的基础上再多取 n 个）
+    Prefix(usize),
+    /// 到这个分隔符为止（含），如 `_` 把 `FIN_A1` 分到 `FIN_`
+    Until(char),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyGroup {
+    /// 完整前缀
+    pub name: String,
+    /// 组内（含子组）key 的数目
+    pub count: usize,
+    pub children: Vec<KeyGroup>,
+    /// 直接属于本组、不再往下分的 key
+    pub keys: Vec<String>,
+}
+
+/// 按 levels 一层层分组，返回名字为空的根。某层取不出更长前缀的 key 留在上一层的 keys 里；
+/// 同一层只有一个组、且没有 key 直接落在这一层时不单独成层。组与 key 都按名字排序
+pub fn group_keys<'a>(keys: impl IntoIterator<Item = &'a str>, levels: &[GroupLevel]) -> KeyGroup {
+    let mut keys: Vec<&str> = keys.into_iter().collect();
+    keys.sort_unstable();
+    let (children, direct) = build(&keys, 0, levels);
+    KeyGroup {
+        name: String::new(),
+        count: keys.len(),
+        children,
+        keys: direct,
+    }
+}
+
+/// 返回 (子组, 直接留在本层的 key)
+fn build(keys: &[&str], depth: usize, levels: &[GroupLevel]) -> (Vec<KeyGroup>, Vec<String>) {
+    let Some((level, rest)) = levels.split_first() else {
+        return (Vec::new(), keys.iter().map(|k| k.to_string()).collect());
+    };
+    let mut groups: Vec<(String, Vec<&str>)> = Vec::new();
+    let mut direct = Vec::new();
+    for &k in keys {
+        match prefix_end(k, depth, level) {
+            Some(end) => {
+                let name = &k[..end];
+                match groups.last_mut() {
+                    Some((n, members)) if n == name => members.push(k),
+                    _ => groups.push((name.to_string(), vec![k])),
+                }
+            }
+            None => direct.push(k.to_string()),
+        }
+    }
+
+    let mut out = Vec::new();
+    for (name, members) in groups {
+        let end = name.len();
+        let (children, keys) = build(&members, end, rest);
+        out.push(KeyGroup {
+            name,
+            count: members.len(),
+            children,
+            keys,
+        });
+    }
+    // 只有一个组、也没有散落的 key：这一层没有意义，直接用下一层
+    if out.len() == 1 && direct.is_empty() {
+        let only = out.remove(0);
+        return (only.children, only.keys);
+    }
+    (out, direct)
+}
+
+/// 在 depth 之后按 level 取前缀的结束位置；取不出比 depth 长、又比整个 key 短的前缀时为 None
+pub(crate) fn prefix_end(key: &str, depth: usize, level: &GroupLevel) -> Option<usize> {
+    let tail = key.get(depth..)?;
+    let len = match *level {
+        GroupLevel::Prefix(n) => tail.char_indices().nth(n).map(|(i, _)| i)?,
+        GroupLevel::Until(sep) => tail.find(sep)? + sep.len_utf8(),
+    };
+    (len > 0 && depth + len < key.len()).then_some(depth + len)
+}
@@ -0,0 +1,196 @@
+//! 哈希 key 格式（SA 用 CRC32、IV 用 Jenkins one-at-a-time）的 key 计算与名称字典。
+//!
+//! 这些格式的文件里只有哈希，没有 key 原名；用已知 key 名的字典反查，
+//! 查不到的显示成 `0xXXXXXXXX`，写回时按十六进制原样还原，哈希不会丢失。
+
+use std::collections::HashMap;
+
+/// key 的哈希算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum HashKind {
+    /// GTA SA：key 转大写后的 CRC32（初值 0xFFFFFFFF，不做最终取反）
+    Crc32,
+    /// GTA IV：key 转小写后的 Jenkins one-at-a-time
+    Jenkins,
+}
+
+impl HashKind {
+    pub fn hash(self, key: &str) -> u32 {
+        match self {
+            HashKind::Crc32 => crc32_key(key),
+            HashKind::Jenkins => jenkins_key(key),
+        }
+    }
+}
+
+const fn crc32_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xEDB8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+}
+
+static CRC32_TABLE: [u32; 256] = crc32_table();
+
+/// SA 的 key 哈希（游戏里的 CKeyGen::GetUppercaseKey）
+pub fn crc32_key(key: &str) -> u32 {
+    key.bytes().fold(0xFFFF_FFFF, |crc, b| {
+        let b = b.to_ascii_uppercase();
+        CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// 标准 CRC32（与 zip 相同），用来核对文件内容
+pub(crate) fn crc32_bytes(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(0xFFFF_FFFF, |crc, &b| {
+        CRC32_TABLE[((crc ^ b as u32) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+/// IV 的 key 哈希（joaat，输入先转小写）
+pub fn jenkins_key(key: &str) -> u32 {
+    let mut h: u32 = 0;
+    for b in key.bytes() {
+        h = h.wrapping_add(b.to_ascii_lowercase() as u32);
+        h = h.wrapping_add(h << 10);
+        h ^= h >> 6;
+    }
+    h = h.wrapping_add(h << 3);
+    h ^= h >> 11;
+    h.wrapping_add(h << 15)
+}
+
+/// 哈希 -> 已知 key 名
+#[derive(Debug, Clone)]
+pub struct HashDictionary {
+    kind: HashKind,
+    names: HashMap<u32, String>,
+}
+
+impl HashDictionary {
+    pub fn new(kind: HashKind) -> Self {
+        HashDictionary {
+            kind,
+            names: HashMap::new(),
+        }
+    }
+
+    pub fn kind(&self) -> HashKind {
+        self.kind
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// 加一个 key 名；返回它的哈希
+    pub fn insert(&mut self, name: &str) -> u32 {
+        let hash = self.kind.hash(name);
+        self.names.entry(hash).or_insert_with(|| name.to_string());
+        hash
+    }
+
+    /// 读字典文本，返回新增条数。每行一个 key 名；也接受 `0xHASH=NAME` / `HASH NAME`
+    /// （别的工具导出的字典常带哈希，此时以文件里的哈希为准）。空行和 `#` 注释行忽略。
+    pub fn load_text(&mut self, text: &str) -> usize {
+        let before = self.names.len();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let pair = line
+                .split_once('=')
+                .or_else(|| line.split_once(char::is_whitespace));
+            let with_hash = pair.and_then(|(h, n)| {
+                let h = h.trim();
+                let hash = parse_hex(h).or_else(|| parse_bare_hex(h))?;
+                Some((hash, n.trim()))
+            });
+            match with_hash {
+                Some((hash, name)) if !name.is_empty() => {
+                    self.names.entry(hash).or_insert_with(|| name.to_string());
+                }
+                _ => {
+                    self.insert(line);
+                }
+            }
+        }
+        self.names.len() - before
+    }
+
+    /// 显示用的 key：字典里有就是原名，否则是 `0xXXXXXXXX`
+    pub fn name_for(&self, hash: u32) -> String {
+        match self.names.get(&hash) {
+            Some(n) => n.clone(),
+            None => format_hash(hash),
+        }
+    }
+
+    /// name_for 的逆操作：`0x` 开头按十六进制原样取回，否则现算哈希
+    pub fn hash_for(&self, key: &str) -> u32 {
+        parse_hex(key).unwrap_or_else(|| self.kind.hash(key))
+    }
+}
+
+/// 几个不同的 key 名算出同一个哈希：转成哈希 key 格式后只剩一条，游戏里其它几条的文字会被覆盖
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HashCollision {
+    pub hash: u32,
+    /// 按出现顺序
+    pub keys: Vec<String>,
+}
+
+/// 按 kind 给 keys 算哈希（`0xXXXXXXXX` 形式的按原哈希），找出撞到一起的。
+/// 只差大小写的 key 也算（算法本身不分大小写），按哈希排序
+pub fn hash_collisions<'a>(
+    kind: HashKind,
+    keys: impl IntoIterator<Item = &'a str>,
+) -> Vec<HashCollision> {
+    let mut by_hash: HashMap<u32, Vec<String>> = HashMap::new();
+    for key in keys {
+        let hash = parse_hex(key).unwrap_or_else(|| kind.hash(key));
+        by_hash.entry(hash).or_default().push(key.to_string());
+    }
+    let mut out: Vec<HashCollision> = by_hash
+        .into_iter()
+        .filter(|(_, keys)| keys.len() > 1)
+        .map(|(hash, keys)| HashCollision { hash, keys })
+        .collect();
+    out.sort_by_key(|c| c.hash);
+    out
+}
+
+pub fn format_hash(hash: u32) -> String {
+    format!("0x{hash:08X}")
+}
+
+fn parse_hex(s: &str) -> Option<u32> {
+    parse_bare_hex(s.strip_prefix("0x").or_else(|| s.strip_prefix("0X"))?)
+}
+
+fn parse_bare_hex(hex: &str) -> Option<u32> {
+    if hex.len() != 8 {
+        return None;
+    }
+    u32::from_str_radix(hex, 16).ok()
+}
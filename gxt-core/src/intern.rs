@@ -0,0 +1,20 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// 相同的 value 文本只保留一份（GXT 里同一句台词经常重复出现几百次）。
+/// 共享后还能直接用 Arc::ptr_eq 判断两条 value 是否相同，查重时省去逐字比较。
+#[derive(Debug, Default)]
+pub struct Interner {
+    set: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, s: &str) -> Arc<str> {
+        if let Some(existing) = self.set.get(s) {
+            return existing.clone();
+        }
+        let arc: Arc<str> = Arc::from(s);
+        self.set.insert(arc.clone());
+        arc
+    }
+}
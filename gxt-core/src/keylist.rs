@@ -0,0 +1,90 @@
+//! 只有 key 名的清单，给写任务脚本的人在代码里引用文本用：
+//! 每行一个 key，或 Sanny Builder 能直接 `{$INCLUDE}` 的常量文件。
+
+use std::collections::HashSet;
+
+use crate::entry::GxtEntry;
+use crate::group::GroupLevel;
+use crate::tables::split_tables;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum KeyListFormat {
+    /// 每行一个 key；分组时每组前一行 `[表名]`
+    #[default]
+    Plain,
+    /// `const ... end` 块，每行 `名字 = 'KEY'`；分组时每组前一行 `// 表名` 注释
+    Constants,
+}
+
+/// 生成 key 清单。level 给了时按前缀分组（组按第一次出现的顺序，组内保持原顺序）；
+/// prefix 加在常量名前面（如 `TXT_`），避免与脚本里别的常量重名
+pub fn key_list(
+    entries: &[GxtEntry],
+    format: KeyListFormat,
+    level: Option<&GroupLevel>,
+    prefix: &str,
+) -> String {
+    let groups: Vec<(Option<String>, Vec<GxtEntry>)> = match level {
+        Some(level) => split_tables(entries, level)
+            .into_iter()
+            .map(|(name, entries)| (Some(name), entries))
+            .collect(),
+        None => vec![(None, entries.to_vec())],
+    };
+
+    let mut out = String::new();
+    match format {
+        KeyListFormat::Plain => {
+            for (i, (name, entries)) in groups.iter().enumerate() {
+                if let Some(name) = name {
+                    if i > 0 {
+                        out.push('\n');
+                    }
+                    out.push_str(&format!("[{name}]\n"));
+                }
+                for e in entries {
+                    out.push_str(&e.key);
+                    out.push('\n');
+                }
+            }
+        }
+        KeyListFormat::Constants => {
+            let mut used = HashSet::new();
+            out.push_str("const\n");
+            for (name, entries) in &groups {
+                if let Some(name) = name {
+                    out.push_str(&format!("// {name}\n"));
+                }
+                for e in entries {
+                    let constant = constant_name(prefix, &e.key, &mut used);
+                    // key 里有单引号时改用长字符串
+                    let quote = if e.key.contains('\'') { '"' } else { '\'' };
+                    out.push_str(&format!("{constant} = {quote}{}{quote}\n", e.key));
+                }
+            }
+            out.push_str("end\n");
+        }
+    }
+    out
+}
+
+/// key 转成合法的常量名：不是字母数字的换成 `_`，不能以数字开头；重名时加 `_2`、`_3`…
+fn constant_name(prefix: &str, key: &str, used: &mut HashSet<String>) -> String {
+    let mut name: String = prefix
+        .chars()
+        .chain(key.chars())
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    let mut unique = name.clone();
+    let mut n = 2;
+    while !used.insert(unique.to_ascii_uppercase()) {
+        unique = format!("{name}_{n}");
+        n += 1;
+    }
+    unique
+}
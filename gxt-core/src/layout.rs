@@ -0,0 +1,140 @@
+//! 建好的 GXT 的结构图：每个字段在文件里的偏移、长度以及属于哪个 key，
+//! 给用十六进制编辑器查引擎问题的人对照编辑器里的条目与原始字节。
+//! 可以输出成 JSON（serde），或 010 Editor 的模板（.bt）。
+
+use crate::format::{parse_key_table, KEY_RECORD_BYTES};
+
+/// 文件头：`TKEY` + 大小
+const TKEY_HEADER_BYTES: usize = 8;
+
+/// 字段的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum LayoutField {
+    /// `TKEY`
+    TkeyMagic,
+    /// TKEY 段的字节数
+    TkeySize,
+    /// key 记录里 value 在 TDAT 数据区里的 offset
+    KeyOffset,
+    /// key 记录里 8 字节的 key 名
+    KeyName,
+    /// `TDAT`
+    TdatMagic,
+    /// TDAT 数据区的字节数
+    TdatSize,
+    /// UTF-16 的 value，含结尾的 0
+    Value,
+}
+
+impl LayoutField {
+    fn label(self) -> &'static str {
+        match self {
+            LayoutField::TkeyMagic => "TKEY",
+            LayoutField::TkeySize => "TKEY size",
+            LayoutField::KeyOffset => "offset",
+            LayoutField::KeyName => "key",
+            LayoutField::TdatMagic => "TDAT",
+            LayoutField::TdatSize => "TDAT size",
+            LayoutField::Value => "value",
+        }
+    }
+}
+
+/// 一个字段；offset 从文件（嵌在 exe 里的为 GXT 段）开头算
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LayoutItem {
+    pub offset: usize,
+    pub length: usize,
+    pub field: LayoutField,
+    /// key 记录与 value 所属的 key
+    pub key: Option<String>,
+}
+
+/// 按文件里的先后列出所有字段；value 按 offset 排（几个 key 共用的 value 各列一次）
+pub fn gxt_layout(bytes: &[u8]) -> Result<Vec<LayoutItem>, String> {
+    let (keys, val_field) = parse_key_table(bytes)?;
+    let item = |offset, length, field, key: Option<&str>| LayoutItem {
+        offset,
+        length,
+        field,
+        key: key.map(str::to_string),
+    };
+    let mut out = vec![
+        item(0, 4, LayoutField::TkeyMagic, None),
+        item(4, 4, LayoutField::TkeySize, None),
+    ];
+    for (i, (key, _)) in keys.iter().enumerate() {
+        let at = TKEY_HEADER_BYTES + i * KEY_RECORD_BYTES;
+        out.push(item(at, 4, LayoutField::KeyOffset, Some(key)));
+        out.push(item(at + 4, 8, LayoutField::KeyName, Some(key)));
+    }
+    let tdat = TKEY_HEADER_BYTES + keys.len() * KEY_RECORD_BYTES;
+    out.push(item(tdat, 4, LayoutField::TdatMagic, None));
+    out.push(item(tdat + 4, 4, LayoutField::TdatSize, None));
+
+    let data = tdat + 8;
+    let mut values = Vec::with_capacity(keys.len());
+    for (key, idx) in &keys {
+        let start = *idx as usize;
+        let end = (start..val_field.len().saturating_sub(1))
+            .step_by(2)
+            .find(|&i| val_field[i] == 0 && val_field[i + 1] == 0)
+            .ok_or_else(|| format!("Unterminated value for key {key}: idx={idx}"))?;
+        values.push(item(
+            data + start,
+            end + 2 - start,
+            LayoutField::Value,
+            Some(key),
+        ));
+    }
+    values.sort_by_key(|v| v.offset);
+    out.extend(values);
+    Ok(out)
+}
+
+/// 010 Editor 的属性值里 `"` 与 `\` 要转义
+fn bt_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// 把结构图写成 010 Editor 模板：每个字段按 offset 定位声明一次，显示名是 key
+pub fn layout_template(items: &[LayoutItem], title: &str) -> String {
+    let mut out = format!(
+        "//------------------------------------------------\n\
+         //--- 010 Editor Binary Template\n\
+         //   File: {}\n\
+         //   Layout of a GXT (TKEY/TDAT) file, offsets from the start of the GXT\n\
+         //------------------------------------------------\n\
+         LittleEndian();\n\n",
+        title.replace('\n', " ")
+    );
+    for (i, it) in items.iter().enumerate() {
+        let label = it.field.label();
+        let name = match &it.key {
+            Some(key) => format!("{key} {label}"),
+            None => label.to_string(),
+        };
+        let decl = match it.field {
+            LayoutField::TkeyMagic | LayoutField::TdatMagic => format!("char f{i}[4]"),
+            LayoutField::TkeySize | LayoutField::TdatSize | LayoutField::KeyOffset => {
+                format!("uint32 f{i}")
+            }
+            LayoutField::KeyName => format!("char f{i}[8]"),
+            LayoutField::Value => format!("wchar_t f{i}[{}]", it.length / 2),
+        };
+        let format = if it.field == LayoutField::KeyOffset {
+            ", format=hex"
+        } else {
+            ""
+        };
+        out.push_str(&format!(
+            "FSeek({});\n{decl} <name=\"{}\"{format}>;\n",
+            it.offset,
+            bt_string(&name)
+        ));
+    }
+    out
+}
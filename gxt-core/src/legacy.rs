@@ -0,0 +1,288 @@
+//! 老社区工具导出的文本格式，让旧的翻译存档能直接进编辑器。
+//! 大多只能导入；社区补丁里最常见的 american.gxt.txt 样式也能导出（见 write_legacy）。
+//!
+//! 这些工具大多按游戏的单字节字符表输出，文件通常不是 UTF-8：
+//! 读入时非 UTF-8 的文件按 Latin-1 逐字节解释（与游戏字符表的码位一致）。
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+
+use crate::entry::GxtEntry;
+use crate::validate::validate_key;
+
+/// 支持的旧格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum LegacyFormat {
+    /// GXT Editor 1.3 的 txt 导出：
+    /// - `KEY = value`（等号两边可有空格），`[TABLE]` 表头与 `;` / `//` 注释行忽略
+    /// - value 可以整体用双引号包起来（保留首尾空格）
+    /// - 游戏字符表里的特殊字符写成 `{XX}`（十六进制码位），换行写成 `\n`
+    GxtEditor13,
+    /// gxt2txt 的输出：
+    /// - `[KEY]` 独占一行，之后到下一个 `[KEY]` / 空行为止都是 value，多行之间用 `~n~` 连接
+    /// - 非 ASCII 字符写成 `\xXX`，反斜杠本身写成 `\\`
+    Gxt2txt,
+    /// CLEO / Sanny Builder 的 FXT 自定义文本：
+    /// - 每行 `KEY 文字`，key 与文字之间用空白隔开，文字里的 `~n~` 等 token 原样保留
+    /// - 空行和 `//`、`#` 开头的注释行忽略
+    Fxt,
+    /// 社区补丁里流传的 `american.gxt.txt` 样式：
+    /// - `[TABLE]` 表头，之后每行 `KEY<Tab>文字`
+    /// - `;` 开头的注释行与空行忽略
+    /// - 同一个 key 出现多次时后面的为准（补丁文件常在后面追加修正）
+    Community,
+}
+
+/// 导出 Community 样式时用的表名（编辑器里的文档不分表）
+pub const COMMUNITY_TABLE: &str = "MAIN";
+
+/// 根据内容猜格式：出现 `KEY<Tab>` 的是社区 dump，先出现 `[KEY]` 后紧跟非 `=` 行的是 gxt2txt，
+/// 出现 `KEY=` 的是 GXT Editor 1.3
+pub fn detect_legacy_format(text: &str) -> Option<LegacyFormat> {
+    let mut saw_header = false;
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(';') || line.starts_with("//") {
+            continue;
+        }
+        if line.starts_with('[') && line.ends_with(']') {
+            saw_header = true;
+            continue;
+        }
+        if line
+            .split_once('\t')
+            .is_some_and(|(key, _)| validate_key(key).is_ok())
+        {
+            return Some(LegacyFormat::Community);
+        }
+        if line.contains('=') && !line.starts_with('=') {
+            return Some(LegacyFormat::GxtEditor13);
+        }
+        if saw_header {
+            return Some(LegacyFormat::Gxt2txt);
+        }
+        return None;
+    }
+    None
+}
+
+/// 文件字节 -> 文本：UTF-8（可带 BOM）原样用，否则按 Latin-1
+pub fn decode_legacy_bytes(bytes: &[u8]) -> String {
+    let bytes = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.to_string(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect(),
+    }
+}
+
+/// 解析旧格式；出错时带行号
+pub fn parse_legacy(text: &str, format: LegacyFormat) -> Result<Vec<GxtEntry>, String> {
+    match format {
+        LegacyFormat::GxtEditor13 => parse_gxt_editor13(text),
+        LegacyFormat::Gxt2txt => parse_gxt2txt(text),
+        LegacyFormat::Fxt => parse_fxt(text),
+        LegacyFormat::Community => parse_community(text),
+    }
+}
+
+/// 把条目写成旧格式；目前只有 Community 能导出
+pub fn write_legacy(entries: &[GxtEntry], format: LegacyFormat) -> Result<String, String> {
+    if format != LegacyFormat::Community {
+        return Err(format!("{format:?} can only be imported"));
+    }
+    let mut out = format!("[{COMMUNITY_TABLE}]\n");
+    for e in entries {
+        out.push_str(&e.key);
+        out.push('\t');
+        // 制表符与换行会打乱行结构，写成转义
+        for c in e.value.chars() {
+            match c {
+                '\t' | '\n' | '\r' => {
+                    let _ = write!(out, "\\u{{{:04X}}}", c as u32);
+                }
+                c => out.push(c),
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// 码位写成本库的 value 转义；0x80..=0x9F 必须用转义，其余直接是字符
+fn push_code(out: &mut String, code: u32) {
+    match char::from_u32(code) {
+        Some(c) if !(0x80..=0x9F).contains(&code) => out.push(c),
+        _ => {
+            let _ = write!(out, "\\u{{{code:04X}}}");
+        }
+    }
+}
+
+fn parse_gxt_editor13(text: &str) -> Result<Vec<GxtEntry>, String> {
+    let mut entries = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with(';')
+            || trimmed.starts_with("//")
+            || (trimmed.starts_with('[') && trimmed.ends_with(']'))
+        {
+            continue;
+        }
+        let (key, raw) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Line {}: missing '='", n + 1))?;
+        let key = key.trim();
+        validate_key(key).map_err(|e| format!("Line {}: {e}", n + 1))?;
+
+        let raw = raw.trim();
+        let raw = match raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+            Some(inner) => inner,
+            None => raw,
+        };
+
+        let mut value = String::with_capacity(raw.len());
+        let mut it = raw.char_indices().peekable();
+        while let Some((i, c)) = it.next() {
+            match c {
+                '{' => {
+                    let hex = raw[i + 1..].split_once('}').map_or("", |(h, _)| h);
+                    match u32::from_str_radix(hex, 16) {
+                        Ok(code) if hex.len() <= 4 => {
+                            push_code(&mut value, code);
+                            for _ in 0..=hex.len() {
+                                it.next();
+                            }
+                        }
+                        _ => value.push('{'),
+                    }
+                }
+                '\\' if it.peek().map(|&(_, c)| c) == Some('n') => {
+                    it.next();
+                    value.push_str("~n~");
+                }
+                c => value.push(c),
+            }
+        }
+        entries.push(GxtEntry {
+            key: key.to_string(),
+            value: value.into(),
+        });
+    }
+    Ok(entries)
+}
+
+fn gxt2txt_unescape(line: &str, n: usize) -> Result<String, String> {
+    let mut out = String::with_capacity(line.len());
+    let mut it = line.chars();
+    while let Some(c) = it.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match it.next() {
+            Some('\\') => out.push_str("\\u{005C}"),
+            Some('x') => {
+                let hex: String = it.by_ref().take(2).collect();
+                let code = u32::from_str_radix(&hex, 16)
+                    .map_err(|_| format!("Line {}: bad escape \\x{hex}", n + 1))?;
+                push_code(&mut out, code);
+            }
+            Some(other) => {
+                return Err(format!("Line {}: unknown escape \\{other}", n + 1));
+            }
+            None => out.push_str("\\u{005C}"),
+        }
+    }
+    Ok(out)
+}
+
+fn parse_gxt2txt(text: &str) -> Result<Vec<GxtEntry>, String> {
+    let mut entries: Vec<GxtEntry> = Vec::new();
+    let mut current: Option<(String, Vec<String>)> = None;
+
+    let mut flush = |cur: &mut Option<(String, Vec<String>)>| {
+        if let Some((key, lines)) = cur.take() {
+            entries.push(GxtEntry {
+                key,
+                value: lines.join("~n~").into(),
+            });
+        }
+    };
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if let Some(key) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            flush(&mut current);
+            validate_key(key).map_err(|e| format!("Line {}: {e}", n + 1))?;
+            current = Some((key.to_string(), Vec::new()));
+            continue;
+        }
+        if line.trim().is_empty() {
+            flush(&mut current);
+            continue;
+        }
+        match current.as_mut() {
+            Some((_, lines)) => lines.push(gxt2txt_unescape(line, n)?),
+            None => return Err(format!("Line {}: text before the first [KEY]", n + 1)),
+        }
+    }
+    flush(&mut current);
+    Ok(entries)
+}
+
+fn parse_fxt(text: &str) -> Result<Vec<GxtEntry>, String> {
+    let mut entries = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r').trim_start();
+        if line.is_empty() || line.starts_with("//") || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(k, v)| (k, v.trim_start()));
+        validate_key(key).map_err(|e| format!("Line {}: {e}", n + 1))?;
+        entries.push(GxtEntry {
+            key: key.to_string(),
+            value: value.into(),
+        });
+    }
+    Ok(entries)
+}
+
+fn parse_community(text: &str) -> Result<Vec<GxtEntry>, String> {
+    let mut entries: Vec<GxtEntry> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        let trimmed = line.trim();
+        if trimmed.is_empty()
+            || trimmed.starts_with(';')
+            || (trimmed.starts_with('[') && trimmed.ends_with(']'))
+        {
+            continue;
+        }
+        let (key, raw) = line
+            .trim_start()
+            .split_once('\t')
+            .ok_or_else(|| format!("Line {}: missing tab between KEY and text", n + 1))?;
+        validate_key(key).map_err(|e| format!("Line {}: {e}", n + 1))?;
+        let mut value = String::with_capacity(raw.len());
+        for c in raw.chars() {
+            push_code(&mut value, c as u32);
+        }
+        match index.get(key) {
+            Some(&i) => entries[i].value = value.into(),
+            None => {
+                index.insert(key.to_string(), entries.len());
+                entries.push(GxtEntry {
+                    key: key.to_string(),
+                    value: value.into(),
+                });
+            }
+        }
+    }
+    Ok(entries)
+}
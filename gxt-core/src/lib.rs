@@ -0,0 +1,107 @@
+//! Rockstar `.gxt`（TKEY/TDAT 单表格式）的解析与生成。
+//!
+//! 不依赖 Tauri，编辑器、命令行工具以及其它 Rust 程序都可以直接用：
+//!
+//! ```no_run
+//! let bytes = std::fs::read("american.gxt").unwrap();
+//! let entries = gxt_core::parse_gxt_bytes(&bytes).unwrap();
+//! let rebuilt = gxt_core::build_gxt_bytes(&entries).unwrap();
+//! # let _ = rebuilt;
+//! ```
+//!
+//! value 中无法直接用文本表示的 UTF-16 单元（0x80..=0x9F 特殊区间、不成对 surrogate）
+//! 会以 `\u{XXXX}` 形式出现，写回时还原为原始单元；见 [`escape`]。
+//!
+//! 所有错误都以 `String` 描述返回，可直接展示给用户。
+//! 启用 `serde` feature 后 [`GxtEntry`] 实现 Serialize/Deserialize。
+
+pub mod args;
+pub mod astral;
+pub mod cache;
+pub mod charset;
+pub mod codepage;
+pub mod delta;
+pub mod diff;
+pub mod embed;
+pub mod entry;
+pub mod escape;
+pub mod format;
+pub mod group;
+pub mod hash;
+pub mod intern;
+pub mod keylist;
+pub mod layout;
+pub mod legacy;
+pub mod limits;
+pub mod metrics;
+pub mod numbers;
+pub mod preset;
+pub mod preview;
+pub mod scm;
+pub mod selftest;
+pub mod sniff;
+pub mod space;
+pub mod tables;
+pub mod text;
+pub mod tokens;
+pub mod translit;
+pub mod update;
+pub mod usage;
+pub mod validate;
+pub mod wrap;
+
+pub use args::{ArgRule, ArgRules, ArgTokens, ArgType, ArgViolation};
+pub use astral::{astral_chars, astral_fallback, substitute_astral, AstralPolicy};
+pub use cache::ValueCache;
+pub use charset::{char_ranges, render_charset, used_chars, CharsetFormat};
+pub use codepage::Codepage;
+pub use delta::{apply_delta, delta_info, make_delta, DeltaInfo, MAGIC_DELTA};
+pub use diff::{diff_entries, ChangedEntry, EntryDiff};
+pub use embed::{find_section, section_at, splice_section, EmbeddedSection};
+pub use entry::GxtEntry;
+pub use escape::{
+    decode_utf16z_with_escapes, encode_utf16z_with_escapes, encoded_utf16z_len, lint_escapes,
+    percent_decode, EscapeIssue,
+};
+pub use format::{
+    build_gxt_bytes, build_gxt_bytes_aliased, build_gxt_bytes_cached, build_gxt_bytes_shared,
+    build_gxt_bytes_with, check_tdat_size, decode_values, parse_gxt_bytes, parse_gxt_bytes_checked,
+    parse_key_table, KeyTable, LoadWarning, KEY_RECORD_BYTES, MAGIC_TDAT, MAGIC_TKEY,
+};
+pub use group::{group_keys, GroupLevel, KeyGroup};
+pub use hash::{
+    crc32_key, format_hash, hash_collisions, jenkins_key, HashCollision, HashDictionary, HashKind,
+};
+pub use intern::Interner;
+pub use keylist::{key_list, KeyListFormat};
+pub use layout::{gxt_layout, layout_template, LayoutField, LayoutItem};
+pub use legacy::{
+    decode_legacy_bytes, detect_legacy_format, parse_legacy, write_legacy, LegacyFormat,
+    COMMUNITY_TABLE,
+};
+pub use limits::{matches_pattern, LengthRule, LengthRules, LengthViolation};
+pub use metrics::{render_metrics, visible_part, MetricsOptions, RenderMetrics, Truncation};
+pub use numbers::{NumberRules, UnitSystem};
+pub use preset::{
+    CharRange, FontDef, GamePreset, GlyphWidth, KeyBindingDef, KeyCase, KeyRules, ScreenDef,
+    SkeletonEntry, TokenDef, TokenKind, TokenSet,
+};
+pub use preview::{preview, PreviewSegment};
+pub use scm::{
+    key_label, rename_in_source, scan_compiled, scan_decompiled, scan_script, scan_source, KeyRef,
+    RefAt, ScriptContext,
+};
+pub use selftest::{run_selftest, SelftestCase, SelftestReport};
+pub use sniff::{sniff_text_format, TextFormat};
+pub use space::{
+    analyze_space, compact_gxt_bytes, find_overlaps, repair_overlaps, OverlapRepair,
+    OverlappingKey, SpaceReport,
+};
+pub use tables::{join_tables, split_tables, MISC_TABLE};
+pub use text::{entries_from_text, entries_to_text};
+pub use tokens::{newlines_to_token, segments, token_to_newlines, tokens, Segment};
+pub use translit::Transliteration;
+pub use update::{update_translation, SourceUpdate};
+pub use usage::{parse_display_log, DisplayLog, KeyUsage};
+pub use validate::{sort_entries, unsorted_keys, validate_entries, validate_key};
+pub use wrap::{line_widths, wrap_value, wrap_value_with, BreakRules};
@@ -0,0 +1,160 @@
+//! 按 key 模式的长度限制：不同的界面元素截断的位置不同（如拾取物名比字幕短得多），
+//! 项目在规则文件里按 key 分类给出最多多少字、多宽、几行、编码后多少字节。
+
+use crate::escape::encoded_utf16z_len;
+use crate::preset::GamePreset;
+use crate::tokens::{segments, Segment};
+use crate::wrap::line_widths;
+
+const LINE_BREAK: &str = "~n~";
+
+/// 一条规则；没给的限制不检查
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LengthRule {
+    /// key 模式：`*` 匹配任意多个字符，`?` 匹配一个；不区分大小写
+    pub pattern: String,
+    /// 给人看的名字，如“拾取物名”
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub name: String,
+    /// 显示出来的字符数（token 不算，转义算一个）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_chars: Option<usize>,
+    /// 每行的宽度（按预设字体的字宽）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_width: Option<u32>,
+    /// 量宽度用的预设字体编号；缺省用预设的第一个字体
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub font: Option<u32>,
+    /// 行数（按 `~n~` 分）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_lines: Option<usize>,
+    /// 编码后的字节数（UTF-16，含 0 结尾）：脚本把文字复制进固定大小缓冲区的地方
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_bytes: Option<usize>,
+}
+
+/// 规则文件：若干 `[[rules]]`，按顺序取第一条匹配的
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LengthRules {
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub rules: Vec<LengthRule>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "kind", rename_all = "lowercase"))]
+pub enum LengthViolation {
+    Chars {
+        len: usize,
+        max: usize,
+    },
+    /// line 从 1 开始
+    Width {
+        line: usize,
+        width: u32,
+        max: u32,
+    },
+    Lines {
+        lines: usize,
+        max: usize,
+    },
+    Bytes {
+        bytes: usize,
+        max: usize,
+    },
+}
+
+impl LengthRules {
+    pub fn rule_for(&self, key: &str) -> Option<&LengthRule> {
+        self.rules.iter().find(|r| matches_pattern(&r.pattern, key))
+    }
+}
+
+impl LengthRule {
+    /// 检查 value；没给预设（或预设里没有对应字体）时不查宽度
+    pub fn check(&self, value: &str, preset: Option<&GamePreset>) -> Vec<LengthViolation> {
+        let mut out = Vec::new();
+        if let Some(max) = self.max_chars {
+            let len = display_len(value);
+            if len > max {
+                out.push(LengthViolation::Chars { len, max });
+            }
+        }
+        if let Some(max) = self.max_lines {
+            let lines = segments(value)
+                .iter()
+                .filter(|s| matches!(s, Segment::Token(t) if t.eq_ignore_ascii_case(LINE_BREAK)))
+                .count()
+                + 1;
+            if lines > max {
+                out.push(LengthViolation::Lines { lines, max });
+            }
+        }
+        if let Some(max) = self.max_bytes {
+            // 转义写错时编码不了，由转义检查报告
+            if let Ok(bytes) = encoded_utf16z_len(value) {
+                if bytes > max {
+                    out.push(LengthViolation::Bytes { bytes, max });
+                }
+            }
+        }
+        let font = preset.and_then(|p| match self.font {
+            Some(id) => p.fonts.iter().find(|f| f.id == id),
+            None => p.fonts.first(),
+        });
+        if let (Some(max), Some(font), Some(preset)) = (self.max_width, font, preset) {
+            for (i, width) in line_widths(value, font, &preset.tokens)
+                .into_iter()
+                .enumerate()
+            {
+                if width > max {
+                    out.push(LengthViolation::Width {
+                        line: i + 1,
+                        width,
+                        max,
+                    });
+                }
+            }
+        }
+        out
+    }
+}
+
+/// 显示出来的字符数：token 不算，转义算一个
+fn display_len(value: &str) -> usize {
+    segments(value)
+        .iter()
+        .map(|s| match s {
+            Segment::Text(t) => t.chars().count(),
+            Segment::Token(_) => 0,
+            Segment::Escape(_) => 1,
+        })
+        .sum()
+}
+
+/// `*` / `?` 通配，不区分大小写
+pub fn matches_pattern(pattern: &str, key: &str) -> bool {
+    let p: Vec<char> = pattern.chars().map(|c| c.to_ascii_uppercase()).collect();
+    let k: Vec<char> = key.chars().map(|c| c.to_ascii_uppercase()).collect();
+    // 回溯到上一个 `*`
+    let (mut pi, mut ki) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while ki < k.len() {
+        if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, ki));
+            pi += 1;
+        } else if pi < p.len() && (p[pi] == '?' || p[pi] == k[ki]) {
+            pi += 1;
+            ki += 1;
+        } else if let Some((sp, sk)) = star {
+            pi = sp + 1;
+            ki = sk + 1;
+            star = Some((sp, sk + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
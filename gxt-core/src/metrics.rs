@@ -0,0 +1,234 @@
+//! 估算一条文本在游戏里显示出来有多大：每行多宽、几行、自动换行后占几行、
+//! 换算到某个分辨率是多少像素，以及放不放得下。编辑时给“放得下 / 超出”的提示用。
+
+use crate::preset::{token_width, FontDef, GamePreset, TokenDef};
+use crate::preview::unescape;
+use crate::tokens::{segments, Segment};
+use crate::wrap::{line_widths, wrap_value};
+
+const LINE_BREAK: &str = "~n~";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MetricsOptions {
+    /// 文本框宽度（单位同字宽）；给了时按它估算游戏自动换行后的行数
+    pub box_width: Option<u32>,
+    /// 文本框最多显示几行
+    pub max_rows: Option<usize>,
+    /// 实际分辨率 `(宽, 高)`；给了时换算出像素
+    pub resolution: Option<(u32, u32)>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenderMetrics {
+    /// 每行（按 `~n~` 分）的宽度，单位同字宽
+    pub line_widths: Vec<u32>,
+    /// 按 `~n~` 分的行数
+    pub lines: usize,
+    /// 按 box_width 自动换行后的行数（没给 box_width 时等于 lines）
+    pub rows: usize,
+    /// 换行后最宽一行的宽度
+    pub width: u32,
+    /// 换算到 resolution 的宽、高（像素）；没给 resolution 或字体没有行高时为 None
+    pub pixel_width: Option<u32>,
+    pub pixel_height: Option<u32>,
+    /// 有行比文本框宽（单词本身就放不下），或行数超过 max_rows
+    pub overflows: bool,
+}
+
+pub fn render_metrics(
+    value: &str,
+    font: &FontDef,
+    preset: &GamePreset,
+    options: &MetricsOptions,
+) -> RenderMetrics {
+    let widths = line_widths(value, font, &preset.tokens);
+    let rows_widths = match options.box_width {
+        Some(max) => line_widths(
+            &wrap_value(value, font, &preset.tokens, max, true),
+            font,
+            &preset.tokens,
+        ),
+        None => widths.clone(),
+    };
+    let width = rows_widths.iter().copied().max().unwrap_or(0);
+    let rows = rows_widths.len();
+    let overflows = options.box_width.is_some_and(|max| width > max)
+        || options.max_rows.is_some_and(|max| rows > max);
+
+    let screen = preset.screen;
+    let (pixel_width, pixel_height) = match options.resolution {
+        Some((w, h)) if screen.width > 0 && screen.height > 0 => (
+            Some(scale(width, w, screen.width)),
+            (font.line_height > 0).then(|| scale(rows as u32 * font.line_height, h, screen.height)),
+        ),
+        _ => (None, None),
+    };
+
+    RenderMetrics {
+        lines: widths.len(),
+        line_widths: widths,
+        rows,
+        width,
+        pixel_width,
+        pixel_height,
+        overflows,
+    }
+}
+
+fn scale(v: u32, to: u32, from: u32) -> u32 {
+    (u64::from(v) * u64::from(to) / u64::from(from)) as u32
+}
+
+/// 游戏实际显示出来的部分（见 visible_part）
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Truncation {
+    /// 显示出来的文字；自动换行处换成了 `~n~`，分行与游戏里看到的一致
+    pub shown: String,
+    /// value 里从这个字节位置起的文字显示不出来；全部显示得下时为 None
+    pub cut_at: Option<usize>,
+    /// 显示出来的行数
+    pub rows: usize,
+}
+
+/// 游戏显示 value 时实际能看到的部分。wrap 为 true 时按 box_width 自动换行，
+/// 超过 max_rows 的行看不到；为 false 时（单行的 HUD 文字等）每行超出 box_width 的部分被截掉
+pub fn visible_part(
+    value: &str,
+    font: &FontDef,
+    preset: &GamePreset,
+    options: &MetricsOptions,
+    wrap: bool,
+) -> Truncation {
+    visible_part_with(value, font, &preset.tokens, options, wrap)
+}
+
+pub(crate) fn visible_part_with(
+    value: &str,
+    font: &FontDef,
+    tokens: &[TokenDef],
+    options: &MetricsOptions,
+    wrap: bool,
+) -> Truncation {
+    let laid_out = match options.box_width {
+        Some(max) if wrap => wrap_value(value, font, tokens, max, true),
+        _ => value.to_string(),
+    };
+    let mut rows = split_rows(&laid_out);
+    let hidden_rows = options.max_rows.is_some_and(|max| rows.len() > max);
+    if let Some(max) = options.max_rows {
+        rows.truncate(max);
+    }
+    // 第一处看不到的文字前面有几个单位（见 units）
+    let mut kept = 0;
+    let mut first_lost = None;
+    for row in &mut rows {
+        if let (Some(max), false) = (options.box_width, wrap) {
+            let clipped = clip_row(row, font, tokens, max);
+            if first_lost.is_none() && units(&clipped) < units(row) {
+                first_lost = Some(kept + units(&clipped));
+            }
+            *row = clipped;
+        }
+        kept += units(row);
+    }
+    if hidden_rows && first_lost.is_none() {
+        first_lost = Some(kept);
+    }
+    let shown = rows.join(LINE_BREAK);
+    let cut_at = first_lost.and_then(|n| nth_unit_offset(value, n));
+    Truncation {
+        shown,
+        cut_at,
+        rows: rows.len(),
+    }
+}
+
+/// 按 `~n~` 分行（不分大小写）
+fn split_rows(value: &str) -> Vec<String> {
+    let mut rows = vec![String::new()];
+    for seg in segments(value) {
+        match seg {
+            Segment::Token(t) if t.eq_ignore_ascii_case(LINE_BREAK) => rows.push(String::new()),
+            Segment::Token(s) | Segment::Text(s) | Segment::Escape(s) => {
+                rows.last_mut().unwrap().push_str(s)
+            }
+        }
+    }
+    rows
+}
+
+/// 一行里放得进 max_width 的前缀
+fn clip_row(row: &str, font: &FontDef, tokens: &[TokenDef], max_width: u32) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for seg in segments(row) {
+        match seg {
+            Segment::Text(t) => {
+                for c in t.chars() {
+                    width += font.char_width(c);
+                    if width > max_width {
+                        return out;
+                    }
+                    out.push(c);
+                }
+            }
+            Segment::Token(t) | Segment::Escape(t) => {
+                width += match seg {
+                    Segment::Escape(e) => unescape(e).chars().map(|c| font.char_width(c)).sum(),
+                    _ => token_width(tokens, t),
+                };
+                if width > max_width {
+                    return out;
+                }
+                out.push_str(t);
+            }
+        }
+    }
+    out
+}
+
+/// 自动换行只动空白与 `~n~`：其余的字符、转义、token 逐个对得上，按它们数出截断位置
+fn units(value: &str) -> usize {
+    segments(value)
+        .iter()
+        .map(|seg| match seg {
+            Segment::Text(t) => t.chars().filter(|c| !c.is_whitespace()).count(),
+            Segment::Token(t) if t.eq_ignore_ascii_case(LINE_BREAK) => 0,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// 第 n 个（从 0 数）单位在 value 里的字节位置；不到 n+1 个时为 None
+fn nth_unit_offset(value: &str, n: usize) -> Option<usize> {
+    let base = value.as_ptr() as usize;
+    let mut seen = 0;
+    for seg in segments(value) {
+        match seg {
+            Segment::Text(t) => {
+                let start = t.as_ptr() as usize - base;
+                for (i, c) in t.char_indices() {
+                    if c.is_whitespace() {
+                        continue;
+                    }
+                    if seen == n {
+                        return Some(start + i);
+                    }
+                    seen += 1;
+                }
+            }
+            Segment::Token(t) if t.eq_ignore_ascii_case(LINE_BREAK) => {}
+            Segment::Token(t) | Segment::Escape(t) => {
+                if seen == n {
+                    return Some(t.as_ptr() as usize - base);
+                }
+                seen += 1;
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,262 @@
+//! 数字与单位的本地化：把原文（英文写法：`.` 小数点、`,` 千位分隔）里的数字按目标语言的
+//! 分隔符改写，紧跟单位的英制/公制数值（mph↔km/h、ft↔m）按需换算。
+//! 只改文字部分，token 与转义原样保留。
+
+use crate::tokens::{segments, Segment};
+
+const MPH_TO_KMH: f64 = 1.609344;
+const FT_TO_M: f64 = 0.3048;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum UnitSystem {
+    /// 不换算
+    #[default]
+    Keep,
+    /// mph -> km/h，ft -> m
+    Metric,
+    /// km/h -> mph，m -> ft
+    Imperial,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct NumberRules {
+    /// 小数点
+    pub decimal: char,
+    /// 千位分隔符（只用于原文里本来就分了组的数）；None 表示去掉分组
+    pub group: Option<char>,
+    pub units: UnitSystem,
+}
+
+impl Default for NumberRules {
+    fn default() -> Self {
+        NumberRules {
+            decimal: '.',
+            group: Some(','),
+            units: UnitSystem::Keep,
+        }
+    }
+}
+
+/// 单位：写法、属于哪一制、换算成另一制后的写法与系数
+struct Unit {
+    names: &'static [&'static str],
+    metric: bool,
+    to: &'static str,
+    factor: f64,
+}
+
+/// 先列长的，避免 `m` 抢先匹配 `meters`
+const UNITS: [Unit; 4] = [
+    Unit {
+        names: &["mph"],
+        metric: false,
+        to: "km/h",
+        factor: MPH_TO_KMH,
+    },
+    Unit {
+        names: &["km/h"],
+        metric: true,
+        to: "mph",
+        factor: 1.0 / MPH_TO_KMH,
+    },
+    Unit {
+        names: &["feet", "foot", "ft"],
+        metric: false,
+        to: "m",
+        factor: FT_TO_M,
+    },
+    Unit {
+        names: &["meters", "metres", "meter", "metre", "m"],
+        metric: true,
+        to: "ft",
+        factor: 1.0 / FT_TO_M,
+    },
+];
+
+impl NumberRules {
+    /// 常见语言的规则（按语言代码的主标签，如 `de`、`ru-RU`）；不认识的返回 None
+    pub fn for_locale(code: &str) -> Option<NumberRules> {
+        let lang = code
+            .split(['-', '_'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let (decimal, group, units) = match lang.as_str() {
+            "en" => ('.', ',', UnitSystem::Keep),
+            "de" | "es" | "it" | "pt" | "nl" | "tr" | "id" | "da" | "el" | "ro" => {
+                (',', '.', UnitSystem::Metric)
+            }
+            "fr" | "ru" | "uk" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "hu" | "bg" => {
+                (',', ' ', UnitSystem::Metric)
+            }
+            "zh" | "ja" | "ko" => ('.', ',', UnitSystem::Metric),
+            _ => return None,
+        };
+        Some(NumberRules {
+            decimal,
+            group: Some(group),
+            units,
+        })
+    }
+
+    /// 改写一条 value；没有变化时返回 None
+    pub fn apply(&self, value: &str) -> Option<String> {
+        let mut out = String::with_capacity(value.len());
+        for seg in segments(value) {
+            match seg {
+                Segment::Text(t) => self.apply_text(t, &mut out),
+                other => out.push_str(other.as_str()),
+            }
+        }
+        (out != value).then_some(out)
+    }
+
+    fn apply_text(&self, text: &str, out: &mut String) {
+        let b = text.as_bytes();
+        let mut i = 0;
+        let mut copied = 0;
+        while i < b.len() {
+            let starts_number =
+                b[i].is_ascii_digit() && (i == 0 || (!is_word_byte(b[i - 1]) && b[i - 1] != b'.'));
+            if !starts_number {
+                i += 1;
+                continue;
+            }
+            let Some(num) = parse_number(b, i) else {
+                // 版本号之类，整段跳过
+                while i < b.len() && (b[i].is_ascii_digit() || b[i] == b'.' || b[i] == b',') {
+                    i += 1;
+                }
+                continue;
+            };
+            out.push_str(&text[copied..i]);
+            let mut end = num.end;
+            let mut digits = num.value;
+            let mut unit_text = None;
+            if let Some((unit, space, unit_end)) = self.unit_after(text, num.end) {
+                digits *= unit.factor;
+                unit_text = Some((space, unit.to));
+                end = unit_end;
+            }
+            out.push_str(&self.format(digits, num.decimals, num.grouped));
+            if let Some((space, to)) = unit_text {
+                out.push_str(space);
+                out.push_str(to);
+            }
+            i = end;
+            copied = end;
+        }
+        out.push_str(&text[copied..]);
+    }
+
+    /// at 处（数字之后）要换算的单位：返回单位、数字与单位之间的空白、单位结束的位置
+    fn unit_after<'a>(&self, text: &'a str, at: usize) -> Option<(&'static Unit, &'a str, usize)> {
+        let want_metric = match self.units {
+            UnitSystem::Keep => return None,
+            UnitSystem::Metric => false,
+            UnitSystem::Imperial => true,
+        };
+        let rest = &text[at..];
+        let space_len = if rest.starts_with(' ') { 1 } else { 0 };
+        let word = &rest[space_len..];
+        for unit in UNITS.iter().filter(|u| u.metric == want_metric) {
+            for name in unit.names {
+                let Some(head) = word.get(..name.len()) else {
+                    continue;
+                };
+                let whole = word
+                    .as_bytes()
+                    .get(name.len())
+                    .is_none_or(|&c| !is_word_byte(c));
+                if head.eq_ignore_ascii_case(name) && whole {
+                    return Some((unit, &rest[..space_len], at + space_len + name.len()));
+                }
+            }
+        }
+        None
+    }
+
+    fn format(&self, value: f64, decimals: usize, grouped: bool) -> String {
+        let s = format!("{value:.decimals$}");
+        let (int, frac) = match s.split_once('.') {
+            Some((i, f)) => (i, Some(f)),
+            None => (s.as_str(), None),
+        };
+        let mut out = String::new();
+        match self.group.filter(|_| grouped) {
+            Some(sep) => {
+                for (n, c) in int.chars().enumerate() {
+                    if n > 0 && (int.len() - n) % 3 == 0 {
+                        out.push(sep);
+                    }
+                    out.push(c);
+                }
+            }
+            None => out.push_str(int),
+        }
+        if let Some(f) = frac {
+            out.push(self.decimal);
+            out.push_str(f);
+        }
+        out
+    }
+}
+
+struct Number {
+    value: f64,
+    /// 小数位数
+    decimals: usize,
+    /// 原文按千位分了组
+    grouped: bool,
+    end: usize,
+}
+
+/// 英文写法的数：`1234`、`1,234,567`、`12.5`、`1,234.5`。后面紧跟 `.数字`（版本号、IP）时返回 None
+fn parse_number(b: &[u8], start: usize) -> Option<Number> {
+    let digits = |mut i: usize| {
+        while i < b.len() && b[i].is_ascii_digit() {
+            i += 1;
+        }
+        i
+    };
+    let mut i = digits(start);
+    let mut int: String = std::str::from_utf8(&b[start..i]).ok()?.to_string();
+    let mut grouped = false;
+    // 分组：`,` 后恰好三位数字；第一组最多三位
+    while int.len() <= 3 || grouped {
+        let group_end = digits(i + 1);
+        if i < b.len() && b[i] == b',' && group_end == i + 4 {
+            int.push_str(std::str::from_utf8(&b[i + 1..group_end]).ok()?);
+            grouped = true;
+            i = group_end;
+        } else {
+            break;
+        }
+    }
+    let mut decimals = 0;
+    let mut text = int;
+    if i + 1 < b.len() && b[i] == b'.' && b[i + 1].is_ascii_digit() {
+        let frac_end = digits(i + 1);
+        text.push('.');
+        text.push_str(std::str::from_utf8(&b[i + 1..frac_end]).ok()?);
+        decimals = frac_end - i - 1;
+        i = frac_end;
+        if i + 1 < b.len() && b[i] == b'.' && b[i + 1].is_ascii_digit() {
+            return None;
+        }
+    }
+    Some(Number {
+        value: text.parse().ok()?,
+        decimals,
+        grouped,
+        end: i,
+    })
+}
+
+fn is_word_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_'
+}
@@ -0,0 +1,443 @@
+//! 游戏预设：各游戏（及大型 mod）不同的 token、允许字符、key 规则、字体等常量。
+//!
+//! 这里只定义数据结构与基于它的检查；预设本身以文件形式分发（编辑器内置 TOML，
+//! 用户可复制修改后给全面转换类 mod 使用）。
+
+use crate::astral::AstralPolicy;
+use crate::escape::encoded_utf16z_len;
+use crate::tokens::{segments, Segment};
+
+/// 按键提示的前缀：`~k~~PED_SPRINT~`
+pub const KEY_PREFIX: &str = "~k~";
+
+/// 一个游戏（或 mod）的预设
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GamePreset {
+    /// 唯一 id（如 `gta3`、`vc`、`sa`）；用户预设与内置预设同 id 时覆盖内置的
+    pub id: String,
+    pub name: String,
+    /// 游戏认识的 token；为空表示不检查
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tokens: Vec<TokenDef>,
+    /// 字体里有字形的字符；为空表示不限制
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub allowed_chars: Vec<CharRange>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub key_rules: KeyRules,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub fonts: Vec<FontDef>,
+    /// 有特殊含义、只能以 `\u{XXXX}` 形式编辑的 UTF-16 区间
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub special_ranges: Vec<CharRange>,
+    /// `~k~~ACTION~` 里游戏认识的按键动作及其默认按键；为空表示不检查
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub key_bindings: Vec<KeyBindingDef>,
+    /// 引擎用二分查找在 TKEY 里找 key：key 必须按字节序排列，否则找不到（游戏里不报错，只是缺字）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub sorted_keys: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub screen: ScreenDef,
+    /// 与 CSV / JSON / PO 等外部格式互转时，换行对应的 token（如 `~n~`）：导入时换行换成它，
+    /// 导出时它换成换行。为空则换行原样保留
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub newline_token: Option<String>,
+    /// 引擎把 value 复制进固定大小的缓冲区时，编码后（UTF-16，含 0 结尾）最多多少字节；
+    /// 超过的在游戏里被截断甚至崩溃。为空表示不检查
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_value_bytes: Option<usize>,
+    /// U+FFFF 以上的字符（emoji 等）怎么处理：字体画不出来的游戏设成 warn 或 substitute
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub astral: AstralPolicy,
+    /// 新建文档时预先放进去的条目：游戏启动时必须读到的 key（缺了会崩溃或显示空白菜单）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub skeleton: Vec<SkeletonEntry>,
+}
+
+/// 新建文档时的一条；value 是占位的原文，等译者改
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SkeletonEntry {
+    pub key: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TokenDef {
+    /// 含两侧的 `~`，如 `~r~`；不分大小写
+    pub token: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: String,
+    /// 颜色 token 的显示色（`#RRGGBB`）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub color: Option<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub kind: TokenKind,
+    /// 后面紧跟的参数 token 个数（如 `~k~` 后跟 `~PED_SPRINT~` 为 1）；参数不当作 token 检查
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub args: usize,
+    /// 在游戏里占的宽度（按字体的单位，图标或展开后的数字）；0 表示不占宽度
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub width: u32,
+}
+
+/// token 的类别；给了 color 的按颜色处理，与 kind 无关
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum TokenKind {
+    /// 换行、高亮等排版控制
+    #[default]
+    Format,
+    Color,
+    /// 运行时替换成数字或文字，如 `~1~`
+    Placeholder,
+    /// 显示成图标
+    Glyph,
+}
+
+/// 项目里的自定义 token 定义（全面转换 mod 加了或改了 token 时用），叠加到游戏预设上
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct TokenSet {
+    /// 为 true 时完全替换预设的 token；否则同名的覆盖、其余追加
+    pub replace: bool,
+    pub tokens: Vec<TokenDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyBindingDef {
+    /// 不含 `~`，如 `PED_SPRINT`；不分大小写
+    pub action: String,
+    /// 默认键位下显示的按键名，如 `SPACE`
+    pub label: String,
+}
+
+/// 闭区间 [start, end] 的码点
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CharRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl CharRange {
+    pub fn contains(&self, c: char) -> bool {
+        (self.start..=self.end).contains(&(c as u32))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KeyRules {
+    pub min_len: usize,
+    pub max_len: usize,
+    /// 只允许 A-Z / 0-9 / `_`（原版文件的习惯；默认只要求可见 ASCII）
+    pub strict_charset: bool,
+    /// key 里字母的大小写要求
+    pub case: KeyCase,
+    /// 导入外部格式时把 key 转成 case 要求的大小写，而不是留给检查报告
+    pub normalize_on_import: bool,
+}
+
+impl Default for KeyRules {
+    fn default() -> Self {
+        KeyRules {
+            min_len: 1,
+            max_len: 8,
+            strict_charset: false,
+            case: KeyCase::Any,
+            normalize_on_import: false,
+        }
+    }
+}
+
+impl KeyRules {
+    /// 按 case 要求转换后的 key；normalize_on_import 没开时原样返回
+    pub fn import_key(&self, key: &str) -> String {
+        if self.normalize_on_import {
+            self.case.normalize(key)
+        } else {
+            key.to_string()
+        }
+    }
+}
+
+/// key 的大小写要求（只看 ASCII 字母）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum KeyCase {
+    /// 不管大小写
+    #[default]
+    Any,
+    /// 只能用大写字母（原版文件的习惯）
+    EnforceUpper,
+    /// 只能用小写字母
+    EnforceLower,
+}
+
+impl KeyCase {
+    pub fn accepts(self, key: &str) -> bool {
+        match self {
+            KeyCase::Any => true,
+            KeyCase::EnforceUpper => !key.bytes().any(|b| b.is_ascii_lowercase()),
+            KeyCase::EnforceLower => !key.bytes().any(|b| b.is_ascii_uppercase()),
+        }
+    }
+
+    pub fn normalize(self, key: &str) -> String {
+        match self {
+            KeyCase::Any => key.to_string(),
+            KeyCase::EnforceUpper => key.to_ascii_uppercase(),
+            KeyCase::EnforceLower => key.to_ascii_lowercase(),
+        }
+    }
+}
+
+/// 字宽、行高所用的虚拟屏幕（游戏按它缩放到实际分辨率）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ScreenDef {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for ScreenDef {
+    fn default() -> Self {
+        ScreenDef {
+            width: 640,
+            height: 448,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FontDef {
+    /// 脚本里用的字体编号
+    pub id: u32,
+    pub name: String,
+    /// 没在 widths 里列出的字符的宽度（像素）；为 0 时半角算 1、全角算 2
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub default_width: u32,
+    /// 按码点区间给出的字宽（像素），先列出的优先
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub widths: Vec<GlyphWidth>,
+    /// 行高（像素）；0 表示不知道，估算不了文字占多高
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub line_height: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlyphWidth {
+    pub start: u32,
+    pub end: u32,
+    pub width: u32,
+}
+
+impl FontDef {
+    pub fn char_width(&self, c: char) -> u32 {
+        let code = c as u32;
+        if let Some(g) = self
+            .widths
+            .iter()
+            .find(|g| (g.start..=g.end).contains(&code))
+        {
+            return g.width;
+        }
+        match self.default_width {
+            0 if is_wide(c) => 2,
+            0 => 1,
+            w => w,
+        }
+    }
+}
+
+/// 东亚全角字符（CJK、假名、谚文、全角符号）
+pub(crate) fn is_wide(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x115F
+        | 0x2E80..=0x303E
+        | 0x3040..=0xA4CF
+        | 0xAC00..=0xD7A3
+        | 0xF900..=0xFAFF
+        | 0xFE30..=0xFE4F
+        | 0xFF00..=0xFF60
+        | 0xFFE0..=0xFFE6
+        | 0x20000..=0x3FFFD)
+}
+
+impl GamePreset {
+    /// 叠加项目的自定义 token 后的预设
+    pub fn with_tokens(mut self, set: &TokenSet) -> Self {
+        if set.replace {
+            self.tokens.clear();
+        }
+        for def in &set.tokens {
+            match self
+                .tokens
+                .iter_mut()
+                .find(|t| t.token.eq_ignore_ascii_case(&def.token))
+            {
+                Some(t) => *t = def.clone(),
+                None => self.tokens.push(def.clone()),
+            }
+        }
+        self
+    }
+
+    /// 按本预设的 key 规则检查（在通用的 validate_key 之上）
+    pub fn check_key(&self, key: &str) -> Result<(), String> {
+        let r = &self.key_rules;
+        if key.len() < r.min_len || key.len() > r.max_len {
+            return Err(format!(
+                "KEY length must be {}..={} for {}: {key:?}",
+                r.min_len, r.max_len, self.name
+            ));
+        }
+        if r.strict_charset
+            && !key
+                .bytes()
+                .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+        {
+            return Err(format!(
+                "KEY may only contain A-Z, 0-9 and _ for {}: {key:?}",
+                self.name
+            ));
+        }
+        if !r.case.accepts(key) {
+            let case = match r.case {
+                KeyCase::EnforceLower => "lowercase",
+                _ => "uppercase",
+            };
+            return Err(format!("KEY must be {case} for {}: {key:?}", self.name));
+        }
+        Ok(())
+    }
+
+    pub fn is_allowed(&self, c: char) -> bool {
+        self.allowed_chars.is_empty() || self.allowed_chars.iter().any(|r| r.contains(c))
+    }
+
+    /// value 里字体没有字形的字符（去重，按出现顺序；token 与转义不算）
+    pub fn disallowed_chars(&self, value: &str) -> Vec<char> {
+        let mut out = Vec::new();
+        for seg in segments(value) {
+            if let Segment::Text(t) = seg {
+                for c in t.chars() {
+                    if !self.is_allowed(c) && !out.contains(&c) {
+                        out.push(c);
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// value 编码后超过 max_value_bytes 时返回它的字节数（转义写错、编码不了时不算）
+    pub fn oversized_value(&self, value: &str) -> Option<usize> {
+        let max = self.max_value_bytes?;
+        encoded_utf16z_len(value).ok().filter(|&bytes| bytes > max)
+    }
+
+    pub fn token(&self, token: &str) -> Option<&TokenDef> {
+        self.tokens
+            .iter()
+            .find(|t| t.token.eq_ignore_ascii_case(token))
+    }
+
+    pub fn key_binding(&self, action: &str) -> Option<&KeyBindingDef> {
+        self.key_bindings
+            .iter()
+            .find(|k| k.action.eq_ignore_ascii_case(action))
+    }
+
+    /// value 里本预设不认识的 token（预设没列 token 时不检查；`~k~` 后面的按键动作等参数不算）
+    pub fn unknown_tokens<'a>(&self, value: &'a str) -> Vec<&'a str> {
+        if self.tokens.is_empty() {
+            return Vec::new();
+        }
+        self.split_args(value)
+            .tokens
+            .into_iter()
+            .filter(|t| self.token(t).is_none())
+            .collect()
+    }
+
+    /// value 里后面跟的参数不够 args 个的 token
+    pub fn missing_token_args<'a>(&self, value: &'a str) -> Vec<&'a str> {
+        self.split_args(value).missing_args
+    }
+
+    /// value 里 `~k~~ACTION~` 用到、但本游戏没有的按键动作（预设没列按键时不检查）
+    pub fn unknown_key_bindings<'a>(&self, value: &'a str) -> Vec<&'a str> {
+        if self.key_bindings.is_empty() {
+            return Vec::new();
+        }
+        self.split_args(value)
+            .actions
+            .into_iter()
+            .filter(|a| self.key_binding(a).is_none())
+            .collect()
+    }
+
+    /// token 的参数个数：`~k~` 固定 1 个，其它看定义
+    pub fn token_args(&self, token: &str) -> usize {
+        if token.eq_ignore_ascii_case(KEY_PREFIX) {
+            return 1;
+        }
+        self.token(token).map_or(0, |t| t.args)
+    }
+
+    /// 把 value 里的 token 分成普通 token、`~k~` 后面的按键动作（去掉两侧 `~`）
+    /// 和参数不够的 token；其它 token 的参数直接跳过
+    fn split_args<'a>(&self, value: &'a str) -> SplitTokens<'a> {
+        let mut out = SplitTokens::default();
+        let mut segs = segments(value).into_iter().peekable();
+        while let Some(seg) = segs.next() {
+            let Segment::Token(t) = seg else {
+                continue;
+            };
+            out.tokens.push(t);
+            let want = self.token_args(t);
+            let mut got = 0;
+            while got < want {
+                let Some(Segment::Token(a)) = segs.next_if(|s| matches!(s, Segment::Token(_)))
+                else {
+                    break;
+                };
+                if t.eq_ignore_ascii_case(KEY_PREFIX) {
+                    out.actions.push(a.trim_matches('~'));
+                }
+                got += 1;
+            }
+            if got < want {
+                out.missing_args.push(t);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Default)]
+struct SplitTokens<'a> {
+    tokens: Vec<&'a str>,
+    actions: Vec<&'a str>,
+    missing_args: Vec<&'a str>,
+}
+
+/// token 占的宽度（没有定义的不占）
+pub(crate) fn token_width(tokens: &[TokenDef], token: &str) -> u32 {
+    tokens
+        .iter()
+        .find(|t| t.token.eq_ignore_ascii_case(token))
+        .map_or(0, |t| t.width)
+}
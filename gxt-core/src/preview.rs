@@ -0,0 +1,125 @@
+//! 把 value 渲染成“游戏里文本框大概是什么样”的显示片段，供前端预览。
+//!
+//! - 有颜色的 token（预设里 `color` 非空）切换之后文字的颜色，`~s~` 恢复默认色
+//! - `~n~` 换行
+//! - `~k~~ACTION~` 是按键提示，合成一个片段
+//! - 其它 token（`~1~` 数字占位、`~h~` 高亮等）原样作为一个片段
+//! - 转义还原成对应的字符
+
+use crate::preset::{GamePreset, KEY_PREFIX};
+use crate::tokens::{segments, Segment};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "kebab-case"))]
+pub enum PreviewSegment {
+    /// color 为 None 表示文本框的默认色
+    Text {
+        text: String,
+        color: Option<String>,
+    },
+    LineBreak,
+    /// `~k~~PED_SPRINT~`：action 为 `PED_SPRINT`；label 为按键名（不知道时为 None）
+    KeyBinding {
+        action: String,
+        label: Option<String>,
+        color: Option<String>,
+    },
+    /// 没有显示效果的 token；description 取自预设
+    Token {
+        token: String,
+        description: Option<String>,
+    },
+}
+
+const LINE_BREAK: &str = "~n~";
+const RESET_COLOR: &str = "~s~";
+
+/// 渲染 value；preset 为 None 时不认识颜色与按键名（只处理换行、按键动作与转义）
+pub fn preview(value: &str, preset: Option<&GamePreset>) -> Vec<PreviewSegment> {
+    let mut out: Vec<PreviewSegment> = Vec::new();
+    let mut color: Option<String> = None;
+    let mut segs = segments(value).into_iter().peekable();
+
+    while let Some(seg) = segs.next() {
+        match seg {
+            Segment::Text(t) => push_text(&mut out, t, &color),
+            Segment::Escape(e) => push_text(&mut out, &unescape(e), &color),
+            Segment::Token(t) if t.eq_ignore_ascii_case(LINE_BREAK) => {
+                out.push(PreviewSegment::LineBreak)
+            }
+            Segment::Token(t) if t.eq_ignore_ascii_case(KEY_PREFIX) => {
+                match segs.next_if(|s| matches!(s, Segment::Token(_))) {
+                    Some(action) => {
+                        let action = action.as_str().trim_matches('~');
+                        out.push(PreviewSegment::KeyBinding {
+                            action: action.to_string(),
+                            label: preset
+                                .and_then(|p| p.key_binding(action))
+                                .map(|k| k.label.clone()),
+                            color: color.clone(),
+                        })
+                    }
+                    None => out.push(PreviewSegment::Token {
+                        token: t.to_string(),
+                        description: None,
+                    }),
+                }
+            }
+            Segment::Token(t) if t.eq_ignore_ascii_case(RESET_COLOR) => color = None,
+            Segment::Token(t) => {
+                let def = preset.and_then(|p| p.token(t));
+                match def.and_then(|d| d.color.clone()) {
+                    Some(c) => color = Some(c),
+                    None => {
+                        // 参数 token 跟在后面一起显示
+                        let mut token = t.to_string();
+                        for _ in 0..def.map_or(0, |d| d.args) {
+                            match segs.next_if(|s| matches!(s, Segment::Token(_))) {
+                                Some(arg) => token.push_str(arg.as_str()),
+                                None => break,
+                            }
+                        }
+                        out.push(PreviewSegment::Token {
+                            token,
+                            description: def.map(|d| d.description.clone()),
+                        })
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// 同色的相邻文字并成一段
+fn push_text(out: &mut Vec<PreviewSegment>, text: &str, color: &Option<String>) {
+    if let Some(PreviewSegment::Text {
+        text: last,
+        color: c,
+    }) = out.last_mut()
+    {
+        if c == color {
+            last.push_str(text);
+            return;
+        }
+    }
+    out.push(PreviewSegment::Text {
+        text: text.to_string(),
+        color: color.clone(),
+    });
+}
+
+/// `\\`、`\xNNNN`、`\uNNNN`、`\u{...}` 还原成字符；不是合法码点时原样保留
+pub(crate) fn unescape(e: &str) -> String {
+    let hex = match e.as_bytes().get(1) {
+        Some(b'\\') => return "\\".to_string(),
+        Some(b'u') if e.get(2..3) == Some("{") => &e[3..e.len() - 1],
+        _ => &e[2..],
+    };
+    u32::from_str_radix(hex, 16)
+        .ok()
+        .and_then(char::from_u32)
+        .map(String::from)
+        .unwrap_or_else(|| e.to_string())
+}
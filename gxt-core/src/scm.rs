@@ -0,0 +1,213 @@
+//! 在任务脚本里找引用的 GXT key：编译好的 main.scm / CLEO 脚本（.cs 等），
+//! 以及 Sanny Builder 的源码。只认得出“文本标签”形式的参数，找到的是候选而非确定的引用。
+
+/// III / VC 里第一个参数就是文本标签的显示文字指令（print_big、print_now、print_help 等）。
+/// 这两代的标签参数没有类型字节，只能靠指令认
+const III_VC_TEXT_OPCODES: [u16; 14] = [
+    0x00BA, 0x00BB, 0x00BC, 0x00BD, 0x01E3, 0x01E4, 0x01E5, 0x01E6, 0x02FD, 0x02FE, 0x02FF, 0x0300,
+    0x036D, 0x03E5,
+];
+/// SA 里 8 字节短字符串参数的类型字节
+const SA_SHORT_STRING: u8 = 0x09;
+/// 指令号的最高位表示取反（`not`）
+const NOT_FLAG: u16 = 0x8000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum RefAt {
+    /// 编译好的脚本里标签的字节偏移
+    Offset(usize),
+    /// 源码里的行号（从 1 开始）
+    Line(usize),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyRef {
+    pub key: String,
+    pub at: RefAt,
+}
+
+/// 8 字节的标签：1~7 个字母、数字或下划线，后面全是 0
+pub fn key_label(raw: &[u8]) -> Option<&str> {
+    let len = raw.iter().position(|&b| b == 0)?;
+    let name = &raw[..len];
+    if len == 0 || !name.iter().copied().all(is_label_byte) || !raw[len..].iter().all(|&b| b == 0) {
+        return None;
+    }
+    std::str::from_utf8(name).ok()
+}
+
+/// 反编译的 main.scm 里显示某个 key 的地方：给译者看这句话出现在哪个任务、怎么显示
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScriptContext {
+    /// 任务名：`// Originally:` 注释里的名字，没有时用线程名，再没有时用 `Mission N`
+    pub mission: Option<String>,
+    /// 指令号（如 `00BC`）；反编译时隐藏了指令号的为 None
+    pub opcode: Option<String>,
+    /// 指令名（如 `print_big`）
+    pub command: Option<String>,
+    /// 行号（从 1 开始）
+    pub line: usize,
+}
+
+/// 给线程起名的指令：它的参数是线程名而不是 GXT key
+const NAME_THREAD_OPCODE: &str = "03A4";
+
+/// 解析 Sanny Builder 反编译的 main.scm 文本，找出每个 key 在哪个任务、由哪条指令显示（按出现顺序）
+pub fn scan_decompiled(text: &str) -> Vec<(String, ScriptContext)> {
+    let mut out = Vec::new();
+    let mut header: Option<String> = None;
+    let mut original: Option<String> = None;
+    let mut thread: Option<String> = None;
+    for (n, line) in text.lines().enumerate() {
+        let t = line.trim();
+        if let Some(rest) = t.strip_prefix("//") {
+            let rest = rest.trim_matches(|c: char| c == '-' || c.is_whitespace());
+            if let Some(name) = rest.strip_prefix("Originally:") {
+                original = Some(name.trim().to_string()).filter(|s| !s.is_empty());
+            } else if rest.starts_with("Mission ") && t.contains("---") {
+                header = Some(rest.to_string());
+                original = None;
+                thread = None;
+            }
+            continue;
+        }
+        let code = t.split("//").next().unwrap_or("");
+        let (opcode, rest) = match code.split_once(':') {
+            Some((op, rest)) if op.len() == 4 && op.bytes().all(|b| b.is_ascii_hexdigit()) => {
+                (Some(op.to_ascii_uppercase()), rest.trim_start())
+            }
+            _ => (None, code),
+        };
+        let command = rest
+            .split(|c: char| c.is_whitespace() || c == '\'')
+            .next()
+            .filter(|c| !c.is_empty())
+            .map(str::to_string);
+        let labels = scan_source(code);
+        if opcode.as_deref() == Some(NAME_THREAD_OPCODE)
+            || command.as_deref() == Some("name_thread")
+        {
+            if let Some(name) = labels.into_iter().next() {
+                thread = Some(name.key);
+            }
+            continue;
+        }
+        for r in labels {
+            out.push((
+                r.key,
+                ScriptContext {
+                    mission: original
+                        .clone()
+                        .or_else(|| thread.clone())
+                        .or_else(|| header.clone()),
+                    opcode: opcode.clone(),
+                    command: command.clone(),
+                    line: n + 1,
+                },
+            ));
+        }
+    }
+    out
+}
+
+/// 按内容判断：能按 UTF-8 读且没有 0 字节的当作源码，否则当作编译好的脚本
+pub fn scan_script(bytes: &[u8]) -> Vec<KeyRef> {
+    match std::str::from_utf8(bytes) {
+        Ok(text) if !bytes.contains(&0) => scan_source(text),
+        _ => scan_compiled(bytes),
+    }
+}
+
+/// 编译好的脚本：SA 找类型为短字符串的参数，III / VC 找显示文字指令后面紧跟的标签
+pub fn scan_compiled(bytes: &[u8]) -> Vec<KeyRef> {
+    let mut out = Vec::new();
+    let mut i = 0;
+    while i + 8 < bytes.len() {
+        if bytes[i] == SA_SHORT_STRING {
+            if let Some(key) = key_label(&bytes[i + 1..i + 9]) {
+                out.push(KeyRef {
+                    key: key.to_string(),
+                    at: RefAt::Offset(i + 1),
+                });
+                i += 9;
+                continue;
+            }
+        }
+        if i + 10 <= bytes.len() {
+            let opcode = u16::from_le_bytes([bytes[i], bytes[i + 1]]) & !NOT_FLAG;
+            if III_VC_TEXT_OPCODES.contains(&opcode) {
+                if let Some(key) = key_label(&bytes[i + 2..i + 10]) {
+                    out.push(KeyRef {
+                        key: key.to_string(),
+                        at: RefAt::Offset(i + 2),
+                    });
+                    i += 10;
+                    continue;
+                }
+            }
+        }
+        i += 1;
+    }
+    out
+}
+
+/// Sanny Builder 源码：单引号里 1~7 个字母、数字或下划线的短字符串（`//` 之后的注释不算）
+pub fn scan_source(text: &str) -> Vec<KeyRef> {
+    let mut out = Vec::new();
+    for (n, line) in text.lines().enumerate() {
+        let code = line.split("//").next().unwrap_or("");
+        let mut parts = code.split('\'');
+        // 奇数段在引号里
+        parts.next();
+        while let Some(quoted) = parts.next() {
+            let valid = (1..=7).contains(&quoted.len()) && quoted.bytes().all(is_label_byte);
+            if valid {
+                out.push(KeyRef {
+                    key: quoted.to_string(),
+                    at: RefAt::Line(n + 1),
+                });
+            }
+            parts.next();
+        }
+    }
+    out
+}
+
+/// 源码里把 `'OLD'` 换成 `'NEW'`（只换 scan_source 认得出的引用，注释里的不动）。
+/// 返回改过的行号（从 1 开始）与改后的全文；换行符保持原样
+pub fn rename_in_source(text: &str, old: &str, new: &str) -> (Vec<usize>, String) {
+    let mut lines = Vec::new();
+    let mut out = String::with_capacity(text.len());
+    for (n, line) in text.split_inclusive('\n').enumerate() {
+        let (code, comment) = match line.find("//") {
+            Some(i) => line.split_at(i),
+            None => (line, ""),
+        };
+        // 与 scan_source 一样按引号切分，只换引号里正好是 old 的段
+        let mut changed = false;
+        for (i, part) in code.split('\'').enumerate() {
+            if i > 0 {
+                out.push('\'');
+            }
+            if i % 2 == 1 && part == old {
+                out.push_str(new);
+                changed = true;
+            } else {
+                out.push_str(part);
+            }
+        }
+        out.push_str(comment);
+        if changed {
+            lines.push(n + 1);
+        }
+    }
+    (lines, out)
+}
+
+fn is_label_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
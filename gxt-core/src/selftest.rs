@@ -0,0 +1,601 @@
+//! 自检：用内置的样例（各种格式与边界情况）走一遍 解析 -> 生成 -> 解析，
+//! 有任何不一致就报出来。让用户在把几千条翻译交给编辑器之前确认安装没有问题。
+
+use std::sync::Arc;
+
+use crate::args::{ArgRule, ArgRules, ArgType, ArgViolation};
+use crate::astral::{astral_chars, substitute_astral};
+use crate::cache::ValueCache;
+use crate::charset::{render_charset, used_chars, CharsetFormat};
+use crate::codepage::Codepage;
+use crate::embed::{find_section, splice_section};
+use crate::entry::GxtEntry;
+use crate::escape::{decode_utf16z_with_escapes, lint_escapes, percent_decode};
+use crate::format::{
+    build_gxt_bytes, build_gxt_bytes_cached, build_gxt_bytes_shared, parse_gxt_bytes,
+    parse_gxt_bytes_checked, LoadWarning,
+};
+use crate::layout::{gxt_layout, LayoutField, LayoutItem};
+use crate::legacy::{parse_legacy, write_legacy, LegacyFormat};
+use crate::metrics::{visible_part_with, MetricsOptions};
+use crate::preset::{FontDef, KeyCase, KeyRules};
+use crate::sniff::{sniff_text_format, TextFormat};
+use crate::text::{entries_from_text, entries_to_text};
+use crate::usage::parse_display_log;
+use crate::validate::sort_entries;
+
+/// “大文件”样例的条数
+const LARGE_COUNT: usize = 5000;
+
+/// 一项检查的结果；detail 是失败原因
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelftestCase {
+    /// `样例/格式`，如 `escapes/gxt`
+    pub name: String,
+    pub passed: bool,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelftestReport {
+    pub cases: Vec<SelftestCase>,
+}
+
+impl SelftestReport {
+    pub fn passed(&self) -> bool {
+        self.cases.iter().all(|c| c.passed)
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &SelftestCase> {
+        self.cases.iter().filter(|c| !c.passed)
+    }
+}
+
+fn entries(pairs: &[(&str, &str)]) -> Vec<GxtEntry> {
+    pairs
+        .iter()
+        .map(|&(key, value)| GxtEntry {
+            key: key.to_string(),
+            value: Arc::from(value),
+        })
+        .collect()
+}
+
+/// 内置样例；value 都写成解析结果的规范形式（转义大写、四位）
+fn fixtures() -> Vec<(&'static str, Vec<GxtEntry>)> {
+    let large = (0..LARGE_COUNT)
+        .map(|i| GxtEntry {
+            key: format!("L{i:05}"),
+            value: Arc::from(format!("Entry {i} ~n~~1~ of {LARGE_COUNT}")),
+        })
+        .collect();
+    vec![
+        ("empty", Vec::new()),
+        (
+            "basic",
+            entries(&[
+                ("CRED1", "Hello"),
+                ("INTRO", "Welcome to ~y~Liberty City~s~."),
+            ]),
+        ),
+        ("empty-value", entries(&[("BLANK", ""), ("NEXT", "x")])),
+        (
+            "keys",
+            entries(&[
+                ("ABCDEFGH", "eight bytes"),
+                ("A", "one byte"),
+                ("A B_~!", "space and punctuation"),
+                ("0123", "digits"),
+            ]),
+        ),
+        (
+            "tokens",
+            entries(&[("TOK", "~r~Red ~n~~1~ of ~1~~k~~PED_FIREWEAPON~")]),
+        ),
+        (
+            "escapes",
+            entries(&[
+                ("SPECIAL", "\\u{0080}a\\u{0090}b\\u{009F}"),
+                ("LONE", "x\\u{D800}y\\u{DC00}"),
+                ("SLASH", "C:\\path\\"),
+            ]),
+        ),
+        (
+            "unicode",
+            entries(&[
+                ("LATIN1", "Café ¿qué? ß"),
+                ("CYR", "Привет, мир"),
+                ("CJK", "你好，世界"),
+                ("ASTRAL", "emoji 😀 𝄞"),
+            ]),
+        ),
+        ("newlines", entries(&[("NL", "line1\nline2\r\n")])),
+        (
+            "shared-values",
+            entries(&[
+                ("DUP1", "same"),
+                ("DUP2", "same"),
+                ("DUP3", "other"),
+                ("DUP4", "same"),
+            ]),
+        ),
+        (
+            "unsorted",
+            entries(&[("ZZZ", "last"), ("AAA", "first"), ("MMM", "mid")]),
+        ),
+        ("large", large),
+    ]
+}
+
+struct Runner {
+    cases: Vec<SelftestCase>,
+}
+
+impl Runner {
+    fn check(&mut self, name: String, result: Result<(), String>) {
+        self.cases.push(SelftestCase {
+            name,
+            passed: result.is_ok(),
+            detail: result.err(),
+        });
+    }
+}
+
+/// 两组条目不一致时给出第一处差别
+fn compare(expected: &[GxtEntry], got: &[GxtEntry]) -> Result<(), String> {
+    if expected.len() != got.len() {
+        return Err(format!(
+            "Expected {} entries, got {}",
+            expected.len(),
+            got.len()
+        ));
+    }
+    match expected.iter().zip(got).find(|(a, b)| a != b) {
+        Some((a, b)) => Err(format!(
+            "Entry mismatch: expected {}={:?}, got {}={:?}",
+            a.key, a.value, b.key, b.value
+        )),
+        None => Ok(()),
+    }
+}
+
+fn same_bytes(what: &str, expected: &[u8], got: &[u8]) -> Result<(), String> {
+    match expected.iter().zip(got).position(|(a, b)| a != b) {
+        None if expected.len() == got.len() => Ok(()),
+        None => Err(format!(
+            "{what}: expected {} bytes, got {}",
+            expected.len(),
+            got.len()
+        )),
+        Some(at) => Err(format!("{what}: bytes differ at offset {at}")),
+    }
+}
+
+fn gxt_round_trip(entries: &[GxtEntry]) -> Result<(), String> {
+    let bytes = build_gxt_bytes(entries)?;
+    let parsed = parse_gxt_bytes(&bytes)?;
+    compare(entries, &parsed)?;
+    same_bytes("Rebuild", &bytes, &build_gxt_bytes(&parsed)?)?;
+    // 保存时走的是带缓存的编码：第二次命中缓存也要得到同样的字节
+    let mut cache = ValueCache::default();
+    build_gxt_bytes_cached(&parsed, &mut cache)?;
+    same_bytes(
+        "Cached rebuild",
+        &bytes,
+        &build_gxt_bytes_cached(&parsed, &mut cache)?,
+    )
+}
+
+fn shared_round_trip(entries: &[GxtEntry]) -> Result<(), String> {
+    compare(
+        entries,
+        &parse_gxt_bytes(&build_gxt_bytes_shared(entries)?)?,
+    )
+}
+
+/// value 的写法在文本里可能不同（换行写成转义），所以比较写出的 GXT 字节
+fn text_round_trip(entries: &[GxtEntry]) -> Result<(), String> {
+    let back = entries_from_text(&entries_to_text(entries)?)?;
+    same_bytes("Text", &build_gxt_bytes(entries)?, &build_gxt_bytes(&back)?)
+}
+
+fn embedded_round_trip(entries: &[GxtEntry]) -> Result<(), String> {
+    let bytes = build_gxt_bytes(entries)?;
+    let container = [b"HEAD\0\0\0\0".as_slice(), &bytes, b"TAIL"].concat();
+    let section = find_section(&container).ok_or("Embedded section not found")?;
+    let parsed = parse_gxt_bytes(&container[section.offset..section.offset + section.len])?;
+    compare(entries, &parsed)?;
+    let (spliced, _) = splice_section(&container, section, &build_gxt_bytes(&parsed)?, false)?;
+    same_bytes("Splice", &container, &spliced)
+}
+
+fn codepage_round_trip() -> Result<(), String> {
+    // 0xA0..=0xFF 的每个单元解码后再编码回去都不变
+    let raw: String = (0xA0..=0xFFu32).filter_map(char::from_u32).collect();
+    let decoded = Codepage::Cp1251.decode(&raw);
+    if !decoded.contains('Я') {
+        return Err("CP1251 decode did not produce Cyrillic".to_string());
+    }
+    let encoded = Codepage::Cp1251.encode(&decoded);
+    if encoded != raw {
+        return Err("CP1251 encode is not the inverse of decode".to_string());
+    }
+    Ok(())
+}
+
+fn legacy_cases(run: &mut Runner) {
+    let samples = [
+        (
+            "legacy/gxt-editor-1.3",
+            LegacyFormat::GxtEditor13,
+            "[MAIN]\n; comment\nKEY1 = Hello\\nWorld\nKEY2 = \"  padded  \"\nKEY3 = {90}x\n",
+            entries(&[
+                ("KEY1", "Hello~n~World"),
+                ("KEY2", "  padded  "),
+                ("KEY3", "\\u{0090}x"),
+            ]),
+        ),
+        (
+            "legacy/gxt2txt",
+            LegacyFormat::Gxt2txt,
+            "[KEY1]\nline1\nline2\n\n[KEY2]\nsingle\n",
+            entries(&[("KEY1", "line1~n~line2"), ("KEY2", "single")]),
+        ),
+        (
+            "legacy/fxt",
+            LegacyFormat::Fxt,
+            "// comment\nKEY1 Hello ~n~World\n\nKEY2\tTabbed\nKEY3\n",
+            entries(&[("KEY1", "Hello ~n~World"), ("KEY2", "Tabbed"), ("KEY3", "")]),
+        ),
+        (
+            "legacy/community",
+            LegacyFormat::Community,
+            "; american.gxt\n[MAIN]\nKEY1\tHello ~n~World\nKEY2\t x=1 \n\n[INTRO]\nKEY1\tFixed\n",
+            entries(&[("KEY1", "Fixed"), ("KEY2", " x=1 ")]),
+        ),
+    ];
+    for (name, format, text, expected) in samples {
+        run.check(
+            name.to_string(),
+            parse_legacy(text, format).and_then(|got| compare(&expected, &got)),
+        );
+    }
+    // 制表符导出成转义，再读回来是转义形式（编码结果不变）
+    let sample = entries(&[("KEY1", "Tab\there"), ("KEY2", "\\u{0090}x ~n~")]);
+    let expected = entries(&[("KEY1", "Tab\\u{0009}here"), ("KEY2", "\\u{0090}x ~n~")]);
+    run.check(
+        "legacy/community-export".to_string(),
+        write_legacy(&sample, LegacyFormat::Community)
+            .and_then(|text| parse_legacy(&text, LegacyFormat::Community))
+            .and_then(|got| compare(&expected, &got)),
+    );
+}
+
+fn sniff_cases(run: &mut Runner) {
+    let samples: [(&str, &[u8], Option<TextFormat>); 8] = [
+        (
+            "sniff/key-value",
+            b"KEY1=Hello\nKEY2=World\n",
+            Some(TextFormat::KeyValue),
+        ),
+        (
+            "sniff/gxt-editor-1.3",
+            b"[MAIN]\nKEY1 = Hello\n",
+            Some(TextFormat::Legacy(LegacyFormat::GxtEditor13)),
+        ),
+        (
+            "sniff/gxt2txt",
+            b"[KEY1]\nline one\n\n[KEY2]\nline two\n",
+            Some(TextFormat::Legacy(LegacyFormat::Gxt2txt)),
+        ),
+        (
+            "sniff/community",
+            b"[MAIN]\nKEY1\tHello = there\n",
+            Some(TextFormat::Legacy(LegacyFormat::Community)),
+        ),
+        (
+            "sniff/fxt",
+            b"// mod text\nMYTXT1 Hello there\nMYTXT2 Bye\n",
+            Some(TextFormat::Legacy(LegacyFormat::Fxt)),
+        ),
+        (
+            "sniff/csv",
+            b"key,en,zh\nKEY1,Hello,x\n",
+            Some(TextFormat::Csv),
+        ),
+        (
+            "sniff/json",
+            b"[\n  {\"key\": \"K\", \"value\": \"v\"}\n]",
+            Some(TextFormat::Json),
+        ),
+        (
+            "sniff/prose",
+            b"This is just a letter.\nNothing to see here.\n",
+            None,
+        ),
+    ];
+    for (name, bytes, expected) in samples {
+        let got = sniff_text_format(bytes);
+        run.check(
+            name.to_string(),
+            if got == expected {
+                Ok(())
+            } else {
+                Err(format!("expected {expected:?}, got {got:?}"))
+            },
+        );
+    }
+}
+
+/// 转义检查：完整的与不像转义的不报，写错的报出来并给出建议
+fn escape_lint_cases(run: &mut Runner) {
+    let samples: [(&str, &str, &[Option<&str>]); 5] = [
+        (
+            "escape-lint/well-formed",
+            "\\x0041 \\u0041 \\u{1F600} \\\\x1 C:\\users",
+            &[],
+        ),
+        ("escape-lint/short-x", "Hi\\x41!", &[Some("\\x0041")]),
+        ("escape-lint/short-u", "\\u7", &[Some("\\u0007")]),
+        ("escape-lint/unclosed", "\\u{41 end", &[Some("\\u{41}")]),
+        ("escape-lint/out-of-range", "\\u{110000}", &[None]),
+    ];
+    for (name, value, expected) in samples {
+        let issues = lint_escapes(value);
+        let got: Vec<Option<&str>> = issues.iter().map(|i| i.suggestion.as_deref()).collect();
+        run.check(
+            name.to_string(),
+            if got == expected {
+                Ok(())
+            } else {
+                Err(format!("expected {expected:?}, got {got:?}"))
+            },
+        );
+    }
+}
+
+/// 排序后生成：同样的条目不论原来什么顺序，写出的字节都一样
+fn sorted_bytes_case() -> Result<(), String> {
+    let mut a = entries(&[("ZED", "Last"), ("ALPHA", "First"), ("MID_1", "~r~Middle")]);
+    let mut b: Vec<GxtEntry> = a.iter().rev().cloned().collect();
+    sort_entries(&mut a);
+    sort_entries(&mut b);
+    same_bytes("sorted", &build_gxt_bytes(&a)?, &build_gxt_bytes(&b)?)
+}
+
+/// %XX 解码：末尾的转义也要解，不完整的原样保留
+fn percent_decode_cases(run: &mut Runner) {
+    let samples = [
+        ("percent/middle", "A%20B", "A B"),
+        ("percent/trailing", "KEY%41", "KEYA"),
+        ("percent/incomplete", "KEY%4", "KEY%4"),
+        ("percent/not-hex", "%ZZ%", "%ZZ%"),
+        ("percent/utf8", "%E4%B8%AD", "中"),
+    ];
+    for (name, input, expected) in samples {
+        let got = percent_decode(input);
+        run.check(
+            name.to_string(),
+            if got == expected {
+                Ok(())
+            } else {
+                Err(format!("expected {expected:?}, got {got:?}"))
+            },
+        );
+    }
+}
+
+/// 显示日志：时间戳与注释行忽略，地点分开计数
+fn usage_log_case() -> Result<(), String> {
+    let log = parse_display_log(
+        "; session 1\n[00:00:05] INTRO1 intro\n[00:00:09] INTRO1 intro\nHELP_01\n[00:01:00] INTRO1 sweet1\n\n[x]\n",
+    );
+    let intro = log.usage.get("INTRO1").ok_or("INTRO1 not counted")?;
+    let help = log.usage.get("HELP_01").ok_or("HELP_01 not counted")?;
+    let places: Vec<(&str, u64)> = intro.places.iter().map(|(p, n)| (p.as_str(), *n)).collect();
+    if intro.count != 3 || places != [("intro", 2), ("sweet1", 1)] {
+        return Err(format!("INTRO1: {intro:?}"));
+    }
+    if help.count != 1 || !help.places.is_empty() || log.usage.len() != 2 || log.skipped != 1 {
+        return Err(format!("unexpected log: {log:?}"));
+    }
+    Ok(())
+}
+
+fn lenient_case() -> Result<(), String> {
+    let mut bytes = build_gxt_bytes(&entries(&[("KEY1", "a"), ("KEY2", "b")]))?;
+    // 第二个 key 改成与第一个重复，再在末尾加几个字节
+    bytes[27] = b'1';
+    bytes.extend_from_slice(b"junk");
+    if parse_gxt_bytes_checked(&bytes, false).is_ok() {
+        return Err("strict parse accepted a duplicate key".into());
+    }
+    let (got, warnings) = parse_gxt_bytes_checked(&bytes, true)?;
+    compare(&entries(&[("KEY1", "a")]), &got)?;
+    let expected = [
+        LoadWarning::DuplicateKey { key: "KEY1".into() },
+        LoadWarning::TrailingData { len: 4 },
+    ];
+    if warnings != expected {
+        return Err(format!("unexpected warnings: {warnings:?}"));
+    }
+    Ok(())
+}
+
+fn astral_case() -> Result<(), String> {
+    let value = "Hi 😀 ~r~\\u{1F44D}\\u00E9";
+    let chars = astral_chars(value);
+    if chars != ['😀', '👍'] {
+        return Err(format!("unexpected astral chars: {chars:?}"));
+    }
+    let got = substitute_astral(value);
+    if got.as_deref() != Some("Hi :D ~r~(y)\\u00E9") {
+        return Err(format!("unexpected substitution: {got:?}"));
+    }
+    Ok(())
+}
+
+fn truncation_case() -> Result<(), String> {
+    let font = FontDef {
+        id: 0,
+        name: "test".into(),
+        default_width: 0,
+        widths: Vec::new(),
+        line_height: 0,
+    };
+    let rows = MetricsOptions {
+        box_width: Some(11),
+        max_rows: Some(1),
+        resolution: None,
+    };
+    let t = visible_part_with("Hello world again~n~second", &font, &[], &rows, true);
+    if t.shown != "Hello world" || t.cut_at != Some(12) {
+        return Err(format!("unexpected wrapped truncation: {t:?}"));
+    }
+    let clip = MetricsOptions {
+        box_width: Some(4),
+        ..Default::default()
+    };
+    let t = visible_part_with("ABCDEF~n~GH", &font, &[], &clip, false);
+    if t.shown != "ABCD~n~GH" || t.cut_at != Some(4) || t.rows != 2 {
+        return Err(format!("unexpected clipped truncation: {t:?}"));
+    }
+    Ok(())
+}
+
+fn key_case_case() -> Result<(), String> {
+    let rules = KeyRules {
+        case: KeyCase::EnforceUpper,
+        normalize_on_import: true,
+        ..Default::default()
+    };
+    if rules.case.accepts("Mis_1a") || !rules.case.accepts("MIS_1A") {
+        return Err("enforce-upper accepted a lowercase key".into());
+    }
+    let got = rules.import_key("Mis_1a");
+    if got != "MIS_1A" {
+        return Err(format!("unexpected normalized key: {got:?}"));
+    }
+    Ok(())
+}
+
+fn arg_types_case() -> Result<(), String> {
+    let rules = ArgRules {
+        rules: vec![ArgRule {
+            pattern: "MIS_*".into(),
+            name: String::new(),
+            args: vec![ArgType::Number, ArgType::String],
+        }],
+        ..Default::default()
+    };
+    let rule = rules.rule_for("mis_1").ok_or("rule not matched")?;
+    if !rules.check(rule, "Won $~1~ from ~a~~n~").is_empty() {
+        return Err("well-formed arguments reported".into());
+    }
+    let got = rules.check(rule, "~a~ paid $~1~ ~1~");
+    let expected = [
+        ArgViolation::Mismatch {
+            index: 1,
+            expected: ArgType::Number,
+            token: "~a~".into(),
+        },
+        ArgViolation::Mismatch {
+            index: 2,
+            expected: ArgType::String,
+            token: "~1~".into(),
+        },
+        ArgViolation::Extra {
+            index: 3,
+            token: "~1~".into(),
+        },
+    ];
+    if got != expected {
+        return Err(format!("unexpected violations: {got:?}"));
+    }
+    let got = rules.check(rule, "Won ~1~");
+    if got
+        != [ArgViolation::Missing {
+            index: 2,
+            expected: ArgType::String,
+        }]
+    {
+        return Err(format!("dropped argument not reported: {got:?}"));
+    }
+    Ok(())
+}
+
+fn layout_case() -> Result<(), String> {
+    let bytes = build_gxt_bytes(&entries(&[("A1", "Hi"), ("B22", "There")]))?;
+    let items = gxt_layout(&bytes)?;
+    let at = |it: &LayoutItem| &bytes[it.offset..it.offset + it.length];
+    for it in &items {
+        if it.offset + it.length > bytes.len() {
+            return Err(format!("{:?} runs past the end", it.field));
+        }
+        let ok = match it.field {
+            LayoutField::TdatMagic => at(it) == b"TDAT",
+            LayoutField::KeyName => at(it).starts_with(it.key.as_deref().unwrap_or("?").as_bytes()),
+            LayoutField::Value => {
+                let mut s = String::new();
+                decode_utf16z_with_escapes(at(it), 0, &mut s)?;
+                let want = if it.key.as_deref() == Some("A1") {
+                    "Hi"
+                } else {
+                    "There"
+                };
+                s == want
+            }
+            _ => true,
+        };
+        if !ok {
+            return Err(format!(
+                "{:?} of {:?} points at the wrong bytes",
+                it.field, it.key
+            ));
+        }
+    }
+    if items
+        .last()
+        .is_none_or(|it| it.offset + it.length != bytes.len())
+    {
+        return Err("layout does not cover the whole file".into());
+    }
+    Ok(())
+}
+
+fn charset_case() -> Result<(), String> {
+    let entries = entries(&[("A1", "BA~n~C\\u4E01"), ("A2", "\\u{4E00} D~r~")]);
+    let text = render_charset(&used_chars(&entries), CharsetFormat::Ranges);
+    if text != "U+0020\nU+0041-0044\nU+4E00-4E01\n" {
+        return Err(format!("unexpected ranges: {text:?}"));
+    }
+    Ok(())
+}
+
+/// 跑全部检查
+pub fn run_selftest() -> SelftestReport {
+    let mut run = Runner { cases: Vec::new() };
+    for (name, entries) in fixtures() {
+        run.check(format!("{name}/gxt"), gxt_round_trip(&entries));
+        run.check(format!("{name}/gxt-shared"), shared_round_trip(&entries));
+        run.check(format!("{name}/text"), text_round_trip(&entries));
+        run.check(format!("{name}/embedded"), embedded_round_trip(&entries));
+    }
+    run.check("codepage/cp1251".to_string(), codepage_round_trip());
+    legacy_cases(&mut run);
+    sniff_cases(&mut run);
+    escape_lint_cases(&mut run);
+    percent_decode_cases(&mut run);
+    run.check("usage/display-log".to_string(), usage_log_case());
+    run.check("charset/ranges".to_string(), charset_case());
+    run.check("astral/substitute".to_string(), astral_case());
+    run.check("gxt/lenient".to_string(), lenient_case());
+    run.check("metrics/truncation".to_string(), truncation_case());
+    run.check("preset/key-case".to_string(), key_case_case());
+    run.check("args/types".to_string(), arg_types_case());
+    run.check("layout/offsets".to_string(), layout_case());
+    run.check("gxt/sorted-bytes".to_string(), sorted_bytes_case());
+    SelftestReport { cases: run.cases }
+}
@@ -0,0 +1,135 @@
+//! 猜一个不是 GXT 的文件是哪种文本导出：用户常把 txt / csv 误当 GXT 打开，
+//! 与其只报“文件头不是 TKEY”，不如告诉他该用哪个导入。
+
+use crate::format::MAGIC_TKEY;
+use crate::legacy::{decode_legacy_bytes, detect_legacy_format, LegacyFormat};
+use crate::validate::validate_key;
+
+/// 只看文件开头这么多字节
+const SNIFF_BYTES: usize = 64 * 1024;
+/// 最多看这么多行有内容的行
+const SNIFF_LINES: usize = 40;
+
+/// 认得出的文本格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum TextFormat {
+    /// 本库的 `KEY=value` 文本（entries_to_text 的输出）
+    KeyValue,
+    /// `[{"key": ..., "value": ...}]`
+    Json,
+    /// `key,原文,译文` 的双语 CSV
+    Csv,
+    /// 老工具的导出，见 legacy 模块
+    Legacy(LegacyFormat),
+}
+
+/// 一行像哪种格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    /// `KEY=value`，等号前后没有空格
+    KeyValue,
+    /// `KEY = value`
+    SpacedKeyValue,
+    /// `[KEY]` / `[TABLE]`
+    Header,
+    /// `KEY,...`
+    Csv,
+    /// `KEY 文字`
+    Fxt,
+}
+
+fn classify(line: &str) -> Option<LineKind> {
+    if line.starts_with('[') && line.ends_with(']') {
+        return Some(LineKind::Header);
+    }
+    let end = line
+        .find(|c: char| c == '=' || c == ',' || c.is_whitespace())
+        .unwrap_or(line.len());
+    let key = &line[..end];
+    validate_key(key).ok()?;
+    let rest = &line[end..];
+    if rest.starts_with('=') {
+        return Some(LineKind::KeyValue);
+    }
+    if rest.starts_with(',') {
+        return Some(LineKind::Csv);
+    }
+    if rest.trim_start().starts_with('=') {
+        return Some(LineKind::SpacedKeyValue);
+    }
+    // 普通句子的第一个词也是合法的 key，FXT 只认全大写的（实际的 key 都是这样）
+    key.bytes()
+        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'_')
+        .then_some(LineKind::Fxt)
+}
+
+/// 文件内容像哪种文本格式；是 GXT、二进制或认不出时为 None
+pub fn sniff_text_format(bytes: &[u8]) -> Option<TextFormat> {
+    if bytes.starts_with(MAGIC_TKEY) {
+        return None;
+    }
+    let head = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    if head.contains(&0) {
+        return None;
+    }
+    let text = decode_legacy_bytes(head);
+    let trimmed = text.trim_start();
+    if trimmed.starts_with('[') && trimmed[1..].trim_start().starts_with('{') {
+        return Some(TextFormat::Json);
+    }
+
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with([';', '#']) && !l.starts_with("//"))
+        .take(SNIFF_LINES)
+        .collect();
+    let first = lines.first()?;
+    if first.starts_with('[') {
+        if let Some(f @ (LegacyFormat::Gxt2txt | LegacyFormat::Community)) =
+            detect_legacy_format(&text)
+        {
+            return Some(TextFormat::Legacy(f));
+        }
+    }
+    if first
+        .get(..4)
+        .is_some_and(|h| h.eq_ignore_ascii_case("key,"))
+    {
+        return Some(TextFormat::Csv);
+    }
+
+    let kinds: Vec<LineKind> = lines.iter().filter_map(|l| classify(l)).collect();
+    // 认不出的行太多（普通文档、代码等）就不猜
+    if kinds.len() * 5 < lines.len() * 4 {
+        return None;
+    }
+    let count = |k: LineKind| kinds.iter().filter(|&&x| x == k).count();
+    let headers = count(LineKind::Header);
+    let body = kinds.len() - headers;
+    let (kv, spaced, csv, fxt) = (
+        count(LineKind::KeyValue),
+        count(LineKind::SpacedKeyValue),
+        count(LineKind::Csv),
+        count(LineKind::Fxt),
+    );
+    if body == 0 {
+        return None;
+    }
+    if kv + spaced == body {
+        return Some(if headers > 0 || spaced > 0 {
+            TextFormat::Legacy(LegacyFormat::GxtEditor13)
+        } else {
+            TextFormat::KeyValue
+        });
+    }
+    if csv == body {
+        return Some(TextFormat::Csv);
+    }
+    if fxt == body {
+        return Some(TextFormat::Legacy(LegacyFormat::Fxt));
+    }
+    None
+}
@@ -0,0 +1,185 @@
+//! TDAT 空间分析：没有 key 指向的字节、相同 value 重复存放浪费的字节，以及压缩后的大小；
+//! 另外检查指向别的 value 中间的 key（部分共用的文字）。
+
+use std::collections::{HashMap, HashSet};
+
+use crate::format::{
+    build_gxt_bytes_aliased, build_gxt_bytes_shared, parse_gxt_bytes, parse_key_table,
+    KEY_RECORD_BYTES,
+};
+
+/// 文件头：`TKEY` + 大小，`TDAT` + 大小
+const HEADER_BYTES: usize = 16;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpaceReport {
+    pub file_size: usize,
+    pub key_count: usize,
+    /// TDAT 数据区大小
+    pub value_bytes: usize,
+    /// 任何 key 都读不到的字节（旧值残留、填充等）
+    pub orphan_bytes: usize,
+    /// 没人引用的区间 `(起始 offset, 长度)`，按 offset 排序
+    pub orphan_ranges: Vec<(usize, usize)>,
+    /// 内容与前面某条完全相同、却单独存了一份的 value 个数
+    pub duplicate_values: usize,
+    /// 这些重复副本占的字节
+    pub duplicate_bytes: usize,
+    /// 已经与别的 key 共用同一个 offset 的 key 个数
+    pub shared_keys: usize,
+    /// 去掉无主字节、相同 value 只存一份后的文件大小（即 compact_gxt_bytes 的输出大小）
+    pub compacted_size: usize,
+}
+
+impl SpaceReport {
+    pub fn saved_bytes(&self) -> usize {
+        self.file_size.saturating_sub(self.compacted_size)
+    }
+}
+
+/// 分析一个 GXT 文件的 TDAT 用了多少、浪费了多少
+pub fn analyze_space(bytes: &[u8]) -> Result<SpaceReport, String> {
+    let (keys, val_field) = parse_key_table(bytes)?;
+
+    // 每个被引用的 offset 对应的字符串（含结尾的 0），多个 key 共用时只算一次
+    let mut strings: HashMap<usize, &[u8]> = HashMap::new();
+    for &(key, idx) in &keys {
+        let start = idx as usize;
+        if start >= val_field.len() || !start.is_multiple_of(2) {
+            return Err(format!("Bad value offset for key {key}: idx={idx}"));
+        }
+        let end = terminator(val_field, start)
+            .ok_or_else(|| format!("Unterminated value for key {key}: idx={idx}"))?;
+        strings.insert(start, &val_field[start..end]);
+    }
+
+    let mut ranges: Vec<(usize, usize)> = strings.iter().map(|(&s, b)| (s, s + b.len())).collect();
+    ranges.sort_unstable();
+    let mut orphan_ranges = Vec::new();
+    let mut covered_to = 0usize;
+    for (start, end) in ranges {
+        if start > covered_to {
+            orphan_ranges.push((covered_to, start - covered_to));
+        }
+        covered_to = covered_to.max(end);
+    }
+    if covered_to < val_field.len() {
+        orphan_ranges.push((covered_to, val_field.len() - covered_to));
+    }
+
+    // 按内容去重：同样的字节在不同 offset 各存一份就是浪费
+    let mut seen: HashSet<&[u8]> = HashSet::new();
+    let mut duplicate_values = 0;
+    let mut duplicate_bytes = 0;
+    let mut unique_bytes = 0;
+    let mut offsets: Vec<&usize> = strings.keys().collect();
+    offsets.sort_unstable();
+    for off in offsets {
+        let s = strings[off];
+        if seen.insert(s) {
+            unique_bytes += s.len();
+        } else {
+            duplicate_values += 1;
+            duplicate_bytes += s.len();
+        }
+    }
+
+    Ok(SpaceReport {
+        file_size: bytes.len(),
+        key_count: keys.len(),
+        value_bytes: val_field.len(),
+        orphan_bytes: orphan_ranges.iter().map(|(_, len)| len).sum(),
+        orphan_ranges,
+        duplicate_values,
+        duplicate_bytes,
+        shared_keys: keys.len() - strings.len(),
+        compacted_size: HEADER_BYTES + keys.len() * KEY_RECORD_BYTES + unique_bytes,
+    })
+}
+
+/// 重新生成文件：key 顺序不变，丢掉无主字节，相同 value 共用一个 offset
+pub fn compact_gxt_bytes(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    build_gxt_bytes_shared(&parse_gxt_bytes(bytes)?)
+}
+
+/// offset 落在另一条 value 中间的 key：读到的是那条的后半截，两条共用同一段文字，
+/// 改其中一条时另一条在游戏里也会跟着变（老工具为省空间这样做，也可能是文件损坏）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverlappingKey {
+    pub key: String,
+    pub offset: usize,
+    /// 包含它的那条（同一个结尾、起点最靠前）
+    pub host_key: String,
+    pub host_offset: usize,
+}
+
+/// 怎样处理部分共用的 value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum OverlapRepair {
+    /// 有意为之：重写文件时仍指向宿主 value 的结尾
+    Keep,
+    /// 拆开：每条都有自己的一份文字，之后可以分别修改
+    Split,
+}
+
+/// 找出 offset 指向别的 value 中间的 key（按 TKEY 顺序）
+pub fn find_overlaps(bytes: &[u8]) -> Result<Vec<OverlappingKey>, String> {
+    let (keys, val_field) = parse_key_table(bytes)?;
+    // 以同一个结尾 0 结束的字符串里，起点最靠前的是宿主：结尾位置 -> (起点, key)
+    let mut hosts: HashMap<usize, (usize, &str)> = HashMap::new();
+    let mut ends = Vec::with_capacity(keys.len());
+    for &(key, idx) in &keys {
+        let start = idx as usize;
+        if start >= val_field.len() || !start.is_multiple_of(2) {
+            return Err(format!("Bad value offset for key {key}: idx={idx}"));
+        }
+        let end = terminator(val_field, start)
+            .ok_or_else(|| format!("Unterminated value for key {key}: idx={idx}"))?;
+        ends.push(end);
+        let host = hosts.entry(end).or_insert((start, key));
+        if start < host.0 {
+            *host = (start, key);
+        }
+    }
+    Ok(keys
+        .iter()
+        .zip(ends)
+        .filter_map(|(&(key, idx), end)| {
+            let (host_offset, host_key) = hosts[&end];
+            (idx as usize > host_offset).then(|| OverlappingKey {
+                key: key.to_string(),
+                offset: idx as usize,
+                host_key: host_key.to_string(),
+                host_offset,
+            })
+        })
+        .collect())
+}
+
+/// 重写文件并按 repair 处理部分共用的 value；同时像 compact_gxt_bytes 一样丢掉无主字节、
+/// 相同 value 只存一份。条目内容与顺序不变
+pub fn repair_overlaps(bytes: &[u8], repair: OverlapRepair) -> Result<Vec<u8>, String> {
+    let entries = parse_gxt_bytes(bytes)?;
+    match repair {
+        OverlapRepair::Split => build_gxt_bytes_shared(&entries),
+        OverlapRepair::Keep => {
+            let aliases: HashMap<String, String> = find_overlaps(bytes)?
+                .into_iter()
+                .map(|o| (o.key, o.host_key))
+                .collect();
+            build_gxt_bytes_aliased(&entries, &aliases)
+        }
+    }
+}
+
+/// start 处 UTF-16 字符串结束的位置（含结尾的 0）
+fn terminator(val_field: &[u8], start: usize) -> Option<usize> {
+    val_field[start..]
+        .chunks_exact(2)
+        .position(|u| u == [0, 0])
+        .map(|i| start + i * 2 + 2)
+}
@@ -0,0 +1,65 @@
+//! 目录形式的工程：每张“表”（按 key 前缀分的一组条目）一个文本/JSON 文件，
+//! 翻译可以在版本控制里按文件审阅。这里只做拆分与合并，读写文件由调用方负责。
+
+use std::collections::HashMap;
+
+use crate::entry::GxtEntry;
+use crate::group::{prefix_end, GroupLevel};
+
+/// 取不出前缀的 key 放进这张表
+pub const MISC_TABLE: &str = "_misc";
+
+/// 按 level 取 key 的前缀分表。表按第一次出现的顺序排列，表内保持原顺序
+pub fn split_tables(entries: &[GxtEntry], level: &GroupLevel) -> Vec<(String, Vec<GxtEntry>)> {
+    let mut tables: Vec<(String, Vec<GxtEntry>)> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+    for e in entries {
+        let name = match prefix_end(&e.key, 0, level) {
+            Some(end) => table_name(&e.key[..end]),
+            None => MISC_TABLE.to_string(),
+        };
+        let i = *index.entry(name.clone()).or_insert_with(|| {
+            tables.push((name, Vec::new()));
+            tables.len() - 1
+        });
+        tables[i].1.push(e.clone());
+    }
+    tables
+}
+
+/// 前缀转成能做文件名的表名：去掉结尾的分隔符，文件名里不能用的字符换成 `_`
+fn table_name(prefix: &str) -> String {
+    let name: String = prefix
+        .trim_end_matches(|c: char| !c.is_ascii_alphanumeric())
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() {
+        MISC_TABLE.to_string()
+    } else {
+        name
+    }
+}
+
+/// split_tables 的逆操作：按给定顺序首尾相接。同一个 key 出现在两张表里时报错（带表名）
+pub fn join_tables(
+    tables: impl IntoIterator<Item = (String, Vec<GxtEntry>)>,
+) -> Result<Vec<GxtEntry>, String> {
+    let mut seen: HashMap<String, String> = HashMap::new();
+    let mut out = Vec::new();
+    for (name, entries) in tables {
+        for e in entries {
+            if let Some(first) = seen.insert(e.key.clone(), name.clone()) {
+                return Err(format!("Duplicate key {} in {first} and {name}", e.key));
+            }
+            out.push(e);
+        }
+    }
+    Ok(out)
+}
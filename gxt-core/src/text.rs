@@ -0,0 +1,55 @@
+use std::sync::Arc;
+
+use crate::entry::GxtEntry;
+use crate::intern::Interner;
+use crate::validate::validate_key;
+
+/// 纯文本格式：每行一条 `KEY=value`，空行忽略。
+///
+/// value 里的换行/回车写成 `\u{000A}` / `\u{000D}`：编码器本来就把这种转义
+/// 还原成同一个 UTF-16 单元，所以 文本 -> GXT 与直接带换行的 value 字节级一致。
+/// key 里不能含 `=`（否则分不清边界）。
+pub fn entries_to_text(entries: &[GxtEntry]) -> Result<String, String> {
+    let mut out = String::new();
+    for e in entries {
+        if e.key.contains('=') {
+            return Err(format!("KEY contains '=', cannot be written as text: {:?}", e.key));
+        }
+        out.push_str(&e.key);
+        out.push('=');
+        for ch in e.value.chars() {
+            match ch {
+                '\n' => out.push_str("\\u{000A}"),
+                '\r' => out.push_str("\\u{000D}"),
+                c => out.push(c),
+            }
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// entries_to_text 的逆操作；出错时带行号
+pub fn entries_from_text(text: &str) -> Result<Vec<GxtEntry>, String> {
+    let mut interner = Interner::default();
+    let mut entries = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.is_empty() {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("Line {}: missing '=' between KEY and value", n + 1))?;
+        validate_key(key).map_err(|e| format!("Line {}: {e}", n + 1))?;
+
+        let value: Arc<str> = interner.intern(value);
+        entries.push(GxtEntry {
+            key: key.to_string(),
+            value,
+        });
+    }
+
+    Ok(entries)
+}
@@ -0,0 +1,114 @@
+/// value 文本按“普通文字 / ~token~ / 转义”切分。
+///
+/// - token：`~` 开头、`~` 结尾、中间没有空白，如 `~r~`、`~1~`、`~k~`
+/// - 转义：`\\`、`\xNNNN`、`\uNNNN`、`\u{...}`（与编码器认得的一致）
+///
+/// 机翻、占位符检查等需要“别碰这些片段”的功能都基于它。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    Text(&'a str),
+    Token(&'a str),
+    Escape(&'a str),
+}
+
+impl<'a> Segment<'a> {
+    pub fn as_str(&self) -> &'a str {
+        match *self {
+            Segment::Text(s) | Segment::Token(s) | Segment::Escape(s) => s,
+        }
+    }
+}
+
+pub fn segments(s: &str) -> Vec<Segment<'_>> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::new();
+    let mut text_start = 0usize;
+    let mut i = 0usize;
+
+    while i < bytes.len() {
+        let special = match bytes[i] {
+            b'~' => token_len(&bytes[i..]).map(|n| (n, true)),
+            b'\\' => escape_len(&bytes[i..]).map(|n| (n, false)),
+            _ => None,
+        };
+        match special {
+            Some((n, is_token)) => {
+                if text_start < i {
+                    out.push(Segment::Text(&s[text_start..i]));
+                }
+                let piece = &s[i..i + n];
+                out.push(if is_token {
+                    Segment::Token(piece)
+                } else {
+                    Segment::Escape(piece)
+                });
+                i += n;
+                text_start = i;
+            }
+            None => i += 1,
+        }
+    }
+    if text_start < bytes.len() {
+        out.push(Segment::Text(&s[text_start..]));
+    }
+    out
+}
+
+/// 只取出 token（保持出现顺序）
+pub fn tokens(s: &str) -> Vec<&str> {
+    segments(s)
+        .into_iter()
+        .filter_map(|seg| match seg {
+            Segment::Token(t) => Some(t),
+            _ => None,
+        })
+        .collect()
+}
+
+/// 外部格式导入时：文字里真正的换行（`\r\n` / `\n`）换成游戏的换行 token（如 `~n~`）
+pub fn newlines_to_token(s: &str, token: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for seg in segments(s) {
+        match seg {
+            Segment::Text(t) => out.push_str(&t.replace("\r\n", "\n").replace('\n', token)),
+            other => out.push_str(other.as_str()),
+        }
+    }
+    out
+}
+
+/// 导出给外部工具时：换行 token（不分大小写）换成 `\n`，让对方看到自然的断行
+pub fn token_to_newlines(s: &str, token: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for seg in segments(s) {
+        match seg {
+            Segment::Token(t) if t.eq_ignore_ascii_case(token) => out.push('\n'),
+            other => out.push_str(other.as_str()),
+        }
+    }
+    out
+}
+
+fn token_len(b: &[u8]) -> Option<usize> {
+    let end = b[1..]
+        .iter()
+        .position(|&c| c == b'~' || c.is_ascii_whitespace())?;
+    if b[1 + end] != b'~' {
+        return None;
+    }
+    Some(end + 2)
+}
+
+fn escape_len(b: &[u8]) -> Option<usize> {
+    let is_hex = |c: &u8| c.is_ascii_hexdigit();
+    match b.get(1)? {
+        b'\\' => Some(2),
+        b'x' if b.len() >= 6 && b[2..6].iter().all(is_hex) => Some(6),
+        b'u' if b.get(2) == Some(&b'{') => {
+            let close = b[3..].iter().position(|&c| c == b'}')?;
+            (close > 0 && b[3..3 + close].iter().all(is_hex)).then_some(close + 4)
+        }
+        b'u' if b.len() >= 6 && b[2..6].iter().all(is_hex) => Some(6),
+        _ => None,
+    }
+}
@@ -0,0 +1,50 @@
+//! 音译：游戏字体里没有的字母按表换成有的（如西里尔字母、希腊字母转拉丁字母）。
+//! 只动文字部分，token 与 `\u{...}` 转义原样保留。
+
+use std::collections::BTreeMap;
+
+use crate::tokens::{segments, Segment};
+
+/// 一张音译表
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Transliteration {
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub description: String,
+    /// 单个字符 -> 替换成的文字（可以是多个字母，也可以为空表示删掉）；大小写分别列出
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub map: BTreeMap<String, String>,
+}
+
+impl Transliteration {
+    pub fn get(&self, c: char) -> Option<&str> {
+        let mut buf = [0u8; 4];
+        self.map.get(&*c.encode_utf8(&mut buf)).map(String::as_str)
+    }
+
+    /// 把 value 里 keep 返回 false 且表里有的字符换掉。
+    /// 返回新值与被换掉的字符（去重，按出现顺序）
+    pub fn apply(&self, value: &str, keep: impl Fn(char) -> bool) -> (String, Vec<char>) {
+        let mut out = String::with_capacity(value.len());
+        let mut replaced = Vec::new();
+        for seg in segments(value) {
+            let Segment::Text(t) = seg else {
+                out.push_str(seg.as_str());
+                continue;
+            };
+            for c in t.chars() {
+                match self.get(c).filter(|_| !keep(c)) {
+                    Some(to) => {
+                        out.push_str(to);
+                        if !replaced.contains(&c) {
+                            replaced.push(c);
+                        }
+                    }
+                    None => out.push(c),
+                }
+            }
+        }
+        (out, replaced)
+    }
+}
@@ -0,0 +1,59 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::entry::GxtEntry;
+
+/// 原文更新后合并译文（类似 gettext 的 msgmerge）的结果
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SourceUpdate {
+    /// 合并后的译文，按新原文的顺序
+    pub entries: Vec<GxtEntry>,
+    /// 新原文里新增的 key；译文先填原文，视为未翻译
+    pub added: Vec<String>,
+    /// 新原文里已没有的 key 及其旧译文（不再出现在 entries 中）
+    pub archived: Vec<GxtEntry>,
+    /// 原文变了而译文是按旧原文翻的，需要复核
+    pub fuzzy: Vec<String>,
+}
+
+/// 用新原文更新一份译文：
+/// - 新 key：加入，value 取新原文（未翻译）
+/// - 已删除的 key：从译文移到 archived
+/// - 原文变化：译文仍等于旧原文（未翻译）时直接换成新原文；否则保留译文并标记 fuzzy
+pub fn update_translation(
+    old_source: &[GxtEntry],
+    new_source: &[GxtEntry],
+    translation: &[GxtEntry],
+) -> SourceUpdate {
+    let old_map: HashMap<&str, &GxtEntry> =
+        old_source.iter().map(|e| (e.key.as_str(), e)).collect();
+    let tr_map: HashMap<&str, &GxtEntry> =
+        translation.iter().map(|e| (e.key.as_str(), e)).collect();
+    let new_keys: HashSet<&str> = new_source.iter().map(|e| e.key.as_str()).collect();
+
+    let mut out = SourceUpdate::default();
+    for src in new_source {
+        let Some(tr) = tr_map.get(src.key.as_str()) else {
+            out.added.push(src.key.clone());
+            out.entries.push(src.clone());
+            continue;
+        };
+        match old_map.get(src.key.as_str()) {
+            Some(old) if old.value != src.value => {
+                if tr.value == old.value {
+                    out.entries.push(src.clone());
+                } else {
+                    out.fuzzy.push(src.key.clone());
+                    out.entries.push((*tr).clone());
+                }
+            }
+            _ => out.entries.push((*tr).clone()),
+        }
+    }
+    out.archived = translation
+        .iter()
+        .filter(|e| !new_keys.contains(e.key.as_str()))
+        .cloned()
+        .collect();
+    out
+}
@@ -0,0 +1,65 @@
+//! 游戏里实际显示过哪些文字：配套的 CLEO 记录脚本每显示一条文字就往日志里写一行
+//! `[时间] KEY 地点`（时间与地点都可以没有，地点一般是任务或脚本名）。
+//! 把日志数成每个 key 出现的次数，译者可以先翻玩家最常看到的。
+
+use std::collections::BTreeMap;
+
+use crate::validate::validate_key;
+
+/// 一个 key 在日志里出现的次数与地点
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyUsage {
+    pub count: u64,
+    /// 地点 -> 在那里出现的次数；日志里没写地点的只算进 count
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub places: BTreeMap<String, u64>,
+}
+
+impl KeyUsage {
+    /// 合并另一份统计（多次游玩的日志累加）
+    pub fn merge(&mut self, other: &KeyUsage) {
+        self.count += other.count;
+        for (place, n) in &other.places {
+            *self.places.entry(place.clone()).or_default() += n;
+        }
+    }
+}
+
+/// 数完的日志
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DisplayLog {
+    pub usage: BTreeMap<String, KeyUsage>,
+    /// 有内容但认不出 key 的行
+    pub skipped: usize,
+}
+
+/// 数一份显示日志。空行与 `;` / `#` / `//` 开头的注释行跳过
+pub fn parse_display_log(text: &str) -> DisplayLog {
+    let mut log = DisplayLog::default();
+    for line in text.lines() {
+        let mut t = line.trim();
+        if t.is_empty() || t.starts_with([';', '#']) || t.starts_with("//") {
+            continue;
+        }
+        if t.starts_with('[') {
+            if let Some(end) = t.find(']') {
+                t = t[end + 1..].trim_start();
+            }
+        }
+        let (key, place) = match t.split_once(char::is_whitespace) {
+            Some((key, place)) => (key, place.trim()),
+            None => (t, ""),
+        };
+        if validate_key(key).is_err() {
+            log.skipped += 1;
+            continue;
+        }
+        let usage = log.usage.entry(key.to_string()).or_default();
+        usage.count += 1;
+        if !place.is_empty() {
+            *usage.places.entry(place.to_string()).or_default() += 1;
+        }
+    }
+    log
+}
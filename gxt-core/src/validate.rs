@@ -0,0 +1,45 @@
+use std::collections::HashSet;
+
+use crate::entry::GxtEntry;
+
+/// 每个 key 合法且互不重复
+pub fn validate_entries(entries: &[GxtEntry]) -> Result<(), String> {
+    let mut seen = HashSet::with_capacity(entries.len());
+    for e in entries {
+        validate_key(&e.key)?;
+        if !seen.insert(e.key.clone()) {
+            return Err(format!("Duplicate key: {}", e.key));
+        }
+    }
+    Ok(())
+}
+
+/// 比前一个 key 小（按字节序）的 key，即破坏了 TKEY 有序性的位置
+pub fn unsorted_keys(entries: &[GxtEntry]) -> Vec<&str> {
+    entries
+        .windows(2)
+        .filter(|w| w[1].key.as_bytes() < w[0].key.as_bytes())
+        .map(|w| w[1].key.as_str())
+        .collect()
+}
+
+/// 按 key 的字节序排序（引擎二分查找要求的顺序）
+pub fn sort_entries(entries: &mut [GxtEntry]) {
+    entries.sort_by(|a, b| a.key.as_bytes().cmp(b.key.as_bytes()));
+}
+
+/// KEY：1..=8，且只允许 A-Z / 0-9
+/// KEY：1..=8 bytes，允许 ASCII 可见字符：0x20(' ')..0x7E('~')
+pub fn validate_key(key: &str) -> Result<(), String> {
+    let len = key.len(); // 对 ASCII 来说 len = 字节数
+    if len == 0 || len > 8 {
+        return Err(format!("Invalid KEY length (must be 1..=8 bytes): {key:?}"));
+    }
+    if !key.bytes().all(|b| (0x20..=0x7E).contains(&b)) {
+        return Err(format!(
+            "Invalid KEY chars (printable ASCII 0x20..0x7E only): {key:?}"
+        ));
+    }
+    Ok(())
+}
+
@@ -0,0 +1,279 @@
+//! 按字体度量给 value 重新断行（插入/挪动 `~n~`），让每行不超过文本框宽度。
+//!
+//! - token 按定义里的 width 计宽（没定义的不占宽度），且跟它前后紧挨的文字算作一个词，不会被拆开
+//! - 只在空白处断行；全角字符（中日韩）之间也可以断，但不会让行首是 `，。` 之类的标点
+//! - 单个词比整行还宽时独占一行，不在词中间断
+//! - 按目标语言还可以再加规则（见 BreakRules）：行尾不留开括号、全角数字不拆开、
+//!   不换行空格不断、日文小假名不在行首、韩文只在空格处断
+
+use std::mem;
+
+use crate::preset::{is_wide, token_width, FontDef, TokenDef};
+use crate::preview::unescape;
+use crate::tokens::{segments, Segment};
+
+const LINE_BREAK: &str = "~n~";
+
+/// 不能出现在行首的全角标点
+const NO_LINE_START: &str = "，。、；：？！）」』》〉】〕”’…·～";
+/// 按语言断行时，另外不能出现在行首的
+const CJK_NO_LINE_START: &str = "］｝〗〙％‰℃";
+/// 日文里不能出现在行首的小假名、长音与叠字符
+const JA_NO_LINE_START: &str = "ぁぃぅぇぉっゃゅょゎゕゖァィゥェォッャュョヮヵヶー々ゝゞヽヾ゠";
+/// 按语言断行时不能出现在行尾的开括号、开引号
+const NO_LINE_END: &str = "（「『《〈【〔“‘［｛〖〘";
+
+/// 断行规则。Generic 是不分语言的基本规则（游戏自己的自动换行也差不多是这样）；
+/// 按目标语言选其它的，排出来的中日韩文字更符合排版习惯
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum BreakRules {
+    /// 全角字符之间可断，行首不放 `，。` 之类的标点
+    #[default]
+    Generic,
+    /// 在 Generic 之上：行尾不放开括号/开引号，全角数字之间、不换行空格处不断
+    Chinese,
+    /// 在 Chinese 之上：小假名、长音 `ー`、叠字符 `々` 不放在行首
+    Japanese,
+    /// 韩文词之间有空格，只在空格处断（韩文音节之间不断），其余同 Chinese
+    Korean,
+}
+
+impl BreakRules {
+    /// 按语言代码（`zh-CN`、`ja`、`ko_KR` 等）选规则；其它语言用 Generic
+    pub fn for_language(code: &str) -> BreakRules {
+        let lang = code
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "zh" => BreakRules::Chinese,
+            "ja" => BreakRules::Japanese,
+            "ko" => BreakRules::Korean,
+            _ => BreakRules::Generic,
+        }
+    }
+
+    fn no_line_start(self, c: char) -> bool {
+        NO_LINE_START.contains(c)
+            || (self != BreakRules::Generic && CJK_NO_LINE_START.contains(c))
+            || (self == BreakRules::Japanese && JA_NO_LINE_START.contains(c))
+    }
+
+    fn no_line_end(self, c: char) -> bool {
+        self != BreakRules::Generic && NO_LINE_END.contains(c)
+    }
+
+    /// 空白里不能断的（法文数字分组、`100 km` 这类用不换行空格连起来的）
+    fn keeps_together(self, c: char) -> bool {
+        self != BreakRules::Generic && matches!(c, '\u{00A0}' | '\u{2007}' | '\u{202F}')
+    }
+
+    /// 相邻的两个可见字符 l、c 之间能不能断
+    fn can_break(self, l: char, c: char) -> bool {
+        if !(is_wide(l) || is_wide(c)) || self.no_line_start(c) || self.no_line_end(l) {
+            return false;
+        }
+        match self {
+            BreakRules::Generic => true,
+            _ if is_fullwidth_digit(l) && is_fullwidth_digit(c) => false,
+            BreakRules::Korean => !(is_hangul(l) || is_hangul(c)),
+            _ => true,
+        }
+    }
+}
+
+fn is_fullwidth_digit(c: char) -> bool {
+    ('０'..='９').contains(&c) || matches!(c, '．' | '，')
+}
+
+fn is_hangul(c: char) -> bool {
+    matches!(c as u32, 0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7A3)
+}
+
+/// 每一行（按 `~n~` 分）的显示宽度。tokens 为游戏预设（含项目自定义）的 token 定义
+pub fn line_widths(value: &str, font: &FontDef, tokens: &[TokenDef]) -> Vec<u32> {
+    let mut out = vec![0];
+    for seg in segments(value) {
+        match seg {
+            Segment::Token(t) if t.eq_ignore_ascii_case(LINE_BREAK) => out.push(0),
+            Segment::Token(t) => *out.last_mut().unwrap() += token_width(tokens, t),
+            Segment::Text(t) => *out.last_mut().unwrap() += text_width(t, font),
+            Segment::Escape(e) => *out.last_mut().unwrap() += text_width(&unescape(e), font),
+        }
+    }
+    out
+}
+
+fn text_width(s: &str, font: &FontDef) -> u32 {
+    s.chars().map(|c| font.char_width(c)).sum()
+}
+
+enum Item {
+    /// space 为词前的空白（行首时丢掉）
+    Word {
+        space: String,
+        text: String,
+        width: u32,
+    },
+    Break,
+}
+
+/// 重新断行，使每行宽度不超过 max_width。所有行都放得下时原样返回。
+///
+/// keep_breaks 为 false 时单个 `~n~` 当作空白重排，连续多个（分段）保留；
+/// 为 true 时原有的 `~n~` 都保留，只在超宽的行里插入新的
+pub fn wrap_value(
+    value: &str,
+    font: &FontDef,
+    tokens: &[TokenDef],
+    max_width: u32,
+    keep_breaks: bool,
+) -> String {
+    wrap_value_with(
+        value,
+        font,
+        tokens,
+        max_width,
+        keep_breaks,
+        BreakRules::Generic,
+    )
+}
+
+/// 与 wrap_value 相同，按指定语言的断行规则
+pub fn wrap_value_with(
+    value: &str,
+    font: &FontDef,
+    tokens: &[TokenDef],
+    max_width: u32,
+    keep_breaks: bool,
+    rules: BreakRules,
+) -> String {
+    if line_widths(value, font, tokens)
+        .iter()
+        .all(|w| *w <= max_width)
+    {
+        return value.to_string();
+    }
+
+    let space_width = |s: &str| text_width(s, font);
+    let mut out = String::new();
+    let mut line = 0u32;
+    let mut line_empty = true;
+    for item in split_items(value, font, tokens, keep_breaks, rules) {
+        match item {
+            Item::Break => {
+                out.push_str(LINE_BREAK);
+                line = 0;
+                line_empty = true;
+            }
+            Item::Word { space, text, width } => {
+                let gap = space_width(&space);
+                if !line_empty && line + gap + width > max_width {
+                    out.push_str(LINE_BREAK);
+                    line = 0;
+                } else if !line_empty || out.is_empty() {
+                    out.push_str(&space);
+                    line += gap;
+                }
+                out.push_str(&text);
+                line += width;
+                line_empty = false;
+            }
+        }
+    }
+    out
+}
+
+/// 把 value 切成不可再分的词与强制换行
+fn split_items(
+    value: &str,
+    font: &FontDef,
+    tokens: &[TokenDef],
+    keep_breaks: bool,
+    rules: BreakRules,
+) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut space = String::new();
+    let mut text = String::new();
+    let mut width = 0u32;
+    // 当前词最后一个可见字符
+    let mut last: Option<char> = None;
+    let mut breaks = 0usize;
+
+    let flush = |items: &mut Vec<Item>, space: &mut String, text: &mut String, width: &mut u32| {
+        if !text.is_empty() {
+            items.push(Item::Word {
+                space: mem::take(space),
+                text: mem::take(text),
+                width: mem::take(width),
+            });
+        }
+    };
+    // 前面攒下的 ~n~：单个且可重排时变成空白，否则原样保留
+    let resolve =
+        |items: &mut Vec<Item>, space: &mut String, breaks: &mut usize| match mem::take(breaks) {
+            0 => {}
+            1 if !keep_breaks => {
+                if space.is_empty() {
+                    space.push(' ');
+                }
+            }
+            n => {
+                space.clear();
+                items.extend((0..n).map(|_| Item::Break));
+            }
+        };
+
+    for seg in segments(value) {
+        match seg {
+            Segment::Token(t) if t.eq_ignore_ascii_case(LINE_BREAK) => {
+                flush(&mut items, &mut space, &mut text, &mut width);
+                last = None;
+                breaks += 1;
+            }
+            Segment::Token(t) => {
+                if text.is_empty() {
+                    resolve(&mut items, &mut space, &mut breaks);
+                }
+                text.push_str(t);
+                width += token_width(tokens, t);
+            }
+            Segment::Escape(e) => {
+                if text.is_empty() {
+                    resolve(&mut items, &mut space, &mut breaks);
+                }
+                let c = unescape(e);
+                text.push_str(e);
+                width += text_width(&c, font);
+                last = c.chars().last();
+            }
+            Segment::Text(t) => {
+                for c in t.chars() {
+                    if c.is_whitespace() && !rules.keeps_together(c) {
+                        flush(&mut items, &mut space, &mut text, &mut width);
+                        last = None;
+                        space.push(c);
+                        continue;
+                    }
+                    let can_break = last.is_some_and(|l| rules.can_break(l, c));
+                    if can_break {
+                        flush(&mut items, &mut space, &mut text, &mut width);
+                    }
+                    if text.is_empty() {
+                        resolve(&mut items, &mut space, &mut breaks);
+                    }
+                    text.push(c);
+                    width += font.char_width(c);
+                    last = Some(c);
+                }
+            }
+        }
+    }
+    flush(&mut items, &mut space, &mut text, &mut width);
+    // 末尾的 ~n~ 都保留
+    items.extend((0..breaks).map(|_| Item::Break));
+    items
+}
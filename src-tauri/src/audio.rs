@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+use tauri::{AppHandle, Manager};
+
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+use crate::task::Tasks;
+
+/// 读 WAV（RIFF）头算时长；不是 PCM WAV 或头损坏时返回 None
+pub(crate) fn wav_duration_ms(path: &Path) -> Option<u64> {
+    let mut f = File::open(path).ok()?;
+    let mut riff = [0u8; 12];
+    f.read_exact(&mut riff).ok()?;
+    if &riff[0..4] != b"RIFF" || &riff[8..12] != b"WAVE" {
+        return None;
+    }
+
+    let mut byte_rate = None;
+    loop {
+        let mut head = [0u8; 8];
+        f.read_exact(&mut head).ok()?;
+        let size = u32::from_le_bytes([head[4], head[5], head[6], head[7]]) as u64;
+        match &head[0..4] {
+            b"fmt " if size >= 12 => {
+                let mut fmt = [0u8; 12];
+                f.read_exact(&mut fmt).ok()?;
+                byte_rate = Some(u32::from_le_bytes([fmt[8], fmt[9], fmt[10], fmt[11]]) as u64);
+                f.seek(SeekFrom::Current(size as i64 - 12 + (size & 1) as i64))
+                    .ok()?;
+            }
+            b"data" => {
+                let rate = byte_rate.filter(|&r| r > 0)?;
+                return Some(size * 1000 / rate);
+            }
+            _ => {
+                // chunk 按偶数字节对齐
+                f.seek(SeekFrom::Current((size + (size & 1)) as i64)).ok()?;
+            }
+        }
+    }
+}
+
+/// 扫描音频目录时的选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioScanOptions {
+    /// 游戏的音频目录（可多个，如 `audio`、`audio/sfx`）
+    pub dirs: Vec<String>,
+    /// 参与匹配的扩展名（不含点，不分大小写）
+    #[serde(default = "default_extensions")]
+    pub extensions: Vec<String>,
+    /// 文件名（不含扩展名）命名规则，`{key}` 代表 key，`*` 代表任意字符；不分大小写。
+    /// 例：`{key}`、`{key}_*`、`*_{key}`
+    #[serde(default = "default_patterns")]
+    pub patterns: Vec<String>,
+    /// 只检查匹配该正则的 key（对白表的 key）；缺省检查全部
+    #[serde(default)]
+    pub key_filter: Option<String>,
+    #[serde(default = "default_recursive")]
+    pub recursive: bool,
+}
+
+fn default_extensions() -> Vec<String> {
+    ["wav", "mp3", "ogg", "adf"].map(String::from).to_vec()
+}
+fn default_patterns() -> Vec<String> {
+    vec!["{key}".into()]
+}
+fn default_recursive() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioLink {
+    pub key: String,
+    pub files: Vec<String>,
+    /// 第一个 WAV 的时长（其它格式不解析）
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioReport {
+    /// 有音频的条目
+    pub linked: Vec<AudioLink>,
+    /// 没找到音频的条目
+    pub missing: Vec<String>,
+    /// 没有对应 key 的音频文件
+    pub orphan_files: Vec<String>,
+}
+
+fn collect_files(dir: &Path, recursive: bool, exts: &[String], out: &mut Vec<PathBuf>) {
+    let Ok(read) = fs::read_dir(dir) else {
+        return;
+    };
+    for item in read.flatten() {
+        let path = item.path();
+        if path.is_dir() {
+            if recursive {
+                collect_files(&path, recursive, exts, out);
+            }
+            continue;
+        }
+        let matches_ext = path
+            .extension()
+            .and_then(|x| x.to_str())
+            .is_some_and(|x| exts.iter().any(|e| e.eq_ignore_ascii_case(x)));
+        if matches_ext {
+            out.push(path);
+        }
+    }
+}
+
+/// 命名规则 -> 正则；`{key}` 变成捕获组，只认 key 可能用到的字符
+fn pattern_regex(pattern: &str) -> Result<Regex, String> {
+    if !pattern.contains("{key}") {
+        return Err(format!(
+            "Audio naming pattern must contain {{key}}: {pattern}"
+        ));
+    }
+    let mut re = String::from("(?i)^");
+    for (i, part) in pattern.split("{key}").enumerate() {
+        if i > 0 {
+            re.push_str("(?P<key>[^.]+?)");
+        }
+        let parts: Vec<String> = part.split('*').map(regex::escape).collect();
+        re.push_str(&parts.join(".*"));
+    }
+    re.push('$');
+    Regex::new(&re).map_err(|e| format!("Invalid audio naming pattern {pattern}: {e}"))
+}
+
+/// 扫描游戏音频目录，按命名规则把对白 key 与音频文件对应起来（配音项目查漏用）。
+/// 给了 task_id 时按文件数推送进度，可取消
+#[tauri::command]
+pub async fn gxt_audio_scan(
+    app: AppHandle,
+    doc_id: DocId,
+    options: AudioScanOptions,
+    task_id: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<AudioReport, String> {
+    let mut task = app.state::<Tasks>().start(&app, task_id)?;
+    let filter = options
+        .key_filter
+        .as_deref()
+        .map(Regex::new)
+        .transpose()
+        .map_err(|e| format!("Invalid key filter: {e}"))?;
+    let keys: Vec<String> = session.with(doc_id, |open| {
+        Ok(open
+            .doc
+            .entries
+            .iter()
+            .map(|e| e.key.clone())
+            .filter(|k| filter.as_ref().is_none_or(|f| f.is_match(k)))
+            .collect())
+    })?;
+    let patterns = options
+        .patterns
+        .iter()
+        .map(String::as_str)
+        .map(pattern_regex)
+        .collect::<Result<Vec<_>, String>>()?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut files = Vec::new();
+        for dir in &options.dirs {
+            collect_files(
+                Path::new(dir),
+                options.recursive,
+                &options.extensions,
+                &mut files,
+            );
+        }
+        files.sort();
+
+        let by_upper: HashMap<String, &str> = keys
+            .iter()
+            .map(|k| (k.to_uppercase(), k.as_str()))
+            .collect();
+        let mut found: HashMap<&str, Vec<PathBuf>> = HashMap::new();
+        let mut orphan_files = Vec::new();
+        let total = files.len();
+        for (i, file) in files.into_iter().enumerate() {
+            task.step(i, total)?;
+            let Some(stem) = file.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let key = patterns.iter().find_map(|re| {
+                let caps = re.captures(stem)?;
+                by_upper.get(&caps["key"].to_uppercase()).copied()
+            });
+            match key {
+                Some(k) => found.entry(k).or_default().push(file),
+                None => orphan_files.push(file.to_string_lossy().into_owned()),
+            }
+        }
+
+        let mut linked = Vec::new();
+        let mut missing = Vec::new();
+        for key in &keys {
+            match found.remove(key.as_str()) {
+                Some(paths) => {
+                    let duration_ms = paths.iter().find_map(|p| wav_duration_ms(p.as_path()));
+                    linked.push(AudioLink {
+                        key: key.clone(),
+                        files: paths
+                            .iter()
+                            .map(|p| p.to_string_lossy().into_owned())
+                            .collect(),
+                        duration_ms,
+                    });
+                }
+                None => missing.push(key.clone()),
+            }
+        }
+        task.progress(total, total);
+        Ok::<_, String>(AudioReport {
+            linked,
+            missing,
+            orphan_files,
+        })
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use crate::gxt;
+use crate::session::{DocId, Session};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AutofillResult {
+    /// 同 key 直接采用参考译文的条目
+    pub filled: Vec<String>,
+    /// 填了但要复核的条目（标为 fuzzy）：按原文匹配到的，或参考译文对应的原文已经变了
+    pub fuzzy: Vec<String>,
+    /// 锁定而跳过的条目
+    pub skipped_locked: Vec<String>,
+}
+
+/// 用另一份已翻译的 GXT（如上一版 mod 的译文）填充文档。
+///
+/// - 同 key：直接用参考译文
+/// - 给了 reference_source_path（参考译文当时依据的原文）时：
+///   - 同 key 但文档里的原文与当时的原文不同：照填，标为 fuzzy
+///   - 参考里没有这个 key，但文档的当前文本等于参考原文里某条：用那条的译文，标为 fuzzy
+#[tauri::command]
+pub async fn gxt_autofill(
+    doc_id: DocId,
+    reference_path: String,
+    reference_source_path: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<AutofillResult, String> {
+    let reference = gxt::load_document(reference_path).await?.entries;
+    let reference_source = match reference_source_path {
+        Some(p) => Some(gxt::load_document(p).await?.entries),
+        None => None,
+    };
+
+    let translated: HashMap<&str, &str> = reference
+        .iter()
+        .filter(|e| !e.value.is_empty())
+        .map(|e| (e.key.as_str(), &*e.value))
+        .collect();
+    let old_source: HashMap<&str, &str> = reference_source
+        .iter()
+        .flatten()
+        .map(|e| (e.key.as_str(), &*e.value))
+        .collect();
+    // 原文文本 -> 参考译文（同一原文有多条时取第一条）
+    let mut by_source: HashMap<&str, &str> = HashMap::new();
+    for e in reference_source.iter().flatten() {
+        if let Some(t) = translated.get(e.key.as_str()) {
+            by_source.entry(&*e.value).or_insert(*t);
+        }
+    }
+
+    session.edit(doc_id, |open| {
+        let mut result = AutofillResult::default();
+        let mut fills: Vec<(String, String, bool)> = Vec::new();
+        for e in &open.doc.entries {
+            let current = &*e.value;
+            let fill = match translated.get(e.key.as_str()) {
+                Some(t) => {
+                    let stale = old_source
+                        .get(e.key.as_str())
+                        .is_some_and(|src| *src != current && *t != current);
+                    Some((*t, stale))
+                }
+                None => by_source.get(current).map(|t| (*t, true)),
+            };
+            let Some((value, fuzzy)) = fill else {
+                continue;
+            };
+            if value == current && !fuzzy {
+                continue;
+            }
+            if open.is_locked(&e.key) {
+                result.skipped_locked.push(e.key.clone());
+                continue;
+            }
+            fills.push((e.key.clone(), value.to_string(), fuzzy));
+        }
+
+        for (key, value, fuzzy) in fills {
+            open.set_entry(key.clone(), value);
+            if fuzzy {
+                open.fuzzy.insert(key.clone());
+                result.fuzzy.push(key);
+            } else {
+                open.fuzzy.remove(&key);
+                result.filled.push(key);
+            }
+        }
+        Ok(result)
+    })
+}
@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::AppHandle;
+
+use crate::doc_profile;
+use crate::gxt::{self, GxtEntry};
+use crate::i18n::tr;
+use crate::plugins;
+use crate::presets;
+use crate::rollback::{self, Target};
+use crate::session::Session;
+
+/// 对目录下每个 GXT 做什么
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchOptions {
+    /// 连子目录一起找
+    #[serde(default)]
+    pub recursive: bool,
+    /// 按 lenient 模式读（见 gxt_load），读文件时的问题只算警告
+    #[serde(default)]
+    pub lenient: bool,
+    /// 用这个转换器（见 gxt_plugins_list）导出一份，扩展名按转换器的
+    #[serde(default)]
+    pub export: Option<String>,
+    /// 重新生成 .gxt（整理 TKEY/TDAT，丢掉无主字节）
+    #[serde(default)]
+    pub repack: bool,
+    /// 重新生成时按 key 排序
+    #[serde(default)]
+    pub sort: bool,
+    /// 输出目录，保持相对目录结构；为空时写在原文件旁边（repack 即原地覆盖，覆盖前先备份）
+    #[serde(default)]
+    pub out_dir: Option<String>,
+}
+
+/// 一个文件的结果；读不了的文件也给出一项，error 里说明原因
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchFileResult {
+    pub path: String,
+    pub entries: usize,
+    /// 读文件时的警告条数（lenient 时才会有）
+    pub warnings: usize,
+    /// 校验发现的问题（key 不合法、重复、TDAT 超限等）
+    pub problems: Vec<String>,
+    /// 写出的文件
+    pub outputs: Vec<String>,
+    /// 读不了或写不出时的原因
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchReport {
+    pub files: Vec<BatchFileResult>,
+    /// 没有错误也没有问题的文件数
+    pub ok: usize,
+    /// 跳过的文件（编辑器里打开着、有未保存修改的不原地覆盖）
+    pub skipped: Vec<String>,
+}
+
+/// 批量处理目录下的所有 .gxt：逐个读入、校验，按选项导出或重新打包，给出每个文件的结果。
+/// 一个文件出错不影响其它文件
+#[tauri::command]
+#[tracing::instrument(skip(app, session), err)]
+pub async fn gxt_batch_convert(
+    app: AppHandle,
+    dir: String,
+    options: BatchOptions,
+    session: tauri::State<'_, Session>,
+) -> Result<BatchReport, String> {
+    let root = PathBuf::from(&dir);
+    let recursive = options.recursive;
+    let files = tauri::async_runtime::spawn_blocking(move || gxt_files(&root, recursive))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))??;
+
+    let mut report = BatchReport::default();
+    let in_place = options.repack && options.out_dir.is_none();
+    let mut targets = Vec::new();
+    if in_place {
+        let dirty: Vec<String> = session
+            .list()
+            .into_iter()
+            .filter(|d| d.dirty)
+            .filter_map(|d| d.file_path)
+            .collect();
+        for path in &files {
+            let path = path.to_string_lossy().into_owned();
+            if dirty.contains(&path) {
+                report.skipped.push(path);
+            } else {
+                targets.push(path);
+            }
+        }
+        let backup: Vec<Target> = targets.iter().map(|p| Target::File(p)).collect();
+        rollback::backup_before(&app, "batch-repack", &backup)?;
+    } else {
+        targets = files
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+    }
+
+    for path in targets {
+        let mut res = BatchFileResult {
+            path: path.clone(),
+            ..Default::default()
+        };
+        if let Err(e) = convert_one(&app, &dir, &options, &mut res).await {
+            res.error = Some(e);
+        }
+        if res.error.is_none() && res.problems.is_empty() {
+            report.ok += 1;
+        }
+        report.files.push(res);
+    }
+    tracing::info!(files = report.files.len(), ok = report.ok, "batch done");
+    Ok(report)
+}
+
+async fn convert_one(
+    app: &AppHandle,
+    root: &str,
+    options: &BatchOptions,
+    res: &mut BatchFileResult,
+) -> Result<(), String> {
+    let (doc, warnings) =
+        gxt::load_document_checked(res.path.clone(), None, options.lenient).await?;
+    let mut entries = doc.entries;
+    res.entries = entries.len();
+    res.warnings = warnings.len();
+    if let Err(e) = gxt_core::validate_entries(&entries) {
+        res.problems.push(e);
+    }
+    if let Err(e) = gxt_core::check_tdat_size(&entries) {
+        res.problems.push(e);
+    }
+
+    let source = Path::new(&res.path);
+    if let Some(plugin) = &options.export {
+        let converter = plugins::find(app, plugin)?;
+        let ext = converter
+            .info()
+            .export_extension
+            .clone()
+            .ok_or_else(|| format!("Converter {plugin} does not support export"))?;
+        let out = output_path(root, options.out_dir.as_deref(), source, &ext)?;
+        let mut exported = entries.clone();
+        presets::export_newlines(
+            &mut exported,
+            presets::newline_token(app, Some(&res.path)).as_deref(),
+        );
+        converter.export(&exported, &out)?;
+        res.outputs.push(out.to_string_lossy().into_owned());
+    }
+    if options.repack {
+        if !res.problems.is_empty() {
+            return Err(tr!("repack.has_problems"));
+        }
+        if options.sort {
+            gxt_core::sort_entries(&mut entries);
+        }
+        let out = output_path(root, options.out_dir.as_deref(), source, "gxt")?;
+        fs::write(&out, repack(&res.path, &entries)?)
+            .map_err(|e| tr!("file.write_failed", error = e))?;
+        res.outputs.push(out.to_string_lossy().into_owned());
+    }
+    Ok(())
+}
+
+/// 按文件的编码配置（见 doc_profile）重新生成 GXT
+fn repack(path: &str, entries: &[GxtEntry]) -> Result<Vec<u8>, String> {
+    let encoded = doc_profile::encode_entries(path, entries);
+    gxt_core::build_gxt_bytes(encoded.as_deref().unwrap_or(entries))
+}
+
+/// 输出文件：out_dir 下与 source 相对 root 同样的位置，扩展名换成 ext
+fn output_path(
+    root: &str,
+    out_dir: Option<&str>,
+    source: &Path,
+    ext: &str,
+) -> Result<PathBuf, String> {
+    let out = match out_dir {
+        Some(dir) => {
+            let rel = source.strip_prefix(root).unwrap_or(source);
+            Path::new(dir).join(rel)
+        }
+        None => source.to_path_buf(),
+    }
+    .with_extension(ext);
+    if let Some(parent) = out.parent() {
+        fs::create_dir_all(parent).map_err(|e| tr!("file.write_failed", error = e))?;
+    }
+    Ok(out)
+}
+
+/// 目录下扩展名为 .gxt（不分大小写）的文件，按路径排序
+fn gxt_files(dir: &Path, recursive: bool) -> Result<Vec<PathBuf>, String> {
+    let read = fs::read_dir(dir).map_err(|e| tr!("file.read_failed", error = e))?;
+    let mut out = Vec::new();
+    for item in read.flatten() {
+        let path = item.path();
+        if path.is_dir() {
+            if recursive {
+                out.extend(gxt_files(&path, true)?);
+            }
+        } else if path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("gxt"))
+        {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use gxt_core::{GroupLevel, KeyGroup};
+
+use crate::gxt;
+use crate::i18n::{self, tr};
+use crate::session::{DocId, Session};
+
+enum Change<'a> {
+    Added(&'a str),
+    Changed(&'a str, &'a str),
+    Removed(&'a str),
+}
+
+impl Change<'_> {
+    /// 节内的顺序：新增、修改、删除
+    fn order(&self) -> usize {
+        match self {
+            Change::Added(_) => 0,
+            Change::Changed(..) => 1,
+            Change::Removed(_) => 2,
+        }
+    }
+
+    fn describe(&self) -> String {
+        match *self {
+            Change::Added(v) | Change::Removed(v) => code(v),
+            Change::Changed(old, new) => format!("{} → {}", code(old), code(new)),
+        }
+    }
+}
+
+/// 文档（含未保存修改）相对上一版发布文件的更新日志（Markdown），给 mod 发布说明用。
+/// 按 key 前缀分节（level 缺省为前 4 个字符），每节内分新增/修改/删除
+#[tauri::command]
+pub async fn gxt_changelog(
+    doc_id: DocId,
+    previous_path: String,
+    title: Option<String>,
+    level: Option<GroupLevel>,
+    session: tauri::State<'_, Session>,
+) -> Result<String, String> {
+    let previous = gxt::load_document(previous_path).await?.entries;
+    let diff = session.with(doc_id, |open| {
+        Ok(gxt_core::diff_entries(&previous, &open.doc.entries))
+    })?;
+
+    let mut changes: HashMap<&str, Change> = HashMap::new();
+    for e in &diff.added {
+        changes.insert(&e.key, Change::Added(&e.value));
+    }
+    for c in &diff.changed {
+        changes.insert(&c.key, Change::Changed(&c.old, &c.new));
+    }
+    for e in &diff.removed {
+        changes.insert(&e.key, Change::Removed(&e.value));
+    }
+
+    let title = title.unwrap_or_else(|| tr!("changelog.title"));
+    let mut out = format!("# {title}\n\n");
+    if diff.is_empty() {
+        out.push_str(&tr!("changelog.no_changes"));
+        out.push('\n');
+        return Ok(out);
+    }
+    out.push_str(&tr!(
+        "changelog.summary",
+        added = diff.added.len(),
+        changed = diff.changed.len(),
+        removed = diff.removed.len()
+    ));
+    out.push('\n');
+
+    let level = level.unwrap_or(GroupLevel::Prefix(4));
+    let root = gxt_core::group_keys(changes.keys().copied(), &[level]);
+    let mut sections: Vec<(String, Vec<String>)> = root
+        .children
+        .iter()
+        .map(|g| {
+            let mut keys = Vec::new();
+            collect_keys(g, &mut keys);
+            (g.name.clone(), keys)
+        })
+        .collect();
+    if !root.keys.is_empty() {
+        sections.push((tr!("label.other"), root.keys.clone()));
+    }
+    // 只有一节时不分节
+    let headed = sections.len() > 1;
+
+    for (name, keys) in &sections {
+        if headed {
+            out.push_str(&format!("\n## {name}\n"));
+        }
+        let heading = if headed { "###" } else { "##" };
+        for (i, label) in ["label.added", "label.changed", "label.removed"]
+            .into_iter()
+            .enumerate()
+        {
+            let lines: Vec<String> = keys
+                .iter()
+                .filter_map(|k| Some((k, changes.get(k.as_str())?)))
+                .filter(|(_, c)| c.order() == i)
+                .map(|(k, c)| format!("- **{k}**: {}\n", c.describe()))
+                .collect();
+            if lines.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{heading} {}\n\n", i18n::translate(label, &[])));
+            out.extend(lines);
+        }
+    }
+    Ok(out)
+}
+
+fn collect_keys(g: &KeyGroup, out: &mut Vec<String>) {
+    for c in &g.children {
+        collect_keys(c, out);
+    }
+    out.extend(g.keys.iter().cloned());
+}
+
+/// 行内代码；值里的 `~r~` 之类不会被当成删除线，含反引号时用双反引号
+fn code(value: &str) -> String {
+    if value.is_empty() {
+        format!("*{}*", tr!("label.empty"))
+    } else if value.contains('`') {
+        format!("`` {value} ``")
+    } else {
+        format!("`{value}`")
+    }
+}
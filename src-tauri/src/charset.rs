@@ -0,0 +1,41 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+use std::fs;
+
+use gxt_core::CharsetFormat;
+
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharsetExport {
+    /// 用到的字符数（即要做的字形数）
+    pub chars: usize,
+    /// 合并成的连续区段数
+    pub ranges: usize,
+}
+
+/// 导出一个或几个文档用到的字符集给做字库的人（区段清单或 FontForge 选择脚本）。
+/// 几个文档时取并集：同一套字库给 american.gxt 和任务文本共用
+#[tauri::command]
+pub fn gxt_export_fontforge_charset(
+    doc_ids: Vec<DocId>,
+    path: String,
+    format: CharsetFormat,
+    session: tauri::State<'_, Session>,
+) -> Result<CharsetExport, String> {
+    let mut chars = BTreeSet::new();
+    for doc_id in doc_ids {
+        session.with(doc_id, |open| {
+            chars.extend(gxt_core::used_chars(&open.doc.entries));
+            Ok(())
+        })?;
+    }
+    let text = gxt_core::render_charset(&chars, format);
+    fs::write(&path, text).map_err(|e| tr!("file.write_failed", error = e))?;
+    Ok(CharsetExport {
+        chars: chars.len(),
+        ranges: gxt_core::char_ranges(&chars).len(),
+    })
+}
@@ -0,0 +1,304 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashSet, VecDeque};
+use std::sync::Mutex;
+
+use tauri::AppHandle;
+
+use crate::i18n::tr;
+use crate::merge_report::{self, Decision, MergeReport};
+use crate::meta;
+use crate::project::Projects;
+use crate::rollback::{self, Target};
+use crate::session::{DocId, Session};
+
+/// 最多留几份；再复制时丢掉最早的
+const RING_SIZE: usize = 16;
+
+/// 复制下来的一组条目
+#[derive(Debug, Clone)]
+struct Clip {
+    name: String,
+    /// 从哪个文件复制的（未保存过的文档为 None）
+    source: Option<String>,
+    /// Unix 秒
+    at: u64,
+    entries: Vec<(String, String)>,
+}
+
+#[derive(Debug, Default)]
+struct Ring {
+    /// 最近的在最前
+    clips: VecDeque<Clip>,
+    /// 没给名字时按顺序编号
+    next: u32,
+}
+
+/// 条目剪贴板（tauri managed state）：只在本次运行期间有效，不经过系统剪贴板，
+/// 在打开的文档之间搬条目时 key 与转义原样保留
+#[derive(Debug, Default)]
+pub struct Clipboard {
+    ring: Mutex<Ring>,
+}
+
+impl Clipboard {
+    fn with<T>(&self, f: impl FnOnce(&mut Ring) -> Result<T, String>) -> Result<T, String> {
+        let mut g = self
+            .ring
+            .lock()
+            .map_err(|_| "Clipboard lock poisoned".to_string())?;
+        f(&mut g)
+    }
+
+    /// 放进一份；同名的换掉。返回它的信息
+    fn push(
+        &self,
+        name: Option<String>,
+        source: Option<String>,
+        entries: Vec<(String, String)>,
+    ) -> Result<ClipInfo, String> {
+        self.with(|ring| {
+            let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| {
+                ring.next += 1;
+                ring.next.to_string()
+            });
+            ring.clips.retain(|c| c.name != name);
+            let clip = Clip {
+                name,
+                source,
+                at: meta::now_secs(),
+                entries,
+            };
+            let info = ClipInfo::from(&clip);
+            ring.clips.push_front(clip);
+            ring.clips.truncate(RING_SIZE);
+            Ok(info)
+        })
+    }
+
+    /// name 对应的一份；没给名字时取最近的
+    fn get(&self, name: Option<&str>) -> Result<Clip, String> {
+        self.with(|ring| match name {
+            Some(name) => ring
+                .clips
+                .iter()
+                .find(|c| c.name == name)
+                .cloned()
+                .ok_or_else(|| tr!("clip.not_found", name = name)),
+            None => ring.clips.front().cloned().ok_or_else(|| tr!("clip.empty")),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClipInfo {
+    pub name: String,
+    pub source: Option<String>,
+    pub at: u64,
+    pub keys: Vec<String>,
+}
+
+impl From<&Clip> for ClipInfo {
+    fn from(clip: &Clip) -> Self {
+        ClipInfo {
+            name: clip.name.clone(),
+            source: clip.source.clone(),
+            at: clip.at,
+            keys: clip.entries.iter().map(|(k, _)| k.clone()).collect(),
+        }
+    }
+}
+
+/// 粘贴时目标文档里已经有同名 key 怎么办
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PasteConflict {
+    /// 有冲突就什么都不贴，只报告冲突的 key（由前端问用户）
+    #[default]
+    Abort,
+    /// 跳过同名的，只贴新的
+    Skip,
+    /// 覆盖同名的
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PasteReport {
+    /// 贴进去的 key（含覆盖的）
+    pub pasted: Vec<String>,
+    /// 覆盖了原有值的 key
+    pub overwritten: Vec<String>,
+    /// 目标文档里已有、没有贴的 key（Abort 时有它就什么都没贴）
+    pub conflicts: Vec<String>,
+    /// 目标文档里锁定着、没有贴的 key
+    pub locked: Vec<String>,
+    /// 目标文档里软删除过、没有贴回去的 key
+    pub deleted: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CutReport {
+    #[serde(flatten)]
+    pub clip: ClipInfo,
+    /// 已复制但没能删掉的 key（锁定的或项目保护的）
+    pub kept: Vec<String>,
+}
+
+/// 复制文档里的一组 key 到剪贴板；name 为空时自动编号，同名的换掉
+#[tauri::command]
+pub fn gxt_clip_copy(
+    doc_id: DocId,
+    keys: Vec<String>,
+    name: Option<String>,
+    session: tauri::State<'_, Session>,
+    clipboard: tauri::State<'_, Clipboard>,
+) -> Result<ClipInfo, String> {
+    let (source, entries) = pick(&session, doc_id, &keys)?;
+    clipboard.push(name, source, entries)
+}
+
+/// 剪切：复制后从文档里删掉（整批算一步撤销）；锁定的、项目保护的只复制不删
+#[tauri::command]
+pub fn gxt_clip_cut(
+    app: AppHandle,
+    doc_id: DocId,
+    keys: Vec<String>,
+    name: Option<String>,
+    session: tauri::State<'_, Session>,
+    projects: tauri::State<'_, Projects>,
+    clipboard: tauri::State<'_, Clipboard>,
+) -> Result<CutReport, String> {
+    let (source, entries) = pick(&session, doc_id, &keys)?;
+    // 只读打开的文档只能复制
+    session.edit(doc_id, |_| Ok(()))?;
+    rollback::backup_before(&app, "cut-entries", &[Target::Document(doc_id)])?;
+    let kept = session.edit(doc_id, |open| {
+        let own_batch = !open.in_batch();
+        if own_batch {
+            open.begin_batch()?;
+        }
+        let mut kept = Vec::new();
+        for (key, _) in &entries {
+            let protected = projects
+                .check_removal(open.doc.file_path.as_deref(), key, false)
+                .is_err();
+            if protected || !open.remove_entry(key) {
+                kept.push(key.clone());
+            }
+        }
+        if own_batch {
+            open.commit_batch()?;
+        }
+        Ok(kept)
+    })?;
+    let clip = clipboard.push(name, source, entries)?;
+    Ok(CutReport { clip, kept })
+}
+
+/// 剪贴板里的内容，最近的在最前
+#[tauri::command]
+pub fn gxt_clip_list(clipboard: tauri::State<'_, Clipboard>) -> Result<Vec<ClipInfo>, String> {
+    clipboard.with(|ring| Ok(ring.clips.iter().map(ClipInfo::from).collect()))
+}
+
+/// 把剪贴板里的一份（name 为空时取最近的）贴到文档里，整批算一步撤销
+#[tauri::command]
+pub fn gxt_clip_paste(
+    app: AppHandle,
+    doc_id: DocId,
+    name: Option<String>,
+    conflict: Option<PasteConflict>,
+    session: tauri::State<'_, Session>,
+    clipboard: tauri::State<'_, Clipboard>,
+) -> Result<PasteReport, String> {
+    let clip = clipboard.get(name.as_deref())?;
+    let conflict = conflict.unwrap_or_default();
+    let mut report = PasteReport::default();
+    let existing: HashSet<String> = session.edit(doc_id, |open| {
+        Ok(open.doc.entries.iter().map(|e| e.key.clone()).collect())
+    })?;
+    report.conflicts = clip
+        .entries
+        .iter()
+        .filter(|(k, _)| existing.contains(k))
+        .map(|(k, _)| k.clone())
+        .collect();
+    if conflict == PasteConflict::Abort && !report.conflicts.is_empty() {
+        return Ok(report);
+    }
+    if conflict == PasteConflict::Overwrite {
+        report.overwritten = std::mem::take(&mut report.conflicts);
+    }
+
+    rollback::backup_before(&app, "paste-entries", &[Target::Document(doc_id)])?;
+    let (report, audit) = session.edit(doc_id, |open| {
+        let own_batch = !open.in_batch();
+        if own_batch {
+            open.begin_batch()?;
+        }
+        let mut audit = MergeReport::new("paste-entries", open.doc.file_path.clone(), clip.source);
+        for (key, value) in clip.entries {
+            if report.conflicts.contains(&key) {
+                audit.push(&key, Decision::Kept, Some("already in document".into()));
+                continue;
+            }
+            if open.is_locked(&key) {
+                audit.push(&key, Decision::Kept, Some("locked".into()));
+                report.overwritten.retain(|k| *k != key);
+                report.locked.push(key);
+                continue;
+            }
+            if !open.merge_entry(key.clone(), value) {
+                audit.push(&key, Decision::Skipped, Some("tombstoned".into()));
+                report.deleted.push(key);
+                continue;
+            }
+            let decision = if report.overwritten.contains(&key) {
+                Decision::Overwritten
+            } else {
+                Decision::Added
+            };
+            audit.push(&key, decision, None);
+            report.pasted.push(key);
+        }
+        if own_batch {
+            open.commit_batch()?;
+        }
+        Ok((report, audit))
+    })?;
+    merge_report::record(&app, &audit);
+    Ok(report)
+}
+
+/// 清掉剪贴板里的一份；name 为空时全部清掉
+#[tauri::command]
+pub fn gxt_clip_clear(
+    name: Option<String>,
+    clipboard: tauri::State<'_, Clipboard>,
+) -> Result<(), String> {
+    clipboard.with(|ring| {
+        match name {
+            Some(name) => ring.clips.retain(|c| c.name != name),
+            None => ring.clips.clear(),
+        }
+        Ok(())
+    })
+}
+
+/// 来源文件与 (key, value) 列表
+type Picked = (Option<String>, Vec<(String, String)>);
+
+/// 按文档里的顺序取出 keys 对应的条目
+fn pick(session: &Session, doc_id: DocId, keys: &[String]) -> Result<Picked, String> {
+    session.with(doc_id, |open| {
+        let wanted: HashSet<&str> = keys.iter().map(String::as_str).collect();
+        let entries = open
+            .doc
+            .entries
+            .iter()
+            .filter(|e| wanted.contains(e.key.as_str()))
+            .map(|e| (e.key.clone(), e.value.to_string()))
+            .collect();
+        Ok((open.doc.file_path.clone(), entries))
+    })
+}
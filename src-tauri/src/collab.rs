@@ -0,0 +1,998 @@
+//! 局域网协作：一个实例把打开的文档开放到局域网上（host），其他实例连上来（join），
+//! 两个译者同时翻一个文件，不用来回传文件。
+//!
+//! - 条目级的认领（claim）由 host 管：一条被别人认领着的时候自己改不了，
+//!   认领几分钟不续期就自动失效（对方断线也不会一直占着）
+//! - host 上的所有修改（本地的和 guest 发来的）按顺序记进变更序列，
+//!   guest 轮询 `/changes` 取回，应用到本地的只读副本上
+//! - 保存只在 host 上进行；guest 的副本只读，改条目走 gxt_collab_set_entry 等命令
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Read;
+use std::net::UdpSocket;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use gxt_core::percent_decode;
+use tauri::{AppHandle, Emitter, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::events::DocEvent;
+use crate::gxt::{self, GxtDocument, GxtEntry};
+use crate::http_server::{header_value, make_token, token_matches, TOKEN_HEADER};
+use crate::i18n::tr;
+use crate::meta::{self, now_secs};
+use crate::project::Projects;
+use crate::session::{DocId, OpenDocument, Session};
+
+/// 认领的条目变了；载荷是全部认领（Vec<Claim>）
+pub const EVENT_COLLAB_CLAIMS: &str = "gxt://collab-claims";
+/// guest 与 host 断开了（host 停了、关了文档或连不上）；载荷是原因
+pub const EVENT_COLLAB_ENDED: &str = "gxt://collab-ended";
+
+const DEFAULT_PORT: u16 = 7411;
+/// 认领不续期多久后失效（秒）
+const CLAIM_TTL_SECS: u64 = 300;
+/// host 保留的变更条数；落后更多的 guest 整体重新拉取
+const LOG_LIMIT: usize = 5000;
+const POLL_INTERVAL: Duration = Duration::from_millis(800);
+/// guest 连续这么多次取不到变更就算断开
+const MAX_POLL_FAILURES: u32 = 10;
+const PEER_HEADER: &str = "X-Gxt-Peer";
+/// 请求体的上限（字节）；一条译文远用不到这么多
+const MAX_BODY: u64 = 1 << 20;
+/// host 本机的译者在认领里的 id
+const HOST_PEER: &str = "host";
+
+/// 一条认领：peer 在改 key
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Claim {
+    pub key: String,
+    pub peer: String,
+    /// 显示给别人的名字
+    pub name: String,
+    /// 最后一次认领/续期，Unix 秒
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Peer {
+    pub id: String,
+    pub name: String,
+    /// Unix 秒
+    pub last_seen: u64,
+}
+
+/// host 变更序列里的一条
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollabChange {
+    pub seq: u64,
+    #[serde(flatten)]
+    pub event: DocEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "role", rename_all = "kebab-case")]
+pub enum CollabStatus {
+    Off,
+    Host {
+        doc_id: DocId,
+        port: u16,
+        /// 本机在局域网上的地址（猜不出来时为 None），连同 port 与 token 告诉对方
+        address: Option<String>,
+        token: String,
+        peers: Vec<Peer>,
+        claims: Vec<Claim>,
+    },
+    Guest {
+        doc_id: DocId,
+        address: String,
+        peer: String,
+        claims: Vec<Claim>,
+    },
+}
+
+// -------------------- Host --------------------
+
+/// host 的协作状态；HTTP 线程与命令共用。
+/// 加锁顺序：Session -> Collab.host -> Shared，持有 Shared 时不能再碰 Session
+struct Shared {
+    doc_id: DocId,
+    token: String,
+    name: String,
+    seq: u64,
+    log: VecDeque<CollabChange>,
+    peers: BTreeMap<String, Peer>,
+    next_peer: u64,
+    claims: BTreeMap<String, Claim>,
+}
+
+impl Shared {
+    fn expire(&mut self) {
+        let now = now_secs();
+        self.claims
+            .retain(|_, c| now.saturating_sub(c.at) < CLAIM_TTL_SECS);
+    }
+
+    fn claim_list(&mut self) -> Vec<Claim> {
+        self.expire();
+        self.claims.values().cloned().collect()
+    }
+
+    fn peer_name(&self, peer: &str) -> String {
+        match self.peers.get(peer) {
+            Some(p) => p.name.clone(),
+            None => self.name.clone(),
+        }
+    }
+
+    /// 别人认领着 key 时报错
+    fn check_write(&mut self, key: &str, peer: &str) -> Result<(), String> {
+        self.expire();
+        match self.claims.get(key) {
+            Some(c) if c.peer != peer => Err(format!("{key} is being edited by {}", c.name)),
+            _ => Ok(()),
+        }
+    }
+
+    /// 认领或续期
+    fn claim(&mut self, key: &str, peer: &str) -> Result<(), String> {
+        self.check_write(key, peer)?;
+        let name = self.peer_name(peer);
+        self.claims.insert(
+            key.to_string(),
+            Claim {
+                key: key.to_string(),
+                peer: peer.to_string(),
+                name,
+                at: now_secs(),
+            },
+        );
+        Ok(())
+    }
+
+    /// 放开自己的认领；返回是否真的放开了
+    fn release(&mut self, key: &str, peer: &str) -> bool {
+        match self.claims.get(key) {
+            Some(c) if c.peer == peer => self.claims.remove(key).is_some(),
+            _ => false,
+        }
+    }
+
+    fn record(&mut self, events: &[DocEvent]) {
+        for event in events {
+            let keep = matches!(
+                event,
+                DocEvent::EntryAdded { .. }
+                    | DocEvent::EntryChanged { .. }
+                    | DocEvent::EntryDeleted { .. }
+                    | DocEvent::DocumentReplaced { .. }
+                    | DocEvent::DocumentClosed
+            );
+            if !keep {
+                continue;
+            }
+            self.seq += 1;
+            self.log.push_back(CollabChange {
+                seq: self.seq,
+                event: event.clone(),
+            });
+            if self.log.len() > LOG_LIMIT {
+                self.log.pop_front();
+            }
+        }
+    }
+
+    /// since 之后的变更；返回 None 表示 guest 落后太多，要整体重新拉取
+    fn since(&self, since: u64) -> Option<Vec<CollabChange>> {
+        let oldest = self.log.front().map_or(self.seq + 1, |c| c.seq);
+        if since + 1 < oldest {
+            return None;
+        }
+        Some(self.log.iter().filter(|c| c.seq > since).cloned().collect())
+    }
+}
+
+struct Hosting {
+    shared: Arc<Mutex<Shared>>,
+    server: Arc<Server>,
+    port: u16,
+    thread: JoinHandle<()>,
+}
+
+// -------------------- Guest --------------------
+
+/// guest 连接 host 用的信息
+#[derive(Clone)]
+struct Conn {
+    client: reqwest::Client,
+    base: String,
+    token: String,
+    peer: String,
+}
+
+impl Conn {
+    async fn request<T: DeserializeOwned>(
+        &self,
+        method: reqwest::Method,
+        path: &str,
+        body: Option<serde_json::Value>,
+    ) -> Result<T, String> {
+        let url = format!("{}{path}", self.base);
+        let mut req = self
+            .client
+            .request(method, &url)
+            .header(TOKEN_HEADER, &self.token)
+            .header(PEER_HEADER, &self.peer)
+            .timeout(Duration::from_secs(10));
+        if let Some(b) = body {
+            req = req.json(&b);
+        }
+        let resp = req
+            .send()
+            .await
+            .map_err(|e| format!("Request to {url} failed: {e}"))?;
+        let status = resp.status();
+        let v: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("Host response is not JSON ({status}): {e}"))?;
+        if !status.is_success() {
+            let msg = v["error"].as_str().map(str::to_string);
+            return Err(msg.unwrap_or_else(|| format!("Host returned {status}")));
+        }
+        serde_json::from_value(v).map_err(|e| format!("Unexpected host response: {e}"))
+    }
+}
+
+struct Joined {
+    doc_id: DocId,
+    address: String,
+    conn: Conn,
+    claims: Arc<Mutex<Vec<Claim>>>,
+    stop: Arc<AtomicBool>,
+    thread: JoinHandle<()>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct JoinReply {
+    peer: String,
+    seq: u64,
+    entries: Vec<GxtEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ChangesReply {
+    seq: u64,
+    /// 落后太多，changes 为空，要重新拉取全部条目
+    resync: bool,
+    changes: Vec<CollabChange>,
+    claims: Vec<Claim>,
+}
+
+// -------------------- State --------------------
+
+/// tauri managed state：同一时间只能 host 一份文档，或加入一个 host
+#[derive(Default)]
+pub struct Collab {
+    host: Mutex<Option<Hosting>>,
+    guest: Mutex<Option<Joined>>,
+}
+
+impl Collab {
+    fn hosted(&self) -> Option<Arc<Mutex<Shared>>> {
+        self.host
+            .lock()
+            .ok()
+            .and_then(|g| g.as_ref().map(|h| h.shared.clone()))
+    }
+
+    fn guest_conn(&self) -> Option<(DocId, Conn)> {
+        self.guest
+            .lock()
+            .ok()
+            .and_then(|g| g.as_ref().map(|j| (j.doc_id, j.conn.clone())))
+    }
+
+    /// events::dispatch 调用：host 的文档变了就记进变更序列
+    pub fn record(&self, doc_id: DocId, events: &[DocEvent]) {
+        let Some(shared) = self.hosted() else {
+            return;
+        };
+        if let Ok(mut s) = shared.lock() {
+            if s.doc_id == doc_id {
+                s.record(events);
+            }
+        };
+    }
+
+    /// 本机直接改 host 的文档时（gxt_set_entry 等）检查 key 没被 guest 认领
+    pub fn check_local(&self, doc_id: DocId, key: &str) -> Result<(), String> {
+        let Some(shared) = self.hosted() else {
+            return Ok(());
+        };
+        let mut s = shared
+            .lock()
+            .map_err(|_| "Collaboration lock poisoned".to_string())?;
+        if s.doc_id != doc_id {
+            return Ok(());
+        }
+        s.check_write(key, HOST_PEER)
+    }
+}
+
+/// 本机在局域网上的地址：向外“连”一个 UDP 套接字（不会真的发包）看系统选的网卡
+fn lan_address() -> Option<String> {
+    let socket = UdpSocket::bind(("0.0.0.0", 0)).ok()?;
+    socket.connect(("8.8.8.8", 80)).ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+fn emit_claims(app: &AppHandle, claims: &[Claim]) {
+    let _ = app.emit(EVENT_COLLAB_CLAIMS, claims);
+}
+
+/// 在 0.0.0.0:port（默认 7411）上开放 doc_id；name 是别人看到的本机译者名，没给时用设置里的作者名。
+/// 除 `/join` 外都要带 `X-Gxt-Peer`，全部要带 `X-Gxt-Token`：
+///
+/// - `POST   /join`              请求体 `{"name": "..."}`，返回 peer id、当前序号与全部条目
+/// - `GET    /entries`           全部条目
+/// - `GET    /changes?since=N`   N 之后的变更与当前认领
+/// - `PUT    /entries/{key}`     请求体 `{"value": "..."}`；别人认领着时 409
+/// - `DELETE /entries/{key}`
+/// - `POST   /claims/{key}`      认领/续期；`DELETE` 放开
+/// - `POST   /leave`
+#[tauri::command]
+pub fn gxt_collab_host(
+    app: AppHandle,
+    doc_id: DocId,
+    port: Option<u16>,
+    name: Option<String>,
+    session: tauri::State<'_, Session>,
+    collab: tauri::State<'_, Collab>,
+) -> Result<CollabStatus, String> {
+    session.edit(doc_id, |_| Ok(()))?;
+    if collab.guest_conn().is_some() {
+        return Err("Leave the current collaboration session first".to_string());
+    }
+    {
+        let mut g = collab
+            .host
+            .lock()
+            .map_err(|_| "Collaboration lock poisoned".to_string())?;
+        if let Some(h) = g.as_ref() {
+            return Err(format!("Already hosting on port {}", h.port));
+        }
+
+        let token = make_token()?;
+        let server = Arc::new(
+            Server::http(("0.0.0.0", port.unwrap_or(DEFAULT_PORT)))
+                .map_err(|e| format!("Start collaboration server failed: {e}"))?,
+        );
+        let port = server
+            .server_addr()
+            .to_ip()
+            .map(|a| a.port())
+            .unwrap_or(DEFAULT_PORT);
+        let shared = Arc::new(Mutex::new(Shared {
+            doc_id,
+            token,
+            name: name
+                .filter(|n| !n.trim().is_empty())
+                .or_else(meta::current_author)
+                .unwrap_or_else(|| "host".to_string()),
+            seq: 0,
+            log: VecDeque::new(),
+            peers: BTreeMap::new(),
+            next_peer: 0,
+            claims: BTreeMap::new(),
+        }));
+
+        let worker = server.clone();
+        let state = shared.clone();
+        let handle = app.clone();
+        let thread = std::thread::spawn(move || {
+            for req in worker.incoming_requests() {
+                handle_request(&handle, &state, req);
+            }
+        });
+        tracing::info!(doc_id, port, "collaboration host started");
+        *g = Some(Hosting {
+            shared,
+            server,
+            port,
+            thread,
+        });
+    }
+    Ok(status(&collab))
+}
+
+/// 停止 host，或离开加入的 host（本地副本留着，仍是只读的）
+#[tauri::command]
+pub async fn gxt_collab_stop(collab: tauri::State<'_, Collab>) -> Result<CollabStatus, String> {
+    let hosting = collab
+        .host
+        .lock()
+        .map_err(|_| "Collaboration lock poisoned".to_string())?
+        .take();
+    if let Some(h) = hosting {
+        h.server.unblock();
+        let _ = h.thread.join();
+        tracing::info!(port = h.port, "collaboration host stopped");
+    }
+    let joined = collab
+        .guest
+        .lock()
+        .map_err(|_| "Collaboration lock poisoned".to_string())?
+        .take();
+    if let Some(j) = joined {
+        j.stop.store(true, Ordering::Relaxed);
+        // 通知失败（host 已经不在了）也照样离开
+        let left = j
+            .conn
+            .request::<serde_json::Value>(reqwest::Method::POST, "/leave", None)
+            .await;
+        if let Err(e) = left {
+            tracing::warn!(error = %e, "leave collaboration failed");
+        }
+        let _ = tauri::async_runtime::spawn_blocking(move || j.thread.join()).await;
+        tracing::info!(address = %j.address, "left collaboration");
+    }
+    Ok(CollabStatus::Off)
+}
+
+fn status(collab: &Collab) -> CollabStatus {
+    if let Ok(g) = collab.host.lock() {
+        if let Some(h) = g.as_ref() {
+            if let Ok(mut s) = h.shared.lock() {
+                return CollabStatus::Host {
+                    doc_id: s.doc_id,
+                    port: h.port,
+                    address: lan_address(),
+                    token: s.token.clone(),
+                    peers: s.peers.values().cloned().collect(),
+                    claims: s.claim_list(),
+                };
+            }
+        }
+    }
+    if let Ok(g) = collab.guest.lock() {
+        if let Some(j) = g.as_ref() {
+            return CollabStatus::Guest {
+                doc_id: j.doc_id,
+                address: j.address.clone(),
+                peer: j.conn.peer.clone(),
+                claims: j.claims.lock().map(|c| c.clone()).unwrap_or_default(),
+            };
+        }
+    }
+    CollabStatus::Off
+}
+
+#[tauri::command]
+pub fn gxt_collab_status(collab: tauri::State<'_, Collab>) -> CollabStatus {
+    status(&collab)
+}
+
+/// 加入 address（`192.168.1.5:7411`）上的 host；在本地打开一份只读副本并开始同步，返回它
+#[tauri::command]
+pub async fn gxt_collab_join(
+    app: AppHandle,
+    address: String,
+    token: String,
+    name: Option<String>,
+    collab: tauri::State<'_, Collab>,
+) -> Result<GxtDocument, String> {
+    if collab.hosted().is_some() || collab.guest_conn().is_some() {
+        return Err("Leave the current collaboration session first".to_string());
+    }
+    let address = address.trim().trim_end_matches('/').to_string();
+    let base = if address.contains("://") {
+        address.clone()
+    } else {
+        format!("http://{address}")
+    };
+    let mut conn = Conn {
+        client: reqwest::Client::new(),
+        base,
+        token: token.trim().to_string(),
+        peer: String::new(),
+    };
+    let name = name
+        .filter(|n| !n.trim().is_empty())
+        .or_else(meta::current_author)
+        .unwrap_or_else(|| "guest".to_string());
+    let reply: JoinReply = conn
+        .request(
+            reqwest::Method::POST,
+            "/join",
+            Some(serde_json::json!({ "name": name })),
+        )
+        .await?;
+    conn.peer = reply.peer;
+
+    let mut doc = GxtDocument {
+        file_path: None,
+        entries: reply.entries,
+        doc_id: None,
+        embedded: None,
+        read_only: true,
+    };
+    let doc_id = gxt::register_loaded(&app.state::<Session>(), &mut doc)?;
+
+    let claims = Arc::new(Mutex::new(Vec::new()));
+    let stop = Arc::new(AtomicBool::new(false));
+    let thread = {
+        let (app, conn, claims, stop) = (app.clone(), conn.clone(), claims.clone(), stop.clone());
+        std::thread::spawn(move || poll(&app, doc_id, &conn, reply.seq, &claims, &stop))
+    };
+    let mut g = collab
+        .guest
+        .lock()
+        .map_err(|_| "Collaboration lock poisoned".to_string())?;
+    *g = Some(Joined {
+        doc_id,
+        address: address.clone(),
+        conn,
+        claims,
+        stop,
+        thread,
+    });
+    tracing::info!(%address, doc_id, "joined collaboration");
+    Ok(doc)
+}
+
+/// guest 的同步线程：轮询 host 的变更并应用到本地副本，直到离开或断开
+fn poll(
+    app: &AppHandle,
+    doc_id: DocId,
+    conn: &Conn,
+    mut seq: u64,
+    claims: &Mutex<Vec<Claim>>,
+    stop: &AtomicBool,
+) {
+    let mut failures = 0;
+    let reason = loop {
+        if stop.load(Ordering::Relaxed) {
+            return;
+        }
+        let path = format!("/changes?since={seq}");
+        let res = tauri::async_runtime::block_on(conn.request::<ChangesReply>(
+            reqwest::Method::GET,
+            &path,
+            None,
+        ))
+        .and_then(|reply| {
+            let closed = apply_changes(app, doc_id, conn, &reply)?;
+            Ok((reply, closed))
+        });
+        match res {
+            Ok((reply, closed)) => {
+                failures = 0;
+                seq = reply.seq;
+                if let Ok(mut g) = claims.lock() {
+                    if *g != reply.claims {
+                        *g = reply.claims;
+                        emit_claims(app, &g);
+                    }
+                }
+                if closed {
+                    break "The host closed the document".to_string();
+                }
+            }
+            Err(e) => {
+                failures += 1;
+                tracing::warn!(error = %e, failures, "poll collaboration changes failed");
+                if failures >= MAX_POLL_FAILURES {
+                    break e;
+                }
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    };
+    if stop.load(Ordering::Relaxed) {
+        return;
+    }
+    // 自己断开的：去掉登记（不 join 自己的线程）
+    if let Ok(mut g) = app.state::<Collab>().guest.lock() {
+        if g.as_ref().is_some_and(|j| j.doc_id == doc_id) {
+            g.take();
+        }
+    }
+    tracing::info!(reason = %reason, "collaboration ended");
+    let _ = app.emit(EVENT_COLLAB_ENDED, reason);
+}
+
+/// 把一批变更应用到本地副本；返回 host 是否关了文档
+fn apply_changes(
+    app: &AppHandle,
+    doc_id: DocId,
+    conn: &Conn,
+    reply: &ChangesReply,
+) -> Result<bool, String> {
+    let resync = reply.resync
+        || reply
+            .changes
+            .iter()
+            .any(|c| matches!(c.event, DocEvent::DocumentReplaced { .. }));
+    let entries = if resync {
+        Some(tauri::async_runtime::block_on(
+            conn.request::<Vec<GxtEntry>>(reqwest::Method::GET, "/entries", None),
+        )?)
+    } else {
+        None
+    };
+    let session = app.state::<Session>();
+    // 副本是只读的，不走 edit
+    session.with(doc_id, |open| {
+        if let Some(entries) = entries {
+            let doc = GxtDocument {
+                entries,
+                ..open.doc.clone()
+            };
+            open.replace_entries(doc);
+        }
+        let mut closed = false;
+        for c in &reply.changes {
+            match &c.event {
+                DocEvent::EntryAdded { key, value } | DocEvent::EntryChanged { key, value }
+                    if !resync =>
+                {
+                    open.set_entry(key.clone(), value.clone());
+                }
+                DocEvent::EntryDeleted { key } if !resync => {
+                    open.remove_entry(key);
+                }
+                DocEvent::DocumentClosed => closed = true,
+                _ => {}
+            }
+        }
+        Ok(closed)
+    })
+}
+
+// -------------------- Claims and edits --------------------
+
+/// 认领 key（开始编辑一条前调用，编辑中定期再调用续期）；别人认领着时报错
+#[tauri::command]
+pub async fn gxt_collab_claim(
+    app: AppHandle,
+    key: String,
+    collab: tauri::State<'_, Collab>,
+) -> Result<(), String> {
+    if let Some(shared) = collab.hosted() {
+        let claims = {
+            let mut s = shared
+                .lock()
+                .map_err(|_| "Collaboration lock poisoned".to_string())?;
+            s.claim(&key, HOST_PEER)?;
+            s.claim_list()
+        };
+        emit_claims(&app, &claims);
+        return Ok(());
+    }
+    let (_, conn) = collab.guest_conn().ok_or_else(not_connected)?;
+    let path = format!("/claims/{}", encode_key(&key));
+    conn.request::<serde_json::Value>(reqwest::Method::POST, &path, None)
+        .await
+        .map(|_| ())
+}
+
+/// 放开自己对 key 的认领
+#[tauri::command]
+pub async fn gxt_collab_release(
+    app: AppHandle,
+    key: String,
+    collab: tauri::State<'_, Collab>,
+) -> Result<(), String> {
+    if let Some(shared) = collab.hosted() {
+        let claims = {
+            let mut s = shared
+                .lock()
+                .map_err(|_| "Collaboration lock poisoned".to_string())?;
+            if !s.release(&key, HOST_PEER) {
+                return Ok(());
+            }
+            s.claim_list()
+        };
+        emit_claims(&app, &claims);
+        return Ok(());
+    }
+    let (_, conn) = collab.guest_conn().ok_or_else(not_connected)?;
+    let path = format!("/claims/{}", encode_key(&key));
+    conn.request::<serde_json::Value>(reqwest::Method::DELETE, &path, None)
+        .await
+        .map(|_| ())
+}
+
+/// 协作中新增或修改一条：host 上直接改，guest 上发给 host，成功后也改本地副本
+#[tauri::command]
+pub async fn gxt_collab_set_entry(
+    app: AppHandle,
+    key: String,
+    value: String,
+    collab: tauri::State<'_, Collab>,
+) -> Result<(), String> {
+    gxt::validate_key(&key)?;
+    write(&app, &collab, key, Some(value)).await
+}
+
+/// 协作中删除一条（按项目设置软删除）
+#[tauri::command]
+pub async fn gxt_collab_remove_entry(
+    app: AppHandle,
+    key: String,
+    collab: tauri::State<'_, Collab>,
+) -> Result<(), String> {
+    write(&app, &collab, key, None).await
+}
+
+async fn write(
+    app: &AppHandle,
+    collab: &Collab,
+    key: String,
+    value: Option<String>,
+) -> Result<(), String> {
+    if let Some(shared) = collab.hosted() {
+        return write_entry(app, &shared, HOST_PEER, key, value);
+    }
+    let (doc_id, conn) = collab.guest_conn().ok_or_else(not_connected)?;
+    let path = format!("/entries/{}", encode_key(&key));
+    match &value {
+        Some(v) => {
+            let body = serde_json::json!({ "value": v });
+            conn.request::<serde_json::Value>(reqwest::Method::PUT, &path, Some(body))
+                .await?;
+        }
+        None => {
+            conn.request::<serde_json::Value>(reqwest::Method::DELETE, &path, None)
+                .await?;
+        }
+    }
+    // 不等下一次轮询，先改本地副本；轮询到同样的变更时不会再变
+    app.state::<Session>().with(doc_id, |open| {
+        match value {
+            Some(v) => open.set_entry(key, v),
+            None => {
+                open.remove_entry(&key);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// 按 peer 的身份改 host 的文档：与 gxt_set_entry / gxt_remove_entry 的检查相同，
+/// 另外别人认领着时拒绝
+fn write_entry(
+    app: &AppHandle,
+    shared: &Mutex<Shared>,
+    peer: &str,
+    key: String,
+    value: Option<String>,
+) -> Result<(), String> {
+    let doc_id = shared
+        .lock()
+        .map_err(|_| "Collaboration lock poisoned".to_string())?
+        .doc_id;
+    let projects = app.state::<Projects>();
+    app.state::<Session>()
+        .edit(doc_id, |open: &mut OpenDocument| {
+            shared
+                .lock()
+                .map_err(|_| "Collaboration lock poisoned".to_string())?
+                .check_write(&key, peer)?;
+            if open.is_locked(&key) {
+                return Err(tr!("entry.locked", key = key));
+            }
+            match value {
+                Some(v) => {
+                    open.tombstones.remove(&key);
+                    open.set_entry(key, v);
+                }
+                None => {
+                    let file_path = open.doc.file_path.as_deref();
+                    projects.check_removal(file_path, &key, false)?;
+                    if projects.soft_delete(file_path) {
+                        open.tombstone_entry(&key);
+                    } else {
+                        open.remove_entry(&key);
+                    }
+                }
+            }
+            Ok(())
+        })
+}
+
+fn not_connected() -> String {
+    "Not in a collaboration session".to_string()
+}
+
+/// key 只有可见 ASCII；编码掉 URL 里有特殊含义的字符
+fn encode_key(key: &str) -> String {
+    key.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' => (b as char).to_string(),
+            _ => format!("%{b:02X}"),
+        })
+        .collect()
+}
+
+// -------------------- Request handling --------------------
+
+type HandlerResult = Result<serde_json::Value, (u16, String)>;
+
+#[derive(Deserialize)]
+struct JoinBody {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PutBody {
+    value: String,
+}
+
+/// 读 JSON 请求体；超过 MAX_BODY 的不读完，直接 413
+pub(crate) fn read_body<T: DeserializeOwned>(req: &mut Request) -> Result<T, (u16, String)> {
+    let too_large = || (413, format!("Request body is larger than {MAX_BODY} bytes"));
+    if req.body_length().is_some_and(|n| n as u64 > MAX_BODY) {
+        return Err(too_large());
+    }
+    let mut body = String::new();
+    req.as_reader()
+        .take(MAX_BODY + 1)
+        .read_to_string(&mut body)
+        .map_err(|e| (400, format!("Read body failed: {e}")))?;
+    if body.len() as u64 > MAX_BODY {
+        return Err(too_large());
+    }
+    serde_json::from_str(&body).map_err(|e| (400, format!("Bad JSON body: {e}")))
+}
+
+fn lock(shared: &Mutex<Shared>) -> Result<std::sync::MutexGuard<'_, Shared>, (u16, String)> {
+    shared
+        .lock()
+        .map_err(|_| (500, "Collaboration lock poisoned".to_string()))
+}
+
+fn handle_request(app: &AppHandle, shared: &Mutex<Shared>, mut req: Request) {
+    let result = route(app, shared, &mut req);
+    let (status, body) = match result {
+        Ok(v) => (200, v),
+        Err((status, msg)) => (status, serde_json::json!({ "error": msg })),
+    };
+    let response = Response::from_data(body.to_string().into_bytes())
+        .with_status_code(status)
+        .with_header(
+            Header::from_bytes("Content-Type", "application/json").expect("static header is valid"),
+        );
+    let _ = req.respond(response);
+}
+
+fn route(app: &AppHandle, shared: &Mutex<Shared>, req: &mut Request) -> HandlerResult {
+    let url = req.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((url.as_str(), ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let method = req.method().clone();
+
+    let doc_id = {
+        let s = lock(shared)?;
+        if !token_matches(header_value(req, TOKEN_HEADER), &s.token) {
+            return Err((401, "Bad or missing collaboration token".to_string()));
+        }
+        s.doc_id
+    };
+    if method == Method::Post && segments.as_slice() == ["join"] {
+        let body: JoinBody = read_body(req)?;
+        return join(app, shared, doc_id, body.name);
+    }
+
+    let peer = header_value(req, PEER_HEADER)
+        .unwrap_or_default()
+        .to_string();
+    {
+        let mut s = lock(shared)?;
+        match s.peers.get_mut(&peer) {
+            Some(p) => p.last_seen = now_secs(),
+            None => return Err((403, "Unknown peer; join first".to_string())),
+        }
+    }
+
+    let session = app.state::<Session>();
+    match (&method, segments.as_slice()) {
+        (Method::Get, ["entries"]) => {
+            let entries = session
+                .with(doc_id, |open| Ok(open.doc.entries.clone()))
+                .map_err(|e| (409, e))?;
+            Ok(serde_json::json!(entries))
+        }
+        (Method::Get, ["changes"]) => {
+            let since = query
+                .split('&')
+                .find_map(|kv| kv.strip_prefix("since="))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0);
+            let mut s = lock(shared)?;
+            let changes = s.since(since);
+            Ok(serde_json::json!(ChangesReply {
+                seq: s.seq,
+                resync: changes.is_none(),
+                changes: changes.unwrap_or_default(),
+                claims: s.claim_list(),
+            }))
+        }
+        (Method::Put, ["entries", key]) => {
+            let key = percent_decode(key);
+            let body: PutBody = read_body(req)?;
+            gxt::validate_key(&key).map_err(|e| (400, e))?;
+            write_entry(app, shared, &peer, key, Some(body.value)).map_err(|e| (409, e))?;
+            Ok(serde_json::json!({ "ok": true }))
+        }
+        (Method::Delete, ["entries", key]) => {
+            write_entry(app, shared, &peer, percent_decode(key), None).map_err(|e| (409, e))?;
+            Ok(serde_json::json!({ "ok": true }))
+        }
+        (Method::Post, ["claims", key]) => {
+            let claims = {
+                let mut s = lock(shared)?;
+                s.claim(&percent_decode(key), &peer).map_err(|e| (409, e))?;
+                s.claim_list()
+            };
+            emit_claims(app, &claims);
+            Ok(serde_json::json!({ "ok": true }))
+        }
+        (Method::Delete, ["claims", key]) => {
+            let claims = {
+                let mut s = lock(shared)?;
+                s.release(&percent_decode(key), &peer);
+                s.claim_list()
+            };
+            emit_claims(app, &claims);
+            Ok(serde_json::json!({ "ok": true }))
+        }
+        (Method::Post, ["leave"]) => {
+            let claims = {
+                let mut s = lock(shared)?;
+                s.peers.remove(&peer);
+                s.claims.retain(|_, c| c.peer != peer);
+                s.claim_list()
+            };
+            tracing::info!(%peer, "collaboration peer left");
+            emit_claims(app, &claims);
+            Ok(serde_json::json!({ "ok": true }))
+        }
+        _ => Err((404, "Not found".to_string())),
+    }
+}
+
+/// 先取序号再取条目：两者之间的变更 guest 会再应用一次，结果不变
+fn join(app: &AppHandle, shared: &Mutex<Shared>, doc_id: DocId, name: String) -> HandlerResult {
+    let (peer, seq) = {
+        let mut s = lock(shared)?;
+        s.next_peer += 1;
+        let id = format!("p{}", s.next_peer);
+        let name = match name.trim() {
+            "" => id.clone(),
+            n => n.to_string(),
+        };
+        s.peers.insert(
+            id.clone(),
+            Peer {
+                id: id.clone(),
+                name,
+                last_seen: now_secs(),
+            },
+        );
+        (id, s.seq)
+    };
+    let entries = app
+        .state::<Session>()
+        .with(doc_id, |open| Ok(open.doc.entries.clone()))
+        .map_err(|e| (409, e))?;
+    tracing::info!(%peer, "collaboration peer joined");
+    Ok(serde_json::json!(JoinReply { peer, seq, entries }))
+}
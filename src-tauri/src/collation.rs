@@ -0,0 +1,240 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+use gxt_core::Segment;
+use tauri::AppHandle;
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+use crate::settings;
+
+/// 显示用的排序规则：表格与视图按 value 排序时用，让德语、瑞典语等按译者习惯的字母顺序排。
+/// 与保存时 TKEY 要求的字节序无关
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CollationSettings {
+    /// 语言代码（BCP 47，如 `de`、`sv-SE`）；None 时用界面语言
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// ICU 风格的附加规则，接在该语言的内置规则之后，如 `&z < å < ä < ö`。
+    /// 支持 `&` 锚点与 `<`（字母不同）、`<<`（重音不同）、`<<<`（大小写不同）、`=`（相同），
+    /// 每一项都是单个字符
+    #[serde(default)]
+    pub rules: Option<String>,
+}
+
+/// 各语言内置的规则：只列与通用顺序（重音只是次要差别）不同的字母
+fn locale_rules(lang: &str) -> &'static str {
+    match lang {
+        "sv" | "fi" => "&z < å < ä << æ < ö << ø",
+        "da" | "nb" | "nn" | "no" => "&z < æ << ä < ø << ö < å",
+        "es" => "&n < ñ",
+        "pl" => "&a < ą &c < ć &e < ę &l < ł &n < ń &o < ó &s < ś &z < ź < ż",
+        "cs" | "sk" => "&c < č &r < ř &s < š &z < ž",
+        "tr" | "az" => "&c < ç &g < ğ &h < ı &o < ö &s < ş &u < ü",
+        _ => "",
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Weight {
+    /// 字母
+    primary: u64,
+    /// 重音
+    secondary: u32,
+    /// 大小写
+    tertiary: u8,
+}
+
+const COMMON_SECONDARY: u32 = 1;
+const LOWER: u8 = 1;
+const UPPER: u8 = 2;
+/// 连写字母（ß、æ）比展开的写法靠后
+const VARIANT: u8 = 2;
+
+fn root_weight(c: char) -> Weight {
+    Weight {
+        // 低 16 位留给规则插在它后面的字母
+        primary: u64::from(c as u32) << 16,
+        secondary: COMMON_SECONDARY,
+        tertiary: LOWER,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Collator {
+    /// 规则改过的（小写）字母
+    tailored: HashMap<char, Weight>,
+}
+
+enum Relation {
+    Primary,
+    Secondary,
+    Tertiary,
+    Identical,
+}
+
+impl Collator {
+    pub fn new(locale: &str, rules: Option<&str>) -> Result<Collator, String> {
+        let lang = locale
+            .split(['-', '_'])
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let mut c = Collator::default();
+        c.apply_rules(locale_rules(&lang))?;
+        if let Some(rules) = rules {
+            c.apply_rules(rules)?;
+        }
+        Ok(c)
+    }
+
+    fn weight_of(&self, c: char) -> Weight {
+        self.tailored
+            .get(&c)
+            .copied()
+            .unwrap_or_else(|| root_weight(c))
+    }
+
+    fn apply_rules(&mut self, rules: &str) -> Result<(), String> {
+        let mut used: HashSet<u64> = self.tailored.values().map(|w| w.primary).collect();
+        let mut prev: Option<Weight> = None;
+        let mut relation: Option<Relation> = None;
+        let mut chars = rules.chars().filter(|c| !c.is_whitespace()).peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '&' => {
+                    let anchor = chars
+                        .next()
+                        .ok_or_else(|| "Collation rule ends after '&'".to_string())?;
+                    prev = Some(self.weight_of(lower(anchor)));
+                }
+                '<' => {
+                    let mut n = 1;
+                    while n < 3 && chars.next_if_eq(&'<').is_some() {
+                        n += 1;
+                    }
+                    relation = Some(match n {
+                        1 => Relation::Primary,
+                        2 => Relation::Secondary,
+                        _ => Relation::Tertiary,
+                    });
+                }
+                '=' => relation = Some(Relation::Identical),
+                item => {
+                    let (Some(p), Some(rel)) = (prev, relation.take()) else {
+                        return Err(format!(
+                            "Invalid collation rule near '{item}': expected '&' or a relation first"
+                        ));
+                    };
+                    let w = match rel {
+                        Relation::Primary => {
+                            let mut primary = p.primary + 1;
+                            while used.contains(&primary) {
+                                primary += 1;
+                            }
+                            used.insert(primary);
+                            Weight {
+                                primary,
+                                secondary: COMMON_SECONDARY,
+                                tertiary: LOWER,
+                            }
+                        }
+                        Relation::Secondary => Weight {
+                            secondary: p.secondary + 1,
+                            tertiary: LOWER,
+                            ..p
+                        },
+                        Relation::Tertiary => Weight {
+                            tertiary: p.tertiary + 1,
+                            ..p
+                        },
+                        Relation::Identical => p,
+                    };
+                    self.tailored.insert(lower(item), w);
+                    prev = Some(w);
+                }
+            }
+        }
+        if relation.is_some() {
+            return Err("Collation rule ends with a relation".to_string());
+        }
+        Ok(())
+    }
+
+    fn push_char(&self, c: char, out: &mut Vec<Weight>) {
+        let l = lower(c);
+        let tertiary = if c.is_uppercase() { UPPER } else { LOWER };
+        if let Some(w) = self.tailored.get(&l) {
+            out.push(Weight { tertiary, ..*w });
+            return;
+        }
+        let (expansion, marked): (&str, bool) = match l {
+            'ß' => ("ss", false),
+            'æ' => ("ae", false),
+            'œ' => ("oe", false),
+            'ø' => ("o", true),
+            'ł' => ("l", true),
+            'đ' => ("d", true),
+            _ => ("", false),
+        };
+        if !expansion.is_empty() {
+            for e in expansion.chars() {
+                out.push(Weight {
+                    tertiary,
+                    ..root_weight(e)
+                });
+            }
+            if let Some(last) = out.last_mut() {
+                if marked {
+                    last.secondary += 1 + l as u32;
+                } else {
+                    // 与展开后的写法只差在第三级：`ss` 在 `ß` 前
+                    last.tertiary += VARIANT;
+                }
+            }
+            return;
+        }
+        decompose_canonical(l, |d| match out.last_mut() {
+            Some(last) if is_combining_mark(d) => last.secondary += 1 + d as u32,
+            _ => out.push(Weight {
+                tertiary,
+                ..self.weight_of(d)
+            }),
+        });
+    }
+
+    /// 排序键：先比所有字母，再比重音，最后比大小写；token 与转义不参与
+    pub fn sort_key(&self, value: &str) -> Vec<u64> {
+        let mut elems = Vec::with_capacity(value.len());
+        for seg in gxt_core::segments(value) {
+            if let Segment::Text(t) = seg {
+                for c in t.chars() {
+                    self.push_char(c, &mut elems);
+                }
+            }
+        }
+        let mut key = Vec::with_capacity(elems.len() * 3 + 2);
+        key.extend(elems.iter().map(|w| w.primary));
+        key.push(0);
+        key.extend(elems.iter().map(|w| u64::from(w.secondary)));
+        key.push(0);
+        key.extend(elems.iter().map(|w| u64::from(w.tertiary)));
+        key
+    }
+}
+
+/// 单个字符的小写（小写是多个字符时不变）
+fn lower(c: char) -> char {
+    let mut l = c.to_lowercase();
+    match (l.next(), l.next()) {
+        (Some(x), None) => x,
+        _ => c,
+    }
+}
+
+/// 按设置建排序规则：没指定语言时用界面语言
+pub fn from_settings(app: &AppHandle) -> Result<Collator, String> {
+    let s = settings::load(app)?;
+    let locale = s.collation.locale.or(s.locale).unwrap_or_default();
+    Collator::new(&locale, s.collation.rules.as_deref())
+}
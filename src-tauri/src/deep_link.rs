@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use gxt_core::percent_decode;
+
+/// 注册到系统的 URL 协议名（见 shell::ShellIntegration::url_protocol）
+pub const SCHEME: &str = "gxt";
+
+/// `gxt://open?path=...&key=...`：在编辑器里打开 path，key 给了时跳到这一条。
+/// 贴在 bug 单、群聊里，点一下就到要看的那条
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeepLink {
+    pub path: String,
+    pub key: Option<String>,
+}
+
+/// 命令行参数是 gxt:// 链接时解析出来；不是链接或缺 path 时为 None
+pub fn parse(arg: &str) -> Option<DeepLink> {
+    let prefix = format!("{SCHEME}://");
+    let rest = arg
+        .get(..prefix.len())
+        .filter(|p| p.eq_ignore_ascii_case(&prefix))
+        .map(|_| &arg[prefix.len()..])?;
+    let (action, query) = rest.split_once('?')?;
+    if !action.trim_end_matches('/').eq_ignore_ascii_case("open") {
+        return None;
+    }
+    let mut path = None;
+    let mut key = None;
+    for pair in query.split('&') {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match name {
+            "path" => path = Some(percent_decode(value)),
+            "key" => key = Some(percent_decode(value)),
+            _ => {}
+        }
+    }
+    Some(DeepLink {
+        path: path.filter(|p| !p.is_empty())?,
+        key: key.filter(|k| !k.is_empty()),
+    })
+}
+
+/// 链接里的参数值：字母数字与 `-_.~/:` 以外的字节都写成 %XX
+fn percent_encode(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for b in s.bytes() {
+        if b.is_ascii_alphanumeric() || b"-_.~/:".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
+
+/// 生成指向 path（与其中 key 那一条）的链接，给“复制链接”用
+#[tauri::command]
+pub fn gxt_deep_link(path: String, key: Option<String>) -> String {
+    let mut link = format!("{SCHEME}://open?path={}", percent_encode(&path));
+    if let Some(key) = key.filter(|k| !k.is_empty()) {
+        link.push_str("&key=");
+        link.push_str(&percent_encode(&key));
+    }
+    link
+}
@@ -0,0 +1,80 @@
+use std::fs;
+
+use gxt_core::DeltaInfo;
+use tauri::AppHandle;
+
+use crate::gxt;
+use crate::i18n::tr;
+use crate::merge_report::{self, Decision, MergeReport};
+use crate::snapshots;
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| tr!("file.read_failed", error = e))
+}
+
+/// 生成从 old_path 到 new_path 的差分包写到 output（发布译文更新时只分发它）；返回差分包的头部信息
+#[tauri::command]
+pub async fn gxt_delta_export(
+    old_path: String,
+    new_path: String,
+    output: String,
+) -> Result<DeltaInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let delta = gxt_core::make_delta(&read(&old_path)?, &read(&new_path)?)?;
+        fs::write(&output, &delta).map_err(|e| tr!("file.write_failed", error = e))?;
+        tracing::info!(path = %output, bytes = delta.len(), "exported delta");
+        gxt_core::delta_info(&delta)
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+/// 读差分包的头部（打补丁前给用户看新旧文件的大小）
+#[tauri::command]
+pub async fn gxt_delta_info(delta_path: String) -> Result<DeltaInfo, String> {
+    tauri::async_runtime::spawn_blocking(move || gxt_core::delta_info(&read(&delta_path)?))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+/// 把差分包打到 path 上，写到 output（没给时覆盖原文件）。
+/// 文件与生成差分包时的旧文件不一致时报错，不写任何东西。打完后按条目对比前后留下合并报告
+#[tauri::command]
+pub async fn gxt_delta_apply(
+    app: AppHandle,
+    path: String,
+    delta_path: String,
+    output: Option<String>,
+) -> Result<(), String> {
+    let output = output.unwrap_or_else(|| path.clone());
+    let out = output.clone();
+    let audit = tauri::async_runtime::spawn_blocking(move || {
+        let original = read(&path)?;
+        let patched = gxt_core::apply_delta(&original, &read(&delta_path)?)?;
+        fs::write(&out, &patched).map_err(|e| tr!("file.write_failed", error = e))?;
+        let mut audit = MergeReport::new("delta-apply", Some(out), Some(delta_path));
+        // 打补丁前后不是能解析的 GXT 时只是没有逐条记录
+        if let (Ok(old), Ok(new)) = (
+            gxt::parse_gxt_bytes(&original),
+            gxt::parse_gxt_bytes(&patched),
+        ) {
+            let diff = gxt_core::diff_entries(&old, &new);
+            for e in &diff.added {
+                audit.push(&e.key, Decision::Added, None);
+            }
+            for c in &diff.changed {
+                audit.push(&c.key, Decision::Overwritten, None);
+            }
+            for e in &diff.removed {
+                audit.push(&e.key, Decision::Removed, None);
+            }
+        }
+        Ok::<_, String>(audit)
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+    tracing::info!(path = %output, "applied delta");
+    snapshots::record(&app, &output);
+    merge_report::record(&app, &audit);
+    Ok(())
+}
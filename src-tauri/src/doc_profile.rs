@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::sync::RwLock;
+
+use gxt_core::{Codepage, GxtEntry};
+use tauri::{AppHandle, Listener};
+
+use crate::settings::{self, Settings, EVENT_SETTINGS_CHANGED};
+
+/// 按文件路径记住的读写方式：重新打开同一个文件时不用再选一遍
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentProfile {
+    /// 游戏预设 id；文件不属于当前项目时代替项目设置的预设
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// 设置里 encoding_profiles 的名字（转义/字符映射）
+    #[serde(default)]
+    pub encoding_profile: Option<String>,
+    /// 字库替换式本地化所用的代码页：读入时解码、保存时编码回去
+    #[serde(default)]
+    pub codepage: Option<Codepage>,
+}
+
+/// 设置里的 document_profiles；启动时读一次，之后跟着设置变化（读写文件的地方拿不到 AppHandle）
+static PROFILES: RwLock<BTreeMap<String, DocumentProfile>> = RwLock::new(BTreeMap::new());
+
+fn set_profiles(profiles: BTreeMap<String, DocumentProfile>) {
+    if let Ok(mut g) = PROFILES.write() {
+        *g = profiles;
+    }
+}
+
+/// 启动时调用：读各文件的设置并在设置变化时更新
+pub fn init(app: &AppHandle) {
+    set_profiles(
+        settings::load(app)
+            .map(|s| s.document_profiles)
+            .unwrap_or_default(),
+    );
+    app.listen(EVENT_SETTINGS_CHANGED, |event| {
+        if let Ok(s) = serde_json::from_str::<Settings>(event.payload()) {
+            set_profiles(s.document_profiles);
+        }
+    });
+}
+
+pub(crate) fn get(path: &str) -> Option<DocumentProfile> {
+    PROFILES.read().ok()?.get(path).cloned()
+}
+
+fn codepage(path: &str) -> Option<Codepage> {
+    get(path)?.codepage
+}
+
+/// 读入后调用：按该文件的代码页把 value 换成真正的文字
+pub(crate) fn decode_entries(path: &str, entries: &mut [GxtEntry]) {
+    let Some(cp) = codepage(path) else {
+        return;
+    };
+    for e in entries {
+        e.value = cp.decode(&e.value).into();
+    }
+}
+
+/// 保存前调用：该文件设了代码页时返回编码回去的条目，否则为 None（直接用原来的）
+pub(crate) fn encode_entries(path: &str, entries: &[GxtEntry]) -> Option<Vec<GxtEntry>> {
+    let cp = codepage(path)?;
+    Some(
+        entries
+            .iter()
+            .map(|e| GxtEntry {
+                key: e.key.clone(),
+                value: cp.encode(&e.value).into(),
+            })
+            .collect(),
+    )
+}
+
+/// 某个文件记住的设置；没有时为 None
+#[tauri::command]
+pub fn gxt_document_profile(path: String) -> Option<DocumentProfile> {
+    get(&path)
+}
+
+/// 记住（profile 为 None 时忘掉）某个文件的设置；改了代码页的文件要重新打开才按新的解码
+#[tauri::command]
+pub fn gxt_document_profile_set(
+    app: AppHandle,
+    path: String,
+    profile: Option<DocumentProfile>,
+) -> Result<(), String> {
+    settings::update(&app, |s| {
+        match profile.filter(|p| *p != DocumentProfile::default()) {
+            Some(p) => s.document_profiles.insert(path, p),
+            None => s.document_profiles.remove(&path),
+        };
+    })
+}
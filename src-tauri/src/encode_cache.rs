@@ -0,0 +1,27 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use gxt_core::ValueCache;
+
+/// 按保存路径区分的编码缓存（tauri managed state）
+#[derive(Debug, Default)]
+pub struct EncodeCache {
+    by_path: Mutex<HashMap<String, ValueCache>>,
+}
+
+impl EncodeCache {
+    /// 取出某路径的缓存（不存在则给一个空的），用完后 `put_back`
+    pub fn take(&self, path: &str) -> ValueCache {
+        self.by_path
+            .lock()
+            .ok()
+            .and_then(|mut m| m.remove(path))
+            .unwrap_or_default()
+    }
+
+    pub fn put_back(&self, path: String, cache: ValueCache) {
+        if let Ok(mut m) = self.by_path.lock() {
+            m.insert(path, cache);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use gxt_core::EscapeIssue;
+use tauri::AppHandle;
+
+use crate::gxt::GxtDocument;
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+use crate::settings::{self, Severity};
+
+/// 设置里 validation 表中对应本检查的规则 id
+pub const RULE_ESCAPE_SYNTAX: &str = "escape-syntax";
+
+/// 文档里一条写错的转义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscapeProblem {
+    pub key: String,
+    #[serde(flatten)]
+    pub issue: EscapeIssue,
+    /// 设置里 `escape-syntax` 规则的严重程度（没设置时为 error）
+    pub severity: Severity,
+}
+
+fn severity(app: &AppHandle) -> Severity {
+    settings::load(app)
+        .ok()
+        .and_then(|s| s.validation.get(RULE_ESCAPE_SYNTAX).copied())
+        .unwrap_or(Severity::Error)
+}
+
+/// 保存前检查：规则为 error 时，有写错的转义就不保存（否则会被当成普通文字写进文件）
+pub(crate) fn check_for_save(app: &AppHandle, doc: &GxtDocument) -> Result<(), String> {
+    if severity(app) != Severity::Error {
+        return Ok(());
+    }
+    for e in &doc.entries {
+        if let Some(issue) = gxt_core::lint_escapes(&e.value).into_iter().next() {
+            return Err(match &issue.suggestion {
+                Some(suggestion) => tr!(
+                    "escape.invalid_suggest",
+                    key = e.key,
+                    text = issue.text,
+                    message = issue.message,
+                    suggestion = suggestion,
+                ),
+                None => tr!(
+                    "escape.invalid",
+                    key = e.key,
+                    text = issue.text,
+                    message = issue.message,
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 编辑中的一个值里写错的转义（边打字边提示用）
+#[tauri::command]
+pub fn gxt_escape_lint(value: String) -> Vec<EscapeIssue> {
+    gxt_core::lint_escapes(&value)
+}
+
+/// 检查文档里所有值的转义；keys 给出时只查这些 key。规则关掉时为空
+#[tauri::command]
+pub fn gxt_escape_check(
+    app: AppHandle,
+    doc_id: DocId,
+    keys: Option<Vec<String>>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<EscapeProblem>, String> {
+    let severity = severity(&app);
+    if severity == Severity::Off {
+        return Ok(Vec::new());
+    }
+    session.with(doc_id, |open| {
+        Ok(open
+            .doc
+            .entries
+            .iter()
+            .filter(|e| keys.as_ref().is_none_or(|k| k.contains(&e.key)))
+            .flat_map(|e| {
+                gxt_core::lint_escapes(&e.value)
+                    .into_iter()
+                    .map(|issue| EscapeProblem {
+                        key: e.key.clone(),
+                        issue,
+                        severity,
+                    })
+            })
+            .collect())
+    })
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Mutex, OnceLock};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::collab::Collab;
+use crate::journal::Journal;
+use crate::session::DocId;
+
+/// 文档变更事件（所有窗口都会收到；HTTP 服务开着时也会推给 `/events` 的订阅者）
+pub const EVENT_DOC_CHANGE: &str = "gxt://doc-change";
+
+/// 结构化的文档变更；序列化为 `{"type": "entry-changed", ...}`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum DocEvent {
+    EntryAdded {
+        key: String,
+        value: String,
+    },
+    EntryChanged {
+        key: String,
+        value: String,
+    },
+    EntryDeleted {
+        key: String,
+    },
+    /// 整体替换（打开新文件、脚本/批量操作）；订阅者应重新拉取全部条目
+    DocumentReplaced {
+        file_path: Option<String>,
+        entry_count: usize,
+    },
+    DocumentSaved {
+        file_path: Option<String>,
+        entry_count: usize,
+    },
+    DocumentClosed,
+    /// 未保存修改的状态变了（标题栏 `*` 等）
+    DirtyChanged {
+        dirty: bool,
+    },
+}
+
+/// 带上文档 id 的变更事件（实际发给前端/订阅者的结构）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocChange {
+    pub doc_id: DocId,
+    #[serde(flatten)]
+    pub event: DocEvent,
+}
+
+type SinkFn = Box<dyn Fn(DocId, Vec<DocEvent>) + Send + Sync>;
+
+/// Session 产生事件后交给它分发；应用启动时设置一次
+#[derive(Default)]
+pub struct EventSink(OnceLock<SinkFn>);
+
+impl EventSink {
+    pub fn set(&self, f: impl Fn(DocId, Vec<DocEvent>) + Send + Sync + 'static) {
+        let _ = self.0.set(Box::new(f));
+    }
+
+    pub fn send(&self, doc_id: DocId, events: Vec<DocEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        if let Some(f) = self.0.get() {
+            f(doc_id, events);
+        }
+    }
+}
+
+impl fmt::Debug for EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("EventSink")
+            .field(&self.0.get().is_some())
+            .finish()
+    }
+}
+
+/// 进程内订阅者（HTTP `/events` 流等）
+#[derive(Default)]
+pub struct EventHub {
+    subscribers: Mutex<Vec<Sender<DocChange>>>,
+}
+
+impl EventHub {
+    pub fn subscribe(&self) -> Receiver<DocChange> {
+        let (tx, rx) = mpsc::channel();
+        if let Ok(mut g) = self.subscribers.lock() {
+            g.push(tx);
+        }
+        rx
+    }
+
+    /// 发送失败（接收端已经断开）的订阅者顺便清掉
+    pub fn publish(&self, events: &[DocChange]) {
+        if let Ok(mut g) = self.subscribers.lock() {
+            g.retain(|tx| events.iter().all(|ev| tx.send(ev.clone()).is_ok()));
+        }
+    }
+
+    /// 断开全部订阅者（HTTP 服务停止时）
+    pub fn close_all(&self) {
+        if let Ok(mut g) = self.subscribers.lock() {
+            g.clear();
+        }
+    }
+}
+
+/// 把事件写进预写日志（协作 host 的文档还记进变更序列），再转发成 Tauri 事件并推给 EventHub
+pub fn dispatch(app: &AppHandle, doc_id: DocId, events: Vec<DocEvent>) {
+    if let Some(journal) = app.try_state::<Journal>() {
+        journal.record(app, doc_id, &events);
+    }
+    if let Some(collab) = app.try_state::<Collab>() {
+        collab.record(doc_id, &events);
+    }
+    let events: Vec<DocChange> = events
+        .into_iter()
+        .map(|event| DocChange { doc_id, event })
+        .collect();
+    for ev in &events {
+        let _ = app.emit(EVENT_DOC_CHANGE, ev);
+    }
+    app.state::<EventHub>().publish(&events);
+}
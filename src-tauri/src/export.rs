@@ -0,0 +1,92 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+
+use tauri::{AppHandle, Manager};
+
+use crate::gxt::GxtEntry;
+use crate::i18n::tr;
+use crate::mirrors::MirrorFormat;
+use crate::session::{DocId, Session};
+use crate::task::{Task, Tasks};
+use crate::tms::csv_field;
+
+/// 每写这么多条报告一次进度、检查一次取消
+const CHUNK: usize = 2000;
+
+/// 写到一半的文件名；写完才改成目标文件名，取消或出错时删掉
+const PART_SUFFIX: &str = ".part";
+
+/// 一段条目按 format 渲染出的文字；first 为是否是第一段（CSV 的表头、JSON 的分隔）。
+/// 各段首尾相接与一次性渲染（见 mirrors.rs）的结果相同，JSON 的结尾由调用方补上
+fn render_chunk(format: MirrorFormat, entries: &[GxtEntry], first: bool) -> Result<String, String> {
+    let mut out = String::new();
+    match format {
+        MirrorFormat::Text => return gxt_core::entries_to_text(entries),
+        MirrorFormat::Csv => {
+            if first {
+                out.push_str("key,value\n");
+            }
+            for e in entries {
+                out.push_str(&format!("{},{}\n", csv_field(&e.key), csv_field(&e.value)));
+            }
+        }
+        MirrorFormat::Json => {
+            for (i, e) in entries.iter().enumerate() {
+                out.push_str(if first && i == 0 { "[\n  " } else { ",\n  " });
+                let item = serde_json::to_string_pretty(e)
+                    .map_err(|e| tr!("json.encode_failed", error = e))?;
+                out.push_str(&item.replace('\n', "\n  "));
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// 把整份文档导出成 JSON / CSV / 文本，分段边渲染边写盘，不在内存里拼出整个文件
+/// （IV 时代 10 万条的文件也不会占一大块内存）。给了 task_id 时推送进度，
+/// 可用 gxt_cancel_task 取消；取消或出错时不留下写了一半的文件。返回写出的条数
+#[tauri::command]
+pub async fn gxt_export_entries(
+    app: AppHandle,
+    doc_id: DocId,
+    path: String,
+    format: MirrorFormat,
+    task_id: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<usize, String> {
+    let mut task = app.state::<Tasks>().start(&app, task_id)?;
+    // value 是共用的 Arc，这里只复制 key
+    let entries = session.with(doc_id, |open| Ok(open.doc.entries.clone()))?;
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let part = format!("{path}{PART_SUFFIX}");
+        let total = entries.len();
+        let write = |task: &mut Task| -> Result<(), String> {
+            let file = File::create(&part).map_err(|e| tr!("file.write_failed", error = e))?;
+            let mut w = BufWriter::new(file);
+            for (n, chunk) in entries.chunks(CHUNK).enumerate() {
+                task.step(n * CHUNK, total)?;
+                let text = render_chunk(format, chunk, n == 0)?;
+                w.write_all(text.as_bytes())
+                    .map_err(|e| tr!("file.write_failed", error = e))?;
+            }
+            if format == MirrorFormat::Json {
+                let end: &[u8] = if entries.is_empty() { b"[]" } else { b"\n]" };
+                w.write_all(end)
+                    .map_err(|e| tr!("file.write_failed", error = e))?;
+            }
+            w.flush().map_err(|e| tr!("file.write_failed", error = e))?;
+            task.check()?;
+            fs::rename(&part, &path).map_err(|e| tr!("file.write_failed", error = e))
+        };
+        if let Err(e) = write(&mut task) {
+            let _ = fs::remove_file(&part);
+            return Err(e);
+        }
+        task.progress(total, total);
+        tracing::info!(path = %path, entries = total, "exported entries");
+        Ok(total)
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use tauri::AppHandle;
+
+use crate::i18n::tr;
+use crate::meta;
+use crate::settings;
+
+/// 设置里没配置模板时用的
+pub const DEFAULT_TEMPLATE: &str = "{basename}_{lang}.{ext}";
+
+/// 填进模板的字段
+pub(crate) struct NameFields<'a> {
+    /// 文档文件名去掉扩展名
+    pub basename: &'a str,
+    /// 语言代码；不属于项目的文档为空
+    pub lang: &'a str,
+    /// 导出格式的扩展名（不带点）
+    pub ext: &'a str,
+}
+
+/// 设置里的模板，没有时为 DEFAULT_TEMPLATE
+pub(crate) fn template(app: &AppHandle) -> String {
+    settings::load(app)
+        .ok()
+        .and_then(|s| s.export_name_template)
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_TEMPLATE.to_string())
+}
+
+/// 文件名里不能出现的字符换成 `_`（语言名、文件名里带的斜杠等）
+fn sanitize(s: &str) -> String {
+    s.chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect()
+}
+
+/// Unix 秒 -> UTC 的 (年, 月, 日, 时, 分, 秒)
+pub(crate) fn utc_parts(secs: u64) -> (i64, u32, u32, u32, u32, u32) {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+    // 公历换算（Howard Hinnant 的 civil_from_days）
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (
+        year,
+        month,
+        day,
+        (rem / 3600) as u32,
+        (rem % 3600 / 60) as u32,
+        (rem % 60) as u32,
+    )
+}
+
+/// 按模板生成文件名。可用的字段：`{basename}` `{lang}` `{ext}` `{date}`（YYYY-MM-DD）
+/// `{time}`（HHMMSS），日期时间为 UTC。字段值里不能用在文件名里的字符换成 `_`；
+/// 模板里有未知字段或没闭合的 `{` 时报错
+pub(crate) fn render(template: &str, fields: &NameFields, now: u64) -> Result<String, String> {
+    let (y, mo, d, h, mi, s) = utc_parts(now);
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        out.push_str(&rest[..open]);
+        let close = rest[open..]
+            .find('}')
+            .ok_or_else(|| tr!("export.bad_template", template = template))?;
+        let value = match &rest[open + 1..open + close] {
+            "basename" => sanitize(fields.basename),
+            "lang" => sanitize(fields.lang),
+            "ext" => sanitize(fields.ext),
+            "date" => format!("{y:04}-{mo:02}-{d:02}"),
+            "time" => format!("{h:02}{mi:02}{s:02}"),
+            name => return Err(tr!("export.unknown_field", field = format!("{{{name}}}"))),
+        };
+        out.push_str(&value);
+        rest = &rest[open + close + 1..];
+    }
+    out.push_str(rest);
+    // 模板本身也不能带目录
+    let name = sanitize(&out);
+    if name.trim().is_empty() {
+        return Err(tr!("export.bad_template", template = template));
+    }
+    Ok(name)
+}
+
+/// 文档路径的文件名去掉扩展名；未保存过的文档为 "untitled"
+pub(crate) fn basename(file_path: Option<&str>) -> String {
+    file_path
+        .and_then(|p| Path::new(p).file_stem())
+        .and_then(|s| s.to_str())
+        .unwrap_or("untitled")
+        .to_string()
+}
+
+/// 预览模板生成的文件名（设置界面里用）；template 没给时用设置里的
+#[tauri::command]
+pub fn gxt_export_file_name(
+    app: AppHandle,
+    template: Option<String>,
+    file_path: Option<String>,
+    lang: Option<String>,
+    ext: String,
+) -> Result<String, String> {
+    let template = template.unwrap_or_else(|| self::template(&app));
+    let basename = basename(file_path.as_deref());
+    render(
+        &template,
+        &NameFields {
+            basename: &basename,
+            lang: lang.as_deref().unwrap_or_default(),
+            ext: &ext,
+        },
+        meta::now_secs(),
+    )
+}
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tauri::{AppHandle, Manager};
+
+use crate::gxt::{self, GxtEntry};
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+/// 临时文件所在目录（系统临时目录下）
+const TEMP_DIR: &str = "gxt-editor-diff";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalDiff {
+    pub left: String,
+    pub right: String,
+    /// 是否已启动外部工具；没有配置工具时只返回两个文件路径
+    pub launched: bool,
+}
+
+/// 读出一份文档：.gxt 按二进制解析，其它按 `KEY=value` 文本解析
+async fn read_any(path: &str) -> Result<Vec<GxtEntry>, String> {
+    if path.to_lowercase().ends_with(".gxt") {
+        return Ok(gxt::load_document(path.to_string()).await?.entries);
+    }
+    let text = fs::read_to_string(path).map_err(|e| tr!("file.read_failed", error = e))?;
+    gxt_core::entries_from_text(&text)
+}
+
+/// 按 key 排序后的文本，保证两边顺序稳定、逐行可比
+fn write_dump(dir: &Path, name: &str, mut entries: Vec<GxtEntry>) -> Result<PathBuf, String> {
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    let text = gxt_core::entries_to_text(&entries)?;
+    let path = dir.join(name);
+    fs::write(&path, text).map_err(|e| format!("Write temp file failed: {e}"))?;
+    Ok(path)
+}
+
+fn stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("document")
+        .to_string()
+}
+
+/// 把两份文档导出为规范化文本并交给外部 diff 工具（Beyond Compare、WinMerge 等）。
+///
+/// - right 为 None 时右侧用 doc_id 指定的打开文档
+/// - tool 是命令行模板，如 `["BCompare.exe", "{left}", "{right}"]`；
+///   为空时不启动任何程序，只返回两个临时文件路径
+#[tauri::command]
+pub async fn gxt_external_diff(
+    app: AppHandle,
+    left: String,
+    right: Option<String>,
+    doc_id: Option<DocId>,
+    tool: Option<Vec<String>>,
+) -> Result<ExternalDiff, String> {
+    let left_entries = read_any(&left).await?;
+    let (right_name, right_entries) = match &right {
+        Some(p) => (stem(p), read_any(p).await?),
+        None => {
+            let id = doc_id.ok_or("Either right or doc_id is required")?;
+            app.state::<Session>().with(id, |open| {
+                let name = open.doc.file_path.as_deref().map_or("current".into(), stem);
+                Ok((name, open.doc.entries.clone()))
+            })?
+        }
+    };
+
+    let dir = std::env::temp_dir().join(TEMP_DIR);
+    fs::create_dir_all(&dir).map_err(|e| format!("Create temp dir failed: {e}"))?;
+    let left_path = write_dump(&dir, &format!("{}.left.txt", stem(&left)), left_entries)?;
+    let right_path = write_dump(&dir, &format!("{right_name}.right.txt"), right_entries)?;
+    let left_str = left_path.to_string_lossy().into_owned();
+    let right_str = right_path.to_string_lossy().into_owned();
+
+    let mut launched = false;
+    if let Some((program, args)) = tool.as_deref().and_then(|t| t.split_first()) {
+        let args = args.iter().map(|a| {
+            a.replace("{left}", &left_str)
+                .replace("{right}", &right_str)
+        });
+        Command::new(program)
+            .args(args)
+            .spawn()
+            .map_err(|e| format!("Launch diff tool {program} failed: {e}"))?;
+        launched = true;
+    }
+
+    Ok(ExternalDiff {
+        left: left_str,
+        right: right_str,
+        launched,
+    })
+}
@@ -0,0 +1,575 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use gxt_core::{GxtEntry, HashKind};
+
+use crate::gxt::{self, GxtDocument};
+use crate::hashes::HashDictionaries;
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+/// 支持实时预览的游戏
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Game {
+    Gta3,
+    Vc,
+    Sa,
+}
+
+impl Game {
+    /// 游戏内存中字符串的编码：III/VC 是 UTF-16（与 .gxt 相同），SA 是单字节
+    fn encode(self, value: &str) -> Result<Vec<u8>, String> {
+        let mut units = Vec::new();
+        gxt_core::encode_utf16z_with_escapes(value, &mut units)?;
+        match self {
+            Game::Gta3 | Game::Vc => Ok(units),
+            Game::Sa => units
+                .chunks_exact(2)
+                .map(|b| u16::from_le_bytes([b[0], b[1]]))
+                .map(|u| {
+                    u8::try_from(u).map_err(|_| {
+                        format!("Character U+{u:04X} cannot be shown by the SA text renderer")
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    fn unit_size(self) -> usize {
+        match self {
+            Game::Gta3 | Game::Vc => 2,
+            Game::Sa => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunningGame {
+    pub game: Game,
+    pub pid: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewResult {
+    pub game: Game,
+    pub pid: u32,
+    /// 找到了几处这条文本；不止一处且没有 confirm 时什么都没写
+    pub matches: usize,
+    /// 改写了几处
+    pub replaced: usize,
+}
+
+/// 已经写进游戏的值：下次预览同一个 key 时要找的是它，而不是磁盘上的原值
+#[derive(Default)]
+pub struct GamePreview {
+    injected: Mutex<HashMap<(u32, String), String>>,
+}
+
+/// 当前运行中的 GTA III / VC / SA 进程
+#[tauri::command]
+pub fn gxt_game_find() -> Result<Vec<RunningGame>, String> {
+    platform::find_games()
+}
+
+/// 把一条编辑中的文本直接写进运行中游戏的文本池，不用重启游戏就能看到效果。
+///
+/// 做法是在游戏加载的主文本表里按 key 找到 value 的位置，确认那里是这条文本当前的内容
+/// （磁盘上文件里的值，或上次预览写入的值）后原地覆盖。key 不在主文本表里（如任务文本）时
+/// 退回到在内存里找相同的文本；找到不止一处时不写，返回 matches 让用户确认后带上 confirm 再调用。
+/// 新文本不能比原文本长（文本池里没有多余空间），变短时用 0 补齐。
+/// 只在 Windows 上可用；游戏需以同权限运行。
+#[tauri::command]
+pub async fn gxt_game_preview(
+    doc_id: DocId,
+    key: String,
+    value: String,
+    game: Option<Game>,
+    confirm: Option<bool>,
+    session: tauri::State<'_, Session>,
+    preview: tauri::State<'_, GamePreview>,
+) -> Result<PreviewResult, String> {
+    let running = platform::find_games()?;
+    let target = match game {
+        Some(g) => running.into_iter().find(|r| r.game == g),
+        None => running.into_iter().next(),
+    }
+    .ok_or_else(|| tr!("game.not_running"))?;
+
+    let file_path = session.with(doc_id, |open| {
+        open.doc
+            .file_path
+            .clone()
+            .ok_or_else(|| tr!("game.save_first"))
+    })?;
+    let on_disk = gxt::load_document(file_path).await?;
+    let disk_value = on_disk
+        .entries
+        .iter()
+        .find(|e| e.key == key)
+        .map(|e| e.value.to_string())
+        .ok_or_else(|| format!("{key} is not in the saved file; the game has no slot for it"))?;
+
+    let slot = (target.pid, key.clone());
+    let current = preview
+        .injected
+        .lock()
+        .map_err(|_| "Preview lock poisoned".to_string())?
+        .get(&slot)
+        .cloned()
+        .unwrap_or(disk_value);
+
+    if current.is_empty() {
+        return Err(format!(
+            "{key} is empty in the game; there is nothing to locate"
+        ));
+    }
+    let old = target.game.encode(&current)?;
+    let mut new = target.game.encode(&value)?;
+    if new.len() > old.len() {
+        return Err(format!(
+            "New text is {} characters longer than the text loaded in the game; \
+             save and restart the game to preview it",
+            (new.len() - old.len()) / target.game.unit_size()
+        ));
+    }
+    new.resize(old.len(), 0);
+
+    let lookup = key.clone();
+    let confirm = confirm.unwrap_or(false);
+    let (matches, replaced) = tauri::async_runtime::spawn_blocking(move || {
+        let image = MemoryImage::new(platform::read_memory(target.pid)?);
+        let found = locate(&image, target.game, &lookup, &old);
+        drop(image);
+        if found.len() > 1 && !confirm {
+            return Ok((found.len(), 0));
+        }
+        let replaced = platform::write_memory(target.pid, &found, &new)?;
+        Ok::<_, String>((found.len(), replaced))
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+    if matches == 0 {
+        return Err(format!(
+            "Could not find the current text of {key} in the game's memory \
+             (is the game using this file?)"
+        ));
+    }
+    let result = PreviewResult {
+        game: target.game,
+        pid: target.pid,
+        matches,
+        replaced,
+    };
+    if matches > 1 && !confirm {
+        return Ok(result);
+    }
+    if replaced == 0 {
+        return Err(format!("Writing {key} into the game's memory failed"));
+    }
+
+    if let Ok(mut g) = preview.injected.lock() {
+        g.insert(slot, value);
+    }
+    Ok(result)
+}
+
+/// 从运行中的游戏内存里读出已加载的文本，作为一个新的（未保存的）文档打开。
+/// 原 .gxt 丢了或被打包进改过的 exe 时可以用它找回文本。
+///
+/// 读的是游戏当前的主文本表（任务文本表只在任务进行中才加载，不在其中）；
+/// SA 内存里只有 key 的哈希，能在哈希字典里查到的显示原名
+#[tauri::command]
+pub async fn gxt_game_dump(
+    app: tauri::AppHandle,
+    game: Option<Game>,
+    session: tauri::State<'_, Session>,
+    dicts: tauri::State<'_, HashDictionaries>,
+) -> Result<GxtDocument, String> {
+    let running = platform::find_games()?;
+    let target = match game {
+        Some(g) => running.into_iter().find(|r| r.game == g),
+        None => running.into_iter().next(),
+    }
+    .ok_or_else(|| tr!("game.not_running"))?;
+
+    let pool = tauri::async_runtime::spawn_blocking(move || {
+        let image = MemoryImage::new(platform::read_memory(target.pid)?);
+        read_text_pool(&image, target.game).ok_or_else(|| tr!("game.no_text_pool"))
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+
+    let entries = match pool {
+        TextPool::Named(entries) => entries,
+        TextPool::Hashed(hashed) => dicts.with(&app, HashKind::Crc32, |d| {
+            hashed
+                .into_iter()
+                .map(|(hash, value)| GxtEntry {
+                    key: d.name_for(hash),
+                    value: value.into(),
+                })
+                .collect::<Vec<_>>()
+        })?,
+    };
+    let mut doc = GxtDocument {
+        file_path: None,
+        entries,
+        doc_id: None,
+        embedded: None,
+        read_only: false,
+    };
+    gxt::register_loaded(&session, &mut doc)?;
+    Ok(doc)
+}
+
+/// 一段进程内存
+pub(crate) struct MemoryRegion {
+    pub base: usize,
+    pub bytes: Vec<u8>,
+}
+
+/// 读出来的进程内存，可按游戏里的地址取字节
+struct MemoryImage {
+    regions: Vec<MemoryRegion>,
+}
+
+impl MemoryImage {
+    fn new(mut regions: Vec<MemoryRegion>) -> Self {
+        regions.sort_by_key(|r| r.base);
+        MemoryImage { regions }
+    }
+
+    /// 从 addr 到所在内存段末尾的字节
+    fn at(&self, addr: usize) -> Option<&[u8]> {
+        let i = self
+            .regions
+            .partition_point(|r| r.base <= addr)
+            .checked_sub(1)?;
+        let r = &self.regions[i];
+        r.bytes.get(addr - r.base..).filter(|b| !b.is_empty())
+    }
+
+    /// addr 处以 0 结尾的字符串，按 unit 字节一个字符，转成带转义的文本
+    fn string_at(&self, addr: usize, unit: usize) -> Option<String> {
+        let tail = self.at(addr)?;
+        let len = tail
+            .chunks_exact(unit)
+            .position(|c| c.iter().all(|&b| b == 0))?;
+        // SA 的单字节文本按 Latin-1 扩成 UTF-16，和 .gxt 用同一套转义
+        let mut units: Vec<u8> = match unit {
+            2 => tail[..len * 2].to_vec(),
+            _ => tail[..len].iter().flat_map(|&b| [b, 0]).collect(),
+        };
+        units.extend_from_slice(&[0, 0]);
+        let mut out = String::new();
+        gxt_core::decode_utf16z_with_escapes(&units, 0, &mut out).ok()?;
+        Some(out)
+    }
+}
+
+enum TextPool {
+    Named(Vec<GxtEntry>),
+    /// SA：(key 哈希, value)
+    Hashed(Vec<(u32, String)>),
+}
+
+/// 少于这么多条的表不算（内存里碰巧像 key 表的数据通常很短）
+const MIN_POOL_ENTRIES: usize = 64;
+
+/// 在内存里找游戏加载的 key 表（CKeyArray 的条目数组），取最长的那张读出来。
+///
+/// III/VC 的条目是 12 字节：value 指针 + 8 字节 key 名；SA 是 8 字节：value 指针 + key 哈希。
+/// 游戏用二分查找，条目按 key（SA 按哈希）升序排列，这一点用来排除无关数据
+fn read_text_pool(image: &MemoryImage, game: Game) -> Option<TextPool> {
+    let (stride, unit) = match game {
+        Game::Gta3 | Game::Vc => (12, 2),
+        Game::Sa => (8, 1),
+    };
+    let (region, start, count) = longest_key_run(image, game, stride)?;
+    let records = image.regions[region].bytes[start..start + count * stride].chunks_exact(stride);
+    let value_at = |rec: &[u8]| image.string_at(read_u32(rec) as usize, unit);
+    match game {
+        Game::Gta3 | Game::Vc => records
+            .map(|rec| {
+                Some(GxtEntry {
+                    key: gxt_core::key_label(&rec[4..12])?.to_string(),
+                    value: value_at(rec)?.into(),
+                })
+            })
+            .collect::<Option<_>>()
+            .map(TextPool::Named),
+        Game::Sa => records
+            .map(|rec| Some((read_u32(&rec[4..]), value_at(rec)?)))
+            .collect::<Option<_>>()
+            .map(TextPool::Hashed),
+    }
+}
+
+/// 最长的一串合法条目：(内存段下标, 段内偏移, 条数)
+fn longest_key_run(
+    image: &MemoryImage,
+    game: Game,
+    stride: usize,
+) -> Option<(usize, usize, usize)> {
+    let mut best: Option<(usize, usize, usize)> = None;
+    for (ri, region) in image.regions.iter().enumerate() {
+        let bytes = &region.bytes;
+        let mut i = 0;
+        while i + stride <= bytes.len() {
+            let mut n = 0;
+            let mut prev: Option<&[u8]> = None;
+            while let Some(rec) = bytes.get(i + n * stride..i + (n + 1) * stride) {
+                let sort_key = match game {
+                    Game::Gta3 | Game::Vc => match gxt_core::key_label(&rec[4..12]) {
+                        Some(k) => k.as_bytes(),
+                        None => break,
+                    },
+                    Game::Sa => &rec[4..8],
+                };
+                let ascending = prev.is_none_or(|p| match game {
+                    Game::Sa => read_u32(p) < read_u32(sort_key),
+                    _ => p < sort_key,
+                });
+                if !ascending || image.at(read_u32(rec) as usize).is_none() {
+                    break;
+                }
+                prev = Some(sort_key);
+                n += 1;
+            }
+            if n >= MIN_POOL_ENTRIES && best.is_none_or(|(_, _, m)| n > m) {
+                best = Some((ri, i, n));
+            }
+            i += if n > 0 { n * stride } else { 4 };
+        }
+    }
+    best
+}
+
+/// 主文本表里 key 的 value 在游戏内存里的地址
+fn value_address(image: &MemoryImage, game: Game, key: &str) -> Option<usize> {
+    let stride = match game {
+        Game::Gta3 | Game::Vc => gxt_core::KEY_RECORD_BYTES,
+        Game::Sa => 8,
+    };
+    let (region, start, count) = longest_key_run(image, game, stride)?;
+    let hash = gxt_core::crc32_key(key);
+    image.regions[region].bytes[start..start + count * stride]
+        .chunks_exact(stride)
+        .find(|rec| match game {
+            Game::Gta3 | Game::Vc => gxt_core::key_label(&rec[4..12]) == Some(key),
+            Game::Sa => read_u32(&rec[4..]) == hash,
+        })
+        .map(|rec| read_u32(rec) as usize)
+}
+
+/// 游戏内存里 key 当前的文本 old 所在的地址。先看主文本表里 key 的 value（只有一处）；
+/// 对不上时在所有内存里找与 old 相同、前面是字符串边界的位置，可能不止一处
+fn locate(image: &MemoryImage, game: Game, key: &str, old: &[u8]) -> Vec<usize> {
+    if let Some(addr) = value_address(image, game, key) {
+        if image.at(addr).is_some_and(|b| b.starts_with(old)) {
+            return vec![addr];
+        }
+    }
+    let unit = game.unit_size();
+    let mut found = Vec::new();
+    for r in &image.regions {
+        let buf = &r.bytes;
+        let mut i = 0;
+        while i + old.len() <= buf.len() {
+            let at_boundary = i < unit || buf[i - unit..i].iter().all(|&b| b == 0);
+            if at_boundary && buf[i..i + old.len()] == *old {
+                found.push(r.base + i);
+                i += old.len();
+            } else {
+                i += unit;
+            }
+        }
+    }
+    found
+}
+
+fn read_u32(b: &[u8]) -> u32 {
+    u32::from_le_bytes([b[0], b[1], b[2], b[3]])
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::mem::{size_of, zeroed};
+
+    use windows_sys::Win32::Foundation::{CloseHandle, HANDLE, INVALID_HANDLE_VALUE};
+    use windows_sys::Win32::System::Diagnostics::Debug::{ReadProcessMemory, WriteProcessMemory};
+    use windows_sys::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, Process32FirstW, Process32NextW, PROCESSENTRY32W,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows_sys::Win32::System::Memory::{
+        VirtualQueryEx, MEMORY_BASIC_INFORMATION, MEM_COMMIT, PAGE_EXECUTE_READ,
+        PAGE_EXECUTE_READWRITE, PAGE_EXECUTE_WRITECOPY, PAGE_GUARD, PAGE_READONLY, PAGE_READWRITE,
+        PAGE_WRITECOPY,
+    };
+    use windows_sys::Win32::System::Threading::{
+        OpenProcess, PROCESS_QUERY_INFORMATION, PROCESS_VM_OPERATION, PROCESS_VM_READ,
+        PROCESS_VM_WRITE,
+    };
+
+    use super::{Game, MemoryRegion, RunningGame};
+
+    const EXES: [(Game, &str); 3] = [
+        (Game::Gta3, "gta3.exe"),
+        (Game::Vc, "gta-vc.exe"),
+        (Game::Sa, "gta_sa.exe"),
+    ];
+
+    /// 句柄在离开作用域时关闭
+    struct Handle(HANDLE);
+
+    impl Drop for Handle {
+        fn drop(&mut self) {
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+
+    pub fn find_games() -> Result<Vec<RunningGame>, String> {
+        let snap = unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) };
+        if snap == INVALID_HANDLE_VALUE {
+            return Err("Enumerate processes failed".into());
+        }
+        let snap = Handle(snap);
+
+        let mut out = Vec::new();
+        let mut entry: PROCESSENTRY32W = unsafe { zeroed() };
+        entry.dwSize = size_of::<PROCESSENTRY32W>() as u32;
+        let mut ok = unsafe { Process32FirstW(snap.0, &mut entry) } != 0;
+        while ok {
+            let len = entry
+                .szExeFile
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(entry.szExeFile.len());
+            let exe = String::from_utf16_lossy(&entry.szExeFile[..len]).to_lowercase();
+            if let Some(&(game, _)) = EXES.iter().find(|(_, name)| *name == exe) {
+                out.push(RunningGame {
+                    game,
+                    pid: entry.th32ProcessID,
+                });
+            }
+            ok = unsafe { Process32NextW(snap.0, &mut entry) } != 0;
+        }
+        Ok(out)
+    }
+
+    /// 把 bytes 写到进程里的各个地址；返回写成功了几处
+    pub fn write_memory(pid: u32, addrs: &[usize], bytes: &[u8]) -> Result<usize, String> {
+        let access = PROCESS_VM_WRITE | PROCESS_VM_OPERATION | PROCESS_QUERY_INFORMATION;
+        let h = unsafe { OpenProcess(access, 0, pid) };
+        if h.is_null() {
+            return Err(format!(
+                "Open game process {pid} failed (run the editor with the same privileges as the game)"
+            ));
+        }
+        let h = Handle(h);
+
+        let mut replaced = 0;
+        for &addr in addrs {
+            let mut written = 0usize;
+            let ok = unsafe {
+                WriteProcessMemory(
+                    h.0,
+                    addr as *const c_void,
+                    bytes.as_ptr() as *const c_void,
+                    bytes.len(),
+                    &mut written,
+                )
+            } != 0;
+            if ok && written == bytes.len() {
+                replaced += 1;
+            }
+        }
+        Ok(replaced)
+    }
+
+    /// 进程所有已提交、可读的内存
+    pub fn read_memory(pid: u32) -> Result<Vec<MemoryRegion>, String> {
+        let h = unsafe { OpenProcess(PROCESS_VM_READ | PROCESS_QUERY_INFORMATION, 0, pid) };
+        if h.is_null() {
+            return Err(format!(
+                "Open game process {pid} failed (run the editor with the same privileges as the game)"
+            ));
+        }
+        let h = Handle(h);
+
+        let readable = PAGE_READONLY
+            | PAGE_READWRITE
+            | PAGE_WRITECOPY
+            | PAGE_EXECUTE_READ
+            | PAGE_EXECUTE_READWRITE
+            | PAGE_EXECUTE_WRITECOPY;
+        let mut out = Vec::new();
+        let mut addr: usize = 0;
+        loop {
+            let mut info: MEMORY_BASIC_INFORMATION = unsafe { zeroed() };
+            let n = unsafe {
+                VirtualQueryEx(
+                    h.0,
+                    addr as *const c_void,
+                    &mut info,
+                    size_of::<MEMORY_BASIC_INFORMATION>(),
+                )
+            };
+            if n == 0 {
+                break;
+            }
+            let base = info.BaseAddress as usize;
+            let size = info.RegionSize;
+            addr = base.saturating_add(size);
+
+            if info.State == MEM_COMMIT
+                && info.Protect & readable != 0
+                && info.Protect & PAGE_GUARD == 0
+            {
+                let mut buf = vec![0u8; size];
+                let mut read = 0usize;
+                let ok = unsafe {
+                    ReadProcessMemory(
+                        h.0,
+                        base as *const c_void,
+                        buf.as_mut_ptr() as *mut c_void,
+                        size,
+                        &mut read,
+                    )
+                } != 0;
+                if ok {
+                    buf.truncate(read);
+                    out.push(MemoryRegion { base, bytes: buf });
+                }
+            }
+            if addr == 0 {
+                break;
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use super::{MemoryRegion, RunningGame};
+
+    pub fn find_games() -> Result<Vec<RunningGame>, String> {
+        Err("In-game preview is only supported on Windows".into())
+    }
+
+    pub fn write_memory(_pid: u32, _addrs: &[usize], _bytes: &[u8]) -> Result<usize, String> {
+        Err("In-game preview is only supported on Windows".into())
+    }
+
+    pub fn read_memory(_pid: u32) -> Result<Vec<MemoryRegion>, String> {
+        Err("Reading game memory is only supported on Windows".into())
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use gxt_core::EntryDiff;
+
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+/// 文本项目（`KEY=value` 每行一条）中某条目最后一次改动的提交
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryBlame {
+    pub key: String,
+    pub commit: String,
+    pub author: String,
+    pub author_mail: String,
+    /// Unix 秒
+    pub time: i64,
+    pub summary: String,
+    /// 尚未提交的改动
+    pub uncommitted: bool,
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let out = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Run git failed (is git installed?): {e}"))?;
+    if !out.status.success() {
+        return Err(format!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or(""),
+            String::from_utf8_lossy(&out.stderr).trim()
+        ));
+    }
+    String::from_utf8(out.stdout).map_err(|e| format!("git output is not UTF-8: {e}"))
+}
+
+/// 文件在仓库中的位置；不在仓库里时报错
+struct Located {
+    /// 文件所在目录（git -C 的工作目录）
+    dir: PathBuf,
+    /// 文件名（相对 dir，给 blame / ls-files 用）
+    name: String,
+    /// 相对仓库根的路径（给 `HEAD:<path>` 用）
+    repo_path: String,
+}
+
+fn locate(path: &str) -> Result<Located, String> {
+    let path = Path::new(path);
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid path")?;
+    let prefix = run_git(&dir, &["rev-parse", "--show-prefix"])
+        .map_err(|_| format!("{} is not inside a git repository", path.display()))?;
+    Ok(Located {
+        repo_path: format!("{}{}", prefix.trim_end(), name),
+        name: name.to_string(),
+        dir,
+    })
+}
+
+/// 文本项目是否位于 git 仓库中（前端据此决定显示历史相关按钮）
+#[tauri::command]
+pub fn gxt_git_is_tracked(path: String) -> bool {
+    locate(&path)
+        .and_then(|l| run_git(&l.dir, &["ls-files", "--error-unmatch", "--", &l.name]))
+        .is_ok()
+}
+
+/// 每个条目最后一次被修改的提交/作者（基于 git blame）
+#[tauri::command]
+pub async fn gxt_git_blame(path: String) -> Result<Vec<EntryBlame>, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let l = locate(&path)?;
+        let out = run_git(&l.dir, &["blame", "--line-porcelain", "--", &l.name])?;
+        Ok(parse_blame(&out))
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+fn parse_blame(porcelain: &str) -> Vec<EntryBlame> {
+    let mut out = Vec::new();
+    let mut cur: Option<EntryBlame> = None;
+
+    for line in porcelain.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            // 一行内容结束一个 blame 块
+            if let Some(mut b) = cur.take() {
+                if let Some((key, _)) = content.split_once('=') {
+                    b.key = key.to_string();
+                    out.push(b);
+                }
+            }
+            continue;
+        }
+        match cur.as_mut() {
+            None => {
+                let commit = line.split(' ').next().unwrap_or("").to_string();
+                cur = Some(EntryBlame {
+                    key: String::new(),
+                    uncommitted: commit.chars().all(|c| c == '0'),
+                    commit,
+                    author: String::new(),
+                    author_mail: String::new(),
+                    time: 0,
+                    summary: String::new(),
+                });
+            }
+            Some(b) => {
+                if let Some(v) = line.strip_prefix("author ") {
+                    b.author = v.to_string();
+                } else if let Some(v) = line.strip_prefix("author-mail ") {
+                    b.author_mail = v.trim_matches(|c| c == '<' || c == '>').to_string();
+                } else if let Some(v) = line.strip_prefix("author-time ") {
+                    b.time = v.parse().unwrap_or(0);
+                } else if let Some(v) = line.strip_prefix("summary ") {
+                    b.summary = v.to_string();
+                }
+            }
+        }
+    }
+    out
+}
+
+/// 文档 与 该文本文件在 HEAD 中的版本 的差异（HEAD 为旧、文档为新）
+#[tauri::command]
+pub async fn gxt_git_diff_head(
+    doc_id: DocId,
+    path: String,
+    session: tauri::State<'_, Session>,
+) -> Result<EntryDiff, String> {
+    let head_text = tauri::async_runtime::spawn_blocking(move || {
+        let l = locate(&path)?;
+        run_git(&l.dir, &["show", &format!("HEAD:{}", l.repo_path)])
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+
+    let head = gxt_core::entries_from_text(&head_text)?;
+    session.with(doc_id, |open| {
+        Ok(gxt_core::diff_entries(&head, &open.doc.entries))
+    })
+}
@@ -1,424 +1,492 @@
 use serde::{Deserialize, Serialize};
 
-use std::collections::HashSet;
 use std::fs;
-use std::path::PathBuf;
-
-const MAGIC_TKEY: &[u8; 4] = b"TKEY";
-const MAGIC_TDAT: &[u8; 4] = b"TDAT";
-
-// 你的“特殊区间”
-const SPECIAL_MIN: u16 = 0x0080;
-const SPECIAL_MAX: u16 = 0x009F;
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct GxtEntry {
-    pub key: String,
-    pub value: String,
-}
+use std::path::{Path, PathBuf};
+
+use gxt_core::{parse_gxt_bytes_checked, EmbeddedSection, LoadWarning, TextFormat};
+use sha2::{Digest, Sha256};
+use tauri::{AppHandle, Manager};
+
+use crate::doc_profile;
+use crate::encode_cache::EncodeCache;
+use crate::escapes;
+use crate::hooks;
+use crate::i18n::tr;
+use crate::meta;
+use crate::mirrors;
+use crate::plugins::{self, PluginInfo};
+use crate::presets;
+use crate::session::{DocId, Session};
+use crate::snapshots;
+use crate::translit::{self, TransliteratedEntry};
+use crate::window::{detect_open_kind, OpenFile, Windows};
+
+// 解析/生成逻辑都在 gxt-core；这里只是 Tauri 命令层
+pub use gxt_core::{
+    build_gxt_bytes, build_gxt_bytes_cached, build_gxt_bytes_with, decode_values, parse_gxt_bytes,
+    parse_key_table, validate_entries, validate_key, GxtEntry,
+};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GxtDocument {
     /// None 表示“新文件/未保存过”
     pub file_path: Option<String>,
     pub entries: Vec<GxtEntry>,
+    /// 后端分配的文档 id；None 表示还没在后端登记（例如刚导入、尚未保存）
+    #[serde(default)]
+    pub doc_id: Option<DocId>,
+    /// 嵌在更大的容器文件里时 GXT 段的位置；保存时只替换这一段
+    #[serde(default)]
+    pub embedded: Option<EmbeddedSection>,
+    /// 只读打开（如当参考用的原版 american.gxt）：后端拒绝一切修改与保存
+    #[serde(default)]
+    pub read_only: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SaveResult {
     pub file_path: Option<String>,
+    /// 保存后该文档在后端的 id（首次保存未登记的文档时会新分配）
+    pub doc_id: Option<DocId>,
+    /// 嵌入的段保存后的位置（写回后长度可能变了）
+    #[serde(default)]
+    pub embedded: Option<EmbeddedSection>,
+    /// 保存前按游戏预设的要求给 key 排了序（前端要重新取条目）
+    #[serde(default)]
+    pub sorted: bool,
+    /// 写盘前按项目的音译表、或按预设换掉 emoji 等字符的条目（文档本身不变）
+    #[serde(default)]
+    pub transliterated: Vec<TransliteratedEntry>,
+    /// 另存为覆盖了一个无关的已有文件时，那个文件被移进了回收站（这里是它的路径）
+    #[serde(default)]
+    pub trashed: Option<String>,
+    /// 写到磁盘的字节数（嵌入时是整个容器文件）
+    #[serde(default)]
+    pub bytes_written: usize,
+    /// 写出的文件的 SHA-256（小写十六进制），发布流程可以直接记下来
+    #[serde(default)]
+    pub sha256: String,
+    #[serde(default)]
+    pub entry_count: usize,
+    #[serde(default)]
+    pub format: SavedFormat,
+    /// 按设置重新生成了的可读副本（见 mirrors.rs）
+    #[serde(default)]
+    pub mirrors: Vec<String>,
 }
 
-/// 只负责按路径加载（前端 open dialog 选完路径后调用；文件关联/命令行启动也调用它）
-#[tauri::command]
-pub async fn gxt_load(path: String) -> Result<GxtDocument, String> {
-    let path_buf = PathBuf::from(&path);
-
-    let bytes = tauri::async_runtime::spawn_blocking(move || fs::read(&path_buf))
-        .await
-        .map_err(|e| format!("Join error: {e}"))?
-        .map_err(|e| format!("Read file failed: {e}"))?;
-
-    let entries = parse_gxt_bytes(&bytes)?;
-    Ok(GxtDocument {
-        file_path: Some(path),
-        entries,
-    })
+/// 实际写出的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SavedFormat {
+    /// 独立的 GXT 文件
+    #[default]
+    Gxt,
+    /// 替换了容器文件里的 GXT 段
+    Embedded,
 }
 
-/// 保存：写入 doc.file_path 指定的路径（Ctrl+S / SaveAs 都走这一个）
-/// - Ctrl+S：前端会传当前 file_path
-/// - SaveAs：前端会先弹 save dialog，然后把选中的路径写进 doc.file_path 再调用本函数
-#[tauri::command]
-pub async fn gxt_save(doc: GxtDocument) -> Result<SaveResult, String> {
-    validate_entries(&doc.entries)?;
-
-    let path = doc
-        .file_path
-        .clone()
-        .ok_or_else(|| "No file_path in doc. Use Save As to choose a path first.".to_string())?;
-
-    let bytes = build_gxt_bytes(&doc.entries)?;
-    let path_buf = PathBuf::from(&path);
+/// 写出的文件内容的字节数与 SHA-256
+pub(crate) fn file_digest(bytes: &[u8]) -> (usize, String) {
+    (bytes.len(), format!("{:x}", Sha256::digest(bytes)))
+}
 
-    tauri::async_runtime::spawn_blocking(move || fs::write(path_buf, bytes))
-        .await
-        .map_err(|e| format!("Join error: {e}"))?
-        .map_err(|e| format!("Write file failed: {e}"))?;
+/// 嵌在容器文件里的 GXT 从哪里开始
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SectionAt {
+    /// 给定的字节偏移
+    Offset(usize),
+    /// 找第一个能解析的 `TKEY`
+    Scan,
+}
 
-    Ok(SaveResult {
-        file_path: Some(path),
-    })
+/// gxt_load 失败的原因
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum LoadError {
+    Failed {
+        message: String,
+    },
+    /// 不是 GXT，看起来是某种文本导出：前端可以提示改用 importer 导入（gxt_plugin_import）
+    LooksLike {
+        message: String,
+        format: TextFormat,
+        /// 对应的转换器；双语 CSV 为 None（要先打开原文再用 gxt_tms_import）
+        importer: Option<PluginInfo>,
+    },
 }
 
-/// 供前端启动时询问：如果是双击 .gxt 启动，Windows 通常会把路径放在 argv[1]
-#[tauri::command]
-pub fn gxt_startup_path() -> Option<String> {
-    let mut it = std::env::args().skip(1);
-    let p = it.next()?;
-    if p.to_lowercase().ends_with(".gxt") {
-        Some(p)
-    } else {
-        None
+impl From<String> for LoadError {
+    fn from(message: String) -> Self {
+        LoadError::Failed { message }
     }
 }
 
-// -------------------- Core: parse/build --------------------
-
-fn parse_gxt_bytes(bytes: &[u8]) -> Result<Vec<GxtEntry>, String> {
-    let mut cur = 0usize;
-
-    // TKEY
-    require_magic(bytes, &mut cur, MAGIC_TKEY)?;
-
-    // key_field_size
-    let key_field_size = read_u32_le(bytes, &mut cur)? as usize;
-    if key_field_size % 12 != 0 {
-        return Err(format!("Invalid key_field_size: {key_field_size} (not divisible by 12)"));
+/// 只负责按路径加载（前端 open dialog 选完路径后调用；文件关联/命令行启动也调用它）。
+/// at 不为空时按嵌在容器文件里的 GXT 读取。
+/// 不是 GXT 而像是文本导出（txt、FXT、CSV 等）时返回 LooksLike，而不是只报文件头不对。
+/// lenient 时重复的 key、坏的 offset 等只作为警告，丢掉读不出的条目后照常打开（抢救损坏的文件）；
+/// 读文件时发现的问题都留在文档上，用 gxt_load_warnings 取
+#[tauri::command]
+pub async fn gxt_load(
+    path: String,
+    at: Option<SectionAt>,
+    read_only: Option<bool>,
+    lenient: Option<bool>,
+    session: tauri::State<'_, Session>,
+) -> Result<GxtDocument, LoadError> {
+    let loaded = load_document_checked(path.clone(), at, lenient.unwrap_or(false)).await;
+    let (mut doc, warnings) = match loaded {
+        Ok(loaded) => loaded,
+        Err(message) if at.is_none() => return Err(sniff_failed_load(path, message).await),
+        Err(message) => return Err(message.into()),
+    };
+    doc.read_only = read_only.unwrap_or(false);
+    let doc_id = register_loaded(&session, &mut doc)?;
+    if !warnings.is_empty() {
+        tracing::warn!(count = warnings.len(), "loaded with warnings");
+        session.with(doc_id, |open| {
+            open.warnings = warnings;
+            Ok(())
+        })?;
     }
+    Ok(doc)
+}
 
-    let entry_count = key_field_size / 12;
-    let mut keys: Vec<(String, u32)> = Vec::with_capacity(entry_count);
-    let mut seen = HashSet::with_capacity(entry_count);
-
-    for _ in 0..entry_count {
-        let idx = read_u32_le(bytes, &mut cur)?;
-        let key_raw = read_bytes(bytes, &mut cur, 8)?;
-        let key = decode_key_8bytes(key_raw)?;
+/// 打开时读文件发现的问题：带 key 的针对那一条，不带的针对整个文件。保存后清空
+#[tauri::command]
+pub fn gxt_load_warnings(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<LoadWarning>, String> {
+    session.with(doc_id, |open| Ok(open.warnings.clone()))
+}
 
-        if !seen.insert(key.clone()) {
-            return Err(format!("Duplicate key in file: {key}"));
+/// 文件 → 新建：放进预设 skeleton 里游戏启动时要读的 key（III 的预设没有，即空文档）。
+/// 给了 base（游戏原版的 american.gxt 等）时再带上它的全部 key 与原文，同名的以它为准，
+/// 译者在此基础上改，游戏要读的每条文字都在。返回的文档没有 file_path，第一次保存时另存为
+#[tauri::command]
+#[tracing::instrument(skip(app, session), err)]
+pub async fn gxt_new_document(
+    app: AppHandle,
+    preset: Option<String>,
+    base: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<GxtDocument, String> {
+    let preset = match preset {
+        Some(id) => Some(app.state::<presets::Presets>().get(&id)?),
+        None => None,
+    };
+    let mut entries: Vec<GxtEntry> = preset
+        .iter()
+        .flat_map(|p| &p.skeleton)
+        .map(|s| GxtEntry {
+            key: s.key.clone(),
+            value: s.value.as_str().into(),
+        })
+        .collect();
+    if let Some(base) = base {
+        for e in load_document(base).await?.entries {
+            match entries.iter_mut().find(|x| x.key == e.key) {
+                Some(x) => x.value = e.value,
+                None => entries.push(e),
+            }
         }
-        keys.push((key, idx));
     }
-
-    // TDAT
-    require_magic(bytes, &mut cur, MAGIC_TDAT)?;
-
-    let val_field_size = read_u32_le(bytes, &mut cur)? as usize;
-    let val_field = read_bytes(bytes, &mut cur, val_field_size)?;
-
-    let mut entries = Vec::with_capacity(keys.len());
-    for (key, idx) in keys {
-        let idx_usize = idx as usize;
-        if idx_usize >= val_field.len() {
-            return Err(format!("Value offset out of range for key {key}: idx={idx}"));
-        }
-        if idx_usize % 2 != 0 {
-            return Err(format!(
-                "Value offset is not aligned (must be even) for key {key}: idx={idx}"
-            ));
-        }
-
-        let value = decode_utf16z_with_escapes(val_field, idx_usize)?;
-        entries.push(GxtEntry { key, value });
+    if preset.as_ref().is_some_and(|p| p.sorted_keys) {
+        gxt_core::sort_entries(&mut entries);
     }
-
-    Ok(entries)
+    validate_entries(&entries)?;
+    let mut doc = GxtDocument {
+        file_path: None,
+        entries,
+        doc_id: None,
+        embedded: None,
+        read_only: false,
+    };
+    session.open(&mut doc)?;
+    Ok(doc)
 }
 
-fn build_gxt_bytes(entries: &[GxtEntry]) -> Result<Vec<u8>, String> {
-    validate_entries(entries)?;
-
-    let mut out: Vec<u8> = Vec::new();
-    out.extend_from_slice(MAGIC_TKEY);
-
-    let key_field_size: u32 = (entries.len() as u32) * 12;
-    out.extend_from_slice(&key_field_size.to_le_bytes());
-
-    let mut val_field: Vec<u8> = Vec::new();
-    let mut offset: u32 = 0;
-
-    for e in entries {
-        out.extend_from_slice(&offset.to_le_bytes());
-
-        let key8 = encode_key_8bytes(&e.key)?;
-        out.extend_from_slice(&key8);
-
-        let written = encode_utf16z_with_escapes(&e.value, &mut val_field)?;
-        offset = offset
-            .checked_add(written)
-            .ok_or("TDAT size overflow (too large)")?;
+/// 读不了的文件再看一眼是不是文本导出
+async fn sniff_failed_load(path: String, message: String) -> LoadError {
+    let format = tauri::async_runtime::spawn_blocking(move || {
+        fs::read(&path)
+            .ok()
+            .and_then(|bytes| gxt_core::sniff_text_format(&bytes))
+    })
+    .await
+    .ok()
+    .flatten();
+    let Some(format) = format else {
+        return message.into();
+    };
+    let importer = plugins::importer_for(format);
+    let name = importer.as_ref().map_or("CSV", |i| i.name.as_str());
+    tracing::info!(?format, "not a GXT file, looks like a text export");
+    LoadError::LooksLike {
+        message: tr!("load.looks_like_text", format = name),
+        format,
+        importer,
     }
-
-    out.extend_from_slice(MAGIC_TDAT);
-    out.extend_from_slice(&(val_field.len() as u32).to_le_bytes());
-    out.extend_from_slice(&val_field);
-
-    Ok(out)
 }
 
-// -------------------- Validation --------------------
-
-fn validate_entries(entries: &[GxtEntry]) -> Result<(), String> {
-    let mut seen = HashSet::with_capacity(entries.len());
-    for e in entries {
-        validate_key(&e.key)?;
-        if !seen.insert(e.key.clone()) {
-            return Err(format!("Duplicate key: {}", e.key));
-        }
-    }
-    Ok(())
+/// 登记刚从磁盘读出的文档，并读入它旁边的附加信息（锁定状态等）
+pub(crate) fn register_loaded(session: &Session, doc: &mut GxtDocument) -> Result<DocId, String> {
+    let doc_id = session.open(doc)?;
+    session.with(doc_id, |open| {
+        meta::apply(open);
+        Ok(())
+    })?;
+    Ok(doc_id)
 }
 
-/// KEY：1..=8，且只允许 A-Z / 0-9
-/// KEY：1..=8 bytes，允许 ASCII 可见字符：0x20(' ')..0x7E('~')
-fn validate_key(key: &str) -> Result<(), String> {
-    let len = key.len(); // 对 ASCII 来说 len = 字节数
-    if len == 0 || len > 8 {
-        return Err(format!("Invalid KEY length (must be 1..=8 bytes): {key:?}"));
-    }
-    if !key.bytes().all(|b| (0x20..=0x7E).contains(&b)) {
-        return Err(format!(
-            "Invalid KEY chars (printable ASCII 0x20..0x7E only): {key:?}"
+/// 只读打开的文档不能保存（要另存一份时前端不带 doc_id，存成新文档）
+pub(crate) fn check_writable(app: &AppHandle, doc: &GxtDocument) -> Result<(), String> {
+    let open_read_only = doc.doc_id.is_some_and(|id| {
+        app.state::<Session>()
+            .list()
+            .iter()
+            .any(|d| d.doc_id == id && d.read_only)
+    });
+    if doc.read_only || open_read_only {
+        return Err(tr!(
+            "doc.read_only",
+            path = doc.file_path.as_deref().unwrap_or_default()
         ));
     }
     Ok(())
 }
 
-// -------------------- Key encoding/decoding --------------------
-
-fn decode_key_8bytes(raw: &[u8]) -> Result<String, String> {
-    if raw.len() != 8 {
-        return Err("Key raw size must be 8".into());
+/// 保存成功后的收尾：同步后端文档，把附加信息写到（另存为时是新的）文件旁边，留一份快照
+pub(crate) fn sync_saved(app: &AppHandle, doc: GxtDocument) -> Result<DocId, String> {
+    let path = doc.file_path.clone();
+    let session = app.state::<Session>();
+    let doc_id = session.sync(doc)?;
+    session.with(doc_id, |open| meta::store(open))?;
+    if let Some(p) = path {
+        snapshots::record(app, &p);
     }
-    let trimmed = raw
-        .iter()
-        .copied()
-        .take_while(|&b| b != 0)
-        .collect::<Vec<u8>>();
-
-    let s = String::from_utf8(trimmed).map_err(|e| format!("Key is not valid UTF-8: {e}"))?;
-    Ok(s)
+    Ok(doc_id)
 }
 
-fn encode_key_8bytes(key: &str) -> Result<[u8; 8], String> {
-    validate_key(key)?;
-    let bytes = key.as_bytes();
-    let mut out = [0u8; 8];
-    out[..bytes.len()].copy_from_slice(bytes);
-    Ok(out)
+/// 其它需要“按路径读出整份文档”的命令都用它
+pub(crate) async fn load_document(path: String) -> Result<GxtDocument, String> {
+    load_document_at(path, None).await
 }
 
-// -------------------- UTF-16Z decode/encode with escapes --------------------
-
-fn decode_utf16z_with_escapes(val_field: &[u8], start: usize) -> Result<String, String> {
-    let mut units: Vec<u16> = Vec::new();
-    let mut p = start;
-
-    while p + 1 < val_field.len() {
-        let u = u16::from_le_bytes([val_field[p], val_field[p + 1]]);
-        p += 2;
-        if u == 0 {
-            break;
-        }
-        units.push(u);
-    }
-
-    if p > val_field.len() {
-        return Err("Unexpected EOF while reading UTF-16Z".into());
-    }
-
-    Ok(units_to_string_with_escapes(&units))
+/// 按路径加载，不管读文件时的警告
+pub(crate) async fn load_document_at(
+    path: String,
+    at: Option<SectionAt>,
+) -> Result<GxtDocument, String> {
+    Ok(load_document_checked(path, at, false).await?.0)
 }
 
-fn units_to_string_with_escapes(units: &[u16]) -> String {
-    let mut out = String::new();
-    let mut i = 0;
-
-    while i < units.len() {
-        let u = units[i];
-
-        // surrogate pair -> Unicode
-        if (0xD800..=0xDBFF).contains(&u) && i + 1 < units.len() {
-            let lo = units[i + 1];
-            if (0xDC00..=0xDFFF).contains(&lo) {
-                let hi = (u as u32) - 0xD800;
-                let lo = (lo as u32) - 0xDC00;
-                let cp = 0x10000 + ((hi << 10) | lo);
-                if let Some(ch) = char::from_u32(cp) {
-                    out.push(ch);
-                    i += 2;
-                    continue;
-                }
-            }
-        }
+/// gxt_load 的实际实现
+#[tracing::instrument(err)]
+pub(crate) async fn load_document_checked(
+    path: String,
+    at: Option<SectionAt>,
+    lenient: bool,
+) -> Result<(GxtDocument, Vec<LoadWarning>), String> {
+    let path_buf = PathBuf::from(&path);
 
-        // 特殊区间 + 不成对 surrogate：输出可逆转义
-        if (SPECIAL_MIN..=SPECIAL_MAX).contains(&u) || (0xD800..=0xDFFF).contains(&u) {
-            out.push_str(&format!("\\u{{{:04X}}}", u));
-            i += 1;
-            continue;
+    let bytes = tauri::async_runtime::spawn_blocking(move || fs::read(&path_buf))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))?
+        .map_err(|e| tr!("file.read_failed", error = e))?;
+
+    let embedded = match at {
+        None => None,
+        Some(SectionAt::Offset(offset)) => Some(gxt_core::section_at(&bytes, offset)?),
+        Some(SectionAt::Scan) => {
+            Some(gxt_core::find_section(&bytes).ok_or("No GXT section (TKEY) found in the file")?)
         }
+    };
+    let section = match embedded {
+        Some(s) => &bytes[s.offset..s.offset + s.len],
+        None => &bytes[..],
+    };
+    let (mut entries, warnings) = parse_gxt_bytes_checked(section, lenient)?;
+    doc_profile::decode_entries(&path, &mut entries);
+    tracing::info!(entries = entries.len(), bytes = bytes.len(), embedded = ?embedded, "loaded");
+    let doc = GxtDocument {
+        file_path: Some(path),
+        entries,
+        doc_id: None,
+        embedded,
+        read_only: false,
+    };
+    Ok((doc, warnings))
+}
 
-        // 普通 BMP
-        if let Some(ch) = char::from_u32(u as u32) {
-            out.push(ch);
-        } else {
-            out.push_str(&format!("\\u{{{:04X}}}", u));
-        }
-        i += 1;
+/// 保存：写入 doc.file_path 指定的路径（Ctrl+S / SaveAs 都走这一个）
+/// - Ctrl+S：前端会传当前 file_path
+/// - SaveAs：前端会先弹 save dialog，然后把选中的路径写进 doc.file_path 再调用本函数
+#[tauri::command]
+pub async fn gxt_save(
+    app: AppHandle,
+    mut doc: GxtDocument,
+    cache: tauri::State<'_, EncodeCache>,
+) -> Result<SaveResult, String> {
+    check_writable(&app, &doc)?;
+    escapes::check_for_save(&app, &doc)?;
+    let sorted = presets::sort_for_save(&app, &mut doc);
+    let (written, transliterated) = translit::for_save(&app, &doc);
+    let replaced = replaced_target(&app, &doc);
+    let mut res = save_document(written, &cache, replaced).await?;
+    res.sorted = sorted;
+    res.transliterated = transliterated;
+    res.mirrors = mirrors::write_mirrors(&app, &doc);
+    if let Some(p) = &res.file_path {
+        hooks::run_post_save(&app, p);
     }
-
-    out
+    // 前端传来的才是最新内容，保存成功后以它为准刷新后端文档/索引
+    doc.embedded = res.embedded;
+    res.doc_id = Some(sync_saved(&app, doc)?);
+    Ok(res)
 }
 
-fn encode_utf16z_with_escapes(s: &str, out: &mut Vec<u8>) -> Result<u32, String> {
-    let start_len = out.len();
-    let bytes = s.as_bytes();
-    let mut i = 0usize;
-
-    while i < bytes.len() {
-        if bytes[i] == b'\\' {
-            // \\ => literal '\'
-            if i + 1 < bytes.len() && bytes[i + 1] == b'\\' {
-                push_u16_le(out, b'\\' as u16);
-                i += 2;
-                continue;
-            }
-
-            // \xNNNN
-            if i + 5 < bytes.len() && bytes[i + 1] == b'x' {
-                if let Some(u) = parse_fixed_4hex(&bytes[(i + 2)..(i + 6)]) {
-                    push_u16_le(out, u);
-                    i += 6;
-                    continue;
-                }
-            }
+/// gxt_save 的实际实现
+#[tracing::instrument(skip_all, fields(path = ?doc.file_path), err)]
+pub(crate) async fn save_document(
+    doc: GxtDocument,
+    cache: &EncodeCache,
+    replaced: Option<String>,
+) -> Result<SaveResult, String> {
+    validate_entries(&doc.entries)?;
 
-            // \uNNNN
-            if i + 5 < bytes.len() && bytes[i + 1] == b'u' && bytes[i + 2] != b'{' {
-                if let Some(u) = parse_fixed_4hex(&bytes[(i + 2)..(i + 6)]) {
-                    push_u16_le(out, u);
-                    i += 6;
-                    continue;
-                }
-            }
+    let path = doc.file_path.clone().ok_or_else(|| tr!("file.no_path"))?;
 
-            // \u{...}
-            if i + 3 < bytes.len() && bytes[i + 1] == b'u' && bytes[i + 2] == b'{' {
-                if let Some((cp, consumed)) = parse_braced_hex(&bytes[(i + 3)..]) {
-                    if cp <= 0x10FFFF {
-                        if cp <= 0xFFFF {
-                            push_u16_le(out, cp as u16);
-                        } else {
-                            let cp2 = cp - 0x10000;
-                            let hi = 0xD800 | ((cp2 >> 10) as u16);
-                            let lo = 0xDC00 | ((cp2 & 0x3FF) as u16);
-                            push_u16_le(out, hi);
-                            push_u16_le(out, lo);
-                        }
-                        i += 3 + consumed; // "\" "u" "{" + ... "}"
-                        continue;
-                    } else {
-                        return Err(format!("Invalid codepoint in \\u{{...}}: {cp:X}"));
-                    }
+    // 同一路径反复保存时，没改过的 value 直接复用上次编码结果
+    let encoded = doc_profile::encode_entries(&path, &doc.entries);
+    let entries = encoded.as_deref().unwrap_or(&doc.entries);
+    let mut value_cache = cache.take(&path);
+    let built = build_gxt_bytes_cached(entries, &mut value_cache);
+    cache.put_back(path.clone(), value_cache);
+    let bytes = built?;
+    let size = bytes.len();
+    let path_buf = PathBuf::from(&path);
+    let section = doc.embedded;
+    let trashed = replaced.clone();
+
+    let (embedded, (bytes_written, sha256)) =
+        tauri::async_runtime::spawn_blocking(move || match section {
+            None => {
+                // 新内容已经生成好，这时才把要覆盖的旧文件移走
+                if let Some(old) = &replaced {
+                    trash_file(old)?;
                 }
+                fs::write(&path_buf, &bytes)
+                    .map(|_| (None, file_digest(&bytes)))
+                    .map_err(|e| tr!("file.write_failed", error = e))
             }
+            Some(section) => write_embedded(&path_buf, section, &bytes)
+                .map(|(section, file)| (Some(section), file_digest(&file))),
+        })
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))??;
 
-            // fallback: treat '\' as normal char
-            push_u16_le(out, b'\\' as u16);
-            i += 1;
-            continue;
-        }
-
-        let ch = match s[i..].chars().next() {
-            Some(c) => c,
-            None => break,
-        };
-        let mut buf = [0u16; 2];
-        let encoded = ch.encode_utf16(&mut buf);
-        for &u in encoded.iter() {
-            push_u16_le(out, u);
-        }
-        i += ch.len_utf8();
-    }
-
-    // 0 terminator
-    push_u16_le(out, 0);
-
-    let written = out.len() - start_len;
-    Ok(u32::try_from(written).map_err(|_| "TDAT chunk too large".to_string())?)
-}
-
-fn push_u16_le(out: &mut Vec<u8>, u: u16) {
-    out.extend_from_slice(&u.to_le_bytes());
+    tracing::info!(entries = doc.entries.len(), bytes = size, sha256 = %sha256, "saved");
+    Ok(SaveResult {
+        file_path: Some(path),
+        doc_id: doc.doc_id,
+        format: if embedded.is_some() {
+            SavedFormat::Embedded
+        } else {
+            SavedFormat::Gxt
+        },
+        embedded,
+        sorted: false,
+        transliterated: Vec::new(),
+        trashed: if embedded.is_some() { None } else { trashed },
+        bytes_written,
+        sha256,
+        entry_count: doc.entries.len(),
+        mirrors: Vec::new(),
+    })
 }
 
-fn parse_fixed_4hex(hex4: &[u8]) -> Option<u16> {
-    if hex4.len() != 4 || !hex4.iter().all(|b| is_hex(*b)) {
+/// 另存为要覆盖一个已有的、不是本文档自己的文件时，返回这个文件的路径：保存时先把它移进系统回收站
+/// 而不是直接覆盖掉。没有 doc_id（不知道本文档原来是哪个文件）时不移
+pub(crate) fn replaced_target(app: &AppHandle, doc: &GxtDocument) -> Option<String> {
+    let path = doc.file_path.as_deref()?;
+    let id = doc.doc_id?;
+    if doc.embedded.is_some() || !Path::new(path).is_file() {
         return None;
     }
-    let s = std::str::from_utf8(hex4).ok()?;
-    u16::from_str_radix(s, 16).ok()
+    let own_path = app
+        .state::<Session>()
+        .with(id, |open| Ok(open.doc.file_path.clone()))
+        .ok()
+        .flatten();
+    match own_path {
+        Some(own) if same_file(&own, path) => None,
+        _ => Some(path.to_string()),
+    }
 }
 
-fn parse_braced_hex(input: &[u8]) -> Option<(u32, usize)> {
-    let mut j = 0usize;
-    while j < input.len() && input[j] != b'}' {
-        j += 1;
-    }
-    if j == 0 || j >= input.len() {
-        return None;
-    }
-    let hex = &input[..j];
-    if !hex.iter().all(|b| is_hex(*b)) {
-        return None;
+/// 两个路径是否指向同一个文件（大小写、相对路径、符号链接不同也算同一个）
+fn same_file(a: &str, b: &str) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
     }
-    let s = std::str::from_utf8(hex).ok()?;
-    let cp = u32::from_str_radix(s, 16).ok()?;
-    Some((cp, j + 1))
 }
 
-fn is_hex(b: u8) -> bool {
-    matches!(b, b'0'..=b'9' | b'a'..=b'f' | b'A'..=b'F')
+/// 把 replaced_target 找到的文件移进回收站；移不走时报错（不保存，免得旧文件丢了）。
+/// 要在新内容已经生成好、马上写入前调用，保存失败不会只留下一个被移走的旧文件
+pub(crate) fn trash_file(path: &str) -> Result<(), String> {
+    trash::delete(path).map_err(|e| format!("Move {path} to the recycle bin failed: {e}"))?;
+    tracing::info!(path, "replaced file moved to the recycle bin");
+    Ok(())
 }
 
-// -------------------- Low-level readers --------------------
-
-fn require_magic(bytes: &[u8], cur: &mut usize, magic: &[u8; 4]) -> Result<(), String> {
-    let got = read_bytes(bytes, cur, 4)?;
-    if got != magic {
-        return Err(format!(
-            "Magic mismatch at {:#X}: expected {:?}, got {:?}",
-            *cur - 4,
-            magic,
-            got
-        ));
-    }
-    Ok(())
+/// 把重新生成的 GXT 写回容器文件里原来的位置，前后的字节不动。
+/// 段在文件末尾时允许变长，否则新内容必须放得进原来的段。返回新段的位置与写出的整个文件
+fn write_embedded(
+    path: &Path,
+    section: EmbeddedSection,
+    rebuilt: &[u8],
+) -> Result<(EmbeddedSection, Vec<u8>), String> {
+    let container = fs::read(path).map_err(|e| tr!("file.read_failed", error = e))?;
+    let at_end = section.offset + section.len == container.len();
+    let (out, section) = gxt_core::splice_section(&container, section, rebuilt, at_end)?;
+    fs::write(path, &out).map_err(|e| tr!("file.write_failed", error = e))?;
+    Ok((section, out))
 }
 
-fn read_u32_le(bytes: &[u8], cur: &mut usize) -> Result<u32, String> {
-    let b = read_bytes(bytes, cur, 4)?;
-    Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+/// 供前端启动时询问：这个窗口一打开就要加载的文件。
+/// 主窗口取启动参数里的第一个 .gxt（双击 .gxt 启动时 Windows 会把路径放在 argv[1]），
+/// 文档窗口取创建它时指定的文件
+#[tauri::command]
+pub fn gxt_startup_path(window: tauri::Window, windows: tauri::State<'_, Windows>) -> Option<String> {
+    windows.take_pending(window.label())
 }
 
-fn read_bytes<'a>(bytes: &'a [u8], cur: &mut usize, n: usize) -> Result<&'a [u8], String> {
-    if *cur + n > bytes.len() {
-        return Err("Unexpected EOF".into());
-    }
-    let s = &bytes[*cur..*cur + n];
-    *cur += n;
-    Ok(s)
+/// 同 gxt_startup_path，连同文件类型一起返回：双击的可能是 .fxt、文本导出或项目文件，
+/// 前端按类型交给 gxt_load、gxt_plugin_import 或 gxt_project_open。认不出的文件为 None。
+/// 从 gxt:// 链接启动时带上要跳到的 key
+#[tauri::command]
+pub async fn gxt_startup_file(
+    app: AppHandle,
+    window: tauri::Window,
+    windows: tauri::State<'_, Windows>,
+) -> Result<Option<OpenFile>, String> {
+    let Some(path) = windows.take_pending(window.label()) else {
+        return Ok(None);
+    };
+    let key = windows.take_pending_key(window.label());
+    let file = tauri::async_runtime::spawn_blocking(move || {
+        let kind = detect_open_kind(&app, &path);
+        if kind.is_none() {
+            tracing::warn!(path = %path, "startup file is neither GXT nor importable");
+        }
+        kind.map(|kind| OpenFile { path, kind, key })
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?;
+    Ok(file)
 }
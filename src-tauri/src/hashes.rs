@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use gxt_core::{HashCollision, HashDictionary, HashKind};
+use tauri::{AppHandle, Manager};
+
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+/// 哈希 key 字典（按算法各一份）。第一次用到时自动读 <app data>/hashes/<crc32|jenkins>.txt
+#[derive(Default)]
+pub struct HashDictionaries {
+    by_kind: Mutex<HashMap<HashKind, HashDictionary>>,
+}
+
+impl HashDictionaries {
+    pub(crate) fn with<T>(
+        &self,
+        app: &AppHandle,
+        kind: HashKind,
+        f: impl FnOnce(&mut HashDictionary) -> T,
+    ) -> Result<T, String> {
+        let mut g = self
+            .by_kind
+            .lock()
+            .map_err(|_| "Hash dictionary lock poisoned".to_string())?;
+        let dict = g.entry(kind).or_insert_with(|| {
+            let mut d = HashDictionary::new(kind);
+            if let Some(text) = default_path(app, kind).and_then(|p| fs::read_to_string(p).ok()) {
+                d.load_text(&text);
+            }
+            d
+        });
+        Ok(f(dict))
+    }
+}
+
+fn default_path(app: &AppHandle, kind: HashKind) -> Option<PathBuf> {
+    let name = match kind {
+        HashKind::Crc32 => "crc32.txt",
+        HashKind::Jenkins => "jenkins.txt",
+    };
+    app.path()
+        .app_data_dir()
+        .ok()
+        .map(|d| d.join("hashes").join(name))
+}
+
+/// 追加读入一个字典文件，返回新增的名字数
+#[tauri::command]
+pub fn gxt_hash_dict_load(
+    app: AppHandle,
+    kind: HashKind,
+    path: String,
+    dicts: tauri::State<'_, HashDictionaries>,
+) -> Result<usize, String> {
+    let text = fs::read_to_string(&path).map_err(|e| tr!("file.read_failed", error = e))?;
+    dicts.with(&app, kind, |d| d.load_text(&text))
+}
+
+/// 哈希 -> 显示用 key（查不到的是 `0xXXXXXXXX`）
+#[tauri::command]
+pub fn gxt_hash_resolve(
+    app: AppHandle,
+    kind: HashKind,
+    hashes: Vec<u32>,
+    dicts: tauri::State<'_, HashDictionaries>,
+) -> Result<Vec<String>, String> {
+    dicts.with(&app, kind, |d| {
+        hashes.iter().map(|&h| d.name_for(h)).collect()
+    })
+}
+
+/// 显示用 key -> 写回文件的哈希（`0x` 形式原样还原）
+#[tauri::command]
+pub fn gxt_hash_unresolve(
+    app: AppHandle,
+    kind: HashKind,
+    keys: Vec<String>,
+    dicts: tauri::State<'_, HashDictionaries>,
+) -> Result<Vec<u32>, String> {
+    dicts.with(&app, kind, |d| keys.iter().map(|k| d.hash_for(k)).collect())
+}
+
+/// 一个 key 在两种算法下的哈希
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyHashes {
+    pub key: String,
+    pub crc32: u32,
+    pub jenkins: u32,
+    /// `0xXXXXXXXX`，方便直接贴进 SCM 代码
+    pub crc32_hex: String,
+    pub jenkins_hex: String,
+}
+
+/// 计算若干 key 的哈希（SA 的 CRC32、IV 的 Jenkins 同时给出）
+#[tauri::command]
+pub fn gxt_hash(keys: Vec<String>) -> Vec<KeyHashes> {
+    keys.into_iter()
+        .map(|key| {
+            let crc32 = gxt_core::crc32_key(&key);
+            let jenkins = gxt_core::jenkins_key(&key);
+            KeyHashes {
+                key,
+                crc32,
+                jenkins,
+                crc32_hex: gxt_core::format_hash(crc32),
+                jenkins_hex: gxt_core::format_hash(jenkins),
+            }
+        })
+        .collect()
+}
+
+/// 把文档转成哈希 key 格式（SA 的 CRC32、IV 的 Jenkins）之前检查：不同的 key 名算出同一个哈希时，
+/// 写出去只剩一条，游戏里其它几条的文字会被悄悄覆盖
+#[tauri::command]
+pub fn gxt_hash_collisions(
+    doc_id: DocId,
+    kind: HashKind,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<HashCollision>, String> {
+    session.with(doc_id, |open| {
+        Ok(gxt_core::hash_collisions(
+            kind,
+            open.doc.entries.iter().map(|e| e.key.as_str()),
+        ))
+    })
+}
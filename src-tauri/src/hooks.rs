@@ -0,0 +1,101 @@
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+use std::process::Command;
+
+use tauri::{AppHandle, Manager};
+
+use crate::project::Projects;
+
+/// 项目里配置的保存后要跑的外部命令（复制到游戏目录、打包、跑测试脚本等）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SaveHook {
+    /// 日志里显示的名字；缺省用程序名
+    #[serde(default)]
+    pub name: Option<String>,
+    /// 命令行模板，如 `["xcopy", "{path}", "D:\\Games\\GTA SA\\text\\", "/y"]`。可用的占位：
+    /// `{path}` 保存的文件、`{dir}` 它所在的目录、`{name}` 文件名、`{project}` 项目文件所在目录
+    pub command: Vec<String>,
+    /// 工作目录（相对项目文件所在目录）；缺省为项目文件所在目录
+    #[serde(default)]
+    pub cwd: Option<String>,
+}
+
+impl SaveHook {
+    fn label(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.command.first().map(String::as_str))
+            .unwrap_or_default()
+    }
+}
+
+/// 跑一个钩子；输出写进日志，退出码不为 0 时报错
+fn run(hook: &SaveHook, path: &Path, project_dir: &Path) -> Result<(), String> {
+    let (program, args) = hook
+        .command
+        .split_first()
+        .ok_or_else(|| format!("Save hook {} has an empty command", hook.label()))?;
+    let fill = |a: &str| {
+        a.replace("{path}", &path.to_string_lossy())
+            .replace(
+                "{dir}",
+                &path.parent().unwrap_or(Path::new("")).to_string_lossy(),
+            )
+            .replace(
+                "{name}",
+                &path.file_name().unwrap_or_default().to_string_lossy(),
+            )
+            .replace("{project}", &project_dir.to_string_lossy())
+    };
+    let cwd = match &hook.cwd {
+        Some(dir) => project_dir.join(dir),
+        None => project_dir.to_path_buf(),
+    };
+    let out = Command::new(fill(program))
+        .args(args.iter().map(|a| fill(a)))
+        .current_dir(cwd)
+        .output()
+        .map_err(|e| format!("Run save hook {} failed: {e}", hook.label()))?;
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    tracing::info!(
+        hook = hook.label(),
+        status = ?out.status.code(),
+        stdout = %stdout.trim(),
+        stderr = %stderr.trim(),
+        "save hook finished"
+    );
+    if !out.status.success() {
+        return Err(format!(
+            "Save hook {} exited with {}",
+            hook.label(),
+            out.status
+        ));
+    }
+    Ok(())
+}
+
+/// 保存成功后调用：path 属于当前项目时在后台依次跑项目设置的 post_save 钩子。
+/// 不等它们跑完，也不影响保存结果；某个钩子失败时记日志并不再跑后面的（后面的多半依赖前面的）
+pub(crate) fn run_post_save(app: &AppHandle, path: &str) {
+    let Ok(project) = app.state::<Projects>().current() else {
+        return;
+    };
+    if project.project.settings.post_save.is_empty() || !project.contains_file(path) {
+        return;
+    }
+    let path = path.to_string();
+    tauri::async_runtime::spawn_blocking(move || {
+        let project_dir = Path::new(&project.path)
+            .parent()
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        for hook in &project.project.settings.post_save {
+            if let Err(e) = run(hook, Path::new(&path), &project_dir) {
+                tracing::warn!(path = %path, error = %e, "save hook failed");
+                break;
+            }
+        }
+    });
+}
@@ -0,0 +1,420 @@
+use serde::{Deserialize, Serialize};
+
+use std::io::Write;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+
+use gxt_core::percent_decode;
+use tauri::{AppHandle, Emitter, Manager};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::collab::{self, Collab};
+use crate::events::{DocChange, EventHub};
+use crate::gxt::{self, GxtEntry};
+use crate::keylist;
+use crate::project::Projects;
+use crate::session::{self, DocId, OpenDocument, Session};
+
+/// 外部工具通过 HTTP 改了条目后推给前端，前端据此刷新
+pub const EVENT_EXTERNAL_EDIT: &str = "gxt://external-edit";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalEdit {
+    pub doc_id: DocId,
+    pub key: String,
+    /// None 表示被删除
+    pub value: Option<String>,
+}
+
+/// 请求要在这个头里（或 `?token=`）带上启动时生成的口令
+pub(crate) const TOKEN_HEADER: &str = "X-Gxt-Token";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    /// 这次启动的口令，交给要调用 API 的工具
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+struct Running {
+    server: Arc<Server>,
+    port: u16,
+    token: String,
+    thread: JoinHandle<()>,
+}
+
+/// 本地 HTTP 服务（默认不启动，由前端开关）
+#[derive(Default)]
+pub struct HttpServer {
+    running: Mutex<Option<Running>>,
+}
+
+/// 在 127.0.0.1:port 上启动；只监听本机，不对外网开放。
+/// 本机的其他程序与浏览器里的网页（DNS rebinding）也能连上来，所以每个请求都要带上
+/// 启动时生成的口令（`X-Gxt-Token` 头或 `?token=`），`Host` 也必须是 `127.0.0.1:<port>` 或 `localhost:<port>`。
+///
+/// 针对文档的请求用 `?doc=<id>` 指定文档；只打开了一份文档时可以省略。
+///
+/// - `GET    /docs`              打开的文档列表
+/// - `GET    /entries`           全部条目 JSON
+/// - `GET    /entries/{key}`     单条
+/// - `PUT    /entries/{key}`     请求体为 `{"value": "..."}`，新增或修改
+/// - `DELETE /entries/{key}`     删除
+/// - `POST   /keys/check`        请求体为 key 的 JSON 数组，返回 `{"present": [...], "missing": [...]}`
+/// - `GET    /export?format=txt|json|gxt`
+/// - `GET    /events`            Server-Sent Events 流，每条 `data:` 是一个 DocChange JSON
+#[tauri::command]
+pub fn gxt_http_start(
+    app: AppHandle,
+    port: u16,
+    http: tauri::State<'_, HttpServer>,
+) -> Result<HttpStatus, String> {
+    let mut g = http
+        .running
+        .lock()
+        .map_err(|_| "HTTP server lock poisoned".to_string())?;
+    if let Some(r) = g.as_ref() {
+        return Err(format!("HTTP server already running on port {}", r.port));
+    }
+
+    let server = Arc::new(
+        Server::http(("127.0.0.1", port)).map_err(|e| format!("Start HTTP server failed: {e}"))?,
+    );
+    let port = server
+        .server_addr()
+        .to_ip()
+        .map(|a| a.port())
+        .unwrap_or(port);
+
+    let token = make_token()?;
+
+    let worker = server.clone();
+    let access = Access {
+        port,
+        token: token.clone(),
+    };
+    let thread = std::thread::spawn(move || {
+        for req in worker.incoming_requests() {
+            handle(&app, &access, req);
+        }
+    });
+
+    *g = Some(Running {
+        server,
+        port,
+        token: token.clone(),
+        thread,
+    });
+    Ok(HttpStatus {
+        running: true,
+        port: Some(port),
+        token: Some(token),
+    })
+}
+
+#[tauri::command]
+pub fn gxt_http_stop(
+    app: AppHandle,
+    http: tauri::State<'_, HttpServer>,
+) -> Result<HttpStatus, String> {
+    let running = http
+        .running
+        .lock()
+        .map_err(|_| "HTTP server lock poisoned".to_string())?
+        .take();
+    if let Some(r) = running {
+        // 先断开 /events 流，否则它们的线程会一直等下去
+        app.state::<EventHub>().close_all();
+        r.server.unblock();
+        let _ = r.thread.join();
+    }
+    Ok(HttpStatus {
+        running: false,
+        port: None,
+        token: None,
+    })
+}
+
+#[tauri::command]
+pub fn gxt_http_status(http: tauri::State<'_, HttpServer>) -> HttpStatus {
+    let running = http
+        .running
+        .lock()
+        .ok()
+        .and_then(|g| g.as_ref().map(|r| (r.port, r.token.clone())));
+    HttpStatus {
+        running: running.is_some(),
+        port: running.as_ref().map(|r| r.0),
+        token: running.map(|r| r.1),
+    }
+}
+
+/// 操作系统给的 128 位随机数，十六进制
+pub(crate) fn make_token() -> Result<String, String> {
+    let mut bytes = [0u8; 16];
+    getrandom::getrandom(&mut bytes).map_err(|e| format!("Generate token failed: {e}"))?;
+    Ok(bytes.iter().map(|b| format!("{b:02x}")).collect())
+}
+
+/// 比较口令：长度相同时比完所有字节才出结果，不因为前面不同就提前返回
+pub(crate) fn token_matches(given: Option<&str>, token: &str) -> bool {
+    let Some(given) = given else {
+        return false;
+    };
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+/// 请求头里名为 name 的值
+pub(crate) fn header_value<'a>(req: &'a Request, name: &'static str) -> Option<&'a str> {
+    req.headers()
+        .iter()
+        .find(|h| h.field.equiv(name))
+        .map(|h| h.value.as_str())
+}
+
+// -------------------- Request handling --------------------
+
+#[derive(Deserialize)]
+struct PutBody {
+    value: String,
+}
+
+/// 处理线程检查请求用
+struct Access {
+    port: u16,
+    token: String,
+}
+
+impl Access {
+    fn check(&self, req: &Request, query: &str) -> Result<(), (u16, String)> {
+        let host = header_value(req, "Host").unwrap_or_default();
+        let allowed = [
+            format!("127.0.0.1:{}", self.port),
+            format!("localhost:{}", self.port),
+        ];
+        if !allowed.iter().any(|h| h.eq_ignore_ascii_case(host)) {
+            return Err((403, format!("Bad Host header: {host}")));
+        }
+        let given = header_value(req, TOKEN_HEADER).or_else(|| query_param(query, "token"));
+        if !token_matches(given, &self.token) {
+            return Err((401, "Bad or missing token".to_string()));
+        }
+        Ok(())
+    }
+}
+
+fn handle(app: &AppHandle, access: &Access, mut req: Request) {
+    let url = req.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((url.as_str(), ""));
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let method = req.method().clone();
+
+    if let Err(e) = access.check(&req, query) {
+        let _ = req.respond(error_response(e));
+        return;
+    }
+
+    if method == Method::Get && segments.as_slice() == ["events"] {
+        let rx = app.state::<EventHub>().subscribe();
+        std::thread::spawn(move || stream_events(req, rx));
+        return;
+    }
+
+    let result = match (&method, segments.as_slice()) {
+        (Method::Get, ["docs"]) => json(&app.state::<Session>().list()),
+        (Method::Get, ["entries"]) => list_entries(app, query),
+        (Method::Get, ["entries", key]) => get_entry(app, query, &percent_decode(key)),
+        (Method::Put, ["entries", key]) => collab::read_body(&mut req)
+            .and_then(|body| put_entry(app, query, percent_decode(key), body)),
+        (Method::Delete, ["entries", key]) => delete_entry(app, query, percent_decode(key)),
+        (Method::Post, ["keys", "check"]) => {
+            collab::read_body(&mut req).and_then(|keys| check_keys(app, query, keys))
+        }
+        (Method::Get, ["export"]) => export(app, query),
+        _ => Err((404, "Not found".to_string())),
+    };
+
+    let response = match result {
+        Ok((content_type, body)) => {
+            Response::from_data(body).with_header(content_type_header(content_type))
+        }
+        Err(e) => error_response(e),
+    };
+    let _ = req.respond(response);
+}
+
+fn error_response((status, msg): (u16, String)) -> Response<std::io::Cursor<Vec<u8>>> {
+    Response::from_data(serde_json::json!({ "error": msg }).to_string().into_bytes())
+        .with_status_code(status)
+        .with_header(content_type_header("application/json"))
+}
+
+type HandlerResult = Result<(&'static str, Vec<u8>), (u16, String)>;
+
+fn content_type_header(value: &str) -> Header {
+    Header::from_bytes("Content-Type", value).expect("static header is valid")
+}
+
+fn json<T: Serialize>(value: &T) -> HandlerResult {
+    serde_json::to_vec(value)
+        .map(|b| ("application/json", b))
+        .map_err(|e| (500, format!("JSON encode failed: {e}")))
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .find_map(|kv| kv.strip_prefix(name)?.strip_prefix('='))
+}
+
+/// `?doc=<id>` 指定的文档；没给时只打开了一份文档就用它
+fn doc_id(app: &AppHandle, query: &str) -> Result<DocId, (u16, String)> {
+    if let Some(id) = query_param(query, "doc") {
+        return id
+            .parse()
+            .map_err(|_| (400, format!("Bad document id: {id}")));
+    }
+    match app.state::<Session>().list().as_slice() {
+        [only] => Ok(only.doc_id),
+        [] => Err((409, "No document is open".to_string())),
+        _ => Err((
+            400,
+            "Several documents are open; pass ?doc=<id>".to_string(),
+        )),
+    }
+}
+
+fn with_session<T>(
+    app: &AppHandle,
+    query: &str,
+    f: impl FnOnce(&mut OpenDocument) -> Result<T, String>,
+) -> Result<(DocId, T), (u16, String)> {
+    let id = doc_id(app, query)?;
+    let res = app.state::<Session>().with(id, f).map_err(|e| (409, e))?;
+    Ok((id, res))
+}
+
+/// 改文档的请求：只读打开的文档不让改
+fn edit_session<T>(
+    app: &AppHandle,
+    query: &str,
+    f: impl FnOnce(&mut OpenDocument, DocId) -> Result<T, String>,
+) -> Result<(DocId, T), (u16, String)> {
+    let id = doc_id(app, query)?;
+    let res = app
+        .state::<Session>()
+        .edit(id, |open| f(open, id))
+        .map_err(|e| (409, e))?;
+    Ok((id, res))
+}
+
+fn list_entries(app: &AppHandle, query: &str) -> HandlerResult {
+    let (_, entries) = with_session(app, query, |open| Ok(open.doc.entries.clone()))?;
+    json(&entries)
+}
+
+fn get_entry(app: &AppHandle, query: &str, key: &str) -> HandlerResult {
+    let (_, entry) = with_session(app, query, |open| {
+        Ok(open.doc.entries.iter().find(|e| e.key == key).cloned())
+    })?;
+    match entry {
+        Some(e) => json(&e),
+        None => Err((404, format!("No such key: {key}"))),
+    }
+}
+
+fn check_keys(app: &AppHandle, query: &str, keys: Vec<String>) -> HandlerResult {
+    let (_, check) = with_session(app, query, |open| {
+        Ok(keylist::check_keys(&open.doc.entries, keys))
+    })?;
+    json(&check)
+}
+
+fn put_entry(app: &AppHandle, query: &str, key: String, body: PutBody) -> HandlerResult {
+    gxt::validate_key(&key).map_err(|e| (400, e))?;
+
+    let collab = app.state::<Collab>();
+    let (doc_id, ()) = edit_session(app, query, |open, id| {
+        session::set_entry_checked(open, &collab, id, key.clone(), body.value.clone())
+    })?;
+    let _ = app.emit(
+        EVENT_EXTERNAL_EDIT,
+        ExternalEdit {
+            doc_id,
+            key: key.clone(),
+            value: Some(body.value.clone()),
+        },
+    );
+    json(&GxtEntry {
+        key,
+        value: body.value.into(),
+    })
+}
+
+fn delete_entry(app: &AppHandle, query: &str, key: String) -> HandlerResult {
+    // 项目里受保护的 key 要带 ?force=1 才能删
+    let force = query_param(query, "force").is_some_and(|v| v == "1" || v == "true");
+    let projects = app.state::<Projects>();
+    let collab = app.state::<Collab>();
+    // 和前端删除一样，开了 soft_delete 的项目里是软删除
+    let (doc_id, removed) = edit_session(app, query, |open, id| {
+        session::remove_entry_checked(open, &projects, &collab, id, &key, force, None)
+    })?;
+    if !removed {
+        return Err((404, format!("No such key: {key}")));
+    }
+    let _ = app.emit(
+        EVENT_EXTERNAL_EDIT,
+        ExternalEdit {
+            doc_id,
+            key,
+            value: None,
+        },
+    );
+    json(&serde_json::json!({ "deleted": true }))
+}
+
+fn export(app: &AppHandle, query: &str) -> HandlerResult {
+    let format = query_param(query, "format").unwrap_or("json");
+    let (_, entries) = with_session(app, query, |open| Ok(open.doc.entries.clone()))?;
+    match format {
+        "json" => json(&entries),
+        "txt" => gxt_core::entries_to_text(&entries)
+            .map(|t| ("text/plain; charset=utf-8", t.into_bytes()))
+            .map_err(|e| (500, e)),
+        "gxt" => gxt::build_gxt_bytes(&entries)
+            .map(|b| ("application/octet-stream", b))
+            .map_err(|e| (500, e)),
+        other => Err((400, format!("Unknown export format: {other}"))),
+    }
+}
+
+/// 长连接：自己写响应头，然后每个事件写一条并立即 flush（tiny_http 的分块响应会攒缓冲）。
+/// 客户端断开后下一次写入失败即退出；HTTP 服务停止时 EventHub 断开 rx 也会退出。
+fn stream_events(req: Request, rx: Receiver<DocChange>) {
+    let mut w = req.into_writer();
+    let head = "HTTP/1.1 200 OK\r\n\
+                Content-Type: text/event-stream\r\n\
+                Cache-Control: no-cache\r\n\
+                Connection: keep-alive\r\n\r\n";
+    if w.write_all(head.as_bytes()).and_then(|_| w.flush()).is_err() {
+        return;
+    }
+    while let Ok(ev) = rx.recv() {
+        let Ok(data) = serde_json::to_string(&ev) else {
+            continue;
+        };
+        let line = format!("data: {data}\n\n");
+        if w.write_all(line.as_bytes()).and_then(|_| w.flush()).is_err() {
+            break;
+        }
+    }
+}
@@ -0,0 +1,367 @@
+//! 后端给用户看的文字（错误、警告、报告里的标签）按消息代码查表翻译。
+//!
+//! 代码形如 `file.read_failed`，文本里用 `{name}` 占位。当前语言跟随前端界面语言
+//! （gxt_set_locale，或设置里的 locale），查不到的语言/代码退回英文/代码本身。
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::sync::RwLock;
+
+use tauri::AppHandle;
+
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    ZhCn,
+}
+
+impl Locale {
+    /// BCP 47 标签（`zh-CN`、`zh_Hans`、`en-US` 等）；不认识的返回 None
+    pub fn from_tag(tag: &str) -> Option<Locale> {
+        let lang = tag
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match lang.as_str() {
+            "en" => Some(Locale::En),
+            "zh" => Some(Locale::ZhCn),
+            _ => None,
+        }
+    }
+}
+
+static CURRENT: RwLock<Locale> = RwLock::new(Locale::En);
+
+pub fn current() -> Locale {
+    CURRENT.read().map(|g| *g).unwrap_or(Locale::En)
+}
+
+pub fn set_current(locale: Locale) {
+    if let Ok(mut g) = CURRENT.write() {
+        *g = locale;
+    }
+}
+
+/// (代码, 英文, 简体中文)
+const MESSAGES: &[(&str, &str, &str)] = &[
+    (
+        "file.read_failed",
+        "Read file failed: {error}",
+        "读取文件失败：{error}",
+    ),
+    (
+        "file.write_failed",
+        "Write file failed: {error}",
+        "写入文件失败：{error}",
+    ),
+    (
+        "file.no_path",
+        "No file_path in doc. Use Save As to choose a path first.",
+        "文档还没有路径，请先用“另存为”选择保存位置。",
+    ),
+    (
+        "json.encode_failed",
+        "JSON encode failed: {error}",
+        "JSON 编码失败：{error}",
+    ),
+    (
+        "json.decode_failed",
+        "JSON decode failed: {error}",
+        "JSON 解析失败：{error}",
+    ),
+    (
+        "task.join_failed",
+        "Join error: {error}",
+        "后台任务出错：{error}",
+    ),
+    (
+        "doc.not_found",
+        "No such document: {id}",
+        "找不到文档：{id}",
+    ),
+    (
+        "doc.read_only",
+        "{path} is open read-only; save a copy to edit it",
+        "{path} 是只读打开的，要修改请另存一份",
+    ),
+    ("doc.none_open", "No document is open", "没有打开的文档"),
+    (
+        "scripts.none",
+        "No script folders given and the project has none configured",
+        "没有指定脚本目录，项目设置里也没有配置",
+    ),
+    (
+        "batch.already_open",
+        "A batch is already in progress for this document",
+        "这个文档已经在批量操作中",
+    ),
+    (
+        "batch.not_open",
+        "No batch is in progress for this document",
+        "这个文档没有进行中的批量操作",
+    ),
+    (
+        "repack.has_problems",
+        "Not repacked: the file has validation problems",
+        "有校验问题，没有重新打包",
+    ),
+    (
+        "load.looks_like_text",
+        "This is not a GXT file; it looks like {format}. Import it instead",
+        "这不是 GXT 文件，看起来是 {format}，请用导入打开",
+    ),
+    (
+        "export.bad_template",
+        "Invalid file name template: {template}",
+        "文件名模板不对：{template}",
+    ),
+    (
+        "export.unknown_field",
+        "Unknown field in file name template: {field}",
+        "文件名模板里没有这个字段：{field}",
+    ),
+    (
+        "export.name_clash",
+        "Two files would be exported as {path}; add {lang} or {basename} to the template",
+        "有两个文件都会导出为 {path}，请在模板里加上 {lang} 或 {basename}",
+    ),
+    (
+        "pins.too_many",
+        "At most {limit} entries can be pinned",
+        "最多只能固定 {limit} 条",
+    ),
+    (
+        "rollback.none",
+        "There is no batch operation to revert",
+        "没有可以撤销的批量操作",
+    ),
+    (
+        "escape.invalid",
+        "{key}: malformed escape {text} ({message}); write \\\\ for a literal backslash",
+        "{key}：转义 {text} 写错了（{message}）；要写反斜杠本身请用 \\\\",
+    ),
+    (
+        "escape.invalid_suggest",
+        "{key}: malformed escape {text} ({message}); did you mean {suggestion}?",
+        "{key}：转义 {text} 写错了（{message}），是不是想写 {suggestion}？",
+    ),
+    (
+        "bulk.no_value",
+        "Expected KEY value or KEY=value",
+        "应为 KEY 值 或 KEY=值",
+    ),
+    (
+        "bulk.exists",
+        "{key} already exists in this document",
+        "文档里已经有 {key}",
+    ),
+    (
+        "bulk.duplicate",
+        "{key} appears more than once",
+        "{key} 出现了不止一次",
+    ),
+    (
+        "script.doc_changed",
+        "The document was edited while the script ran; run it again",
+        "脚本运行期间文档被改过，请重新运行",
+    ),
+    (
+        "clip.not_found",
+        "No clipboard entry named {name}",
+        "剪贴板里没有名为 {name} 的内容",
+    ),
+    ("clip.empty", "The clipboard ring is empty", "剪贴板是空的"),
+    ("entry.not_found", "No such key: {key}", "找不到 key：{key}"),
+    (
+        "entry.locked",
+        "{key} is locked; unlock it before editing",
+        "{key} 已锁定，解锁后才能修改",
+    ),
+    (
+        "entry.protected",
+        "{key} is a protected key of this project; confirm to remove it anyway",
+        "{key} 是本项目受保护的 key，确认后才能删除",
+    ),
+    ("project.none_open", "No project is open", "没有打开的项目"),
+    (
+        "project.read_failed",
+        "Read project failed: {error}",
+        "读取项目文件失败：{error}",
+    ),
+    (
+        "project.invalid",
+        "Invalid project file: {error}",
+        "项目文件格式错误：{error}",
+    ),
+    (
+        "settings.read_failed",
+        "Read settings failed: {error}",
+        "读取设置失败：{error}",
+    ),
+    (
+        "settings.write_failed",
+        "Write settings failed: {error}",
+        "保存设置失败：{error}",
+    ),
+    (
+        "settings.invalid",
+        "Invalid settings file: {error}",
+        "设置文件格式错误：{error}",
+    ),
+    (
+        "settings.too_new",
+        "Settings were written by a newer version (format {version}); refusing to overwrite them",
+        "设置文件来自更新的版本（格式 {version}），不会覆盖它",
+    ),
+    (
+        "tm.error",
+        "Translation memory error: {error}",
+        "翻译记忆库出错：{error}",
+    ),
+    (
+        "mt.not_configured",
+        "Machine translation is not configured",
+        "还没有配置机器翻译",
+    ),
+    (
+        "journal.unavailable",
+        "Journal is not available",
+        "无法使用恢复日志",
+    ),
+    (
+        "preset.not_builtin",
+        "No builtin preset: {id}",
+        "没有内置预设：{id}",
+    ),
+    (
+        "game.not_running",
+        "No running GTA III / Vice City / San Andreas process found",
+        "没有找到正在运行的 GTA III / 罪恶都市 / 圣安地列斯",
+    ),
+    (
+        "game.save_first",
+        "Save the document first: the game reads it from disk",
+        "请先保存文档：游戏从磁盘读取文件",
+    ),
+    (
+        "game.no_text_pool",
+        "Could not find the text table in the game's memory (wait until the main menu has loaded)",
+        "没有在游戏内存里找到文本表（请等主菜单加载完成后再试）",
+    ),
+    (
+        "tms.csv_needs_source",
+        "Bilingual CSV needs a reference file for the source column",
+        "双语 CSV 需要指定原文文件（用来填原文列）",
+    ),
+    // 报告里的标签
+    ("label.added", "Added", "新增"),
+    ("label.removed", "Removed", "删除"),
+    ("label.changed", "Changed", "修改"),
+    ("label.translated", "Translated", "已翻译"),
+    ("label.untranslated", "Untranslated", "未翻译"),
+    ("label.warning", "Warning", "警告"),
+    ("label.error", "Error", "错误"),
+    ("label.other", "Other", "其它"),
+    (
+        "sort.required",
+        "{game} finds keys with a binary search: if they are not sorted by byte order, lookups fail silently and text goes missing in game.",
+        "{game} 用二分查找读取 key：没有按字节序排列时找不到条目，游戏里不报错，只是缺字。",
+    ),
+    (
+        "sort.not_required",
+        "{game} does not need sorted keys.",
+        "{game} 不要求 key 有序。",
+    ),
+    (
+        "sort.unknown",
+        "No game preset chosen. Games that need sorted keys: {games}.",
+        "没有选游戏预设。要求 key 有序的游戏：{games}。",
+    ),
+    ("label.empty", "(empty)", "（空）"),
+    ("changelog.title", "Changelog", "更新日志"),
+    (
+        "changelog.summary",
+        "{added} added, {changed} changed, {removed} removed",
+        "新增 {added} 条，修改 {changed} 条，删除 {removed} 条",
+    ),
+    ("changelog.no_changes", "No text changes.", "文本没有变化。"),
+    (
+        "review.no_author",
+        "Set your author name in the settings before approving",
+        "确认前请先在设置里填写作者名",
+    ),
+    (
+        "review.own_edit",
+        "{author} made the last edit; a second person must approve it",
+        "最后一次修改是 {author} 做的，需要另一个人确认",
+    ),
+    (
+        "review.not_assignee",
+        "Assigned to {reviewer}",
+        "已指派给 {reviewer}",
+    ),
+    ("review.empty", "Nothing to approve (empty value)", "值为空，无需确认"),
+    ("review.no_key", "No such key", "没有这个 key"),
+];
+
+fn lookup(code: &str, locale: Locale) -> Option<&'static str> {
+    let (_, en, zh) = MESSAGES.iter().find(|(c, _, _)| *c == code)?;
+    Some(match locale {
+        Locale::En => en,
+        Locale::ZhCn => zh,
+    })
+}
+
+/// 按当前语言渲染一条消息；一般用 `tr!` 宏
+pub fn translate(code: &str, args: &[(&str, &dyn Display)]) -> String {
+    let Some(text) = lookup(code, current()) else {
+        return code.to_string();
+    };
+    let mut out = text.to_string();
+    for (name, value) in args {
+        out = out.replace(&format!("{{{name}}}"), &value.to_string());
+    }
+    out
+}
+
+/// `tr!("file.read_failed", error = e)`
+macro_rules! tr {
+    ($code:literal $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::translate($code, &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*])
+    };
+}
+pub(crate) use tr;
+
+/// 启动时按设置里的语言初始化
+pub fn init(app: &AppHandle) {
+    if let Some(l) = settings::load(app)
+        .ok()
+        .and_then(|s| s.locale)
+        .and_then(|t| Locale::from_tag(&t))
+    {
+        set_current(l);
+    }
+}
+
+/// 前端切换界面语言时调用；之后的错误信息都用这个语言。会记进设置
+#[tauri::command]
+pub fn gxt_set_locale(app: AppHandle, locale: String) -> Result<(), String> {
+    set_current(Locale::from_tag(&locale).unwrap_or(Locale::En));
+    settings::update(&app, |s| s.locale = Some(locale))
+}
+
+/// 某个语言（缺省为当前语言）的整张消息表：代码 -> 文本，供前端显示报告标签等
+#[tauri::command]
+pub fn gxt_messages(locale: Option<String>) -> BTreeMap<&'static str, &'static str> {
+    let locale = locale
+        .as_deref()
+        .and_then(Locale::from_tag)
+        .unwrap_or_else(current);
+    MESSAGES
+        .iter()
+        .filter_map(|(code, ..)| Some((*code, lookup(code, locale)?)))
+        .collect()
+}
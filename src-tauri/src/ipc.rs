@@ -0,0 +1,176 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{InvokeBody, InvokeResponseBody, Request, Response};
+use tauri::AppHandle;
+
+use crate::encode_cache::EncodeCache;
+use crate::escapes;
+use crate::gxt::{self, GxtDocument, SaveResult};
+use crate::hooks;
+use crate::i18n::tr;
+use crate::mirrors;
+use crate::presets;
+use crate::session::Session;
+use crate::translit;
+
+/// raw 请求里用来声明请求体编码的 header
+const TRANSPORT_HEADER: &str = "x-gxt-transport";
+
+/// 大文档的传输方式。默认 JSON 与 gxt_load 完全一致；
+/// 前端在条目很多时可以改用 MessagePack，省掉 JSON 字符串化/解析的开销。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    #[default]
+    Json,
+    Msgpack,
+}
+
+/// 把任意可序列化结果按指定方式编码成 raw response（前端拿到的是 ArrayBuffer）
+pub fn encode_response<T: Serialize>(value: &T, transport: Transport) -> Result<Response, String> {
+    let bytes = match transport {
+        Transport::Json => {
+            serde_json::to_vec(value).map_err(|e| tr!("json.encode_failed", error = e))?
+        }
+        Transport::Msgpack => {
+            rmp_serde::to_vec_named(value).map_err(|e| format!("MessagePack encode failed: {e}"))?
+        }
+    };
+    Ok(Response::new(bytes))
+}
+
+/// 从 raw 请求体解码（与 encode_response 对应）
+pub fn decode_body<T: for<'de> Deserialize<'de>>(bytes: &[u8], transport: Transport) -> Result<T, String> {
+    match transport {
+        Transport::Json => {
+            serde_json::from_slice(bytes).map_err(|e| tr!("json.decode_failed", error = e))
+        }
+        Transport::Msgpack => {
+            rmp_serde::from_slice(bytes).map_err(|e| format!("MessagePack decode failed: {e}"))
+        }
+    }
+}
+
+/// 前端启动时询问后端支持哪些传输方式，再决定走哪条路
+#[tauri::command]
+pub fn gxt_ipc_transports() -> Vec<Transport> {
+    vec![Transport::Json, Transport::Msgpack]
+}
+
+/// 与 gxt_load 相同，但结果以二进制 raw response 返回
+#[tauri::command]
+pub async fn gxt_load_raw(
+    path: String,
+    transport: Option<Transport>,
+    session: tauri::State<'_, Session>,
+) -> Result<Response, String> {
+    let mut doc: GxtDocument = gxt::load_document(path).await?;
+    gxt::register_loaded(&session, &mut doc)?;
+    encode_response(&doc, transport.unwrap_or_default())
+}
+
+/// 与 gxt_save 相同，但文档以 raw 请求体上传：
+/// `invoke("gxt_save_raw", bytes, { headers: { "x-gxt-transport": "msgpack" } })`
+#[tauri::command]
+pub async fn gxt_save_raw(
+    app: AppHandle,
+    request: Request<'_>,
+    cache: tauri::State<'_, EncodeCache>,
+) -> Result<SaveResult, String> {
+    let transport = match request
+        .headers()
+        .get(TRANSPORT_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        Some("msgpack") => Transport::Msgpack,
+        Some("json") | None => Transport::Json,
+        Some(other) => return Err(format!("Unknown transport: {other}")),
+    };
+
+    let mut doc: GxtDocument = match request.body() {
+        InvokeBody::Raw(bytes) => decode_body(bytes, transport)?,
+        InvokeBody::Json(v) => {
+            serde_json::from_value(v.clone()).map_err(|e| tr!("json.decode_failed", error = e))?
+        }
+    };
+
+    gxt::check_writable(&app, &doc)?;
+    escapes::check_for_save(&app, &doc)?;
+    let sorted = presets::sort_for_save(&app, &mut doc);
+    let (written, transliterated) = translit::for_save(&app, &doc);
+    let replaced = gxt::replaced_target(&app, &doc);
+    let mut res = gxt::save_document(written, &cache, replaced).await?;
+    res.sorted = sorted;
+    res.transliterated = transliterated;
+    res.mirrors = mirrors::write_mirrors(&app, &doc);
+    if let Some(p) = &res.file_path {
+        hooks::run_post_save(&app, p);
+    }
+    doc.embedded = res.embedded;
+    res.doc_id = Some(gxt::sync_saved(&app, doc)?);
+    Ok(res)
+}
+
+/// 内存里的 GXT 字节以什么形式传：raw 为二进制（前端拿到/传的是 ArrayBuffer），
+/// base64 为 JSON 里的字符串（给不方便传二进制的调用方）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BytesEncoding {
+    #[default]
+    Raw,
+    Base64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct Base64Body {
+    base64: String,
+}
+
+/// 从内存里的 GXT 字节打开文档（拖进来的文件内容、预览等），不读写磁盘。
+/// 请求体为 GXT 字节本身，或 JSON `{ "base64": "..." }`；文档没有路径，保存时要另存为
+#[tauri::command]
+pub fn gxt_load_bytes(
+    request: Request<'_>,
+    session: tauri::State<'_, Session>,
+) -> Result<GxtDocument, String> {
+    let decoded;
+    let bytes = match request.body() {
+        InvokeBody::Raw(bytes) => &bytes[..],
+        InvokeBody::Json(v) => {
+            let body: Base64Body = serde_json::from_value(v.clone())
+                .map_err(|e| tr!("json.decode_failed", error = e))?;
+            decoded = BASE64
+                .decode(body.base64.trim())
+                .map_err(|e| format!("Invalid base64: {e}"))?;
+            &decoded[..]
+        }
+    };
+    let mut doc = GxtDocument {
+        file_path: None,
+        entries: gxt::parse_gxt_bytes(bytes)?,
+        doc_id: None,
+        embedded: None,
+        read_only: false,
+    };
+    gxt::register_loaded(&session, &mut doc)?;
+    Ok(doc)
+}
+
+/// 把文档生成 GXT 字节直接返回，不写盘（不影响文档的保存状态）
+#[tauri::command]
+pub fn gxt_save_bytes(
+    doc: GxtDocument,
+    encoding: Option<BytesEncoding>,
+) -> Result<Response, String> {
+    gxt::validate_entries(&doc.entries)?;
+    let bytes = gxt::build_gxt_bytes(&doc.entries)?;
+    Ok(match encoding.unwrap_or_default() {
+        BytesEncoding::Raw => Response::new(bytes),
+        BytesEncoding::Base64 => {
+            let json = serde_json::to_string(&BASE64.encode(bytes))
+                .map_err(|e| tr!("json.encode_failed", error = e))?;
+            Response::new(InvokeResponseBody::Json(json))
+        }
+    })
+}
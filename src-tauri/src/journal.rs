@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use tauri::{AppHandle, Manager};
+
+use crate::events::DocEvent;
+use crate::gxt::{self, GxtDocument, GxtEntry};
+use crate::meta;
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+const JOURNAL_DIR: &str = "journal";
+const JOURNAL_EXT: &str = "jsonl";
+
+/// 日志里的一行。第一行总是 Header，记录以哪个文件为基准；之后按顺序重放
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+enum Record {
+    Header {
+        file_path: Option<String>,
+    },
+    Set {
+        key: String,
+        value: String,
+    },
+    Delete {
+        key: String,
+    },
+    /// 整体替换（批量操作、导入的文档）
+    Snapshot {
+        entries: Vec<GxtEntry>,
+    },
+}
+
+/// tauri managed state：每个打开的文档一份预写日志（<app data>/journal/<pid>-<doc id>.jsonl）。
+/// 每次修改都追加一行，保存后清空，关闭文档时删除；崩溃后留下的日志可以重放恢复
+pub struct Journal {
+    dir: Option<PathBuf>,
+    open: Mutex<HashMap<DocId, File>>,
+}
+
+impl Journal {
+    pub fn new(app: &AppHandle) -> Self {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .ok()
+            .map(|d| d.join(JOURNAL_DIR))
+            .filter(|d| fs::create_dir_all(d).is_ok());
+        Journal {
+            dir,
+            open: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn path_of(&self, doc_id: DocId) -> Option<PathBuf> {
+        let name = format!("{}-{doc_id}.{JOURNAL_EXT}", std::process::id());
+        Some(self.dir.as_ref()?.join(name))
+    }
+
+    /// 从头开始一份日志
+    fn start(&self, doc_id: DocId, file_path: Option<String>) -> Option<File> {
+        let mut f = File::create(self.path_of(doc_id)?).ok()?;
+        append(&mut f, &Record::Header { file_path });
+        Some(f)
+    }
+
+    /// 把一批变更事件写进日志（由 events::dispatch 调用，此时 Session 的锁已释放）
+    pub fn record(&self, app: &AppHandle, doc_id: DocId, events: &[DocEvent]) {
+        let Ok(mut files) = self.open.lock() else {
+            return;
+        };
+        for ev in events {
+            match ev {
+                DocEvent::EntryAdded { key, value } | DocEvent::EntryChanged { key, value } => {
+                    if let Some(f) = files.get_mut(&doc_id) {
+                        append(
+                            f,
+                            &Record::Set {
+                                key: key.clone(),
+                                value: value.clone(),
+                            },
+                        );
+                    }
+                }
+                DocEvent::EntryDeleted { key } => {
+                    if let Some(f) = files.get_mut(&doc_id) {
+                        append(f, &Record::Delete { key: key.clone() });
+                    }
+                }
+                DocEvent::DocumentReplaced { file_path, .. } => {
+                    // 刚打开的文件以磁盘内容为基准即可；其它情况要记下整份内容
+                    let fresh = !files.contains_key(&doc_id);
+                    if fresh {
+                        if let Some(f) = self.start(doc_id, file_path.clone()) {
+                            files.insert(doc_id, f);
+                        }
+                    }
+                    if !fresh || file_path.is_none() {
+                        let entries = app
+                            .state::<Session>()
+                            .with(doc_id, |open| Ok(open.doc.entries.clone()));
+                        if let (Some(f), Ok(entries)) = (files.get_mut(&doc_id), entries) {
+                            append(f, &Record::Snapshot { entries });
+                        }
+                    }
+                }
+                DocEvent::DocumentSaved { file_path, .. } => {
+                    if let Some(f) = self.start(doc_id, file_path.clone()) {
+                        files.insert(doc_id, f);
+                    }
+                }
+                DocEvent::DocumentClosed => {
+                    files.remove(&doc_id);
+                    if let Some(p) = self.path_of(doc_id) {
+                        let _ = fs::remove_file(p);
+                    }
+                }
+                DocEvent::DirtyChanged { .. } => {}
+            }
+        }
+    }
+}
+
+fn append(f: &mut File, record: &Record) {
+    if let Ok(mut line) = serde_json::to_string(record) {
+        line.push('\n');
+        let _ = f.write_all(line.as_bytes());
+    }
+}
+
+fn read_records(path: &Path) -> Result<Vec<Record>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("Read journal failed: {e}"))?;
+    // 崩溃时最后一行可能只写了一半，解析不了的行忽略
+    Ok(text
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect())
+}
+
+/// 上次没有正常关闭时留下的日志
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoverableJournal {
+    pub id: String,
+    /// 基准文件；None 表示当时是未保存过的新文档
+    pub file_path: Option<String>,
+    /// 基准之后的修改条数
+    pub edits: usize,
+    /// 最后修改时间（Unix 秒）
+    pub modified: u64,
+}
+
+fn journal_file(journal: &Journal, id: &str) -> Result<PathBuf, String> {
+    let dir = journal.dir.as_ref().ok_or_else(|| tr!("journal.unavailable"))?;
+    if id.contains(['/', '\\']) || id.contains("..") {
+        return Err(format!("Bad journal id: {id}"));
+    }
+    Ok(dir.join(format!("{id}.{JOURNAL_EXT}")))
+}
+
+/// 列出可以恢复的日志（不属于本次运行、且有修改的）
+#[tauri::command]
+pub fn gxt_journal_list(
+    journal: tauri::State<'_, Journal>,
+) -> Result<Vec<RecoverableJournal>, String> {
+    let Some(dir) = &journal.dir else {
+        return Ok(Vec::new());
+    };
+    let mine = format!("{}-", std::process::id());
+    let mut out = Vec::new();
+    for item in fs::read_dir(dir)
+        .map_err(|e| format!("Read journal dir failed: {e}"))?
+        .flatten()
+    {
+        let path = item.path();
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        if id.starts_with(&mine) || path.extension().is_none_or(|x| x != JOURNAL_EXT) {
+            continue;
+        }
+        let Ok(records) = read_records(&path) else {
+            continue;
+        };
+        let Some(Record::Header { file_path }) = records.first() else {
+            continue;
+        };
+        let edits = records.len() - 1;
+        if edits == 0 {
+            // 没有修改，不值得提示
+            let _ = fs::remove_file(&path);
+            continue;
+        }
+        let modified = item
+            .metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        out.push(RecoverableJournal {
+            id: id.to_string(),
+            file_path: file_path.clone(),
+            edits,
+            modified,
+        });
+    }
+    Ok(out)
+}
+
+/// 在基准文件上重放日志并作为新文档打开（处于未保存状态），然后删掉这份日志
+#[tauri::command]
+pub async fn gxt_journal_recover(
+    id: String,
+    journal: tauri::State<'_, Journal>,
+    session: tauri::State<'_, Session>,
+) -> Result<GxtDocument, String> {
+    let path = journal_file(&journal, &id)?;
+    let records = read_records(&path)?;
+    let Some(Record::Header { file_path }) = records.first() else {
+        return Err(format!("Journal {id} has no header"));
+    };
+
+    let mut doc = match file_path {
+        Some(p) => gxt::load_document(p.clone()).await?,
+        None => GxtDocument {
+            file_path: None,
+            entries: Vec::new(),
+            doc_id: None,
+            embedded: None,
+            read_only: false,
+        },
+    };
+    let doc_id = session.open(&mut doc)?;
+    session.with(doc_id, |open| {
+        for r in &records[1..] {
+            match r {
+                Record::Set { key, value } => open.set_entry(key.clone(), value.clone()),
+                Record::Delete { key } => {
+                    open.remove_entry(key);
+                }
+                Record::Snapshot { entries } => {
+                    let mut d = open.doc.clone();
+                    d.entries = entries.clone();
+                    open.replace_entries(d);
+                }
+                Record::Header { .. } => {}
+            }
+        }
+        meta::apply(open);
+        Ok(())
+    })?;
+    let _ = fs::remove_file(&path);
+    tracing::info!(journal = %id, edits = records.len() - 1, "recovered unsaved edits");
+    session.with(doc_id, |open| Ok(open.doc.clone()))
+}
+
+/// 放弃一份日志
+#[tauri::command]
+pub fn gxt_journal_discard(id: String, journal: tauri::State<'_, Journal>) -> Result<(), String> {
+    fs::remove_file(journal_file(&journal, &id)?).map_err(|e| format!("Delete journal failed: {e}"))
+}
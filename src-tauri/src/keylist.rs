@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+use std::fs;
+
+use gxt_core::{GroupLevel, KeyListFormat};
+
+use crate::gxt::GxtEntry;
+use crate::i18n::tr;
+use crate::project;
+use crate::session::{DocId, Session};
+
+/// 一批 key 里哪些在文档里，按请求的顺序（重复的只列一次）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyCheck {
+    pub present: Vec<String>,
+    pub missing: Vec<String>,
+}
+
+pub(crate) fn check_keys(entries: &[GxtEntry], keys: Vec<String>) -> KeyCheck {
+    let have: HashSet<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+    let mut seen = HashSet::new();
+    let mut out = KeyCheck::default();
+    for key in keys {
+        if !seen.insert(key.clone()) {
+            continue;
+        }
+        if have.contains(key.as_str()) {
+            out.present.push(key);
+        } else {
+            out.missing.push(key);
+        }
+    }
+    out
+}
+
+/// 导出只有 key 名的清单给写脚本的人（每行一个，或 Sanny Builder 常量文件）。
+/// group 给了时按前缀分组；返回导出的 key 数
+#[tauri::command]
+pub fn gxt_export_keys(
+    doc_id: DocId,
+    path: String,
+    format: KeyListFormat,
+    group: Option<GroupLevel>,
+    prefix: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<usize, String> {
+    let (text, count) = session.with(doc_id, |open| {
+        let entries = &open.doc.entries;
+        let text = gxt_core::key_list(
+            entries,
+            format,
+            group.as_ref(),
+            prefix.as_deref().unwrap_or(""),
+        );
+        Ok((text, entries.len()))
+    })?;
+    fs::write(&path, text).map_err(|e| tr!("file.write_failed", error = e))?;
+    Ok(count)
+}
+
+/// 查一批 key 在不在（mod 打包前确认脚本引用的文字都有）。给了 path 时查那个文件
+/// （已在编辑器里打开的按当前内容，含未保存的修改），否则查 doc_id 对应的文档
+#[tauri::command]
+pub async fn gxt_has_keys(
+    doc_id: Option<DocId>,
+    path: Option<String>,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<KeyCheck, String> {
+    match path {
+        Some(p) => Ok(check_keys(
+            &project::current_entries(&session, &p).await?,
+            keys,
+        )),
+        None => {
+            let id = doc_id.ok_or("Either path or doc_id is required")?;
+            session.with(id, |open| Ok(check_keys(&open.doc.entries, keys)))
+        }
+    }
+}
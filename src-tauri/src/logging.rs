@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+const LOG_PREFIX: &str = "gxt-editor";
+const LOG_SUFFIX: &str = "log";
+/// 按天滚动，最多保留这么多个文件
+const MAX_LOG_FILES: usize = 7;
+const DEFAULT_RECENT_LINES: usize = 500;
+
+/// tauri managed state：日志目录，以及后台写日志线程的 guard（drop 时把缓冲刷到文件）
+pub struct Logging {
+    dir: PathBuf,
+    _guard: Option<WorkerGuard>,
+}
+
+/// 初始化日志（启动时调用一次）：写到 <app log dir>/gxt-editor.YYYY-MM-DD.log。
+/// 初始化失败不影响编辑器本身，只是没有日志
+pub fn init(app: &AppHandle) -> Logging {
+    let dir = app
+        .path()
+        .app_log_dir()
+        .unwrap_or_else(|_| std::env::temp_dir().join(LOG_PREFIX));
+    let guard = fs::create_dir_all(&dir)
+        .ok()
+        .and_then(|_| {
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_PREFIX)
+                .filename_suffix(LOG_SUFFIX)
+                .max_log_files(MAX_LOG_FILES)
+                .build(&dir)
+                .ok()
+        })
+        .and_then(|appender| {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            tracing_subscriber::fmt()
+                .with_writer(writer)
+                .with_ansi(false)
+                .with_target(false)
+                .with_max_level(tracing::Level::INFO)
+                .try_init()
+                .ok()?;
+            Some(guard)
+        });
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "gxt-editor started");
+    Logging { dir, _guard: guard }
+}
+
+/// 最近的日志（从旧到新），用于附在 bug 报告里。lines 缺省 500 行
+#[tauri::command]
+pub fn gxt_get_recent_logs(
+    lines: Option<usize>,
+    logging: tauri::State<'_, Logging>,
+) -> Result<Vec<String>, String> {
+    let limit = lines.unwrap_or(DEFAULT_RECENT_LINES);
+    let mut files: Vec<PathBuf> = fs::read_dir(&logging.dir)
+        .map_err(|e| format!("Read log dir failed: {e}"))?
+        .flatten()
+        .map(|item| item.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(LOG_PREFIX) && n.ends_with(LOG_SUFFIX))
+        })
+        .collect();
+    // 文件名里带日期，按名字倒序就是从新到旧
+    files.sort_unstable_by(|a, b| b.cmp(a));
+
+    let mut out: Vec<String> = Vec::new();
+    for file in files {
+        if out.len() >= limit {
+            break;
+        }
+        let Ok(text) = fs::read_to_string(&file) else {
+            continue;
+        };
+        let mut chunk: Vec<String> = text.lines().map(String::from).collect();
+        let keep = chunk.len().min(limit - out.len());
+        chunk.drain(..chunk.len() - keep);
+        chunk.append(&mut out);
+        out = chunk;
+    }
+    Ok(out)
+}
@@ -1,13 +1,333 @@
 #![cfg_attr(all(not(debug_assertions), target_os = "windows"), windows_subsystem = "windows")]
+mod audio;
+mod autofill;
+mod batch;
+mod changelog;
+mod charset;
+mod clipboard;
+mod collab;
+mod collation;
+mod deep_link;
+mod delta;
+mod doc_profile;
+mod encode_cache;
+mod escapes;
+mod events;
+mod export;
+mod export_names;
+mod external_diff;
+mod fuzzy;
+mod game_process;
+mod git;
 mod gxt;
+mod hashes;
+mod hooks;
+mod http_server;
+mod i18n;
+mod ipc;
+mod journal;
+mod keylist;
+mod logging;
+mod merge_report;
+mod meta;
+mod mirrors;
+mod mt;
+mod notes;
+mod numbers;
+mod opencc;
+mod overlay;
+mod page;
+mod pins;
+mod plugins;
+mod presets;
+mod profile;
+mod progress;
+mod project;
+mod quote;
+mod reference;
+mod revert;
+mod review;
+mod rollback;
+mod sample;
+mod save_job;
+mod scm_refs;
+mod script;
+mod search;
+mod selftest;
+mod session;
+mod settings;
+mod sheet;
+mod shell;
+mod snapshots;
+mod source_update;
+mod space;
+mod subtitles;
+mod table_files;
+mod task;
+mod templates;
+mod terms;
+mod tm;
+mod tms;
+mod tombstones;
+mod translit;
+mod usage;
+mod views;
+mod window;
+
+use tauri::Manager;
 
 fn main() {
     tauri::Builder::default()
+        // 必须最先注册：已有实例在运行时，本进程把参数转发过去后直接退出
+        .plugin(tauri_plugin_single_instance::init(|app, argv, cwd| {
+            window::open_forwarded_args(app, argv, cwd)
+        }))
         .plugin(tauri_plugin_dialog::init())
+        .manage(encode_cache::EncodeCache::default())
+        .manage(session::Session::default())
+        .manage(save_job::SaveJobs::default())
+        .manage(http_server::HttpServer::default())
+        .manage(tm::TranslationMemory::default())
+        .manage(events::EventHub::default())
+        .manage(hashes::HashDictionaries::default())
+        .manage(game_process::GamePreview::default())
+        .manage(window::Windows::default())
+        .manage(task::Tasks::default())
+        .manage(project::Projects::default())
+        .manage(clipboard::Clipboard::default())
+        .manage(collab::Collab::default())
+        .setup(|app| {
+            app.manage(logging::init(app.handle()));
+            i18n::init(app.handle());
+            meta::init(app.handle());
+            doc_profile::init(app.handle());
+            app.manage(journal::Journal::new(app.handle()));
+            app.manage(presets::init(app.handle()));
+            let handle = app.handle().clone();
+            app.state::<session::Session>()
+                .set_event_sink(move |doc_id, events| events::dispatch(&handle, doc_id, events));
+            window::open_startup_args(app.handle(), std::env::args().skip(1));
+            Ok(())
+        })
+        .on_window_event(|window, event| match event {
+            tauri::WindowEvent::CloseRequested { api, .. } => {
+                window::on_close_requested(window, api)
+            }
+            tauri::WindowEvent::Destroyed => window::on_destroyed(window),
+            _ => {}
+        })
         .invoke_handler(tauri::generate_handler![
       gxt::gxt_load,
       gxt::gxt_save,
       gxt::gxt_startup_path,
+      ipc::gxt_ipc_transports,
+      ipc::gxt_load_raw,
+      ipc::gxt_save_raw,
+      session::gxt_set_entry,
+      session::gxt_remove_entry,
+      session::gxt_fuzzy_list,
+      session::gxt_fuzzy_set,
+      session::gxt_is_dirty,
+      session::gxt_documents,
+      session::gxt_close_document,
+      session::gxt_compare_documents,
+      session::gxt_copy_entries,
+      search::gxt_search,
+      save_job::gxt_save_background,
+      save_job::gxt_cancel_save,
+      profile::gxt_profile,
+      http_server::gxt_http_start,
+      http_server::gxt_http_stop,
+      http_server::gxt_http_status,
+      script::gxt_run_script,
+      plugins::gxt_plugins_list,
+      plugins::gxt_plugin_import,
+      plugins::gxt_plugin_export,
+      tm::gxt_tm_add,
+      tm::gxt_tm_import_files,
+      tm::gxt_tm_suggest,
+      mt::gxt_mt_get_config,
+      mt::gxt_mt_set_config,
+      mt::gxt_mt_translate,
+      mt::gxt_mt_translate_untranslated,
+      tms::gxt_tms_export,
+      tms::gxt_tms_import,
+      git::gxt_git_is_tracked,
+      git::gxt_git_blame,
+      git::gxt_git_diff_head,
+      source_update::gxt_source_update,
+      opencc::gxt_zh_convert,
+      external_diff::gxt_external_diff,
+      terms::gxt_extract_terms,
+      terms::gxt_export_terms,
+      hashes::gxt_hash_dict_load,
+      hashes::gxt_hash_resolve,
+      hashes::gxt_hash_unresolve,
+      hashes::gxt_hash,
+      game_process::gxt_game_find,
+      game_process::gxt_game_preview,
+      subtitles::gxt_export_subtitles,
+      audio::gxt_audio_scan,
+      window::gxt_open_window,
+      window::gxt_open_path,
+      window::gxt_window_attach,
+      window::gxt_window_close,
+      task::gxt_cancel_task,
+      settings::gxt_get_settings,
+      settings::gxt_set_settings,
+      logging::gxt_get_recent_logs,
+      project::gxt_project_open,
+      project::gxt_project_save,
+      project::gxt_project_close,
+      project::gxt_project_find_key,
+      project::gxt_project_stats,
+      project::gxt_project_sync_languages,
+      journal::gxt_journal_list,
+      journal::gxt_journal_recover,
+      journal::gxt_journal_discard,
+      presets::gxt_presets_list,
+      presets::gxt_presets_reload,
+      presets::gxt_preset_customize,
+      i18n::gxt_set_locale,
+      i18n::gxt_messages,
+      project::gxt_project_check_protected,
+      meta::gxt_locked_list,
+      meta::gxt_locked_set,
+      presets::gxt_preview,
+      presets::gxt_preset_check,
+      meta::gxt_modified_entries,
+      progress::gxt_progress,
+      progress::gxt_project_progress,
+      progress::gxt_progress_export,
+      session::gxt_group_keys,
+      autofill::gxt_autofill,
+      snapshots::gxt_snapshot_list,
+      snapshots::gxt_snapshot_diff,
+      snapshots::gxt_snapshot_restore,
+      views::gxt_view_eval,
+      views::gxt_view_save,
+      views::gxt_view_delete,
+      presets::gxt_wrap,
+      changelog::gxt_changelog,
+      session::gxt_list_keys,
+      shell::gxt_shell_register,
+      shell::gxt_shell_unregister,
+      shell::gxt_shell_status,
+      ipc::gxt_load_bytes,
+      ipc::gxt_save_bytes,
+      presets::gxt_check_sort,
+      hashes::gxt_hash_collisions,
+      game_process::gxt_game_dump,
+      presets::gxt_preset_resolved,
+      templates::gxt_template_fields,
+      templates::gxt_template_insert,
+      templates::gxt_template_apply,
+      templates::gxt_template_save,
+      templates::gxt_template_delete,
+      templates::gxt_templates_export,
+      templates::gxt_templates_import,
+      translit::gxt_translit_tables,
+      translit::gxt_transliterate,
+      project::gxt_project_check_tokens,
+      page::gxt_entries_page,
+      search::gxt_search_history_add,
+      search::gxt_search_history,
+      search::gxt_search_history_clear,
+      space::gxt_space_analysis,
+      space::gxt_compact,
+      keylist::gxt_export_keys,
+      scm_refs::gxt_script_missing_keys,
+      scm_refs::gxt_scaffold_from_scripts,
+      session::gxt_import_entries,
+      presets::gxt_render_metrics,
+      numbers::gxt_localize_numbers,
+      doc_profile::gxt_document_profile,
+      doc_profile::gxt_document_profile_set,
+      selftest::gxt_selftest,
+      notes::gxt_notes,
+      notes::gxt_note_set,
+      notes::gxt_notes_import,
+      notes::gxt_notes_export,
+      review::gxt_review_assign,
+      review::gxt_review_approve,
+      review::gxt_review_revoke,
+      review::gxt_review_list,
+      review::gxt_review_changed,
+      session::gxt_begin_batch,
+      session::gxt_commit_batch,
+      session::gxt_rollback_batch,
+      session::gxt_undo_batch,
+      space::gxt_overlaps,
+      space::gxt_repair_overlaps,
+      scm_refs::gxt_rename_key_refs,
+      project::gxt_compare_languages,
+      scm_refs::gxt_import_script_context,
+      scm_refs::gxt_script_context,
+      delta::gxt_delta_export,
+      delta::gxt_delta_info,
+      delta::gxt_delta_apply,
+      rollback::gxt_backup_before_batch,
+      rollback::gxt_last_batch,
+      rollback::gxt_revert_last_batch,
+      pins::gxt_pinned,
+      pins::gxt_pin_set,
+      pins::gxt_pin_reorder,
+      export_names::gxt_export_file_name,
+      tms::gxt_tms_export_project,
+      progress::gxt_table_stats,
+      escapes::gxt_escape_lint,
+      escapes::gxt_escape_check,
+      session::gxt_bulk_add,
+      reference::gxt_reference_attach,
+      reference::gxt_reference_detach,
+      reference::gxt_reference_info,
+      reference::gxt_reference_values,
+      usage::gxt_usage_import,
+      usage::gxt_usage,
+      usage::gxt_usage_clear,
+      charset::gxt_export_fontforge_charset,
+      clipboard::gxt_clip_copy,
+      clipboard::gxt_clip_cut,
+      clipboard::gxt_clip_list,
+      clipboard::gxt_clip_paste,
+      clipboard::gxt_clip_clear,
+      gxt::gxt_load_warnings,
+      sheet::gxt_sheet_preview,
+      sheet::gxt_sheet_import,
+      batch::gxt_batch_convert,
+      revert::gxt_unsaved_changes,
+      revert::gxt_revert_entries,
+      keylist::gxt_has_keys,
+      gxt::gxt_new_document,
+      gxt::gxt_startup_file,
+      window::gxt_detect_open,
+      presets::gxt_truncation_preview,
+      sample::gxt_sample,
+      merge_report::gxt_merge_reports,
+      merge_report::gxt_merge_report,
+      merge_report::gxt_merge_report_export,
+      space::gxt_estimate_size,
+      deep_link::gxt_deep_link,
+      scm_refs::gxt_import_source_refs,
+      scm_refs::gxt_source_refs,
+      tombstones::gxt_tombstones,
+      tombstones::gxt_tombstone_restore,
+      tombstones::gxt_tombstone_purge,
+      table_files::gxt_tables_export,
+      table_files::gxt_tables_assemble,
+      quote::gxt_word_count,
+      quote::gxt_word_count_export,
+      space::gxt_layout_export,
+      overlay::gxt_load_as_overlay,
+      export::gxt_export_entries,
+      collab::gxt_collab_host,
+      collab::gxt_collab_join,
+      collab::gxt_collab_stop,
+      collab::gxt_collab_status,
+      collab::gxt_collab_claim,
+      collab::gxt_collab_release,
+      collab::gxt_collab_set_entry,
+      collab::gxt_collab_remove_entry,
     ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
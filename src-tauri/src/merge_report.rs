@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tauri::{AppHandle, Manager};
+
+use crate::export_names;
+use crate::i18n::tr;
+
+const REPORT_DIR: &str = "merge-reports";
+/// 最多留几份；更早的删掉
+const KEEP: usize = 200;
+
+/// 合并时对一个 key 做了什么
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Decision {
+    /// 目标里原来没有，加进去了
+    Added,
+    /// 目标里原有的值被换掉了
+    Overwritten,
+    /// 目标里原有的值留着没动（锁定的、冲突时保留的等）
+    Kept,
+    /// 换了 key 才加进去
+    Renamed,
+    /// 来源里有、没有合并进去
+    Skipped,
+    /// 从目标里删掉了
+    Removed,
+}
+
+impl Decision {
+    fn label(self) -> &'static str {
+        match self {
+            Decision::Added => "added",
+            Decision::Overwritten => "overwritten",
+            Decision::Kept => "kept",
+            Decision::Renamed => "renamed",
+            Decision::Skipped => "skipped",
+            Decision::Removed => "removed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportItem {
+    pub key: String,
+    pub decision: Decision,
+    /// 原因或补充说明（改名后的 key、没合并的原因等）
+    #[serde(default)]
+    pub detail: Option<String>,
+}
+
+/// 一次合并 / 补丁 / 导入操作的全部决定，写在 <app data>/merge-reports 下（JSON 一份、文本一份），
+/// 事后可以查这个发布文件是怎么拼出来的
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeReport {
+    /// 毫秒时间戳，也是文件名
+    pub id: String,
+    /// 哪种操作，如 `import-entries`
+    pub label: String,
+    /// Unix 毫秒
+    pub created: u64,
+    /// 被改的文件（未保存过的文档为 None）
+    pub target: Option<String>,
+    /// 从哪里合并来的
+    pub source: Option<String>,
+    pub items: Vec<ReportItem>,
+}
+
+/// 列表里的一份：不带逐条决定，只有各种决定的条数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeReportInfo {
+    pub id: String,
+    pub label: String,
+    pub created: u64,
+    pub target: Option<String>,
+    pub source: Option<String>,
+    pub counts: BTreeMap<Decision, usize>,
+}
+
+impl MergeReport {
+    pub(crate) fn new(label: &str, target: Option<String>, source: Option<String>) -> Self {
+        let created = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        MergeReport {
+            id: created.to_string(),
+            label: label.to_string(),
+            created,
+            target,
+            source,
+            items: Vec::new(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, key: &str, decision: Decision, detail: Option<String>) {
+        self.items.push(ReportItem {
+            key: key.to_string(),
+            decision,
+            detail,
+        });
+    }
+
+    fn counts(&self) -> BTreeMap<Decision, usize> {
+        let mut counts = BTreeMap::new();
+        for item in &self.items {
+            *counts.entry(item.decision).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    fn info(&self) -> MergeReportInfo {
+        MergeReportInfo {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            created: self.created,
+            target: self.target.clone(),
+            source: self.source.clone(),
+            counts: self.counts(),
+        }
+    }
+
+    /// 给人看的版本：头部写操作、时间与文件，下面每个决定一行
+    fn to_text(&self) -> String {
+        let (y, mo, d, h, mi, s) = export_names::utc_parts(self.created / 1000);
+        let mut out = format!(
+            "{}  {y:04}-{mo:02}-{d:02} {h:02}:{mi:02}:{s:02} UTC\n",
+            self.label
+        );
+        let file = |p: &Option<String>| p.clone().unwrap_or_else(|| "(unsaved)".into());
+        out.push_str(&format!("target: {}\n", file(&self.target)));
+        if self.source.is_some() {
+            out.push_str(&format!("source: {}\n", file(&self.source)));
+        }
+        let counts: Vec<String> = self
+            .counts()
+            .iter()
+            .map(|(d, n)| format!("{} {n}", d.label()))
+            .collect();
+        out.push_str(&counts.join(", "));
+        out.push_str("\n\n");
+        for item in &self.items {
+            out.push_str(&format!("[{}] {}", item.decision.label(), item.key));
+            if let Some(detail) = &item.detail {
+                out.push_str(&format!("  {detail}"));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+fn report_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|d| d.join(REPORT_DIR))
+        .map_err(|e| format!("Resolve app data dir failed: {e}"))
+}
+
+fn read_report(path: &Path) -> Result<MergeReport, String> {
+    let bytes = fs::read(path).map_err(|e| tr!("file.read_failed", error = e))?;
+    serde_json::from_slice(&bytes).map_err(|e| tr!("json.decode_failed", error = e))
+}
+
+/// 目录下的报告，从新到旧
+fn list(dir: &Path) -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|item| item.path())
+        .filter(|p| p.extension().is_some_and(|x| x == "json"))
+        .collect();
+    out.sort_by(|a, b| b.cmp(a));
+    out
+}
+
+/// 合并类操作改完文档后调用：写下这次的报告，超出份数时删掉最旧的。
+/// 写报告失败不影响操作本身，只记日志
+pub(crate) fn record(app: &AppHandle, report: &MergeReport) {
+    let res = report_dir(app).and_then(|dir| {
+        fs::create_dir_all(&dir).map_err(|e| tr!("file.write_failed", error = e))?;
+        let json =
+            serde_json::to_vec_pretty(report).map_err(|e| tr!("json.encode_failed", error = e))?;
+        fs::write(dir.join(format!("{}.json", report.id)), json)
+            .map_err(|e| tr!("file.write_failed", error = e))?;
+        fs::write(dir.join(format!("{}.txt", report.id)), report.to_text())
+            .map_err(|e| tr!("file.write_failed", error = e))?;
+        for old in list(&dir).into_iter().skip(KEEP) {
+            let _ = fs::remove_file(old.with_extension("txt"));
+            let _ = fs::remove_file(old);
+        }
+        Ok(())
+    });
+    match res {
+        Ok(()) => tracing::info!(
+            label = %report.label,
+            items = report.items.len(),
+            "merge report written"
+        ),
+        Err(e) => tracing::warn!(label = %report.label, error = %e, "merge report failed"),
+    }
+}
+
+/// 留下的合并报告，从新到旧
+#[tauri::command]
+pub fn gxt_merge_reports(app: AppHandle) -> Result<Vec<MergeReportInfo>, String> {
+    let dir = report_dir(&app)?;
+    Ok(list(&dir)
+        .iter()
+        .filter_map(|p| read_report(p).ok())
+        .map(|r| r.info())
+        .collect())
+}
+
+/// 一份合并报告的全部决定
+#[tauri::command]
+pub fn gxt_merge_report(app: AppHandle, id: String) -> Result<MergeReport, String> {
+    if id.is_empty() || !id.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("No such merge report: {id}"));
+    }
+    let path = report_dir(&app)?.join(format!("{id}.json"));
+    if !path.exists() {
+        return Err(format!("No such merge report: {id}"));
+    }
+    read_report(&path)
+}
+
+/// 把一份合并报告另存到 path：扩展名为 .json 时存 JSON，否则存文本
+#[tauri::command]
+pub fn gxt_merge_report_export(app: AppHandle, id: String, path: String) -> Result<(), String> {
+    let report = gxt_merge_report(app, id)?;
+    let out = if Path::new(&path)
+        .extension()
+        .is_some_and(|x| x.eq_ignore_ascii_case("json"))
+    {
+        serde_json::to_vec_pretty(&report).map_err(|e| tr!("json.encode_failed", error = e))?
+    } else {
+        report.to_text().into_bytes()
+    };
+    fs::write(&path, out).map_err(|e| tr!("file.write_failed", error = e))
+}
@@ -0,0 +1,271 @@
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gxt_core::{KeyUsage, ScriptContext};
+use tauri::{AppHandle, Listener};
+
+use crate::i18n::tr;
+use crate::scm_refs::SourceRef;
+use crate::session::{DocId, OpenDocument, Session};
+use crate::settings::{self, Settings, EVENT_SETTINGS_CHANGED};
+
+/// 附加信息放在 GXT 旁边的 `<文件名>.meta.json` 里（GXT 格式本身存不下）
+const SIDECAR_SUFFIX: &str = ".meta.json";
+
+/// 一个 GXT 文件的附加信息
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntryMeta {
+    /// 已锁定（如审校通过）的 key：手动编辑与批量操作都跳过，解锁后才能改
+    #[serde(default)]
+    pub locked: BTreeSet<String>,
+    /// key -> 最后一次修改
+    #[serde(default)]
+    pub stamps: BTreeMap<String, EntryStamp>,
+    /// key -> 注释
+    #[serde(default)]
+    pub notes: BTreeMap<String, String>,
+    /// key -> 指派的审校人
+    #[serde(default)]
+    pub reviewers: BTreeMap<String, String>,
+    /// key -> 第二个人的确认
+    #[serde(default)]
+    pub approvals: BTreeMap<String, Approval>,
+    /// key -> 反编译的 main.scm 里显示它的地方（见 scm_refs.rs）；只存在文件里，不随编辑变化
+    #[serde(default)]
+    pub contexts: BTreeMap<String, Vec<ScriptContext>>,
+    /// 固定在导航栏上的 key，按固定的先后（见 pins.rs）
+    #[serde(default)]
+    pub pinned: Vec<String>,
+    /// key -> 游玩日志里显示过的次数与地点（见 usage.rs）；只存在文件里，不随编辑变化
+    #[serde(default)]
+    pub usage: BTreeMap<String, KeyUsage>,
+    /// key -> mod 源码里出现它的行（见 scm_refs.rs）；只存在文件里，不随编辑变化
+    #[serde(default)]
+    pub source_refs: BTreeMap<String, Vec<SourceRef>>,
+    /// key -> 软删除记录（见 tombstones.rs）
+    #[serde(default)]
+    pub tombstones: BTreeMap<String, Tombstone>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntryStamp {
+    /// Unix 秒
+    pub modified: u64,
+    /// 设置里的作者名；没设置时为 None
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+/// 审校人的签字确认
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Approval {
+    pub by: String,
+    /// Unix 秒
+    pub at: u64,
+    /// 确认时的译文；之后改过就不再算确认
+    pub value: String,
+}
+
+/// 软删除的条目：不写进 GXT，但记下删过，与别人的文件合并时不会再被加回来
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Tombstone {
+    /// 删除前的译文，恢复时用
+    pub value: String,
+    /// Unix 秒
+    pub at: u64,
+    #[serde(default)]
+    pub author: Option<String>,
+}
+
+/// 当前作者名（设置里的 author）；启动时读一次，之后跟着设置变化
+static AUTHOR: RwLock<Option<String>> = RwLock::new(None);
+
+fn set_author(author: Option<String>) {
+    if let Ok(mut g) = AUTHOR.write() {
+        *g = author.filter(|a| !a.trim().is_empty());
+    }
+}
+
+/// 设置里的作者名
+pub fn current_author() -> Option<String> {
+    AUTHOR.read().ok().and_then(|g| g.clone())
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 以当前时间和作者生成一条修改记录
+pub fn stamp_now() -> EntryStamp {
+    EntryStamp {
+        modified: now_secs(),
+        author: current_author(),
+    }
+}
+
+/// 启动时调用：读作者名并在设置变化时更新
+pub fn init(app: &AppHandle) {
+    set_author(settings::load(app).ok().and_then(|s| s.author));
+    app.listen(EVENT_SETTINGS_CHANGED, |event| {
+        if let Ok(s) = serde_json::from_str::<Settings>(event.payload()) {
+            set_author(s.author);
+        }
+    });
+}
+
+fn sidecar_path(file_path: &str) -> PathBuf {
+    PathBuf::from(format!("{file_path}{SIDECAR_SUFFIX}"))
+}
+
+/// 读附加信息；没有或读不了时为空
+pub fn load(file_path: &str) -> EntryMeta {
+    let path = sidecar_path(file_path);
+    let Ok(bytes) = fs::read(&path) else {
+        return EntryMeta::default();
+    };
+    serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+        tracing::warn!(path = %path.display(), error = %e, "ignore invalid sidecar");
+        EntryMeta::default()
+    })
+}
+
+/// 写附加信息；没有任何内容时删掉旁边的文件
+pub fn save(file_path: &str, meta: &EntryMeta) -> Result<(), String> {
+    let path = sidecar_path(file_path);
+    if meta.locked.is_empty()
+        && meta.stamps.is_empty()
+        && meta.notes.is_empty()
+        && meta.reviewers.is_empty()
+        && meta.approvals.is_empty()
+        && meta.contexts.is_empty()
+        && meta.pinned.is_empty()
+        && meta.usage.is_empty()
+        && meta.source_refs.is_empty()
+        && meta.tombstones.is_empty()
+    {
+        return match fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(tr!("file.write_failed", error = e))
+            }
+            _ => Ok(()),
+        };
+    }
+    let bytes =
+        serde_json::to_vec_pretty(meta).map_err(|e| tr!("json.encode_failed", error = e))?;
+    fs::write(&path, bytes).map_err(|e| tr!("file.write_failed", error = e))
+}
+
+/// 把文档文件旁边的附加信息读进打开的文档（打开/恢复文档后调用）。
+/// 内存里已有的修改记录（恢复时重放出来的）比文件里的新，保留它们
+pub fn apply(open: &mut OpenDocument) {
+    if let Some(p) = &open.doc.file_path {
+        let meta = load(p);
+        open.locked = meta.locked;
+        open.notes = meta.notes;
+        open.reviewers = meta.reviewers;
+        open.approvals = meta.approvals;
+        open.pinned = meta.pinned;
+        open.tombstones = meta.tombstones;
+        let newer = std::mem::replace(&mut open.stamps, meta.stamps);
+        open.stamps.extend(newer);
+    }
+}
+
+/// 把打开的文档的附加信息写到它的文件旁边（未保存过的文档先只留在内存里）；
+/// 不在内存里的（脚本上下文、显示次数）保留文件里原有的
+pub fn store(open: &OpenDocument) -> Result<(), String> {
+    match &open.doc.file_path {
+        Some(p) => save(
+            p,
+            &EntryMeta {
+                locked: open.locked.clone(),
+                stamps: open.stamps.clone(),
+                notes: open.notes.clone(),
+                reviewers: open.reviewers.clone(),
+                approvals: open.approvals.clone(),
+                pinned: open.pinned.clone(),
+                tombstones: open.tombstones.clone(),
+                ..load(p)
+            },
+        ),
+        None => Ok(()),
+    }
+}
+
+/// 当前锁定的 key
+#[tauri::command]
+pub fn gxt_locked_list(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    session.with(doc_id, |open| Ok(open.locked.iter().cloned().collect()))
+}
+
+/// 锁定/解锁一批 key；立即写回附加信息文件
+#[tauri::command]
+pub fn gxt_locked_set(
+    doc_id: DocId,
+    keys: Vec<String>,
+    locked: bool,
+    session: tauri::State<'_, Session>,
+) -> Result<(), String> {
+    session.edit(doc_id, |open| {
+        for k in keys {
+            if locked {
+                open.locked.insert(k);
+            } else {
+                open.locked.remove(&k);
+            }
+        }
+        // 修改记录等保存时再写：这里只更新文件里的锁定状态
+        match &open.doc.file_path {
+            Some(p) => {
+                let mut meta = load(p);
+                meta.locked = open.locked.clone();
+                save(p, &meta)
+            }
+            None => Ok(()),
+        }
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModifiedEntry {
+    pub key: String,
+    #[serde(flatten)]
+    pub stamp: EntryStamp,
+}
+
+/// 有修改记录的条目，按修改时间从新到旧。
+/// since（Unix 秒）只要这之后改的，author 只要这个人改的（审校时看“上次审校以来改了什么”）
+#[tauri::command]
+pub fn gxt_modified_entries(
+    doc_id: DocId,
+    since: Option<u64>,
+    author: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<ModifiedEntry>, String> {
+    session.with(doc_id, |open| {
+        let mut out: Vec<ModifiedEntry> = open
+            .stamps
+            .iter()
+            .filter(|(_, st)| since.is_none_or(|t| st.modified >= t))
+            .filter(|(_, st)| author.is_none() || st.author == author)
+            .map(|(key, stamp)| ModifiedEntry {
+                key: key.clone(),
+                stamp: stamp.clone(),
+            })
+            .collect();
+        out.sort_by_key(|e| Reverse(e.stamp.modified));
+        Ok(out)
+    })
+}
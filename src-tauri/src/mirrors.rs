@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use tauri::AppHandle;
+
+use crate::gxt::{GxtDocument, GxtEntry};
+use crate::i18n::tr;
+use crate::settings;
+use crate::tms::csv_field;
+
+/// 保存 GXT 时顺带重新生成的可读副本（提交进版本库时看得出改了什么）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MirrorFormat {
+    /// `[{"key": ..., "value": ...}]`，与 JSON 导出相同
+    Json,
+    /// `key,value` 两列
+    Csv,
+    /// `KEY=value` 文本，与文本导出相同
+    Text,
+}
+
+impl MirrorFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            MirrorFormat::Json => "json",
+            MirrorFormat::Csv => "csv",
+            MirrorFormat::Text => "txt",
+        }
+    }
+
+    fn render(self, entries: &[GxtEntry]) -> Result<Vec<u8>, String> {
+        match self {
+            MirrorFormat::Json => {
+                serde_json::to_vec_pretty(entries).map_err(|e| tr!("json.encode_failed", error = e))
+            }
+            MirrorFormat::Csv => {
+                let mut out = String::from("key,value\n");
+                for e in entries {
+                    out.push_str(&format!("{},{}\n", csv_field(&e.key), csv_field(&e.value)));
+                }
+                Ok(out.into_bytes())
+            }
+            MirrorFormat::Text => gxt_core::entries_to_text(entries).map(String::into_bytes),
+        }
+    }
+}
+
+/// 副本的路径：GXT 旁边同名、换成副本的扩展名
+fn mirror_path(gxt_path: &Path, format: MirrorFormat) -> PathBuf {
+    gxt_path.with_extension(format.extension())
+}
+
+/// 按设置重新生成刚保存的文档的副本，返回写了的文件（内容没变的不重写，免得改动时间跳）。
+/// 副本写失败不影响保存本身，只记日志
+pub(crate) fn write_mirrors(app: &AppHandle, doc: &GxtDocument) -> Vec<String> {
+    let formats = settings::load(app)
+        .map(|s| s.export_mirrors)
+        .unwrap_or_default();
+    let Some(path) = &doc.file_path else {
+        return Vec::new();
+    };
+    let mut written = Vec::new();
+    for format in formats {
+        let out = mirror_path(Path::new(path), format);
+        let result = format.render(&doc.entries).and_then(|bytes| {
+            if fs::read(&out).is_ok_and(|old| old == bytes) {
+                return Ok(false);
+            }
+            fs::write(&out, bytes)
+                .map(|_| true)
+                .map_err(|e| tr!("file.write_failed", error = e))
+        });
+        match result {
+            Ok(true) => written.push(out.to_string_lossy().into_owned()),
+            Ok(false) => {}
+            Err(e) => tracing::warn!(path = %out.display(), error = %e, "export mirror failed"),
+        }
+    }
+    written
+}
@@ -0,0 +1,339 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use gxt_core::Segment;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager};
+
+use crate::gxt;
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+use crate::settings;
+use crate::task::{Task, Tasks};
+
+/// 每次请求最多带多少段文本
+const BATCH: usize = 50;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MtProvider {
+    #[default]
+    Deepl,
+    Google,
+    Libretranslate,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MtConfig {
+    pub provider: MtProvider,
+    /// 为空时用各家默认地址（LibreTranslate 必须填）
+    #[serde(default)]
+    pub endpoint: String,
+    #[serde(default)]
+    pub api_key: String,
+    /// 为空表示让服务自动识别
+    #[serde(default)]
+    pub source_lang: String,
+    pub target_lang: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MtResult {
+    pub key: String,
+    pub source: String,
+    /// 失败时为 None，原因见 error
+    pub translated: Option<String>,
+    pub error: Option<String>,
+}
+
+fn load_config(app: &AppHandle) -> Result<MtConfig, String> {
+    settings::load(app)?
+        .mt
+        .ok_or_else(|| tr!("mt.not_configured"))
+}
+
+#[tauri::command]
+pub fn gxt_mt_get_config(app: AppHandle) -> Result<Option<MtConfig>, String> {
+    Ok(settings::load(&app)?.mt)
+}
+
+/// 机翻配置保存在设置文件的 `mt` 一节
+#[tauri::command]
+pub fn gxt_mt_set_config(app: AppHandle, config: MtConfig) -> Result<(), String> {
+    settings::update(&app, |s| s.mt = Some(config))
+}
+
+// -------------------- Placeholder protection --------------------
+
+/// 把 ~token~ 和转义换成 `⟦N⟧`，机翻服务一般会原样保留这种记号
+fn protect(value: &str) -> (String, Vec<String>) {
+    let mut text = String::new();
+    let mut kept = Vec::new();
+    for seg in gxt_core::segments(value) {
+        match seg {
+            Segment::Text(t) => text.push_str(t),
+            Segment::Token(t) | Segment::Escape(t) => {
+                text.push_str(&format!("⟦{}⟧", kept.len()));
+                kept.push(t.to_string());
+            }
+        }
+    }
+    (text, kept)
+}
+
+/// protect 的逆操作；记号丢失或被改动时报错，而不是悄悄产出坏文本
+fn restore(translated: &str, kept: &[String]) -> Result<String, String> {
+    let re = regex::Regex::new(r"⟦\s*(\d+)\s*⟧").expect("static regex");
+    let mut used = vec![false; kept.len()];
+    let mut bad = None;
+    let out = re.replace_all(translated, |c: &regex::Captures| {
+        let i: usize = c[1].parse().unwrap_or(usize::MAX);
+        match kept.get(i) {
+            Some(orig) => {
+                used[i] = true;
+                orig.clone()
+            }
+            None => {
+                bad = Some(c[0].to_string());
+                String::new()
+            }
+        }
+    });
+    if let Some(b) = bad {
+        return Err(format!("MT introduced an unknown placeholder {b}"));
+    }
+    if let Some(i) = used.iter().position(|u| !u) {
+        return Err(format!("MT dropped placeholder {}", kept[i]));
+    }
+    Ok(out.into_owned())
+}
+
+// -------------------- Providers --------------------
+
+async fn translate_batch(
+    client: &reqwest::Client,
+    cfg: &MtConfig,
+    texts: &[String],
+) -> Result<Vec<String>, String> {
+    let source = (!cfg.source_lang.is_empty()).then_some(cfg.source_lang.as_str());
+    let (url, req) = match cfg.provider {
+        MtProvider::Deepl => {
+            let url = if cfg.endpoint.is_empty() {
+                "https://api-free.deepl.com/v2/translate"
+            } else {
+                cfg.endpoint.as_str()
+            };
+            let mut body = json!({ "text": texts, "target_lang": cfg.target_lang });
+            if let Some(s) = source {
+                body["source_lang"] = json!(s);
+            }
+            (
+                url.to_string(),
+                client
+                    .post(url)
+                    .header("Authorization", format!("DeepL-Auth-Key {}", cfg.api_key))
+                    .json(&body),
+            )
+        }
+        MtProvider::Google => {
+            let url = if cfg.endpoint.is_empty() {
+                "https://translation.googleapis.com/language/translate/v2"
+            } else {
+                cfg.endpoint.as_str()
+            };
+            let mut body = json!({ "q": texts, "target": cfg.target_lang, "format": "text" });
+            if let Some(s) = source {
+                body["source"] = json!(s);
+            }
+            (
+                url.to_string(),
+                client
+                    .post(url)
+                    .query(&[("key", cfg.api_key.as_str())])
+                    .json(&body),
+            )
+        }
+        MtProvider::Libretranslate => {
+            if cfg.endpoint.is_empty() {
+                return Err("LibreTranslate needs an endpoint URL".into());
+            }
+            let url = format!("{}/translate", cfg.endpoint.trim_end_matches('/'));
+            let body = json!({
+                "q": texts,
+                "source": source.unwrap_or("auto"),
+                "target": cfg.target_lang,
+                "format": "text",
+                "api_key": cfg.api_key,
+            });
+            (url.clone(), client.post(url).json(&body))
+        }
+    };
+
+    let resp = req
+        .send()
+        .await
+        .map_err(|e| format!("MT request to {url} failed: {e}"))?;
+    let status = resp.status();
+    let v: Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("MT response is not JSON ({status}): {e}"))?;
+    if !status.is_success() {
+        return Err(format!("MT service returned {status}: {v}"));
+    }
+
+    let texts_of = |arr: Option<&Vec<Value>>, field: Option<&str>| -> Option<Vec<String>> {
+        arr?.iter()
+            .map(|t| match field {
+                Some(f) => t.get(f)?.as_str().map(str::to_string),
+                None => t.as_str().map(str::to_string),
+            })
+            .collect()
+    };
+    let out = match cfg.provider {
+        MtProvider::Deepl => texts_of(v["translations"].as_array(), Some("text")),
+        MtProvider::Google => {
+            texts_of(v["data"]["translations"].as_array(), Some("translatedText"))
+        }
+        MtProvider::Libretranslate => texts_of(v["translatedText"].as_array(), None),
+    }
+    .ok_or_else(|| format!("Unexpected MT response: {v}"))?;
+
+    if out.len() != texts.len() {
+        return Err(format!(
+            "MT returned {} results for {} texts",
+            out.len(),
+            texts.len()
+        ));
+    }
+    Ok(out)
+}
+
+/// 机翻若干 (key, 原文)，写回 doc_id 文档并标记为 fuzzy；单条失败不影响其它条。
+/// 取消时不再发新的请求，已经译好的照常写回并返回
+async fn translate_and_apply(
+    app: &AppHandle,
+    doc_id: DocId,
+    items: Vec<(String, String)>,
+    mut task: Task,
+) -> Result<Vec<MtResult>, String> {
+    let cfg = load_config(app)?;
+    // 锁定的条目不机翻
+    let items: Vec<(String, String)> = app.state::<Session>().with(doc_id, |open| {
+        Ok(items
+            .into_iter()
+            .filter(|(k, _)| !open.is_locked(k))
+            .collect())
+    })?;
+    let client = reqwest::Client::new();
+    let mut results = Vec::with_capacity(items.len());
+
+    for chunk in items.chunks(BATCH) {
+        if task.is_cancelled() {
+            break;
+        }
+        let protected: Vec<(String, Vec<String>)> = chunk.iter().map(|(_, v)| protect(v)).collect();
+        let texts: Vec<String> = protected.iter().map(|(t, _)| t.clone()).collect();
+
+        match translate_batch(&client, &cfg, &texts).await {
+            Ok(translated) => {
+                for (((key, source), (_, kept)), t) in chunk.iter().zip(&protected).zip(translated)
+                {
+                    let (translated, error) = match restore(&t, kept) {
+                        Ok(v) => (Some(v), None),
+                        Err(e) => (None, Some(e)),
+                    };
+                    results.push(MtResult {
+                        key: key.clone(),
+                        source: source.clone(),
+                        translated,
+                        error,
+                    });
+                }
+            }
+            Err(e) => {
+                results.extend(chunk.iter().map(|(key, source)| MtResult {
+                    key: key.clone(),
+                    source: source.clone(),
+                    translated: None,
+                    error: Some(e.clone()),
+                }));
+            }
+        }
+        task.progress(results.len(), items.len());
+    }
+
+    app.state::<Session>().edit(doc_id, |open| {
+        for r in &results {
+            // 机翻期间被软删除了的不加回来
+            if let Some(t) = &r.translated {
+                if open.merge_entry(r.key.clone(), t.clone()) {
+                    open.fuzzy.insert(r.key.clone());
+                }
+            }
+        }
+        Ok(())
+    })?;
+    Ok(results)
+}
+
+/// 机翻文档中选中的 key（以其当前 value 为原文）
+#[tauri::command]
+pub async fn gxt_mt_translate(
+    app: AppHandle,
+    doc_id: DocId,
+    keys: Vec<String>,
+    task_id: Option<String>,
+) -> Result<Vec<MtResult>, String> {
+    let task = app.state::<Tasks>().start(&app, task_id)?;
+    let items = app.state::<Session>().with(doc_id, |open| {
+        let by_key: HashMap<&str, &str> = open
+            .doc
+            .entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+        keys.iter()
+            .map(|k| {
+                by_key
+                    .get(k.as_str())
+                    .map(|v| (k.clone(), v.to_string()))
+                    .ok_or_else(|| tr!("entry.not_found", key = k))
+            })
+            .collect::<Result<Vec<_>, String>>()
+    })?;
+    translate_and_apply(&app, doc_id, items, task).await
+}
+
+/// 以 source_path 的原文文件为准，机翻 doc_id 文档里所有“未翻译”的条目：
+/// 文档缺这个 key、value 为空，或与原文完全相同。
+#[tauri::command]
+pub async fn gxt_mt_translate_untranslated(
+    app: AppHandle,
+    doc_id: DocId,
+    source_path: String,
+    task_id: Option<String>,
+) -> Result<Vec<MtResult>, String> {
+    let task = app.state::<Tasks>().start(&app, task_id)?;
+    let source = gxt::load_document(source_path).await?;
+    let items = app.state::<Session>().with(doc_id, |open| {
+        let current: HashMap<&str, &str> = open
+            .doc
+            .entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+        Ok(source
+            .entries
+            .iter()
+            .filter(|e| !e.value.is_empty())
+            .filter(|e| match current.get(e.key.as_str()) {
+                None => true,
+                Some(v) => v.is_empty() || *v == &*e.value,
+            })
+            .map(|e| (e.key.clone(), e.value.to_string()))
+            .collect::<Vec<_>>())
+    })?;
+    translate_and_apply(&app, doc_id, items, task).await
+}
@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+
+use crate::i18n::tr;
+use crate::meta;
+use crate::session::{DocId, OpenDocument, Session};
+use crate::tms::{csv_field, csv_records};
+
+/// 别的 GXT 工具放在文件旁边的注释格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteFormat {
+    /// `KEY=注释` 文本（老工具常见的 .ini / .txt）：`[节]` 表头与 `;` / `#` 注释行忽略，
+    /// 注释里的换行写成 `\n`，反斜杠写成 `\\`
+    Ini,
+    /// `key,note` 两列的 CSV（表格软件里整理的注释），第一行是表头时跳过
+    Csv,
+    /// `{ "KEY": "注释" }` 的 JSON 对象
+    Json,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotesImport {
+    pub imported: usize,
+    /// 已有注释、没有覆盖的 key
+    pub kept: Vec<String>,
+    /// 文件里有、文档里没有的 key（不导入）
+    pub unknown_keys: Vec<String>,
+}
+
+/// 把注释写回文档旁边的附加信息文件（未保存过的文档先只留在内存里）
+pub(crate) fn store_notes(open: &OpenDocument) -> Result<(), String> {
+    match &open.doc.file_path {
+        Some(p) => {
+            let mut m = meta::load(p);
+            m.notes = open.notes.clone();
+            meta::save(p, &m)
+        }
+        None => Ok(()),
+    }
+}
+
+fn ini_escape(note: &str) -> String {
+    let mut out = String::with_capacity(note.len());
+    for c in note.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn ini_unescape(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut it = raw.chars().peekable();
+    while let Some(c) = it.next() {
+        match (c, it.peek()) {
+            ('\\', Some('n')) => {
+                it.next();
+                out.push('\n');
+            }
+            ('\\', Some('\\')) => {
+                it.next();
+                out.push('\\');
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn read_notes(format: NoteFormat, text: &str) -> Result<Vec<(String, String)>, String> {
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+    match format {
+        NoteFormat::Ini => {
+            let mut out = Vec::new();
+            for (n, line) in text.lines().enumerate() {
+                let t = line.trim();
+                if t.is_empty()
+                    || t.starts_with(';')
+                    || t.starts_with('#')
+                    || (t.starts_with('[') && t.ends_with(']'))
+                {
+                    continue;
+                }
+                let (key, note) = t
+                    .split_once('=')
+                    .ok_or_else(|| format!("Line {}: missing '='", n + 1))?;
+                out.push((key.trim().to_string(), ini_unescape(note.trim())));
+            }
+            Ok(out)
+        }
+        NoteFormat::Csv => {
+            let rows = csv_records(text)?;
+            let header = rows
+                .first()
+                .and_then(|r| r.first())
+                .is_some_and(|c| c.trim().eq_ignore_ascii_case("key"));
+            Ok(rows
+                .into_iter()
+                .skip(usize::from(header))
+                .filter(|r| r.len() >= 2 && !r[0].trim().is_empty())
+                .map(|r| (r[0].trim().to_string(), r[1].clone()))
+                .collect())
+        }
+        NoteFormat::Json => {
+            let map: BTreeMap<String, String> =
+                serde_json::from_str(text).map_err(|e| format!("Invalid notes JSON: {e}"))?;
+            Ok(map.into_iter().collect())
+        }
+    }
+}
+
+fn write_notes(format: NoteFormat, notes: &BTreeMap<String, String>) -> Result<String, String> {
+    match format {
+        NoteFormat::Ini => Ok(notes
+            .iter()
+            .map(|(k, n)| format!("{k}={}\n", ini_escape(n)))
+            .collect()),
+        NoteFormat::Csv => {
+            let mut out = String::from("key,note\n");
+            for (k, n) in notes {
+                out.push_str(&format!("{},{}\n", csv_field(k), csv_field(n)));
+            }
+            Ok(out)
+        }
+        NoteFormat::Json => {
+            serde_json::to_string_pretty(notes).map_err(|e| tr!("json.encode_failed", error = e))
+        }
+    }
+}
+
+/// 全部注释（key -> 注释）
+#[tauri::command]
+pub fn gxt_notes(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<BTreeMap<String, String>, String> {
+    session.with(doc_id, |open| Ok(open.notes.clone()))
+}
+
+/// 设置一条的注释（空白即删除）；立即写回附加信息文件
+#[tauri::command]
+pub fn gxt_note_set(
+    doc_id: DocId,
+    key: String,
+    note: String,
+    session: tauri::State<'_, Session>,
+) -> Result<(), String> {
+    session.with(doc_id, |open| {
+        if note.trim().is_empty() {
+            open.notes.remove(&key);
+        } else {
+            open.notes.insert(key, note);
+        }
+        store_notes(open)
+    })
+}
+
+/// 从别的工具的注释文件导入（换用本编辑器时不丢已经做的标注）。
+/// 已有注释的条目只有 overwrite 时才覆盖；空注释跳过
+#[tauri::command]
+pub fn gxt_notes_import(
+    doc_id: DocId,
+    path: String,
+    format: NoteFormat,
+    overwrite: bool,
+    session: tauri::State<'_, Session>,
+) -> Result<NotesImport, String> {
+    let bytes = fs::read(&path).map_err(|e| tr!("file.read_failed", error = e))?;
+    let notes = read_notes(format, &String::from_utf8_lossy(&bytes))?;
+    session.with(doc_id, |open| {
+        let mut report = NotesImport::default();
+        for (key, note) in notes {
+            if note.trim().is_empty() {
+                continue;
+            }
+            if !open.doc.entries.iter().any(|e| e.key == key) {
+                report.unknown_keys.push(key);
+                continue;
+            }
+            if !overwrite && open.notes.contains_key(&key) {
+                report.kept.push(key);
+                continue;
+            }
+            open.notes.insert(key, note);
+            report.imported += 1;
+        }
+        store_notes(open)?;
+        Ok(report)
+    })
+}
+
+/// 把注释导出成别的工具能读的格式；返回导出的条数
+#[tauri::command]
+pub fn gxt_notes_export(
+    doc_id: DocId,
+    path: String,
+    format: NoteFormat,
+    session: tauri::State<'_, Session>,
+) -> Result<usize, String> {
+    let notes = session.with(doc_id, |open| Ok(open.notes.clone()))?;
+    fs::write(&path, write_notes(format, &notes)?)
+        .map_err(|e| tr!("file.write_failed", error = e))?;
+    Ok(notes.len())
+}
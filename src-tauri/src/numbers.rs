@@ -0,0 +1,54 @@
+use serde::{Deserialize, Serialize};
+
+use gxt_core::NumberRules;
+
+use crate::session::{DocId, Session};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NumberChange {
+    pub key: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// 按目标语言改写数字的小数点/千位分隔符，标了单位的 mph/km/h、ft/m 按需换算。
+/// rules 没给时按 locale（如 `de`、`ru-RU`）取内置规则；keys 为空时处理整个文档，锁定的条目不动。
+/// apply 为 false 时只返回改动预览
+#[tauri::command]
+pub fn gxt_localize_numbers(
+    doc_id: DocId,
+    rules: Option<NumberRules>,
+    locale: Option<String>,
+    keys: Option<Vec<String>>,
+    apply: bool,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<NumberChange>, String> {
+    let rules = match (rules, locale) {
+        (Some(r), _) => r,
+        (None, Some(l)) => NumberRules::for_locale(&l)
+            .ok_or_else(|| format!("No built-in number rules for locale {l}"))?,
+        (None, None) => return Err("Give number rules or a locale".into()),
+    };
+    session.edit(doc_id, |open| {
+        let changes: Vec<NumberChange> = open
+            .doc
+            .entries
+            .iter()
+            .filter(|e| keys.as_ref().is_none_or(|k| k.contains(&e.key)))
+            .filter(|e| !open.is_locked(&e.key))
+            .filter_map(|e| {
+                rules.apply(&e.value).map(|new| NumberChange {
+                    key: e.key.clone(),
+                    old: e.value.to_string(),
+                    new,
+                })
+            })
+            .collect();
+        if apply {
+            for c in &changes {
+                open.set_entry(c.key.clone(), c.new.clone());
+            }
+        }
+        Ok(changes)
+    })
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gxt_core::Segment;
+use tauri::{AppHandle, Manager};
+
+use crate::gxt::GxtEntry;
+use crate::i18n::tr;
+use crate::rollback::{self, Target};
+use crate::session::{DocId, Session};
+
+/// 简繁转换方向（与 OpenCC 同名配置的词典链一致）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ZhConversion {
+    /// 简体 -> 繁体（OpenCC 标准）
+    S2t,
+    /// 繁体 -> 简体
+    T2s,
+    /// 简体 -> 台湾正体
+    S2tw,
+    /// 台湾正体 -> 简体
+    Tw2s,
+}
+
+impl ZhConversion {
+    /// 依次执行的若干轮；每轮由若干词典合并而成（词组优先于单字靠最长匹配实现）
+    fn stages(self) -> &'static [&'static [&'static str]] {
+        match self {
+            ZhConversion::S2t => &[&["STPhrases.txt", "STCharacters.txt"]],
+            ZhConversion::T2s => &[&["TSPhrases.txt", "TSCharacters.txt"]],
+            ZhConversion::S2tw => &[&["STPhrases.txt", "STCharacters.txt"], &["TWVariants.txt"]],
+            ZhConversion::Tw2s => &[
+                &["TWVariantsRevPhrases.txt", "TWVariantsRev.txt"],
+                &["TSPhrases.txt", "TSCharacters.txt"],
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ZhConvertResult {
+    /// 转换后的全部条目（前端用它替换当前列表）
+    pub entries: Vec<GxtEntry>,
+    pub changed: Vec<String>,
+}
+
+/// 一轮转换用的词典：OpenCC 的 `词\t候选1 候选2` 文本格式，取第一个候选
+#[derive(Default)]
+struct Dictionary {
+    map: HashMap<String, String>,
+    max_len: usize,
+}
+
+impl Dictionary {
+    fn load(dir: &Path, files: &[&str]) -> Result<Self, String> {
+        let mut dict = Dictionary::default();
+        let mut found = false;
+        // 词组表在前：同一个词条以先出现的为准
+        for name in files {
+            let Ok(text) = fs::read_to_string(dir.join(name)) else {
+                continue;
+            };
+            found = true;
+            for line in text.lines() {
+                let Some((from, to)) = line.split_once('\t') else {
+                    continue;
+                };
+                let Some(to) = to.split_whitespace().next() else {
+                    continue;
+                };
+                if from.is_empty() || dict.map.contains_key(from) {
+                    continue;
+                }
+                dict.max_len = dict.max_len.max(from.chars().count());
+                dict.map.insert(from.to_string(), to.to_string());
+            }
+        }
+        if !found {
+            return Err(format!(
+                "OpenCC dictionary not found in {}: need one of {}",
+                dir.display(),
+                files.join(", ")
+            ));
+        }
+        Ok(dict)
+    }
+
+    /// 正向最长匹配
+    fn convert(&self, text: &str) -> String {
+        let chars: Vec<(usize, char)> = text.char_indices().collect();
+        let mut out = String::with_capacity(text.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let start = chars[i].0;
+            let mut hit = None;
+            for n in (1..=self.max_len.min(chars.len() - i)).rev() {
+                let end = chars.get(i + n).map_or(text.len(), |c| c.0);
+                if let Some(to) = self.map.get(&text[start..end]) {
+                    hit = Some((n, to));
+                    break;
+                }
+            }
+            match hit {
+                Some((n, to)) => {
+                    out.push_str(to);
+                    i += n;
+                }
+                None => {
+                    out.push(chars[i].1);
+                    i += 1;
+                }
+            }
+        }
+        out
+    }
+}
+
+/// 只转换普通文本，~token~ 与转义原样保留
+fn convert_value(stages: &[Dictionary], value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for seg in gxt_core::segments(value) {
+        match seg {
+            Segment::Text(t) => {
+                let mut s = t.to_string();
+                for dict in stages {
+                    s = dict.convert(&s);
+                }
+                out.push_str(&s);
+            }
+            Segment::Token(t) | Segment::Escape(t) => out.push_str(t),
+        }
+    }
+    out
+}
+
+/// 默认词典目录：<app data>/opencc（放入 OpenCC 仓库 data/dictionary 下的 .txt 即可）
+fn default_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|d| d.join("opencc"))
+        .map_err(|e| format!("Resolve app data dir failed: {e}"))
+}
+
+/// 对指定文档做简繁转换；keys 为空时转换全部条目
+#[tauri::command]
+pub async fn gxt_zh_convert(
+    app: AppHandle,
+    doc_id: DocId,
+    conversion: ZhConversion,
+    dictionary_dir: Option<String>,
+    keys: Option<Vec<String>>,
+) -> Result<ZhConvertResult, String> {
+    let dir = match dictionary_dir {
+        Some(d) => PathBuf::from(d),
+        None => default_dir(&app)?,
+    };
+    let stages = tauri::async_runtime::spawn_blocking(move || {
+        conversion
+            .stages()
+            .iter()
+            .map(|files| Dictionary::load(&dir, files))
+            .collect::<Result<Vec<_>, String>>()
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+
+    let wanted: Option<HashSet<String>> = keys.map(|ks| ks.into_iter().collect());
+    rollback::backup_before(&app, "zh-convert", &[Target::Document(doc_id)])?;
+    app.state::<Session>().edit(doc_id, |open| {
+        let targets: Vec<(String, String)> = open
+            .doc
+            .entries
+            .iter()
+            .filter(|e| wanted.as_ref().is_none_or(|ks| ks.contains(&e.key)))
+            .filter(|e| !open.is_locked(&e.key))
+            .map(|e| (e.key.clone(), e.value.to_string()))
+            .collect();
+        let mut changed = Vec::new();
+        for (key, value) in targets {
+            let converted = convert_value(&stages, &value);
+            if converted != value {
+                open.set_entry(key.clone(), converted);
+                changed.push(key);
+            }
+        }
+        Ok(ZhConvertResult {
+            entries: open.doc.entries.clone(),
+            changed,
+        })
+    })
+}
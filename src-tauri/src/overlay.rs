@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+
+use gxt_core::ChangedEntry;
+
+use crate::gxt::{self, GxtDocument};
+use crate::session::{DocId, Session};
+
+/// 以已打开的 base 为准给另一份文件分类的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Overlay {
+    /// 新打开的文档（已在后端登记）
+    pub doc: GxtDocument,
+    pub base_doc_id: DocId,
+    /// 与 base 完全相同的 key（按新文档中顺序，下同）
+    pub identical: Vec<String>,
+    /// 两边都有但 value 不同：old 为 base 的，new 为新文档的（按 base 中顺序）
+    pub changed: Vec<ChangedEntry>,
+    /// base 里没有的 key
+    pub added: Vec<String>,
+    /// base 里有、新文档里没有的 key（按 base 中顺序）
+    pub missing: Vec<String>,
+}
+
+/// 打开 path 并立即按 key 与已打开的 base_doc_id 比较（如拿 mod 的补丁对比原版），
+/// 前端不用再取两份条目自己比。read_only 同 gxt_load
+#[tauri::command]
+pub async fn gxt_load_as_overlay(
+    base_doc_id: DocId,
+    path: String,
+    read_only: Option<bool>,
+    session: tauri::State<'_, Session>,
+) -> Result<Overlay, String> {
+    // base 没打开时先报错，不白读文件
+    let base = session.with(base_doc_id, |open| Ok(open.doc.entries.clone()))?;
+    let (mut doc, warnings) = gxt::load_document_checked(path, None, false).await?;
+    doc.read_only = read_only.unwrap_or(false);
+    let doc_id = gxt::register_loaded(&session, &mut doc)?;
+    if !warnings.is_empty() {
+        session.with(doc_id, |open| {
+            open.warnings = warnings;
+            Ok(())
+        })?;
+    }
+
+    let diff = gxt_core::diff_entries(&base, &doc.entries);
+    let added: Vec<String> = diff.added.into_iter().map(|e| e.key).collect();
+    let differs: HashSet<&str> = added
+        .iter()
+        .map(String::as_str)
+        .chain(diff.changed.iter().map(|c| c.key.as_str()))
+        .collect();
+    let identical = doc
+        .entries
+        .iter()
+        .filter(|e| !differs.contains(e.key.as_str()))
+        .map(|e| e.key.clone())
+        .collect();
+    tracing::info!(
+        identical = doc.entries.len() - differs.len(),
+        changed = diff.changed.len(),
+        added = added.len(),
+        "loaded overlay"
+    );
+    Ok(Overlay {
+        identical,
+        changed: diff.changed,
+        added,
+        missing: diff.removed.into_iter().map(|e| e.key).collect(),
+        doc,
+        base_doc_id,
+    })
+}
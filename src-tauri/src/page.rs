@@ -0,0 +1,297 @@
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use tauri::AppHandle;
+
+use crate::collation::{self, Collator};
+use crate::gxt::GxtEntry;
+use crate::progress::{self, EntryStatus};
+use crate::project::{self, Projects};
+use crate::session::{DocId, OpenDocument, Session};
+use crate::views::SearchScope;
+
+/// 一页默认的行数
+const DEFAULT_LIMIT: usize = 200;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SortField {
+    /// 文档里的顺序
+    #[default]
+    Order,
+    /// key 的字节序（与保存时一致）
+    Key,
+    /// value 按设置里的排序规则（collation）
+    Value,
+    /// value 的字符数
+    ValueLen,
+    Status,
+    /// 最后修改时间（没有记录的排在最前）
+    Modified,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SortKey {
+    pub field: SortField,
+    #[serde(default)]
+    pub desc: bool,
+}
+
+/// 复合筛选：给了的条件同时满足，any_of 里的子筛选至少满足一个
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntryFilter {
+    /// 不区分大小写的子串
+    pub query: Option<String>,
+    pub scope: SearchScope,
+    /// key 前缀（不区分大小写）
+    pub key_prefix: Option<String>,
+    /// value 里要含的 token，如 `~1~`
+    pub token: Option<String>,
+    /// 只要这些状态的；为空不限制
+    pub statuses: Vec<EntryStatus>,
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    /// Unix 秒，含边界；没有修改记录的条目不满足
+    pub modified_after: Option<u64>,
+    pub modified_before: Option<u64>,
+    /// 只要上次保存后改过的（含新加的）
+    pub unsaved: bool,
+    pub any_of: Vec<EntryFilter>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PageQuery {
+    pub offset: usize,
+    /// 为 0 时用默认值
+    pub limit: usize,
+    /// 依次比较；都相等时按文档顺序
+    pub sort: Vec<SortKey>,
+    pub filter: EntryFilter,
+    /// 判断“未翻译”用的原文文件；没给时文档若是当前项目的语言文件则用项目原版
+    pub source_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryRow {
+    /// 在文档里的位置
+    pub index: usize,
+    pub key: String,
+    pub value: String,
+    pub len: usize,
+    pub status: EntryStatus,
+    pub locked: bool,
+    pub modified: Option<u64>,
+    /// 与上次保存时的值不同（见 gxt_revert_entries）
+    pub unsaved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntryPage {
+    /// 筛选后的总行数（表格按它算滚动条）
+    pub total: usize,
+    pub rows: Vec<EntryRow>,
+}
+
+/// 一条的派生数据，筛选、排序、出行共用（视图也用）
+pub(crate) struct RowInfo<'a> {
+    pub index: usize,
+    pub key: &'a str,
+    pub value: &'a str,
+    pub len: usize,
+    pub status: EntryStatus,
+    pub modified: Option<u64>,
+    pub unsaved: bool,
+    /// value 的排序键；只在按 value 排序时才算
+    collated: Vec<u64>,
+}
+
+pub(crate) fn row_info<'a>(
+    open: &'a OpenDocument,
+    source: &HashMap<&str, &str>,
+    index: usize,
+    e: &'a GxtEntry,
+) -> RowInfo<'a> {
+    let original = source.get(e.key.as_str()).copied();
+    RowInfo {
+        index,
+        key: &e.key,
+        value: &e.value,
+        len: e.value.chars().count(),
+        status: progress::status_of(
+            &e.key,
+            &e.value,
+            original,
+            &open.fuzzy,
+            &open.locked,
+            &open.approvals,
+        ),
+        modified: open.stamps.get(&e.key).map(|s| s.modified),
+        unsaved: open.saved_value(&e.key) != Some(&*e.value),
+        collated: Vec::new(),
+    }
+}
+
+impl EntryFilter {
+    pub(crate) fn matches(&self, row: &RowInfo) -> bool {
+        let text_ok = self.query.as_ref().is_none_or(|q| {
+            let q = q.to_lowercase();
+            let in_key = || row.key.to_lowercase().contains(&q);
+            let in_value = || row.value.to_lowercase().contains(&q);
+            match self.scope {
+                SearchScope::Key => in_key(),
+                SearchScope::Value => in_value(),
+                SearchScope::Both => in_key() || in_value(),
+            }
+        });
+        let prefix_ok = self.key_prefix.as_ref().is_none_or(|p| {
+            row.key.len() >= p.len()
+                && row.key.is_char_boundary(p.len())
+                && row.key[..p.len()].eq_ignore_ascii_case(p)
+        });
+        let token_ok = self.token.as_ref().is_none_or(|t| {
+            gxt_core::tokens(row.value)
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(t))
+        });
+        let status_ok = self.statuses.is_empty() || self.statuses.contains(&row.status);
+        let len_ok =
+            self.min_len.is_none_or(|n| row.len >= n) && self.max_len.is_none_or(|n| row.len <= n);
+        let modified_ok = self
+            .modified_after
+            .is_none_or(|t| row.modified.is_some_and(|m| m >= t))
+            && self
+                .modified_before
+                .is_none_or(|t| row.modified.is_some_and(|m| m <= t))
+            && (!self.unsaved || row.unsaved);
+        let any_ok = self.any_of.is_empty() || self.any_of.iter().any(|f| f.matches(row));
+        text_ok && prefix_ok && token_ok && status_ok && len_ok && modified_ok && any_ok
+    }
+}
+
+fn compare(a: &RowInfo, b: &RowInfo, sort: &[SortKey]) -> Ordering {
+    for s in sort {
+        let ord = match s.field {
+            SortField::Order => a.index.cmp(&b.index),
+            SortField::Key => a.key.cmp(b.key),
+            SortField::Value => a.collated.cmp(&b.collated),
+            SortField::ValueLen => a.len.cmp(&b.len),
+            SortField::Status => a.status.cmp(&b.status),
+            SortField::Modified => a.modified.cmp(&b.modified),
+        };
+        let ord = if s.desc { ord.reverse() } else { ord };
+        if ord != Ordering::Equal {
+            return ord;
+        }
+    }
+    a.index.cmp(&b.index)
+}
+
+/// 按 sort 排序；为空时保持原顺序
+pub(crate) fn sort_rows(rows: &mut [RowInfo], sort: &[SortKey], collator: &Collator) {
+    if sort.is_empty() {
+        return;
+    }
+    if sort.iter().any(|s| s.field == SortField::Value) {
+        for r in rows.iter_mut() {
+            r.collated = collator.sort_key(r.value);
+        }
+    }
+    rows.sort_by(|a, b| compare(a, b, sort));
+}
+
+/// sort 里有按 value 排序时按设置建排序规则，否则不用读设置
+pub(crate) fn collator_for(app: &AppHandle, sort: &[SortKey]) -> Result<Collator, String> {
+    if sort.iter().any(|s| s.field == SortField::Value) {
+        collation::from_settings(app)
+    } else {
+        Ok(Collator::default())
+    }
+}
+
+fn page_of(
+    open: &OpenDocument,
+    source: &HashMap<&str, &str>,
+    query: &PageQuery,
+    collator: &Collator,
+) -> EntryPage {
+    let mut rows: Vec<RowInfo> = open
+        .doc
+        .entries
+        .iter()
+        .enumerate()
+        .map(|(index, e)| row_info(open, source, index, e))
+        .filter(|r| query.filter.matches(r))
+        .collect();
+    sort_rows(&mut rows, &query.sort, collator);
+
+    let limit = match query.limit {
+        0 => DEFAULT_LIMIT,
+        n => n,
+    };
+    EntryPage {
+        total: rows.len(),
+        rows: rows
+            .iter()
+            .skip(query.offset)
+            .take(limit)
+            .map(|r| entry_row(open, r))
+            .collect(),
+    }
+}
+
+pub(crate) fn entry_row(open: &OpenDocument, r: &RowInfo) -> EntryRow {
+    EntryRow {
+        index: r.index,
+        key: r.key.to_string(),
+        value: r.value.to_string(),
+        len: r.len,
+        status: r.status,
+        locked: open.is_locked(r.key),
+        modified: r.modified,
+        unsaved: r.unsaved,
+    }
+}
+
+/// 判断“未翻译”用的原文条目：给了 source_path 就用它，否则文档若是当前项目的语言文件则用项目原版
+pub(crate) async fn status_source(
+    session: &Session,
+    projects: &Projects,
+    doc_id: DocId,
+    source_path: Option<&str>,
+) -> Result<Vec<GxtEntry>, String> {
+    let source_path = match source_path {
+        Some(p) => Some(p.to_string()),
+        None => {
+            let file = session.with(doc_id, |open| Ok(open.doc.file_path.clone()))?;
+            projects.current().ok().and_then(|p| {
+                let lang = p.languages.iter().any(|l| Some(&l.path) == file.as_ref());
+                lang.then_some(p.base_path)
+            })
+        }
+    };
+    match source_path {
+        Some(p) => project::current_entries(session, &p).await,
+        None => Ok(Vec::new()),
+    }
+}
+
+/// 表格视图取一页：后端做筛选和排序，只把这一页的行传给前端（5 万行的文档也不用整份传过去）
+#[tauri::command]
+pub async fn gxt_entries_page(
+    app: AppHandle,
+    doc_id: DocId,
+    query: Option<PageQuery>,
+    session: tauri::State<'_, Session>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<EntryPage, String> {
+    let query = query.unwrap_or_default();
+    let source = status_source(&session, &projects, doc_id, query.source_path.as_deref()).await?;
+    let source: HashMap<&str, &str> = source.iter().map(|e| (e.key.as_str(), &*e.value)).collect();
+
+    let collator = collator_for(&app, &query.sort)?;
+    session.with(doc_id, |open| Ok(page_of(open, &source, &query, &collator)))
+}
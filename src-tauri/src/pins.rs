@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+use crate::meta;
+use crate::session::{DocId, OpenDocument, Session};
+
+/// 每个文档最多固定几条（导航栏放得下的量）
+const PIN_LIMIT: usize = 20;
+
+/// 一条固定的条目，value 是当前的（含未保存的修改）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinnedEntry {
+    pub key: String,
+    /// key 已经不在文档里时为 None
+    pub value: Option<String>,
+}
+
+/// 把固定的 key 写回文档旁边的附加信息文件（未保存过的文档先只留在内存里）
+fn store_pins(open: &OpenDocument) -> Result<(), String> {
+    match &open.doc.file_path {
+        Some(p) => {
+            let mut m = meta::load(p);
+            m.pinned = open.pinned.clone();
+            meta::save(p, &m)
+        }
+        None => Ok(()),
+    }
+}
+
+/// 固定的条目（mod 标题、制作人员等常看的文字），按固定的先后
+#[tauri::command]
+pub fn gxt_pinned(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<PinnedEntry>, String> {
+    session.with(doc_id, |open| {
+        Ok(open
+            .pinned
+            .iter()
+            .map(|key| PinnedEntry {
+                key: key.clone(),
+                value: open
+                    .doc
+                    .entries
+                    .iter()
+                    .find(|e| &e.key == key)
+                    .map(|e| e.value.to_string()),
+            })
+            .collect())
+    })
+}
+
+/// 固定/取消固定一批 key；新固定的排在最后。立即写回附加信息文件
+#[tauri::command]
+pub fn gxt_pin_set(
+    doc_id: DocId,
+    keys: Vec<String>,
+    pinned: bool,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    session.with(doc_id, |open| {
+        if pinned {
+            let mut next = open.pinned.clone();
+            for k in keys {
+                if !next.contains(&k) {
+                    next.push(k);
+                }
+            }
+            if next.len() > PIN_LIMIT {
+                return Err(tr!("pins.too_many", limit = PIN_LIMIT));
+            }
+            open.pinned = next;
+        } else {
+            open.pinned.retain(|k| !keys.contains(k));
+        }
+        store_pins(open)?;
+        Ok(open.pinned.clone())
+    })
+}
+
+/// 调整固定条目的先后：keys 为新的顺序，没列出的保持原来的相对顺序排在后面
+#[tauri::command]
+pub fn gxt_pin_reorder(
+    doc_id: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    session.with(doc_id, |open| {
+        let mut ordered: Vec<String> = Vec::with_capacity(open.pinned.len());
+        for k in keys {
+            if open.pinned.contains(&k) && !ordered.contains(&k) {
+                ordered.push(k);
+            }
+        }
+        let rest: Vec<String> = open
+            .pinned
+            .iter()
+            .filter(|k| !ordered.contains(k))
+            .cloned()
+            .collect();
+        ordered.extend(rest);
+        open.pinned = ordered;
+        store_pins(open)?;
+        Ok(open.pinned.clone())
+    })
+}
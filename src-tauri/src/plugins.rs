@@ -0,0 +1,413 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use gxt_core::{LegacyFormat, TextFormat};
+use tauri::{AppHandle, Manager};
+
+use crate::gxt::{GxtDocument, GxtEntry};
+use crate::i18n::tr;
+use crate::presets::{self, Presets};
+use crate::session::Session;
+
+/// 插件清单文件名（每个插件一个子目录）
+const MANIFEST: &str = "plugin.json";
+
+/// 一个格式转换器能做什么；前端用它生成“导入/导出为…”菜单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// 可导入的文件扩展名（不含点，小写）
+    #[serde(default)]
+    pub import_extensions: Vec<String>,
+    /// 导出时使用的扩展名；空表示不支持导出
+    #[serde(default)]
+    pub export_extension: Option<String>,
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+/// 格式转换器。内置文本/JSON 与外部进程插件都实现它。
+pub trait Converter: Send + Sync {
+    fn info(&self) -> &PluginInfo;
+    fn import(&self, path: &Path) -> Result<Vec<GxtEntry>, String>;
+    fn export(&self, entries: &[GxtEntry], path: &Path) -> Result<(), String>;
+}
+
+// -------------------- Built-in converters --------------------
+
+struct TextConverter(PluginInfo);
+
+impl Converter for TextConverter {
+    fn info(&self) -> &PluginInfo {
+        &self.0
+    }
+
+    fn import(&self, path: &Path) -> Result<Vec<GxtEntry>, String> {
+        let text = fs::read_to_string(path).map_err(|e| tr!("file.read_failed", error = e))?;
+        gxt_core::entries_from_text(&text)
+    }
+
+    fn export(&self, entries: &[GxtEntry], path: &Path) -> Result<(), String> {
+        let text = gxt_core::entries_to_text(entries)?;
+        fs::write(path, text).map_err(|e| tr!("file.write_failed", error = e))
+    }
+}
+
+struct JsonConverter(PluginInfo);
+
+impl Converter for JsonConverter {
+    fn info(&self) -> &PluginInfo {
+        &self.0
+    }
+
+    fn import(&self, path: &Path) -> Result<Vec<GxtEntry>, String> {
+        let bytes = fs::read(path).map_err(|e| tr!("file.read_failed", error = e))?;
+        serde_json::from_slice(&bytes).map_err(|e| tr!("json.decode_failed", error = e))
+    }
+
+    fn export(&self, entries: &[GxtEntry], path: &Path) -> Result<(), String> {
+        let bytes =
+            serde_json::to_vec_pretty(entries).map_err(|e| tr!("json.encode_failed", error = e))?;
+        fs::write(path, bytes).map_err(|e| tr!("file.write_failed", error = e))
+    }
+}
+
+/// 老编辑器导出的 txt（见 gxt_core::legacy；除社区 dump 外只能导入）
+struct LegacyConverter(PluginInfo, LegacyFormat);
+
+impl Converter for LegacyConverter {
+    fn info(&self) -> &PluginInfo {
+        &self.0
+    }
+
+    fn import(&self, path: &Path) -> Result<Vec<GxtEntry>, String> {
+        let bytes = fs::read(path).map_err(|e| tr!("file.read_failed", error = e))?;
+        gxt_core::parse_legacy(&gxt_core::decode_legacy_bytes(&bytes), self.1)
+    }
+
+    fn export(&self, entries: &[GxtEntry], path: &Path) -> Result<(), String> {
+        if self.0.export_extension.is_none() {
+            return Err(format!("Converter {} does not support export", self.0.id));
+        }
+        let text = gxt_core::write_legacy(entries, self.1)?;
+        fs::write(path, text).map_err(|e| tr!("file.write_failed", error = e))
+    }
+}
+
+fn builtin_converters() -> Vec<Box<dyn Converter>> {
+    vec![
+        Box::new(TextConverter(PluginInfo {
+            id: "txt".into(),
+            name: "KEY=value text".into(),
+            description: "One entry per line".into(),
+            import_extensions: vec!["txt".into()],
+            export_extension: Some("txt".into()),
+            builtin: true,
+        })),
+        Box::new(JsonConverter(PluginInfo {
+            id: "json".into(),
+            name: "JSON".into(),
+            description: "[{\"key\": ..., \"value\": ...}]".into(),
+            import_extensions: vec!["json".into()],
+            export_extension: Some("json".into()),
+            builtin: true,
+        })),
+        Box::new(LegacyConverter(
+            PluginInfo {
+                id: "gxted13".into(),
+                name: "GXT Editor 1.3 dump".into(),
+                description: "KEY = value, {XX} character codes".into(),
+                import_extensions: vec!["txt".into()],
+                export_extension: None,
+                builtin: true,
+            },
+            LegacyFormat::GxtEditor13,
+        )),
+        Box::new(LegacyConverter(
+            PluginInfo {
+                id: "gxt2txt".into(),
+                name: "gxt2txt output".into(),
+                description: "[KEY] followed by value lines, \\xXX escapes".into(),
+                import_extensions: vec!["txt".into()],
+                export_extension: None,
+                builtin: true,
+            },
+            LegacyFormat::Gxt2txt,
+        )),
+        Box::new(LegacyConverter(
+            PluginInfo {
+                id: "fxt".into(),
+                name: "FXT custom text".into(),
+                description: "KEY text, as used by CLEO / Sanny Builder".into(),
+                import_extensions: vec!["fxt".into(), "txt".into()],
+                export_extension: None,
+                builtin: true,
+            },
+            LegacyFormat::Fxt,
+        )),
+        Box::new(LegacyConverter(
+            PluginInfo {
+                id: "community".into(),
+                name: "american.gxt.txt dump".into(),
+                description: "[TABLE] headers, KEY<Tab>text, ; comments".into(),
+                import_extensions: vec!["txt".into()],
+                export_extension: Some("txt".into()),
+                builtin: true,
+            },
+            LegacyFormat::Community,
+        )),
+    ]
+}
+
+/// 能导入这种文本格式的内置转换器；双语 CSV 没有（要用 gxt_tms_import 导入到打开的文档里）
+pub(crate) fn importer_for(format: TextFormat) -> Option<PluginInfo> {
+    let id = match format {
+        TextFormat::KeyValue => "txt",
+        TextFormat::Json => "json",
+        TextFormat::Legacy(LegacyFormat::GxtEditor13) => "gxted13",
+        TextFormat::Legacy(LegacyFormat::Gxt2txt) => "gxt2txt",
+        TextFormat::Legacy(LegacyFormat::Fxt) => "fxt",
+        TextFormat::Legacy(LegacyFormat::Community) => "community",
+        TextFormat::Csv => return None,
+    };
+    builtin_converters()
+        .into_iter()
+        .map(|c| c.info().clone())
+        .find(|info| info.id == id)
+}
+
+/// 按扩展名能导入 path 的转换器（含外部插件，如处理 .po 的）；有几个时取第一个
+pub(crate) fn importer_by_extension(app: &AppHandle, path: &Path) -> Option<PluginInfo> {
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    discover(app)
+        .into_iter()
+        .map(|c| c.info().clone())
+        .find(|info| info.import_extensions.contains(&ext))
+}
+
+// -------------------- External-process plugins --------------------
+
+/// plugin.json 的内容
+#[derive(Debug, Clone, Deserialize)]
+struct Manifest {
+    #[serde(flatten)]
+    info: PluginInfo,
+    /// 可执行文件（插件目录内的相对路径，或 PATH 上的程序）及固定参数
+    command: Vec<String>,
+}
+
+/// 外部进程协议：
+/// - 导入：`<command...> import <path>`，stdout 输出条目 JSON 数组
+/// - 导出：`<command...> export <path>`，stdin 收到条目 JSON 数组
+/// - 退出码非 0 视为失败，stderr 作为错误信息
+struct ProcessConverter {
+    info: PluginInfo,
+    dir: PathBuf,
+    command: Vec<String>,
+}
+
+impl ProcessConverter {
+    fn command(&self, action: &str, path: &Path) -> Result<Command, String> {
+        let (program, args) = self
+            .command
+            .split_first()
+            .ok_or_else(|| format!("Plugin {} has an empty command", self.info.id))?;
+        // 插件目录里有同名文件就用它，否则交给 PATH（如 "python"）
+        let local = self.dir.join(program);
+        let program = if local.is_file() {
+            local
+        } else {
+            PathBuf::from(program)
+        };
+        let mut cmd = Command::new(program);
+        cmd.args(args).arg(action).arg(path).current_dir(&self.dir);
+        Ok(cmd)
+    }
+}
+
+fn stderr_message(id: &str, stderr: &[u8]) -> String {
+    format!(
+        "Plugin {id} failed: {}",
+        String::from_utf8_lossy(stderr).trim()
+    )
+}
+
+impl Converter for ProcessConverter {
+    fn info(&self) -> &PluginInfo {
+        &self.info
+    }
+
+    fn import(&self, path: &Path) -> Result<Vec<GxtEntry>, String> {
+        let out = self
+            .command("import", path)?
+            .output()
+            .map_err(|e| format!("Run plugin {} failed: {e}", self.info.id))?;
+        if !out.status.success() {
+            return Err(stderr_message(&self.info.id, &out.stderr));
+        }
+        serde_json::from_slice(&out.stdout)
+            .map_err(|e| format!("Plugin {} returned invalid JSON: {e}", self.info.id))
+    }
+
+    fn export(&self, entries: &[GxtEntry], path: &Path) -> Result<(), String> {
+        let input = serde_json::to_vec(entries).map_err(|e| tr!("json.encode_failed", error = e))?;
+        let mut child = self
+            .command("export", path)?
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Run plugin {} failed: {e}", self.info.id))?;
+        // 另开线程写 stdin，同时在这里读 stderr：插件先往 stderr 写满管道再读 stdin 时不会互相卡住
+        let stdin = child.stdin.take();
+        let writer = std::thread::spawn(move || match stdin {
+            Some(mut stdin) => stdin.write_all(&input),
+            None => Ok(()),
+        });
+        let out = child
+            .wait_with_output()
+            .map_err(|e| format!("Run plugin {} failed: {e}", self.info.id))?;
+        let written = writer
+            .join()
+            .map_err(|_| format!("Write to plugin {} failed", self.info.id))?;
+        // 插件没读完就退出时写入会失败；这时它的 stderr 更说明问题
+        if !out.status.success() {
+            return Err(stderr_message(&self.info.id, &out.stderr));
+        }
+        written.map_err(|e| format!("Write to plugin {} failed: {e}", self.info.id))
+    }
+}
+
+/// 插件目录：<app data>/plugins/<插件>/plugin.json
+fn plugins_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|d| d.join("plugins"))
+        .map_err(|e| format!("Resolve app data dir failed: {e}"))
+}
+
+/// 内置转换器 + 插件目录里所有能读懂清单的插件（读不懂的跳过，不影响其它插件）
+fn discover(app: &AppHandle) -> Vec<Box<dyn Converter>> {
+    let mut all = builtin_converters();
+
+    let Ok(dir) = plugins_dir(app) else {
+        return all;
+    };
+    let Ok(read) = fs::read_dir(&dir) else {
+        return all;
+    };
+    for item in read.flatten() {
+        let plugin_dir = item.path();
+        let Ok(bytes) = fs::read(plugin_dir.join(MANIFEST)) else {
+            continue;
+        };
+        let Ok(manifest) = serde_json::from_slice::<Manifest>(&bytes) else {
+            continue;
+        };
+        if all.iter().any(|c| c.info().id == manifest.info.id) {
+            continue;
+        }
+        let mut info = manifest.info;
+        info.builtin = false;
+        all.push(Box::new(ProcessConverter {
+            info,
+            dir: plugin_dir,
+            command: manifest.command,
+        }));
+    }
+    all
+}
+
+pub(crate) fn find(app: &AppHandle, id: &str) -> Result<Box<dyn Converter>, String> {
+    discover(app)
+        .into_iter()
+        .find(|c| c.info().id == id)
+        .ok_or_else(|| format!("No such converter: {id}"))
+}
+
+#[tauri::command]
+pub fn gxt_plugins_list(app: AppHandle) -> Vec<PluginInfo> {
+    discover(&app).iter().map(|c| c.info().clone()).collect()
+}
+
+/// 预设 id 对应的换行 token（见 GamePreset::newline_token）
+fn preset_newline(app: &AppHandle, preset: Option<&str>) -> Result<Option<String>, String> {
+    match preset {
+        Some(id) => Ok(app.state::<Presets>().get(id)?.newline_token),
+        None => Ok(None),
+    }
+}
+
+/// 用指定转换器导入并登记为新文档；返回的文档没有 file_path（保存时需要另存为 .gxt）。
+/// 给了 preset 且它设了换行 token 时，导入的换行换成它；它的 key 规则开了 normalize_on_import 时，
+/// key 转成要求的大小写
+#[tauri::command]
+#[tracing::instrument(skip(app, session), err)]
+pub async fn gxt_plugin_import(
+    app: AppHandle,
+    plugin: String,
+    path: String,
+    preset: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<GxtDocument, String> {
+    let preset = match preset.as_deref() {
+        Some(id) => Some(app.state::<Presets>().get(id)?),
+        None => None,
+    };
+    let mut doc = tauri::async_runtime::spawn_blocking(move || {
+        let mut entries = find(&app, &plugin)?.import(Path::new(&path))?;
+        if let Some(preset) = &preset {
+            for e in &mut entries {
+                if let Some(token) = &preset.newline_token {
+                    e.value = gxt_core::newlines_to_token(&e.value, token).into();
+                }
+                e.key = preset.key_rules.import_key(&e.key);
+            }
+        }
+        gxt_core::validate_entries(&entries)?;
+        Ok::<_, String>(GxtDocument {
+            file_path: None,
+            entries,
+            doc_id: None,
+            embedded: None,
+            read_only: false,
+        })
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+    tracing::info!(entries = doc.entries.len(), "imported");
+    session.open(&mut doc)?;
+    Ok(doc)
+}
+
+/// 给了 preset 且它设了换行 token 时，导出前它换成真正的换行
+#[tauri::command]
+#[tracing::instrument(skip(app, entries), fields(entries = entries.len()), err)]
+pub async fn gxt_plugin_export(
+    app: AppHandle,
+    plugin: String,
+    path: String,
+    mut entries: Vec<GxtEntry>,
+    preset: Option<String>,
+) -> Result<(), String> {
+    presets::export_newlines(
+        &mut entries,
+        preset_newline(&app, preset.as_deref())?.as_deref(),
+    );
+    tauri::async_runtime::spawn_blocking(move || {
+        let converter = find(&app, &plugin)?;
+        if converter.info().export_extension.is_none() {
+            return Err(format!("Converter {plugin} does not support export"));
+        }
+        converter.export(&entries, Path::new(&path))
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
@@ -0,0 +1,587 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use gxt_core::{
+    ArgRule, ArgRules, ArgType, ArgViolation, AstralPolicy, BreakRules, GamePreset, KeyRules,
+    LengthRule, LengthRules, LengthViolation, MetricsOptions, PreviewSegment, RenderMetrics,
+    TokenSet, Truncation,
+};
+use tauri::{AppHandle, Manager};
+
+use crate::doc_profile;
+use crate::gxt::{GxtDocument, GxtEntry};
+use crate::project::Projects;
+use crate::session::{DocId, Session};
+use crate::settings::{self, Severity};
+
+use crate::i18n::tr;
+
+/// 内置预设（源文件在 src-tauri/presets/）
+const BUILTIN: [(&str, &str); 3] = [
+    ("gta3", include_str!("../presets/gta3.toml")),
+    ("vc", include_str!("../presets/vc.toml")),
+    ("sa", include_str!("../presets/sa.toml")),
+];
+
+const USER_DIR: &str = "presets";
+
+/// tauri managed state：内置预设 + <config dir>/presets/*.toml（同 id 时用户的覆盖内置的）
+pub struct Presets {
+    loaded: RwLock<Vec<GamePreset>>,
+}
+
+impl Presets {
+    pub(crate) fn get(&self, id: &str) -> Result<GamePreset, String> {
+        self.loaded
+            .read()
+            .ok()
+            .and_then(|g| g.iter().find(|p| p.id == id).cloned())
+            .ok_or_else(|| format!("No such preset: {id}"))
+    }
+
+    /// id 对应的预设，叠加当前项目的自定义 token（项目设置了 tokens 文件时）
+    pub(crate) fn resolve(&self, id: &str, projects: &Projects) -> Result<GamePreset, String> {
+        let preset = self.get(id)?;
+        match projects.current().ok().and_then(|p| p.tokens_path) {
+            Some(path) => Ok(preset.with_tokens(&load_token_set(&path)?)),
+            None => Ok(preset),
+        }
+    }
+}
+
+/// 读项目的自定义 token 文件：`replace = true/false` 加若干 `[[tokens]]`（字段同预设里的）
+fn load_token_set(path: &str) -> Result<TokenSet, String> {
+    let text =
+        fs::read_to_string(path).map_err(|e| format!("Read token file {path} failed: {e}"))?;
+    toml::from_str(&text).map_err(|e| format!("Invalid token file {path}: {e}"))
+}
+
+/// 当前项目的长度限制规则（项目设置了 length_rules 文件时）
+fn project_length_rules(projects: &Projects) -> Result<Option<LengthRules>, String> {
+    let Some(path) = projects.current().ok().and_then(|p| p.length_rules_path) else {
+        return Ok(None);
+    };
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("Read length rules {path} failed: {e}"))?;
+    toml::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("Invalid length rules {path}: {e}"))
+}
+
+/// 当前项目的参数类型规则（项目设置了 arg_rules 文件时）
+fn project_arg_rules(projects: &Projects) -> Result<Option<ArgRules>, String> {
+    let Some(path) = projects.current().ok().and_then(|p| p.arg_rules_path) else {
+        return Ok(None);
+    };
+    let text =
+        fs::read_to_string(&path).map_err(|e| format!("Read argument rules {path} failed: {e}"))?;
+    toml::from_str(&text)
+        .map(Some)
+        .map_err(|e| format!("Invalid argument rules {path}: {e}"))
+}
+
+fn arg_detail(rule: &ArgRule, v: ArgViolation) -> String {
+    let ty = |t: ArgType| match t {
+        ArgType::Number => "number",
+        ArgType::String => "string",
+        ArgType::Time => "time",
+    };
+    let what = match v {
+        ArgViolation::Missing { index, expected } => {
+            format!("argument {index} ({}) is missing", ty(expected))
+        }
+        ArgViolation::Extra { index, token } => {
+            format!("argument {index} {token} has nothing to fill it")
+        }
+        ArgViolation::Mismatch {
+            index,
+            expected,
+            token,
+        } => format!(
+            "argument {index} should be a {}, found {token}",
+            ty(expected)
+        ),
+    };
+    let name = if rule.name.is_empty() {
+        &rule.pattern
+    } else {
+        &rule.name
+    };
+    format!("{what} ({name})")
+}
+
+fn length_detail(rule: &LengthRule, v: LengthViolation) -> String {
+    let what = match v {
+        LengthViolation::Chars { len, max } => format!("{len} chars, max {max}"),
+        LengthViolation::Width { line, width, max } => {
+            format!("line {line} is {width} wide, max {max}")
+        }
+        LengthViolation::Lines { lines, max } => format!("{lines} lines, max {max}"),
+        LengthViolation::Bytes { bytes, max } => format!("{bytes} bytes encoded, max {max}"),
+    };
+    let name = if rule.name.is_empty() {
+        &rule.pattern
+    } else {
+        &rule.name
+    };
+    format!("{what} ({name})")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetReport {
+    pub presets: Vec<GamePreset>,
+    /// 读不了的用户预设文件及原因（不影响其它预设）
+    pub errors: Vec<String>,
+}
+
+fn user_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map(|d| d.join(USER_DIR))
+        .map_err(|e| format!("Resolve config dir failed: {e}"))
+}
+
+fn parse(text: &str) -> Result<GamePreset, String> {
+    toml::from_str(text).map_err(|e| e.to_string())
+}
+
+fn load_all(app: &AppHandle) -> PresetReport {
+    let mut presets: Vec<GamePreset> = BUILTIN
+        .iter()
+        .map(|(id, text)| parse(text).unwrap_or_else(|e| panic!("Bad builtin preset {id}: {e}")))
+        .collect();
+    let mut errors = Vec::new();
+
+    let files = user_dir(app)
+        .ok()
+        .and_then(|d| fs::read_dir(d).ok())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|item| item.path())
+        .filter(|p| {
+            p.extension()
+                .is_some_and(|x| x.eq_ignore_ascii_case("toml"))
+        });
+    for path in files {
+        let parsed = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|t| parse(&t));
+        match parsed {
+            Ok(p) => match presets.iter_mut().find(|old| old.id == p.id) {
+                Some(old) => *old = p,
+                None => presets.push(p),
+            },
+            Err(e) => errors.push(format!("{}: {e}", path.display())),
+        }
+    }
+    for e in &errors {
+        tracing::warn!(error = %e, "skip preset");
+    }
+    PresetReport { presets, errors }
+}
+
+/// 启动时加载一次
+pub fn init(app: &AppHandle) -> Presets {
+    Presets {
+        loaded: RwLock::new(load_all(app).presets),
+    }
+}
+
+#[tauri::command]
+pub fn gxt_presets_list(presets: tauri::State<'_, Presets>) -> Vec<GamePreset> {
+    presets
+        .loaded
+        .read()
+        .map(|g| g.to_vec())
+        .unwrap_or_default()
+}
+
+/// 用户改了预设文件后重新加载
+#[tauri::command]
+pub fn gxt_presets_reload(app: AppHandle, presets: tauri::State<'_, Presets>) -> PresetReport {
+    let report = load_all(&app);
+    if let Ok(mut g) = presets.loaded.write() {
+        *g = report.presets.clone();
+    }
+    report
+}
+
+/// 把一个内置预设复制到用户预设目录供修改；返回文件路径（已存在时不覆盖）
+#[tauri::command]
+pub fn gxt_preset_customize(app: AppHandle, id: String) -> Result<String, String> {
+    let (_, text) = BUILTIN
+        .iter()
+        .find(|(b, _)| *b == id)
+        .ok_or_else(|| tr!("preset.not_builtin", id = id))?;
+    let dir = user_dir(&app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Create preset dir failed: {e}"))?;
+    let path = dir.join(format!("{id}.toml"));
+    if !path.exists() {
+        fs::write(&path, text).map_err(|e| format!("Write preset failed: {e}"))?;
+    }
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// 预设叠加当前项目自定义 token 之后的样子（前端高亮 token、列 token 面板用）
+#[tauri::command]
+pub fn gxt_preset_resolved(
+    preset_id: String,
+    presets: tauri::State<'_, Presets>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<GamePreset, String> {
+    presets.resolve(&preset_id, &projects)
+}
+
+/// 把 value 渲染成显示片段（颜色、按键提示、换行），供前端按游戏文本框的样子预览。
+/// preset_id 为空或找不到时不认颜色；项目的 token 文件读不了时只用预设本身的
+#[tauri::command]
+pub fn gxt_preview(
+    value: String,
+    preset_id: Option<String>,
+    presets: tauri::State<'_, Presets>,
+    projects: tauri::State<'_, Projects>,
+) -> Vec<PreviewSegment> {
+    let preset = preset_id.and_then(|id| {
+        presets
+            .resolve(&id, &projects)
+            .inspect_err(|e| tracing::warn!(error = %e, "skip project tokens"))
+            .or_else(|_| presets.get(&id))
+            .ok()
+    });
+    gxt_core::preview(&value, preset.as_ref())
+}
+
+/// 估算 value 在游戏里显示的尺寸：每行宽度、行数、自动换行后的行数、某分辨率下的像素，
+/// 以及是否超出文本框。编辑时随输入调用，给“放得下 / 超出”的提示
+#[tauri::command]
+pub fn gxt_render_metrics(
+    value: String,
+    preset_id: String,
+    font_id: u32,
+    options: Option<MetricsOptions>,
+    presets: tauri::State<'_, Presets>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<RenderMetrics, String> {
+    let preset = presets.resolve(&preset_id, &projects)?;
+    let font = preset
+        .fonts
+        .iter()
+        .find(|f| f.id == font_id)
+        .ok_or_else(|| format!("No font {font_id} in preset {}", preset.id))?;
+    Ok(gxt_core::render_metrics(
+        &value,
+        font,
+        &preset,
+        &options.unwrap_or_default(),
+    ))
+}
+
+/// 一条在游戏里实际能看到的部分：自动换行后超过 max_rows 的行看不到；wrap 为 false 时
+/// （不自动换行的 HUD 文字等）每行超出 box_width 的部分被截掉。让译者看到文字从哪里被切掉
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub fn gxt_truncation_preview(
+    doc_id: DocId,
+    key: String,
+    preset_id: String,
+    font_id: u32,
+    options: MetricsOptions,
+    wrap: Option<bool>,
+    session: tauri::State<'_, Session>,
+    presets: tauri::State<'_, Presets>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<Truncation, String> {
+    let preset = presets.resolve(&preset_id, &projects)?;
+    let font = preset
+        .fonts
+        .iter()
+        .find(|f| f.id == font_id)
+        .ok_or_else(|| format!("No font {font_id} in preset {}", preset.id))?;
+    let value = session.with(doc_id, |open| {
+        open.doc
+            .entries
+            .iter()
+            .find(|e| e.key == key)
+            .map(|e| e.value.to_string())
+            .ok_or_else(|| tr!("entry.not_found", key = key))
+    })?;
+    Ok(gxt_core::visible_part(
+        &value,
+        font,
+        &preset,
+        &options,
+        wrap.unwrap_or(true),
+    ))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PresetIssue {
+    pub key: String,
+    /// 规则 id：`preset-key` / `unknown-token` / `token-args` / `unknown-key-binding` / `disallowed-char` /
+    /// `max-length` / `value-bytes` / `astral-char`
+    pub rule: String,
+    pub detail: String,
+    /// 设置里该规则的严重程度（没设置时为 warning）
+    pub severity: Severity,
+}
+
+/// 按预设（叠加项目的自定义 token）检查文档：key 规则、游戏不认识的 token 与按键动作、
+/// 参数不够的 token、字体里没有的字符、游戏显示不了的 U+FFFF 以上的字符、编码后超出游戏缓冲区的值，
+/// 以及项目长度规则里超长的值
+#[tauri::command]
+pub fn gxt_preset_check(
+    app: AppHandle,
+    doc_id: DocId,
+    preset_id: String,
+    presets: tauri::State<'_, Presets>,
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<PresetIssue>, String> {
+    let preset = presets.resolve(&preset_id, &projects)?;
+    let limits = project_length_rules(&projects)?;
+    let arg_rules = project_arg_rules(&projects)?;
+    let rules = settings::load(&app)?.validation;
+    let severity = |rule: &str| rules.get(rule).copied().unwrap_or(Severity::Warning);
+
+    session.with(doc_id, |open| {
+        let mut out = Vec::new();
+        let mut push = |key: &str, rule: &str, detail: String| {
+            if severity(rule) != Severity::Off {
+                out.push(PresetIssue {
+                    key: key.to_string(),
+                    rule: rule.to_string(),
+                    detail,
+                    severity: severity(rule),
+                });
+            }
+        };
+        for e in &open.doc.entries {
+            if let Err(msg) = preset.check_key(&e.key) {
+                push(&e.key, "preset-key", msg);
+            }
+            for t in preset.unknown_tokens(&e.value) {
+                push(&e.key, "unknown-token", t.to_string());
+            }
+            for t in preset.missing_token_args(&e.value) {
+                push(&e.key, "token-args", t.to_string());
+            }
+            for a in preset.unknown_key_bindings(&e.value) {
+                push(&e.key, "unknown-key-binding", a.to_string());
+            }
+            let chars = preset.disallowed_chars(&e.value);
+            if !chars.is_empty() {
+                push(&e.key, "disallowed-char", chars.into_iter().collect());
+            }
+            if preset.astral != AstralPolicy::Allow {
+                let chars = gxt_core::astral_chars(&e.value);
+                if !chars.is_empty() {
+                    push(&e.key, "astral-char", chars.into_iter().collect());
+                }
+            }
+            if let Some(bytes) = preset.oversized_value(&e.value) {
+                let max = preset.max_value_bytes.unwrap_or_default();
+                push(
+                    &e.key,
+                    "value-bytes",
+                    format!("{bytes} bytes encoded, max {max}"),
+                );
+            }
+            if let Some(rule) = limits.as_ref().and_then(|l| l.rule_for(&e.key)) {
+                for v in rule.check(&e.value, Some(&preset)) {
+                    push(&e.key, "max-length", length_detail(rule, v));
+                }
+            }
+            if let Some(rules) = &arg_rules {
+                if let Some(rule) = rules.rule_for(&e.key) {
+                    for v in rules.check(rule, &e.value) {
+                        push(&e.key, "arg-type", arg_detail(rule, v));
+                    }
+                }
+            }
+        }
+        Ok(out)
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WrapOptions {
+    pub preset_id: String,
+    /// 预设 fonts 里的字体编号
+    pub font_id: u32,
+    /// 文本框宽度（像素；字体没有度量时按半角 1、全角 2 计）
+    pub max_width: u32,
+    /// 原有的 `~n~` 都保留，只在超宽的行里补新的（默认单个 `~n~` 参与重排）
+    #[serde(default)]
+    pub keep_breaks: bool,
+    /// 断行规则；不给时按文档在当前项目里的语言代码选（zh / ja / ko），不属于项目的用通用规则
+    #[serde(default)]
+    pub rules: Option<BreakRules>,
+}
+
+/// 按预设里字体的字宽给条目重新断行，使每行不超过文本框宽度。
+/// keys 为空时处理整个文档；锁定的条目不动。返回改动了的 key
+#[tauri::command]
+pub fn gxt_wrap(
+    doc_id: DocId,
+    options: WrapOptions,
+    keys: Option<Vec<String>>,
+    presets: tauri::State<'_, Presets>,
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    let preset = presets.resolve(&options.preset_id, &projects)?;
+    let font = preset
+        .fonts
+        .iter()
+        .find(|f| f.id == options.font_id)
+        .ok_or_else(|| format!("No font {} in preset {}", options.font_id, preset.id))?;
+    let rules = match options.rules {
+        Some(r) => r,
+        None => {
+            let path = session.with(doc_id, |open| Ok(open.doc.file_path.clone()))?;
+            projects
+                .current()
+                .ok()
+                .and_then(|p| {
+                    p.languages
+                        .into_iter()
+                        .find(|l| Some(&l.path) == path.as_ref())
+                })
+                .map_or(BreakRules::Generic, |l| BreakRules::for_language(&l.code))
+        }
+    };
+
+    session.edit(doc_id, |open| {
+        let wrapped: Vec<(String, String)> = open
+            .doc
+            .entries
+            .iter()
+            .filter(|e| keys.as_ref().is_none_or(|k| k.contains(&e.key)))
+            .filter(|e| !open.is_locked(&e.key))
+            .filter_map(|e| {
+                let v = gxt_core::wrap_value_with(
+                    &e.value,
+                    font,
+                    &preset.tokens,
+                    options.max_width,
+                    options.keep_breaks,
+                    rules,
+                );
+                (v != *e.value).then(|| (e.key.clone(), v))
+            })
+            .collect();
+        let mut changed = Vec::with_capacity(wrapped.len());
+        for (key, value) in wrapped {
+            open.set_entry(key.clone(), value);
+            changed.push(key);
+        }
+        Ok(changed)
+    })
+}
+
+/// 文件属于当前项目时，项目设置里选的预设 id；否则是这个文件记住的预设
+pub(crate) fn project_preset(projects: &Projects, file_path: Option<&str>) -> Option<String> {
+    let file_path = file_path?;
+    match projects.current() {
+        Ok(p) if p.contains_file(file_path) => p.project.settings.preset,
+        _ => doc_profile::get(file_path)?.preset,
+    }
+}
+
+/// 文档所用预设的换行 token（见 GamePreset::newline_token）；没有预设或预设里没设时为 None
+pub(crate) fn newline_token(app: &AppHandle, file_path: Option<&str>) -> Option<String> {
+    let id = project_preset(&app.state::<Projects>(), file_path)?;
+    app.state::<Presets>().get(&id).ok()?.newline_token
+}
+
+/// 文档所用预设的 key 规则；没有预设时为默认规则
+pub(crate) fn key_rules(app: &AppHandle, file_path: Option<&str>) -> KeyRules {
+    project_preset(&app.state::<Projects>(), file_path)
+        .and_then(|id| app.state::<Presets>().get(&id).ok())
+        .map(|p| p.key_rules)
+        .unwrap_or_default()
+}
+
+/// 导出给外部工具前调用：换行 token 换成真正的换行
+pub(crate) fn export_newlines(entries: &mut [GxtEntry], token: Option<&str>) {
+    let Some(token) = token else {
+        return;
+    };
+    for e in entries {
+        e.value = gxt_core::token_to_newlines(&e.value, token).into();
+    }
+}
+
+/// 保存前调用：开了确定性输出，或文档所属项目的预设要求 key 有序，而文档没排好时按字节序排序。
+/// 返回是否排了
+pub(crate) fn sort_for_save(app: &AppHandle, doc: &mut GxtDocument) -> bool {
+    let deterministic = settings::load(app).is_ok_and(|s| s.deterministic_save);
+    let required = deterministic
+        || project_preset(&app.state::<Projects>(), doc.file_path.as_deref())
+            .is_some_and(|id| app.state::<Presets>().get(&id).is_ok_and(|p| p.sorted_keys));
+    if !required || gxt_core::unsorted_keys(&doc.entries).is_empty() {
+        return false;
+    }
+    gxt_core::sort_entries(&mut doc.entries);
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SortCheck {
+    /// 比前一个 key 小、破坏了顺序的 key（为空即已排好）
+    pub out_of_order: Vec<String>,
+    /// 所用的预设是否要求有序（没有预设时为 false）
+    pub required: bool,
+    /// 要求有序的预设名
+    pub required_by: Vec<String>,
+    /// 给用户看的说明
+    pub note: String,
+}
+
+/// 检查 TKEY 是否按字节序排列，并说明哪些游戏要求有序。
+/// preset_id 为空时用文档所属项目的预设
+#[tauri::command]
+pub fn gxt_check_sort(
+    doc_id: DocId,
+    preset_id: Option<String>,
+    presets: tauri::State<'_, Presets>,
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<SortCheck, String> {
+    let (file_path, out_of_order) = session.with(doc_id, |open| {
+        let keys = gxt_core::unsorted_keys(&open.doc.entries);
+        Ok((
+            open.doc.file_path.clone(),
+            keys.into_iter().map(String::from).collect::<Vec<_>>(),
+        ))
+    })?;
+    let preset = match preset_id.or_else(|| project_preset(&projects, file_path.as_deref())) {
+        Some(id) => Some(presets.get(&id)?),
+        None => None,
+    };
+    let required_by: Vec<String> = presets
+        .loaded
+        .read()
+        .map(|g| {
+            g.iter()
+                .filter(|p| p.sorted_keys)
+                .map(|p| p.name.clone())
+                .collect()
+        })
+        .unwrap_or_default();
+    let note = match &preset {
+        Some(p) if p.sorted_keys => tr!("sort.required", game = p.name),
+        Some(p) => tr!("sort.not_required", game = p.name),
+        None => tr!("sort.unknown", games = required_by.join(", ")),
+    };
+    Ok(SortCheck {
+        out_of_order,
+        required: preset.is_some_and(|p| p.sorted_keys),
+        required_by,
+        note,
+    })
+}
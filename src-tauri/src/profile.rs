@@ -0,0 +1,78 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+use crate::gxt::{self, GxtDocument};
+use crate::i18n::tr;
+
+/// 一次完整 load + save 流程各阶段耗时（毫秒）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileReport {
+    pub file_size: usize,
+    pub entry_count: usize,
+    pub read_ms: f64,
+    pub tkey_parse_ms: f64,
+    pub value_decode_ms: f64,
+    pub ipc_serialize_ms: f64,
+    pub encode_ms: f64,
+    pub total_ms: f64,
+}
+
+fn ms_since(t: Instant) -> f64 {
+    t.elapsed().as_secs_f64() * 1000.0
+}
+
+/// 诊断用：按 gxt_load / gxt_save 的真实路径跑一遍并计时（不写盘、不改动当前文档）。
+/// ipc_serialize 用 serde_json 模拟 invoke 返回时的序列化开销。
+#[tauri::command]
+pub async fn gxt_profile(path: String) -> Result<ProfileReport, String> {
+    tauri::async_runtime::spawn_blocking(move || profile_file(path))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+fn profile_file(path: String) -> Result<ProfileReport, String> {
+    let start = Instant::now();
+
+    let t = Instant::now();
+    let bytes = fs::read(PathBuf::from(&path)).map_err(|e| tr!("file.read_failed", error = e))?;
+    let read_ms = ms_since(t);
+
+    let t = Instant::now();
+    let (keys, val_field) = gxt::parse_key_table(&bytes)?;
+    let tkey_parse_ms = ms_since(t);
+
+    let t = Instant::now();
+    let entries = gxt::decode_values(keys, val_field)?;
+    let value_decode_ms = ms_since(t);
+
+    let doc = GxtDocument {
+        file_path: Some(path),
+        entries,
+        doc_id: None,
+        embedded: None,
+        read_only: false,
+    };
+
+    let t = Instant::now();
+    let json = serde_json::to_vec(&doc).map_err(|e| tr!("json.encode_failed", error = e))?;
+    let ipc_serialize_ms = ms_since(t);
+    drop(json);
+
+    let t = Instant::now();
+    gxt::build_gxt_bytes(&doc.entries)?;
+    let encode_ms = ms_since(t);
+
+    Ok(ProfileReport {
+        file_size: bytes.len(),
+        entry_count: doc.entries.len(),
+        read_ms,
+        tkey_parse_ms,
+        value_decode_ms,
+        ipc_serialize_ms,
+        encode_ms,
+        total_ms: ms_since(start),
+    })
+}
@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gxt_core::{GroupLevel, Segment, KEY_RECORD_BYTES};
+
+use crate::gxt::{self, GxtEntry};
+use crate::i18n::tr;
+use crate::meta::{self, Approval};
+use crate::project::{self, Projects};
+use crate::session::{DocId, Session};
+
+/// 以原文为准时一条的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntryStatus {
+    /// 缺 key、值为空或与原文相同
+    Untranslated,
+    /// 需要复核（机翻、原文变过）
+    Fuzzy,
+    Translated,
+    /// 审校人签字确认过，之后没再改
+    Approved,
+    /// 已锁定（审校通过）
+    Reviewed,
+}
+
+const ALL_STATUSES: [EntryStatus; 5] = [
+    EntryStatus::Untranslated,
+    EntryStatus::Fuzzy,
+    EntryStatus::Translated,
+    EntryStatus::Approved,
+    EntryStatus::Reviewed,
+];
+
+/// 一条的状态；source 为原文（没有原文时只有空值算未翻译）
+pub(crate) fn status_of(
+    key: &str,
+    value: &str,
+    source: Option<&str>,
+    fuzzy: &BTreeSet<String>,
+    locked: &BTreeSet<String>,
+    approvals: &BTreeMap<String, Approval>,
+) -> EntryStatus {
+    if value.is_empty() || source == Some(value) {
+        EntryStatus::Untranslated
+    } else if locked.contains(key) {
+        EntryStatus::Reviewed
+    } else if approvals.get(key).is_some_and(|a| a.value == value) {
+        EntryStatus::Approved
+    } else if fuzzy.contains(key) {
+        EntryStatus::Fuzzy
+    } else {
+        EntryStatus::Translated
+    }
+}
+
+/// 条数以及对应原文的字数/字符数（只算显示出来的文字，token 不算）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Counts {
+    pub entries: usize,
+    pub words: usize,
+    pub chars: usize,
+}
+
+impl Counts {
+    pub(crate) fn add(&mut self, text: &str) {
+        let mut display = String::new();
+        for seg in gxt_core::segments(text) {
+            match seg {
+                Segment::Text(t) => display.push_str(t),
+                Segment::Token(t) if t.eq_ignore_ascii_case("~n~") => display.push(' '),
+                Segment::Token(_) => {}
+                Segment::Escape(_) => display.push('?'),
+            }
+        }
+        self.entries += 1;
+        self.words += display.split_whitespace().count();
+        self.chars += display.chars().filter(|c| !c.is_whitespace()).count();
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressReport {
+    /// 项目语言代码；单个文档时为 None
+    pub code: Option<String>,
+    pub path: Option<String>,
+    pub total: Counts,
+    /// 每种状态都有一项（没有条目时为 0）
+    pub by_status: BTreeMap<EntryStatus, Counts>,
+    /// 已翻译（含待复核与已审校）占原文字数的比例（0~1）
+    pub translated: f64,
+    /// 已审校占原文字数的比例（0~1）
+    pub reviewed: f64,
+}
+
+/// 按原文统计译文各状态的条数与字数；source 为 None 时以译文自身为原文（只有空值算未翻译）
+fn report(
+    source: Option<&[GxtEntry]>,
+    entries: &[GxtEntry],
+    fuzzy: &BTreeSet<String>,
+    locked: &BTreeSet<String>,
+    approvals: &BTreeMap<String, Approval>,
+) -> ProgressReport {
+    let values: HashMap<&str, &str> = entries
+        .iter()
+        .map(|e| (e.key.as_str(), &*e.value))
+        .collect();
+    let mut by_status: BTreeMap<EntryStatus, Counts> = ALL_STATUSES
+        .iter()
+        .map(|s| (*s, Counts::default()))
+        .collect();
+    let mut total = Counts::default();
+
+    for src in source
+        .unwrap_or(entries)
+        .iter()
+        .filter(|e| !e.value.is_empty())
+    {
+        let value = values.get(src.key.as_str()).copied().unwrap_or_default();
+        let original = source.is_some().then_some(&*src.value);
+        let status = status_of(&src.key, value, original, fuzzy, locked, approvals);
+        total.add(&src.value);
+        if let Some(c) = by_status.get_mut(&status) {
+            c.add(&src.value);
+        }
+    }
+
+    let words = |s: EntryStatus| by_status.get(&s).map_or(0, |c| c.words);
+    let ratio = |n: usize| {
+        if total.words == 0 {
+            1.0
+        } else {
+            n as f64 / total.words as f64
+        }
+    };
+    let translated = ratio(total.words - words(EntryStatus::Untranslated));
+    let reviewed = ratio(words(EntryStatus::Reviewed));
+    ProgressReport {
+        code: None,
+        path: None,
+        total,
+        by_status,
+        translated,
+        reviewed,
+    }
+}
+
+/// 单个打开的文档的进度。source_path 为原文文件（不给时只把空值当作未翻译）
+#[tauri::command]
+pub async fn gxt_progress(
+    doc_id: DocId,
+    source_path: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<ProgressReport, String> {
+    let source = match source_path {
+        Some(p) => Some(gxt::load_document(p).await?.entries),
+        None => None,
+    };
+    session.with(doc_id, |open| {
+        let mut r = report(
+            source.as_deref(),
+            &open.doc.entries,
+            &open.fuzzy,
+            &open.locked,
+            &open.approvals,
+        );
+        r.path = open.doc.file_path.clone();
+        Ok(r)
+    })
+}
+
+/// 当前项目每个语言文件相对原版的进度。打开着的文件按编辑中的内容统计，
+/// 没打开的文件没有 fuzzy 标记，锁定状态从附加信息文件读
+#[tauri::command]
+pub async fn gxt_project_progress(
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<ProgressReport>, String> {
+    let p = projects.current()?;
+    let base = project::current_entries(&session, &p.base_path).await?;
+    let mut out = Vec::new();
+    for lang in &p.languages {
+        let open_id = session
+            .list()
+            .into_iter()
+            .find(|d| d.file_path.as_deref() == Some(lang.path.as_str()))
+            .map(|d| d.doc_id);
+        let mut r = match open_id {
+            Some(id) => session.with(id, |open| {
+                Ok(report(
+                    Some(&base),
+                    &open.doc.entries,
+                    &open.fuzzy,
+                    &open.locked,
+                    &open.approvals,
+                ))
+            })?,
+            None => {
+                let entries = gxt::load_document(lang.path.clone()).await?.entries;
+                let m = meta::load(&lang.path);
+                report(
+                    Some(&base),
+                    &entries,
+                    &BTreeSet::new(),
+                    &m.locked,
+                    &m.approvals,
+                )
+            }
+        };
+        r.code = Some(lang.code.clone());
+        r.path = Some(lang.path.clone());
+        out.push(r);
+    }
+    Ok(out)
+}
+
+/// 一张表（按 key 前缀分的一组条目，见 gxt_core::split_tables）的概况，给侧栏目录用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableStats {
+    pub name: String,
+    /// 文档里这张表的条数
+    pub entries: usize,
+    /// 这张表在 GXT 里占的字节（TKEY 记录 + TDAT 里的 value）
+    pub bytes: usize,
+    /// 相对原文的进度；原文里有、文档里整张表都没有时 entries 为 0
+    pub progress: ProgressReport,
+}
+
+/// 文档按表统计：条数、字节数与翻译进度，按表在原文（没给时为文档）里出现的顺序。
+/// level 缺省为到第一个 `_` 为止（与命令行 split 相同）；source_path 为原文文件
+#[tauri::command]
+pub async fn gxt_table_stats(
+    doc_id: DocId,
+    level: Option<GroupLevel>,
+    source_path: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<TableStats>, String> {
+    let level = level.unwrap_or(GroupLevel::Until('_'));
+    let source = match source_path {
+        Some(p) => Some(gxt::load_document(p).await?.entries),
+        None => None,
+    };
+    session.with(doc_id, |open| {
+        let tables = gxt_core::split_tables(&open.doc.entries, &level);
+        let mut names: Vec<&str> = Vec::new();
+        let source_tables = source
+            .as_deref()
+            .map(|s| gxt_core::split_tables(s, &level))
+            .unwrap_or_default();
+        let source_by_name: HashMap<&str, &[GxtEntry]> = source_tables
+            .iter()
+            .map(|(name, entries)| (name.as_str(), entries.as_slice()))
+            .collect();
+        let doc_by_name: HashMap<&str, &[GxtEntry]> = tables
+            .iter()
+            .map(|(name, entries)| (name.as_str(), entries.as_slice()))
+            .collect();
+        for (name, _) in source_tables.iter().chain(&tables) {
+            if !names.contains(&name.as_str()) {
+                names.push(name);
+            }
+        }
+        names
+            .into_iter()
+            .map(|name| {
+                let entries = doc_by_name.get(name).copied().unwrap_or_default();
+                let bytes = entries.iter().try_fold(0, |n, e| {
+                    Ok::<_, String>(n + KEY_RECORD_BYTES + gxt_core::encoded_utf16z_len(&e.value)?)
+                })?;
+                let source = source
+                    .is_some()
+                    .then(|| source_by_name.get(name).copied().unwrap_or_default());
+                Ok(TableStats {
+                    name: name.to_string(),
+                    entries: entries.len(),
+                    bytes,
+                    progress: report(source, entries, &open.fuzzy, &open.locked, &open.approvals),
+                })
+            })
+            .collect()
+    })
+}
+
+/// 把进度追加到 CSV（每周导出一次就是一份进度表）；文件不存在时先写表头
+#[tauri::command]
+pub fn gxt_progress_export(path: String, reports: Vec<ProgressReport>) -> Result<(), String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let mut out = String::new();
+    if fs::metadata(&path).is_err() {
+        out.push_str("timestamp,code,path,entries,words,chars");
+        for s in ALL_STATUSES {
+            out.push_str(&format!(",{s:?}_entries,{s:?}_words").to_lowercase());
+        }
+        out.push_str(",translated,reviewed\n");
+    }
+    for r in &reports {
+        out.push_str(&format!(
+            "{now},{},{},{},{},{}",
+            csv_field(r.code.as_deref().unwrap_or_default()),
+            csv_field(r.path.as_deref().unwrap_or_default()),
+            r.total.entries,
+            r.total.words,
+            r.total.chars
+        ));
+        for s in ALL_STATUSES {
+            let c = r.by_status.get(&s).copied().unwrap_or_default();
+            out.push_str(&format!(",{},{}", c.entries, c.words));
+        }
+        out.push_str(&format!(",{:.4},{:.4}\n", r.translated, r.reviewed));
+    }
+    let mut text = fs::read_to_string(&path).unwrap_or_default();
+    text.push_str(&out);
+    fs::write(&path, text).map_err(|e| tr!("file.write_failed", error = e))
+}
+
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
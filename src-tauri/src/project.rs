@@ -0,0 +1,719 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use gxt_core::{GamePreset, Segment};
+use tauri::AppHandle;
+
+use crate::gxt::{self, GxtEntry};
+use crate::hooks::SaveHook;
+use crate::i18n::tr;
+use crate::merge_report::{self, Decision, MergeReport};
+use crate::meta;
+use crate::presets::Presets;
+use crate::rollback::{self, Target};
+use crate::session::Session;
+use crate::settings::{self, Severity};
+
+/// 设置里 validation 表中对应本检查的规则 id
+pub const RULE_PROTECTED_KEY: &str = "protected-key";
+pub const RULE_TOKEN_MISMATCH: &str = "token-mismatch";
+
+/// 项目文件：一份原版游戏文件 + 若干语言文件。路径相对项目文件所在目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub name: String,
+    /// 原版（源语言）文件
+    pub base: String,
+    #[serde(default)]
+    pub languages: Vec<ProjectLanguage>,
+    #[serde(default)]
+    pub settings: ProjectSettings,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectLanguage {
+    /// 语言代码或显示名（如 `zh-CN`）
+    pub code: String,
+    pub path: String,
+}
+
+/// 项目级设置，覆盖全局设置里的同名项
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectSettings {
+    /// 使用的编码方案名（见全局设置的 encoding_profiles）
+    #[serde(default)]
+    pub encoding_profile: Option<String>,
+    /// 目标游戏的预设 id（见 presets.rs）；决定保存时是否要给 key 排序等
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// 自定义 token 定义文件（TOML，相对项目文件所在目录）；校验、预览、断行都按它叠加到预设上
+    #[serde(default)]
+    pub tokens: Option<String>,
+    /// 按 key 模式的长度限制文件（TOML，相对项目文件所在目录），见 gxt_core::LengthRules
+    #[serde(default)]
+    pub length_rules: Option<String>,
+    /// 按 key 模式给出 `~1~` 等参数 token 类型的文件（TOML，相对项目文件所在目录），见 gxt_core::ArgRules
+    #[serde(default)]
+    pub arg_rules: Option<String>,
+    /// 保存时用的音译表名（见 translit.rs）：预设字体里没有的字母按表换掉再写盘
+    #[serde(default)]
+    pub transliteration: Option<String>,
+    /// 机翻/TMS 用的源语言代码
+    #[serde(default)]
+    pub source_lang: Option<String>,
+    /// 不允许删除/改名的 key（如 mod 必须保留的原版系统 key）；以 `*` 结尾表示前缀
+    #[serde(default)]
+    pub protected_keys: Vec<String>,
+    /// 任务脚本所在的文件或目录（CLEO 文件夹、Sanny Builder 源码等，相对项目文件所在目录）；
+    /// 改 key 名时在这里找旧 key 的引用
+    #[serde(default)]
+    pub scripts: Vec<String>,
+    /// 删除条目时默认软删除（见 tombstones.rs）：多人同步的项目里，合并别人的文件不会把删掉的 key 加回来
+    #[serde(default)]
+    pub soft_delete: bool,
+    /// 项目里的文件保存成功后依次跑的外部命令（见 hooks.rs）
+    #[serde(default)]
+    pub post_save: Vec<SaveHook>,
+}
+
+impl ProjectSettings {
+    pub fn is_protected(&self, key: &str) -> bool {
+        self.protected_keys
+            .iter()
+            .any(|p| match p.strip_suffix('*') {
+                Some(prefix) => key.starts_with(prefix),
+                None => p == key,
+            })
+    }
+}
+
+/// 已打开的项目：路径都已解析为绝对路径
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedProject {
+    pub path: String,
+    pub project: Project,
+    pub base_path: String,
+    pub languages: Vec<ProjectLanguage>,
+    #[serde(default)]
+    pub tokens_path: Option<String>,
+    #[serde(default)]
+    pub length_rules_path: Option<String>,
+    #[serde(default)]
+    pub arg_rules_path: Option<String>,
+    #[serde(default)]
+    pub scripts_paths: Vec<String>,
+}
+
+impl LoadedProject {
+    /// path 是否是本项目的原版或语言文件
+    pub fn contains_file(&self, path: &str) -> bool {
+        self.base_path == path || self.languages.iter().any(|l| l.path == path)
+    }
+
+    fn new(path: &Path, project: Project) -> Self {
+        let dir = path.parent().unwrap_or(Path::new("."));
+        let resolve = |p: &str| dir.join(p).to_string_lossy().into_owned();
+        LoadedProject {
+            path: path.to_string_lossy().into_owned(),
+            base_path: resolve(&project.base),
+            languages: project
+                .languages
+                .iter()
+                .map(|l| ProjectLanguage {
+                    code: l.code.clone(),
+                    path: resolve(&l.path),
+                })
+                .collect(),
+            tokens_path: project.settings.tokens.as_deref().map(resolve),
+            length_rules_path: project.settings.length_rules.as_deref().map(resolve),
+            arg_rules_path: project.settings.arg_rules.as_deref().map(resolve),
+            scripts_paths: project
+                .settings
+                .scripts
+                .iter()
+                .map(|p| resolve(p))
+                .collect(),
+            project,
+        }
+    }
+}
+
+/// tauri managed state：当前打开的项目
+#[derive(Default)]
+pub struct Projects {
+    current: Mutex<Option<LoadedProject>>,
+}
+
+impl Projects {
+    pub fn current(&self) -> Result<LoadedProject, String> {
+        self.current
+            .lock()
+            .map_err(|_| "Project lock poisoned".to_string())?
+            .clone()
+            .ok_or_else(|| tr!("project.none_open"))
+    }
+
+    /// 删除 key 前检查：文件属于当前项目且 key 受保护时报错（force 为 true 时放行）
+    pub fn check_removal(
+        &self,
+        file_path: Option<&str>,
+        key: &str,
+        force: bool,
+    ) -> Result<(), String> {
+        if force {
+            return Ok(());
+        }
+        let Ok(Some(p)) = self.current.lock().map(|g| g.clone()) else {
+            return Ok(());
+        };
+        if file_path.is_some_and(|f| p.contains_file(f)) && p.project.settings.is_protected(key) {
+            return Err(tr!("entry.protected", key = key));
+        }
+        Ok(())
+    }
+
+    /// 文件属于当前项目且项目设置了 soft_delete
+    pub fn soft_delete(&self, file_path: Option<&str>) -> bool {
+        let Ok(Some(p)) = self.current.lock().map(|g| g.clone()) else {
+            return false;
+        };
+        p.project.settings.soft_delete && file_path.is_some_and(|f| p.contains_file(f))
+    }
+}
+
+/// 读一个文件的条目：已经在编辑器里打开的用内存中的（含未保存修改），否则读盘
+pub(crate) async fn current_entries(
+    session: &Session,
+    path: &str,
+) -> Result<Vec<GxtEntry>, String> {
+    let open = session
+        .list()
+        .into_iter()
+        .find(|d| d.file_path.as_deref() == Some(path));
+    match open {
+        Some(d) => session.with(d.doc_id, |open| Ok(open.doc.entries.clone())),
+        None => Ok(gxt::load_document(path.to_string()).await?.entries),
+    }
+}
+
+#[tauri::command]
+pub fn gxt_project_open(
+    path: String,
+    projects: tauri::State<'_, Projects>,
+) -> Result<LoadedProject, String> {
+    let text = fs::read_to_string(&path).map_err(|e| tr!("project.read_failed", error = e))?;
+    let project: Project =
+        serde_json::from_str(&text).map_err(|e| tr!("project.invalid", error = e))?;
+    let loaded = LoadedProject::new(&PathBuf::from(&path), project);
+    *projects
+        .current
+        .lock()
+        .map_err(|_| "Project lock poisoned".to_string())? = Some(loaded.clone());
+    Ok(loaded)
+}
+
+/// 新建/另存项目文件（不改变当前打开的项目）
+#[tauri::command]
+pub fn gxt_project_save(path: String, project: Project) -> Result<(), String> {
+    let bytes =
+        serde_json::to_vec_pretty(&project).map_err(|e| tr!("json.encode_failed", error = e))?;
+    fs::write(&path, bytes).map_err(|e| format!("Write project failed: {e}"))
+}
+
+#[tauri::command]
+pub fn gxt_project_close(projects: tauri::State<'_, Projects>) -> Result<(), String> {
+    *projects
+        .current
+        .lock()
+        .map_err(|_| "Project lock poisoned".to_string())? = None;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyInLanguage {
+    /// 原版文件为 None
+    pub code: Option<String>,
+    pub path: String,
+    /// 这个文件里没有该 key 时为 None
+    pub value: Option<String>,
+}
+
+/// 在原版和所有语言文件里查同一个 key
+#[tauri::command]
+pub async fn gxt_project_find_key(
+    key: String,
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<KeyInLanguage>, String> {
+    let p = projects.current()?;
+    let files = std::iter::once((None, p.base_path.clone())).chain(
+        p.languages
+            .iter()
+            .map(|l| (Some(l.code.clone()), l.path.clone())),
+    );
+    let mut out = Vec::new();
+    for (code, path) in files {
+        let entries = current_entries(&session, &path).await?;
+        let value = entries
+            .iter()
+            .find(|e| e.key == key)
+            .map(|e| e.value.to_string());
+        out.push(KeyInLanguage { code, path, value });
+    }
+    Ok(out)
+}
+
+/// 两种语言对照时一行的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CompareStatus {
+    /// 两边相同（多半还没翻译）
+    Same,
+    Different,
+    /// 有一边是空值
+    Empty,
+    OnlyA,
+    OnlyB,
+}
+
+/// 对照视图里的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageRow {
+    pub key: String,
+    pub value_a: Option<String>,
+    pub value_b: Option<String>,
+    pub status: CompareStatus,
+}
+
+/// 两个语言文件按 key 对齐（先按 A 的顺序，再接上只在 B 里有的 key），给双栏校对视图用。
+/// 已经在编辑器里打开的文件用内存中的内容
+#[tauri::command]
+pub async fn gxt_compare_languages(
+    path_a: String,
+    path_b: String,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<LanguageRow>, String> {
+    let a = current_entries(&session, &path_a).await?;
+    let b = current_entries(&session, &path_b).await?;
+    let b_values: HashMap<&str, &str> = b.iter().map(|e| (e.key.as_str(), &*e.value)).collect();
+    let a_keys: HashSet<&str> = a.iter().map(|e| e.key.as_str()).collect();
+    let mut rows = Vec::with_capacity(a.len().max(b.len()));
+    for e in &a {
+        let (value_b, status) = match b_values.get(e.key.as_str()) {
+            None => (None, CompareStatus::OnlyA),
+            Some(v) => {
+                let status = if e.value.is_empty() || v.is_empty() {
+                    CompareStatus::Empty
+                } else if *e.value == **v {
+                    CompareStatus::Same
+                } else {
+                    CompareStatus::Different
+                };
+                (Some(v.to_string()), status)
+            }
+        };
+        rows.push(LanguageRow {
+            key: e.key.clone(),
+            value_a: Some(e.value.to_string()),
+            value_b,
+            status,
+        });
+    }
+    rows.extend(
+        b.iter()
+            .filter(|e| !a_keys.contains(e.key.as_str()))
+            .map(|e| LanguageRow {
+                key: e.key.clone(),
+                value_a: None,
+                value_b: Some(e.value.to_string()),
+                status: CompareStatus::OnlyB,
+            }),
+    );
+    Ok(rows)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageStats {
+    pub code: String,
+    pub path: String,
+    /// 原版里有文本的条目数
+    pub total: usize,
+    pub translated: usize,
+    /// 缺 key、值为空或与原文相同
+    pub untranslated: usize,
+    /// 原版里没有的 key
+    pub extra: usize,
+    /// translated / total（0~1）
+    pub progress: f64,
+}
+
+/// 各语言相对原版的翻译进度
+#[tauri::command]
+pub async fn gxt_project_stats(
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<LanguageStats>, String> {
+    let p = projects.current()?;
+    let base = current_entries(&session, &p.base_path).await?;
+    let base_values: HashMap<&str, &str> = base
+        .iter()
+        .filter(|e| !e.value.is_empty())
+        .map(|e| (e.key.as_str(), &*e.value))
+        .collect();
+    let base_keys: HashSet<&str> = base.iter().map(|e| e.key.as_str()).collect();
+
+    let mut out = Vec::new();
+    for lang in &p.languages {
+        let entries = current_entries(&session, &lang.path).await?;
+        let values: HashMap<&str, &str> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+        let translated = base_values
+            .iter()
+            .filter(|(k, src)| values.get(*k).is_some_and(|v| !v.is_empty() && v != *src))
+            .count();
+        let extra = entries
+            .iter()
+            .filter(|e| !base_keys.contains(e.key.as_str()))
+            .count();
+        let total = base_values.len();
+        out.push(LanguageStats {
+            code: lang.code.clone(),
+            path: lang.path.clone(),
+            total,
+            translated,
+            untranslated: total - translated,
+            extra,
+            progress: if total == 0 {
+                1.0
+            } else {
+                translated as f64 / total as f64
+            },
+        });
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LanguageSync {
+    pub code: String,
+    pub path: String,
+    /// 从原版补进来的 key（值先用原文，等待翻译）
+    pub added: Vec<String>,
+    /// 原版里有、但这个语言文件里软删除过的 key：不补回来
+    pub deleted: Vec<String>,
+    /// 原版里已经没有的 key：只标记出来，不自动删除
+    pub removed: Vec<String>,
+    /// 该文件正在编辑器里打开：改动进了编辑中的文档（需要保存），否则直接写回文件
+    pub in_editor: bool,
+}
+
+/// 让所有语言文件的 key 集合与原版一致：缺的 key 以原文补上（打开着的文档里同时标为 fuzzy），
+/// 多出来的 key 列出来交给用户处理。apply 为 false 时只报告不修改
+#[tauri::command]
+pub async fn gxt_project_sync_languages(
+    app: AppHandle,
+    apply: bool,
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<LanguageSync>, String> {
+    let p = projects.current()?;
+    let base = current_entries(&session, &p.base_path).await?;
+    let base_keys: HashSet<&str> = base.iter().map(|e| e.key.as_str()).collect();
+    if apply {
+        let targets: Vec<Target> = p.languages.iter().map(|l| Target::File(&l.path)).collect();
+        rollback::backup_before(&app, "sync-languages", &targets)?;
+    }
+
+    let mut out = Vec::new();
+    for lang in &p.languages {
+        let mut entries = current_entries(&session, &lang.path).await?;
+        let open_id = session
+            .list()
+            .into_iter()
+            .find(|d| d.file_path.as_deref() == Some(lang.path.as_str()))
+            .map(|d| d.doc_id);
+        // 语言文件里软删除过的 key 不从原版补回来
+        let tombstoned: HashSet<String> = match open_id {
+            Some(id) => session.with(id, |open| Ok(open.tombstones.keys().cloned().collect()))?,
+            None => meta::load(&lang.path).tombstones.into_keys().collect(),
+        };
+        let keys: HashSet<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+        let (deleted, missing): (Vec<GxtEntry>, Vec<GxtEntry>) = base
+            .iter()
+            .filter(|e| !keys.contains(e.key.as_str()))
+            .cloned()
+            .partition(|e| tombstoned.contains(&e.key));
+        let deleted: Vec<String> = deleted.into_iter().map(|e| e.key).collect();
+        let removed: Vec<String> = entries
+            .iter()
+            .filter(|e| !base_keys.contains(e.key.as_str()))
+            .map(|e| e.key.clone())
+            .collect();
+
+        if apply && (!missing.is_empty() || (open_id.is_some() && !removed.is_empty())) {
+            match open_id {
+                Some(id) => session.edit(id, |open| {
+                    for e in &missing {
+                        if open.merge_entry(e.key.clone(), e.value.to_string()) {
+                            open.fuzzy.insert(e.key.clone());
+                        }
+                    }
+                    for k in &removed {
+                        open.fuzzy.insert(k.clone());
+                    }
+                    Ok(())
+                })?,
+                None => {
+                    entries.extend(missing.iter().cloned());
+                    let bytes = gxt::build_gxt_bytes(&entries)?;
+                    fs::write(&lang.path, bytes)
+                        .map_err(|e| format!("Write {} failed: {e}", lang.path))?;
+                }
+            }
+        }
+        if apply && (!missing.is_empty() || !deleted.is_empty()) {
+            let mut audit = MergeReport::new(
+                "language-sync",
+                Some(lang.path.clone()),
+                Some(p.base_path.clone()),
+            );
+            for e in &missing {
+                audit.push(&e.key, Decision::Added, None);
+            }
+            for k in &deleted {
+                audit.push(k, Decision::Skipped, Some("tombstoned".into()));
+            }
+            merge_report::record(&app, &audit);
+        }
+        out.push(LanguageSync {
+            code: lang.code.clone(),
+            path: lang.path.clone(),
+            added: missing.into_iter().map(|e| e.key).collect(),
+            deleted,
+            removed,
+            in_editor: open_id.is_some(),
+        });
+    }
+    Ok(out)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectedKeyIssue {
+    /// 原版文件为 None
+    pub code: Option<String>,
+    pub path: String,
+    pub key: String,
+    /// 设置里 `protected-key` 规则的严重程度
+    pub severity: Severity,
+}
+
+/// 校验：原版里受保护的 key 在各文件里是否都还在（打开着的文档按内存中的内容检查）
+#[tauri::command]
+pub async fn gxt_project_check_protected(
+    app: AppHandle,
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<ProtectedKeyIssue>, String> {
+    let severity = settings::load(&app)?
+        .validation
+        .get(RULE_PROTECTED_KEY)
+        .copied()
+        .unwrap_or(Severity::Error);
+    if severity == Severity::Off {
+        return Ok(Vec::new());
+    }
+    let p = projects.current()?;
+    let rules = &p.project.settings;
+    // 前缀规则在原版里展开成具体的 key
+    let base = current_entries(&session, &p.base_path).await?;
+    let mut protected: Vec<String> = base
+        .iter()
+        .filter(|e| rules.is_protected(&e.key))
+        .map(|e| e.key.clone())
+        .collect();
+    for k in &rules.protected_keys {
+        if !k.ends_with('*') && !protected.contains(k) {
+            protected.push(k.clone());
+        }
+    }
+
+    let files = std::iter::once((None, p.base_path.clone())).chain(
+        p.languages
+            .iter()
+            .map(|l| (Some(l.code.clone()), l.path.clone())),
+    );
+    let mut out = Vec::new();
+    for (code, path) in files {
+        let entries = current_entries(&session, &path).await?;
+        let keys: HashSet<&str> = entries.iter().map(|e| e.key.as_str()).collect();
+        for k in protected.iter().filter(|k| !keys.contains(k.as_str())) {
+            out.push(ProtectedKeyIssue {
+                code: code.clone(),
+                path: path.clone(),
+                key: k.clone(),
+                severity,
+            });
+        }
+    }
+    Ok(out)
+}
+
+/// 一个 key 在各语言里与原版不一致的 token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMismatch {
+    pub key: String,
+    /// 原版用到的 token（排序后，重复的列多次）
+    pub expected: Vec<String>,
+    /// 与 TokenMatrix::languages 一一对应；一致、缺 key 或值为空的语言是空的 TokenDiff
+    pub cells: Vec<TokenDiff>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TokenDiff {
+    /// 原版有、这个语言少了的
+    pub missing: Vec<String>,
+    /// 这个语言多出来的
+    pub extra: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenMatrix {
+    /// 语言代码（列）
+    pub languages: Vec<String>,
+    /// 至少一个语言不一致的 key（行）
+    pub rows: Vec<TokenMismatch>,
+    /// 设置里 `token-mismatch` 规则的严重程度
+    pub severity: Severity,
+}
+
+/// value 里要跨语言保持一致的 token：`~n~` 和颜色 token 不算（各语言断行、强调可以不同），
+/// 带参数的 token 与参数合成一项（如 `~k~~ped_sprint~`）；不分大小写，排好序
+fn token_signature(value: &str, preset: Option<&GamePreset>) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut segs = gxt_core::segments(value).into_iter().peekable();
+    while let Some(seg) = segs.next() {
+        let Segment::Token(t) = seg else {
+            continue;
+        };
+        let args = match preset {
+            Some(p) => p.token_args(t),
+            None => usize::from(t.eq_ignore_ascii_case(gxt_core::preset::KEY_PREFIX)),
+        };
+        let mut token = t.to_ascii_lowercase();
+        for _ in 0..args {
+            match segs.next_if(|s| matches!(s, Segment::Token(_))) {
+                Some(a) => token.push_str(&a.as_str().to_ascii_lowercase()),
+                None => break,
+            }
+        }
+        let color = preset
+            .and_then(|p| p.token(t))
+            .is_some_and(|d| d.color.is_some());
+        if !color && token != "~n~" {
+            out.push(token);
+        }
+    }
+    out.sort();
+    out
+}
+
+/// 两个排好序的多重集合之差：(a 有 b 没有, b 有 a 没有)
+fn multiset_diff(a: &[String], b: &[String]) -> (Vec<String>, Vec<String>) {
+    let (mut i, mut j) = (0, 0);
+    let (mut only_a, mut only_b) = (Vec::new(), Vec::new());
+    while i < a.len() || j < b.len() {
+        match (a.get(i), b.get(j)) {
+            (Some(x), Some(y)) if x == y => {
+                i += 1;
+                j += 1;
+            }
+            (Some(x), Some(y)) if x < y => {
+                only_a.push(x.clone());
+                i += 1;
+            }
+            (Some(x), None) => {
+                only_a.push(x.clone());
+                i += 1;
+            }
+            (_, Some(y)) => {
+                only_b.push(y.clone());
+                j += 1;
+            }
+            (None, None) => break,
+        }
+    }
+    (only_a, only_b)
+}
+
+/// 跨语言检查 token：每个 key 在各语言里用到的占位符、按键提示等 token 应与原版相同。
+/// 项目设置了预设时按预设（含项目自定义 token）识别颜色 token 与参数
+#[tauri::command]
+pub async fn gxt_project_check_tokens(
+    app: AppHandle,
+    projects: tauri::State<'_, Projects>,
+    presets: tauri::State<'_, Presets>,
+    session: tauri::State<'_, Session>,
+) -> Result<TokenMatrix, String> {
+    let severity = settings::load(&app)?
+        .validation
+        .get(RULE_TOKEN_MISMATCH)
+        .copied()
+        .unwrap_or(Severity::Warning);
+    let p = projects.current()?;
+    let languages: Vec<String> = p.languages.iter().map(|l| l.code.clone()).collect();
+    if severity == Severity::Off {
+        return Ok(TokenMatrix {
+            languages,
+            rows: Vec::new(),
+            severity,
+        });
+    }
+    let preset = match &p.project.settings.preset {
+        Some(id) => Some(presets.resolve(id, &projects)?),
+        None => None,
+    };
+
+    let base = current_entries(&session, &p.base_path).await?;
+    let mut rows: Vec<TokenMismatch> = base
+        .iter()
+        .map(|e| TokenMismatch {
+            key: e.key.clone(),
+            expected: token_signature(&e.value, preset.as_ref()),
+            cells: Vec::with_capacity(languages.len()),
+        })
+        .collect();
+    for lang in &p.languages {
+        let entries = current_entries(&session, &lang.path).await?;
+        let values: HashMap<&str, &str> = entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+        for row in &mut rows {
+            let cell = match values.get(row.key.as_str()) {
+                Some(v) if !v.is_empty() => {
+                    let (missing, extra) =
+                        multiset_diff(&row.expected, &token_signature(v, preset.as_ref()));
+                    TokenDiff { missing, extra }
+                }
+                _ => TokenDiff::default(),
+            };
+            row.cells.push(cell);
+        }
+    }
+    rows.retain(|r| {
+        r.cells
+            .iter()
+            .any(|c| !c.missing.is_empty() || !c.extra.is_empty())
+    });
+    Ok(TokenMatrix {
+        languages,
+        rows,
+        severity,
+    })
+}
@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+
+use gxt_core::GroupLevel;
+use tauri::{AppHandle, Manager};
+
+use crate::i18n::tr;
+use crate::page;
+use crate::progress::{self, Counts, EntryStatus};
+use crate::project::Projects;
+use crate::session::{DocId, Session};
+use crate::task::Tasks;
+use crate::tm::TranslationMemory;
+
+/// 与记忆库相似度低于它的算新词
+const LOWEST_MATCH: f64 = 0.75;
+
+/// 按与记忆库的匹配程度分的档；翻译商按档给不同的每词单价
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MatchBand {
+    /// 文档里前面已经出现过同样的原文
+    Repetition,
+    /// 记忆库里有一模一样的原文
+    Exact,
+    /// 相似度 95%–99%
+    Fuzzy95,
+    /// 85%–94%
+    Fuzzy85,
+    /// 75%–84%
+    Fuzzy75,
+    /// 记忆库里没有 75% 以上相似的
+    New,
+}
+
+const ALL_BANDS: [MatchBand; 6] = [
+    MatchBand::Repetition,
+    MatchBand::Exact,
+    MatchBand::Fuzzy95,
+    MatchBand::Fuzzy85,
+    MatchBand::Fuzzy75,
+    MatchBand::New,
+];
+
+impl MatchBand {
+    fn of(score: Option<f64>) -> Self {
+        match score {
+            Some(s) if s >= 1.0 => MatchBand::Exact,
+            Some(s) if s >= 0.95 => MatchBand::Fuzzy95,
+            Some(s) if s >= 0.85 => MatchBand::Fuzzy85,
+            Some(s) if s >= LOWEST_MATCH => MatchBand::Fuzzy75,
+            _ => MatchBand::New,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            MatchBand::Repetition => "repetition",
+            MatchBand::Exact => "100%",
+            MatchBand::Fuzzy95 => "95-99%",
+            MatchBand::Fuzzy85 => "85-94%",
+            MatchBand::Fuzzy75 => "75-84%",
+            MatchBand::New => "new",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WordCountQuery {
+    /// 原文文件（同 gxt_entries_page）；没有原文时以文档自身为原文
+    pub source_path: Option<String>,
+    /// 分表方式，缺省为到第一个 `_` 为止
+    pub level: Option<GroupLevel>,
+    /// 只算还要做的条目（未翻译与待复核），报价只报这部分
+    pub pending_only: bool,
+}
+
+/// 原文的条数/字数，合计与按档
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BandCounts {
+    pub total: Counts,
+    /// 每档都有一项（没有条目时为 0）
+    pub bands: BTreeMap<MatchBand, Counts>,
+}
+
+impl BandCounts {
+    fn new() -> Self {
+        BandCounts {
+            total: Counts::default(),
+            bands: ALL_BANDS.iter().map(|b| (*b, Counts::default())).collect(),
+        }
+    }
+
+    fn add(&mut self, band: MatchBand, text: &str) {
+        self.total.add(text);
+        self.bands.entry(band).or_default().add(text);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableWordCount {
+    pub name: String,
+    #[serde(flatten)]
+    pub counts: BandCounts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WordCountReport {
+    pub path: Option<String>,
+    /// 按表在原文里出现的顺序
+    pub tables: Vec<TableWordCount>,
+    #[serde(flatten)]
+    pub counts: BandCounts,
+}
+
+/// 给翻译商报价用的字数分析：按表统计原文字数，并按记忆库匹配程度分档
+/// （文档里重复的原文只有第一次查记忆库，之后算 repetition）。
+/// 给了 task_id 时推送查记忆库的进度，可用 gxt_cancel_task 取消
+#[tauri::command]
+pub async fn gxt_word_count(
+    app: AppHandle,
+    doc_id: DocId,
+    query: Option<WordCountQuery>,
+    task_id: Option<String>,
+    session: tauri::State<'_, Session>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<WordCountReport, String> {
+    let query = query.unwrap_or_default();
+    let mut task = app.state::<Tasks>().start(&app, task_id)?;
+    let source =
+        page::status_source(&session, &projects, doc_id, query.source_path.as_deref()).await?;
+    let level = query.level.clone().unwrap_or(GroupLevel::Until('_'));
+
+    // (表名, 原文)，按表排好
+    let (path, units) = session.with(doc_id, |open| {
+        let values: HashMap<&str, &str> = open
+            .doc
+            .entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+        let has_source = !source.is_empty();
+        let base = if has_source {
+            &source
+        } else {
+            &open.doc.entries
+        };
+        let mut units: Vec<(String, String)> = Vec::new();
+        for (name, entries) in gxt_core::split_tables(base, &level) {
+            for e in entries.iter().filter(|e| !e.value.is_empty()) {
+                if query.pending_only {
+                    let value = values.get(e.key.as_str()).copied().unwrap_or_default();
+                    let status = progress::status_of(
+                        &e.key,
+                        value,
+                        has_source.then_some(&*e.value),
+                        &open.fuzzy,
+                        &open.locked,
+                        &open.approvals,
+                    );
+                    if !matches!(status, EntryStatus::Untranslated | EntryStatus::Fuzzy) {
+                        continue;
+                    }
+                }
+                units.push((name.clone(), e.value.to_string()));
+            }
+        }
+        Ok((open.doc.file_path.clone(), units))
+    })?;
+
+    let mut seen: HashSet<&str> = HashSet::new();
+    let first: Vec<bool> = units.iter().map(|(_, v)| seen.insert(v)).collect();
+    let unique: Vec<&str> = units
+        .iter()
+        .zip(&first)
+        .filter(|(_, first)| **first)
+        .map(|((_, v), _)| v.as_str())
+        .collect();
+    let total = unique.len();
+    let tm = app.state::<TranslationMemory>();
+    let scores = tm.best_scores(&app, &unique, LOWEST_MATCH, &mut |done| {
+        task.step(done, total)
+    })?;
+    task.progress(total, total);
+    let scores: HashMap<&str, Option<f64>> = unique.into_iter().zip(scores).collect();
+
+    let mut report = WordCountReport {
+        path,
+        tables: Vec::new(),
+        counts: BandCounts::new(),
+    };
+    for ((name, value), first) in units.iter().zip(first) {
+        let band = if first {
+            MatchBand::of(scores.get(value.as_str()).copied().flatten())
+        } else {
+            MatchBand::Repetition
+        };
+        if report.tables.last().is_none_or(|t| t.name != *name) {
+            report.tables.push(TableWordCount {
+                name: name.clone(),
+                counts: BandCounts::new(),
+            });
+        }
+        if let Some(t) = report.tables.last_mut() {
+            t.counts.add(band, value);
+        }
+        report.counts.add(band, value);
+    }
+    Ok(report)
+}
+
+/// 把字数分析写成可以直接给出去的报价 CSV：每张表每档一行，最后是各档合计与总价。
+/// rates 为各档的每词单价，没给的档按 0 计
+#[tauri::command]
+pub fn gxt_word_count_export(
+    path: String,
+    report: WordCountReport,
+    rates: BTreeMap<MatchBand, f64>,
+) -> Result<(), String> {
+    let rate = |band: MatchBand| rates.get(&band).copied().unwrap_or(0.0);
+    let mut out = String::from("table,band,entries,words,chars,rate,amount\n");
+    let mut rows = |table: &str, counts: &BandCounts| {
+        let mut amount = 0.0;
+        for band in ALL_BANDS {
+            let c = counts.bands.get(&band).copied().unwrap_or_default();
+            let line = c.words as f64 * rate(band);
+            amount += line;
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{line:.2}\n",
+                progress::csv_field(table),
+                band.label(),
+                c.entries,
+                c.words,
+                c.chars,
+                rate(band)
+            ));
+        }
+        amount
+    };
+    for t in &report.tables {
+        rows(&t.name, &t.counts);
+    }
+    let amount = rows("total", &report.counts);
+    let t = report.counts.total;
+    out.push_str(&format!(
+        "total,all,{},{},{},,{amount:.2}\n",
+        t.entries, t.words, t.chars
+    ));
+    fs::write(&path, out).map_err(|e| tr!("file.write_failed", error = e))
+}
@@ -0,0 +1,99 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use crate::gxt;
+use crate::session::{DocId, Session};
+
+/// 挂在文档上的参考文件（通常是原文）：只留 key -> value，前端按需取，不用在前端再开一份文档
+#[derive(Debug)]
+pub struct ReferenceFile {
+    pub path: String,
+    pub values: HashMap<String, Arc<str>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReferenceInfo {
+    pub path: String,
+    pub entry_count: usize,
+    /// 参考文件里有、文档里没有的 key 数
+    pub missing_in_document: usize,
+}
+
+fn info(reference: &ReferenceFile, keys: impl Iterator<Item = String>) -> ReferenceInfo {
+    let present = keys.filter(|k| reference.values.contains_key(k)).count();
+    ReferenceInfo {
+        path: reference.path.clone(),
+        entry_count: reference.values.len(),
+        missing_in_document: reference.values.len() - present,
+    }
+}
+
+/// 给文档挂上参考文件（已有的换掉）。只在本次打开期间有效
+#[tauri::command]
+pub async fn gxt_reference_attach(
+    doc_id: DocId,
+    path: String,
+    session: tauri::State<'_, Session>,
+) -> Result<ReferenceInfo, String> {
+    let entries = gxt::load_document(path.clone()).await?.entries;
+    let reference = ReferenceFile {
+        path,
+        values: entries.into_iter().map(|e| (e.key, e.value)).collect(),
+    };
+    session.with(doc_id, |open| {
+        let keys = open.doc.entries.iter().map(|e| e.key.clone());
+        let info = info(&reference, keys);
+        open.reference = Some(reference);
+        Ok(info)
+    })
+}
+
+/// 取下参考文件
+#[tauri::command]
+pub fn gxt_reference_detach(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<(), String> {
+    session.with(doc_id, |open| {
+        open.reference = None;
+        Ok(())
+    })
+}
+
+/// 当前挂着的参考文件；没有时为 None
+#[tauri::command]
+pub fn gxt_reference_info(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Option<ReferenceInfo>, String> {
+    session.with(doc_id, |open| {
+        Ok(open
+            .reference
+            .as_ref()
+            .map(|r| info(r, open.doc.entries.iter().map(|e| e.key.clone()))))
+    })
+}
+
+/// 一批 key 在参考文件里的值（编辑区旁边显示原文用）；参考文件里没有的 key 不出现在结果里。
+/// 没挂参考文件时为空
+#[tauri::command]
+pub fn gxt_reference_values(
+    doc_id: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<BTreeMap<String, String>, String> {
+    session.with(doc_id, |open| {
+        let Some(reference) = &open.reference else {
+            return Ok(BTreeMap::new());
+        };
+        Ok(keys
+            .into_iter()
+            .filter_map(|k| {
+                let value = reference.values.get(&k)?.to_string();
+                Some((k, value))
+            })
+            .collect())
+    })
+}
@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use tauri::AppHandle;
+
+use crate::project::Projects;
+use crate::rollback::{self, Target};
+use crate::session::{DocId, Session};
+
+/// 一条上次保存后的改动
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnsavedChange {
+    pub key: String,
+    /// 上次保存时的值；之后才加的为 None
+    pub saved: Option<String>,
+    /// 现在的值；之后删掉的为 None
+    pub current: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RevertReport {
+    /// 恢复成上次保存时的值（含加回来的）
+    pub reverted: Vec<String>,
+    /// 上次保存后才加的，已删掉
+    pub removed: Vec<String>,
+    /// 锁定的或项目保护的，没有动
+    pub kept: Vec<String>,
+}
+
+/// 上次保存（或打开）之后改过、加上、删掉的条目；不看撤销历史
+#[tauri::command]
+pub fn gxt_unsaved_changes(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<UnsavedChange>, String> {
+    session.with(doc_id, |open| {
+        Ok(open
+            .unsaved_keys()
+            .into_iter()
+            .map(|key| UnsavedChange {
+                saved: open.saved_value(&key).map(str::to_string),
+                current: open
+                    .doc
+                    .entries
+                    .iter()
+                    .find(|e| e.key == key)
+                    .map(|e| e.value.to_string()),
+                key,
+            })
+            .collect())
+    })
+}
+
+/// 把一批 key 恢复成上次保存时的值，只放弃这几条的试验性修改（整批算一步撤销）。
+/// 保存后才加的 key 删掉；没改过的 key 不动
+#[tauri::command]
+pub fn gxt_revert_entries(
+    app: AppHandle,
+    doc_id: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<RevertReport, String> {
+    let changed: Vec<String> = session.edit(doc_id, |open| {
+        let unsaved = open.unsaved_keys();
+        Ok(keys.into_iter().filter(|k| unsaved.contains(k)).collect())
+    })?;
+    if changed.is_empty() {
+        return Ok(RevertReport::default());
+    }
+
+    rollback::backup_before(&app, "revert-entries", &[Target::Document(doc_id)])?;
+    session.edit(doc_id, |open| {
+        let own_batch = !open.in_batch();
+        if own_batch {
+            open.begin_batch()?;
+        }
+        let mut report = RevertReport::default();
+        for key in changed {
+            if open.is_locked(&key) {
+                report.kept.push(key);
+                continue;
+            }
+            match open.saved_value(&key).map(str::to_string) {
+                Some(value) => {
+                    open.set_entry(key.clone(), value);
+                    report.reverted.push(key);
+                }
+                None => {
+                    let protected = projects
+                        .check_removal(open.doc.file_path.as_deref(), &key, false)
+                        .is_err();
+                    if protected || !open.remove_entry(&key) {
+                        report.kept.push(key);
+                    } else {
+                        report.removed.push(key);
+                    }
+                }
+            }
+        }
+        if own_batch {
+            open.commit_batch()?;
+        }
+        Ok(report)
+    })
+}
@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+use crate::meta::{self, Approval, EntryStamp};
+use crate::session::{DocId, OpenDocument, Session};
+
+/// 一条的指派与确认。两人审校：译者改完，由（指派的）另一个人签字确认，确认记在附加信息文件里；
+/// 确认之后再改过的条目不再算确认，并出现在 gxt_review_changed 的报告里
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewRow {
+    pub key: String,
+    pub reviewer: Option<String>,
+    pub approval: Option<Approval>,
+    /// 确认过且之后没再改
+    pub approved: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RejectedApproval {
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApproveReport {
+    pub approved: Vec<String>,
+    pub rejected: Vec<RejectedApproval>,
+}
+
+/// 确认之后又改过的条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedAfterApproval {
+    pub key: String,
+    pub approval: Approval,
+    pub value: String,
+    /// 最后一次修改（在编辑器外改的没有记录）
+    pub modified: Option<EntryStamp>,
+}
+
+/// 把指派与确认写回文档旁边的附加信息文件（未保存过的文档先只留在内存里）
+fn store_review(open: &OpenDocument) -> Result<(), String> {
+    match &open.doc.file_path {
+        Some(p) => {
+            let mut m = meta::load(p);
+            m.reviewers = open.reviewers.clone();
+            m.approvals = open.approvals.clone();
+            meta::save(p, &m)
+        }
+        None => Ok(()),
+    }
+}
+
+fn value_of<'a>(open: &'a OpenDocument, key: &str) -> Option<&'a str> {
+    open.doc
+        .entries
+        .iter()
+        .find(|e| e.key == key)
+        .map(|e| &*e.value)
+}
+
+/// 把一批 key 指派给审校人（reviewer 为 None 时取消指派）
+#[tauri::command]
+pub fn gxt_review_assign(
+    doc_id: DocId,
+    keys: Vec<String>,
+    reviewer: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<(), String> {
+    let reviewer = reviewer.filter(|r| !r.trim().is_empty());
+    session.with(doc_id, |open| {
+        for k in keys {
+            match &reviewer {
+                Some(r) => open.reviewers.insert(k, r.clone()),
+                None => open.reviewers.remove(&k),
+            };
+        }
+        store_review(open)
+    })
+}
+
+/// 以设置里的作者名确认一批条目。最后一次修改是自己做的、或指派给了别人的条目不能确认
+#[tauri::command]
+pub fn gxt_review_approve(
+    doc_id: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<ApproveReport, String> {
+    let by = meta::current_author().ok_or_else(|| tr!("review.no_author"))?;
+    let at = meta::now_secs();
+    session.with(doc_id, |open| {
+        let mut report = ApproveReport::default();
+        for key in keys {
+            let reason = match value_of(open, &key) {
+                None => Some(tr!("review.no_key")),
+                Some("") => Some(tr!("review.empty")),
+                Some(_) => match (open.reviewers.get(&key), open.stamps.get(&key)) {
+                    (Some(r), _) if *r != by => Some(tr!("review.not_assignee", reviewer = r)),
+                    (_, Some(st)) if st.author.as_deref() == Some(by.as_str()) => {
+                        Some(tr!("review.own_edit", author = by))
+                    }
+                    _ => None,
+                },
+            };
+            if let Some(reason) = reason {
+                report.rejected.push(RejectedApproval { key, reason });
+                continue;
+            }
+            let value = value_of(open, &key).unwrap_or_default().to_string();
+            open.approvals.insert(
+                key.clone(),
+                Approval {
+                    by: by.clone(),
+                    at,
+                    value,
+                },
+            );
+            report.approved.push(key);
+        }
+        store_review(open)?;
+        Ok(report)
+    })
+}
+
+/// 撤销确认
+#[tauri::command]
+pub fn gxt_review_revoke(
+    doc_id: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<(), String> {
+    session.with(doc_id, |open| {
+        for k in &keys {
+            open.approvals.remove(k);
+        }
+        store_review(open)
+    })
+}
+
+/// 有指派或确认记录的条目（按文档顺序）；reviewer 给了时只要指派给这个人的
+#[tauri::command]
+pub fn gxt_review_list(
+    doc_id: DocId,
+    reviewer: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<ReviewRow>, String> {
+    session.with(doc_id, |open| {
+        Ok(open
+            .doc
+            .entries
+            .iter()
+            .filter(|e| open.reviewers.contains_key(&e.key) || open.approvals.contains_key(&e.key))
+            .filter(|e| reviewer.is_none() || open.reviewers.get(&e.key) == reviewer.as_ref())
+            .map(|e| {
+                let approval = open.approvals.get(&e.key).cloned();
+                ReviewRow {
+                    key: e.key.clone(),
+                    reviewer: open.reviewers.get(&e.key).cloned(),
+                    approved: approval.as_ref().is_some_and(|a| a.value == *e.value),
+                    approval,
+                }
+            })
+            .collect())
+    })
+}
+
+/// 确认之后又改过的条目（按文档顺序）：需要重新确认
+#[tauri::command]
+pub fn gxt_review_changed(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<ChangedAfterApproval>, String> {
+    session.with(doc_id, |open| {
+        Ok(open
+            .doc
+            .entries
+            .iter()
+            .filter_map(|e| {
+                let approval = open.approvals.get(&e.key)?;
+                (approval.value != *e.value).then(|| ChangedAfterApproval {
+                    key: e.key.clone(),
+                    approval: approval.clone(),
+                    value: e.value.to_string(),
+                    modified: open.stamps.get(&e.key).cloned(),
+                })
+            })
+            .collect())
+    })
+}
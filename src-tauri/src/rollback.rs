@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager};
+
+use crate::gxt::{self, GxtEntry};
+use crate::i18n::tr;
+use crate::meta;
+use crate::session::{DocId, Session};
+use crate::snapshots;
+
+const RECOVERY_DIR: &str = "recovery";
+/// 只留最近一次批量操作之前的内容
+const LAST_BATCH_FILE: &str = "last_batch.json";
+
+/// 批量操作要改的东西
+pub enum Target<'a> {
+    /// 打开着的文档（备份内存里的内容，含未保存的修改）
+    Document(DocId),
+    /// 磁盘上的文件；已在编辑器里打开时按打开的文档处理
+    File(&'a str),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupItem {
+    /// 备份时打开着的文档；撤销时写回这个文档，否则写回 file_path
+    doc_id: Option<DocId>,
+    file_path: Option<String>,
+    entries: Vec<GxtEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LastBatch {
+    label: String,
+    /// Unix 秒
+    created: u64,
+    /// 文档 id 只在同一个进程里有效
+    pid: u32,
+    items: Vec<BackupItem>,
+}
+
+/// 最近一次批量操作（给前端显示“撤销 xxx”）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LastBatchInfo {
+    pub label: String,
+    pub created: u64,
+    /// 涉及的文件（未保存过的文档为 None）
+    pub files: Vec<Option<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RevertReport {
+    pub label: String,
+    /// 恢复了的文件（未保存过的文档为 None）
+    pub restored: Vec<Option<String>>,
+    /// 备份之后已经关掉、没法恢复的文档
+    pub skipped: Vec<Option<String>>,
+}
+
+fn backup_path(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|d| d.join(RECOVERY_DIR).join(LAST_BATCH_FILE))
+        .map_err(|e| format!("Resolve app data dir failed: {e}"))
+}
+
+fn read_last(app: &AppHandle) -> Result<Option<LastBatch>, String> {
+    let Ok(bytes) = fs::read(backup_path(app)?) else {
+        return Ok(None);
+    };
+    serde_json::from_slice(&bytes)
+        .map(Some)
+        .map_err(|e| tr!("json.decode_failed", error = e))
+}
+
+fn backup_item(session: &Session, target: &Target) -> Result<BackupItem, String> {
+    let doc_id = match target {
+        Target::Document(id) => Some(*id),
+        Target::File(path) => session
+            .list()
+            .into_iter()
+            .find(|d| d.file_path.as_deref() == Some(*path))
+            .map(|d| d.doc_id),
+    };
+    match (doc_id, target) {
+        (Some(id), _) => session.edit(id, |open| {
+            Ok(BackupItem {
+                doc_id: Some(id),
+                file_path: open.doc.file_path.clone(),
+                entries: open.doc.entries.clone(),
+            })
+        }),
+        (None, Target::File(path)) => {
+            let bytes = fs::read(path).map_err(|e| tr!("file.read_failed", error = e))?;
+            Ok(BackupItem {
+                doc_id: None,
+                file_path: Some(path.to_string()),
+                entries: gxt::parse_gxt_bytes(&bytes)?,
+            })
+        }
+        (None, Target::Document(_)) => unreachable!("document targets always have an id"),
+    }
+}
+
+/// 批量操作（项目范围替换、合并、转换）开始前调用：把要改的文档备份到恢复区，
+/// 取代上一次的备份。备份失败时不做这次操作
+pub fn backup_before(app: &AppHandle, label: &str, targets: &[Target]) -> Result<(), String> {
+    let session = app.state::<Session>();
+    let items = targets
+        .iter()
+        .map(|t| backup_item(&session, t))
+        .collect::<Result<Vec<_>, String>>()?;
+    let batch = LastBatch {
+        label: label.to_string(),
+        created: meta::now_secs(),
+        pid: std::process::id(),
+        items,
+    };
+    let path = backup_path(app)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| tr!("file.write_failed", error = e))?;
+    }
+    let bytes = serde_json::to_vec(&batch).map_err(|e| tr!("json.encode_failed", error = e))?;
+    fs::write(&path, bytes).map_err(|e| tr!("file.write_failed", error = e))?;
+    tracing::info!(
+        label,
+        documents = batch.items.len(),
+        "backed up before batch operation"
+    );
+    Ok(())
+}
+
+/// 前端自己逐个文档做的批量操作（如项目范围替换）开始前调用
+#[tauri::command]
+pub fn gxt_backup_before_batch(
+    app: AppHandle,
+    label: String,
+    doc_ids: Vec<DocId>,
+    paths: Option<Vec<String>>,
+) -> Result<(), String> {
+    let paths = paths.unwrap_or_default();
+    let targets: Vec<Target> = doc_ids
+        .into_iter()
+        .map(Target::Document)
+        .chain(paths.iter().map(|p| Target::File(p)))
+        .collect();
+    backup_before(&app, &label, &targets)
+}
+
+/// 最近一次可以撤销的批量操作；没有时为 None
+#[tauri::command]
+pub fn gxt_last_batch(app: AppHandle) -> Result<Option<LastBatchInfo>, String> {
+    Ok(read_last(&app)?.map(|b| LastBatchInfo {
+        label: b.label,
+        created: b.created,
+        files: b.items.into_iter().map(|i| i.file_path).collect(),
+    }))
+}
+
+/// 撤销最近一次批量操作：打开着的文档换回备份的内容（变为未保存，锁定的条目不变），
+/// 没打开的文件直接写回。与编辑器的撤销步数无关；撤销后备份删除
+#[tauri::command]
+pub fn gxt_revert_last_batch(app: AppHandle) -> Result<RevertReport, String> {
+    let batch = read_last(&app)?.ok_or_else(|| tr!("rollback.none"))?;
+    let session = app.state::<Session>();
+    let same_process = batch.pid == std::process::id();
+    let mut report = RevertReport {
+        label: batch.label,
+        ..Default::default()
+    };
+    for item in batch.items {
+        let open = item.doc_id.filter(|_| same_process).and_then(|id| {
+            session
+                .list()
+                .into_iter()
+                .any(|d| d.doc_id == id)
+                .then_some(id)
+        });
+        match (open, &item.file_path) {
+            (Some(id), _) => {
+                session.edit(id, |open| {
+                    let mut doc = open.doc.clone();
+                    doc.entries = item.entries;
+                    open.replace_entries(doc);
+                    Ok(())
+                })?;
+                report.restored.push(item.file_path);
+            }
+            (None, Some(path)) if item.doc_id.is_none() => {
+                let bytes = gxt::build_gxt_bytes(&item.entries)?;
+                fs::write(path, bytes).map_err(|e| tr!("file.write_failed", error = e))?;
+                snapshots::record(&app, path);
+                report.restored.push(item.file_path);
+            }
+            _ => report.skipped.push(item.file_path),
+        }
+    }
+    let _ = fs::remove_file(backup_path(&app)?);
+    tracing::info!(
+        label = %report.label,
+        restored = report.restored.len(),
+        skipped = report.skipped.len(),
+        "reverted batch operation"
+    );
+    Ok(report)
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+
+use gxt_core::GroupLevel;
+
+use crate::meta;
+use crate::page::{self, EntryFilter, EntryRow, RowInfo};
+use crate::project::Projects;
+use crate::session::{DocId, Session};
+
+/// 抽样的条件：先按 filter（状态等）和表筛选，再从剩下的里抽
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SampleQuery {
+    /// 抽几条；符合条件的不够时全部给出
+    pub count: usize,
+    /// 同一份文档、同样的条件和 seed 抽出的总是同一批；为空时随机取一个，结果里带回
+    pub seed: Option<u64>,
+    pub filter: EntryFilter,
+    /// 只从这几张表（见 gxt_table_stats）里抽；为空不限制
+    pub tables: Vec<String>,
+    /// 分表方式，缺省为到第一个 `_` 为止
+    pub level: Option<GroupLevel>,
+    /// 判断“未翻译”用的原文文件（同 gxt_entries_page）
+    pub source_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EntrySample {
+    /// 这次用的 seed，填回 SampleQuery 可以重现
+    pub seed: u64,
+    /// 符合条件的条数（抽样的总体）
+    pub total: usize,
+    /// 抽到的条目，按文档顺序
+    pub rows: Vec<EntryRow>,
+}
+
+/// splitmix64：够均匀、可重现，不为抽样引入随机数库
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// [0, n) 里的一个数
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// 从 rows 里不放回地抽 count 条（部分 Fisher-Yates），再按文档顺序排好
+fn pick<'a>(mut rows: Vec<RowInfo<'a>>, count: usize, seed: u64) -> Vec<RowInfo<'a>> {
+    let mut rng = SplitMix(seed);
+    let count = count.min(rows.len());
+    for i in 0..count {
+        let j = i + rng.below(rows.len() - i);
+        rows.swap(i, j);
+    }
+    rows.truncate(count);
+    rows.sort_by_key(|r| r.index);
+    rows
+}
+
+/// 给审校抽查用：按状态、表等条件筛选后随机抽 count 条。给同样的 seed 抽出的总是同一批，
+/// 几个人可以核对同一份样本，也方便复查
+#[tauri::command]
+pub async fn gxt_sample(
+    doc_id: DocId,
+    query: SampleQuery,
+    session: tauri::State<'_, Session>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<EntrySample, String> {
+    let source =
+        page::status_source(&session, &projects, doc_id, query.source_path.as_deref()).await?;
+    let source: HashMap<&str, &str> = source.iter().map(|e| (e.key.as_str(), &*e.value)).collect();
+    let seed = query
+        .seed
+        .unwrap_or_else(|| meta::now_secs() ^ u64::from(std::process::id()));
+
+    session.with(doc_id, |open| {
+        let in_tables: Option<HashSet<String>> = (!query.tables.is_empty()).then(|| {
+            let level = query.level.clone().unwrap_or(GroupLevel::Until('_'));
+            gxt_core::split_tables(&open.doc.entries, &level)
+                .into_iter()
+                .filter(|(name, _)| query.tables.contains(name))
+                .flat_map(|(_, entries)| entries.into_iter().map(|e| e.key))
+                .collect()
+        });
+        let rows: Vec<RowInfo> = open
+            .doc
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| in_tables.as_ref().is_none_or(|t| t.contains(&e.key)))
+            .map(|(index, e)| page::row_info(open, &source, index, e))
+            .filter(|r| query.filter.matches(r))
+            .collect();
+        let total = rows.len();
+        Ok(EntrySample {
+            seed,
+            total,
+            rows: pick(rows, query.count, seed)
+                .iter()
+                .map(|r| page::entry_row(open, r))
+                .collect(),
+        })
+    })
+}
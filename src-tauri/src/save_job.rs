@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::doc_profile;
+use crate::encode_cache::EncodeCache;
+use crate::escapes;
+use crate::gxt::{self, GxtDocument, SaveResult, SavedFormat};
+use crate::hooks;
+use crate::i18n::tr;
+use crate::mirrors;
+use crate::presets;
+use crate::translit;
+
+pub const EVENT_SAVE_PROGRESS: &str = "gxt://save-progress";
+pub const EVENT_SAVE_FINISHED: &str = "gxt://save-finished";
+
+/// 写盘时每块大小；每写完一块发一次进度
+const WRITE_CHUNK: usize = 256 * 1024;
+
+/// 编码阶段每隔多少条发一次进度（每条都发会把前端淹没）
+const PROGRESS_EVERY: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveProgress {
+    pub job_id: String,
+    pub entries_encoded: usize,
+    pub total_entries: usize,
+    pub bytes_written: usize,
+    /// 编码完成前未知
+    pub total_bytes: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveFinished {
+    pub job_id: String,
+    pub result: Option<SaveResult>,
+    pub error: Option<String>,
+    pub cancelled: bool,
+}
+
+/// 正在进行的后台保存：job_id -> 取消标记
+#[derive(Debug, Default)]
+pub struct SaveJobs {
+    flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl SaveJobs {
+    fn register(&self, job_id: &str) -> Result<Arc<AtomicBool>, String> {
+        let mut m = self.flags.lock().map_err(|_| "Save job lock poisoned".to_string())?;
+        if m.contains_key(job_id) {
+            return Err(format!("Save job already running: {job_id}"));
+        }
+        let flag = Arc::new(AtomicBool::new(false));
+        m.insert(job_id.to_string(), flag.clone());
+        Ok(flag)
+    }
+
+    fn finish(&self, job_id: &str) {
+        if let Ok(mut m) = self.flags.lock() {
+            m.remove(job_id);
+        }
+    }
+
+    fn cancel(&self, job_id: &str) -> bool {
+        match self.flags.lock().ok().and_then(|m| m.get(job_id).cloned()) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+const CANCELLED: &str = "Save cancelled";
+
+/// 后台保存：立即返回，进度通过 gxt://save-progress 推送，
+/// 结束（成功/失败/取消）时推送一次 gxt://save-finished。
+/// 先写到同目录临时文件再改名，取消或失败都不会破坏原文件。
+#[tauri::command]
+pub fn gxt_save_background(
+    app: AppHandle,
+    job_id: String,
+    mut doc: GxtDocument,
+    jobs: tauri::State<'_, SaveJobs>,
+) -> Result<(), String> {
+    gxt::check_writable(&app, &doc)?;
+    escapes::check_for_save(&app, &doc)?;
+    let sorted = presets::sort_for_save(&app, &mut doc);
+    let (written, transliterated) = translit::for_save(&app, &doc);
+    gxt::validate_entries(&written.entries)?;
+    let path = doc.file_path.clone().ok_or_else(|| tr!("file.no_path"))?;
+    if doc.embedded.is_some() {
+        return Err("Embedded GXT sections can only be saved with gxt_save".to_string());
+    }
+
+    let cancel = jobs.register(&job_id)?;
+    let trashed = gxt::replaced_target(&app, &doc);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let outcome = run_save(&app, &job_id, &path, &written, trashed.as_deref(), &cancel);
+        app.state::<SaveJobs>().finish(&job_id);
+        let entry_count = doc.entries.len();
+        match &outcome {
+            Ok(_) => {
+                tracing::info!(path = %path, entries = doc.entries.len(), "saved in background")
+            }
+            Err(e) => tracing::warn!(path = %path, error = %e, "background save failed"),
+        }
+        let mirrors = if outcome.is_ok() {
+            let written = mirrors::write_mirrors(&app, &doc);
+            hooks::run_post_save(&app, &path);
+            written
+        } else {
+            Vec::new()
+        };
+
+        let finished = match outcome {
+            Ok((bytes_written, sha256)) => match gxt::sync_saved(&app, doc) {
+                Ok(doc_id) => SaveFinished {
+                    job_id,
+                    result: Some(SaveResult {
+                        file_path: Some(path),
+                        doc_id: Some(doc_id),
+                        embedded: None,
+                        sorted,
+                        transliterated,
+                        trashed,
+                        bytes_written,
+                        sha256,
+                        entry_count,
+                        format: SavedFormat::Gxt,
+                        mirrors,
+                    }),
+                    error: None,
+                    cancelled: false,
+                },
+                Err(e) => SaveFinished {
+                    job_id,
+                    result: None,
+                    error: Some(e),
+                    cancelled: false,
+                },
+            },
+            Err(e) => SaveFinished {
+                job_id,
+                result: None,
+                cancelled: e == CANCELLED,
+                error: Some(e),
+            },
+        };
+        let _ = app.emit(EVENT_SAVE_FINISHED, finished);
+    });
+
+    Ok(())
+}
+
+/// 请求取消；返回该 job 是否仍在进行
+#[tauri::command]
+pub fn gxt_cancel_save(job_id: String, jobs: tauri::State<'_, SaveJobs>) -> bool {
+    jobs.cancel(&job_id)
+}
+
+/// 返回写出的字节数与 SHA-256
+fn run_save(
+    app: &AppHandle,
+    job_id: &str,
+    path: &str,
+    doc: &GxtDocument,
+    replaced: Option<&str>,
+    cancel: &AtomicBool,
+) -> Result<(usize, String), String> {
+    let total_entries = doc.entries.len();
+    let progress = |entries_encoded: usize, bytes_written: usize, total_bytes: Option<usize>| {
+        let _ = app.emit(
+            EVENT_SAVE_PROGRESS,
+            SaveProgress {
+                job_id: job_id.to_string(),
+                entries_encoded,
+                total_entries,
+                bytes_written,
+                total_bytes,
+            },
+        );
+    };
+
+    // 编码
+    let cache = app.state::<EncodeCache>();
+    let encoded = doc_profile::encode_entries(path, &doc.entries);
+    let entries = encoded.as_deref().unwrap_or(&doc.entries);
+    let mut value_cache = cache.take(path);
+    let built = gxt::build_gxt_bytes_with(entries, &mut value_cache, &mut |n| {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(CANCELLED.to_string());
+        }
+        if n % PROGRESS_EVERY == 0 {
+            progress(n, 0, None);
+        }
+        Ok(())
+    });
+    cache.put_back(path.to_string(), value_cache);
+    let bytes = built?;
+    progress(total_entries, 0, Some(bytes.len()));
+
+    // 写临时文件
+    let final_path = PathBuf::from(path);
+    let tmp_path = final_path.with_extension("gxt.saving");
+    let write = || -> Result<(), String> {
+        let mut f = fs::File::create(&tmp_path).map_err(|e| tr!("file.write_failed", error = e))?;
+        let mut written = 0usize;
+        for chunk in bytes.chunks(WRITE_CHUNK) {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(CANCELLED.to_string());
+            }
+            f.write_all(chunk).map_err(|e| tr!("file.write_failed", error = e))?;
+            written += chunk.len();
+            progress(total_entries, written, Some(bytes.len()));
+        }
+        f.sync_all().map_err(|e| tr!("file.write_failed", error = e))?;
+        Ok(())
+    };
+
+    if let Err(e) = write() {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e);
+    }
+    // 临时文件写完了才把要覆盖的旧文件移进回收站
+    if let Some(old) = replaced {
+        if let Err(e) = gxt::trash_file(old) {
+            let _ = fs::remove_file(&tmp_path);
+            return Err(e);
+        }
+    }
+
+    fs::rename(&tmp_path, &final_path).map_err(|e| {
+        let _ = fs::remove_file(&tmp_path);
+        format!("Replace file failed: {e}")
+    })?;
+    Ok(gxt::file_digest(&bytes))
+}
@@ -0,0 +1,429 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gxt_core::{RefAt, ScriptContext};
+
+use crate::i18n::tr;
+use crate::meta;
+use crate::project::Projects;
+use crate::session::{DocId, Session};
+
+/// 给的是目录时扫描其中这些扩展名的文件（不递归）
+const SCRIPT_EXTS: [&str; 5] = ["scm", "cs", "cm", "txt", "s"];
+/// 脚本里的文本标签最多这么长
+const MAX_LABEL_LEN: usize = 7;
+/// gxt_import_source_refs 没给 patterns 时扫描的文件
+const SOURCE_PATTERNS: [&str; 12] = [
+    "*.txt", "*.scm", "*.sc", "*.cs", "*.lua", "*.cpp", "*.c", "*.h", "*.hpp", "*.ini", "*.dat",
+    "*.cfg",
+];
+/// 比这大的文件不扫（多半是资源文件）
+const MAX_SOURCE_BYTES: u64 = 8 * 1024 * 1024;
+/// 每个 key 默认最多记这么多处（只记数的不限）
+const DEFAULT_REFS_PER_KEY: usize = 20;
+/// 记下的那一行最多这么多字符
+const MAX_SNIPPET_CHARS: usize = 160;
+
+/// 脚本里引用了、文档里却没有的 key（同一个 key 只记第一次出现的位置）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissingKey {
+    pub key: String,
+    pub file: String,
+    pub at: RefAt,
+    /// 在所有脚本里出现的次数
+    pub count: usize,
+}
+
+/// 脚本里的一处 key 引用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRef {
+    pub file: String,
+    pub at: RefAt,
+}
+
+/// 改 key 名后脚本里要跟着改的地方
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenameRefs {
+    /// 引用旧 key 的位置：源码按行号，编译好的脚本按字节偏移（要重新编译或手工改）
+    pub refs: Vec<ScriptRef>,
+    /// 源码文件的改动（unified diff，零上下文，`git apply --unidiff-zero` 可用）；没有时为空
+    pub patch: String,
+    /// 新 key 超过脚本标签的长度，脚本里引用不了
+    pub too_long: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContextImport {
+    /// 找到了上下文的 key 数
+    pub keys: usize,
+    /// 显示文字的地方的总数
+    pub refs: usize,
+    /// 脚本里显示了、文档里却没有的 key（不记录）
+    pub unknown_keys: Vec<String>,
+}
+
+/// mod 源码里出现某个 key 的一行（见 gxt_import_source_refs）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceRef {
+    /// 相对于扫描的根目录，用 `/` 分隔
+    pub file: String,
+    /// 行号（从 1 开始）
+    pub line: usize,
+    /// 这一行（去掉首尾空白，太长的截断）
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SourceRefImport {
+    /// 扫描了的文件数
+    pub files: usize,
+    /// 找到了引用的 key 数
+    pub keys: usize,
+    /// 引用的总数（含超过 max_per_key 没记下的）
+    pub refs: usize,
+}
+
+fn script_files(paths: &[String]) -> Result<Vec<String>, String> {
+    let mut out = Vec::new();
+    for p in paths {
+        let path = Path::new(p);
+        if !path.is_dir() {
+            out.push(p.clone());
+            continue;
+        }
+        let read = fs::read_dir(path).map_err(|e| tr!("file.read_failed", error = e))?;
+        let mut found: Vec<String> = read
+            .filter_map(|item| item.ok().map(|i| i.path()))
+            .filter(|f| {
+                f.is_file()
+                    && f.extension()
+                        .and_then(|e| e.to_str())
+                        .is_some_and(|e| SCRIPT_EXTS.contains(&e.to_ascii_lowercase().as_str()))
+            })
+            .map(|f| f.to_string_lossy().into_owned())
+            .collect();
+        found.sort();
+        out.extend(found);
+    }
+    Ok(out)
+}
+
+/// 扫描脚本，找出文档里没有的 key（按第一次出现的顺序）
+fn missing_keys(paths: &[String], existing: &HashSet<String>) -> Result<Vec<MissingKey>, String> {
+    let mut out: Vec<MissingKey> = Vec::new();
+    for file in script_files(paths)? {
+        let bytes = fs::read(&file).map_err(|e| tr!("file.read_failed", error = e))?;
+        for r in gxt_core::scan_script(&bytes) {
+            if existing.contains(&r.key) {
+                continue;
+            }
+            match out.iter_mut().find(|m| m.key == r.key) {
+                Some(m) => m.count += 1,
+                None => out.push(MissingKey {
+                    key: r.key,
+                    file: file.clone(),
+                    at: r.at,
+                    count: 1,
+                }),
+            }
+        }
+    }
+    Ok(out)
+}
+
+fn existing_keys(session: &Session, doc_id: DocId) -> Result<HashSet<String>, String> {
+    session.with(doc_id, |open| {
+        Ok(open.doc.entries.iter().map(|e| e.key.clone()).collect())
+    })
+}
+
+/// main.scm / CLEO 脚本（或 Sanny Builder 源码）里引用了、文档里却没有的 key。
+/// paths 可以是文件，也可以是目录（如 CLEO 文件夹）
+#[tauri::command]
+pub fn gxt_script_missing_keys(
+    doc_id: DocId,
+    paths: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<MissingKey>, String> {
+    missing_keys(&paths, &existing_keys(&session, doc_id)?)
+}
+
+/// 给脚本里引用了、文档里没有的 key 建占位条目，让写脚本的人从完整的骨架开始。
+/// keys 给了时只建其中的；value 为空时条目算未翻译，不为空（如 `TODO`）时标成 fuzzy。
+/// 软删除过的 key 不建。返回新建的 key
+#[tauri::command]
+pub fn gxt_scaffold_from_scripts(
+    doc_id: DocId,
+    paths: Vec<String>,
+    keys: Option<Vec<String>>,
+    value: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    let missing = missing_keys(&paths, &existing_keys(&session, doc_id)?)?;
+    let value = value.unwrap_or_default();
+    session.edit(doc_id, |open| {
+        let mut added = Vec::new();
+        for m in missing {
+            if keys.as_ref().is_some_and(|k| !k.contains(&m.key)) {
+                continue;
+            }
+            if !open.merge_entry(m.key.clone(), value.clone()) {
+                continue;
+            }
+            if !value.is_empty() {
+                open.fuzzy.insert(m.key.clone());
+            }
+            added.push(m.key);
+        }
+        Ok(added)
+    })
+}
+
+/// 源码文件改名前后的逐行差别，写成零上下文的 unified diff
+fn push_patch(patch: &mut String, file: &str, before: &str, after: &str, lines: &[usize]) {
+    if lines.is_empty() {
+        return;
+    }
+    patch.push_str(&format!("--- {file}\n+++ {file}\n"));
+    let pairs: Vec<(&str, &str)> = before
+        .split_inclusive('\n')
+        .zip(after.split_inclusive('\n'))
+        .collect();
+    for &n in lines {
+        let Some((old, new)) = pairs.get(n - 1) else {
+            continue;
+        };
+        patch.push_str(&format!("@@ -{n} +{n} @@\n"));
+        for (sign, line) in [('-', old), ('+', new)] {
+            patch.push(sign);
+            patch.push_str(line);
+            if !line.ends_with('\n') {
+                patch.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+}
+
+/// 改 key 名前调用：在脚本里找旧 key 的引用，给出位置与源码的补丁，免得改名后游戏里缺字。
+/// paths 没给时用项目设置里的脚本目录
+#[tauri::command]
+pub fn gxt_rename_key_refs(
+    old: String,
+    new: String,
+    paths: Option<Vec<String>>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<RenameRefs, String> {
+    let paths = match paths {
+        Some(p) => p,
+        None => projects.current()?.scripts_paths,
+    };
+    if paths.is_empty() {
+        return Err(tr!("scripts.none"));
+    }
+    let mut out = RenameRefs {
+        too_long: new.len() > MAX_LABEL_LEN,
+        ..RenameRefs::default()
+    };
+    for file in script_files(&paths)? {
+        let bytes = fs::read(&file).map_err(|e| tr!("file.read_failed", error = e))?;
+        out.refs.extend(
+            gxt_core::scan_script(&bytes)
+                .into_iter()
+                .filter(|r| r.key == old)
+                .map(|r| ScriptRef {
+                    file: file.clone(),
+                    at: r.at,
+                }),
+        );
+        // 与 scan_script 的判断一致：能按 UTF-8 读且没有 0 字节的是源码
+        if let Ok(text) = std::str::from_utf8(&bytes) {
+            if !bytes.contains(&0) {
+                let (lines, renamed) = gxt_core::rename_in_source(text, &old, &new);
+                push_patch(&mut out.patch, &file, text, &renamed, &lines);
+            }
+        }
+    }
+    Ok(out)
+}
+
+/// 从 Sanny Builder 反编译的 main.scm 文本导入上下文：每个 key 在哪个任务、由哪条指令显示。
+/// 替换文档原有的上下文，写进附加信息文件（文档要先保存过）
+#[tauri::command]
+pub fn gxt_import_script_context(
+    doc_id: DocId,
+    path: String,
+    session: tauri::State<'_, Session>,
+) -> Result<ContextImport, String> {
+    let bytes = fs::read(&path).map_err(|e| tr!("file.read_failed", error = e))?;
+    let found = gxt_core::scan_decompiled(&String::from_utf8_lossy(&bytes));
+    let file_path = session
+        .with(doc_id, |open| Ok(open.doc.file_path.clone()))?
+        .ok_or_else(|| tr!("file.no_path"))?;
+    let existing = existing_keys(&session, doc_id)?;
+    let mut report = ContextImport::default();
+    let mut contexts: BTreeMap<String, Vec<ScriptContext>> = BTreeMap::new();
+    for (key, ctx) in found {
+        if !existing.contains(&key) {
+            if !report.unknown_keys.contains(&key) {
+                report.unknown_keys.push(key);
+            }
+            continue;
+        }
+        report.refs += 1;
+        contexts.entry(key).or_default().push(ctx);
+    }
+    report.keys = contexts.len();
+    let mut m = meta::load(&file_path);
+    m.contexts = contexts;
+    meta::save(&file_path, &m)?;
+    Ok(report)
+}
+
+/// 一条的脚本上下文（没导入过时为空）
+#[tauri::command]
+pub fn gxt_script_context(
+    doc_id: DocId,
+    key: String,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<ScriptContext>, String> {
+    let file_path = session.with(doc_id, |open| Ok(open.doc.file_path.clone()))?;
+    Ok(file_path
+        .and_then(|p| meta::load(&p).contexts.remove(&key))
+        .unwrap_or_default())
+}
+
+/// root 下相对路径匹配 patterns（`*` 也跨目录，不分大小写）的文本文件；跳过以 `.` 开头的目录
+fn source_files(root: &Path, dir: &Path, patterns: &[String], out: &mut Vec<PathBuf>) {
+    let Ok(read) = fs::read_dir(dir) else {
+        return;
+    };
+    for item in read.flatten() {
+        let path = item.path();
+        let name = item.file_name();
+        if path.is_dir() {
+            if !name.to_string_lossy().starts_with('.') {
+                source_files(root, &path, patterns, out);
+            }
+            continue;
+        }
+        let rel = relative(root, &path);
+        let small = item.metadata().is_ok_and(|m| m.len() <= MAX_SOURCE_BYTES);
+        if small && patterns.iter().any(|p| gxt_core::matches_pattern(p, &rel)) {
+            out.push(path);
+        }
+    }
+}
+
+fn relative(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// 一行里出现的 key：按字母、数字、`_` 以外的字符切开后逐段查（所以 `'MIS_1'`、`"MIS_1"`、
+/// `MIS_1,` 都算，`MIS_10` 不算 `MIS_1`）
+fn keys_in_line<'a>(line: &'a str, keys: &HashSet<String>) -> Vec<&'a str> {
+    let mut out: Vec<&str> = Vec::new();
+    for word in line.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_')) {
+        if !word.is_empty() && keys.contains(word) && !out.contains(&word) {
+            out.push(word);
+        }
+    }
+    out
+}
+
+fn snippet(line: &str) -> String {
+    let line = line.trim();
+    match line.char_indices().nth(MAX_SNIPPET_CHARS) {
+        Some((i, _)) => format!("{}…", &line[..i]),
+        None => line.to_string(),
+    }
+}
+
+/// 扫描 mod 的源码目录，记下文档里每个 key 在哪些文件的哪一行出现，给译者看这句话用在哪里。
+/// patterns 为相对 root 的通配（如 `scripts/*.lua`），没给时扫常见的脚本与源码扩展名；
+/// 每个 key 最多记 max_per_key 处（缺省 20）。替换文档原有的源码引用，写进附加信息文件（文档要先保存过）
+#[tauri::command]
+pub async fn gxt_import_source_refs(
+    doc_id: DocId,
+    root: String,
+    patterns: Option<Vec<String>>,
+    max_per_key: Option<usize>,
+    session: tauri::State<'_, Session>,
+) -> Result<SourceRefImport, String> {
+    let file_path = session
+        .with(doc_id, |open| Ok(open.doc.file_path.clone()))?
+        .ok_or_else(|| tr!("file.no_path"))?;
+    let keys = existing_keys(&session, doc_id)?;
+    let patterns = patterns
+        .filter(|p| !p.is_empty())
+        .unwrap_or_else(|| SOURCE_PATTERNS.iter().map(|p| p.to_string()).collect());
+    let max_per_key = max_per_key.unwrap_or(DEFAULT_REFS_PER_KEY);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let root = Path::new(&root);
+        if !root.is_dir() {
+            return Err(format!("Not a directory: {}", root.display()));
+        }
+        let mut files = Vec::new();
+        source_files(root, root, &patterns, &mut files);
+        files.sort();
+
+        let mut report = SourceRefImport::default();
+        let mut found: BTreeMap<String, Vec<SourceRef>> = BTreeMap::new();
+        for path in &files {
+            let Ok(bytes) = fs::read(path) else {
+                continue;
+            };
+            // 有 0 字节的是二进制文件
+            if bytes.contains(&0) {
+                continue;
+            }
+            report.files += 1;
+            let text = String::from_utf8_lossy(&bytes);
+            let file = relative(root, path);
+            for (n, line) in text.lines().enumerate() {
+                for key in keys_in_line(line, &keys) {
+                    report.refs += 1;
+                    let refs = found.entry(key.to_string()).or_default();
+                    if refs.len() < max_per_key {
+                        refs.push(SourceRef {
+                            file: file.clone(),
+                            line: n + 1,
+                            text: snippet(line),
+                        });
+                    }
+                }
+            }
+        }
+        report.keys = found.len();
+        let mut m = meta::load(&file_path);
+        m.source_refs = found;
+        meta::save(&file_path, &m)?;
+        tracing::info!(
+            files = report.files,
+            keys = report.keys,
+            "imported source refs"
+        );
+        Ok(report)
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+/// 一条在 mod 源码里出现的地方（没导入过时为空）
+#[tauri::command]
+pub fn gxt_source_refs(
+    doc_id: DocId,
+    key: String,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<SourceRef>, String> {
+    let file_path = session.with(doc_id, |open| Ok(open.doc.file_path.clone()))?;
+    Ok(file_path
+        .and_then(|p| meta::load(&p).source_refs.remove(&key))
+        .unwrap_or_default())
+}
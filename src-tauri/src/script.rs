@@ -0,0 +1,336 @@
+use serde::{Deserialize, Serialize};
+
+use std::cell::RefCell;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::rc::Rc;
+
+use rhai::{Array, Dynamic, Engine, EvalAltResult};
+use tauri::{AppHandle, Manager};
+
+use crate::collab::Collab;
+use crate::gxt::{self, GxtEntry};
+use crate::i18n::tr;
+use crate::project::Projects;
+use crate::rollback::{self, Target};
+use crate::session::{self, DocId, Session};
+use crate::task::{Task, Tasks, CANCELLED};
+
+/// 防止死循环脚本卡住后端
+const MAX_OPERATIONS: u64 = 50_000_000;
+const MAX_STRING_SIZE: usize = 1 << 20;
+/// 每执行这么多次运算看一下是否取消、报一次进度
+const PROGRESS_EVERY: u64 = 10_000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptResult {
+    /// 脚本执行后的全部条目（前端用它替换当前列表）
+    pub entries: Vec<GxtEntry>,
+    pub changed: Vec<String>,
+    pub added: Vec<String>,
+    pub deleted: Vec<String>,
+    /// 脚本里 print() / debug() 的输出
+    pub output: Vec<String>,
+    /// 脚本最后一个表达式的值（转成字符串）
+    pub return_value: String,
+}
+
+/// 脚本操作的是文档的一份拷贝；成功跑完并通过校验后才逐条写回
+#[derive(Default)]
+struct Workspace {
+    /// None = 已删除（保留位置，保证其余条目顺序不变）
+    entries: Vec<Option<(String, String)>>,
+    pos: HashMap<String, usize>,
+    changed: BTreeSet<String>,
+    added: BTreeSet<String>,
+    deleted: BTreeSet<String>,
+}
+
+impl Workspace {
+    fn new(entries: &[GxtEntry]) -> Self {
+        let mut ws = Workspace::default();
+        for e in entries {
+            ws.pos.insert(e.key.clone(), ws.entries.len());
+            ws.entries.push(Some((e.key.clone(), e.value.to_string())));
+        }
+        ws
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        let i = *self.pos.get(key)?;
+        self.entries[i].as_ref().map(|(_, v)| v.as_str())
+    }
+
+    fn set(&mut self, key: &str, value: String) -> Result<(), String> {
+        match self.pos.get(key) {
+            Some(&i) => {
+                if let Some((_, v)) = self.entries[i].as_mut() {
+                    if *v != value {
+                        *v = value;
+                        if !self.added.contains(key) {
+                            self.changed.insert(key.to_string());
+                        }
+                    }
+                }
+                Ok(())
+            }
+            None => self.add(key, value),
+        }
+    }
+
+    fn add(&mut self, key: &str, value: String) -> Result<(), String> {
+        if self.pos.contains_key(key) {
+            return Err(format!("Key already exists: {key}"));
+        }
+        gxt::validate_key(key)?;
+        self.pos.insert(key.to_string(), self.entries.len());
+        self.entries.push(Some((key.to_string(), value)));
+        // 删了又加回来算修改
+        if self.deleted.remove(key) {
+            self.changed.insert(key.to_string());
+        } else {
+            self.added.insert(key.to_string());
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, key: &str) -> bool {
+        let Some(i) = self.pos.remove(key) else {
+            return false;
+        };
+        self.entries[i] = None;
+        self.changed.remove(key);
+        if !self.added.remove(key) {
+            self.deleted.insert(key.to_string());
+        }
+        true
+    }
+
+    fn keys(&self) -> Array {
+        self.entries
+            .iter()
+            .flatten()
+            .map(|(k, _)| Dynamic::from(k.clone()))
+            .collect()
+    }
+
+    fn into_entries(self) -> Vec<GxtEntry> {
+        self.entries
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| GxtEntry {
+                key,
+                value: value.into(),
+            })
+            .collect()
+    }
+}
+
+/// 在指定文档上执行一段 rhai 脚本（沙箱：没有文件/网络访问，有运算次数上限）。
+///
+/// 可用函数：
+/// - `get(key)` -> 字符串，不存在时为 `()`
+/// - `set(key, value)`：修改，不存在则新增
+/// - `add(key, value)`：新增，已存在则报错
+/// - `delete(key)` -> bool
+/// - `has(key)` -> bool，`keys()` -> 数组
+/// - `regex_match(pattern, text)` -> bool
+/// - `regex_replace(pattern, text, replacement)` -> 字符串（替换全部，支持 `$1`）
+/// - `regex_find_all(pattern, text)` -> 数组
+///
+/// 在后台线程里跑；给了 task_id 时可以取消，进度是已执行的运算次数（total 为上限）。
+/// 结果逐条经过与单条编辑相同的检查写回；运行期间文档被改过时不写回，报错让用户重跑
+#[tauri::command]
+pub async fn gxt_run_script(
+    app: AppHandle,
+    doc_id: DocId,
+    script: String,
+    task_id: Option<String>,
+) -> Result<ScriptResult, String> {
+    let task = app.state::<Tasks>().start(&app, task_id)?;
+    tauri::async_runtime::spawn_blocking(move || run_script(&app, doc_id, &script, task))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+fn run_script(
+    app: &AppHandle,
+    doc_id: DocId,
+    script: &str,
+    task: Task,
+) -> Result<ScriptResult, String> {
+    let session = app.state::<Session>();
+    let entries = session.with(doc_id, |open| Ok(open.doc.entries.clone()))?;
+
+    let ws = Rc::new(RefCell::new(Workspace::new(&entries)));
+    let output = Rc::new(RefCell::new(Vec::<String>::new()));
+    let mut engine = build_engine(&ws, &output);
+    let task = Rc::new(RefCell::new(task));
+    let t = task.clone();
+    engine.on_progress(move |ops| {
+        if !ops.is_multiple_of(PROGRESS_EVERY) {
+            return None;
+        }
+        let mut t = t.borrow_mut();
+        if t.is_cancelled() {
+            // 返回 Some 让引擎以 ErrorTerminated 停下
+            return Some(Dynamic::UNIT);
+        }
+        t.progress(ops as usize, MAX_OPERATIONS as usize);
+        None
+    });
+
+    let ret = engine.eval::<Dynamic>(script).map_err(|e| match *e {
+        EvalAltResult::ErrorTerminated(..) => CANCELLED.to_string(),
+        _ => format!("Script error: {e}"),
+    })?;
+
+    drop(engine);
+    let mut ws = Rc::try_unwrap(ws)
+        .map_err(|_| "Script workspace still borrowed".to_string())?
+        .into_inner();
+    let changed = std::mem::take(&mut ws.changed);
+    let added = std::mem::take(&mut ws.added);
+    let deleted = std::mem::take(&mut ws.deleted);
+    let new_entries = ws.into_entries();
+    gxt::validate_entries(&new_entries)?;
+    task.borrow().check()?;
+
+    rollback::backup_before(app, "run-script", &[Target::Document(doc_id)])?;
+    let projects = app.state::<Projects>();
+    let collab = app.state::<Collab>();
+    let (new_entries, changed, added, deleted) = session.edit(doc_id, |open| {
+        // 脚本跑的是开始时的拷贝，期间文档被改过就不写回，免得盖掉别的修改
+        if open.doc.entries != entries {
+            return Err(tr!("script.doc_changed"));
+        }
+        // 锁定的条目不会被脚本改动，改动列表里也去掉它们
+        let unlocked = |keys: BTreeSet<String>| -> Vec<String> {
+            keys.into_iter().filter(|k| !open.is_locked(k)).collect()
+        };
+        let (changed, added, deleted) = (unlocked(changed), unlocked(added), unlocked(deleted));
+        // 整个脚本算一次批量操作：一次变更事件、一步撤销；有一条过不了检查就全部撤回
+        let own_batch = !open.in_batch();
+        if own_batch {
+            open.begin_batch()?;
+        }
+        let applied = (|| {
+            for key in &deleted {
+                session::remove_entry_checked(open, &projects, &collab, doc_id, key, false, None)?;
+            }
+            // 按脚本结果里的顺序写，新加的 key 排在后面
+            let touched: HashSet<&str> = changed.iter().chain(&added).map(String::as_str).collect();
+            for e in &new_entries {
+                if touched.contains(e.key.as_str()) {
+                    let value = e.value.to_string();
+                    session::set_entry_checked(open, &collab, doc_id, e.key.clone(), value)?;
+                }
+            }
+            Ok::<_, String>(())
+        })();
+        if let Err(e) = applied {
+            if own_batch {
+                open.rollback_batch()?;
+            }
+            return Err(e);
+        }
+        if own_batch {
+            open.commit_batch()?;
+        }
+        Ok((open.doc.entries.clone(), changed, added, deleted))
+    })?;
+
+    let output = output.borrow().clone();
+    Ok(ScriptResult {
+        entries: new_entries,
+        changed,
+        added,
+        deleted,
+        output,
+        return_value: if ret.is_unit() {
+            String::new()
+        } else {
+            ret.to_string()
+        },
+    })
+}
+
+fn build_engine(ws: &Rc<RefCell<Workspace>>, output: &Rc<RefCell<Vec<String>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 32);
+    engine.disable_symbol("eval");
+
+    let out = output.clone();
+    engine.on_print(move |s| out.borrow_mut().push(s.to_string()));
+    let out = output.clone();
+    engine.on_debug(move |s, _, _| out.borrow_mut().push(s.to_string()));
+
+    let w = ws.clone();
+    engine.register_fn("get", move |key: &str| -> Dynamic {
+        match w.borrow().get(key) {
+            Some(v) => Dynamic::from(v.to_string()),
+            None => Dynamic::UNIT,
+        }
+    });
+
+    let w = ws.clone();
+    engine.register_fn("has", move |key: &str| w.borrow().get(key).is_some());
+
+    let w = ws.clone();
+    engine.register_fn("keys", move || w.borrow().keys());
+
+    let w = ws.clone();
+    engine.register_fn(
+        "set",
+        move |key: &str, value: &str| -> Result<(), Box<EvalAltResult>> {
+            w.borrow_mut()
+                .set(key, value.to_string())
+                .map_err(|e| e.into())
+        },
+    );
+
+    let w = ws.clone();
+    engine.register_fn(
+        "add",
+        move |key: &str, value: &str| -> Result<(), Box<EvalAltResult>> {
+            w.borrow_mut()
+                .add(key, value.to_string())
+                .map_err(|e| e.into())
+        },
+    );
+
+    let w = ws.clone();
+    engine.register_fn("delete", move |key: &str| w.borrow_mut().delete(key));
+
+    engine.register_fn(
+        "regex_match",
+        |pattern: &str, text: &str| -> Result<bool, Box<EvalAltResult>> {
+            Ok(compile(pattern)?.is_match(text))
+        },
+    );
+    engine.register_fn(
+        "regex_replace",
+        |pattern: &str, text: &str, replacement: &str| -> Result<String, Box<EvalAltResult>> {
+            Ok(compile(pattern)?
+                .replace_all(text, replacement)
+                .into_owned())
+        },
+    );
+    engine.register_fn(
+        "regex_find_all",
+        |pattern: &str, text: &str| -> Result<Array, Box<EvalAltResult>> {
+            Ok(compile(pattern)?
+                .find_iter(text)
+                .map(|m| Dynamic::from(m.as_str().to_string()))
+                .collect())
+        },
+    );
+
+    engine
+}
+
+fn compile(pattern: &str) -> Result<regex::Regex, Box<EvalAltResult>> {
+    regex::Regex::new(pattern).map_err(|e| format!("Invalid regex {pattern:?}: {e}").into())
+}
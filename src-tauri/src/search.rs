@@ -0,0 +1,512 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::OnceLock;
+
+use gxt_core::Segment;
+use pinyin::ToPinyin;
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+use tauri::AppHandle;
+
+use crate::gxt::GxtEntry;
+use crate::session::{DocId, Session};
+use crate::settings;
+use crate::views::SearchScope;
+
+/// n-gram 长度：查询不短于它时走倒排索引，否则退化为线性扫描
+const GRAM: usize = 3;
+
+/// 默认最多返回多少条结果
+const DEFAULT_LIMIT: usize = 500;
+
+/// 搜索历史每种最多留几条
+const HISTORY_LIMIT: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub key: String,
+    pub value: String,
+    /// 命中的是 key 还是 value（两者都可能）
+    pub in_key: bool,
+    pub in_value: bool,
+}
+
+/// 模糊匹配选项（都不开时只做不区分大小写的子串匹配，走索引）
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct SearchOptions {
+    /// 容错：允许少量拼写错误（`ambulanse` 能找到 `ambulance`）
+    #[serde(default)]
+    pub typos: bool,
+    /// 容错时最多几处编辑（增、删、改一个字）；没给时按查询长度定，短查询不容错
+    #[serde(default)]
+    pub max_edits: Option<usize>,
+    /// 用拼音找中文：全拼（`nihao`）或首字母（`nh`），从某个字开始匹配
+    #[serde(default)]
+    pub pinyin: bool,
+    /// 忽略重音等附加符号：`cafe` 能找到 `café`，`strasse` 能找到 `straße`
+    #[serde(default)]
+    pub fold_diacritics: bool,
+    /// 只要整词：命中的前后不能紧挨着字母、数字或 `_`（`car` 不会找到 `cards`）
+    #[serde(default)]
+    pub whole_word: bool,
+    /// 不在 `~token~` 与转义序列里找（`b` 不会找到 `~b~`）
+    #[serde(default)]
+    pub skip_codes: bool,
+}
+
+impl SearchOptions {
+    fn any(&self) -> bool {
+        self.typos || self.pinyin || self.fold_diacritics
+    }
+
+    fn refines(&self) -> bool {
+        self.whole_word || self.skip_codes
+    }
+
+    fn max_edits_for(&self, query_len: usize) -> usize {
+        let default = match query_len {
+            0..=2 => 0,
+            3..=7 => 1,
+            _ => 2,
+        };
+        self.max_edits
+            .unwrap_or(default)
+            .min(query_len.saturating_sub(1))
+    }
+}
+
+/// 每个文档一份的内存索引：
+/// - keys：(小写 key, 原 key) 的有序集合，结果天然按 key 排序
+/// - grams：小写 value 的 trigram -> key 集合
+/// - values：key -> 小写 value（用于候选校验，以及编辑时撤掉旧 gram）
+/// - pinyin：key -> value 的拼音，第一次按拼音搜索时才生成，之后随编辑更新
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    keys: BTreeSet<(String, String)>,
+    grams: HashMap<String, HashSet<String>>,
+    values: HashMap<String, String>,
+    pinyin: OnceLock<HashMap<String, Spelling>>,
+}
+
+/// 一条 value 的拼音：每个字的首字母连起来，与全拼连起来（非汉字按原字符）
+#[derive(Debug, Default)]
+struct Spelling {
+    initials: String,
+    full: String,
+    /// 每个字的全拼在 full 里开始的位置
+    starts: Vec<usize>,
+}
+
+impl Spelling {
+    fn of(value: &str) -> Self {
+        let mut sp = Spelling::default();
+        for c in value.chars() {
+            let start = sp.full.len();
+            match c.to_pinyin() {
+                Some(p) => sp.full.push_str(p.plain()),
+                None => sp.full.push(c),
+            }
+            sp.starts.push(start);
+            if let Some(first) = sp.full[start..].chars().next() {
+                sp.initials.push(first);
+            }
+        }
+        sp
+    }
+
+    /// query（已去空白、小写）是否是从某个字开始的全拼前缀或首字母串
+    fn matches(&self, query: &str) -> bool {
+        self.initials.contains(query)
+            || self
+                .starts
+                .iter()
+                .any(|&i| self.full[i..].starts_with(query))
+    }
+}
+
+impl SearchIndex {
+    pub fn build(entries: &[GxtEntry]) -> Self {
+        let mut idx = SearchIndex::default();
+        for e in entries {
+            idx.insert(&e.key, &e.value);
+        }
+        idx
+    }
+
+    /// 新增或覆盖一条（覆盖时先撤掉旧 value 的 gram）
+    pub fn insert(&mut self, key: &str, value: &str) {
+        self.remove(key);
+
+        let lower = value.to_lowercase();
+        for g in grams_of(&lower) {
+            self.grams.entry(g).or_default().insert(key.to_string());
+        }
+        self.keys.insert((key.to_lowercase(), key.to_string()));
+        if let Some(pinyin) = self.pinyin.get_mut() {
+            pinyin.insert(key.to_string(), Spelling::of(&lower));
+        }
+        self.values.insert(key.to_string(), lower);
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        let Some(old) = self.values.remove(key) else {
+            return;
+        };
+        for g in grams_of(&old) {
+            if let Some(set) = self.grams.get_mut(&g) {
+                set.remove(key);
+                if set.is_empty() {
+                    self.grams.remove(&g);
+                }
+            }
+        }
+        self.keys.remove(&(key.to_lowercase(), key.to_string()));
+        if let Some(pinyin) = self.pinyin.get_mut() {
+            pinyin.remove(key);
+        }
+    }
+
+    fn pinyin(&self) -> &HashMap<String, Spelling> {
+        self.pinyin.get_or_init(|| {
+            self.values
+                .iter()
+                .map(|(key, value)| (key.clone(), Spelling::of(value)))
+                .collect()
+        })
+    }
+
+    /// 返回命中的 key（按 key 排序）以及各自命中位置
+    pub fn search(&self, query: &str, limit: usize) -> Vec<(String, bool, bool)> {
+        let q = query.to_lowercase();
+        if q.is_empty() {
+            return Vec::new();
+        }
+
+        let mut hits: HashMap<&str, (bool, bool)> = HashMap::new();
+
+        // key：包含即命中（key 最多 8 字节，直接扫有序集合足够快）
+        for (lower, key) in &self.keys {
+            if lower.contains(&q) {
+                hits.entry(key.as_str()).or_default().0 = true;
+            }
+        }
+
+        // value：够长就用 gram 求交集，否则全量扫
+        let candidates: Box<dyn Iterator<Item = &String>> = if q.chars().count() >= GRAM {
+            let mut sets: Vec<&HashSet<String>> = Vec::new();
+            for g in grams_of(&q) {
+                match self.grams.get(&g) {
+                    Some(set) => sets.push(set),
+                    None => {
+                        sets.clear();
+                        break;
+                    }
+                }
+            }
+            sets.sort_by_key(|s| s.len());
+            if sets.is_empty() {
+                Box::new(std::iter::empty())
+            } else {
+                let first = sets.remove(0);
+                Box::new(
+                    first
+                        .iter()
+                        .filter(move |k| sets.iter().all(|s| s.contains(*k))),
+                )
+            }
+        } else {
+            Box::new(self.values.keys())
+        };
+
+        for key in candidates {
+            if self.values.get(key).is_some_and(|v| v.contains(&q)) {
+                hits.entry(key.as_str()).or_default().1 = true;
+            }
+        }
+
+        let mut out: Vec<(String, bool, bool)> = hits
+            .into_iter()
+            .map(|(k, (in_key, in_value))| (k.to_string(), in_key, in_value))
+            .collect();
+        out.sort_by(|a, b| a.0.cmp(&b.0));
+        out.truncate(limit);
+        out
+    }
+
+    /// 可能和 query 相差不超过 k 处编辑的 key：一处编辑最多毁掉 GRAM 个 gram，
+    /// 所以至少要共有（query 的 gram 数 - k * GRAM）个。下限不大于 0 时没法筛，返回 None
+    fn typo_candidates(&self, query: &str, k: usize) -> Option<HashSet<&str>> {
+        let grams = grams_of(query);
+        let need = grams.len().checked_sub(k * GRAM).filter(|n| *n > 0)?;
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for g in &grams {
+            for key in self.grams.get(g).into_iter().flatten() {
+                *counts.entry(key.as_str()).or_default() += 1;
+            }
+        }
+        Some(
+            counts
+                .into_iter()
+                .filter(|(_, n)| *n >= need)
+                .map(|(k, _)| k)
+                .collect(),
+        )
+    }
+
+    /// 带容错/拼音/去附加符号的搜索：后两种变换没法建 gram，value 逐条扫；
+    /// 容错先用 gram 粗筛，再逐条算编辑距离
+    pub fn search_with(
+        &self,
+        query: &str,
+        options: SearchOptions,
+        limit: usize,
+    ) -> Vec<(String, bool, bool)> {
+        let q = query.to_lowercase();
+        if q.is_empty() {
+            return Vec::new();
+        }
+        let folded = options.fold_diacritics.then(|| fold_diacritics(&q));
+        let spelled: String = q.chars().filter(|c| !c.is_whitespace()).collect();
+        let q_chars: Vec<char> = q.chars().collect();
+        let edits = options.max_edits_for(q_chars.len());
+        let typos = options.typos && edits > 0;
+        let candidates = typos.then(|| self.typo_candidates(&q, edits)).flatten();
+        let near = |text: &str| within_edits(text, &q_chars, edits);
+        let pinyin = (options.pinyin && !spelled.is_empty()).then(|| self.pinyin());
+
+        let mut out: Vec<(String, bool, bool)> = Vec::new();
+        for (lower, key) in &self.keys {
+            let Some(value) = self.values.get(key) else {
+                continue;
+            };
+            let in_key = lower.contains(&q) || (typos && near(lower));
+            let in_value = value.contains(&q)
+                || (typos
+                    && candidates.as_ref().is_none_or(|c| c.contains(key.as_str()))
+                    && near(value))
+                || folded
+                    .as_ref()
+                    .is_some_and(|f| fold_diacritics(value).contains(f.as_str()))
+                || pinyin
+                    .and_then(|p| p.get(key))
+                    .is_some_and(|sp| sp.matches(&spelled));
+            if in_key || in_value {
+                out.push((key.clone(), in_key, in_value));
+                if out.len() >= limit {
+                    break;
+                }
+            }
+        }
+        out
+    }
+}
+
+/// text 里是否有和 query 相差不超过 k 处编辑的子串（Sellers 算法：子串从哪里开始都不计代价）
+fn within_edits(text: &str, query: &[char], k: usize) -> bool {
+    let m = query.len();
+    if m <= k {
+        return true;
+    }
+    let mut prev: Vec<usize> = (0..=m).collect();
+    let mut cur = vec![0; m + 1];
+    for c in text.chars() {
+        for i in 1..=m {
+            let cost = usize::from(query[i - 1] != c);
+            cur[i] = (prev[i - 1] + cost).min(prev[i] + 1).min(cur[i - 1] + 1);
+        }
+        if cur[m] <= k {
+            return true;
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    false
+}
+
+/// 拆成 NFD 后去掉组合附加符号；几个不分解的字母单独换成 ASCII
+fn fold_diacritics(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.nfd().filter(|c| !is_combining_mark(*c)) {
+        match c {
+            'ß' => out.push_str("ss"),
+            'æ' => out.push_str("ae"),
+            'œ' => out.push_str("oe"),
+            'ø' => out.push('o'),
+            'ł' => out.push('l'),
+            'đ' => out.push('d'),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// s[start..end] 两头是否在词的边界上；命中本身的首尾不是词字符时那一头不要求
+fn word_bounded(s: &str, start: usize, end: usize) -> bool {
+    let hit = &s[start..end];
+    let joined = |edge: Option<char>, outside: Option<char>| {
+        edge.is_some_and(is_word_char) && outside.is_some_and(is_word_char)
+    };
+    !joined(hit.chars().next(), s[..start].chars().next_back())
+        && !joined(hit.chars().next_back(), s[end..].chars().next())
+}
+
+/// text 里有没有满足 whole_word / skip_codes 的 q（已小写）
+fn refined_match(text: &str, q: &str, options: &SearchOptions) -> bool {
+    let pieces: Vec<&str> = if options.skip_codes {
+        gxt_core::segments(text)
+            .into_iter()
+            .filter_map(|seg| match seg {
+                Segment::Text(t) => Some(t),
+                _ => None,
+            })
+            .collect()
+    } else {
+        vec![text]
+    };
+    pieces.iter().any(|piece| {
+        let lower = piece.to_lowercase();
+        lower
+            .match_indices(q)
+            .any(|(i, m)| !options.whole_word || word_bounded(&lower, i, i + m.len()))
+    })
+}
+
+fn grams_of(s: &str) -> HashSet<String> {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.len() < GRAM {
+        return HashSet::new();
+    }
+    chars.windows(GRAM).map(|w| w.iter().collect()).collect()
+}
+
+/// 在指定文档里搜索 key / value（不区分大小写；options 可开容错、拼音与去附加符号匹配）。
+/// whole_word / skip_codes 只约束原样出现的命中，容错、拼音等找到的照常返回
+#[tauri::command]
+pub fn gxt_search(
+    doc_id: DocId,
+    query: String,
+    limit: Option<usize>,
+    options: Option<SearchOptions>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<SearchHit>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    let refine = options.filter(|o| o.refines());
+    // 要再筛一遍时先不截断
+    let wanted = if refine.is_some() { usize::MAX } else { limit };
+    session.with(doc_id, |open| {
+        let hits = match options.filter(|o| o.any()) {
+            Some(o) => open.index.search_with(&query, o, wanted),
+            None => open.index.search(&query, wanted),
+        };
+        let by_key: HashMap<&str, &str> = open
+            .doc
+            .entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+
+        let q = query.to_lowercase();
+        let exact = |text: &str| text.to_lowercase().contains(&q);
+        Ok(hits
+            .into_iter()
+            .filter_map(|(key, mut in_key, mut in_value)| {
+                let value = by_key.get(key.as_str())?.to_string();
+                if let Some(o) = &refine {
+                    let key_only = SearchOptions {
+                        skip_codes: false,
+                        ..*o
+                    };
+                    in_key &= !exact(&key) || refined_match(&key, &q, &key_only);
+                    in_value &= !exact(&value) || refined_match(&value, &q, o);
+                    if !in_key && !in_value {
+                        return None;
+                    }
+                }
+                Some(SearchHit {
+                    key,
+                    value,
+                    in_key,
+                    in_value,
+                })
+            })
+            .take(limit)
+            .collect())
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryKind {
+    Search,
+    Replace,
+}
+
+/// 一条搜索/替换历史（连同当时的选项，常用的正则重启后还在）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchHistoryEntry {
+    pub kind: HistoryKind,
+    pub query: String,
+    /// 替换为（只有替换有）
+    #[serde(default)]
+    pub replacement: Option<String>,
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(default)]
+    pub case_sensitive: bool,
+    #[serde(default)]
+    pub scope: SearchScope,
+    /// 拼音、去附加符号、整词等（字段直接摊在这一层）
+    #[serde(flatten)]
+    pub options: SearchOptions,
+}
+
+/// 记一条历史：放到最前面，之前相同的（种类、查询、替换都一样）去掉；每种最多留 HISTORY_LIMIT 条
+#[tauri::command]
+pub fn gxt_search_history_add(app: AppHandle, entry: SearchHistoryEntry) -> Result<(), String> {
+    if entry.query.is_empty() {
+        return Ok(());
+    }
+    settings::update(&app, |s| {
+        let h = &mut s.search_history;
+        h.retain(|e| {
+            !(e.kind == entry.kind && e.query == entry.query && e.replacement == entry.replacement)
+        });
+        let kind = entry.kind;
+        h.insert(0, entry);
+        let mut kept = 0;
+        h.retain(|e| {
+            if e.kind != kind {
+                return true;
+            }
+            kept += 1;
+            kept <= HISTORY_LIMIT
+        });
+    })
+}
+
+/// 最近的在前；kind 为空时两种都要
+#[tauri::command]
+pub fn gxt_search_history(
+    app: AppHandle,
+    kind: Option<HistoryKind>,
+) -> Result<Vec<SearchHistoryEntry>, String> {
+    Ok(settings::load(&app)?
+        .search_history
+        .into_iter()
+        .filter(|e| kind.is_none_or(|k| e.kind == k))
+        .collect())
+}
+
+/// 清空历史；kind 为空时两种都清
+#[tauri::command]
+pub fn gxt_search_history_clear(app: AppHandle, kind: Option<HistoryKind>) -> Result<(), String> {
+    settings::update(&app, |s| {
+        s.search_history
+            .retain(|e| kind.is_some_and(|k| e.kind != k))
+    })
+}
@@ -0,0 +1,15 @@
+use gxt_core::SelftestReport;
+
+use crate::i18n::tr;
+
+/// 自检：内置样例（各种格式与边界情况）走一遍 解析 -> 生成 -> 解析，报告所有不一致
+#[tauri::command]
+pub async fn gxt_selftest() -> Result<SelftestReport, String> {
+    let report = tauri::async_runtime::spawn_blocking(gxt_core::run_selftest)
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))?;
+    for c in report.failures() {
+        tracing::warn!(case = %c.name, detail = ?c.detail, "selftest failed");
+    }
+    Ok(report)
+}
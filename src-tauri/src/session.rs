@@ -0,0 +1,1029 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use gxt_core::{EntryDiff, GroupLevel, KeyGroup, LoadWarning};
+use tauri::AppHandle;
+
+use crate::collab::Collab;
+use crate::events::{DocEvent, EventSink};
+use crate::gxt::{validate_key, GxtDocument, GxtEntry};
+use crate::i18n::tr;
+use crate::merge_report::{self, Decision, MergeReport};
+use crate::meta::{self, Approval, EntryStamp, Tombstone};
+use crate::project::Projects;
+use crate::reference::ReferenceFile;
+use crate::rollback::{self, Target};
+use crate::search::SearchIndex;
+
+/// 最多能撤销几次批量操作
+const UNDO_LIMIT: usize = 20;
+
+/// gxt_begin_batch 时的状态：回滚时恢复，提交后留作撤销
+#[derive(Debug)]
+struct Batch {
+    entries: Vec<GxtEntry>,
+    fuzzy: BTreeSet<String>,
+    stamps: BTreeMap<String, EntryStamp>,
+    notes: BTreeMap<String, String>,
+    reviewers: BTreeMap<String, String>,
+    approvals: BTreeMap<String, Approval>,
+    tombstones: BTreeMap<String, Tombstone>,
+    dirty: bool,
+}
+
+/// 一次批量操作改了多少条
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchSummary {
+    pub added: usize,
+    pub changed: usize,
+    pub removed: usize,
+}
+
+/// 后端持有的一份打开的文档以及它的派生数据（索引等）
+#[derive(Debug)]
+pub struct OpenDocument {
+    pub doc: GxtDocument,
+    pub index: SearchIndex,
+    /// 需要人工复核的条目（机翻结果、原文变动过的译文等）
+    pub fuzzy: BTreeSet<String>,
+    /// 锁定的条目（见 meta.rs）：set_entry / remove_entry / replace_entries 都不会改它们
+    pub locked: BTreeSet<String>,
+    /// 每条最后一次修改的时间与作者（见 meta.rs）
+    pub stamps: BTreeMap<String, EntryStamp>,
+    /// 每条的注释（见 notes.rs）
+    pub notes: BTreeMap<String, String>,
+    /// 指派的审校人与审校确认（见 review.rs）
+    pub reviewers: BTreeMap<String, String>,
+    pub approvals: BTreeMap<String, Approval>,
+    /// 固定的 key（见 pins.rs）；key 被删掉后仍保留，加回来就又能看到
+    pub pinned: Vec<String>,
+    /// 软删除的条目（见 tombstones.rs）：已不在 doc.entries 里，合并类操作不会再加回来
+    pub tombstones: BTreeMap<String, Tombstone>,
+    /// 挂着的参考文件（见 reference.rs）
+    pub reference: Option<ReferenceFile>,
+    /// 打开时读文件发现的问题（见 gxt_load 的 lenient）；保存后清空，写出的文件已没有这些问题
+    pub warnings: Vec<LoadWarning>,
+    /// 上次保存/打开时每条的值（见 revert.rs）；与撤销历史无关，只在保存时更新
+    saved: HashMap<String, Arc<str>>,
+    /// 上次保存/打开之后是否改过
+    dirty: bool,
+    /// 尚未分发的变更事件；Session 在每次操作结束、释放锁之后统一发出（批量操作中则攒到提交时）
+    events: Vec<DocEvent>,
+    /// 进行中的批量操作
+    batch: Option<Batch>,
+    /// 已提交的批量操作之前的条目，最近的在最后
+    undo: Vec<Vec<GxtEntry>>,
+}
+
+impl OpenDocument {
+    pub fn new(doc: GxtDocument) -> Self {
+        let index = SearchIndex::build(&doc.entries);
+        let saved = saved_values(&doc.entries);
+        OpenDocument {
+            doc,
+            index,
+            fuzzy: BTreeSet::new(),
+            locked: BTreeSet::new(),
+            stamps: BTreeMap::new(),
+            notes: BTreeMap::new(),
+            reviewers: BTreeMap::new(),
+            approvals: BTreeMap::new(),
+            pinned: Vec::new(),
+            tombstones: BTreeMap::new(),
+            reference: None,
+            warnings: Vec::new(),
+            saved,
+            dirty: false,
+            events: Vec::new(),
+            batch: None,
+            undo: Vec::new(),
+        }
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    fn mark_dirty(&mut self) {
+        if !self.dirty {
+            self.dirty = true;
+            self.events.push(DocEvent::DirtyChanged { dirty: true });
+        }
+    }
+
+    fn replaced_event(&self) -> DocEvent {
+        DocEvent::DocumentReplaced {
+            file_path: self.doc.file_path.clone(),
+            entry_count: self.doc.entries.len(),
+        }
+    }
+
+    /// 上次保存/打开时的值；之后才加的 key 为 None
+    pub fn saved_value(&self, key: &str) -> Option<&str> {
+        self.saved.get(key).map(|v| &**v)
+    }
+
+    /// 与上次保存时不同的 key：改过的与新加的按文档顺序，删掉的按 key 排在最后
+    pub fn unsaved_keys(&self) -> Vec<String> {
+        let mut out: Vec<String> = self
+            .doc
+            .entries
+            .iter()
+            .filter(|e| self.saved_value(&e.key) != Some(&*e.value))
+            .map(|e| e.key.clone())
+            .collect();
+        let current: HashSet<&str> = self.doc.entries.iter().map(|e| e.key.as_str()).collect();
+        let mut removed: Vec<String> = self
+            .saved
+            .keys()
+            .filter(|k| !current.contains(k.as_str()))
+            .cloned()
+            .collect();
+        removed.sort();
+        out.extend(removed);
+        out
+    }
+
+    pub fn is_locked(&self, key: &str) -> bool {
+        self.locked.contains(key)
+    }
+
+    /// 只读打开的文档（见 gxt_load 的 read_only）不能改
+    pub fn check_writable(&self) -> Result<(), String> {
+        if self.doc.read_only {
+            return Err(tr!(
+                "doc.read_only",
+                path = self.doc.file_path.as_deref().unwrap_or_default()
+            ));
+        }
+        Ok(())
+    }
+
+    /// 整体替换条目（批量操作用）；锁定的条目保持原样，仍存在的 key 保留其标记
+    pub fn replace_entries(&mut self, mut doc: GxtDocument) {
+        let locked = self.doc.entries.iter().filter(|e| self.is_locked(&e.key));
+        for old in locked {
+            match doc.entries.iter_mut().find(|e| e.key == old.key) {
+                Some(e) => e.value = old.value.clone(),
+                None => doc.entries.push(old.clone()),
+            }
+        }
+        let before: HashMap<&str, &str> = self
+            .doc
+            .entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+        let stamp = meta::stamp_now();
+        for e in &doc.entries {
+            if before.get(e.key.as_str()) != Some(&&*e.value) {
+                self.stamps.insert(e.key.clone(), stamp.clone());
+            }
+        }
+        self.load_entries(doc);
+    }
+
+    /// 不看锁定，直接换成 doc 的内容（保存后同步：磁盘上已经是这个内容了）
+    fn load_entries(&mut self, doc: GxtDocument) {
+        self.index = SearchIndex::build(&doc.entries);
+        let keys: BTreeSet<&str> = doc.entries.iter().map(|e| e.key.as_str()).collect();
+        self.fuzzy.retain(|k| keys.contains(k.as_str()));
+        self.locked.retain(|k| keys.contains(k.as_str()));
+        self.stamps.retain(|k, _| keys.contains(k.as_str()));
+        self.doc = doc;
+        let ev = self.replaced_event();
+        self.events.push(ev);
+        self.mark_dirty();
+    }
+
+    /// 新增或修改一条；索引只增量更新这一条。锁定的条目不改
+    pub fn set_entry(&mut self, key: String, value: String) {
+        if self.is_locked(&key) {
+            return;
+        }
+        self.index.insert(&key, &value);
+        match self.doc.entries.iter_mut().find(|e| e.key == key) {
+            Some(e) => {
+                if *e.value != *value {
+                    e.value = value.as_str().into();
+                    self.stamps.insert(key.clone(), meta::stamp_now());
+                    self.events.push(DocEvent::EntryChanged { key, value });
+                    self.mark_dirty();
+                }
+            }
+            None => {
+                self.doc.entries.push(GxtEntry {
+                    key: key.clone(),
+                    value: value.as_str().into(),
+                });
+                self.stamps.insert(key.clone(), meta::stamp_now());
+                self.events.push(DocEvent::EntryAdded { key, value });
+                self.mark_dirty();
+            }
+        }
+    }
+
+    pub fn in_batch(&self) -> bool {
+        self.batch.is_some()
+    }
+
+    /// 开始批量操作：之后的修改照常生效，但变更事件攒到提交时合成一个
+    pub fn begin_batch(&mut self) -> Result<(), String> {
+        if self.batch.is_some() {
+            return Err(tr!("batch.already_open"));
+        }
+        self.batch = Some(Batch {
+            entries: self.doc.entries.clone(),
+            fuzzy: self.fuzzy.clone(),
+            stamps: self.stamps.clone(),
+            notes: self.notes.clone(),
+            reviewers: self.reviewers.clone(),
+            approvals: self.approvals.clone(),
+            tombstones: self.tombstones.clone(),
+            dirty: self.dirty,
+        });
+        Ok(())
+    }
+
+    /// 提交批量操作：有改动时只发一个 DocumentReplaced，并记为一步撤销
+    pub fn commit_batch(&mut self) -> Result<BatchSummary, String> {
+        let batch = self.batch.take().ok_or_else(|| tr!("batch.not_open"))?;
+        let diff = gxt_core::diff_entries(&batch.entries, &self.doc.entries);
+        let summary = BatchSummary {
+            added: diff.added.len(),
+            changed: diff.changed.len(),
+            removed: diff.removed.len(),
+        };
+        let dirty_changed = self.dirty != batch.dirty;
+        self.events.clear();
+        if summary.added + summary.changed + summary.removed > 0 {
+            let ev = self.replaced_event();
+            self.events.push(ev);
+            self.undo.push(batch.entries);
+            if self.undo.len() > UNDO_LIMIT {
+                self.undo.remove(0);
+            }
+        }
+        if dirty_changed {
+            self.events
+                .push(DocEvent::DirtyChanged { dirty: self.dirty });
+        }
+        Ok(summary)
+    }
+
+    /// 放弃批量操作：恢复开始时的条目与标记，攒下的事件丢掉
+    pub fn rollback_batch(&mut self) -> Result<(), String> {
+        let batch = self.batch.take().ok_or_else(|| tr!("batch.not_open"))?;
+        self.index = SearchIndex::build(&batch.entries);
+        self.doc.entries = batch.entries;
+        self.fuzzy = batch.fuzzy;
+        self.stamps = batch.stamps;
+        self.notes = batch.notes;
+        self.reviewers = batch.reviewers;
+        self.approvals = batch.approvals;
+        self.tombstones = batch.tombstones;
+        self.dirty = batch.dirty;
+        self.events.clear();
+        Ok(())
+    }
+
+    /// 撤销最近一次提交的批量操作（锁定的条目不动；删掉的条目的注释等标记不恢复）；
+    /// 没有可撤销的返回 false
+    pub fn undo_batch(&mut self) -> Result<bool, String> {
+        if self.batch.is_some() {
+            return Err(tr!("batch.already_open"));
+        }
+        let Some(entries) = self.undo.pop() else {
+            return Ok(false);
+        };
+        let mut doc = self.doc.clone();
+        doc.entries = entries;
+        self.replace_entries(doc);
+        Ok(true)
+    }
+
+    /// 删除一条；锁定的条目不删（返回 false）
+    pub fn remove_entry(&mut self, key: &str) -> bool {
+        if self.is_locked(key) {
+            return false;
+        }
+        let before = self.doc.entries.len();
+        self.doc.entries.retain(|e| e.key != key);
+        self.index.remove(key);
+        self.fuzzy.remove(key);
+        self.stamps.remove(key);
+        self.notes.remove(key);
+        self.reviewers.remove(key);
+        self.approvals.remove(key);
+        let removed = self.doc.entries.len() != before;
+        if removed {
+            self.events.push(DocEvent::EntryDeleted {
+                key: key.to_string(),
+            });
+            self.mark_dirty();
+        }
+        removed
+    }
+
+    /// 软删除一条：像 remove_entry 一样删掉，另外记下删除前的译文；锁定的或不存在的返回 false
+    pub fn tombstone_entry(&mut self, key: &str) -> bool {
+        let Some(value) = self
+            .doc
+            .entries
+            .iter()
+            .find(|e| e.key == key)
+            .map(|e| e.value.to_string())
+        else {
+            return false;
+        };
+        if !self.remove_entry(key) {
+            return false;
+        }
+        let stamp = meta::stamp_now();
+        self.tombstones.insert(
+            key.to_string(),
+            Tombstone {
+                value,
+                at: stamp.modified,
+                author: stamp.author,
+            },
+        );
+        true
+    }
+
+    pub fn is_tombstoned(&self, key: &str) -> bool {
+        self.tombstones.contains_key(key)
+    }
+
+    /// 合并类操作（粘贴、语言同步、脚手架等）写一条：软删除过的 key 不加回来，
+    /// 返回 false（合并报告里记成 Skipped）；锁定的与 set_entry 一样不改
+    pub fn merge_entry(&mut self, key: String, value: String) -> bool {
+        if self.is_tombstoned(&key) {
+            return false;
+        }
+        self.set_entry(key, value);
+        true
+    }
+}
+
+fn saved_values(entries: &[GxtEntry]) -> HashMap<String, Arc<str>> {
+    entries
+        .iter()
+        .map(|e| (e.key.clone(), e.value.clone()))
+        .collect()
+}
+
+/// 后端给每个打开的文档分配的 id（前端所有针对文档的命令都带上它）
+pub type DocId = u32;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentInfo {
+    pub doc_id: DocId,
+    pub file_path: Option<String>,
+    pub entry_count: usize,
+    pub dirty: bool,
+    pub read_only: bool,
+}
+
+#[derive(Debug, Default)]
+struct Docs {
+    next_id: DocId,
+    open: BTreeMap<DocId, OpenDocument>,
+}
+
+/// tauri managed state：所有打开的文档。gxt_load 时登记，gxt_save 时同步，编辑命令增量维护
+#[derive(Debug, Default)]
+pub struct Session {
+    docs: Mutex<Docs>,
+    sink: EventSink,
+}
+
+impl Session {
+    /// 变更事件交给谁分发（启动时设置一次）
+    pub fn set_event_sink(&self, f: impl Fn(DocId, Vec<DocEvent>) + Send + Sync + 'static) {
+        self.sink.set(f);
+    }
+
+    /// 登记一份新打开的文档，返回它的 id（写进 doc.doc_id）
+    pub fn open(&self, doc: &mut GxtDocument) -> Result<DocId, String> {
+        let (id, ev) = {
+            let mut g = self
+                .docs
+                .lock()
+                .map_err(|_| "Session lock poisoned".to_string())?;
+            g.next_id += 1;
+            let id = g.next_id;
+            doc.doc_id = Some(id);
+            let open = OpenDocument::new(doc.clone());
+            let ev = open.replaced_event();
+            g.open.insert(id, open);
+            (id, ev)
+        };
+        self.sink.send(id, vec![ev]);
+        Ok(id)
+    }
+
+    /// 关闭文档；返回它是否开着
+    pub fn close(&self, id: DocId) -> bool {
+        let closed = self
+            .docs
+            .lock()
+            .map(|mut g| g.open.remove(&id).is_some())
+            .unwrap_or(false);
+        if closed {
+            self.sink.send(id, vec![DocEvent::DocumentClosed]);
+        }
+        closed
+    }
+
+    /// 保存成功后同步前端传来的内容：doc.doc_id 指向已打开的文档时更新它（保留标记），
+    /// 否则（例如插件导入后第一次保存）登记为新文档。返回文档 id
+    pub fn sync(&self, mut doc: GxtDocument) -> Result<DocId, String> {
+        let mut events = vec![DocEvent::DocumentSaved {
+            file_path: doc.file_path.clone(),
+            entry_count: doc.entries.len(),
+        }];
+        let id = {
+            let mut g = self
+                .docs
+                .lock()
+                .map_err(|_| "Session lock poisoned".to_string())?;
+            match doc.doc_id.filter(|id| g.open.contains_key(id)) {
+                Some(id) => {
+                    if let Some(open) = g.open.get_mut(&id) {
+                        open.load_entries(doc);
+                        // 保存本身就说明了内容已更新，不再单独发 DocumentReplaced；
+                        // 没提交的批量操作也随之结束（内容已经写到磁盘上）
+                        open.events.clear();
+                        open.batch = None;
+                        open.warnings.clear();
+                        open.saved = saved_values(&open.doc.entries);
+                        if open.dirty {
+                            open.dirty = false;
+                            events.push(DocEvent::DirtyChanged { dirty: false });
+                        }
+                    }
+                    id
+                }
+                None => {
+                    g.next_id += 1;
+                    let id = g.next_id;
+                    doc.doc_id = Some(id);
+                    g.open.insert(id, OpenDocument::new(doc));
+                    id
+                }
+            }
+        };
+        self.sink.send(id, events);
+        Ok(id)
+    }
+
+    pub fn list(&self) -> Vec<DocumentInfo> {
+        self.docs
+            .lock()
+            .map(|g| {
+                g.open
+                    .iter()
+                    .map(|(&doc_id, open)| DocumentInfo {
+                        doc_id,
+                        file_path: open.doc.file_path.clone(),
+                        entry_count: open.doc.entries.len(),
+                        dirty: open.dirty,
+                        read_only: open.doc.read_only,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// 在指定文档上执行 f；文档没打开时报错。f 产生的变更事件在释放锁后发出，
+    /// 文档在批量操作中时留到提交
+    pub fn with<T>(
+        &self,
+        id: DocId,
+        f: impl FnOnce(&mut OpenDocument) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let (res, events) = {
+            let mut g = self
+                .docs
+                .lock()
+                .map_err(|_| "Session lock poisoned".to_string())?;
+            let open = g
+                .open
+                .get_mut(&id)
+                .ok_or_else(|| tr!("doc.not_found", id = id))?;
+            let res = f(open);
+            if open.in_batch() {
+                (res, Vec::new())
+            } else {
+                (res, std::mem::take(&mut open.events))
+            }
+        };
+        self.sink.send(id, events);
+        res
+    }
+
+    /// 与 with 相同，但文档是只读打开的时候直接报错（所有修改文档的命令都走这里）
+    pub fn edit<T>(
+        &self,
+        id: DocId,
+        f: impl FnOnce(&mut OpenDocument) -> Result<T, String>,
+    ) -> Result<T, String> {
+        self.with(id, |open| {
+            open.check_writable()?;
+            f(open)
+        })
+    }
+}
+
+/// 前端修改/新增单条时通知后端，保持后端文档与索引同步
+#[tauri::command]
+pub fn gxt_set_entry(
+    doc_id: DocId,
+    key: String,
+    value: String,
+    session: tauri::State<'_, Session>,
+    collab: tauri::State<'_, Collab>,
+) -> Result<(), String> {
+    validate_key(&key)?;
+    session.edit(doc_id, |open| {
+        set_entry_checked(open, &collab, doc_id, key, value)
+    })
+}
+
+/// 改一条之前的检查：锁定的、协作中被 guest 认领着的不改；手动加回软删除的 key 不再算删过。
+/// 前端、HTTP API 的单条改动与批量添加都走这里
+pub(crate) fn set_entry_checked(
+    open: &mut OpenDocument,
+    collab: &Collab,
+    doc_id: DocId,
+    key: String,
+    value: String,
+) -> Result<(), String> {
+    if open.is_locked(&key) {
+        return Err(tr!("entry.locked", key = key));
+    }
+    // 协作中被 guest 认领着的条目等对方放开
+    collab.check_local(doc_id, &key)?;
+    open.tombstones.remove(&key);
+    open.set_entry(key, value);
+    Ok(())
+}
+
+/// 删除单条（改名也是删旧 key 再加新 key）；返回是否真的删掉了。
+/// 项目里受保护的 key 要 force 才能删。tombstone 为 true 时软删除（见 tombstones.rs），
+/// 没给时按项目设置的 soft_delete
+#[tauri::command]
+pub fn gxt_remove_entry(
+    doc_id: DocId,
+    key: String,
+    force: Option<bool>,
+    tombstone: Option<bool>,
+    session: tauri::State<'_, Session>,
+    projects: tauri::State<'_, Projects>,
+    collab: tauri::State<'_, Collab>,
+) -> Result<bool, String> {
+    session.edit(doc_id, |open| {
+        let force = force.unwrap_or(false);
+        remove_entry_checked(open, &projects, &collab, doc_id, &key, force, tombstone)
+    })
+}
+
+/// 删一条之前的检查（受保护、锁定、协作认领），再按 tombstone 或项目的 soft_delete 设置删除
+pub(crate) fn remove_entry_checked(
+    open: &mut OpenDocument,
+    projects: &Projects,
+    collab: &Collab,
+    doc_id: DocId,
+    key: &str,
+    force: bool,
+    tombstone: Option<bool>,
+) -> Result<bool, String> {
+    let file_path = open.doc.file_path.as_deref();
+    projects.check_removal(file_path, key, force)?;
+    if open.is_locked(key) {
+        return Err(tr!("entry.locked", key = key));
+    }
+    collab.check_local(doc_id, key)?;
+    if tombstone.unwrap_or_else(|| projects.soft_delete(file_path)) {
+        Ok(open.tombstone_entry(key))
+    } else {
+        Ok(open.remove_entry(key))
+    }
+}
+
+/// 开始批量操作（如粘贴几百行）：之后的 gxt_set_entry / gxt_remove_entry 等照常调用，
+/// 由 gxt_commit_batch 一次性通知前端，或由 gxt_rollback_batch 全部放弃
+#[tauri::command]
+pub fn gxt_begin_batch(doc_id: DocId, session: tauri::State<'_, Session>) -> Result<(), String> {
+    session.edit(doc_id, |open| open.begin_batch())
+}
+
+/// 提交批量操作：只发一个变更事件，整批算一步撤销
+#[tauri::command]
+pub fn gxt_commit_batch(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<BatchSummary, String> {
+    session.with(doc_id, |open| open.commit_batch())
+}
+
+/// 放弃批量操作，恢复开始时的内容
+#[tauri::command]
+pub fn gxt_rollback_batch(doc_id: DocId, session: tauri::State<'_, Session>) -> Result<(), String> {
+    session.with(doc_id, |open| open.rollback_batch())
+}
+
+/// 撤销最近一次提交的批量操作；返回是否撤销了
+#[tauri::command]
+pub fn gxt_undo_batch(doc_id: DocId, session: tauri::State<'_, Session>) -> Result<bool, String> {
+    session.edit(doc_id, |open| open.undo_batch())
+}
+
+/// 当前被标记为 fuzzy 的 key
+#[tauri::command]
+pub fn gxt_fuzzy_list(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    session.with(doc_id, |open| Ok(open.fuzzy.iter().cloned().collect()))
+}
+
+/// 设置/清除一批 key 的 fuzzy 标记（例如译者确认了机翻结果）
+#[tauri::command]
+pub fn gxt_fuzzy_set(
+    doc_id: DocId,
+    keys: Vec<String>,
+    fuzzy: bool,
+    session: tauri::State<'_, Session>,
+) -> Result<(), String> {
+    session.edit(doc_id, |open| {
+        for k in keys {
+            if fuzzy {
+                open.fuzzy.insert(k);
+            } else {
+                open.fuzzy.remove(&k);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// 按 key 前缀分组成树，给导航侧栏用。levels 缺省为按前 4 个字符分一层
+#[tauri::command]
+pub fn gxt_group_keys(
+    doc_id: DocId,
+    levels: Option<Vec<GroupLevel>>,
+    session: tauri::State<'_, Session>,
+) -> Result<KeyGroup, String> {
+    let levels = levels.unwrap_or_else(|| vec![GroupLevel::Prefix(4)]);
+    session.with(doc_id, |open| {
+        Ok(gxt_core::group_keys(
+            open.doc.entries.iter().map(|e| e.key.as_str()),
+            &levels,
+        ))
+    })
+}
+
+/// gxt_list_keys 的筛选；没给的条件不限制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyFilter {
+    /// key 前缀（不区分大小写）
+    #[serde(default)]
+    pub prefix: Option<String>,
+    /// key 里的子串（不区分大小写）
+    #[serde(default)]
+    pub query: Option<String>,
+    /// 只要 fuzzy 的
+    #[serde(default)]
+    pub fuzzy: bool,
+    /// 只要空值的
+    #[serde(default)]
+    pub empty: bool,
+}
+
+/// 不带 value 的一条，给大纲、跨文件选 key 之类只要 key 的视图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyInfo {
+    pub key: String,
+    /// value 的字符数
+    pub len: usize,
+    pub fuzzy: bool,
+    pub locked: bool,
+}
+
+/// 按文档顺序列出 key（只带长度与状态，不传 value）
+#[tauri::command]
+pub fn gxt_list_keys(
+    doc_id: DocId,
+    filter: Option<KeyFilter>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<KeyInfo>, String> {
+    let filter = filter.unwrap_or_default();
+    let prefix = filter.prefix.map(|p| p.to_lowercase());
+    let query = filter.query.map(|q| q.to_lowercase());
+    session.with(doc_id, |open| {
+        Ok(open
+            .doc
+            .entries
+            .iter()
+            .filter(|e| {
+                let key = e.key.to_lowercase();
+                prefix.as_ref().is_none_or(|p| key.starts_with(p.as_str()))
+                    && query.as_ref().is_none_or(|q| key.contains(q.as_str()))
+                    && (!filter.fuzzy || open.fuzzy.contains(&e.key))
+                    && (!filter.empty || e.value.is_empty())
+            })
+            .map(|e| KeyInfo {
+                key: e.key.clone(),
+                len: e.value.chars().count(),
+                fuzzy: open.fuzzy.contains(&e.key),
+                locked: open.is_locked(&e.key),
+            })
+            .collect())
+    })
+}
+
+/// 文档在上次打开/保存之后是否改过
+#[tauri::command]
+pub fn gxt_is_dirty(doc_id: DocId, session: tauri::State<'_, Session>) -> Result<bool, String> {
+    session.with(doc_id, |open| Ok(open.is_dirty()))
+}
+
+/// 所有打开的文档
+#[tauri::command]
+pub fn gxt_documents(session: tauri::State<'_, Session>) -> Vec<DocumentInfo> {
+    session.list()
+}
+
+/// 关闭文档（前端关标签页时调用）；返回它是否开着
+#[tauri::command]
+pub fn gxt_close_document(doc_id: DocId, session: tauri::State<'_, Session>) -> bool {
+    session.close(doc_id)
+}
+
+/// 比较两份打开的文档（a 为旧、b 为新）
+#[tauri::command]
+pub fn gxt_compare_documents(
+    a: DocId,
+    b: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<EntryDiff, String> {
+    let old = session.with(a, |open| Ok(open.doc.entries.clone()))?;
+    session.with(b, |open| {
+        Ok(gxt_core::diff_entries(&old, &open.doc.entries))
+    })
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CopyReport {
+    /// 实际写进目标文档的条数
+    pub copied: usize,
+    /// 目标文档里锁定着、没有复制的 key
+    pub locked: Vec<String>,
+    /// 目标文档里软删除过、没有加回来的 key
+    pub deleted: Vec<String>,
+    /// 来源文档里没有的 key
+    pub missing: Vec<String>,
+}
+
+/// 把一批 key 从一份文档复制到另一份（已存在则覆盖）
+#[tauri::command]
+pub fn gxt_copy_entries(
+    app: AppHandle,
+    from: DocId,
+    to: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<CopyReport, String> {
+    let mut report = CopyReport::default();
+    let picked: Vec<(String, String)> = session.with(from, |open| {
+        let values: HashMap<&str, &str> = open
+            .doc
+            .entries
+            .iter()
+            .map(|e| (e.key.as_str(), &*e.value))
+            .collect();
+        let mut picked = Vec::with_capacity(keys.len());
+        for key in keys {
+            match values.get(key.as_str()) {
+                Some(v) => picked.push((key.clone(), v.to_string())),
+                None => report.missing.push(key),
+            }
+        }
+        Ok(picked)
+    })?;
+    rollback::backup_before(&app, "copy-entries", &[Target::Document(to)])?;
+    session.edit(to, |open| {
+        for (key, value) in picked {
+            if open.is_locked(&key) {
+                report.locked.push(key);
+            } else if open.merge_entry(key.clone(), value) {
+                report.copied += 1;
+            } else {
+                report.deleted.push(key);
+            }
+        }
+        Ok(report)
+    })
+}
+
+/// 导入时改 key 前缀：以 from 开头的 key 换成 to 开头（from 为空时给所有 key 加上 to）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reprefix {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRename {
+    pub old: String,
+    pub new: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// 改了名的 key（old -> new），写脚本的人照着改引用
+    pub renamed: Vec<KeyRename>,
+    /// 没导入的：改名后不是合法的 key、与目标文档里已有的 key 重名，或目标文档里软删除过
+    pub rejected: Vec<KeyRename>,
+}
+
+/// 从别的 mod 的文档导入条目（keys 为空时全部），可以顺带改 key 前缀以免与本 mod 的 key 冲突。
+/// 与 gxt_copy_entries 不同，目标文档里已有的 key 不覆盖
+#[tauri::command]
+pub fn gxt_import_entries(
+    app: AppHandle,
+    from: DocId,
+    to: DocId,
+    keys: Option<Vec<String>>,
+    reprefix: Option<Reprefix>,
+    session: tauri::State<'_, Session>,
+) -> Result<ImportReport, String> {
+    let (from_path, picked): (Option<String>, Vec<(String, String, String)>) =
+        session.with(from, |open| {
+            let picked = open
+                .doc
+                .entries
+                .iter()
+                .filter(|e| keys.as_ref().is_none_or(|k| k.contains(&e.key)))
+                .map(|e| {
+                    let new = match &reprefix {
+                        Some(r) => match e.key.strip_prefix(r.from.as_str()) {
+                            Some(rest) => format!("{}{rest}", r.to),
+                            None => e.key.clone(),
+                        },
+                        None => e.key.clone(),
+                    };
+                    (e.key.clone(), new, e.value.to_string())
+                })
+                .collect();
+            Ok((open.doc.file_path.clone(), picked))
+        })?;
+    rollback::backup_before(&app, "import-entries", &[Target::Document(to)])?;
+    let (report, audit) = session.edit(to, |open| {
+        let mut taken: HashSet<String> = open.doc.entries.iter().map(|e| e.key.clone()).collect();
+        let mut report = ImportReport::default();
+        let mut audit = MergeReport::new("import-entries", open.doc.file_path.clone(), from_path);
+        for (old, new, value) in picked {
+            let rename = KeyRename {
+                old: old.clone(),
+                new: new.clone(),
+            };
+            if let Err(e) = validate_key(&new) {
+                audit.push(&old, Decision::Skipped, Some(e));
+                report.rejected.push(rename);
+                continue;
+            }
+            if open.is_tombstoned(&new) {
+                audit.push(&old, Decision::Skipped, Some(format!("{new} was deleted")));
+                report.rejected.push(rename);
+                continue;
+            }
+            if !taken.insert(new.clone()) {
+                audit.push(
+                    &old,
+                    Decision::Skipped,
+                    Some(format!("{new} already exists")),
+                );
+                report.rejected.push(rename);
+                continue;
+            }
+            open.set_entry(new, value);
+            report.imported += 1;
+            if old != rename.new {
+                audit.push(&old, Decision::Renamed, Some(rename.new.clone()));
+                report.renamed.push(rename);
+            } else {
+                audit.push(&old, Decision::Added, None);
+            }
+        }
+        Ok((report, audit))
+    })?;
+    merge_report::record(&app, &audit);
+    Ok(report)
+}
+
+/// gxt_bulk_add 里不能添加的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkAddProblem {
+    /// 粘贴内容里的行号（从 1 开始）
+    pub line: usize,
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BulkAddReport {
+    /// 加进去的 key，按粘贴的顺序
+    pub added: Vec<String>,
+    /// 有问题的行；不为空时一条都没加
+    pub problems: Vec<BulkAddProblem>,
+}
+
+/// 一行 `KEY=value` 或 `KEY value`；等号只在 key 后面紧跟时才算分隔符
+fn parse_bulk_line(line: &str) -> Option<(&str, &str)> {
+    let end = line
+        .find(|c: char| c == '=' || c.is_whitespace())
+        .unwrap_or(line.len());
+    let (key, rest) = line.split_at(end);
+    let value = match rest.strip_prefix('=') {
+        Some(v) => v,
+        None => rest.strip_prefix(char::is_whitespace)?.trim_start(),
+    };
+    Some((key, value))
+}
+
+/// 一次加一批新 key（写脚本时常要一下子加几十个）：粘贴的每行是 `KEY value` 或 `KEY=value`，
+/// 空行跳过。先检查全部行（key 不合法、粘贴内容里重复、文档里已有），有问题就一条都不加；
+/// 否则作为一次批量操作加入，撤销时一步撤回
+#[tauri::command]
+pub fn gxt_bulk_add(
+    app: AppHandle,
+    doc_id: DocId,
+    text: String,
+    session: tauri::State<'_, Session>,
+    collab: tauri::State<'_, Collab>,
+) -> Result<BulkAddReport, String> {
+    let existing: HashSet<String> = session.edit(doc_id, |open| {
+        Ok(open.doc.entries.iter().map(|e| e.key.clone()).collect())
+    })?;
+    let mut report = BulkAddReport::default();
+    let mut picked: Vec<(String, String)> = Vec::new();
+    let mut seen: HashSet<&str> = HashSet::new();
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+        let mut problem = |key: &str, reason: String| {
+            report.problems.push(BulkAddProblem {
+                line: n + 1,
+                key: key.to_string(),
+                reason,
+            })
+        };
+        let Some((key, value)) = parse_bulk_line(line.trim_start()) else {
+            problem(line.trim(), tr!("bulk.no_value"));
+            continue;
+        };
+        if let Err(e) = validate_key(key) {
+            problem(key, e);
+        } else if existing.contains(key) {
+            problem(key, tr!("bulk.exists", key = key));
+        } else if !seen.insert(key) {
+            problem(key, tr!("bulk.duplicate", key = key));
+        } else {
+            picked.push((key.to_string(), value.to_string()));
+        }
+    }
+    if !report.problems.is_empty() || picked.is_empty() {
+        return Ok(report);
+    }
+
+    rollback::backup_before(&app, "bulk-add", &[Target::Document(doc_id)])?;
+    session.edit(doc_id, |open| {
+        // 已经在批量操作里时并入那一批
+        let own_batch = !open.in_batch();
+        if own_batch {
+            open.begin_batch()?;
+        }
+        for (key, value) in picked {
+            // 与单条添加相同的检查；有一条不能加就整批撤回
+            if let Err(e) = set_entry_checked(open, &collab, doc_id, key.clone(), value) {
+                if own_batch {
+                    open.rollback_batch()?;
+                }
+                return Err(e);
+            }
+            report.added.push(key);
+        }
+        if own_batch {
+            open.commit_batch()?;
+        }
+        Ok(report)
+    })
+}
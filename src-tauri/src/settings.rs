@@ -0,0 +1,230 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::i18n::tr;
+use gxt_core::Transliteration;
+
+use crate::collation::CollationSettings;
+use crate::doc_profile::DocumentProfile;
+use crate::mirrors::MirrorFormat;
+use crate::mt::MtConfig;
+use crate::search::SearchHistoryEntry;
+use crate::templates::EntryTemplate;
+use crate::views::SavedView;
+
+const SETTINGS_FILE: &str = "settings.json";
+/// 旧版本单独保存的机翻配置；第一次读设置时并入
+const LEGACY_MT_FILE: &str = "mt.json";
+
+/// 设置文件格式版本；结构有不兼容的变化时加一，并在 migrate 里补上升级步骤
+pub const SETTINGS_VERSION: u32 = 1;
+
+/// 设置被修改后推给所有窗口（payload 为新设置）
+pub const EVENT_SETTINGS_CHANGED: &str = "gxt://settings-changed";
+
+/// 编码方案：保存前按 char_map 把字符替换成游戏字体里对应的字形（汉化字库等）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EncodingProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// 原字符 -> 写入文件的字符
+    #[serde(default)]
+    pub char_map: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Off,
+    Warning,
+    Error,
+}
+
+/// 保存时留快照的策略（见 snapshots.rs）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupPolicy {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 每个文件保留几份备份
+    #[serde(default = "default_keep")]
+    pub keep: usize,
+    /// 为空时放在 <app data>/snapshots
+    #[serde(default)]
+    pub dir: Option<String>,
+}
+
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        BackupPolicy {
+            enabled: true,
+            keep: default_keep(),
+            dir: None,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+fn default_keep() -> usize {
+    5
+}
+fn default_version() -> u32 {
+    SETTINGS_VERSION
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settings {
+    #[serde(default = "default_version")]
+    pub version: u32,
+    #[serde(default)]
+    pub encoding_profiles: Vec<EncodingProfile>,
+    /// 校验规则 id -> 严重程度；没列出的规则用内置默认
+    #[serde(default)]
+    pub validation: BTreeMap<String, Severity>,
+    #[serde(default)]
+    pub backup: BackupPolicy,
+    #[serde(default)]
+    pub mt: Option<MtConfig>,
+    /// 界面语言（BCP 47），后端消息也用这个语言
+    #[serde(default)]
+    pub locale: Option<String>,
+    /// 记在条目修改记录里的作者名
+    #[serde(default)]
+    pub author: Option<String>,
+    /// 保存的筛选视图
+    #[serde(default)]
+    pub views: Vec<SavedView>,
+    /// 确定性输出：保存时总按 key 的字节序排序，同样的内容在任何机器上都写出同样的字节
+    /// （生成的 GXT 提交进 git 时不产生无意义的差异）
+    #[serde(default)]
+    pub deterministic_save: bool,
+    /// 条目模板
+    #[serde(default)]
+    pub templates: Vec<EntryTemplate>,
+    /// 自定义音译表（同名的覆盖内置表）
+    #[serde(default)]
+    pub transliterations: Vec<Transliteration>,
+    /// 最近的搜索/替换（最近的在前）
+    #[serde(default)]
+    pub search_history: Vec<SearchHistoryEntry>,
+    /// 文件路径 -> 打开/保存这个文件时用的预设、编码方案与代码页
+    #[serde(default)]
+    pub document_profiles: BTreeMap<String, DocumentProfile>,
+    /// 表格与视图按 value 排序时的排序规则
+    #[serde(default)]
+    pub collation: CollationSettings,
+    /// 批量导出的文件名模板（见 export_names.rs）；为空时用内置的
+    #[serde(default)]
+    pub export_name_template: Option<String>,
+    /// 保存 GXT 时在旁边重新生成的可读副本（见 mirrors.rs）
+    #[serde(default)]
+    pub export_mirrors: Vec<MirrorFormat>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            version: SETTINGS_VERSION,
+            encoding_profiles: Vec::new(),
+            validation: BTreeMap::new(),
+            backup: BackupPolicy::default(),
+            mt: None,
+            locale: None,
+            author: None,
+            views: Vec::new(),
+            deterministic_save: false,
+            templates: Vec::new(),
+            transliterations: Vec::new(),
+            search_history: Vec::new(),
+            document_profiles: BTreeMap::new(),
+            collation: CollationSettings::default(),
+            export_name_template: None,
+            export_mirrors: Vec::new(),
+        }
+    }
+}
+
+fn config_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_config_dir()
+        .map_err(|e| format!("Resolve config dir failed: {e}"))
+}
+
+/// 把旧版本的设置升级到当前版本
+fn migrate(settings: Settings) -> Result<Settings, String> {
+    if settings.version > SETTINGS_VERSION {
+        return Err(tr!("settings.too_new", version = settings.version));
+    }
+    Ok(Settings {
+        version: SETTINGS_VERSION,
+        ..settings
+    })
+}
+
+/// 读设置；文件不存在时是默认设置（并入旧的 mt.json）
+pub fn load(app: &AppHandle) -> Result<Settings, String> {
+    let dir = config_dir(app)?;
+    match fs::read(dir.join(SETTINGS_FILE)) {
+        Ok(bytes) => {
+            let s: Settings =
+                serde_json::from_slice(&bytes).map_err(|e| tr!("settings.invalid", error = e))?;
+            migrate(s)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let mt = fs::read(dir.join(LEGACY_MT_FILE))
+                .ok()
+                .and_then(|b| serde_json::from_slice(&b).ok());
+            Ok(Settings {
+                mt,
+                ..Settings::default()
+            })
+        }
+        Err(e) => Err(tr!("settings.read_failed", error = e)),
+    }
+}
+
+/// 写设置：先写临时文件再改名，写到一半崩溃也不会留下损坏的设置
+pub fn save(app: &AppHandle, settings: &Settings) -> Result<(), String> {
+    let dir = config_dir(app)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("Create config dir failed: {e}"))?;
+    let settings = Settings {
+        version: SETTINGS_VERSION,
+        ..settings.clone()
+    };
+    let bytes =
+        serde_json::to_vec_pretty(&settings).map_err(|e| tr!("json.encode_failed", error = e))?;
+    let path = dir.join(SETTINGS_FILE);
+    let tmp = path.with_extension("json.saving");
+    fs::write(&tmp, bytes).map_err(|e| tr!("settings.write_failed", error = e))?;
+    fs::rename(&tmp, &path).map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        tr!("settings.write_failed", error = e)
+    })?;
+    let _ = app.emit(EVENT_SETTINGS_CHANGED, &settings);
+    Ok(())
+}
+
+/// 读-改-写
+pub fn update(app: &AppHandle, f: impl FnOnce(&mut Settings)) -> Result<(), String> {
+    let mut settings = load(app)?;
+    f(&mut settings);
+    save(app, &settings)
+}
+
+#[tauri::command]
+pub fn gxt_get_settings(app: AppHandle) -> Result<Settings, String> {
+    load(&app)
+}
+
+/// 整体替换设置
+#[tauri::command]
+pub fn gxt_set_settings(app: AppHandle, settings: Settings) -> Result<(), String> {
+    save(&app, &migrate(settings)?)
+}
@@ -0,0 +1,219 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashSet;
+use std::fs;
+
+use tauri::AppHandle;
+
+use crate::gxt::validate_key;
+use crate::i18n::tr;
+use crate::merge_report::{self, Decision, MergeReport};
+use crate::notes::store_notes;
+use crate::presets;
+use crate::rollback::{self, Target};
+use crate::session::{DocId, Session};
+use crate::tms::delimited_records;
+
+/// 译者发回的表格：直接粘贴的文本，或导出的 .csv / .tsv 文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum SheetSource {
+    Text { text: String },
+    File { path: String },
+}
+
+impl SheetSource {
+    fn read(&self) -> Result<String, String> {
+        match self {
+            SheetSource::Text { text } => Ok(text.clone()),
+            SheetSource::File { path } => {
+                fs::read_to_string(path).map_err(|e| tr!("file.read_failed", error = e))
+            }
+        }
+    }
+}
+
+fn default_fuzzy_statuses() -> Vec<String> {
+    vec!["fuzzy".into(), "needs review".into(), "draft".into()]
+}
+
+/// 表格的哪一列对应哪个字段（列号从 0 开始）；没给的字段不导入
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ColumnMapping {
+    pub key: usize,
+    #[serde(default)]
+    pub value: Option<usize>,
+    #[serde(default)]
+    pub comment: Option<usize>,
+    #[serde(default)]
+    pub status: Option<usize>,
+    /// 第一行是表头，跳过
+    #[serde(default)]
+    pub header: bool,
+    /// 为空时猜：文本里有制表符就按制表符分，否则按逗号
+    #[serde(default)]
+    pub delimiter: Option<char>,
+    /// 状态列里这些值（不分大小写）标记为 fuzzy，其它非空值清除 fuzzy，空着的不动
+    #[serde(default = "default_fuzzy_statuses")]
+    pub fuzzy_statuses: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SheetImport {
+    /// 写入了译文的 key
+    pub updated: Vec<String>,
+    /// 新加的 key（add_new 时）
+    pub added: Vec<String>,
+    /// 写了注释的条数
+    pub notes: usize,
+    /// 按状态列标为 fuzzy / 清除 fuzzy 的条数
+    pub fuzzy_set: usize,
+    pub fuzzy_cleared: usize,
+    /// 文档里没有、没有导入的 key
+    pub unknown_keys: Vec<String>,
+    /// 不能用的 key 与原因
+    pub invalid: Vec<(String, String)>,
+    /// 锁定着、没有导入的 key
+    pub locked: Vec<String>,
+    /// 文档里软删除过、没有加回来的 key（add_new 时）
+    pub deleted: Vec<String>,
+}
+
+fn guess_delimiter(text: &str) -> char {
+    if text.contains('\t') {
+        '\t'
+    } else {
+        ','
+    }
+}
+
+/// 按分隔符拆开表格的前 rows 行（默认 20），给前端选列用
+#[tauri::command]
+pub fn gxt_sheet_preview(
+    source: SheetSource,
+    delimiter: Option<char>,
+    rows: Option<usize>,
+) -> Result<Vec<Vec<String>>, String> {
+    let text = source.read()?;
+    let delimiter = delimiter.unwrap_or_else(|| guess_delimiter(&text));
+    let mut records = delimited_records(&text, delimiter)?;
+    records.truncate(rows.unwrap_or(20));
+    Ok(records)
+}
+
+/// 按列映射把表格导入文档（整批算一步撤销）：空的译文单元格不动原值，
+/// 注释写进条目注释，状态列决定 fuzzy 标记。add_new 时文档里没有的 key 作为新条目加入。
+/// 预设的 key 规则开了 normalize_on_import 时，表格里的 key 先转成要求的大小写
+#[tauri::command]
+pub fn gxt_sheet_import(
+    app: AppHandle,
+    doc_id: DocId,
+    source: SheetSource,
+    mapping: ColumnMapping,
+    add_new: Option<bool>,
+    session: tauri::State<'_, Session>,
+) -> Result<SheetImport, String> {
+    let text = source.read()?;
+    let delimiter = mapping.delimiter.unwrap_or_else(|| guess_delimiter(&text));
+    let records = delimited_records(&text, delimiter)?;
+    let add_new = add_new.unwrap_or(false);
+    let file_path = session.edit(doc_id, |open| Ok(open.doc.file_path.clone()))?;
+    let newline = presets::newline_token(&app, file_path.as_deref());
+    let key_rules = presets::key_rules(&app, file_path.as_deref());
+    let fuzzy_statuses: Vec<String> = mapping
+        .fuzzy_statuses
+        .iter()
+        .map(|s| s.trim().to_lowercase())
+        .collect();
+
+    let sheet_path = match &source {
+        SheetSource::File { path } => Some(path.clone()),
+        SheetSource::Text { .. } => None,
+    };
+
+    rollback::backup_before(&app, "sheet-import", &[Target::Document(doc_id)])?;
+    let (report, audit) = session.edit(doc_id, |open| {
+        let own_batch = !open.in_batch();
+        if own_batch {
+            open.begin_batch()?;
+        }
+        let mut existing: HashSet<String> =
+            open.doc.entries.iter().map(|e| e.key.clone()).collect();
+        let mut report = SheetImport::default();
+        let mut audit = MergeReport::new("sheet-import", open.doc.file_path.clone(), sheet_path);
+        let cell = |row: &[String], col: Option<usize>| {
+            col.and_then(|c| row.get(c))
+                .map(|s| s.trim_end_matches('\r').to_string())
+                .filter(|s| !s.trim().is_empty())
+        };
+        for row in records.iter().skip(usize::from(mapping.header)) {
+            let Some(key) = cell(row, Some(mapping.key)) else {
+                continue;
+            };
+            let key = key_rules.import_key(key.trim());
+            if !existing.contains(&key) {
+                if !add_new {
+                    audit.push(&key, Decision::Skipped, Some("not in document".into()));
+                    report.unknown_keys.push(key);
+                    continue;
+                }
+                if let Err(e) = validate_key(&key) {
+                    audit.push(&key, Decision::Skipped, Some(e.clone()));
+                    report.invalid.push((key, e));
+                    continue;
+                }
+                if open.is_tombstoned(&key) {
+                    audit.push(&key, Decision::Skipped, Some("deleted".into()));
+                    report.deleted.push(key);
+                    continue;
+                }
+            }
+            if open.is_locked(&key) {
+                audit.push(&key, Decision::Kept, Some("locked".into()));
+                report.locked.push(key);
+                continue;
+            }
+            if let Some(value) = cell(row, mapping.value) {
+                let value = match &newline {
+                    Some(token) => gxt_core::newlines_to_token(&value, token),
+                    None => value,
+                };
+                open.set_entry(key.clone(), value);
+                if existing.insert(key.clone()) {
+                    audit.push(&key, Decision::Added, None);
+                    report.added.push(key.clone());
+                } else {
+                    audit.push(&key, Decision::Overwritten, None);
+                    report.updated.push(key.clone());
+                }
+            }
+            if !existing.contains(&key) {
+                // 新 key 但没有译文：不凭空建条目
+                audit.push(&key, Decision::Skipped, Some("no translation".into()));
+                report.unknown_keys.push(key);
+                continue;
+            }
+            if let Some(comment) = cell(row, mapping.comment) {
+                open.notes.insert(key.clone(), comment);
+                report.notes += 1;
+            }
+            if let Some(status) = cell(row, mapping.status) {
+                if fuzzy_statuses.contains(&status.trim().to_lowercase()) {
+                    open.fuzzy.insert(key);
+                    report.fuzzy_set += 1;
+                } else if open.fuzzy.remove(&key) {
+                    report.fuzzy_cleared += 1;
+                }
+            }
+        }
+        if own_batch {
+            open.commit_batch()?;
+        }
+        if report.notes > 0 {
+            store_notes(open)?;
+        }
+        Ok((report, audit))
+    })?;
+    merge_report::record(&app, &audit);
+    Ok(report)
+}
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+
+/// 资源管理器集成：`.gxt` 文件关联、“用 GXT Editor 打开”右键菜单与 gxt:// 链接。
+/// 只写当前用户的注册表（HKCU\Software\Classes），不需要管理员权限
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct ShellIntegration {
+    /// 双击 .gxt 用本程序打开
+    pub file_association: bool,
+    /// .gxt 的右键菜单里加一项（不改默认打开方式）
+    pub context_menu: bool,
+    /// 点 `gxt://open?path=...&key=...` 链接时用本程序打开（见 deep_link）
+    #[serde(default)]
+    pub url_protocol: bool,
+}
+
+/// 注册选中的项；已注册的会改成指向当前这个程序
+#[tauri::command]
+pub fn gxt_shell_register(options: ShellIntegration) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Locate executable failed: {e}"))?;
+    let exe = exe.to_string_lossy();
+    if options.file_association {
+        platform::register_association(&exe)?;
+    }
+    if options.context_menu {
+        platform::register_context_menu(&exe)?;
+    }
+    if options.url_protocol {
+        platform::register_protocol(&exe)?;
+    }
+    platform::notify_changed();
+    Ok(())
+}
+
+/// 撤掉选中的项（.gxt 的默认打开方式只在仍指向本程序时才清掉）
+#[tauri::command]
+pub fn gxt_shell_unregister(options: ShellIntegration) -> Result<(), String> {
+    if options.file_association {
+        platform::unregister_association()?;
+    }
+    if options.context_menu {
+        platform::unregister_context_menu()?;
+    }
+    if options.url_protocol {
+        platform::unregister_protocol()?;
+    }
+    platform::notify_changed();
+    Ok(())
+}
+
+/// 当前注册状态（只有指向当前这个程序的才算）
+#[tauri::command]
+pub fn gxt_shell_status() -> Result<ShellIntegration, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Locate executable failed: {e}"))?;
+    platform::status(&exe.to_string_lossy())
+}
+
+#[cfg(windows)]
+mod platform {
+    use std::ffi::c_void;
+    use std::ptr::{null, null_mut};
+
+    use windows_sys::Win32::Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS};
+    use windows_sys::Win32::System::Registry::{
+        RegCloseKey, RegCreateKeyExW, RegDeleteKeyValueW, RegDeleteTreeW, RegGetValueW,
+        RegSetValueExW, HKEY, HKEY_CURRENT_USER, KEY_WRITE, REG_OPTION_NON_VOLATILE, REG_SZ,
+        RRF_RT_REG_SZ,
+    };
+    use windows_sys::Win32::UI::Shell::{SHChangeNotify, SHCNE_ASSOCCHANGED, SHCNF_IDLIST};
+
+    use super::ShellIntegration;
+    use crate::deep_link::SCHEME;
+
+    const PROG_ID: &str = "GxtEditor.gxt";
+    const EXT_KEY: &str = r"Software\Classes\.gxt";
+    const PROG_KEY: &str = r"Software\Classes\GxtEditor.gxt";
+    const MENU_KEY: &str = r"Software\Classes\SystemFileAssociations\.gxt\shell\GxtEditor";
+
+    fn protocol_key() -> String {
+        format!(r"Software\Classes\{SCHEME}")
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        s.encode_utf16().chain(Some(0)).collect()
+    }
+
+    fn command_line(exe: &str) -> String {
+        format!("\"{exe}\" \"%1\"")
+    }
+
+    /// 建好 HKCU 下的 key 并写一个字符串值（name 为空写默认值）
+    fn set_value(key: &str, name: &str, value: &str) -> Result<(), String> {
+        let mut hkey: HKEY = null_mut();
+        let rc = unsafe {
+            RegCreateKeyExW(
+                HKEY_CURRENT_USER,
+                wide(key).as_ptr(),
+                0,
+                null(),
+                REG_OPTION_NON_VOLATILE,
+                KEY_WRITE,
+                null(),
+                &mut hkey,
+                null_mut(),
+            )
+        };
+        if rc != ERROR_SUCCESS {
+            return Err(format!("Create registry key {key} failed: error {rc}"));
+        }
+        let data = wide(value);
+        let name = (!name.is_empty()).then(|| wide(name));
+        let rc = unsafe {
+            let rc = RegSetValueExW(
+                hkey,
+                name.as_ref().map_or(null(), |n| n.as_ptr()),
+                0,
+                REG_SZ,
+                data.as_ptr().cast(),
+                (data.len() * 2) as u32,
+            );
+            RegCloseKey(hkey);
+            rc
+        };
+        if rc != ERROR_SUCCESS {
+            return Err(format!("Write registry value {key} failed: error {rc}"));
+        }
+        Ok(())
+    }
+
+    /// HKCU 下 key 的默认值；不存在时为 None
+    fn get_default(key: &str) -> Option<String> {
+        let key = wide(key);
+        let mut buf = vec![0u16; 1024];
+        let mut size = (buf.len() * 2) as u32;
+        let rc = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                key.as_ptr(),
+                null(),
+                RRF_RT_REG_SZ,
+                null_mut(),
+                buf.as_mut_ptr() as *mut c_void,
+                &mut size,
+            )
+        };
+        if rc != ERROR_SUCCESS {
+            return None;
+        }
+        let len = (size as usize / 2).saturating_sub(1);
+        Some(String::from_utf16_lossy(&buf[..len]))
+    }
+
+    fn delete_tree(key: &str) -> Result<(), String> {
+        let rc = unsafe { RegDeleteTreeW(HKEY_CURRENT_USER, wide(key).as_ptr()) };
+        if rc != ERROR_SUCCESS && rc != ERROR_FILE_NOT_FOUND {
+            return Err(format!("Delete registry key {key} failed: error {rc}"));
+        }
+        Ok(())
+    }
+
+    pub fn register_association(exe: &str) -> Result<(), String> {
+        set_value(PROG_KEY, "", "GTA Text File")?;
+        set_value(
+            &format!(r"{PROG_KEY}\DefaultIcon"),
+            "",
+            &format!("\"{exe}\",0"),
+        )?;
+        set_value(
+            &format!(r"{PROG_KEY}\shell\open\command"),
+            "",
+            &command_line(exe),
+        )?;
+        set_value(EXT_KEY, "", PROG_ID)
+    }
+
+    pub fn unregister_association() -> Result<(), String> {
+        if get_default(EXT_KEY).as_deref() == Some(PROG_ID) {
+            let rc =
+                unsafe { RegDeleteKeyValueW(HKEY_CURRENT_USER, wide(EXT_KEY).as_ptr(), null()) };
+            if rc != ERROR_SUCCESS && rc != ERROR_FILE_NOT_FOUND {
+                return Err(format!(
+                    "Delete registry value {EXT_KEY} failed: error {rc}"
+                ));
+            }
+        }
+        delete_tree(PROG_KEY)
+    }
+
+    pub fn register_context_menu(exe: &str) -> Result<(), String> {
+        set_value(MENU_KEY, "", "Open with GXT Editor")?;
+        set_value(MENU_KEY, "Icon", &format!("\"{exe}\",0"))?;
+        set_value(&format!(r"{MENU_KEY}\command"), "", &command_line(exe))
+    }
+
+    pub fn unregister_context_menu() -> Result<(), String> {
+        delete_tree(MENU_KEY)
+    }
+
+    pub fn register_protocol(exe: &str) -> Result<(), String> {
+        let key = protocol_key();
+        set_value(&key, "", "URL:GXT Editor link")?;
+        // 有这个值资源管理器与浏览器才把它当成 URL 协议
+        set_value(&key, "URL Protocol", "")?;
+        set_value(&format!(r"{key}\DefaultIcon"), "", &format!("\"{exe}\",0"))?;
+        set_value(
+            &format!(r"{key}\shell\open\command"),
+            "",
+            &command_line(exe),
+        )
+    }
+
+    pub fn unregister_protocol() -> Result<(), String> {
+        delete_tree(&protocol_key())
+    }
+
+    pub fn status(exe: &str) -> Result<ShellIntegration, String> {
+        let command = Some(command_line(exe));
+        Ok(ShellIntegration {
+            file_association: get_default(EXT_KEY).as_deref() == Some(PROG_ID)
+                && get_default(&format!(r"{PROG_KEY}\shell\open\command")) == command,
+            context_menu: get_default(&format!(r"{MENU_KEY}\command")) == command,
+            url_protocol: get_default(&format!(r"{}\shell\open\command", protocol_key()))
+                == command,
+        })
+    }
+
+    /// 让资源管理器重新读取文件关联（否则图标与双击行为要等重新登录才更新）
+    pub fn notify_changed() {
+        unsafe { SHChangeNotify(SHCNE_ASSOCCHANGED, SHCNF_IDLIST, null(), null()) }
+    }
+}
+
+#[cfg(not(windows))]
+mod platform {
+    use super::ShellIntegration;
+
+    const UNSUPPORTED: &str = "File association is only supported on Windows";
+
+    pub fn register_association(_exe: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn unregister_association() -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn register_context_menu(_exe: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn unregister_context_menu() -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn register_protocol(_exe: &str) -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn unregister_protocol() -> Result<(), String> {
+        Err(UNSUPPORTED.into())
+    }
+
+    pub fn status(_exe: &str) -> Result<ShellIntegration, String> {
+        Ok(ShellIntegration::default())
+    }
+
+    pub fn notify_changed() {}
+}
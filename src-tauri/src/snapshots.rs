@@ -0,0 +1,159 @@
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use gxt_core::EntryDiff;
+use tauri::{AppHandle, Manager};
+
+use crate::gxt;
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+use crate::settings;
+
+const SNAPSHOT_DIR: &str = "snapshots";
+const SNAPSHOT_EXT: &str = "gxt";
+/// 每个文件一个子目录，里面记下原文件路径
+const SOURCE_FILE: &str = "source.txt";
+
+/// 一份保存时留下的快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// `<文件目录>/<毫秒时间戳>`，传给 diff / restore
+    pub id: String,
+    pub file_path: String,
+    /// 保存时间（Unix 毫秒）
+    pub created: u64,
+    pub size: u64,
+}
+
+/// 快照根目录：设置里 backup.dir，缺省为 <app data>/snapshots
+fn root(app: &AppHandle, dir: Option<String>) -> Result<PathBuf, String> {
+    match dir {
+        Some(d) => Ok(PathBuf::from(d)),
+        None => app
+            .path()
+            .app_data_dir()
+            .map(|d| d.join(SNAPSHOT_DIR))
+            .map_err(|e| format!("Resolve app data dir failed: {e}")),
+    }
+}
+
+/// 同一文件的快照放在以路径哈希命名的子目录里
+fn file_dir(root: &Path, file_path: &str) -> PathBuf {
+    root.join(format!("{:08x}", gxt_core::crc32_key(file_path)))
+}
+
+fn list_dir(dir: &Path, file_path: &str) -> Vec<Snapshot> {
+    let name = dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+        .to_string();
+    let mut out: Vec<Snapshot> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|item| {
+            let path = item.path();
+            if path.extension().is_none_or(|x| x != SNAPSHOT_EXT) {
+                return None;
+            }
+            let created: u64 = path.file_stem()?.to_str()?.parse().ok()?;
+            Some(Snapshot {
+                id: format!("{name}/{created}"),
+                file_path: file_path.to_string(),
+                created,
+                size: item.metadata().map(|m| m.len()).unwrap_or(0),
+            })
+        })
+        .collect();
+    // 从新到旧
+    out.sort_by_key(|s| Reverse(s.created));
+    out
+}
+
+/// 保存成功后调用：把刚写好的文件复制一份快照，超出设置的份数时删掉最旧的。
+/// 快照失败不影响保存，只记日志
+pub fn record(app: &AppHandle, file_path: &str) {
+    let policy = settings::load(app).map(|s| s.backup).unwrap_or_default();
+    if !policy.enabled || policy.keep == 0 {
+        return;
+    }
+    let res = root(app, policy.dir).and_then(|root| {
+        let dir = file_dir(&root, file_path);
+        fs::create_dir_all(&dir).map_err(|e| tr!("file.write_failed", error = e))?;
+        fs::write(dir.join(SOURCE_FILE), file_path)
+            .map_err(|e| tr!("file.write_failed", error = e))?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        fs::copy(file_path, dir.join(format!("{now}.{SNAPSHOT_EXT}")))
+            .map_err(|e| tr!("file.write_failed", error = e))?;
+        for old in list_dir(&dir, file_path).into_iter().skip(policy.keep) {
+            let _ = fs::remove_file(dir.join(format!("{}.{SNAPSHOT_EXT}", old.created)));
+        }
+        Ok(())
+    });
+    if let Err(e) = res {
+        tracing::warn!(path = %file_path, error = %e, "snapshot failed");
+    }
+}
+
+/// 快照 id 对应的文件（防止 id 里带路径跳出快照目录）
+fn snapshot_path(app: &AppHandle, id: &str) -> Result<PathBuf, String> {
+    let (dir, stamp) = id
+        .split_once('/')
+        .filter(|(d, s)| {
+            d.bytes().all(|b| b.is_ascii_hexdigit()) && s.bytes().all(|b| b.is_ascii_digit())
+        })
+        .ok_or_else(|| format!("Bad snapshot id: {id}"))?;
+    let policy = settings::load(app)?.backup;
+    Ok(root(app, policy.dir)?
+        .join(dir)
+        .join(format!("{stamp}.{SNAPSHOT_EXT}")))
+}
+
+/// 某个文件的快照，从新到旧
+#[tauri::command]
+pub fn gxt_snapshot_list(app: AppHandle, file_path: String) -> Result<Vec<Snapshot>, String> {
+    let policy = settings::load(&app)?.backup;
+    let root = root(&app, policy.dir)?;
+    Ok(list_dir(&file_dir(&root, &file_path), &file_path))
+}
+
+/// 快照与文档当前内容（含未保存修改）的差异：old 为快照，new 为文档
+#[tauri::command]
+pub async fn gxt_snapshot_diff(
+    app: AppHandle,
+    doc_id: DocId,
+    id: String,
+    session: tauri::State<'_, Session>,
+) -> Result<EntryDiff, String> {
+    let path = snapshot_path(&app, &id)?;
+    let snapshot = gxt::load_document(path.to_string_lossy().into_owned()).await?;
+    session.with(doc_id, |open| {
+        Ok(gxt_core::diff_entries(&snapshot.entries, &open.doc.entries))
+    })
+}
+
+/// 把文档内容换成快照里的（文档变为未保存状态，不直接覆盖磁盘上的文件；锁定的条目不变）
+#[tauri::command]
+pub async fn gxt_snapshot_restore(
+    app: AppHandle,
+    doc_id: DocId,
+    id: String,
+    session: tauri::State<'_, Session>,
+) -> Result<gxt::GxtDocument, String> {
+    let path = snapshot_path(&app, &id)?;
+    let snapshot = gxt::load_document(path.to_string_lossy().into_owned()).await?;
+    session.edit(doc_id, |open| {
+        let mut doc = open.doc.clone();
+        doc.entries = snapshot.entries;
+        open.replace_entries(doc);
+        Ok(open.doc.clone())
+    })
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use gxt_core::SourceUpdate;
+use tauri::AppHandle;
+
+use crate::gxt::{self, GxtEntry};
+use crate::merge_report::{self, Decision, MergeReport};
+use crate::rollback::{self, Target};
+use crate::session::{DocId, Session};
+
+/// 原文 mod 更新后，把新原文合并进指定的已打开译文（msgmerge 流程）。
+///
+/// old_source_path / new_source_path 为更新前后的原文 .gxt。
+/// 给了 archive_path 时，被删掉的条目以 `KEY=value` 文本追加到该文件
+/// （同 key 以本次为准），以便日后原文把它加回来时还能找回旧译文。
+#[tauri::command]
+pub async fn gxt_source_update(
+    app: AppHandle,
+    doc_id: DocId,
+    old_source_path: String,
+    new_source_path: String,
+    archive_path: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<SourceUpdate, String> {
+    let old_source = gxt::load_document(old_source_path).await?;
+    let new_source = gxt::load_document(new_source_path.clone()).await?;
+
+    let (mut update, deleted) = session.with(doc_id, |open| {
+        let mut update = gxt_core::update_translation(
+            &old_source.entries,
+            &new_source.entries,
+            &open.doc.entries,
+        );
+        // 软删除过的 key 在新原文里仍有时不加回来
+        let (deleted, added): (Vec<String>, Vec<String>) = update
+            .added
+            .into_iter()
+            .partition(|k| open.is_tombstoned(k));
+        update.added = added;
+        update.entries.retain(|e| !open.is_tombstoned(&e.key));
+        gxt::validate_entries(&update.entries)?;
+        Ok((update, deleted))
+    })?;
+
+    rollback::backup_before(&app, "source-update", &[Target::Document(doc_id)])?;
+    if let Some(path) = &archive_path {
+        if !update.archived.is_empty() {
+            write_archive(Path::new(path), &update.archived)?;
+        }
+    }
+
+    let (entries, audit) = session.edit(doc_id, |open| {
+        let mut audit = MergeReport::new(
+            "source-update",
+            open.doc.file_path.clone(),
+            Some(new_source_path),
+        );
+        for k in &update.added {
+            audit.push(k, Decision::Added, None);
+        }
+        for k in &deleted {
+            audit.push(k, Decision::Skipped, Some("deleted".into()));
+        }
+        for e in &update.archived {
+            let archived = archive_path.as_ref().map(|p| format!("archived to {p}"));
+            audit.push(&e.key, Decision::Removed, archived);
+        }
+        let mut doc = open.doc.clone();
+        doc.entries = update.entries.clone();
+        open.replace_entries(doc);
+        for k in &update.fuzzy {
+            if open.is_locked(k) {
+                audit.push(k, Decision::Kept, Some("locked".into()));
+            } else {
+                audit.push(
+                    k,
+                    Decision::Kept,
+                    Some("source changed, marked fuzzy".into()),
+                );
+                open.fuzzy.insert(k.clone());
+            }
+        }
+        // 锁定的条目保持原样，返回替换后的实际内容
+        Ok((open.doc.entries.clone(), audit))
+    })?;
+    update.entries = entries;
+    merge_report::record(&app, &audit);
+    Ok(update)
+}
+
+fn write_archive(path: &Path, archived: &[GxtEntry]) -> Result<(), String> {
+    let mut entries = match fs::read_to_string(path) {
+        Ok(text) => gxt_core::entries_from_text(&text)
+            .map_err(|e| format!("Archive {} is not a text project: {e}", path.display()))?,
+        Err(_) => Vec::new(),
+    };
+    let mut pos: HashMap<String, usize> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, e)| (e.key.clone(), i))
+        .collect();
+    for e in archived {
+        match pos.get(&e.key) {
+            Some(&i) => entries[i] = e.clone(),
+            None => {
+                pos.insert(e.key.clone(), entries.len());
+                entries.push(e.clone());
+            }
+        }
+    }
+    let text = gxt_core::entries_to_text(&entries)?;
+    fs::write(path, text).map_err(|e| format!("Write archive failed: {e}"))
+}
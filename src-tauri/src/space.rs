@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+
+use gxt_core::{GroupLevel, OverlapRepair, OverlappingKey, SpaceReport, KEY_RECORD_BYTES};
+use tauri::AppHandle;
+
+use crate::doc_profile;
+use crate::gxt::GxtEntry;
+use crate::i18n::tr;
+use crate::presets;
+use crate::session::{DocId, Session};
+use crate::snapshots;
+use crate::translit;
+
+/// TKEY / TDAT 段头：4 字节标识 + 4 字节长度
+const SECTION_HEADER_BYTES: usize = 8;
+
+fn read(path: &str) -> Result<Vec<u8>, String> {
+    fs::read(path).map_err(|e| tr!("file.read_failed", error = e))
+}
+
+/// 分析文件里的空间浪费：没有 key 指向的字节、重复存放的 value，以及压缩后的大小
+#[tauri::command]
+pub async fn gxt_space_analysis(path: String) -> Result<SpaceReport, String> {
+    tauri::async_runtime::spawn_blocking(move || gxt_core::analyze_space(&read(&path)?))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+/// 一键压缩：丢掉无主字节、相同 value 只存一份后写回（output 没给时覆盖原文件）。
+/// 条目内容与顺序不变；返回压缩后文件的分析结果
+#[tauri::command]
+pub async fn gxt_compact(
+    app: AppHandle,
+    path: String,
+    output: Option<String>,
+) -> Result<SpaceReport, String> {
+    let output = output.unwrap_or_else(|| path.clone());
+    let out = output.clone();
+    let report = tauri::async_runtime::spawn_blocking(move || {
+        let compacted = gxt_core::compact_gxt_bytes(&read(&path)?)?;
+        fs::write(&out, &compacted).map_err(|e| tr!("file.write_failed", error = e))?;
+        gxt_core::analyze_space(&compacted)
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+    tracing::info!(path = %output, bytes = report.file_size, "compacted");
+    snapshots::record(&app, &output);
+    Ok(report)
+}
+
+/// 指向别的 value 中间的 key（部分共用的文字）；编辑器里改其中一条，保存后它们就各自独立了
+#[tauri::command]
+pub async fn gxt_overlaps(path: String) -> Result<Vec<OverlappingKey>, String> {
+    tauri::async_runtime::spawn_blocking(move || gxt_core::find_overlaps(&read(&path)?))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+/// 修复部分共用的 value：保留共用（有意为之）或拆成各自独立的 value，同时像 gxt_compact 一样压缩。
+/// output 没给时覆盖原文件；返回写出的文件里仍然共用的 key
+#[tauri::command]
+pub async fn gxt_repair_overlaps(
+    app: AppHandle,
+    path: String,
+    repair: OverlapRepair,
+    output: Option<String>,
+) -> Result<Vec<OverlappingKey>, String> {
+    let output = output.unwrap_or_else(|| path.clone());
+    let out = output.clone();
+    let left = tauri::async_runtime::spawn_blocking(move || {
+        let repaired = gxt_core::repair_overlaps(&read(&path)?, repair)?;
+        fs::write(&out, &repaired).map_err(|e| tr!("file.write_failed", error = e))?;
+        gxt_core::find_overlaps(&repaired)
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))??;
+    tracing::info!(path = %output, ?repair, overlaps = left.len(), "repaired overlaps");
+    snapshots::record(&app, &output);
+    Ok(left)
+}
+
+/// 一张表（见 gxt_table_stats）在保存出的文件里占的字节
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableSize {
+    pub name: String,
+    pub entries: usize,
+    /// TKEY 里这张表的记录
+    pub tkey_bytes: usize,
+    /// TDAT 里这张表的 value
+    pub tdat_bytes: usize,
+}
+
+/// 一项上限还剩多少
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeLimit {
+    /// `tdat-offset`（TKEY 里的 offset 是 u32）、`embedded-section`（嵌在 exe 里的段原有的大小）、
+    /// `value-bytes`（预设的 max_value_bytes，按最长的一条算）
+    pub name: String,
+    pub limit: u64,
+    pub used: u64,
+    /// 为负即已超出
+    pub headroom: i64,
+    /// 占用最多的条目（value-bytes 时）
+    pub key: Option<String>,
+}
+
+impl SizeLimit {
+    fn new(name: &str, limit: u64, used: u64, key: Option<String>) -> Self {
+        SizeLimit {
+            name: name.to_string(),
+            limit,
+            used,
+            headroom: limit as i64 - used as i64,
+            key,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SizeEstimate {
+    pub entries: usize,
+    /// 含段头
+    pub tkey_bytes: usize,
+    pub tdat_bytes: usize,
+    /// 整个 GXT（嵌在 exe 里的为段的大小）
+    pub file_bytes: usize,
+    pub tables: Vec<TableSize>,
+    pub limits: Vec<SizeLimit>,
+}
+
+/// 按保存时的做法（音译、emoji 替换、代码页）算出文档现在保存会写出多大的 TKEY / TDAT，
+/// 什么都不写。按表细分，并给出离各项上限还有多少；编辑时可以随时调用看文件涨了多少
+#[tauri::command]
+pub async fn gxt_estimate_size(
+    app: AppHandle,
+    doc_id: DocId,
+    level: Option<GroupLevel>,
+    session: tauri::State<'_, Session>,
+) -> Result<SizeEstimate, String> {
+    let doc = session.with(doc_id, |open| Ok(open.doc.clone()))?;
+    let (written, _) = translit::for_save(&app, &doc);
+    let max_value_bytes =
+        translit::save_preset(&app, doc.file_path.as_deref()).and_then(|p| p.max_value_bytes);
+    let level = level.unwrap_or(GroupLevel::Until('_'));
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let encoded = written
+            .file_path
+            .as_deref()
+            .and_then(|p| doc_profile::encode_entries(p, &written.entries));
+        let entries = encoded.as_deref().unwrap_or(&written.entries);
+
+        let value_bytes = |e: &GxtEntry| gxt_core::encoded_utf16z_len(&e.value);
+        let mut tables = Vec::new();
+        for (name, part) in gxt_core::split_tables(entries, &level) {
+            let tdat_bytes = part.iter().map(value_bytes).sum::<Result<usize, _>>()?;
+            tables.push(TableSize {
+                name,
+                entries: part.len(),
+                tkey_bytes: part.len() * KEY_RECORD_BYTES,
+                tdat_bytes,
+            });
+        }
+        let data: usize = tables.iter().map(|t| t.tdat_bytes).sum();
+        let tkey_bytes = SECTION_HEADER_BYTES + entries.len() * KEY_RECORD_BYTES;
+        let tdat_bytes = SECTION_HEADER_BYTES + data;
+        let file_bytes = tkey_bytes + tdat_bytes;
+
+        let mut limits = vec![SizeLimit::new(
+            "tdat-offset",
+            u64::from(u32::MAX),
+            data as u64,
+            None,
+        )];
+        if let Some(section) = written.embedded {
+            limits.push(SizeLimit::new(
+                "embedded-section",
+                section.len as u64,
+                file_bytes as u64,
+                None,
+            ));
+        }
+        if let Some(max) = max_value_bytes {
+            let mut largest: Option<(usize, &str)> = None;
+            for e in entries {
+                let n = value_bytes(e)?;
+                if largest.is_none_or(|(m, _)| n > m) {
+                    largest = Some((n, e.key.as_str()));
+                }
+            }
+            if let Some((n, key)) = largest {
+                limits.push(SizeLimit::new(
+                    "value-bytes",
+                    max as u64,
+                    n as u64,
+                    Some(key.to_string()),
+                ));
+            }
+        }
+        Ok(SizeEstimate {
+            entries: entries.len(),
+            tkey_bytes,
+            tdat_bytes,
+            file_bytes,
+            tables,
+            limits,
+        })
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+/// 导出 GXT 的结构图（每个字段的 offset、长度与所属 key），给用十六进制编辑器查问题的人。
+/// 给了 doc_id 时按现在保存会写出的字节（排序、音译、代码页同保存），否则读 path 指向的文件；
+/// output 的扩展名为 .bt 时写 010 Editor 模板，否则写 JSON
+#[tauri::command]
+pub async fn gxt_layout_export(
+    app: AppHandle,
+    doc_id: Option<DocId>,
+    path: Option<String>,
+    output: String,
+    session: tauri::State<'_, Session>,
+) -> Result<usize, String> {
+    let (title, bytes) = match doc_id {
+        Some(id) => {
+            let mut doc = session.with(id, |open| Ok(open.doc.clone()))?;
+            presets::sort_for_save(&app, &mut doc);
+            let (written, _) = translit::for_save(&app, &doc);
+            let encoded = written
+                .file_path
+                .as_deref()
+                .and_then(|p| doc_profile::encode_entries(p, &written.entries));
+            let bytes = gxt_core::build_gxt_bytes(encoded.as_deref().unwrap_or(&written.entries))?;
+            (doc.file_path.unwrap_or_else(|| "(unsaved)".into()), bytes)
+        }
+        None => {
+            let path = path.ok_or("Either doc_id or path is required")?;
+            let bytes = read(&path)?;
+            (path, bytes)
+        }
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let items = gxt_core::gxt_layout(&bytes)?;
+        let out = if Path::new(&output)
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("bt"))
+        {
+            gxt_core::layout_template(&items, &title).into_bytes()
+        } else {
+            serde_json::to_vec_pretty(&items).map_err(|e| tr!("json.encode_failed", error = e))?
+        };
+        fs::write(&output, out).map_err(|e| tr!("file.write_failed", error = e))?;
+        Ok(items.len())
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use gxt_core::Segment;
+
+use crate::audio;
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+/// 没有时长信息时按字数估算：每个字符的毫秒数与最短显示时间
+const MS_PER_CHAR: u64 = 60;
+const MIN_DURATION_MS: u64 = 1500;
+/// 自动排布时相邻两条之间的间隔
+const GAP_MS: u64 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+/// 一条字幕的时间信息；start 缺省时紧跟上一条，时长缺省时取音频时长，再没有就按字数估算
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CueTiming {
+    pub key: String,
+    #[serde(default)]
+    pub start_ms: Option<u64>,
+    #[serde(default)]
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubtitleExport {
+    pub cues: usize,
+    /// 时长是估算出来的 key（既没给时长也没找到音频）
+    pub estimated: Vec<String>,
+    /// 文档里没有的 key（跳过）
+    pub missing_keys: Vec<String>,
+}
+
+/// 字幕里显示的文字：~n~ 换行，其它 token 去掉，转义保留原字符
+fn display_text(value: &str) -> String {
+    let mut out = String::new();
+    for seg in gxt_core::segments(value) {
+        match seg {
+            Segment::Text(t) => out.push_str(t),
+            Segment::Token(t) if t.eq_ignore_ascii_case("~n~") => out.push('\n'),
+            Segment::Token(_) => {}
+            Segment::Escape(e) => out.push_str(e),
+        }
+    }
+    out.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn timestamp(ms: u64, format: SubtitleFormat) -> String {
+    let (h, m, s, milli) = (ms / 3_600_000, ms / 60_000 % 60, ms / 1000 % 60, ms % 1000);
+    let sep = match format {
+        SubtitleFormat::Srt => ',',
+        SubtitleFormat::Vtt => '.',
+    };
+    format!("{h:02}:{m:02}:{s:02}{sep}{milli:03}")
+}
+
+/// 把一组对白 key 导出为 SRT / WebVTT 字幕。
+///
+/// audio_dir 给了时，缺时长的条目去找 `<audio_dir>/<key>.wav`（不分大小写）取其时长。
+#[tauri::command]
+pub fn gxt_export_subtitles(
+    doc_id: DocId,
+    format: SubtitleFormat,
+    path: String,
+    timings: Vec<CueTiming>,
+    audio_dir: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<SubtitleExport, String> {
+    let values: HashMap<String, String> = session.with(doc_id, |open| {
+        Ok(open
+            .doc
+            .entries
+            .iter()
+            .map(|e| (e.key.clone(), e.value.to_string()))
+            .collect())
+    })?;
+
+    let wavs: HashMap<String, PathBuf> = match &audio_dir {
+        Some(dir) => fs::read_dir(dir)
+            .map_err(|e| format!("Read audio dir failed: {e}"))?
+            .flatten()
+            .map(|item| item.path())
+            .filter(|p| p.extension().is_some_and(|x| x.eq_ignore_ascii_case("wav")))
+            .filter_map(|p| {
+                let stem = p.file_stem()?.to_str()?.to_uppercase();
+                Some((stem, p))
+            })
+            .collect(),
+        None => HashMap::new(),
+    };
+
+    let mut out = String::new();
+    if format == SubtitleFormat::Vtt {
+        out.push_str("WEBVTT\n\n");
+    }
+    let mut result = SubtitleExport {
+        cues: 0,
+        estimated: Vec::new(),
+        missing_keys: Vec::new(),
+    };
+    let mut cursor = 0u64;
+
+    for t in &timings {
+        let Some(value) = values.get(&t.key) else {
+            result.missing_keys.push(t.key.clone());
+            continue;
+        };
+        let text = display_text(value);
+        let start = t.start_ms.unwrap_or(cursor);
+        let duration = t
+            .duration_ms
+            .or_else(|| {
+                wavs.get(&t.key.to_uppercase())
+                    .and_then(|p| audio::wav_duration_ms(p.as_path()))
+            })
+            .unwrap_or_else(|| {
+                result.estimated.push(t.key.clone());
+                (text.chars().count() as u64 * MS_PER_CHAR).max(MIN_DURATION_MS)
+            });
+        let end = start + duration;
+        cursor = end + GAP_MS;
+        result.cues += 1;
+
+        match format {
+            SubtitleFormat::Srt => out.push_str(&format!("{}\n", result.cues)),
+            // VTT 的 cue 标识用 key，方便回查
+            SubtitleFormat::Vtt => out.push_str(&format!("{}\n", t.key)),
+        }
+        out.push_str(&format!(
+            "{} --> {}\n{}\n\n",
+            timestamp(start, format),
+            timestamp(end, format),
+            text
+        ));
+    }
+
+    fs::write(&path, out).map_err(|e| tr!("file.write_failed", error = e))?;
+    Ok(result)
+}
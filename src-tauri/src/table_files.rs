@@ -0,0 +1,129 @@
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gxt_core::GroupLevel;
+use tauri::AppHandle;
+
+use crate::doc_profile;
+use crate::gxt::{self, GxtDocument};
+use crate::i18n::tr;
+use crate::presets;
+use crate::session::{DocId, Session};
+use crate::translit;
+
+/// 拆出来的一张表
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableFile {
+    pub name: String,
+    pub path: String,
+    pub entries: usize,
+}
+
+/// 目录下的 .gxt 文件（不递归），按路径排序
+fn table_paths(dir: &Path) -> Vec<PathBuf> {
+    let mut out: Vec<PathBuf> = fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|item| item.path())
+        .filter(|p| p.is_file() && p.extension().is_some_and(|x| x.eq_ignore_ascii_case("gxt")))
+        .collect();
+    out.sort();
+    out
+}
+
+/// 把文档按表（key 前缀，见 gxt_core::split_tables）拆成 dir 下每表一个单表 GXT（`<表名>.gxt`），
+/// 给只认单表文件的外部工具用。写法同保存：按预设排序、音译、按文档的编码配置编码。
+/// dir 里已有 .gxt 时报错，force 时先删掉（以免旧表在合回来时混进来）
+#[tauri::command]
+pub async fn gxt_tables_export(
+    app: AppHandle,
+    doc_id: DocId,
+    dir: String,
+    level: Option<GroupLevel>,
+    force: Option<bool>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<TableFile>, String> {
+    let mut doc = session.with(doc_id, |open| Ok(open.doc.clone()))?;
+    presets::sort_for_save(&app, &mut doc);
+    let (written, _) = translit::for_save(&app, &doc);
+    let level = level.unwrap_or(GroupLevel::Until('_'));
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let dir = PathBuf::from(dir);
+        let existing = table_paths(&dir);
+        if !existing.is_empty() {
+            if !force.unwrap_or(false) {
+                return Err(format!(
+                    "{} already has {} GXT files",
+                    dir.display(),
+                    existing.len()
+                ));
+            }
+            for p in &existing {
+                fs::remove_file(p).map_err(|e| tr!("file.write_failed", error = e))?;
+            }
+        }
+        fs::create_dir_all(&dir).map_err(|e| tr!("file.write_failed", error = e))?;
+        let mut out = Vec::new();
+        for (name, entries) in gxt_core::split_tables(&written.entries, &level) {
+            let encoded = written
+                .file_path
+                .as_deref()
+                .and_then(|p| doc_profile::encode_entries(p, &entries));
+            let bytes = gxt_core::build_gxt_bytes(encoded.as_deref().unwrap_or(&entries))?;
+            let path = dir.join(format!("{name}.gxt"));
+            fs::write(&path, bytes).map_err(|e| tr!("file.write_failed", error = e))?;
+            out.push(TableFile {
+                name,
+                path: path.to_string_lossy().into_owned(),
+                entries: entries.len(),
+            });
+        }
+        tracing::info!(dir = %dir.display(), tables = out.len(), "exported tables");
+        Ok(out)
+    })
+    .await
+    .map_err(|e| tr!("task.join_failed", error = e))?
+}
+
+/// gxt_tables_export 的逆操作：按给出的顺序把几个单表 GXT 首尾相接成一份新文档（未保存）。
+/// paths 里给的是目录时取其中的 .gxt，按文件名排序；同一个 key 出现在两个文件里时报错
+#[tauri::command]
+pub async fn gxt_tables_assemble(
+    paths: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<GxtDocument, String> {
+    let mut files = Vec::new();
+    for p in paths {
+        let path = PathBuf::from(&p);
+        if path.is_dir() {
+            files.extend(table_paths(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    let mut tables = Vec::new();
+    for path in files {
+        let name = path
+            .file_stem()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let table = gxt::load_document(path.to_string_lossy().into_owned()).await?;
+        tables.push((name, table.entries));
+    }
+    let entries = gxt_core::join_tables(tables)?;
+    gxt_core::validate_entries(&entries)?;
+    let mut doc = GxtDocument {
+        file_path: None,
+        entries,
+        doc_id: None,
+        embedded: None,
+        read_only: false,
+    };
+    tracing::info!(entries = doc.entries.len(), "assembled tables");
+    session.open(&mut doc)?;
+    Ok(doc)
+}
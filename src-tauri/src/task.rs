@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const EVENT_TASK_PROGRESS: &str = "gxt://task-progress";
+
+/// 被取消的任务以这个错误结束
+pub const CANCELLED: &str = "Cancelled";
+
+/// 两次进度事件之间的最短间隔（每条都发会把前端淹没）
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskProgress {
+    pub task_id: String,
+    pub done: usize,
+    pub total: usize,
+}
+
+/// 正在进行的长任务：task_id -> 取消标记（与后台保存的 SaveJobs 同一做法）
+#[derive(Debug, Default)]
+pub struct Tasks {
+    flags: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl Tasks {
+    /// 登记一个任务。task_id 由前端生成；为 None 时不发进度、也无法取消
+    pub fn start(&self, app: &AppHandle, task_id: Option<String>) -> Result<Task, String> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        if let Some(id) = &task_id {
+            let mut m = self
+                .flags
+                .lock()
+                .map_err(|_| "Task lock poisoned".to_string())?;
+            if m.contains_key(id) {
+                return Err(format!("Task already running: {id}"));
+            }
+            m.insert(id.clone(), cancel.clone());
+        }
+        Ok(Task {
+            app: app.clone(),
+            id: task_id,
+            cancel,
+            last_emit: None,
+        })
+    }
+
+    fn cancel(&self, task_id: &str) -> bool {
+        match self.flags.lock().ok().and_then(|m| m.get(task_id).cloned()) {
+            Some(flag) => {
+                flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// 一个进行中的任务；drop 时自动注销
+pub struct Task {
+    app: AppHandle,
+    id: Option<String>,
+    cancel: Arc<AtomicBool>,
+    last_emit: Option<Instant>,
+}
+
+impl Task {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// 已取消时返回 Err(CANCELLED)
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// 报告进度（节流；完成时一定会发）
+    pub fn progress(&mut self, done: usize, total: usize) {
+        let Some(id) = &self.id else {
+            return;
+        };
+        let due = self
+            .last_emit
+            .is_none_or(|t| t.elapsed() >= PROGRESS_INTERVAL);
+        if !due && done < total {
+            return;
+        }
+        self.last_emit = Some(Instant::now());
+        let _ = self.app.emit(
+            EVENT_TASK_PROGRESS,
+            TaskProgress {
+                task_id: id.clone(),
+                done,
+                total,
+            },
+        );
+    }
+
+    /// check + progress：循环里每处理一项调用一次
+    pub fn step(&mut self, done: usize, total: usize) -> Result<(), String> {
+        self.check()?;
+        self.progress(done, total);
+        Ok(())
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        if let Some(id) = &self.id {
+            if let Ok(mut m) = self.app.state::<Tasks>().flags.lock() {
+                m.remove(id);
+            }
+        }
+    }
+}
+
+/// 请求取消；返回该任务是否仍在进行
+#[tauri::command]
+pub fn gxt_cancel_task(task_id: String, tasks: tauri::State<'_, Tasks>) -> bool {
+    tasks.cancel(&task_id)
+}
@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+
+use tauri::AppHandle;
+
+use crate::gxt::{validate_key, GxtEntry};
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+use crate::settings;
+
+/// 应用到已有条目时，这个字段是条目原来的值
+const VALUE_FIELD: &str = "value";
+
+/// 条目模板（如标准帮助框文本的骨架）。key 与 value 里的 `{字段}` 由用户填写，`{{`/`}}` 是字面的括号
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EntryTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// 新建条目时建议的 key，如 `HELP_{n}`；为空时由用户给出
+    #[serde(default)]
+    pub key: String,
+    pub value: String,
+}
+
+impl EntryTemplate {
+    /// 要用户填写的字段（按出现顺序去重；`{value}` 应用到已有条目时自动填）
+    fn fields(&self) -> Vec<String> {
+        let mut out: Vec<String> = Vec::new();
+        for name in placeholders(&self.key).chain(placeholders(&self.value)) {
+            if !out.iter().any(|f| f == name) {
+                out.push(name.to_string());
+            }
+        }
+        out
+    }
+}
+
+/// 模板文件（团队成员之间共享）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TemplateFile {
+    pub templates: Vec<EntryTemplate>,
+}
+
+fn placeholders(text: &str) -> impl Iterator<Item = &str> {
+    let mut rest = text;
+    std::iter::from_fn(move || loop {
+        let start = rest.find('{')?;
+        if rest[start + 1..].starts_with('{') {
+            rest = &rest[start + 2..];
+            continue;
+        }
+        let len = rest[start + 1..].find('}')?;
+        let name = &rest[start + 1..start + 1 + len];
+        rest = &rest[start + 2 + len..];
+        return Some(name);
+    })
+}
+
+/// 用 fields 填模板里的 `{字段}`；有没给的字段时报错
+fn fill(text: &str, fields: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut it = text.chars().peekable();
+    while let Some(c) = it.next() {
+        match c {
+            '{' if it.peek() == Some(&'{') => {
+                it.next();
+                out.push('{');
+            }
+            '}' if it.peek() == Some(&'}') => {
+                it.next();
+                out.push('}');
+            }
+            '{' => {
+                let name: String = it.by_ref().take_while(|&c| c != '}').collect();
+                let value = fields
+                    .get(&name)
+                    .ok_or_else(|| format!("Template field {{{name}}} is not filled in"))?;
+                out.push_str(value);
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+fn upsert(templates: &mut Vec<EntryTemplate>, template: EntryTemplate) {
+    match templates.iter_mut().find(|t| t.name == template.name) {
+        Some(t) => *t = template,
+        None => templates.push(template),
+    }
+}
+
+fn find(app: &AppHandle, name: &str) -> Result<EntryTemplate, String> {
+    settings::load(app)?
+        .templates
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| format!("No such template: {name}"))
+}
+
+/// 模板要用户填写的字段
+#[tauri::command]
+pub fn gxt_template_fields(app: AppHandle, name: String) -> Result<Vec<String>, String> {
+    Ok(find(&app, &name)?.fields())
+}
+
+/// 用模板新建一条。key 没给时用模板的 key 填字段得到；key 已存在时报错
+#[tauri::command]
+pub fn gxt_template_insert(
+    app: AppHandle,
+    doc_id: DocId,
+    name: String,
+    key: Option<String>,
+    fields: HashMap<String, String>,
+    session: tauri::State<'_, Session>,
+) -> Result<GxtEntry, String> {
+    let template = find(&app, &name)?;
+    let key = match key {
+        Some(k) => k,
+        None => fill(&template.key, &fields)?,
+    };
+    validate_key(&key)?;
+    let value = fill(&template.value, &fields)?;
+    session.edit(doc_id, |open| {
+        if open.doc.entries.iter().any(|e| e.key == key) {
+            return Err(format!("{key} already exists"));
+        }
+        if !open.merge_entry(key.clone(), value.clone()) {
+            return Err(format!("{key} was deleted; restore it instead"));
+        }
+        Ok(GxtEntry {
+            key,
+            value: value.into(),
+        })
+    })
+}
+
+/// 把模板套到选中的条目上：`{value}` 是各条原来的值，其它字段对所有条目相同。
+/// 锁定的条目不动；返回改动了的 key
+#[tauri::command]
+pub fn gxt_template_apply(
+    app: AppHandle,
+    doc_id: DocId,
+    name: String,
+    keys: Vec<String>,
+    mut fields: HashMap<String, String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    let template = find(&app, &name)?;
+    session.edit(doc_id, |open| {
+        let mut filled = Vec::with_capacity(keys.len());
+        for key in keys {
+            if open.is_locked(&key) {
+                continue;
+            }
+            let old = open
+                .doc
+                .entries
+                .iter()
+                .find(|e| e.key == key)
+                .map(|e| e.value.to_string())
+                .ok_or_else(|| tr!("entry.not_found", key = key))?;
+            fields.insert(VALUE_FIELD.to_string(), old.clone());
+            let value = fill(&template.value, &fields)?;
+            if value != old {
+                filled.push((key, value));
+            }
+        }
+        let mut changed = Vec::with_capacity(filled.len());
+        for (key, value) in filled {
+            if open.merge_entry(key.clone(), value) {
+                changed.push(key);
+            }
+        }
+        Ok(changed)
+    })
+}
+
+/// 新增或按名字覆盖一个模板
+#[tauri::command]
+pub fn gxt_template_save(app: AppHandle, template: EntryTemplate) -> Result<(), String> {
+    settings::update(&app, |s| upsert(&mut s.templates, template))
+}
+
+#[tauri::command]
+pub fn gxt_template_delete(app: AppHandle, name: String) -> Result<(), String> {
+    settings::update(&app, |s| s.templates.retain(|t| t.name != name))
+}
+
+/// 把模板导出成文件分享给别人；names 为空时导出全部
+#[tauri::command]
+pub fn gxt_templates_export(
+    app: AppHandle,
+    path: String,
+    names: Option<Vec<String>>,
+) -> Result<usize, String> {
+    let templates: Vec<EntryTemplate> = settings::load(&app)?
+        .templates
+        .into_iter()
+        .filter(|t| names.as_ref().is_none_or(|n| n.contains(&t.name)))
+        .collect();
+    let count = templates.len();
+    let bytes = serde_json::to_vec_pretty(&TemplateFile { templates })
+        .map_err(|e| tr!("json.encode_failed", error = e))?;
+    fs::write(&path, bytes).map_err(|e| tr!("file.write_failed", error = e))?;
+    Ok(count)
+}
+
+/// 导入别人分享的模板文件；同名的覆盖。返回导入的个数
+#[tauri::command]
+pub fn gxt_templates_import(app: AppHandle, path: String) -> Result<usize, String> {
+    let text = fs::read_to_string(&path).map_err(|e| tr!("file.read_failed", error = e))?;
+    let file: TemplateFile =
+        serde_json::from_str(&text).map_err(|e| tr!("json.decode_failed", error = e))?;
+    let count = file.templates.len();
+    settings::update(&app, |s| {
+        for template in file.templates {
+            upsert(&mut s.templates, template);
+        }
+    })?;
+    Ok(count)
+}
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+
+use gxt_core::Segment;
+
+use crate::gxt::{self, GxtEntry};
+use crate::i18n::tr;
+use crate::session::{DocId, Session};
+
+/// 每个候选词最多带几个示例 key
+const MAX_EXAMPLES: usize = 5;
+
+/// 词组首尾不能是这些虚词（中间可以，如 "Grove Street of Doom"）
+const STOPWORDS: &[&str] = &[
+    "a", "an", "and", "are", "as", "at", "be", "but", "by", "for", "from", "has", "have", "he",
+    "her", "his", "i", "if", "in", "is", "it", "its", "me", "my", "no", "not", "of", "on", "or",
+    "our", "she", "so", "that", "the", "their", "them", "then", "there", "they", "this", "to",
+    "up", "was", "we", "were", "what", "when", "who", "will", "with", "you", "your",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermOptions {
+    #[serde(default = "default_min_words")]
+    pub min_words: usize,
+    #[serde(default = "default_max_words")]
+    pub max_words: usize,
+    /// 至少出现在这么多条目里
+    #[serde(default = "default_min_count")]
+    pub min_count: usize,
+    #[serde(default = "default_limit")]
+    pub limit: usize,
+}
+
+fn default_min_words() -> usize {
+    2
+}
+fn default_max_words() -> usize {
+    4
+}
+fn default_min_count() -> usize {
+    3
+}
+fn default_limit() -> usize {
+    200
+}
+
+impl Default for TermOptions {
+    fn default() -> Self {
+        TermOptions {
+            min_words: default_min_words(),
+            max_words: default_max_words(),
+            min_count: default_min_count(),
+            limit: default_limit(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermCandidate {
+    /// 以第一次出现时的大小写为准
+    pub term: String,
+    /// 总出现次数
+    pub count: usize,
+    /// 出现在多少个条目里
+    pub entry_count: usize,
+    pub examples: Vec<String>,
+}
+
+#[derive(Default)]
+struct Tally {
+    term: String,
+    count: usize,
+    keys: Vec<String>,
+}
+
+/// 一段文本拆成单词；~token~ 与转义视为断点，词组不会跨过它们
+fn word_runs(value: &str) -> Vec<Vec<&str>> {
+    let mut runs = Vec::new();
+    for seg in gxt_core::segments(value) {
+        let Segment::Text(t) = seg else {
+            runs.push(Vec::new());
+            continue;
+        };
+        let mut run = Vec::new();
+        let mut start = None;
+        for (i, c) in t.char_indices() {
+            let is_word = c.is_alphanumeric() || c == '\'' || c == '-';
+            match (is_word, start) {
+                (true, None) => start = Some(i),
+                (false, Some(s)) => {
+                    run.push(t[s..i].trim_matches(|c| c == '\'' || c == '-'));
+                    start = None;
+                    // 标点也是断点
+                    if !c.is_whitespace() {
+                        runs.push(std::mem::take(&mut run));
+                    }
+                }
+                (false, None) if !c.is_whitespace() => runs.push(std::mem::take(&mut run)),
+                _ => {}
+            }
+        }
+        if let Some(s) = start {
+            run.push(t[s..].trim_matches(|c| c == '\'' || c == '-'));
+        }
+        runs.push(run);
+    }
+    runs.into_iter()
+        .map(|r| r.into_iter().filter(|w| !w.is_empty()).collect::<Vec<_>>())
+        .filter(|r| !r.is_empty())
+        .collect()
+}
+
+fn is_stopword(w: &str) -> bool {
+    STOPWORDS.contains(&w.to_lowercase().as_str())
+}
+
+/// 统计高频多词词组，按 条目数 降序；被更长词组完全覆盖（次数相同）的短词组会被去掉
+pub(crate) fn extract_terms(entries: &[GxtEntry], opt: &TermOptions) -> Vec<TermCandidate> {
+    let min_words = opt.min_words.max(1);
+    let max_words = opt.max_words.max(min_words);
+    let mut tally: HashMap<String, Tally> = HashMap::new();
+
+    for e in entries {
+        let mut seen_here = HashSet::new();
+        for run in word_runs(&e.value) {
+            for n in min_words..=max_words {
+                for gram in run.windows(n) {
+                    if is_stopword(gram[0]) || is_stopword(gram[n - 1]) {
+                        continue;
+                    }
+                    if gram.iter().all(|w| w.chars().all(|c| c.is_numeric())) {
+                        continue;
+                    }
+                    let term = gram.join(" ");
+                    let t = tally.entry(term.to_lowercase()).or_default();
+                    if t.term.is_empty() {
+                        t.term = term;
+                    }
+                    t.count += 1;
+                    if seen_here.insert(t.term.to_lowercase()) {
+                        t.keys.push(e.key.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    let mut terms: Vec<(String, Tally)> = tally
+        .into_iter()
+        .filter(|(_, t)| t.keys.len() >= opt.min_count)
+        .collect();
+
+    // 短词组只在更长词组里出现（条目数相同）时没有单独价值
+    let entry_counts: HashMap<&str, usize> = terms
+        .iter()
+        .map(|(lower, t)| (lower.as_str(), t.keys.len()))
+        .collect();
+    let mut covered: HashSet<String> = HashSet::new();
+    for (lower, t) in &terms {
+        let words: Vec<&str> = lower.split(' ').collect();
+        for n in 1..words.len() {
+            for sub in words.windows(n) {
+                let sub = sub.join(" ");
+                if entry_counts.get(sub.as_str()) == Some(&t.keys.len()) {
+                    covered.insert(sub);
+                }
+            }
+        }
+    }
+    terms.retain(|(lower, _)| !covered.contains(lower));
+
+    terms.sort_by(|a, b| {
+        b.1.keys
+            .len()
+            .cmp(&a.1.keys.len())
+            .then(b.1.count.cmp(&a.1.count))
+            .then(a.0.cmp(&b.0))
+    });
+    terms.truncate(opt.limit);
+    terms
+        .into_iter()
+        .map(|(_, t)| TermCandidate {
+            term: t.term,
+            count: t.count,
+            entry_count: t.keys.len(),
+            examples: t.keys.into_iter().take(MAX_EXAMPLES).collect(),
+        })
+        .collect()
+}
+
+/// 从原文中提取术语候选。给了 source_path 时分析该文件，否则分析 doc_id 指定的文档。
+#[tauri::command]
+pub async fn gxt_extract_terms(
+    source_path: Option<String>,
+    doc_id: Option<DocId>,
+    options: Option<TermOptions>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<TermCandidate>, String> {
+    let entries = match source_path {
+        Some(p) => gxt::load_document(p).await?.entries,
+        None => {
+            let id = doc_id.ok_or("Either source_path or doc_id is required")?;
+            session.with(id, |open| Ok(open.doc.entries.clone()))?
+        }
+    };
+    let opt = options.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || extract_terms(&entries, &opt))
+        .await
+        .map_err(|e| tr!("task.join_failed", error = e))
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// 把（前端勾选后的）候选词导出为术语表起始 CSV：`term,translation,count,examples`，
+/// translation 列留空给译者填写
+#[tauri::command]
+pub fn gxt_export_terms(terms: Vec<TermCandidate>, path: String) -> Result<(), String> {
+    let mut out = String::from("term,translation,count,examples\n");
+    for t in &terms {
+        out.push_str(&format!(
+            "{},,{},{}\n",
+            csv_field(&t.term),
+            t.count,
+            csv_field(&t.examples.join(" "))
+        ));
+    }
+    fs::write(&path, out).map_err(|e| tr!("file.write_failed", error = e))
+}
@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use rusqlite::{params, Connection};
+use tauri::{AppHandle, Manager};
+
+use crate::fuzzy::similarity;
+use crate::gxt;
+use crate::i18n::tr;
+use crate::meta;
+use crate::task::Tasks;
+
+const DB_FILE: &str = "translation-memory.sqlite";
+
+/// 默认只返回相似度不低于它的建议
+const DEFAULT_MIN_SCORE: f64 = 0.6;
+const DEFAULT_LIMIT: usize = 10;
+
+/// 一条“原文 -> 译文”
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmPair {
+    pub source: String,
+    pub target: String,
+    /// 来自哪个项目/文件（只做展示）
+    #[serde(default)]
+    pub project: Option<String>,
+    #[serde(default)]
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmSuggestion {
+    pub source: String,
+    pub target: String,
+    pub project: Option<String>,
+    pub key: Option<String>,
+    /// 0.0..=1.0
+    pub score: f64,
+}
+
+/// 翻译记忆库（tauri managed state），第一次用到时才打开数据库
+#[derive(Default)]
+pub struct TranslationMemory {
+    conn: Mutex<Option<Connection>>,
+}
+
+impl TranslationMemory {
+    fn with<T>(
+        &self,
+        app: &AppHandle,
+        f: impl FnOnce(&mut Connection) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut g = self
+            .conn
+            .lock()
+            .map_err(|_| "Translation memory lock poisoned".to_string())?;
+        if g.is_none() {
+            *g = Some(open_db(&db_path(app)?)?);
+        }
+        f(g.as_mut().expect("opened above"))
+    }
+}
+
+fn db_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Resolve app data dir failed: {e}"))?;
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Create app data dir failed: {e}"))?;
+    Ok(dir.join(DB_FILE))
+}
+
+fn open_db(path: &Path) -> Result<Connection, String> {
+    let conn =
+        Connection::open(path).map_err(|e| format!("Open translation memory failed: {e}"))?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tm (
+            source     TEXT NOT NULL,
+            target     TEXT NOT NULL,
+            source_len INTEGER NOT NULL,
+            project    TEXT,
+            key        TEXT,
+            updated    INTEGER NOT NULL,
+            PRIMARY KEY (source, target)
+        );
+        CREATE INDEX IF NOT EXISTS tm_source_len ON tm(source_len);",
+    )
+    .map_err(|e| format!("Init translation memory failed: {e}"))?;
+    Ok(conn)
+}
+
+fn insert_pairs(conn: &mut Connection, pairs: &[TmPair]) -> Result<usize, String> {
+    insert_pairs_with(conn, pairs, &mut |_| Ok(()))
+}
+
+/// 每处理一对调用一次 on_progress(已处理数)；它返回 Err 时中止，事务回滚
+fn insert_pairs_with(
+    conn: &mut Connection,
+    pairs: &[TmPair],
+    on_progress: &mut dyn FnMut(usize) -> Result<(), String>,
+) -> Result<usize, String> {
+    let tx = conn.transaction().map_err(|e| tr!("tm.error", error = e))?;
+    let mut n = 0;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT INTO tm (source, target, source_len, project, key, updated)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                 ON CONFLICT(source, target) DO UPDATE SET
+                   project = excluded.project, key = excluded.key, updated = excluded.updated",
+            )
+            .map_err(|e| tr!("tm.error", error = e))?;
+        // SQLite 的整数是 i64
+        let now = meta::now_secs() as i64;
+        for (i, p) in pairs.iter().enumerate() {
+            on_progress(i)?;
+            // 空原文/空译文/原样未翻译的都不值得记
+            if p.source.is_empty() || p.target.is_empty() || p.source == p.target {
+                continue;
+            }
+            stmt.execute(params![
+                p.source,
+                p.target,
+                p.source.chars().count() as i64,
+                p.project,
+                p.key,
+                now
+            ])
+            .map_err(|e| tr!("tm.error", error = e))?;
+            n += 1;
+        }
+    }
+    tx.commit().map_err(|e| tr!("tm.error", error = e))?;
+    Ok(n)
+}
+
+/// 记录若干对原文/译文；返回实际写入条数
+#[tauri::command]
+pub fn gxt_tm_add(
+    app: AppHandle,
+    pairs: Vec<TmPair>,
+    tm: tauri::State<'_, TranslationMemory>,
+) -> Result<usize, String> {
+    tm.with(&app, |conn| insert_pairs(conn, &pairs))
+}
+
+/// 把两份按 key 对齐的 GXT（原文文件 + 译文文件）整体收进记忆库。
+/// 给了 task_id 时推送进度，可用 gxt_cancel_task 取消（取消后什么都不写入）
+#[tauri::command]
+pub async fn gxt_tm_import_files(
+    app: AppHandle,
+    source_path: String,
+    target_path: String,
+    project: Option<String>,
+    task_id: Option<String>,
+) -> Result<usize, String> {
+    let mut task = app.state::<Tasks>().start(&app, task_id)?;
+    let source = gxt::load_document(source_path).await?;
+    let target = gxt::load_document(target_path).await?;
+
+    let by_key: HashMap<&str, &str> = target
+        .entries
+        .iter()
+        .map(|e| (e.key.as_str(), &*e.value))
+        .collect();
+    let pairs: Vec<TmPair> = source
+        .entries
+        .iter()
+        .filter_map(|e| {
+            let target = by_key.get(e.key.as_str())?;
+            Some(TmPair {
+                source: e.value.to_string(),
+                target: target.to_string(),
+                project: project.clone(),
+                key: Some(e.key.clone()),
+            })
+        })
+        .collect();
+
+    let total = pairs.len();
+    let tm = app.state::<TranslationMemory>();
+    let n = tm.with(&app, |conn| {
+        insert_pairs_with(conn, &pairs, &mut |done| task.step(done, total))
+    })?;
+    task.progress(total, total);
+    Ok(n)
+}
+
+/// 记忆库里与 source 相似度不低于 min_score 的最多 limit 条，按相似度降序。
+/// 先按长度在 SQL 里粗筛（长度差超过 1 - min_score 的不可能达标），再逐条算编辑距离
+fn suggest(
+    conn: &Connection,
+    source: &str,
+    limit: usize,
+    min_score: f64,
+) -> Result<Vec<TmSuggestion>, String> {
+    let len = source.chars().count() as f64;
+    // similarity <= min(len_a, len_b) / max(len_a, len_b)
+    let lo = (len * min_score).floor() as i64;
+    let hi = if min_score > 0.0 {
+        (len / min_score).ceil() as i64
+    } else {
+        i64::MAX
+    };
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT source, target, project, key FROM tm
+             WHERE source_len BETWEEN ?1 AND ?2",
+        )
+        .map_err(|e| tr!("tm.error", error = e))?;
+    let rows = stmt
+        .query_map(params![lo, hi], |r| {
+            Ok((
+                r.get::<_, String>(0)?,
+                r.get::<_, String>(1)?,
+                r.get::<_, Option<String>>(2)?,
+                r.get::<_, Option<String>>(3)?,
+            ))
+        })
+        .map_err(|e| tr!("tm.error", error = e))?;
+
+    let mut out = Vec::new();
+    for row in rows {
+        let (src, target, project, key) = row.map_err(|e| tr!("tm.error", error = e))?;
+        let score = similarity(source, &src);
+        if score >= min_score {
+            out.push(TmSuggestion {
+                source: src,
+                target,
+                project,
+                key,
+                score,
+            });
+        }
+    }
+    out.sort_by(|a, b| b.score.total_cmp(&a.score));
+    out.truncate(limit);
+    Ok(out)
+}
+
+impl TranslationMemory {
+    /// 每条原文在记忆库里最高的相似度（都低于 min_score 时为 None），给字数分析用。
+    /// 每查一条调用一次 on_progress(已查条数)；它返回 Err 时中止
+    pub(crate) fn best_scores(
+        &self,
+        app: &AppHandle,
+        sources: &[&str],
+        min_score: f64,
+        on_progress: &mut dyn FnMut(usize) -> Result<(), String>,
+    ) -> Result<Vec<Option<f64>>, String> {
+        self.with(app, |conn| {
+            let mut out = Vec::with_capacity(sources.len());
+            for (i, source) in sources.iter().enumerate() {
+                on_progress(i)?;
+                let best = suggest(conn, source, 1, min_score)?;
+                out.push(best.first().map(|s| s.score));
+            }
+            Ok(out)
+        })
+    }
+}
+
+/// 对一条原文给出模糊匹配建议（按相似度降序）
+#[tauri::command]
+pub fn gxt_tm_suggest(
+    app: AppHandle,
+    source: String,
+    limit: Option<usize>,
+    min_score: Option<f64>,
+    tm: tauri::State<'_, TranslationMemory>,
+) -> Result<Vec<TmSuggestion>, String> {
+    let min_score = min_score.unwrap_or(DEFAULT_MIN_SCORE).clamp(0.0, 1.0);
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    tm.with(&app, |conn| suggest(conn, &source, limit, min_score))
+}
@@ -0,0 +1,613 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use gxt_core::Segment;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use tauri::AppHandle;
+
+use crate::export_names::{self, NameFields};
+use crate::gxt::{self, GxtEntry};
+use crate::i18n::tr;
+use crate::merge_report::{self, Decision, MergeReport};
+use crate::meta;
+use crate::presets;
+use crate::project::{self, Projects};
+use crate::session::{DocId, Session};
+
+/// Crowdin / Weblate 都能直接托管的两种格式，以及给外部校对用的双语 CSV
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TmsFormat {
+    /// XLIFF 1.2：trans-unit 的 id/resname 就是 GXT key，token 包在 <ph> 里
+    Xliff,
+    /// Android strings.xml（无 plurals）：token 包在 <xliff:g> 里
+    Android,
+    /// 双语对照 CSV：`key,原文,译文` 三列，表格软件里直接改第三列
+    Csv,
+}
+
+impl TmsFormat {
+    /// 导出文件的扩展名（文件名模板里的 `{ext}`）
+    fn extension(self) -> &'static str {
+        match self {
+            TmsFormat::Xliff => "xlf",
+            TmsFormat::Android => "xml",
+            TmsFormat::Csv => "csv",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TmsImportResult {
+    /// 导入后的全部条目（前端用它替换当前列表）
+    pub entries: Vec<GxtEntry>,
+    pub updated: usize,
+    /// 文件里有、当前文档里没有的 key（不会自动新增）
+    pub unknown_keys: Vec<String>,
+}
+
+// -------------------- Export --------------------
+
+/// 导出当前文档。
+/// - 给了 source_path：source 取自原文文件，target 为当前文档（用于上传已有译文）
+/// - 没给：当前文档本身作为 source（用于上传原文），XLIFF 不带 target；CSV 必须给
+///
+/// 预设设了换行 token 时，导出的文字里它换成真正的换行
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn gxt_tms_export(
+    app: AppHandle,
+    doc_id: DocId,
+    format: TmsFormat,
+    path: String,
+    source_path: Option<String>,
+    source_lang: Option<String>,
+    target_lang: Option<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<(), String> {
+    let mut source = match source_path {
+        Some(p) => Some(gxt::load_document(p).await?),
+        None => None,
+    };
+    let (mut entries, fuzzy, file_path) = session.with(doc_id, |open| {
+        Ok((
+            open.doc.entries.clone(),
+            open.fuzzy.clone(),
+            open.doc.file_path.clone(),
+        ))
+    })?;
+    let newline = presets::newline_token(&app, file_path.as_deref());
+    presets::export_newlines(&mut entries, newline.as_deref());
+    if let Some(src) = &mut source {
+        presets::export_newlines(&mut src.entries, newline.as_deref());
+    }
+
+    let original = Path::new(&path)
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("text")
+        .to_string();
+    let out = render_export(
+        format,
+        &entries,
+        &fuzzy,
+        source.as_ref().map(|d| d.entries.as_slice()),
+        source_lang.as_deref(),
+        target_lang.as_deref(),
+        &original,
+    )?;
+    fs::write(&path, out).map_err(|e| tr!("file.write_failed", error = e))
+}
+
+/// 导出整个项目：每个语言文件一份，原版作为 source，写到 dir 下，
+/// 文件名按模板生成（template 没给时用设置里的，见 export_names.rs）。返回写出的文件
+#[tauri::command]
+pub async fn gxt_tms_export_project(
+    app: AppHandle,
+    format: TmsFormat,
+    dir: String,
+    template: Option<String>,
+    source_lang: Option<String>,
+    projects: tauri::State<'_, Projects>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    let p = projects.current()?;
+    let template = template.unwrap_or_else(|| export_names::template(&app));
+    let now = meta::now_secs();
+    let newline = presets::newline_token(&app, Some(&p.base_path));
+    let mut base = project::current_entries(&session, &p.base_path).await?;
+    presets::export_newlines(&mut base, newline.as_deref());
+
+    let mut written = Vec::with_capacity(p.languages.len());
+    for lang in &p.languages {
+        let mut entries = project::current_entries(&session, &lang.path).await?;
+        presets::export_newlines(&mut entries, newline.as_deref());
+        let fuzzy = session
+            .list()
+            .into_iter()
+            .find(|d| d.file_path.as_deref() == Some(lang.path.as_str()))
+            .map(|d| session.with(d.doc_id, |open| Ok(open.fuzzy.clone())))
+            .transpose()?
+            .unwrap_or_default();
+        let basename = export_names::basename(Some(&lang.path));
+        let name = export_names::render(
+            &template,
+            &NameFields {
+                basename: &basename,
+                lang: &lang.code,
+                ext: format.extension(),
+            },
+            now,
+        )?;
+        let out = render_export(
+            format,
+            &entries,
+            &fuzzy,
+            Some(&base),
+            source_lang.as_deref(),
+            Some(&lang.code),
+            &basename,
+        )?;
+        let path = PathBuf::from(&dir).join(name);
+        if written.contains(&path.to_string_lossy().into_owned()) {
+            return Err(tr!("export.name_clash", path = path.display()));
+        }
+        fs::write(&path, out).map_err(|e| tr!("file.write_failed", error = e))?;
+        written.push(path.to_string_lossy().into_owned());
+    }
+    tracing::info!(dir = %dir, files = written.len(), "exported project");
+    Ok(written)
+}
+
+/// 按格式生成导出文件的内容；original 是 XLIFF 里记的原文件名（不带扩展名）
+fn render_export(
+    format: TmsFormat,
+    entries: &[GxtEntry],
+    fuzzy: &BTreeSet<String>,
+    source: Option<&[GxtEntry]>,
+    source_lang: Option<&str>,
+    target_lang: Option<&str>,
+    original: &str,
+) -> Result<String, String> {
+    Ok(match format {
+        TmsFormat::Xliff => {
+            let source_by_key: Option<HashMap<&str, &str>> = source.map(|entries| {
+                entries
+                    .iter()
+                    .map(|e| (e.key.as_str(), &*e.value))
+                    .collect()
+            });
+            let mut s = String::new();
+            s.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+            s.push_str("<xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n");
+            s.push_str(&format!(
+                "  <file original=\"{}.gxt\" source-language=\"{}\"{} datatype=\"plaintext\">\n    <body>\n",
+                xml_escape(original),
+                xml_escape(source_lang.unwrap_or("en")),
+                match (&source_by_key, target_lang) {
+                    (Some(_), Some(t)) => format!(" target-language=\"{}\"", xml_escape(t)),
+                    _ => String::new(),
+                }
+            ));
+            for e in entries {
+                let key = xml_escape(&e.key);
+                s.push_str(&format!(
+                    "      <trans-unit id=\"{key}\" resname=\"{key}\" xml:space=\"preserve\">\n"
+                ));
+                match &source_by_key {
+                    Some(src) => {
+                        let src_value = src.get(e.key.as_str()).copied().unwrap_or("");
+                        let state = if fuzzy.contains(&e.key) {
+                            "needs-review-translation"
+                        } else if e.value.is_empty() || &*e.value == src_value {
+                            "new"
+                        } else {
+                            "translated"
+                        };
+                        s.push_str(&format!(
+                            "        <source>{}</source>\n",
+                            xliff_text(src_value)
+                        ));
+                        s.push_str(&format!(
+                            "        <target state=\"{state}\">{}</target>\n",
+                            xliff_text(&e.value)
+                        ));
+                    }
+                    None => s.push_str(&format!(
+                        "        <source>{}</source>\n",
+                        xliff_text(&e.value)
+                    )),
+                }
+                s.push_str(&format!("        <note>GXT key: {key}</note>\n"));
+                s.push_str("      </trans-unit>\n");
+            }
+            s.push_str("    </body>\n  </file>\n</xliff>\n");
+            s
+        }
+        TmsFormat::Android => {
+            let mut s = String::new();
+            s.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+            s.push_str("<resources xmlns:xliff=\"urn:oasis:names:tc:xliff:document:1.2\">\n");
+            for e in entries {
+                s.push_str(&format!(
+                    "    <!-- GXT key: {} -->\n    <string name=\"{}\">{}</string>\n",
+                    e.key.replace("--", "- -"),
+                    android_name(&e.key),
+                    android_text(&e.value)
+                ));
+            }
+            s.push_str("</resources>\n");
+            s
+        }
+        TmsFormat::Csv => {
+            let source = source.ok_or_else(|| tr!("tms.csv_needs_source"))?;
+            let source_by_key: HashMap<&str, &str> =
+                source.iter().map(|e| (e.key.as_str(), &*e.value)).collect();
+            // BOM 让 Excel 按 UTF-8 打开
+            let mut s = format!(
+                "\u{FEFF}key,{},{}\n",
+                csv_field(source_lang.unwrap_or("source")),
+                csv_field(target_lang.unwrap_or("translation"))
+            );
+            for e in entries {
+                let src_value = source_by_key.get(e.key.as_str()).copied().unwrap_or("");
+                s.push_str(&format!(
+                    "{},{},{}\n",
+                    csv_field(&e.key),
+                    csv_field(src_value),
+                    csv_field(&e.value)
+                ));
+            }
+            s
+        }
+    })
+}
+
+pub(crate) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// token/转义包成 <ph>，平台会把它当作不可编辑的占位符
+fn xliff_text(value: &str) -> String {
+    let mut out = String::new();
+    let mut id = 0;
+    for seg in gxt_core::segments(value) {
+        match seg {
+            Segment::Text(t) => out.push_str(&xml_escape(t)),
+            Segment::Token(t) | Segment::Escape(t) => {
+                id += 1;
+                out.push_str(&format!("<ph id=\"{id}\">{}</ph>", xml_escape(t)));
+            }
+        }
+    }
+    out
+}
+
+/// Android 资源名只允许 [A-Za-z0-9_] 且不能以数字开头：
+/// 固定加前缀 k，字母数字原样保留，其它字符（包括 _）写成 _XX（十六进制）。可逆。
+fn android_name(key: &str) -> String {
+    let mut out = String::from("k");
+    for b in key.bytes() {
+        if b.is_ascii_alphanumeric() {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("_{b:02X}"));
+        }
+    }
+    out
+}
+
+fn android_key(name: &str) -> Option<String> {
+    let rest = name.strip_prefix('k')?.as_bytes();
+    let mut out = Vec::with_capacity(rest.len());
+    let mut i = 0;
+    while i < rest.len() {
+        if rest[i] == b'_' {
+            let hex = std::str::from_utf8(rest.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(rest[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+fn android_escape(t: &str, at_start: bool) -> String {
+    let mut out = String::new();
+    for (i, c) in t.chars().enumerate() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\'' => out.push_str("\\'"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '@' | '?' if at_start && i == 0 => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    xml_escape(&out)
+}
+
+fn android_text(value: &str) -> String {
+    let mut out = String::new();
+    let mut id = 0;
+    for seg in gxt_core::segments(value) {
+        let at_start = out.is_empty();
+        match seg {
+            Segment::Text(t) => out.push_str(&android_escape(t, at_start)),
+            Segment::Token(t) | Segment::Escape(t) => {
+                id += 1;
+                out.push_str(&format!(
+                    "<xliff:g id=\"{id}\">{}</xliff:g>",
+                    android_escape(t, at_start)
+                ));
+            }
+        }
+    }
+    out
+}
+
+fn android_unescape(s: &str) -> String {
+    // 整段被双引号包住时，引号只是 Android 的“保留空白”语法
+    let s = match s.strip_prefix('"').and_then(|r| r.strip_suffix('"')) {
+        Some(inner) if !s.ends_with("\\\"") => inner,
+        _ => s,
+    };
+    let mut out = String::new();
+    let mut it = s.chars();
+    while let Some(c) = it.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match it.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+// -------------------- Import --------------------
+
+/// 从平台下载的文件里取出 (key, 译文, 是否待复核)
+fn read_units(format: TmsFormat, xml: &str) -> Result<Vec<(String, String, bool)>, String> {
+    if format == TmsFormat::Csv {
+        return read_csv_units(xml);
+    }
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(false);
+
+    let (unit_tag, text_tag): (&[u8], &[u8]) = match format {
+        TmsFormat::Xliff => (b"trans-unit", b"target"),
+        _ => (b"string", b"string"),
+    };
+
+    let mut out = Vec::new();
+    let mut key: Option<String> = None;
+    let mut needs_review = false;
+    let mut text: Option<String> = None;
+
+    loop {
+        let ev = reader
+            .read_event()
+            .map_err(|e| format!("XML error at {}: {e}", reader.buffer_position()))?;
+        match ev {
+            Event::Start(e) => {
+                let name = e.local_name();
+                if name.as_ref() == unit_tag {
+                    let attr = match format {
+                        TmsFormat::Xliff => "resname",
+                        _ => "name",
+                    };
+                    let raw = e
+                        .try_get_attribute(attr)
+                        .ok()
+                        .flatten()
+                        .or_else(|| e.try_get_attribute("id").ok().flatten());
+                    key = match raw {
+                        Some(a) => {
+                            let v = a
+                                .unescape_value()
+                                .map_err(|e| format!("XML error: {e}"))?
+                                .into_owned();
+                            match format {
+                                TmsFormat::Xliff => Some(v),
+                                _ => android_key(&v),
+                            }
+                        }
+                        None => None,
+                    };
+                    needs_review = false;
+                }
+                if name.as_ref() == text_tag && key.is_some() {
+                    if let Ok(Some(state)) = e.try_get_attribute("state") {
+                        needs_review = state.value.as_ref().starts_with(b"needs-review");
+                    }
+                    text = Some(String::new());
+                }
+            }
+            Event::Text(t) => {
+                if let Some(buf) = text.as_mut() {
+                    buf.push_str(&t.unescape().map_err(|e| format!("XML error: {e}"))?);
+                }
+            }
+            Event::CData(t) => {
+                if let Some(buf) = text.as_mut() {
+                    buf.push_str(&String::from_utf8_lossy(&t));
+                }
+            }
+            Event::End(e) => {
+                if e.local_name().as_ref() == text_tag {
+                    if let (Some(k), Some(t)) = (key.clone(), text.take()) {
+                        let t = match format {
+                            TmsFormat::Xliff => t,
+                            _ => android_unescape(&t),
+                        };
+                        out.push((k, t, needs_review));
+                    }
+                }
+                if e.local_name().as_ref() == unit_tag {
+                    key = None;
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(out)
+}
+
+/// 双语 CSV 只读第一列（key）和第三列（译文），原文列怎么改都不管。
+/// 译文为空的行跳过，不会把已有译文清掉
+fn read_csv_units(text: &str) -> Result<Vec<(String, String, bool)>, String> {
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+    Ok(csv_records(text)?
+        .into_iter()
+        .skip(1)
+        .filter_map(|mut row| {
+            let value = row.get_mut(2).map(std::mem::take)?;
+            let key = row.swap_remove(0);
+            (!key.is_empty() && !value.is_empty()).then_some((key, value, false))
+        })
+        .collect())
+}
+
+/// RFC 4180：逗号分隔，双引号包住的字段里可以有逗号、换行和 `""`
+pub(crate) fn csv_records(text: &str) -> Result<Vec<Vec<String>>, String> {
+    delimited_records(text, ',')
+}
+
+/// 与 csv_records 相同，分隔符由调用方给（表格软件复制出来的是制表符）
+pub(crate) fn delimited_records(text: &str, delimiter: char) -> Result<Vec<Vec<String>>, String> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = 1;
+    let mut quote_line = 1;
+    let mut it = text.chars().peekable();
+    while let Some(c) = it.next() {
+        match c {
+            '"' if quoted => {
+                if it.peek() == Some(&'"') {
+                    it.next();
+                    field.push('"');
+                } else {
+                    quoted = false;
+                }
+            }
+            '"' if field.is_empty() => {
+                quoted = true;
+                quote_line = line;
+            }
+            '\n' if quoted => {
+                line += 1;
+                field.push(c);
+            }
+            _ if quoted => field.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' if it.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                line += 1;
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => field.push(c),
+        }
+    }
+    if quoted {
+        return Err(format!("Unterminated quoted field at line {quote_line}"));
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+/// 把平台导出的译文合并回当前文档：只更新已有 key，
+/// XLIFF 中 state=needs-review-* 的条目标记为 fuzzy，其余（包括 CSV 里改过的）清除 fuzzy。
+/// 预设设了换行 token 时，译文里的换行换成它
+#[tauri::command]
+pub fn gxt_tms_import(
+    app: AppHandle,
+    doc_id: DocId,
+    format: TmsFormat,
+    path: String,
+    session: tauri::State<'_, Session>,
+) -> Result<TmsImportResult, String> {
+    let xml = fs::read_to_string(&path).map_err(|e| tr!("file.read_failed", error = e))?;
+    let mut units = read_units(format, &xml)?;
+    let file_path = session.with(doc_id, |open| Ok(open.doc.file_path.clone()))?;
+    if let Some(token) = presets::newline_token(&app, file_path.as_deref()) {
+        for (_, value, _) in &mut units {
+            *value = gxt_core::newlines_to_token(value, &token);
+        }
+    }
+
+    let (result, audit) = session.edit(doc_id, |open| {
+        let existing: HashSet<String> = open.doc.entries.iter().map(|e| e.key.clone()).collect();
+        let mut audit = MergeReport::new("tms-import", open.doc.file_path.clone(), Some(path));
+        let mut updated = 0;
+        let mut unknown_keys = Vec::new();
+        for (key, value, review) in units {
+            if !existing.contains(&key) {
+                audit.push(&key, Decision::Skipped, Some("not in document".into()));
+                unknown_keys.push(key);
+                continue;
+            }
+            if open.is_locked(&key) {
+                audit.push(&key, Decision::Kept, Some("locked".into()));
+                continue;
+            }
+            audit.push(&key, Decision::Overwritten, None);
+            open.set_entry(key.clone(), value);
+            if review {
+                open.fuzzy.insert(key);
+            } else {
+                open.fuzzy.remove(&key);
+            }
+            updated += 1;
+        }
+        let result = TmsImportResult {
+            entries: open.doc.entries.clone(),
+            updated,
+            unknown_keys,
+        };
+        Ok((result, audit))
+    })?;
+    merge_report::record(&app, &audit);
+    Ok(result)
+}
@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+
+use crate::meta::{self, Tombstone};
+use crate::session::{DocId, Session};
+
+/// 软删除：删掉的条目不写进 GXT，但删除记录留在附加信息文件里（随文件一起同步），
+/// 导入、按表格导入、原文更新等合并操作遇到这些 key 时跳过，别人那份里还有也不会加回来
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TombstoneEntry {
+    pub key: String,
+    #[serde(flatten)]
+    pub tombstone: Tombstone,
+}
+
+/// 软删除的条目，最近删的在前
+#[tauri::command]
+pub fn gxt_tombstones(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<TombstoneEntry>, String> {
+    session.with(doc_id, |open| {
+        let mut out: Vec<TombstoneEntry> = open
+            .tombstones
+            .iter()
+            .map(|(key, t)| TombstoneEntry {
+                key: key.clone(),
+                tombstone: t.clone(),
+            })
+            .collect();
+        out.sort_by_key(|d| Reverse(d.tombstone.at));
+        Ok(out)
+    })
+}
+
+/// 恢复一批软删除的条目（整批算一步撤销）：以删除前的译文加回来；
+/// 文档里已经又有这个 key 时只去掉删除记录，锁定的 key 不动。返回加回来的 key
+#[tauri::command]
+pub fn gxt_tombstone_restore(
+    doc_id: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<String>, String> {
+    session.edit(doc_id, |open| {
+        let own_batch = !open.in_batch();
+        if own_batch {
+            open.begin_batch()?;
+        }
+        let mut restored = Vec::new();
+        for key in keys {
+            let Some(t) = open.tombstones.remove(&key) else {
+                continue;
+            };
+            if open.doc.entries.iter().any(|e| e.key == key) {
+                continue;
+            }
+            if open.is_locked(&key) {
+                open.tombstones.insert(key, t);
+                continue;
+            }
+            open.set_entry(key.clone(), t.value);
+            restored.push(key);
+        }
+        if own_batch {
+            open.commit_batch()?;
+        }
+        Ok(restored)
+    })
+}
+
+/// 忘掉一批删除记录（不恢复条目）：之后合并时这些 key 又会照常加进来。立即写回附加信息文件
+#[tauri::command]
+pub fn gxt_tombstone_purge(
+    doc_id: DocId,
+    keys: Vec<String>,
+    session: tauri::State<'_, Session>,
+) -> Result<usize, String> {
+    session.edit(doc_id, |open| {
+        let before = open.tombstones.len();
+        open.tombstones.retain(|k, _| !keys.contains(k));
+        if let Some(p) = &open.doc.file_path {
+            let mut m = meta::load(p);
+            m.tombstones = open.tombstones.clone();
+            meta::save(p, &m)?;
+        }
+        Ok(before - open.tombstones.len())
+    })
+}
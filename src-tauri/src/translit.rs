@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use gxt_core::{AstralPolicy, GamePreset, Transliteration};
+use tauri::{AppHandle, Manager};
+
+use crate::gxt::GxtDocument;
+use crate::presets::{self, Presets};
+use crate::project::Projects;
+use crate::rollback::{self, Target};
+use crate::session::{DocId, Session};
+use crate::settings;
+
+/// 内置音译表（源文件在 src-tauri/transliteration/）
+const BUILTIN: [(&str, &str); 2] = [
+    ("cyrillic", include_str!("../transliteration/cyrillic.toml")),
+    ("greek", include_str!("../transliteration/greek.toml")),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransliteratedEntry {
+    pub key: String,
+    /// 被换掉的字符（去重）
+    pub chars: String,
+    /// 音译后的值
+    pub value: String,
+}
+
+/// 内置表 + 设置里的表（同名时设置里的覆盖内置的）
+fn tables(app: &AppHandle) -> Result<Vec<Transliteration>, String> {
+    let mut out: Vec<Transliteration> = BUILTIN
+        .iter()
+        .map(|(id, text)| {
+            toml::from_str(text).unwrap_or_else(|e| panic!("Bad builtin transliteration {id}: {e}"))
+        })
+        .collect();
+    for t in settings::load(app)?.transliterations {
+        match out.iter_mut().find(|old| old.name == t.name) {
+            Some(old) => *old = t,
+            None => out.push(t),
+        }
+    }
+    Ok(out)
+}
+
+fn table(app: &AppHandle, name: &str) -> Result<Transliteration, String> {
+    tables(app)?
+        .into_iter()
+        .find(|t| t.name == name)
+        .ok_or_else(|| format!("No such transliteration table: {name}"))
+}
+
+/// 按表音译 entries；给了预设时只换字体里没有字形的字符，否则表里有的都换
+fn apply<'a>(
+    table: &Transliteration,
+    preset: Option<&GamePreset>,
+    entries: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<TransliteratedEntry> {
+    entries
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let (new, chars) = table.apply(value, |c| preset.is_some_and(|p| p.is_allowed(c)));
+            (!chars.is_empty()).then(|| TransliteratedEntry {
+                key: key.to_string(),
+                chars: chars.into_iter().collect(),
+                value: new,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub fn gxt_translit_tables(app: AppHandle) -> Result<Vec<Transliteration>, String> {
+    tables(&app)
+}
+
+/// 批量音译文档（keys 为空时整个文档），锁定的条目不动。返回每条换掉了哪些字符
+#[tauri::command]
+pub fn gxt_transliterate(
+    app: AppHandle,
+    doc_id: DocId,
+    table_name: String,
+    preset_id: Option<String>,
+    keys: Option<Vec<String>>,
+    presets: tauri::State<'_, Presets>,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<TransliteratedEntry>, String> {
+    let table = table(&app, &table_name)?;
+    let preset = match preset_id {
+        Some(id) => Some(presets.get(&id)?),
+        None => None,
+    };
+    rollback::backup_before(&app, "transliterate", &[Target::Document(doc_id)])?;
+    session.edit(doc_id, |open| {
+        let report = apply(
+            &table,
+            preset.as_ref(),
+            open.doc
+                .entries
+                .iter()
+                .filter(|e| keys.as_ref().is_none_or(|k| k.contains(&e.key)))
+                .filter(|e| !open.is_locked(&e.key))
+                .map(|e| (e.key.as_str(), &*e.value)),
+        );
+        for r in &report {
+            open.set_entry(r.key.clone(), r.value.clone());
+        }
+        Ok(report)
+    })
+}
+
+/// 保存时调用：文档属于当前项目、项目设置了音译表和预设时，
+/// 把预设字体里没有的字符按表换掉后再写盘；预设的 astral 为 substitute 时
+/// 再把 U+FFFF 以上的字符换成文字（编辑器里的文本都不变）。返回要写的文档和报告
+pub(crate) fn for_save(
+    app: &AppHandle,
+    doc: &GxtDocument,
+) -> (GxtDocument, Vec<TransliteratedEntry>) {
+    let mut written = doc.clone();
+    let Some(preset) = save_preset(app, doc.file_path.as_deref()) else {
+        return (written, Vec::new());
+    };
+    let mut report = match project_table(app) {
+        Some(table) => apply(
+            &table,
+            Some(&preset),
+            doc.entries.iter().map(|e| (e.key.as_str(), &*e.value)),
+        ),
+        None => Vec::new(),
+    };
+    if preset.astral == AstralPolicy::Substitute {
+        substitute_astral(&mut report, doc);
+    }
+    let by_key: HashMap<&str, &str> = report
+        .iter()
+        .map(|r| (r.key.as_str(), r.value.as_str()))
+        .collect();
+    for e in &mut written.entries {
+        if let Some(v) = by_key.get(e.key.as_str()) {
+            e.value = (*v).into();
+        }
+    }
+    (written, report)
+}
+
+/// 在音译的结果上再换掉 U+FFFF 以上的字符，换掉的字符记进同一条报告
+fn substitute_astral(report: &mut Vec<TransliteratedEntry>, doc: &GxtDocument) {
+    for e in &doc.entries {
+        let i = report.iter().position(|r| r.key == e.key);
+        let current = i.map_or(&*e.value, |i| report[i].value.as_str());
+        let Some(value) = gxt_core::substitute_astral(current) else {
+            continue;
+        };
+        let chars: String = gxt_core::astral_chars(current).into_iter().collect();
+        match i {
+            Some(i) => {
+                report[i].chars.push_str(&chars);
+                report[i].value = value;
+            }
+            None => report.push(TransliteratedEntry {
+                key: e.key.clone(),
+                chars,
+                value,
+            }),
+        }
+    }
+}
+
+/// 文档所用的预设（见 presets::project_preset）
+pub(crate) fn save_preset(app: &AppHandle, file_path: Option<&str>) -> Option<GamePreset> {
+    let preset_id = presets::project_preset(&app.state::<Projects>(), file_path)?;
+    app.state::<Presets>().get(&preset_id).ok()
+}
+
+/// 当前项目设置的音译表
+fn project_table(app: &AppHandle) -> Option<Transliteration> {
+    let name = app
+        .state::<Projects>()
+        .current()
+        .ok()?
+        .project
+        .settings
+        .transliteration?;
+    table(app, &name)
+        .inspect_err(|e| tracing::warn!(error = %e, "skip transliteration"))
+        .ok()
+}
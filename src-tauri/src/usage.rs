@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+
+use gxt_core::KeyUsage;
+
+use crate::i18n::tr;
+use crate::meta;
+use crate::session::{DocId, Session};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageImport {
+    /// 记进去的显示次数
+    pub hits: u64,
+    /// 出现过的 key 数
+    pub keys: usize,
+    /// 认不出 key 的行
+    pub skipped: usize,
+    /// 日志里有、文档里没有的 key（不记）
+    pub unknown_keys: Vec<String>,
+}
+
+/// 一条的显示统计
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRow {
+    pub key: String,
+    #[serde(flatten)]
+    pub usage: KeyUsage,
+}
+
+/// 文档的文件路径；没保存过的文档没有地方放附加信息
+fn file_path(session: &Session, doc_id: DocId) -> Result<String, String> {
+    session
+        .with(doc_id, |open| Ok(open.doc.file_path.clone()))?
+        .ok_or_else(|| tr!("file.no_path"))
+}
+
+/// 导入配套 CLEO 记录脚本写的显示日志（见 gxt_core::usage），统计每个 key 显示了几次、在哪里显示。
+/// 默认累加到已有的统计上（多次游玩的日志可以分批导入），replace 为 true 时先清空。
+/// 写进附加信息文件（文档要先保存过）
+#[tauri::command]
+pub fn gxt_usage_import(
+    doc_id: DocId,
+    paths: Vec<String>,
+    replace: Option<bool>,
+    session: tauri::State<'_, Session>,
+) -> Result<UsageImport, String> {
+    let file_path = file_path(&session, doc_id)?;
+    let existing: HashSet<String> = session.with(doc_id, |open| {
+        Ok(open.doc.entries.iter().map(|e| e.key.clone()).collect())
+    })?;
+    let mut m = meta::load(&file_path);
+    if replace.unwrap_or(false) {
+        m.usage.clear();
+    }
+    let mut report = UsageImport::default();
+    let mut seen: HashSet<String> = HashSet::new();
+    for path in &paths {
+        let bytes = fs::read(path).map_err(|e| tr!("file.read_failed", error = e))?;
+        let log = gxt_core::parse_display_log(&String::from_utf8_lossy(&bytes));
+        report.skipped += log.skipped;
+        for (key, usage) in log.usage {
+            if !existing.contains(&key) {
+                if !report.unknown_keys.contains(&key) {
+                    report.unknown_keys.push(key);
+                }
+                continue;
+            }
+            report.hits += usage.count;
+            m.usage.entry(key.clone()).or_default().merge(&usage);
+            seen.insert(key);
+        }
+    }
+    report.keys = seen.len();
+    meta::save(&file_path, &m)?;
+    Ok(report)
+}
+
+/// 显示统计，最常显示的在前；没导入过时为空
+#[tauri::command]
+pub fn gxt_usage(
+    doc_id: DocId,
+    session: tauri::State<'_, Session>,
+) -> Result<Vec<UsageRow>, String> {
+    let Some(path) = session.with(doc_id, |open| Ok(open.doc.file_path.clone()))? else {
+        return Ok(Vec::new());
+    };
+    let usage: BTreeMap<String, KeyUsage> = meta::load(&path).usage;
+    let mut rows: Vec<UsageRow> = usage
+        .into_iter()
+        .map(|(key, usage)| UsageRow { key, usage })
+        .collect();
+    rows.sort_by_key(|r| Reverse(r.usage.count));
+    Ok(rows)
+}
+
+/// 清掉文档的显示统计
+#[tauri::command]
+pub fn gxt_usage_clear(doc_id: DocId, session: tauri::State<'_, Session>) -> Result<(), String> {
+    let path = file_path(&session, doc_id)?;
+    let mut m = meta::load(&path);
+    m.usage.clear();
+    meta::save(&path, &m)
+}
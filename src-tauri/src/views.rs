@@ -0,0 +1,150 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use tauri::AppHandle;
+
+use crate::gxt;
+use crate::page::{self, SortKey};
+use crate::progress::EntryStatus;
+use crate::project::Projects;
+use crate::search::SearchHit;
+use crate::session::{DocId, Session};
+use crate::settings;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    Key,
+    Value,
+    #[default]
+    Both,
+}
+
+/// 保存在设置里的一个筛选（“视图”），如“未翻译的帮助文本”“含 ~1~ 的条目”。
+/// 各条件同时满足才算命中；没给的条件不限制
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SavedView {
+    pub name: String,
+    /// 不区分大小写的子串
+    #[serde(default)]
+    pub query: String,
+    #[serde(default)]
+    pub scope: SearchScope,
+    /// key 前缀（不区分大小写），如 `HELP`
+    #[serde(default)]
+    pub key_prefix: Option<String>,
+    /// value 里要含的 token，如 `~1~`
+    #[serde(default)]
+    pub token: Option<String>,
+    /// 只要这些状态的条目；为空不限制
+    #[serde(default)]
+    pub statuses: Vec<EntryStatus>,
+    /// 结果的顺序（同表格的排序）；为空时按文档顺序
+    #[serde(default)]
+    pub sort: Vec<SortKey>,
+}
+
+impl SavedView {
+    fn matches(&self, key: &str, value: &str) -> bool {
+        let q = self.query.to_lowercase();
+        let text_ok = q.is_empty()
+            || match self.scope {
+                SearchScope::Key => key.to_lowercase().contains(&q),
+                SearchScope::Value => value.to_lowercase().contains(&q),
+                SearchScope::Both => {
+                    key.to_lowercase().contains(&q) || value.to_lowercase().contains(&q)
+                }
+            };
+        let prefix_ok = self.key_prefix.as_ref().is_none_or(|p| {
+            key.len() >= p.len()
+                && key.is_char_boundary(p.len())
+                && key[..p.len()].eq_ignore_ascii_case(p)
+        });
+        let token_ok = self.token.as_ref().is_none_or(|t| {
+            gxt_core::tokens(value)
+                .iter()
+                .any(|x| x.eq_ignore_ascii_case(t))
+        });
+        text_ok && prefix_ok && token_ok
+    }
+}
+
+/// 在文档上执行一个保存的视图。判断“未翻译”时的原文：source_path，
+/// 没给时文档若是当前项目的语言文件则用项目原版，否则只把空值当作未翻译
+#[tauri::command]
+pub async fn gxt_view_eval(
+    app: AppHandle,
+    doc_id: DocId,
+    name: String,
+    source_path: Option<String>,
+    session: tauri::State<'_, Session>,
+    projects: tauri::State<'_, Projects>,
+) -> Result<Vec<SearchHit>, String> {
+    let view = settings::load(&app)?
+        .views
+        .into_iter()
+        .find(|v| v.name == name)
+        .ok_or_else(|| format!("No such view: {name}"))?;
+
+    let source_path = match source_path {
+        Some(p) => Some(p),
+        None if view.statuses.is_empty() => None,
+        None => {
+            let file = session.with(doc_id, |open| Ok(open.doc.file_path.clone()))?;
+            projects.current().ok().and_then(|p| {
+                let lang = p.languages.iter().any(|l| Some(&l.path) == file.as_ref());
+                lang.then_some(p.base_path)
+            })
+        }
+    };
+    let source = match source_path {
+        Some(p) => Some(gxt::load_document(p).await?.entries),
+        None => None,
+    };
+    let source: HashMap<&str, &str> = source
+        .iter()
+        .flatten()
+        .map(|e| (e.key.as_str(), &*e.value))
+        .collect();
+
+    let collator = page::collator_for(&app, &view.sort)?;
+    let q = view.query.to_lowercase();
+    session.with(doc_id, |open| {
+        let mut rows: Vec<page::RowInfo> = open
+            .doc
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| view.matches(&e.key, &e.value))
+            .map(|(index, e)| page::row_info(open, &source, index, e))
+            .filter(|r| view.statuses.is_empty() || view.statuses.contains(&r.status))
+            .collect();
+        page::sort_rows(&mut rows, &view.sort, &collator);
+        Ok(rows
+            .iter()
+            .map(|r| SearchHit {
+                key: r.key.to_string(),
+                value: r.value.to_string(),
+                in_key: !q.is_empty() && r.key.to_lowercase().contains(&q),
+                in_value: !q.is_empty() && r.value.to_lowercase().contains(&q),
+            })
+            .collect())
+    })
+}
+
+/// 新增或按名字覆盖一个视图
+#[tauri::command]
+pub fn gxt_view_save(app: AppHandle, view: SavedView) -> Result<(), String> {
+    settings::update(&app, |s| {
+        match s.views.iter_mut().find(|v| v.name == view.name) {
+            Some(v) => *v = view,
+            None => s.views.push(view),
+        }
+    })
+}
+
+#[tauri::command]
+pub fn gxt_view_delete(app: AppHandle, name: String) -> Result<(), String> {
+    settings::update(&app, |s| s.views.retain(|v| v.name != name))
+}
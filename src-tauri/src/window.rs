@@ -0,0 +1,374 @@
+use serde::Serialize;
+
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+
+use gxt_core::TextFormat;
+use tauri::{
+    AppHandle, CloseRequestApi, Emitter, Manager, WebviewUrl, WebviewWindowBuilder, Window,
+};
+
+use crate::deep_link::{self, DeepLink};
+use crate::plugins::{self, PluginInfo};
+use crate::project::Project;
+use crate::session::{DocId, DocumentInfo, Session};
+
+/// 已经开着的窗口收到“打开这个文件”的请求（payload 为路径）
+pub const EVENT_OPEN_FILE: &str = "gxt://open-file";
+/// 已经开着的窗口收到 gxt:// 链接（payload 为 DeepLink）：打开 path（已打开的不重开）后跳到 key
+pub const EVENT_OPEN_ENTRY: &str = "gxt://open-entry";
+/// 窗口要关闭但还有未保存的文档（payload 为这些文档）；前端提示保存后调用 gxt_window_close
+pub const EVENT_CLOSE_REQUESTED: &str = "gxt://close-requested";
+
+pub const MAIN_WINDOW: &str = "main";
+/// 文档窗口的 label 前缀（capabilities 里按 `doc-*` 授权）
+const DOC_WINDOW_PREFIX: &str = "doc-";
+
+#[derive(Default)]
+struct WindowState {
+    next_id: u32,
+    /// 窗口创建时要打开的文件，等前端加载完用 gxt_startup_path 取走
+    pending: HashMap<String, String>,
+    /// 从 gxt:// 链接打开时，文件打开后要跳到的 key
+    pending_keys: HashMap<String, String>,
+    /// 每个窗口里打开过的文档（可能已被关闭）；窗口销毁时一并关闭
+    docs: HashMap<String, BTreeSet<DocId>>,
+}
+
+/// tauri managed state：窗口与文档的对应关系。其它后端状态（Session、翻译记忆等）本来就是全应用共享的
+#[derive(Default)]
+pub struct Windows {
+    state: Mutex<WindowState>,
+}
+
+impl Windows {
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, WindowState>, String> {
+        self.state
+            .lock()
+            .map_err(|_| "Window state lock poisoned".to_string())
+    }
+
+    /// 窗口创建后要打开的文件（只能取一次）
+    pub fn take_pending(&self, label: &str) -> Option<String> {
+        self.lock().ok()?.pending.remove(label)
+    }
+
+    /// 窗口创建后打开的文件里要跳到的 key（只能取一次）
+    pub fn take_pending_key(&self, label: &str) -> Option<String> {
+        self.lock().ok()?.pending_keys.remove(label)
+    }
+
+    /// 已经打开 path 的窗口
+    fn window_of(&self, session: &Session, path: &str) -> Option<String> {
+        let ids: BTreeSet<DocId> = session
+            .list()
+            .into_iter()
+            .filter(|d| d.file_path.as_deref().is_some_and(|p| same_path(p, path)))
+            .map(|d| d.doc_id)
+            .collect();
+        let g = self.lock().ok()?;
+        g.docs
+            .iter()
+            .find(|(_, docs)| !docs.is_disjoint(&ids))
+            .map(|(label, _)| label.clone())
+    }
+
+    /// 窗口里没有仍然开着的文档
+    fn is_empty(&self, session: &Session, label: &str) -> bool {
+        let open: BTreeSet<DocId> = session.list().into_iter().map(|d| d.doc_id).collect();
+        self.lock()
+            .map(|g| g.docs.get(label).is_none_or(|docs| docs.is_disjoint(&open)))
+            .unwrap_or(false)
+    }
+
+    /// 窗口里有未保存修改的文档
+    fn dirty_docs(&self, session: &Session, label: &str) -> Vec<DocumentInfo> {
+        let Some(mine) = self.lock().ok().and_then(|g| g.docs.get(label).cloned()) else {
+            return Vec::new();
+        };
+        session
+            .list()
+            .into_iter()
+            .filter(|d| d.dirty && mine.contains(&d.doc_id))
+            .collect()
+    }
+}
+
+/// Windows 下路径不分大小写
+fn same_path(a: &str, b: &str) -> bool {
+    if cfg!(windows) {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+/// 启动参数/拖进来的文件是什么，前端据此交给 gxt_load、importer 或 gxt_project_open
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum OpenKind {
+    Gxt,
+    Project,
+    /// 文本导出等：importer 为 None 时是双语 CSV（要先打开原文再用 gxt_tms_import）
+    Import {
+        format: Option<TextFormat>,
+        importer: Option<PluginInfo>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OpenFile {
+    pub path: String,
+    #[serde(flatten)]
+    pub kind: OpenKind,
+    /// 从 gxt:// 链接启动时，打开后跳到这一条
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+}
+
+/// 看文件内容认出类型：.gxt 直接算；JSON 先看是不是项目文件；再按文本格式嗅探，
+/// 认不出时按扩展名找转换器（外部插件的格式）。都不是时为 None
+pub fn detect_open_kind(app: &AppHandle, path: &str) -> Option<OpenKind> {
+    if is_gxt_arg(path) {
+        return Some(OpenKind::Gxt);
+    }
+    let bytes = fs::read(path).ok()?;
+    if serde_json::from_slice::<Project>(&bytes).is_ok() {
+        return Some(OpenKind::Project);
+    }
+    if let Some(format) = gxt_core::sniff_text_format(&bytes) {
+        return Some(OpenKind::Import {
+            format: Some(format),
+            importer: plugins::importer_for(format),
+        });
+    }
+    plugins::importer_by_extension(app, Path::new(path)).map(|importer| OpenKind::Import {
+        format: None,
+        importer: Some(importer),
+    })
+}
+
+fn is_gxt_arg(arg: &str) -> bool {
+    arg.to_lowercase().ends_with(".gxt")
+}
+
+/// 启动参数里要打开的文件：.gxt 以及能导入的（按扩展名粗筛，打开时再按内容认）
+fn is_openable_arg(app: &AppHandle, arg: &str) -> bool {
+    let path = Path::new(arg);
+    is_gxt_arg(arg)
+        || path
+            .extension()
+            .is_some_and(|x| x.eq_ignore_ascii_case("json"))
+        || plugins::importer_by_extension(app, path).is_some()
+}
+
+/// 启动参数里要打开的文件与要跳到的 key：gxt:// 链接，或 is_openable_arg 认的文件
+fn open_target(app: &AppHandle, arg: String) -> Option<DeepLink> {
+    match deep_link::parse(&arg) {
+        Some(link) => Some(link),
+        None => is_openable_arg(app, &arg).then_some(DeepLink {
+            path: arg,
+            key: None,
+        }),
+    }
+}
+
+/// 新建一个文档窗口；path 给了时窗口加载完后会打开它
+pub fn create_window(app: &AppHandle, path: Option<String>) -> Result<String, String> {
+    let windows = app.state::<Windows>();
+    let label = {
+        let mut g = windows.lock()?;
+        g.next_id += 1;
+        let label = format!("{DOC_WINDOW_PREFIX}{}", g.next_id);
+        if let Some(p) = path {
+            g.pending.insert(label.clone(), p);
+        }
+        label
+    };
+    WebviewWindowBuilder::new(app, &label, WebviewUrl::App("index.html".into()))
+        .title("gxt-editor")
+        .inner_size(1500.0, 900.0)
+        .build()
+        .map_err(|e| format!("Create window failed: {e}"))?;
+    Ok(label)
+}
+
+/// 把一个文件交给合适的窗口：已经在某个窗口里打开的就切到那个窗口，
+/// 主窗口还空着就让主窗口打开，否则新开一个窗口。返回处理它的窗口 label
+pub fn route_open(app: &AppHandle, path: String) -> Result<String, String> {
+    let windows = app.state::<Windows>();
+    let session = app.state::<Session>();
+
+    if let Some(label) = windows.window_of(&session, &path) {
+        if let Some(w) = app.get_webview_window(&label) {
+            let _ = w.unminimize();
+            let _ = w.set_focus();
+        }
+        return Ok(label);
+    }
+    if windows.is_empty(&session, MAIN_WINDOW) {
+        if let Some(w) = app.get_webview_window(MAIN_WINDOW) {
+            app.emit_to(MAIN_WINDOW, EVENT_OPEN_FILE, &path)
+                .map_err(|e| format!("Emit event failed: {e}"))?;
+            let _ = w.set_focus();
+            return Ok(MAIN_WINDOW.to_string());
+        }
+    }
+    create_window(app, Some(path))
+}
+
+/// 同 route_open，另外给了 key 时让处理它的窗口打开后跳到这一条：
+/// 新开的窗口加载完用 gxt_startup_file 取，已经开着的窗口收到 EVENT_OPEN_ENTRY
+pub fn route_link(app: &AppHandle, link: DeepLink) -> Result<String, String> {
+    let label = route_open(app, link.path.clone())?;
+    let Some(key) = link.key.clone() else {
+        return Ok(label);
+    };
+    let windows = app.state::<Windows>();
+    let mut g = windows.lock()?;
+    if g.pending.contains_key(&label) {
+        g.pending_keys.insert(label.clone(), key);
+    } else {
+        drop(g);
+        app.emit_to(label.as_str(), EVENT_OPEN_ENTRY, &link)
+            .map_err(|e| format!("Emit event failed: {e}"))?;
+    }
+    Ok(label)
+}
+
+/// 启动参数里的 .gxt、能导入的文件与 gxt:// 链接：第一个交给主窗口，其余各开一个窗口（启动时调用一次）
+pub fn open_startup_args(app: &AppHandle, args: impl IntoIterator<Item = String>) {
+    let mut targets = args.into_iter().filter_map(|a| open_target(app, a));
+    let Some(first) = targets.next() else {
+        return;
+    };
+    if let Ok(mut g) = app.state::<Windows>().lock() {
+        g.pending.insert(MAIN_WINDOW.to_string(), first.path);
+        if let Some(key) = first.key {
+            g.pending_keys.insert(MAIN_WINDOW.to_string(), key);
+        }
+    }
+    for t in targets {
+        if let Ok(label) = create_window(app, Some(t.path)) {
+            if let (Some(key), Ok(mut g)) = (t.key, app.state::<Windows>().lock()) {
+                g.pending_keys.insert(label, key);
+            }
+        }
+    }
+}
+
+/// 第二个实例（例如双击了另一个 .gxt、点了 gxt:// 链接）把命令行转发过来：
+/// 其中的文件与链接按 route_link 打开，没有时把主窗口提到前台
+pub fn open_forwarded_args(app: &AppHandle, argv: Vec<String>, cwd: String) {
+    let links: Vec<DeepLink> = argv
+        .into_iter()
+        .skip(1)
+        .filter_map(|a| open_target(app, a))
+        .map(|mut t| {
+            t.path = Path::new(&cwd).join(&t.path).to_string_lossy().into_owned();
+            t
+        })
+        .collect();
+    if links.is_empty() {
+        if let Some(w) = app.get_webview_window(MAIN_WINDOW) {
+            let _ = w.unminimize();
+            let _ = w.set_focus();
+        }
+        return;
+    }
+    // 新建窗口不能在事件回调里同步进行
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        for link in links {
+            let path = link.path.clone();
+            if let Err(e) = route_link(&app, link) {
+                tracing::warn!(path = %path, error = %e, "open forwarded file failed");
+            }
+        }
+    });
+}
+
+/// 窗口关闭前检查：有未保存的文档就拦下，交给前端提示
+pub fn on_close_requested(window: &Window, api: &CloseRequestApi) {
+    let app = window.app_handle();
+    let dirty = app
+        .state::<Windows>()
+        .dirty_docs(&app.state::<Session>(), window.label());
+    if dirty.is_empty() {
+        return;
+    }
+    api.prevent_close();
+    let _ = app.emit_to(window.label(), EVENT_CLOSE_REQUESTED, &dirty);
+}
+
+/// 窗口销毁时关闭它的所有文档
+pub fn on_destroyed(window: &Window) {
+    let app = window.app_handle();
+    let docs = app
+        .state::<Windows>()
+        .lock()
+        .ok()
+        .and_then(|mut g| {
+            g.pending.remove(window.label());
+            g.pending_keys.remove(window.label());
+            g.docs.remove(window.label())
+        })
+        .unwrap_or_default();
+    let session = app.state::<Session>();
+    for id in docs {
+        session.close(id);
+    }
+}
+
+/// 新开一个窗口（path 为空时是空白窗口）；返回窗口 label
+#[tauri::command]
+pub async fn gxt_open_window(app: AppHandle, path: Option<String>) -> Result<String, String> {
+    create_window(&app, path)
+}
+
+/// 打开文件：已在某个窗口里打开就切过去，否则交给空闲的主窗口或新窗口
+#[tauri::command]
+pub async fn gxt_open_path(app: AppHandle, path: String) -> Result<String, String> {
+    route_open(&app, path)
+}
+
+/// 前端处理完关闭提示（已保存或确认放弃修改）后调用，直接关闭窗口不再检查
+#[tauri::command]
+pub fn gxt_window_close(window: Window) -> Result<(), String> {
+    window
+        .destroy()
+        .map_err(|e| format!("Close window failed: {e}"))
+}
+
+/// 前端打开/导入文档后调用，把文档记到当前窗口名下（用于路由与关窗时释放）
+#[tauri::command]
+pub fn gxt_window_attach(
+    window: Window,
+    doc_id: DocId,
+    windows: tauri::State<'_, Windows>,
+) -> Result<(), String> {
+    let mut g = windows.lock()?;
+    for docs in g.docs.values_mut() {
+        docs.remove(&doc_id);
+    }
+    g.docs
+        .entry(window.label().to_string())
+        .or_default()
+        .insert(doc_id);
+    Ok(())
+}
+
+/// 一个要打开的文件是什么类型（EVENT_OPEN_FILE 收到的路径、拖进窗口的文件）；认不出时为 None。
+/// 也可以是粘贴进来的 gxt:// 链接，这时带上要跳到的 key
+#[tauri::command]
+pub async fn gxt_detect_open(app: AppHandle, path: String) -> Option<OpenFile> {
+    let DeepLink { path, key } = deep_link::parse(&path).unwrap_or(DeepLink { path, key: None });
+    tauri::async_runtime::spawn_blocking(move || {
+        detect_open_kind(&app, &path).map(|kind| OpenFile { path, kind, key })
+    })
+    .await
+    .ok()
+    .flatten()
+}
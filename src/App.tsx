@@ -353,7 +353,7 @@ export default function App() {
             setDoc(doc);
             setSnack({ open: true, msg: t.snackLoaded, severity: "success" });
         } catch (e: any) {
-            setSnack({ open: true, msg: e?.toString?.() ?? t.snackLoadFail, severity: "error" });
+            setSnack({ open: true, msg: e?.message ?? e?.toString?.() ?? t.snackLoadFail, severity: "error" });
         } finally {
             setBusy(null);
         }
@@ -367,7 +367,7 @@ export default function App() {
             setDoc(doc);
             setSnack({ open: true, msg: t.snackLoadedAssoc, severity: "success" });
         } catch (e: any) {
-            setSnack({ open: true, msg: e?.toString?.() ?? t.snackLoadFail, severity: "error" });
+            setSnack({ open: true, msg: e?.message ?? e?.toString?.() ?? t.snackLoadFail, severity: "error" });
         } finally {
             setBusy(null);
         }